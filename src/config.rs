@@ -0,0 +1,658 @@
+use crate::{interfaces::files::StorageClass, routes::RouteTimeouts};
+use std::{
+    ffi::OsString,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+    time::Duration,
+};
+use thiserror::Error;
+
+/// S3 refuses presigned URLs that are valid for longer than a week.
+pub const MAX_PRESIGN_DURATION: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+/// S3 refuses objects larger than 5 TiB.
+const MAX_OBJECT_SIZE: usize = 1024 * 1024 * 1024 * 1024 * 5;
+/// S3 refuses parts smaller than 5 MiB, except for the last one.
+const MIN_UPLOAD_PART_SIZE: usize = 1024 * 1024 * 5;
+/// The last part takes the remainder and may be almost twice as large, which must stay within the
+/// 5 GiB S3 allows per part.
+const MAX_UPLOAD_PART_SIZE: usize = 1024 * 1024 * 1024 * 2;
+/// S3 allows at most 10000 parts per multipart upload.
+const MAX_UPLOAD_PARTS: usize = 10000;
+/// Meilisearch answers at most 1000 hits per search, however it is paged.
+const MAX_INDEX_SWEEP_SAMPLE_SIZE: usize = 1000;
+
+#[derive(Error, Debug)]
+#[error("invalid configuration:{}", .0.iter().map(|problem| format!("\n- {problem}")).collect::<String>())]
+pub struct ConfigError(pub Vec<ConfigProblem>);
+
+#[derive(Error, Debug)]
+pub enum ConfigProblem {
+    #[error("environment variable `{name}` is required")]
+    Missing { name: &'static str },
+
+    #[error("environment variable `{name}` is not valid unicode")]
+    NotUnicode { name: &'static str },
+
+    #[error("environment variable `{name}` must be {expected}, got `{value}`")]
+    Invalid {
+        name: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+}
+
+/// Every setting of the server, read and validated once on startup.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub proxy: ProxyConfig,
+    pub database: DatabaseConfig,
+    pub search_engine: SearchEngineConfig,
+    pub s3: S3Config,
+    pub cors: CorsConfig,
+    pub file_gc: FileGcConfig,
+    pub housekeeping: HousekeepingConfig,
+    pub index_sweep: IndexSweepConfig,
+    pub presign: PresignConfig,
+    pub upload: UploadConfig,
+    pub storage_cost: StorageCostConfig,
+    pub route_timeouts: RouteTimeouts,
+    pub telemetry: TelemetryConfig,
+    pub mailer: MailerConfig,
+    pub admins: AdminsConfig,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ServerConfig {
+    pub address: IpAddr,
+    pub port: u16,
+}
+
+/// The reverse proxies in front of the server. Only the headers of these proxies are believed
+/// about who the client is.
+#[derive(Debug, Clone, Default)]
+pub struct ProxyConfig {
+    pub trusted_proxies: Vec<IpNetwork>,
+}
+
+impl ProxyConfig {
+    pub fn is_trusted(&self, address: IpAddr) -> bool {
+        self.trusted_proxies
+            .iter()
+            .any(|network| network.contains(address))
+    }
+}
+
+/// An address with a prefix length, such as `10.0.0.0/8`. A bare address is a network of itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpNetwork {
+    address: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNetwork {
+    pub fn contains(&self, address: IpAddr) -> bool {
+        // clients reaching a dual-stack socket over IPv4 appear as mapped IPv6 addresses
+        let address = match address {
+            IpAddr::V6(v6) => v6
+                .to_ipv4_mapped()
+                .map(IpAddr::V4)
+                .unwrap_or(IpAddr::V6(v6)),
+            address => address,
+        };
+
+        match (self.address, address) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                u32::from(network) & mask == u32::from(address) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                u128::from(network) & mask == u128::from(address) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, prefix_len) = match s.split_once('/') {
+            Some((address, prefix_len)) => (address, Some(prefix_len)),
+            None => (s, None),
+        };
+        let address: IpAddr = address.parse().map_err(|_| ())?;
+        let max_prefix_len = match address {
+            IpAddr::V4(_) => Ipv4Addr::BITS,
+            IpAddr::V6(_) => Ipv6Addr::BITS,
+        } as u8;
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len.parse().map_err(|_| ())?,
+            None => max_prefix_len,
+        };
+
+        if max_prefix_len < prefix_len {
+            return Err(());
+        }
+
+        Ok(Self {
+            address,
+            prefix_len,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DatabaseConfig {
+    pub url: String,
+    /// A read replica of the database, for list and scan queries.
+    pub read_url: Option<String>,
+    /// Fail on pending or drifted migrations instead of applying them.
+    pub check_migrations_only: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchEngineConfig {
+    pub url: String,
+    pub api_key: Option<String>,
+    /// Fail on an index schema mismatch instead of fixing it.
+    pub check_only: bool,
+}
+
+/// The credentials are not part of it; the AWS SDK resolves them through its usual provider
+/// chain.
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    pub region: String,
+    pub bucket_name: String,
+    /// Overrides the endpoint of S3, for S3-compatible storages such as MinIO.
+    pub endpoint_url: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    /// `None` allows any origin.
+    pub allowed_origins: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FileGcConfig {
+    pub interval: Duration,
+    /// How long a file may stay unready before it is purged.
+    pub unready_file_ttl: Duration,
+}
+
+/// How often auxiliary tables are pruned, and how long their rows are kept. Rows without a
+/// retention window here are pruned as soon as they can no longer be used, e.g. expired sessions.
+#[derive(Debug, Clone, Copy)]
+pub struct HousekeepingConfig {
+    pub interval: Duration,
+    /// How long canceled, completed and failed admin tasks are kept.
+    pub admin_task_retention: Duration,
+    /// How long the records of finished uploads are kept.
+    pub upload_record_retention: Duration,
+    /// How long the views of files by admins are kept after their last view.
+    pub file_view_retention: Duration,
+}
+
+/// How often the latest file documents of the search index are checked for files that no longer
+/// exist, and how many of them.
+#[derive(Debug, Clone, Copy)]
+pub struct IndexSweepConfig {
+    pub interval: Duration,
+    pub sample_size: usize,
+}
+
+/// How long the presigned URLs handed out by the routes stay valid.
+#[derive(Debug, Clone, Copy)]
+pub struct PresignConfig {
+    pub download_url: Duration,
+    pub upload_url: Duration,
+}
+
+/// How large files may be and how their multipart uploads are split into parts.
+#[derive(Debug, Clone, Copy)]
+pub struct UploadConfig {
+    pub max_file_size: usize,
+    pub part_size: usize,
+}
+
+impl UploadConfig {
+    /// Number of parts a file of `size` bytes is uploaded in. Every part has the part size, except
+    /// for the last one, which also takes the remainder.
+    pub fn part_count(&self, size: usize) -> u32 {
+        if size <= self.part_size {
+            1
+        } else {
+            (size / self.part_size) as u32
+        }
+    }
+
+    /// Offset and size of the part `part_number`, counting from 1, of a file of `size` bytes.
+    pub fn part_range(&self, size: usize, part_number: u32) -> (usize, usize) {
+        let offset = (part_number as usize - 1) * self.part_size;
+        let part_size = if part_number == self.part_count(size) {
+            size - offset
+        } else {
+            self.part_size
+        };
+
+        (offset, part_size)
+    }
+}
+
+/// What storing a GiB for a month costs in each storage class, in the currency of the bill.
+#[derive(Debug, Clone, Copy)]
+pub struct StorageCostConfig {
+    pub standard_per_gb_month: f64,
+}
+
+impl StorageCostConfig {
+    pub fn rate(&self, storage_class: StorageClass) -> f64 {
+        match storage_class {
+            StorageClass::Standard => self.standard_per_gb_month,
+        }
+    }
+
+    /// What storing `bytes` for a month costs in the storage class, rounded to the cent. Storage
+    /// is billed by GiB, which S3 calls GB.
+    pub fn monthly_cost(&self, storage_class: StorageClass, bytes: u64) -> f64 {
+        let gb = bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+
+        (gb * self.rate(storage_class) * 100.0).round() / 100.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TelemetryConfig {
+    pub log_format: LogFormat,
+    /// Spans are exported only if set.
+    pub otlp_endpoint: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogFormat {
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MailerConfig {
+    /// Mails are only logged if not set.
+    pub smtp: Option<SmtpConfig>,
+    /// Link sent in password reset mails, with `{token}` replaced by the reset token. The bare
+    /// token is sent if not set.
+    pub password_reset_url: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AdminsConfig {
+    /// Lets `POST /admins/bootstrap` create an admin once even if there already are admins.
+    pub bootstrap_token: Option<String>,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_lookup(|name| std::env::var_os(name))
+    }
+
+    /// Reads the settings through `lookup` instead of the process environment. Every problem is
+    /// collected, so a single error reports all of them.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<OsString>) -> Result<Self, ConfigError> {
+        let mut env = EnvReader {
+            lookup: &lookup,
+            problems: Vec::new(),
+        };
+
+        let smtp = env.optional("SMTP_HOST").map(|host| SmtpConfig {
+            host,
+            port: env.parse("SMTP_PORT", 587, "a port number"),
+            username: env.optional("SMTP_USERNAME"),
+            password: env.optional("SMTP_PASSWORD"),
+            from: env.required("MAIL_FROM"),
+        });
+        let config = Self {
+            server: ServerConfig {
+                address: env.parse(
+                    "SERVER_ADDRESS",
+                    IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                    "an ip address",
+                ),
+                port: env.parse("SERVER_PORT", 8000, "a port number"),
+            },
+            proxy: ProxyConfig {
+                trusted_proxies: env.ip_networks("TRUSTED_PROXIES"),
+            },
+            database: DatabaseConfig {
+                url: env.required("DATABASE_URL"),
+                read_url: env.optional("DATABASE_READ_URL"),
+                check_migrations_only: env.flag("DATABASE_CHECK_MIGRATIONS_ONLY"),
+            },
+            search_engine: SearchEngineConfig {
+                url: env.required("MEILISEARCH_URL"),
+                api_key: env.optional("MEILISEARCH_API_KEY"),
+                check_only: env.flag("MEILISEARCH_CHECK_ONLY"),
+            },
+            s3: S3Config {
+                region: env.required("AWS_REGION"),
+                bucket_name: env.required("AWS_S3_BUCKET_NAME"),
+                endpoint_url: env.optional("AWS_S3_ENDPOINT_URL"),
+            },
+            cors: CorsConfig {
+                allowed_origins: env.optional("CORS_ALLOWED_ORIGINS").and_then(|origins| {
+                    let origins = Vec::from_iter(
+                        origins
+                            .split(',')
+                            .map(|origin| origin.trim())
+                            .filter(|origin| !origin.is_empty())
+                            .map(|origin| origin.to_owned()),
+                    );
+
+                    if origins.iter().any(|origin| origin == "*") {
+                        None
+                    } else {
+                        Some(origins)
+                    }
+                }),
+            },
+            file_gc: FileGcConfig {
+                interval: env.secs("FILE_GC_INTERVAL_SECS", 60 * 60 * 6, None),
+                unready_file_ttl: env.secs("FILE_GC_UNREADY_TTL_SECS", 60 * 60 * 2, None),
+            },
+            housekeeping: HousekeepingConfig {
+                interval: env.secs("HOUSEKEEPING_INTERVAL_SECS", 60 * 60 * 24, None),
+                admin_task_retention: env.secs(
+                    "ADMIN_TASK_RETENTION_SECS",
+                    60 * 60 * 24 * 90,
+                    None,
+                ),
+                upload_record_retention: env.secs(
+                    "UPLOAD_RECORD_RETENTION_SECS",
+                    60 * 60 * 24 * 90,
+                    None,
+                ),
+                file_view_retention: env.secs("FILE_VIEW_RETENTION_SECS", 60 * 60 * 24 * 90, None),
+            },
+            index_sweep: IndexSweepConfig {
+                interval: env.secs("INDEX_SWEEP_INTERVAL_SECS", 60 * 15, None),
+                sample_size: env.sample_size("INDEX_SWEEP_SAMPLE_SIZE", 1000),
+            },
+            presign: PresignConfig {
+                download_url: env.secs(
+                    "DOWNLOAD_URL_DURATION_SECS",
+                    60 * 60,
+                    Some(MAX_PRESIGN_DURATION),
+                ),
+                upload_url: env.secs(
+                    "UPLOAD_URL_DURATION_SECS",
+                    60 * 60,
+                    Some(MAX_PRESIGN_DURATION),
+                ),
+            },
+            upload: env.upload(),
+            storage_cost: StorageCostConfig {
+                standard_per_gb_month: env.rate("STORAGE_COST_STANDARD_PER_GB_MONTH", 0.023),
+            },
+            route_timeouts: RouteTimeouts {
+                search: env.secs("SEARCH_TIMEOUT_SECS", 5, None),
+                index: env.secs("INDEX_TIMEOUT_SECS", 10, None),
+                storage: env.secs("STORAGE_TIMEOUT_SECS", 15, None),
+            },
+            telemetry: TelemetryConfig {
+                log_format: env.parse("LOG_FORMAT", LogFormat::Text, "`text` or `json`"),
+                otlp_endpoint: env
+                    .optional("OTEL_EXPORTER_OTLP_ENDPOINT")
+                    .filter(|endpoint| !endpoint.is_empty()),
+            },
+            mailer: MailerConfig {
+                smtp,
+                password_reset_url: env.optional("PASSWORD_RESET_URL"),
+            },
+            admins: AdminsConfig {
+                bootstrap_token: env
+                    .optional("BOOTSTRAP_TOKEN")
+                    .filter(|token| !token.is_empty()),
+            },
+        };
+
+        if env.problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError(env.problems))
+        }
+    }
+}
+
+/// Reads environment variables, recording problems instead of stopping at the first one. The
+/// values returned for problematic variables are placeholders and must not be used.
+struct EnvReader<'a> {
+    lookup: &'a dyn Fn(&str) -> Option<OsString>,
+    problems: Vec<ConfigProblem>,
+}
+
+impl EnvReader<'_> {
+    fn optional(&mut self, name: &'static str) -> Option<String> {
+        match (self.lookup)(name)?.into_string() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                self.problems.push(ConfigProblem::NotUnicode { name });
+                None
+            }
+        }
+    }
+
+    fn required(&mut self, name: &'static str) -> String {
+        if (self.lookup)(name).is_none() {
+            self.problems.push(ConfigProblem::Missing { name });
+            return String::new();
+        }
+
+        self.optional(name).unwrap_or_default()
+    }
+
+    fn parse<T>(&mut self, name: &'static str, default: T, expected: &'static str) -> T
+    where
+        T: FromStr,
+    {
+        let value = match self.optional(name) {
+            Some(value) => value,
+            None => {
+                return default;
+            }
+        };
+
+        match value.trim().parse() {
+            Ok(parsed) => parsed,
+            Err(_) => {
+                self.problems.push(ConfigProblem::Invalid {
+                    name,
+                    value,
+                    expected,
+                });
+                default
+            }
+        }
+    }
+
+    fn flag(&mut self, name: &'static str) -> bool {
+        let value = match self.optional(name) {
+            Some(value) => value,
+            None => {
+                return false;
+            }
+        };
+
+        match value.as_str() {
+            "" | "0" | "false" => false,
+            "1" | "true" => true,
+            _ => {
+                self.problems.push(ConfigProblem::Invalid {
+                    name,
+                    value,
+                    expected: "`true` or `false`",
+                });
+                false
+            }
+        }
+    }
+
+    /// The part size bounds the largest file size, since a file must fit in [`MAX_UPLOAD_PARTS`]
+    /// parts. The largest file size defaults to that bound, capped at 5 TiB.
+    fn upload(&mut self) -> UploadConfig {
+        const PART_SIZE_EXPECTED: &str = "a number of bytes between 5 MiB and 2 GiB";
+        const MAX_FILE_SIZE_EXPECTED: &str =
+            "a positive number of bytes, at most 5 TiB and fitting in 10000 parts";
+
+        let default_part_size = 1024 * 1024 * 64;
+        let mut part_size = self.parse(
+            "UPLOAD_PART_SIZE_BYTES",
+            default_part_size,
+            PART_SIZE_EXPECTED,
+        );
+
+        if !(MIN_UPLOAD_PART_SIZE..=MAX_UPLOAD_PART_SIZE).contains(&part_size) {
+            self.problems.push(ConfigProblem::Invalid {
+                name: "UPLOAD_PART_SIZE_BYTES",
+                value: part_size.to_string(),
+                expected: PART_SIZE_EXPECTED,
+            });
+            part_size = default_part_size;
+        }
+
+        let largest_file_size = (part_size * MAX_UPLOAD_PARTS - 1).min(MAX_OBJECT_SIZE);
+        let max_file_size = self.parse(
+            "UPLOAD_MAX_FILE_SIZE_BYTES",
+            largest_file_size,
+            MAX_FILE_SIZE_EXPECTED,
+        );
+
+        if max_file_size == 0 || largest_file_size < max_file_size {
+            self.problems.push(ConfigProblem::Invalid {
+                name: "UPLOAD_MAX_FILE_SIZE_BYTES",
+                value: max_file_size.to_string(),
+                expected: MAX_FILE_SIZE_EXPECTED,
+            });
+        }
+
+        UploadConfig {
+            max_file_size: max_file_size.min(largest_file_size),
+            part_size,
+        }
+    }
+
+    /// A comma-separated list of addresses and networks in CIDR notation.
+    fn ip_networks(&mut self, name: &'static str) -> Vec<IpNetwork> {
+        let value = match self.optional(name) {
+            Some(value) => value,
+            None => {
+                return Vec::new();
+            }
+        };
+        let mut networks = Vec::new();
+
+        for network in value.split(',').map(|network| network.trim()) {
+            if network.is_empty() {
+                continue;
+            }
+
+            match network.parse() {
+                Ok(network) => networks.push(network),
+                Err(_) => {
+                    self.problems.push(ConfigProblem::Invalid {
+                        name,
+                        value: network.to_owned(),
+                        expected: "a comma-separated list of ip addresses and networks",
+                    });
+                }
+            }
+        }
+
+        networks
+    }
+
+    /// A positive number of documents, at most the 1000 hits Meilisearch pages through.
+    fn sample_size(&mut self, name: &'static str, default: usize) -> usize {
+        const EXPECTED: &str = "a number of documents between 1 and 1000";
+
+        let sample_size = self.parse(name, default, EXPECTED);
+
+        if !(1..=MAX_INDEX_SWEEP_SAMPLE_SIZE).contains(&sample_size) {
+            self.problems.push(ConfigProblem::Invalid {
+                name,
+                value: sample_size.to_string(),
+                expected: EXPECTED,
+            });
+            return default;
+        }
+
+        sample_size
+    }
+
+    /// A price that is neither negative nor infinite.
+    fn rate(&mut self, name: &'static str, default: f64) -> f64 {
+        const EXPECTED: &str = "a non-negative price";
+
+        let rate = self.parse(name, default, EXPECTED);
+
+        if !rate.is_finite() || rate < 0.0 {
+            self.problems.push(ConfigProblem::Invalid {
+                name,
+                value: rate.to_string(),
+                expected: EXPECTED,
+            });
+            return default;
+        }
+
+        rate
+    }
+
+    /// A positive number of seconds, at most `max`.
+    fn secs(&mut self, name: &'static str, default_secs: u64, max: Option<Duration>) -> Duration {
+        let expected = if max.is_some() {
+            "a positive number of seconds, at most a week"
+        } else {
+            "a positive number of seconds"
+        };
+        let secs = self.parse(name, default_secs, expected);
+        let duration = Duration::from_secs(secs);
+
+        if secs == 0 || max.is_some_and(|max| max < duration) {
+            self.problems.push(ConfigProblem::Invalid {
+                name,
+                value: secs.to_string(),
+                expected,
+            });
+            return Duration::from_secs(default_secs);
+        }
+
+        duration
+    }
+}
@@ -1,41 +1,179 @@
+use crate::config::DatabaseConfig;
+use chrono::{DateTime, Utc};
 use sqlx::{migrate, migrate::Migrator, PgPool};
 use thiserror::Error;
 
+pub static MIGRATOR: Migrator = migrate!("src/db/migrations");
+
 #[derive(Error, Debug)]
 pub enum DatabaseError {
-    #[error("environment variable `DATABASE_URL` is unable to be retrieved: {0:#?}")]
-    RetrieveDatabaseUrl(std::env::VarError),
-
     #[error("database connection failure: {0:#?}")]
     DatabaseConnectionFailure(#[from] sqlx::Error),
 
     #[error("database migration failure: {0:#?}")]
     DatabaseMigrationFailure(#[from] sqlx::migrate::MigrateError),
+
+    #[error("database migrations are not up to date, apply them before starting: {0}")]
+    MigrationsNotUpToDate(String),
 }
 
 pub struct Database {
     pool: PgPool,
+    read_pool: PgPool,
 }
 
 impl Database {
-    pub async fn init() -> Result<Self, DatabaseError> {
-        static MIGRATOR: Migrator = migrate!("src/db/migrations");
-
-        let database_url =
-            std::env::var("DATABASE_URL").map_err(DatabaseError::RetrieveDatabaseUrl)?;
-        let pool = PgPool::connect(&database_url)
+    pub async fn init(config: &DatabaseConfig) -> Result<Self, DatabaseError> {
+        let pool = PgPool::connect(&config.url)
             .await
             .map_err(DatabaseError::DatabaseConnectionFailure)?;
 
-        MIGRATOR
-            .run(&pool)
-            .await
-            .map_err(DatabaseError::DatabaseMigrationFailure)?;
+        if config.check_migrations_only {
+            let drifted = Vec::from_iter(
+                list_migrations(&pool)
+                    .await?
+                    .into_iter()
+                    .filter(|migration| migration.state != MigrationState::Applied)
+                    .map(|migration| {
+                        format!(
+                            "{} ({}): {:?}",
+                            migration.version, migration.description, migration.state
+                        )
+                    }),
+            );
+
+            if !drifted.is_empty() {
+                return Err(DatabaseError::MigrationsNotUpToDate(drifted.join(", ")));
+            }
+        } else {
+            MIGRATOR
+                .run(&pool)
+                .await
+                .map_err(DatabaseError::DatabaseMigrationFailure)?;
+        }
+
+        // connected after the migrations ran, so that the replica already sees their schema
+        let read_pool = match &config.read_url {
+            Some(read_url) => PgPool::connect(read_url)
+                .await
+                .map_err(DatabaseError::DatabaseConnectionFailure)?,
+            None => pool.clone(),
+        };
 
-        Ok(Self { pool })
+        Ok(Self { pool, read_pool })
     }
 
+    /// The primary, for writes and for reads that must see them.
     pub fn pool(&self) -> PgPool {
         self.pool.clone()
     }
+
+    /// The replica, or the primary if no replica is configured, for scans that may lag behind.
+    pub fn read_pool(&self) -> PgPool {
+        self.read_pool.clone()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// The migration file was edited after it had been applied.
+    ChecksumMismatch,
+    /// The migration was attempted, but did not succeed.
+    Failed,
+    /// The migration was applied, but is not embedded in this build; the database is ahead of it.
+    Unknown,
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationEntry {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+    /// Checksum of the embedded migration.
+    pub checksum: Option<Vec<u8>>,
+    /// Checksum recorded when the migration was applied.
+    pub applied_checksum: Option<Vec<u8>>,
+    pub installed_on: Option<DateTime<Utc>>,
+}
+
+/// Compares the migrations embedded in [`MIGRATOR`] with the ones recorded in the database, in
+/// version order.
+pub async fn list_migrations(pool: &PgPool) -> Result<Vec<MigrationEntry>, sqlx::Error> {
+    let table_exists =
+        sqlx::query_scalar!(r#"SELECT to_regclass('_sqlx_migrations') IS NOT NULL AS "exists!""#)
+            .fetch_one(pool)
+            .await?;
+    let mut applied = if table_exists {
+        sqlx::query_as!(
+            RawAppliedMigration,
+            "
+SELECT version, description, installed_on, success, checksum
+FROM _sqlx_migrations
+ORDER BY version ASC"
+        )
+        .fetch_all(pool)
+        .await?
+    } else {
+        Vec::new()
+    };
+
+    let mut migrations = Vec::new();
+
+    for migration in MIGRATOR.iter() {
+        if migration.migration_type.is_down_migration() {
+            continue;
+        }
+
+        let position = applied
+            .iter()
+            .position(|applied| applied.version == migration.version);
+        let applied = position.map(|position| applied.remove(position));
+        let state = match &applied {
+            None => MigrationState::Pending,
+            Some(applied) if !applied.success => MigrationState::Failed,
+            Some(applied) if applied.checksum != *migration.checksum => {
+                MigrationState::ChecksumMismatch
+            }
+            Some(_) => MigrationState::Applied,
+        };
+
+        migrations.push(MigrationEntry {
+            version: migration.version,
+            description: migration.description.to_string(),
+            state,
+            checksum: Some(migration.checksum.to_vec()),
+            applied_checksum: applied.as_ref().map(|applied| applied.checksum.clone()),
+            installed_on: applied.map(|applied| applied.installed_on),
+        });
+    }
+
+    // whatever is left was applied by a newer build
+    for applied in applied {
+        migrations.push(MigrationEntry {
+            version: applied.version,
+            description: applied.description,
+            state: if applied.success {
+                MigrationState::Unknown
+            } else {
+                MigrationState::Failed
+            },
+            checksum: None,
+            applied_checksum: Some(applied.checksum),
+            installed_on: Some(applied.installed_on),
+        });
+    }
+
+    migrations.sort_by_key(|migration| migration.version);
+
+    Ok(migrations)
+}
+
+struct RawAppliedMigration {
+    version: i64,
+    description: String,
+    installed_on: DateTime<Utc>,
+    success: bool,
+    checksum: Vec<u8>,
 }
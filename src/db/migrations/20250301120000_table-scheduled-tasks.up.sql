@@ -0,0 +1,13 @@
+-- Add up migration script here
+
+CREATE TABLE scheduled_tasks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    cron_expression TEXT NOT NULL,
+    metadata JSONB NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    last_run_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX scheduled_tasks_idx_created_at_id ON scheduled_tasks (created_at DESC, id ASC);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+
+ALTER TABLE admin_tasks DROP COLUMN depends_on;
@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+ALTER TABLE admin_tasks ADD COLUMN depends_on UUID REFERENCES admin_tasks (id) ON DELETE SET NULL;
+
+CREATE INDEX admin_tasks_idx_depends_on ON admin_tasks (depends_on);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+ALTER TABLE admin_tasks DROP COLUMN failed_at;
+ALTER TABLE admin_tasks DROP COLUMN error;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+ALTER TABLE admin_tasks ADD COLUMN error TEXT;
+ALTER TABLE admin_tasks ADD COLUMN failed_at TIMESTAMP;
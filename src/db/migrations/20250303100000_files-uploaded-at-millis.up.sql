@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- the search index stores `uploaded_at` in milliseconds; keep the database at the same precision
+-- so that values read from either side compare equal
+ALTER TABLE files ALTER COLUMN uploaded_at TYPE TIMESTAMP(3);
@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+ALTER TABLE files ADD COLUMN checksum TEXT;
+
+CREATE INDEX files_idx_checksum ON files (checksum);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP INDEX admin_sessions_idx_last_used_at;
+
+ALTER TABLE admin_sessions DROP COLUMN last_used_at;
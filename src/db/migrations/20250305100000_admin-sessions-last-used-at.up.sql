@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+ALTER TABLE admin_sessions ADD COLUMN last_used_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+CREATE INDEX admin_sessions_idx_last_used_at ON admin_sessions(last_used_at);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+ALTER TABLE admins DROP COLUMN role;
+
+DROP TYPE admin_role;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+
+CREATE TYPE admin_role AS ENUM ('viewer', 'editor', 'admin');
+
+-- the admins that exist already keep full access
+ALTER TABLE admins ADD COLUMN role admin_role NOT NULL DEFAULT 'admin';
+ALTER TABLE admins ALTER COLUMN role SET DEFAULT 'viewer';
@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+CREATE TABLE admin_bootstrap_tokens (
+    token_hash TEXT PRIMARY KEY,
+    used_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
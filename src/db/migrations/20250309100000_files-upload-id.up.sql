@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+-- the multipart upload that completed the file, so a retried completion can be recognized
+ALTER TABLE files ADD COLUMN upload_id TEXT;
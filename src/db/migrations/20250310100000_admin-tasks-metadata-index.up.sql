@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+-- for looking up the tasks whose metadata references an entity, e.g. `metadata @> '{"file_id": ...}'`
+CREATE INDEX admin_tasks_idx_metadata ON admin_tasks USING GIN (metadata jsonb_path_ops);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+
+DROP INDEX files_idx_name_pattern;
@@ -0,0 +1,3 @@
+-- Add up migration script here
+
+CREATE INDEX files_idx_name_pattern ON files (name text_pattern_ops);
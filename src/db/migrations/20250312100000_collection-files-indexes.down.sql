@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP INDEX files_idx_name_id_is_ready;
+DROP INDEX file_tags_idx_tag_file_id;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+CREATE INDEX file_tags_idx_tag_file_id ON file_tags (tag, file_id);
+CREATE INDEX files_idx_name_id_is_ready ON files (name ASC, id ASC, is_ready);
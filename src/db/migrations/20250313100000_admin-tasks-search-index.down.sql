@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP INDEX admin_tasks_idx_search_text;
+DROP FUNCTION admin_task_search_text;
@@ -0,0 +1,20 @@
+-- Add up migration script here
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- the text an admin task search matches: the name, the error and every scalar value of the metadata,
+-- however deeply nested, but none of the metadata keys
+CREATE FUNCTION admin_task_search_text(name TEXT, metadata JSONB, error TEXT)
+RETURNS TEXT
+LANGUAGE SQL
+IMMUTABLE
+PARALLEL SAFE
+AS $$
+SELECT name || ' ' || COALESCE(error, '') || ' ' || COALESCE((
+    SELECT string_agg(value #>> '{}', ' ')
+    FROM jsonb_path_query(metadata, 'strict $.**') AS value
+    WHERE jsonb_typeof(value) NOT IN ('object', 'array')
+), '')
+$$;
+
+CREATE INDEX admin_tasks_idx_search_text ON admin_tasks USING GIN (admin_task_search_text(name, metadata, error) gin_trgm_ops);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+
+DROP INDEX admin_tasks_idx_enqueued_at_id;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+-- for exporting the tasks enqueued in a time range
+CREATE INDEX admin_tasks_idx_enqueued_at_id ON admin_tasks (enqueued_at ASC, id ASC);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP INDEX admin_tasks_idx_initiator_admin_id;
+ALTER TABLE admin_tasks DROP COLUMN initiator_admin_id;
@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- the admin whose request enqueued the task; null for system tasks and for tasks enqueued before
+ALTER TABLE admin_tasks ADD COLUMN initiator_admin_id UUID REFERENCES admins (id) ON DELETE SET NULL;
+
+CREATE INDEX admin_tasks_idx_initiator_admin_id ON admin_tasks (initiator_admin_id);
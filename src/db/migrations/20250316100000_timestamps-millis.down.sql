@@ -0,0 +1,19 @@
+-- Add down migration script here
+
+ALTER TABLE scheduled_tasks
+    ALTER COLUMN last_run_at TYPE TIMESTAMP,
+    ALTER COLUMN created_at TYPE TIMESTAMP;
+
+ALTER TABLE collections ALTER COLUMN created_at TYPE TIMESTAMP;
+
+ALTER TABLE admin_tasks
+    ALTER COLUMN failed_at TYPE TIMESTAMP,
+    ALTER COLUMN updated_at TYPE TIMESTAMP,
+    ALTER COLUMN enqueued_at TYPE TIMESTAMP;
+
+ALTER TABLE admin_sessions
+    ALTER COLUMN expired_at TYPE TIMESTAMP,
+    ALTER COLUMN last_used_at TYPE TIMESTAMP,
+    ALTER COLUMN logined_at TYPE TIMESTAMP;
+
+ALTER TABLE admins ALTER COLUMN joined_at TYPE TIMESTAMP;
@@ -0,0 +1,21 @@
+-- Add up migration script here
+
+-- responses render timestamps with millisecond precision; keep the database at the same precision
+-- so that a timestamp read back from a response, e.g. as a pagination cursor, compares equal
+ALTER TABLE admins ALTER COLUMN joined_at TYPE TIMESTAMP(3);
+
+ALTER TABLE admin_sessions
+    ALTER COLUMN logined_at TYPE TIMESTAMP(3),
+    ALTER COLUMN last_used_at TYPE TIMESTAMP(3),
+    ALTER COLUMN expired_at TYPE TIMESTAMP(3);
+
+ALTER TABLE admin_tasks
+    ALTER COLUMN enqueued_at TYPE TIMESTAMP(3),
+    ALTER COLUMN updated_at TYPE TIMESTAMP(3),
+    ALTER COLUMN failed_at TYPE TIMESTAMP(3);
+
+ALTER TABLE collections ALTER COLUMN created_at TYPE TIMESTAMP(3);
+
+ALTER TABLE scheduled_tasks
+    ALTER COLUMN created_at TYPE TIMESTAMP(3),
+    ALTER COLUMN last_run_at TYPE TIMESTAMP(3);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP TABLE upload_records;
+DROP TYPE upload_outcome;
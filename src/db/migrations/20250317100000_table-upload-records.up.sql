@@ -0,0 +1,20 @@
+-- Add up migration script here
+
+CREATE TYPE upload_outcome AS ENUM ('pending', 'completed', 'aborted', 'expired');
+
+-- no foreign key to files: a record outlives its file, so that the gc can still abort an upload
+-- left behind by a deleted file
+CREATE TABLE upload_records (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    file_id UUID NOT NULL,
+    upload_id TEXT NOT NULL UNIQUE,
+    part_count INTEGER NOT NULL,
+    total_bytes BIGINT NOT NULL,
+    outcome upload_outcome NOT NULL DEFAULT 'pending',
+    started_at TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at TIMESTAMP(3)
+);
+
+CREATE INDEX upload_records_idx_file_id_started_at ON upload_records (file_id, started_at DESC);
+CREATE INDEX upload_records_idx_started_at_pending ON upload_records (started_at) WHERE outcome = 'pending';
+CREATE INDEX upload_records_idx_completed_at ON upload_records (completed_at);
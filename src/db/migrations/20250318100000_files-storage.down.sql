@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+ALTER TABLE files
+    DROP COLUMN region,
+    DROP COLUMN bucket;
@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- the configured bucket is not known here; the rows without one are backfilled with it on startup
+ALTER TABLE files
+    ADD COLUMN bucket TEXT,
+    ADD COLUMN region TEXT;
@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+-- keyed by the tag itself rather than referenced from file_tags and collection_tags, so that
+-- defining or deleting a definition never touches the tags in use
+CREATE TABLE tag_definitions (
+    name TEXT PRIMARY KEY,
+    color TEXT,
+    description TEXT,
+    created_at TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- protected files are skipped by every deletion path until an admin unprotects them
+ALTER TABLE files
+    ADD COLUMN protected BOOLEAN NOT NULL DEFAULT FALSE;
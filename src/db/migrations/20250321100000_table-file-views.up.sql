@@ -0,0 +1,12 @@
+-- Add up migration script here
+
+CREATE TABLE file_views (
+    admin_id UUID NOT NULL REFERENCES admins(id) ON DELETE CASCADE,
+    file_id UUID NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+    view_count BIGINT NOT NULL DEFAULT 1,
+    last_viewed_at TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (admin_id, file_id)
+);
+
+CREATE INDEX file_views_idx_admin_id_last_viewed_at ON file_views (admin_id, last_viewed_at DESC);
+CREATE INDEX file_views_idx_last_viewed_at ON file_views (last_viewed_at);
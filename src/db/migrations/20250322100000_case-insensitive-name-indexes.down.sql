@@ -0,0 +1,6 @@
+-- Add down migration script here
+
+DROP INDEX files_idx_lower_name_id_is_ready;
+DROP INDEX collections_idx_lower_name_id;
+CREATE INDEX files_idx_name_id_is_ready ON files (name ASC, id ASC, is_ready);
+CREATE INDEX collections_idx_name_id ON collections (name, id);
@@ -0,0 +1,7 @@
+-- Add up migration script here
+
+-- collections and the files of a collection are listed by their lowercase name
+DROP INDEX collections_idx_name_id;
+DROP INDEX files_idx_name_id_is_ready;
+CREATE INDEX collections_idx_lower_name_id ON collections (LOWER(name), id);
+CREATE INDEX files_idx_lower_name_id_is_ready ON files (LOWER(name) ASC, id ASC, is_ready);
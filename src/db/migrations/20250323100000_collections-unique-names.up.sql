@@ -0,0 +1,3 @@
+-- Add up migration script here
+
+ALTER TABLE collections ADD COLUMN unique_names BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP INDEX files_idx_superseded_by;
+ALTER TABLE files DROP COLUMN superseded_by;
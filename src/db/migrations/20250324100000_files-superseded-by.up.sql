@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+ALTER TABLE files ADD COLUMN superseded_by UUID REFERENCES files(id) ON DELETE SET NULL;
+
+-- for walking version chains backwards, from a file to the files it replaced
+CREATE INDEX files_idx_superseded_by ON files (superseded_by) WHERE superseded_by IS NOT NULL;
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP INDEX files_uidx_version;
+ALTER TABLE files DROP COLUMN version_number;
+ALTER TABLE files DROP COLUMN version_of;
@@ -0,0 +1,8 @@
+-- Add up migration script here
+
+-- the first version of a file has no `version_of`, and each later version points to the first
+-- one; no foreign key, so that the versions stay together once the first one is deleted
+ALTER TABLE files ADD COLUMN version_of UUID;
+ALTER TABLE files ADD COLUMN version_number INTEGER NOT NULL DEFAULT 1;
+
+CREATE UNIQUE INDEX files_uidx_version ON files ((COALESCE(version_of, id)), version_number);
@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- the multipart upload handed out for the file that is neither completed nor aborted yet, so that
+-- repeated requests for upload urls hand it out again instead of starting another one
+ALTER TABLE files ADD COLUMN active_upload_id TEXT;
@@ -0,0 +1,28 @@
+-- Add up migration script here
+
+-- the name keyed for natural sorting: lowercased, with every run of digits stripped of its leading
+-- zeros and padded to 20 digits, so that `img2` sorts before `img10`; compared bytewise regardless
+-- of the database collation
+ALTER TABLE files ADD COLUMN name_sort_key TEXT COLLATE "C";
+
+-- keys the existing names as the repository does for new ones; `lower` may fold some non-ASCII
+-- letters differently, which only holds until the file is renamed
+UPDATE files
+SET name_sort_key = COALESCE(
+    (
+        SELECT string_agg(
+            CASE
+                WHEN run[1] !~ '^[0-9]' THEN run[1]
+                WHEN length(ltrim(run[1], '0')) < 20 THEN lpad(ltrim(run[1], '0'), 20, '0')
+                ELSE ltrim(run[1], '0')
+            END,
+            '' ORDER BY position
+        )
+        FROM regexp_matches(lower(name), '[0-9]+|[^0-9]+', 'g') WITH ORDINALITY AS runs (run, position)
+    ),
+    ''
+);
+
+ALTER TABLE files ALTER COLUMN name_sort_key SET NOT NULL;
+
+CREATE INDEX files_idx_name_sort_key ON files (name_sort_key, id);
@@ -0,0 +1,30 @@
+-- Add up migration script here
+
+-- the files a bulk deletion has yet to process, kept out of its metadata so that the metadata
+-- stays small however many files the deletion takes on
+CREATE TABLE admin_task_queued_files (
+    task_id UUID NOT NULL REFERENCES admin_tasks (id) ON DELETE CASCADE,
+    file_id UUID NOT NULL,
+    PRIMARY KEY (task_id, file_id)
+);
+
+INSERT INTO admin_task_queued_files (task_id, file_id)
+SELECT admin_tasks.id, queued.file_id::UUID
+FROM admin_tasks
+CROSS JOIN LATERAL jsonb_array_elements_text(admin_tasks.metadata->'file_ids') WITH ORDINALITY AS queued (file_id, position)
+WHERE admin_tasks.name = 'bulk-delete-files'
+    AND admin_tasks.status IN ('pending', 'in_progress')
+    AND COALESCE((admin_tasks.metadata->>'processed_count')::BIGINT, 0) < queued.position
+ON CONFLICT DO NOTHING;
+
+-- the rewrite is no change of the tasks, so it keeps their update times
+ALTER TABLE admin_tasks DISABLE TRIGGER trigger_update_admin_task_updated_at;
+
+UPDATE admin_tasks
+SET metadata = (metadata - 'file_ids') || jsonb_build_object(
+    'file_count', jsonb_array_length(metadata->'file_ids'),
+    'protected_count', COALESCE(jsonb_array_length(metadata->'protected_file_ids'), 0)
+)
+WHERE name = 'bulk-delete-files' AND jsonb_typeof(metadata->'file_ids') = 'array';
+
+ALTER TABLE admin_tasks ENABLE TRIGGER trigger_update_admin_task_updated_at;
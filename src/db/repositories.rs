@@ -1,22 +1,44 @@
+use std::collections::HashSet;
 use thiserror::Error;
 
 pub mod admin;
 pub mod collection;
 pub mod file;
 
+/// Most rows a pruning statement deletes at once, so that it does not hold its locks for long.
+/// Pruning repeats the statement until fewer rows are left.
+pub const PRUNE_CHUNK_SIZE: i64 = 10000;
+/// Most files a version chain, from the first version of a file to its latest, may hold. It
+/// bounds the walks along `superseded_by`.
+pub const MAX_VERSION_CHAIN_LENGTH: i32 = 32;
+/// Most tags a file or a collection may have, so that neither its search document nor its
+/// listing grows without bound.
+pub const MAX_TAGS: usize = 64;
+/// Digits a run of digits is padded to in a name sort key, enough for any `u64`.
+const NAME_SORT_KEY_DIGITS: usize = 20;
+
 #[derive(Error, Debug)]
 pub enum RepositoryError {
     #[error("database error: {0:#?}")]
     DatabaseError(#[from] sqlx::Error),
     #[error("duplicated entity: `{key}` = `{value}`")]
     Conflict { key: String, value: String },
+    #[error("too many tags: {attempted} of at most {limit}")]
+    TooManyTags { limit: usize, attempted: usize },
+    #[error("file names shared within a collection that requires unique names: {names:?}")]
+    NameConflict { names: Vec<String> },
+    #[error("invalid superseding file: {reason}")]
+    InvalidSupersession { reason: String },
 }
 
 impl RepositoryError {
     pub fn from_sqlx_err(err: sqlx::Error, f: impl FnOnce(&str) -> String) -> Self {
         match err {
             sqlx::Error::Database(err) if err.is_unique_violation() => {
-                let key = err.constraint().unwrap_or("__unknown__").to_owned();
+                let key = err
+                    .constraint()
+                    .map(str::to_owned)
+                    .unwrap_or_else(unknown_value);
                 let value = f(&key);
                 Self::Conflict { key, value }
             }
@@ -24,3 +46,86 @@ impl RepositoryError {
         }
     }
 }
+
+/// Fails with [`RepositoryError::TooManyTags`] if the tags kept and the tags added come to more
+/// than [`MAX_TAGS`]. A tag added that is already kept counts once.
+fn check_tag_count<'a>(
+    kept: impl IntoIterator<Item = &'a str>,
+    added: &'a [String],
+) -> Result<(), RepositoryError> {
+    let attempted =
+        HashSet::<&str>::from_iter(kept.into_iter().chain(added.iter().map(String::as_str))).len();
+
+    if MAX_TAGS < attempted {
+        return Err(RepositoryError::TooManyTags {
+            limit: MAX_TAGS,
+            attempted,
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns the first tag added that is kept already or added more than once, the one a unique
+/// violation on the tags of a file or a collection is about.
+fn duplicate_tag<'a>(
+    kept: impl IntoIterator<Item = &'a str>,
+    added: &'a [String],
+) -> Option<String> {
+    let mut seen = HashSet::<&str>::from_iter(kept);
+
+    added.iter().find(|tag| !seen.insert(tag.as_str())).cloned()
+}
+
+/// The value of a [`RepositoryError::Conflict`] whose value cannot be told.
+fn unknown_value() -> String {
+    "__unknown__".to_owned()
+}
+
+/// Keys a file name for natural sorting, so that `img2.png` sorts before `img10.png`: letters are
+/// lowercased, and every run of digits is stripped of its leading zeros and padded to
+/// [`NAME_SORT_KEY_DIGITS`] digits. Names whose keys are equal, like `img2` and `IMG02`, are told
+/// apart by their id. Longer runs are kept as they are, and may sort out of order against other
+/// runs of 20 digits or more.
+pub fn name_sort_key(name: &str) -> String {
+    let mut key = String::with_capacity(name.len());
+    let mut chars = name.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if !c.is_ascii_digit() {
+            key.extend(c.to_lowercase());
+            continue;
+        }
+
+        let mut digits = String::from(c);
+
+        while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+            digits.push(digit);
+        }
+
+        let digits = digits.trim_start_matches('0');
+
+        key.extend(std::iter::repeat_n(
+            '0',
+            NAME_SORT_KEY_DIGITS.saturating_sub(digits.len()),
+        ));
+        key.push_str(digits);
+    }
+
+    key
+}
+
+/// Escapes the wildcards of `LIKE`, and its escape character, so that `value` matches literally.
+pub fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
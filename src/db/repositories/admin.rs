@@ -1,4 +1,6 @@
-use super::RepositoryError;
+use super::{RepositoryError, PRUNE_CHUNK_SIZE};
+use crate::interfaces::admins::AdminRole;
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -12,6 +14,7 @@ impl AdminRepository {
         Self { db_pool }
     }
 
+    #[tracing::instrument(level = "debug", skip_all)]
     pub async fn find_one_by_id(
         &self,
         id: Uuid,
@@ -23,6 +26,7 @@ SELECT
     id,
     username,
     email,
+    role AS \"role: AdminRole\",
     joined_at
 FROM admins
 WHERE id = $1",
@@ -34,6 +38,7 @@ WHERE id = $1",
         Ok(admin.map(|raw| raw.into()))
     }
 
+    #[tracing::instrument(level = "debug", skip_all)]
     pub async fn find_one_by_username_for_login(
         &self,
         username: impl AsRef<str>,
@@ -54,6 +59,7 @@ WHERE username = $1",
         Ok(for_login.map(|raw| raw.into()))
     }
 
+    #[tracing::instrument(level = "debug", skip_all)]
     pub async fn find_one_by_email_for_login(
         &self,
         email: impl AsRef<str>,
@@ -74,6 +80,270 @@ WHERE email = $1",
         Ok(for_login.map(|raw| raw.into()))
     }
 
+    /// Finds the session of the token with its admin, if the session has neither expired nor been
+    /// idle since `idle_since`.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn find_one_by_session_token(
+        &self,
+        token: impl AsRef<str>,
+        idle_since: DateTime<Utc>,
+    ) -> Result<Option<(entities::AdminEntity, entities::AdminSessionEntity)>, RepositoryError>
+    {
+        let session = sqlx::query_as!(
+            row_types::RawAdminWithSession,
+            "
+SELECT
+    admins.id,
+    admins.username,
+    admins.email,
+    admins.role AS \"role: AdminRole\",
+    admins.joined_at,
+    admin_sessions.token,
+    admin_sessions.logined_at,
+    admin_sessions.last_used_at,
+    admin_sessions.expired_at
+FROM admin_sessions
+JOIN admins ON admins.id = admin_sessions.admin_id
+WHERE
+    admin_sessions.token = $1
+    AND CURRENT_TIMESTAMP < admin_sessions.expired_at
+    AND $2 < admin_sessions.last_used_at",
+            token.as_ref(),
+            idle_since.naive_utc()
+        )
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(session.map(|raw| raw.into()))
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn create_session(
+        &self,
+        admin_id: Uuid,
+        token: String,
+        expired_at: DateTime<Utc>,
+    ) -> Result<entities::AdminSessionEntity, RepositoryError> {
+        let session = sqlx::query_as!(
+            row_types::RawAdminSession,
+            "
+INSERT INTO admin_sessions (
+    admin_id,
+    token,
+    expired_at
+) VALUES ($1, $2, $3)
+RETURNING
+    admin_id,
+    token,
+    logined_at,
+    last_used_at,
+    expired_at",
+            admin_id,
+            token,
+            expired_at.naive_utc(),
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(session.into())
+    }
+
+    /// Marks the session as used now, unless it already was since `used_since`. Returns `false`
+    /// if it was not touched.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn touch_session(
+        &self,
+        token: impl AsRef<str>,
+        used_since: DateTime<Utc>,
+    ) -> Result<bool, RepositoryError> {
+        let result = sqlx::query!(
+            "
+UPDATE admin_sessions
+SET last_used_at = CURRENT_TIMESTAMP
+WHERE token = $1 AND last_used_at <= $2",
+            token.as_ref(),
+            used_since.naive_utc()
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    /// Replaces the token of a live session in a single statement, so the old token stops working
+    /// the moment the new one is issued. The absolute expiry is kept.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn rotate_session(
+        &self,
+        token: impl AsRef<str>,
+        new_token: String,
+        idle_since: DateTime<Utc>,
+    ) -> Result<Option<entities::AdminSessionEntity>, RepositoryError> {
+        let session = sqlx::query_as!(
+            row_types::RawAdminSession,
+            "
+UPDATE admin_sessions
+SET token = $2, last_used_at = CURRENT_TIMESTAMP
+WHERE
+    token = $1
+    AND CURRENT_TIMESTAMP < expired_at
+    AND $3 < last_used_at
+RETURNING
+    admin_id,
+    token,
+    logined_at,
+    last_used_at,
+    expired_at",
+            token.as_ref(),
+            new_token,
+            idle_since.naive_utc()
+        )
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(session.map(|raw| raw.into()))
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn delete_session(&self, token: impl AsRef<str>) -> Result<(), RepositoryError> {
+        sqlx::query!(
+            "
+DELETE FROM admin_sessions
+WHERE token = $1",
+            token.as_ref()
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes the sessions that have expired or been idle since `idle_since`, returning how many
+    /// were deleted. They are deleted [`PRUNE_CHUNK_SIZE`] at a time.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn delete_expired_sessions(
+        &self,
+        idle_since: DateTime<Utc>,
+    ) -> Result<u64, RepositoryError> {
+        let mut deleted_count = 0;
+
+        loop {
+            let result = sqlx::query!(
+                "
+DELETE FROM admin_sessions
+WHERE id IN (
+    SELECT id
+    FROM admin_sessions
+    WHERE expired_at <= CURRENT_TIMESTAMP OR last_used_at <= $1
+    LIMIT $2
+)",
+                idle_since.naive_utc(),
+                PRUNE_CHUNK_SIZE
+            )
+            .execute(&self.db_pool)
+            .await?;
+
+            deleted_count += result.rows_affected();
+
+            if result.rows_affected() < PRUNE_CHUNK_SIZE as u64 {
+                return Ok(deleted_count);
+            }
+        }
+    }
+
+    /// Deletes every session of the admin, returning how many were deleted.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn delete_sessions_of_admin(&self, admin_id: Uuid) -> Result<u64, RepositoryError> {
+        let result = sqlx::query!(
+            "
+DELETE FROM admin_sessions
+WHERE admin_id = $1",
+            admin_id
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn create_password_reset(
+        &self,
+        admin_id: Uuid,
+        token_hash: String,
+        expired_at: DateTime<Utc>,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query!(
+            "
+INSERT INTO admin_password_resets (
+    admin_id,
+    token_hash,
+    expired_at
+) VALUES ($1, $2, $3)",
+            admin_id,
+            token_hash,
+            expired_at.naive_utc()
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks the password reset as used, returning the admin it was issued for. Unknown, expired
+    /// and already used resets resolve to `None`; concurrent uses cannot both succeed.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn use_password_reset(
+        &self,
+        token_hash: impl AsRef<str>,
+    ) -> Result<Option<Uuid>, RepositoryError> {
+        let admin_id = sqlx::query_scalar!(
+            "
+UPDATE admin_password_resets
+SET used_at = CURRENT_TIMESTAMP
+WHERE
+    token_hash = $1
+    AND used_at IS NULL
+    AND CURRENT_TIMESTAMP < expired_at
+RETURNING admin_id",
+            token_hash.as_ref()
+        )
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(admin_id)
+    }
+
+    /// Deletes the password resets that have expired or been used, returning how many were
+    /// deleted. They are deleted [`PRUNE_CHUNK_SIZE`] at a time.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn delete_stale_password_resets(&self) -> Result<u64, RepositoryError> {
+        let mut deleted_count = 0;
+
+        loop {
+            let result = sqlx::query!(
+                "
+DELETE FROM admin_password_resets
+WHERE id IN (
+    SELECT id
+    FROM admin_password_resets
+    WHERE expired_at <= CURRENT_TIMESTAMP OR used_at IS NOT NULL
+    LIMIT $1
+)",
+                PRUNE_CHUNK_SIZE
+            )
+            .execute(&self.db_pool)
+            .await?;
+
+            deleted_count += result.rows_affected();
+
+            if result.rows_affected() < PRUNE_CHUNK_SIZE as u64 {
+                return Ok(deleted_count);
+            }
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
     pub async fn create_one(
         &self,
         admin: entities::AdminEntityForCreation,
@@ -84,14 +354,16 @@ WHERE email = $1",
 INSERT INTO admins (
     username,
     email,
-    pw_hash
-) VALUES ($1, $2, $3)
+    pw_hash,
+    role
+) VALUES ($1, $2, $3, $4)
 RETURNING
     id,
     joined_at",
             admin.username,
             admin.email,
             admin.pw_hash,
+            admin.role as _,
         )
         .fetch_one(&self.db_pool)
         .await
@@ -107,32 +379,122 @@ RETURNING
             id: after_creation.id,
             username: admin.username,
             email: admin.email,
+            role: admin.role,
             joined_at: after_creation.joined_at.and_utc(),
         })
     }
 
+    /// Creates the first admin, either while there is no admin at all or, if `token_hash` is
+    /// given, once for that token. Returns `None` if there already is an admin or the token has
+    /// been used.
+    ///
+    /// The admins table is locked against inserts until the transaction ends, so concurrent
+    /// calls cannot both see it empty.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn create_first_one(
+        &self,
+        admin: entities::AdminEntityForCreation,
+        token_hash: Option<String>,
+    ) -> Result<Option<entities::AdminEntity>, RepositoryError> {
+        let mut tx = self.db_pool.begin().await?;
+
+        sqlx::query!("LOCK TABLE admins IN SHARE ROW EXCLUSIVE MODE")
+            .execute(&mut *tx)
+            .await?;
+
+        let allowed = match token_hash {
+            Some(token_hash) => {
+                let result = sqlx::query!(
+                    "
+INSERT INTO admin_bootstrap_tokens (token_hash)
+VALUES ($1)
+ON CONFLICT (token_hash) DO NOTHING",
+                    token_hash
+                )
+                .execute(&mut *tx)
+                .await?;
+
+                result.rows_affected() != 0
+            }
+            None => {
+                let exists =
+                    sqlx::query_scalar!(r#"SELECT EXISTS (SELECT 1 FROM admins) AS "exists!""#)
+                        .fetch_one(&mut *tx)
+                        .await?;
+
+                !exists
+            }
+        };
+
+        if !allowed {
+            return Ok(None);
+        }
+
+        let after_creation = sqlx::query_as!(
+            row_types::RawAdminAfterCreation,
+            "
+INSERT INTO admins (
+    username,
+    email,
+    pw_hash,
+    role
+) VALUES ($1, $2, $3, $4)
+RETURNING
+    id,
+    joined_at",
+            admin.username,
+            admin.email,
+            admin.pw_hash,
+            admin.role as _,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|err| {
+            RepositoryError::from_sqlx_err(err, |index| match index {
+                "admins_idx_username" => admin.username.clone(),
+                "admins_idx_email" => admin.email.clone(),
+                _ => "__unknown__".to_owned(),
+            })
+        })?;
+
+        tx.commit().await?;
+
+        Ok(Some(entities::AdminEntity {
+            id: after_creation.id,
+            username: admin.username,
+            email: admin.email,
+            role: admin.role,
+            joined_at: after_creation.joined_at.and_utc(),
+        }))
+    }
+
+    /// Returns `None` if there is no admin with the id.
+    #[tracing::instrument(level = "debug", skip_all)]
     pub async fn update_one(
         &self,
         admin: entities::AdminEntityForUpdate,
-    ) -> Result<entities::AdminEntity, RepositoryError> {
+    ) -> Result<Option<entities::AdminEntity>, RepositoryError> {
         let after_update = sqlx::query_as!(
             row_types::RawAdminAfterUpdate,
             "
 UPDATE admins SET
     username = COALESCE($1, username),
     email = COALESCE($2, email),
-    pw_hash = COALESCE($3, pw_hash)
-WHERE id = $4
+    pw_hash = COALESCE($3, pw_hash),
+    role = COALESCE($4, role)
+WHERE id = $5
 RETURNING
     username,
     email,
+    role AS \"role: AdminRole\",
     joined_at",
             admin.username,
             admin.email,
             admin.pw_hash,
+            admin.role as _,
             admin.id,
         )
-        .fetch_one(&self.db_pool)
+        .fetch_optional(&self.db_pool)
         .await
         .map_err(|err| {
             RepositoryError::from_sqlx_err(err, |index| match index {
@@ -142,16 +504,18 @@ RETURNING
             })
         })?;
 
-        Ok(entities::AdminEntity {
+        Ok(after_update.map(|after_update| entities::AdminEntity {
             id: admin.id,
             username: after_update.username,
             email: after_update.email,
+            role: after_update.role,
             joined_at: after_update.joined_at.and_utc(),
-        })
+        }))
     }
 }
 
 pub mod row_types {
+    use crate::interfaces::admins::AdminRole;
     use chrono::NaiveDateTime;
     use uuid::Uuid;
 
@@ -159,6 +523,7 @@ pub mod row_types {
         pub id: Uuid,
         pub username: String,
         pub email: String,
+        pub role: AdminRole,
         pub joined_at: NaiveDateTime,
     }
 
@@ -167,6 +532,26 @@ pub mod row_types {
         pub pw_hash: String,
     }
 
+    pub struct RawAdminSession {
+        pub admin_id: Uuid,
+        pub token: String,
+        pub logined_at: NaiveDateTime,
+        pub last_used_at: NaiveDateTime,
+        pub expired_at: NaiveDateTime,
+    }
+
+    pub struct RawAdminWithSession {
+        pub id: Uuid,
+        pub username: String,
+        pub email: String,
+        pub role: AdminRole,
+        pub joined_at: NaiveDateTime,
+        pub token: String,
+        pub logined_at: NaiveDateTime,
+        pub last_used_at: NaiveDateTime,
+        pub expired_at: NaiveDateTime,
+    }
+
     pub struct RawAdminAfterCreation {
         pub id: Uuid,
         pub joined_at: NaiveDateTime,
@@ -175,11 +560,13 @@ pub mod row_types {
     pub struct RawAdminAfterUpdate {
         pub username: String,
         pub email: String,
+        pub role: AdminRole,
         pub joined_at: NaiveDateTime,
     }
 }
 
 pub mod entities {
+    use crate::interfaces::admins::AdminRole;
     use chrono::{DateTime, Utc};
     use serde::{Deserialize, Serialize};
     use uuid::Uuid;
@@ -189,6 +576,7 @@ pub mod entities {
         pub id: Uuid,
         pub username: String,
         pub email: String,
+        pub role: AdminRole,
         pub joined_at: DateTime<Utc>,
     }
 
@@ -198,6 +586,7 @@ pub mod entities {
                 id: raw.id,
                 username: raw.username,
                 email: raw.email,
+                role: raw.role,
                 joined_at: raw.joined_at.and_utc(),
             }
         }
@@ -218,11 +607,54 @@ pub mod entities {
         }
     }
 
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct AdminSessionEntity {
+        pub admin_id: Uuid,
+        pub token: String,
+        pub logined_at: DateTime<Utc>,
+        pub last_used_at: DateTime<Utc>,
+        pub expired_at: DateTime<Utc>,
+    }
+
+    impl From<super::row_types::RawAdminSession> for AdminSessionEntity {
+        fn from(raw: super::row_types::RawAdminSession) -> Self {
+            Self {
+                admin_id: raw.admin_id,
+                token: raw.token,
+                logined_at: raw.logined_at.and_utc(),
+                last_used_at: raw.last_used_at.and_utc(),
+                expired_at: raw.expired_at.and_utc(),
+            }
+        }
+    }
+
+    impl From<super::row_types::RawAdminWithSession> for (AdminEntity, AdminSessionEntity) {
+        fn from(raw: super::row_types::RawAdminWithSession) -> Self {
+            (
+                AdminEntity {
+                    id: raw.id,
+                    username: raw.username,
+                    email: raw.email,
+                    role: raw.role,
+                    joined_at: raw.joined_at.and_utc(),
+                },
+                AdminSessionEntity {
+                    admin_id: raw.id,
+                    token: raw.token,
+                    logined_at: raw.logined_at.and_utc(),
+                    last_used_at: raw.last_used_at.and_utc(),
+                    expired_at: raw.expired_at.and_utc(),
+                },
+            )
+        }
+    }
+
     #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct AdminEntityForCreation {
         pub username: String,
         pub email: String,
         pub pw_hash: String,
+        pub role: AdminRole,
     }
 
     #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -246,12 +678,14 @@ pub mod entities {
         pub username: Option<String>,
         pub email: Option<String>,
         pub pw_hash: Option<String>,
+        pub role: Option<AdminRole>,
     }
 
     #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct AdminEntityAfterUpdate {
         pub username: String,
         pub email: String,
+        pub role: AdminRole,
         pub joined_at: DateTime<Utc>,
     }
 
@@ -260,6 +694,7 @@ pub mod entities {
             Self {
                 username: raw.username,
                 email: raw.email,
+                role: raw.role,
                 joined_at: raw.joined_at.and_utc(),
             }
         }
@@ -1,19 +1,31 @@
-use super::RepositoryError;
+use super::{check_tag_count, duplicate_tag, unknown_value, RepositoryError};
+use crate::interfaces::{collections::CollectionFileListSort, tags::TagRenameImpact};
 use futures::future::try_join;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+/// Most files [`CollectionRepository::list_files`] lists at once.
+const LIST_FILES_MAX_LIMIT: usize = 1000;
+/// Most names a [`RepositoryError::NameConflict`] lists.
+const NAME_CONFLICT_MAX_NAMES: i64 = 100;
+
 #[derive(Clone)]
 pub struct CollectionRepository {
     db_pool: PgPool,
+    /// The replica, if one is configured, for the scans that may lag behind the writes.
+    read_db_pool: PgPool,
 }
 
 impl CollectionRepository {
-    pub fn new(db_pool: PgPool) -> Self {
-        Self { db_pool }
+    pub fn new(db_pool: PgPool, read_db_pool: PgPool) -> Self {
+        Self {
+            db_pool,
+            read_db_pool,
+        }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(collection_id = %collection_id))]
     pub async fn find_one_by_id(
         &self,
         collection_id: Uuid,
@@ -21,7 +33,7 @@ impl CollectionRepository {
         let collection_task = sqlx::query_as!(
             row_types::RawCollection,
             "
-SELECT id, name, created_at
+SELECT id, name, unique_names, created_at
 FROM collections
 WHERE id = $1",
             collection_id
@@ -43,26 +55,98 @@ ORDER BY tag",
         Ok(collection.map(|raw| (raw, tags).into()))
     }
 
+    /// Finds the collection like [`CollectionRepository::find_one_by_id`], along with how many
+    /// files it has.
+    #[tracing::instrument(level = "debug", skip_all, fields(collection_id = %collection_id))]
+    pub async fn find_one_with_file_count_by_id(
+        &self,
+        collection_id: Uuid,
+    ) -> Result<Option<(entities::CollectionEntity, u64)>, RepositoryError> {
+        let Some(collection) = self.find_one_by_id(collection_id).await? else {
+            return Ok(None);
+        };
+        let mut conn = self.db_pool.acquire().await?;
+        let file_counts = count_files(&mut conn, &[collection_id]).await?;
+        let file_count = file_counts.get(&collection_id).copied().unwrap_or(0);
+
+        Ok(Some((collection, file_count)))
+    }
+
+    /// Whether the collection a list cursor points at still exists with the same name.
+    #[tracing::instrument(level = "debug", skip_all, fields(collection_id = %cursor.id))]
+    pub async fn cursor_matches(
+        &self,
+        cursor: &entities::CollectionCursorEntity,
+    ) -> Result<bool, RepositoryError> {
+        let matches = sqlx::query_scalar!(
+            "
+SELECT EXISTS (
+    SELECT 1
+    FROM collections
+    WHERE id = $1 AND name = $2
+) AS \"matches!\"",
+            cursor.id,
+            &cursor.name
+        )
+        .fetch_one(&self.read_db_pool)
+        .await?;
+
+        Ok(matches)
+    }
+
+    /// Whether the file a collection file list cursor points at still exists with the same name.
+    #[tracing::instrument(level = "debug", skip_all, fields(file_id = %cursor.id))]
+    pub async fn file_cursor_matches(
+        &self,
+        cursor: &entities::CollectionFileCursorEntity,
+    ) -> Result<bool, RepositoryError> {
+        let matches = sqlx::query_scalar!(
+            "
+SELECT EXISTS (
+    SELECT 1
+    FROM files
+    WHERE id = $1 AND name = $2
+) AS \"matches!\"",
+            cursor.id,
+            &cursor.name
+        )
+        .fetch_one(&self.read_db_pool)
+        .await?;
+
+        Ok(matches)
+    }
+
+    /// Lists the collections after the cursor, ordered by name, regardless of case, and then id,
+    /// only those that have every tag in `tags`.
+    #[tracing::instrument(level = "debug", skip_all)]
     pub async fn list(
         &self,
         limit: usize,
         cursor: Option<entities::CollectionCursorEntity>,
+        tags: &[String],
     ) -> Result<Vec<entities::CollectionEntity>, RepositoryError> {
-        let mut tx = self.db_pool.begin().await?;
+        let mut tx = self.read_db_pool.begin().await?;
 
         let collections = match cursor {
             Some(cursor) => {
                 sqlx::query_as!(
                     row_types::RawCollection,
                     "
-SELECT id, name, created_at
+SELECT id, name, unique_names, created_at
 FROM collections
-WHERE $1 <= name AND $2 < id
-ORDER BY name ASC, id ASC
+WHERE
+    ($1, $2) < (LOWER(name), id)
+    AND $4::TEXT[] <@ ARRAY(
+        SELECT tag
+        FROM collection_tags
+        WHERE collection_id = collections.id
+    )
+ORDER BY LOWER(name) ASC, id ASC
 LIMIT $3",
-                    &cursor.name,
+                    &cursor.name_key,
                     cursor.id,
                     limit as i64,
+                    tags
                 )
                 .fetch_all(&mut *tx)
                 .await?
@@ -71,11 +155,17 @@ LIMIT $3",
                 sqlx::query_as!(
                     row_types::RawCollection,
                     "
-SELECT id, name, created_at
+SELECT id, name, unique_names, created_at
 FROM collections
-ORDER BY name ASC, id ASC
+WHERE $2::TEXT[] <@ ARRAY(
+    SELECT tag
+    FROM collection_tags
+    WHERE collection_id = collections.id
+)
+ORDER BY LOWER(name) ASC, id ASC
 LIMIT $1",
                     limit as i64,
+                    tags
                 )
                 .fetch_all(&mut *tx)
                 .await?
@@ -123,33 +213,111 @@ ORDER BY tag",
             .collect())
     }
 
+    /// Lists collections like [`CollectionRepository::list`], along with how many files each
+    /// collection has.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn list_with_file_counts(
+        &self,
+        limit: usize,
+        cursor: Option<entities::CollectionCursorEntity>,
+    ) -> Result<Vec<(entities::CollectionEntity, u64)>, RepositoryError> {
+        let collections = self.list(limit, cursor, &[]).await?;
+        let mut conn = self.read_db_pool.acquire().await?;
+        let file_counts = count_files(
+            &mut conn,
+            &collections
+                .iter()
+                .map(|collection| collection.id)
+                .collect::<Vec<_>>(),
+        )
+        .await?;
+
+        Ok(collections
+            .into_iter()
+            .map(|collection| {
+                let file_count = file_counts.get(&collection.id).copied().unwrap_or(0);
+                (collection, file_count)
+            })
+            .collect())
+    }
+
+    /// Lists the ready files of the collection, those tagged with every tag of the collection,
+    /// ordered by name, regardless of case, or naturally by [`super::name_sort_key`], and then id.
+    /// Files sharing a name are paged through by their id. At most
+    /// [`LIST_FILES_MAX_LIMIT`] files are listed at once.
+    #[tracing::instrument(level = "debug", skip_all, fields(collection_id = %collection_id))]
     pub async fn list_files(
         &self,
         collection_id: Uuid,
         limit: usize,
         cursor: Option<entities::CollectionFileCursorEntity>,
+        sort: CollectionFileListSort,
     ) -> Result<Vec<super::file::entities::FileEntity>, RepositoryError> {
-        let files = match cursor {
-            Some(cursor) => {
+        let limit = limit.min(LIST_FILES_MAX_LIMIT);
+        let mut tx = self.read_db_pool.begin().await?;
+
+        let files = match (sort, cursor) {
+            (CollectionFileListSort::Name, Some(cursor)) => {
                 sqlx::query_as!(
                     super::file::row_types::RawFile,
                     "
-SELECT DISTINCT ON (file.name, file.id)
+SELECT
     file.id,
     file.name,
     file.size,
     file.mime_type,
-    file.uploaded_at
+    file.checksum,
+    file.is_ready,
+    file.protected,
+    file.superseded_by,
+    file.uploaded_at,
+    file.bucket,
+    file.region
 FROM files file
-JOIN file_tags ON file.id = file_tags.file_id
 WHERE file.id IN (
     SELECT t.file_id
     FROM file_tags t
-    WHERE t.tag IN (
-        SELECT c_tags.tag
+    JOIN collection_tags c_tags ON c_tags.tag = t.tag AND c_tags.collection_id = $1
+    GROUP BY t.file_id
+    HAVING COUNT(
+        DISTINCT t.tag
+    ) = (
+        SELECT COUNT(c_tags.tag)
         FROM collection_tags c_tags
         WHERE c_tags.collection_id = $1
     )
+) AND file.is_ready = TRUE AND ($2, $3) < (LOWER(file.name), file.id)
+ORDER BY LOWER(file.name) ASC, file.id ASC
+LIMIT $4",
+                    collection_id,
+                    &cursor.name_key,
+                    cursor.id,
+                    limit as i64,
+                )
+                .fetch_all(&mut *tx)
+                .await?
+            }
+            (CollectionFileListSort::Name, None) => {
+                sqlx::query_as!(
+                    super::file::row_types::RawFile,
+                    "
+SELECT
+    file.id,
+    file.name,
+    file.size,
+    file.mime_type,
+    file.checksum,
+    file.is_ready,
+    file.protected,
+    file.superseded_by,
+    file.uploaded_at,
+    file.bucket,
+    file.region
+FROM files file
+WHERE file.id IN (
+    SELECT t.file_id
+    FROM file_tags t
+    JOIN collection_tags c_tags ON c_tags.tag = t.tag AND c_tags.collection_id = $1
     GROUP BY t.file_id
     HAVING COUNT(
         DISTINCT t.tag
@@ -158,37 +326,76 @@ WHERE file.id IN (
         FROM collection_tags c_tags
         WHERE c_tags.collection_id = $1
     )
-) AND $2 <= file.name AND $3 < file.id
-ORDER BY file.name ASC, file.id ASC
-LIMIT $4",
+) AND file.is_ready = TRUE
+ORDER BY LOWER(file.name) ASC, file.id ASC
+LIMIT $2",
                     collection_id,
-                    &cursor.name,
-                    cursor.id,
                     limit as i64,
                 )
-                .fetch_all(&self.db_pool)
+                .fetch_all(&mut *tx)
                 .await?
             }
-            None => {
+            (CollectionFileListSort::NameNatural, Some(cursor)) => {
                 sqlx::query_as!(
                     super::file::row_types::RawFile,
                     "
-SELECT DISTINCT ON (file.name, file.id)
+SELECT
     file.id,
     file.name,
     file.size,
     file.mime_type,
-    file.uploaded_at
+    file.checksum,
+    file.is_ready,
+    file.protected,
+    file.superseded_by,
+    file.uploaded_at,
+    file.bucket,
+    file.region
 FROM files file
-JOIN file_tags ON file.id = file_tags.file_id
 WHERE file.id IN (
     SELECT t.file_id
     FROM file_tags t
-    WHERE t.tag IN (
-        SELECT c_tags.tag
+    JOIN collection_tags c_tags ON c_tags.tag = t.tag AND c_tags.collection_id = $1
+    GROUP BY t.file_id
+    HAVING COUNT(
+        DISTINCT t.tag
+    ) = (
+        SELECT COUNT(c_tags.tag)
         FROM collection_tags c_tags
         WHERE c_tags.collection_id = $1
     )
+) AND file.is_ready = TRUE AND ($2, $3) < (file.name_sort_key, file.id)
+ORDER BY file.name_sort_key ASC, file.id ASC
+LIMIT $4",
+                    collection_id,
+                    &cursor.name_key,
+                    cursor.id,
+                    limit as i64,
+                )
+                .fetch_all(&mut *tx)
+                .await?
+            }
+            (CollectionFileListSort::NameNatural, None) => {
+                sqlx::query_as!(
+                    super::file::row_types::RawFile,
+                    "
+SELECT
+    file.id,
+    file.name,
+    file.size,
+    file.mime_type,
+    file.checksum,
+    file.is_ready,
+    file.protected,
+    file.superseded_by,
+    file.uploaded_at,
+    file.bucket,
+    file.region
+FROM files file
+WHERE file.id IN (
+    SELECT t.file_id
+    FROM file_tags t
+    JOIN collection_tags c_tags ON c_tags.tag = t.tag AND c_tags.collection_id = $1
     GROUP BY t.file_id
     HAVING COUNT(
         DISTINCT t.tag
@@ -197,12 +404,13 @@ WHERE file.id IN (
         FROM collection_tags c_tags
         WHERE c_tags.collection_id = $1
     )
-)
+) AND file.is_ready = TRUE
+ORDER BY file.name_sort_key ASC, file.id ASC
 LIMIT $2",
                     collection_id,
                     limit as i64,
                 )
-                .fetch_all(&self.db_pool)
+                .fetch_all(&mut *tx)
                 .await?
             }
         };
@@ -215,9 +423,11 @@ WHERE file_id = ANY($1::uuid[])
 ORDER BY tag",
             &files.iter().map(|file| file.id).collect::<Vec<_>>()
         )
-        .fetch_all(&self.db_pool)
+        .fetch_all(&mut *tx)
         .await?;
 
+        tx.commit().await?;
+
         let mut files_map =
             HashMap::<_, _>::from_iter(files.iter().map(|file| (file.id, Vec::with_capacity(10))));
 
@@ -241,24 +451,28 @@ ORDER BY tag",
         Ok(files)
     }
 
+    #[tracing::instrument(level = "debug", skip_all)]
+    /// Returns the created collection along with how many files it has.
     pub async fn create_one(
         &self,
         collection: entities::CollectionEntityForCreation,
-    ) -> Result<entities::CollectionEntity, RepositoryError> {
+    ) -> Result<(entities::CollectionEntity, u64), RepositoryError> {
         let mut tx = self.db_pool.begin().await?;
 
         let after_creation = sqlx::query_as!(
             row_types::RawCollectionAfterCreation,
             "
-INSERT INTO collections (name)
-VALUES ($1)
+INSERT INTO collections (name, unique_names)
+VALUES ($1, $2)
 RETURNING id, created_at",
-            collection.name
+            collection.name,
+            collection.unique_names
         )
         .fetch_one(&mut *tx)
         .await?;
 
         if !collection.tags.is_empty() {
+            check_tag_count([], &collection.tags)?;
             sqlx::query!(
                 "
 INSERT INTO collection_tags (collection_id, tag)
@@ -268,32 +482,67 @@ SELECT $1, UNNEST($2::text[])
                 &collection.tags[..]
             )
             .execute(&mut *tx)
-            .await?;
+            .await
+            .map_err(|err| {
+                RepositoryError::from_sqlx_err(err, |_| {
+                    duplicate_tag([], &collection.tags).unwrap_or_else(unknown_value)
+                })
+            })?;
+        }
+
+        if collection.unique_names {
+            check_unique_collection_names(&mut tx, after_creation.id).await?;
         }
 
+        let file_counts = count_files(&mut tx, &[after_creation.id]).await?;
+        let file_count = file_counts.get(&after_creation.id).copied().unwrap_or(0);
+
         tx.commit().await?;
 
-        Ok((collection, after_creation).into())
+        Ok(((collection, after_creation).into(), file_count))
     }
 
+    /// Returns the updated collection along with how many files it has, whether anything about it
+    /// changed and the tags it had before.
+    #[allow(clippy::type_complexity)]
+    #[tracing::instrument(level = "debug", skip_all)]
     pub async fn update_one(
         &self,
         collection: entities::CollectionEntityForUpdate,
         tags_for_creation: Vec<String>,
         tags_for_deletion: Vec<String>,
-    ) -> Result<Option<entities::CollectionEntity>, RepositoryError> {
+    ) -> Result<Option<(entities::CollectionEntity, u64, bool, Vec<String>)>, RepositoryError> {
         let mut tx = self.db_pool.begin().await?;
 
         let collection_id = collection.id;
+        let before_update = sqlx::query_as!(
+            row_types::RawCollectionBeforeUpdate,
+            "
+SELECT name, unique_names
+FROM collections
+WHERE id = $1
+FOR UPDATE",
+            collection_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let before_update = match before_update {
+            Some(before_update) => before_update,
+            None => {
+                return Ok(None);
+            }
+        };
+
         let collection = sqlx::query_as!(
             row_types::RawCollectionAfterUpdate,
             "
 UPDATE collections
-SET name = COALESCE($1, name)
+SET name = COALESCE($1, name), unique_names = COALESCE($3, unique_names)
 WHERE id = $2
-RETURNING name, created_at",
+RETURNING name, unique_names, created_at",
             collection.name,
             collection_id,
+            collection.unique_names,
         )
         .fetch_optional(&mut *tx)
         .await?;
@@ -315,6 +564,8 @@ ORDER BY tag",
         )
         .fetch_all(&mut *tx)
         .await?;
+        let tags_before_update = Vec::from_iter(tags.iter().map(|tag| tag.tag.clone()));
+        let tag_count_before_update = tags.len();
 
         if !tags_for_deletion.is_empty() {
             sqlx::query!(
@@ -331,7 +582,10 @@ WHERE collection_id = $1 AND tag = ANY($2::text[])
             tags.retain(|tag| !tags_for_deletion.contains(&tag.tag));
         }
 
+        let tag_count_before_creation = tags.len();
+
         if !tags_for_creation.is_empty() {
+            check_tag_count(tags.iter().map(|tag| tag.tag.as_str()), &tags_for_creation)?;
             sqlx::query!(
                 "
 INSERT INTO collection_tags (collection_id, tag)
@@ -341,7 +595,13 @@ SELECT $1, UNNEST($2::text[])
                 &tags_for_creation
             )
             .execute(&mut *tx)
-            .await?;
+            .await
+            .map_err(|err| {
+                RepositoryError::from_sqlx_err(err, |_| {
+                    duplicate_tag(tags.iter().map(|tag| tag.tag.as_str()), &tags_for_creation)
+                        .unwrap_or_else(unknown_value)
+                })
+            })?;
 
             tags.extend(
                 tags_for_creation
@@ -350,29 +610,151 @@ SELECT $1, UNNEST($2::text[])
             );
         }
 
+        // tags are only ever removed or added, so a change shows in the count
+        let tags_changed = tag_count_before_update != tag_count_before_creation
+            || tag_count_before_creation != tags.len();
+
+        // the members change with the tags, so they are checked again
+        if collection.unique_names && (!before_update.unique_names || tags_changed) {
+            check_unique_collection_names(&mut tx, collection_id).await?;
+        }
+
+        let file_counts = count_files(&mut tx, &[collection_id]).await?;
+        let file_count = file_counts.get(&collection_id).copied().unwrap_or(0);
+
         tx.commit().await?;
         tags.sort_unstable_by(|a, b| a.tag.cmp(&b.tag));
 
-        Ok(Some(entities::CollectionEntity {
-            id: collection_id,
-            name: collection.name,
-            created_at: collection.created_at.and_utc(),
-            tags: tags.into_iter().map(|raw| raw.tag).collect(),
-        }))
+        let changed = before_update.name != collection.name
+            || before_update.unique_names != collection.unique_names
+            || tags_changed;
+
+        Ok(Some((
+            entities::CollectionEntity {
+                id: collection_id,
+                name: collection.name,
+                unique_names: collection.unique_names,
+                created_at: collection.created_at.and_utc(),
+                tags: tags.into_iter().map(|raw| raw.tag).collect(),
+            },
+            file_count,
+            changed,
+            tags_before_update,
+        )))
     }
 
-    pub async fn delete_one(&self, collection_id: Uuid) -> Result<(), RepositoryError> {
+    /// Counts the collections `tag` would be renamed or merged on, and whether any collection has
+    /// `new_name` already, along with the names of up to `sample_limit` of the collections, by
+    /// name.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn preview_tag_rename(
+        &self,
+        tag: &str,
+        new_name: &str,
+        sample_limit: usize,
+    ) -> Result<(TagRenameImpact, bool), RepositoryError> {
+        let counts = sqlx::query!(
+            r#"
+SELECT
+    COUNT(*) FILTER (WHERE merged.collection_id IS NULL) AS "renamed_count!",
+    COUNT(merged.collection_id) AS "merged_count!",
+    EXISTS (SELECT 1 FROM collection_tags WHERE tag = $2) AS "target_exists!"
+FROM collection_tags
+LEFT JOIN collection_tags merged
+    ON merged.collection_id = collection_tags.collection_id AND merged.tag = $2
+WHERE collection_tags.tag = $1"#,
+            tag,
+            new_name
+        )
+        .fetch_one(&self.read_db_pool)
+        .await?;
+        let sample_names = sqlx::query_scalar!(
+            "
+SELECT collections.name
+FROM collection_tags
+JOIN collections ON collections.id = collection_tags.collection_id
+WHERE collection_tags.tag = $1
+ORDER BY collections.name ASC, collections.id ASC
+LIMIT $2",
+            tag,
+            sample_limit as i64
+        )
+        .fetch_all(&self.read_db_pool)
+        .await?;
+
+        Ok((
+            TagRenameImpact {
+                renamed_count: counts.renamed_count as u64,
+                merged_count: counts.merged_count as u64,
+                sample_names,
+            },
+            counts.target_exists,
+        ))
+    }
+
+    /// Renames `tag` to `new_name` on every collection, merging it into `new_name` on collections
+    /// that have both, and returns their ids.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn rename_tag(
+        &self,
+        tag: &str,
+        new_name: &str,
+    ) -> Result<Vec<Uuid>, RepositoryError> {
         let mut tx = self.db_pool.begin().await?;
 
+        let collection_ids = sqlx::query_scalar!(
+            "
+SELECT collection_id
+FROM collection_tags
+WHERE tag = $1
+FOR UPDATE",
+            tag
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "
+INSERT INTO collection_tags (collection_id, tag)
+SELECT collection_id, $2
+FROM UNNEST($1::uuid[]) AS collection_id
+ON CONFLICT DO NOTHING",
+            &collection_ids,
+            new_name
+        )
+        .execute(&mut *tx)
+        .await?;
+
         sqlx::query!(
             "
 DELETE FROM collection_tags
-WHERE collection_id = $1",
-            collection_id
+WHERE collection_id = ANY($1::uuid[]) AND tag = $2",
+            &collection_ids,
+            tag
         )
         .execute(&mut *tx)
         .await?;
 
+        tx.commit().await?;
+
+        Ok(collection_ids)
+    }
+
+    /// Returns the tags the collection had.
+    #[tracing::instrument(level = "debug", skip_all, fields(collection_id = %collection_id))]
+    pub async fn delete_one(&self, collection_id: Uuid) -> Result<Vec<String>, RepositoryError> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let tags = sqlx::query_scalar!(
+            "
+DELETE FROM collection_tags
+WHERE collection_id = $1
+RETURNING tag",
+            collection_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
         sqlx::query!(
             "
 DELETE FROM collections
@@ -384,10 +766,172 @@ WHERE id = $1",
 
         tx.commit().await?;
 
-        Ok(())
+        Ok(tags)
     }
 }
 
+/// Counts the files of each collection, the same files [`CollectionRepository::list_files`]
+/// lists: the ready ones tagged with every tag of the collection.
+async fn count_files(
+    conn: &mut PgConnection,
+    collection_ids: &[Uuid],
+) -> Result<HashMap<Uuid, u64>, RepositoryError> {
+    let file_counts = sqlx::query_as!(
+        row_types::RawCollectionFileCount,
+        "
+SELECT collection.id AS \"collection_id!\", COUNT(matched_file.file_id) AS \"file_count!\"
+FROM UNNEST($1::uuid[]) AS collection (id)
+LEFT JOIN LATERAL (
+    SELECT t.file_id
+    FROM file_tags t
+    JOIN collection_tags c_tags ON c_tags.tag = t.tag AND c_tags.collection_id = collection.id
+    JOIN files file ON file.id = t.file_id AND file.is_ready = TRUE
+    GROUP BY t.file_id
+    HAVING COUNT(
+        DISTINCT t.tag
+    ) = (
+        SELECT COUNT(c_tags.tag)
+        FROM collection_tags c_tags
+        WHERE c_tags.collection_id = collection.id
+    )
+) matched_file ON TRUE
+GROUP BY collection.id",
+        collection_ids
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(file_counts
+        .into_iter()
+        .map(|raw| (raw.collection_id, raw.file_count as u64))
+        .collect())
+}
+
+/// Fails with [`RepositoryError::NameConflict`] if any of the files shares its name with another
+/// file of a collection that requires unique names and has them both. Files count from their
+/// creation on, so that a name is claimed before its upload completes, while the versions of a file
+/// may share its name. The collections are locked first, so that concurrent writes to the same
+/// collection are checked one after another.
+pub(super) async fn check_unique_file_names(
+    conn: &mut PgConnection,
+    file_ids: &[Uuid],
+) -> Result<(), RepositoryError> {
+    let collection_ids = sqlx::query_scalar!(
+        "
+SELECT collections.id
+FROM collections
+WHERE
+    collections.unique_names = TRUE
+    AND EXISTS (
+        SELECT 1
+        FROM collection_tags c_tags
+        WHERE c_tags.collection_id = collections.id
+    )
+    AND EXISTS (
+        SELECT 1
+        FROM UNNEST($1::uuid[]) AS file (id)
+        WHERE NOT EXISTS (
+            SELECT 1
+            FROM collection_tags c_tags
+            WHERE c_tags.collection_id = collections.id AND NOT EXISTS (
+                SELECT 1
+                FROM file_tags t
+                WHERE t.file_id = file.id AND t.tag = c_tags.tag
+            )
+        )
+    )
+ORDER BY collections.id
+FOR UPDATE",
+        file_ids
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    if collection_ids.is_empty() {
+        return Ok(());
+    }
+
+    // only files sharing a name are compared, so the name index narrows the candidates first
+    let names = sqlx::query_scalar!(
+        "
+SELECT DISTINCT file.name AS \"name!\"
+FROM UNNEST($1::uuid[]) AS collection (id)
+CROSS JOIN files file
+JOIN files other
+    ON other.name = file.name
+    AND COALESCE(other.version_of, other.id) <> COALESCE(file.version_of, file.id)
+WHERE file.id = ANY($2::uuid[]) AND NOT EXISTS (
+    SELECT 1
+    FROM collection_tags c_tags
+    WHERE c_tags.collection_id = collection.id AND (
+        NOT EXISTS (
+            SELECT 1
+            FROM file_tags t
+            WHERE t.file_id = file.id AND t.tag = c_tags.tag
+        )
+        OR NOT EXISTS (
+            SELECT 1
+            FROM file_tags t
+            WHERE t.file_id = other.id AND t.tag = c_tags.tag
+        )
+    )
+)
+ORDER BY file.name
+LIMIT $3",
+        &collection_ids,
+        file_ids,
+        NAME_CONFLICT_MAX_NAMES
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    if !names.is_empty() {
+        return Err(RepositoryError::NameConflict { names });
+    }
+
+    Ok(())
+}
+
+/// Fails with [`RepositoryError::NameConflict`] if any two files of the collection share a name,
+/// counting the files still uploading like [`check_unique_file_names`] does.
+async fn check_unique_collection_names(
+    conn: &mut PgConnection,
+    collection_id: Uuid,
+) -> Result<(), RepositoryError> {
+    let names = sqlx::query_scalar!(
+        "
+SELECT file.name
+FROM files file
+WHERE file.id IN (
+    SELECT t.file_id
+    FROM file_tags t
+    JOIN collection_tags c_tags ON c_tags.tag = t.tag AND c_tags.collection_id = $1
+    GROUP BY t.file_id
+    HAVING COUNT(
+        DISTINCT t.tag
+    ) = (
+        SELECT COUNT(c_tags.tag)
+        FROM collection_tags c_tags
+        WHERE c_tags.collection_id = $1
+    )
+)
+GROUP BY file.name
+HAVING COUNT(DISTINCT COALESCE(file.version_of, file.id)) > 1
+ORDER BY file.name
+LIMIT $2",
+        collection_id,
+        NAME_CONFLICT_MAX_NAMES
+    )
+    .fetch_all(conn)
+    .await?;
+
+    if !names.is_empty() {
+        return Err(RepositoryError::NameConflict { names });
+    }
+
+    Ok(())
+}
+
 pub mod row_types {
     use chrono::NaiveDateTime;
     use uuid::Uuid;
@@ -395,6 +939,7 @@ pub mod row_types {
     pub struct RawCollection {
         pub id: Uuid,
         pub name: String,
+        pub unique_names: bool,
         pub created_at: NaiveDateTime,
     }
 
@@ -412,10 +957,21 @@ pub mod row_types {
         pub created_at: NaiveDateTime,
     }
 
+    pub struct RawCollectionBeforeUpdate {
+        pub name: String,
+        pub unique_names: bool,
+    }
+
     pub struct RawCollectionAfterUpdate {
         pub name: String,
+        pub unique_names: bool,
         pub created_at: NaiveDateTime,
     }
+
+    pub struct RawCollectionFileCount {
+        pub collection_id: Uuid,
+        pub file_count: i64,
+    }
 }
 
 pub mod entities {
@@ -427,6 +983,8 @@ pub mod entities {
     pub struct CollectionEntity {
         pub id: Uuid,
         pub name: String,
+        /// Whether no two files of the collection may share a name.
+        pub unique_names: bool,
         pub created_at: DateTime<Utc>,
         pub tags: Vec<String>,
     }
@@ -446,6 +1004,7 @@ pub mod entities {
             Self {
                 id: raw.id,
                 name: raw.name,
+                unique_names: raw.unique_names,
                 created_at: raw.created_at.and_utc(),
                 tags: tags.into_iter().map(|raw| raw.tag).collect(),
             }
@@ -467,6 +1026,7 @@ pub mod entities {
             Self {
                 id: raw.id,
                 name: collection.name,
+                unique_names: collection.unique_names,
                 created_at: raw.created_at.and_utc(),
                 tags: collection.tags,
             }
@@ -477,17 +1037,23 @@ pub mod entities {
     pub struct CollectionCursorEntity {
         pub id: Uuid,
         pub name: String,
+        /// The lowercase name, which the collections are ordered by.
+        pub name_key: String,
     }
 
     #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct CollectionFileCursorEntity {
         pub id: Uuid,
         pub name: String,
+        /// The key the files are ordered by: the lowercase name, or the natural sort key of the
+        /// name, see [`crate::db::repositories::name_sort_key`].
+        pub name_key: String,
     }
 
     #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct CollectionEntityForCreation {
         pub name: String,
+        pub unique_names: bool,
         pub tags: Vec<String>,
     }
 
@@ -495,5 +1061,6 @@ pub mod entities {
     pub struct CollectionEntityForUpdate {
         pub id: Uuid,
         pub name: Option<String>,
+        pub unique_names: Option<bool>,
     }
 }
@@ -1,20 +1,34 @@
-use super::RepositoryError;
+use super::{
+    check_tag_count, collection::check_unique_file_names, duplicate_tag, escape_like,
+    name_sort_key, unknown_value, RepositoryError, MAX_TAGS, MAX_VERSION_CHAIN_LENGTH,
+    PRUNE_CHUNK_SIZE,
+};
+use crate::interfaces::{
+    files::{FileCollection, FileStorage, UploadOutcome},
+    tags::TagRenameImpact,
+};
 use chrono::{DateTime, Utc};
-use futures::future::try_join;
-use sqlx::PgPool;
+use futures::{future::try_join, stream::BoxStream, StreamExt};
+use sqlx::{PgConnection, PgPool};
 use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct FileRepository {
     db_pool: PgPool,
+    /// The replica, if one is configured, for the scans that may lag behind the writes.
+    read_db_pool: PgPool,
 }
 
 impl FileRepository {
-    pub fn new(db_pool: PgPool) -> Self {
-        Self { db_pool }
+    pub fn new(db_pool: PgPool, read_db_pool: PgPool) -> Self {
+        Self {
+            db_pool,
+            read_db_pool,
+        }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(file_id = %file_id))]
     pub async fn find_one_by_id(
         &self,
         file_id: Uuid,
@@ -27,7 +41,13 @@ SELECT
     name,
     size,
     mime_type,
-    uploaded_at
+    checksum,
+    is_ready,
+    protected,
+    superseded_by,
+    uploaded_at,
+    bucket,
+    region
 FROM files
 WHERE id = $1 AND is_ready = TRUE",
             file_id
@@ -49,6 +69,40 @@ ORDER BY tag",
         Ok(file.map(|raw| (raw, tags).into()))
     }
 
+    /// Finds the ready files among `file_ids`, in id order. Missing and unready files are left out.
+    #[tracing::instrument(level = "debug", skip_all, fields(count = file_ids.len()))]
+    pub async fn find_many_by_ids(
+        &self,
+        file_ids: &[Uuid],
+    ) -> Result<Vec<entities::FileEntity>, RepositoryError> {
+        let mut conn = self.db_pool.acquire().await?;
+        let files = sqlx::query_as!(
+            row_types::RawFile,
+            "
+SELECT
+    id,
+    name,
+    size,
+    mime_type,
+    checksum,
+    is_ready,
+    protected,
+    superseded_by,
+    uploaded_at,
+    bucket,
+    region
+FROM files
+WHERE id = ANY($1::uuid[]) AND is_ready = TRUE
+ORDER BY id",
+            file_ids
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        with_tags(&mut conn, files).await
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(file_id = %file_id))]
     pub async fn find_one_for_upload(
         &self,
         file_id: Uuid,
@@ -56,7 +110,7 @@ ORDER BY tag",
         let file = sqlx::query_as!(
             row_types::RawFileForUpload,
             "
-SELECT size, mime_type
+SELECT size, mime_type, bucket, region, active_upload_id
 FROM files
 WHERE id = $1",
             file_id
@@ -67,12 +121,90 @@ WHERE id = $1",
         Ok(file.map(|raw| raw.into()))
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(file_id = %file_id))]
+    pub async fn find_one_for_completion(
+        &self,
+        file_id: Uuid,
+    ) -> Result<Option<entities::FileEntityForCompletion>, RepositoryError> {
+        let file = sqlx::query_as!(
+            row_types::RawFileForCompletion,
+            "
+SELECT is_ready, upload_id, size, bucket, region
+FROM files
+WHERE id = $1",
+            file_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(file.map(|raw| raw.into()))
+    }
+
+    /// Finds the latest unready file with the name, size and checksum whose upload is active, i.e.
+    /// the same content being uploaded already.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn find_one_uploading_duplicate(
+        &self,
+        name: &str,
+        size: usize,
+        checksum: &str,
+    ) -> Result<Option<entities::UploadingFileEntity>, RepositoryError> {
+        let file = sqlx::query_as!(
+            row_types::RawUploadingFile,
+            "
+SELECT id, active_upload_id AS \"active_upload_id!\", bucket, region
+FROM files
+WHERE
+    checksum = $1
+    AND name = $2
+    AND size = $3
+    AND is_ready = FALSE
+    AND active_upload_id IS NOT NULL
+ORDER BY uploaded_at DESC
+LIMIT 1",
+            checksum,
+            name,
+            size as i64
+        )
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(file.map(|raw| raw.into()))
+    }
+
+    /// Whether the file a list cursor points at still exists with the same `uploaded_at`.
+    #[tracing::instrument(level = "debug", skip_all, fields(file_id = %cursor.id))]
+    pub async fn cursor_matches(
+        &self,
+        cursor: &entities::FileCursorEntity,
+    ) -> Result<bool, RepositoryError> {
+        let matches = sqlx::query_scalar!(
+            "
+SELECT EXISTS (
+    SELECT 1
+    FROM files
+    WHERE id = $1 AND uploaded_at = $2
+) AS \"matches!\"",
+            cursor.id,
+            cursor.uploaded_at.naive_utc()
+        )
+        .fetch_one(&self.read_db_pool)
+        .await?;
+
+        Ok(matches)
+    }
+
+    /// Lists the ready files after the cursor, only those whose name starts with `name_prefix`
+    /// if given. The prefix is matched literally; `%` and `_` are no wildcards.
+    #[tracing::instrument(level = "debug", skip_all)]
     pub async fn list(
         &self,
         limit: usize,
         cursor: Option<entities::FileCursorEntity>,
+        name_prefix: Option<&str>,
     ) -> Result<Vec<entities::FileEntity>, RepositoryError> {
-        let mut tx = self.db_pool.begin().await?;
+        let name_pattern = name_prefix.map(|prefix| format!("{}%", escape_like(prefix)));
+        let mut tx = self.read_db_pool.begin().await?;
 
         let files = match cursor {
             Some(cursor) => {
@@ -84,14 +216,25 @@ SELECT
     name,
     size,
     mime_type,
-    uploaded_at
+    checksum,
+    is_ready,
+    protected,
+    superseded_by,
+    uploaded_at,
+    bucket,
+    region
 FROM files
-WHERE uploaded_at <= $1 AND $2 < id AND is_ready = TRUE
+WHERE
+    uploaded_at <= $1
+    AND $2 < id
+    AND is_ready = TRUE
+    AND ($4::TEXT IS NULL OR name LIKE $4)
 ORDER BY uploaded_at DESC, id ASC
 LIMIT $3",
                     cursor.uploaded_at.naive_utc(),
                     cursor.id,
-                    limit as i64
+                    limit as i64,
+                    name_pattern
                 )
                 .fetch_all(&mut *tx)
                 .await?
@@ -105,52 +248,162 @@ SELECT
     name,
     size,
     mime_type,
-    uploaded_at
+    checksum,
+    is_ready,
+    protected,
+    superseded_by,
+    uploaded_at,
+    bucket,
+    region
 FROM files
-WHERE is_ready = TRUE
+WHERE is_ready = TRUE AND ($2::TEXT IS NULL OR name LIKE $2)
 ORDER BY uploaded_at DESC, id ASC
 LIMIT $1",
-                    limit as i64
+                    limit as i64,
+                    name_pattern
                 )
                 .fetch_all(&mut *tx)
                 .await?
             }
         };
+        let files = with_tags(&mut tx, files).await?;
 
-        let tags = sqlx::query_as!(
-            row_types::RawFileTagWithFileId,
-            "
-SELECT file_id, tag
-FROM file_tags
-WHERE file_id = ANY($1::uuid[])",
-            &files.iter().map(|file| file.id).collect::<Vec<_>>()
-        )
-        .fetch_all(&mut *tx)
-        .await?;
+        tx.commit().await?;
+
+        Ok(files)
+    }
+
+    /// Lists the ready files after the cursor in the natural order of their names, see
+    /// [`name_sort_key`], only those whose name starts with `name_prefix` if given.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn list_by_name(
+        &self,
+        limit: usize,
+        cursor: Option<entities::FileNameCursorEntity>,
+        name_prefix: Option<&str>,
+    ) -> Result<Vec<entities::FileEntity>, RepositoryError> {
+        let name_pattern = name_prefix.map(|prefix| format!("{}%", escape_like(prefix)));
+        let mut tx = self.read_db_pool.begin().await?;
+
+        let files = match cursor {
+            Some(cursor) => {
+                sqlx::query_as!(
+                    row_types::RawFile,
+                    "
+SELECT
+    id,
+    name,
+    size,
+    mime_type,
+    checksum,
+    is_ready,
+    protected,
+    superseded_by,
+    uploaded_at,
+    bucket,
+    region
+FROM files
+WHERE
+    ($1, $2) < (name_sort_key, id)
+    AND is_ready = TRUE
+    AND ($4::TEXT IS NULL OR name LIKE $4)
+ORDER BY name_sort_key ASC, id ASC
+LIMIT $3",
+                    &cursor.name_sort_key,
+                    cursor.id,
+                    limit as i64,
+                    name_pattern
+                )
+                .fetch_all(&mut *tx)
+                .await?
+            }
+            None => {
+                sqlx::query_as!(
+                    row_types::RawFile,
+                    "
+SELECT
+    id,
+    name,
+    size,
+    mime_type,
+    checksum,
+    is_ready,
+    protected,
+    superseded_by,
+    uploaded_at,
+    bucket,
+    region
+FROM files
+WHERE is_ready = TRUE AND ($2::TEXT IS NULL OR name LIKE $2)
+ORDER BY name_sort_key ASC, id ASC
+LIMIT $1",
+                    limit as i64,
+                    name_pattern
+                )
+                .fetch_all(&mut *tx)
+                .await?
+            }
+        };
+        let files = with_tags(&mut tx, files).await?;
 
         tx.commit().await?;
 
-        let mut files_map =
-            HashMap::<_, _>::from_iter(files.iter().map(|file| (file.id, Vec::with_capacity(10))));
+        Ok(files)
+    }
 
-        for tag in tags {
-            files_map
-                .entry(tag.file_id)
-                .or_default()
-                .push(row_types::RawFileTag { tag: tag.tag });
-        }
+    /// Whether the file a name list cursor points at still exists with the same name.
+    #[tracing::instrument(level = "debug", skip_all, fields(file_id = %cursor.id))]
+    pub async fn name_cursor_matches(
+        &self,
+        cursor: &entities::FileNameCursorEntity,
+    ) -> Result<bool, RepositoryError> {
+        let matches = sqlx::query_scalar!(
+            "
+SELECT EXISTS (
+    SELECT 1
+    FROM files
+    WHERE id = $1 AND name = $2
+) AS \"matches!\"",
+            cursor.id,
+            &cursor.name
+        )
+        .fetch_one(&self.read_db_pool)
+        .await?;
 
-        Ok(files
-            .into_iter()
-            .map(|file| {
-                let mut tags = files_map.remove(&file.id).unwrap_or_default();
-                tags.sort_unstable_by(|a, b| a.tag.cmp(&b.tag));
+        Ok(matches)
+    }
 
-                (file, tags).into()
-            })
-            .collect())
+    /// Streams every ready file with its tags, newest first, without loading them all into memory.
+    pub fn stream_ready(&self) -> BoxStream<'_, Result<entities::FileEntity, RepositoryError>> {
+        sqlx::query_as!(
+            row_types::RawFileWithTags,
+            "
+SELECT
+    file.id,
+    file.name,
+    file.size,
+    file.mime_type,
+    file.checksum,
+    file.is_ready,
+    file.protected,
+    file.superseded_by,
+    file.uploaded_at,
+    COALESCE(
+        ARRAY_AGG(file_tags.tag ORDER BY file_tags.tag) FILTER (WHERE file_tags.tag IS NOT NULL),
+        '{}'
+    ) AS \"tags!\"
+FROM files file
+LEFT JOIN file_tags ON file.id = file_tags.file_id
+WHERE file.is_ready = TRUE
+GROUP BY file.id
+ORDER BY file.uploaded_at DESC, file.id ASC"
+        )
+        .fetch(&self.read_db_pool)
+        .map(|result| result.map(|raw| raw.into()).map_err(|err| err.into()))
+        .boxed()
     }
 
+    #[tracing::instrument(level = "debug", skip_all)]
     pub async fn create_one(
         &self,
         file: entities::FileEntityForCreation,
@@ -160,17 +413,22 @@ WHERE file_id = ANY($1::uuid[])",
         let after_creation = sqlx::query_as!(
             row_types::RawFileAfterCreation,
             "
-INSERT INTO files (name, size, mime_type)
-VALUES ($1, $2, $3)
-RETURNING id, uploaded_at",
+INSERT INTO files (name, size, mime_type, checksum, bucket, region, name_sort_key)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+RETURNING id, is_ready, uploaded_at",
             &file.name,
             file.size as i64,
             &file.mime_type,
+            file.checksum.as_deref(),
+            &file.storage.bucket,
+            &file.storage.region,
+            name_sort_key(&file.name),
         )
         .fetch_one(&mut *tx)
         .await?;
 
         if !file.tags.is_empty() {
+            check_tag_count([], &file.tags)?;
             sqlx::query!(
                 "
 INSERT INTO file_tags (file_id, tag)
@@ -180,7 +438,14 @@ SELECT $1, UNNEST($2::text[])
                 &file.tags[..]
             )
             .execute(&mut *tx)
-            .await?;
+            .await
+            .map_err(|err| {
+                RepositoryError::from_sqlx_err(err, |_| {
+                    duplicate_tag([], &file.tags).unwrap_or_else(unknown_value)
+                })
+            })?;
+
+            check_unique_file_names(&mut tx, &[after_creation.id]).await?;
         }
 
         tx.commit().await?;
@@ -188,15 +453,281 @@ SELECT $1, UNNEST($2::text[])
         Ok((file, after_creation).into())
     }
 
+    /// Creates every file in one transaction, returning them in the order given.
+    #[tracing::instrument(level = "debug", skip_all, fields(file_count = files.len()))]
+    pub async fn create_many(
+        &self,
+        files: Vec<entities::FileEntityForCreation>,
+    ) -> Result<Vec<entities::FileEntity>, RepositoryError> {
+        let file_ids = Vec::from_iter(files.iter().map(|_| Uuid::new_v4()));
+        let mut tag_file_ids = Vec::new();
+        let mut tags = Vec::new();
+
+        for (file_id, file) in file_ids.iter().zip(&files) {
+            check_tag_count([], &file.tags)?;
+            tag_file_ids.extend(file.tags.iter().map(|_| *file_id));
+            tags.extend(file.tags.iter().cloned());
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+
+        let after_creations = sqlx::query_as!(
+            row_types::RawFileAfterCreation,
+            "
+INSERT INTO files (id, name, size, mime_type, checksum, bucket, region, name_sort_key)
+SELECT * FROM UNNEST(
+    $1::uuid[],
+    $2::text[],
+    $3::bigint[],
+    $4::text[],
+    $5::text[],
+    $6::text[],
+    $7::text[],
+    $8::text[]
+)
+RETURNING id, is_ready, uploaded_at",
+            &file_ids,
+            &Vec::from_iter(files.iter().map(|file| file.name.clone())),
+            &Vec::from_iter(files.iter().map(|file| file.size as i64)),
+            &Vec::from_iter(files.iter().map(|file| file.mime_type.clone())),
+            &Vec::from_iter(files.iter().map(|file| file.checksum.clone())) as &[Option<String>],
+            &Vec::from_iter(files.iter().map(|file| file.storage.bucket.clone())),
+            &Vec::from_iter(files.iter().map(|file| file.storage.region.clone())),
+            &Vec::from_iter(files.iter().map(|file| name_sort_key(&file.name))),
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if !tags.is_empty() {
+            sqlx::query!(
+                "
+INSERT INTO file_tags (file_id, tag)
+SELECT * FROM UNNEST($1::uuid[], $2::text[])",
+                &tag_file_ids,
+                &tags
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| {
+                RepositoryError::from_sqlx_err(err, |_| {
+                    files
+                        .iter()
+                        .find_map(|file| duplicate_tag([], &file.tags))
+                        .unwrap_or_else(unknown_value)
+                })
+            })?;
+
+            check_unique_file_names(&mut tx, &file_ids).await?;
+        }
+
+        tx.commit().await?;
+
+        // the rows are returned in no particular order
+        let mut after_creations = HashMap::<_, _>::from_iter(
+            after_creations
+                .into_iter()
+                .map(|after_creation| (after_creation.id, after_creation)),
+        );
+
+        Ok(files
+            .into_iter()
+            .zip(file_ids)
+            .filter_map(|(file, file_id)| {
+                let after_creation = after_creations.remove(&file_id)?;
+                Some((file, after_creation).into())
+            })
+            .collect())
+    }
+
+    /// Creates the next version of the file as a new unready file, with the tags of the file and
+    /// its name unless another is given. Returns `None` if there is no file with the id.
+    #[tracing::instrument(level = "debug", skip_all, fields(file_id = %file_id))]
+    pub async fn create_version(
+        &self,
+        file_id: Uuid,
+        version: entities::FileEntityForVersion,
+    ) -> Result<Option<entities::FileVersionEntity>, RepositoryError> {
+        let mut tx = self.db_pool.begin().await?;
+
+        lock_version_chains(&mut tx).await?;
+
+        let source = sqlx::query_as!(
+            row_types::RawFileVersionSource,
+            "
+SELECT COALESCE(version_of, id) AS \"chain_id!\", name
+FROM files
+WHERE id = $1",
+            file_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let source = match source {
+            Some(source) => source,
+            None => {
+                return Ok(None);
+            }
+        };
+
+        let name = version.name.unwrap_or(source.name);
+        let after_creation = sqlx::query_as!(
+            row_types::RawFileVersionAfterCreation,
+            "
+INSERT INTO files (
+    name,
+    size,
+    mime_type,
+    checksum,
+    bucket,
+    region,
+    version_of,
+    version_number,
+    name_sort_key
+)
+SELECT $1, $2, $3, $4, $5, $6, $7, MAX(version_number) + 1, $8
+FROM files
+WHERE COALESCE(version_of, id) = $7
+RETURNING id, is_ready, uploaded_at, version_number",
+            &name,
+            version.size as i64,
+            &version.mime_type,
+            version.checksum.as_deref(),
+            &version.storage.bucket,
+            &version.storage.region,
+            source.chain_id,
+            name_sort_key(&name),
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|err| RepositoryError::from_sqlx_err(err, |_| source.chain_id.to_string()))?;
+
+        let tags = sqlx::query_scalar!(
+            "
+INSERT INTO file_tags (file_id, tag)
+SELECT $1, tag
+FROM file_tags
+WHERE file_id = $2
+RETURNING tag",
+            after_creation.id,
+            file_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if !tags.is_empty() {
+            check_unique_file_names(&mut tx, &[after_creation.id]).await?;
+        }
+
+        tx.commit().await?;
+
+        let mut tags = tags;
+        tags.sort_unstable();
+
+        Ok(Some(entities::FileVersionEntity {
+            version_number: after_creation.version_number,
+            file: entities::FileEntity {
+                id: after_creation.id,
+                name,
+                size: version.size,
+                mime_type: version.mime_type,
+                checksum: version.checksum,
+                is_ready: after_creation.is_ready,
+                protected: false,
+                superseded_by: None,
+                uploaded_at: after_creation.uploaded_at.and_utc(),
+                tags,
+                storage: Some(version.storage),
+            },
+        }))
+    }
+
+    /// Lists every version of the file, ready or not, by version number. Returns `None` if there
+    /// is no file with the id.
+    #[tracing::instrument(level = "debug", skip_all, fields(file_id = %file_id))]
+    pub async fn find_versions(
+        &self,
+        file_id: Uuid,
+    ) -> Result<Option<Vec<entities::FileVersionEntity>>, RepositoryError> {
+        let versions = sqlx::query_as!(
+            row_types::RawFileVersion,
+            "
+SELECT
+    file.id,
+    file.name,
+    file.size,
+    file.mime_type,
+    file.checksum,
+    file.is_ready,
+    file.protected,
+    file.superseded_by,
+    file.uploaded_at,
+    file.bucket,
+    file.region,
+    file.version_number,
+    COALESCE(
+        ARRAY_AGG(file_tags.tag ORDER BY file_tags.tag) FILTER (WHERE file_tags.tag IS NOT NULL),
+        '{}'
+    ) AS \"tags!\"
+FROM files file
+LEFT JOIN file_tags ON file.id = file_tags.file_id
+WHERE COALESCE(file.version_of, file.id) = (
+    SELECT COALESCE(version_of, id)
+    FROM files
+    WHERE id = $1
+)
+GROUP BY file.id
+ORDER BY file.version_number ASC",
+            file_id
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        // the chain holds at least the file itself
+        if versions.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(versions.into_iter().map(|raw| raw.into()).collect()))
+    }
+
+    /// Returns the updated file along with whether anything about it changed, or `None` if there
+    /// is no file with the id or if the update changes the size or the MIME type of a ready file,
+    /// which must keep matching the uploaded object. Nothing is updated in the latter cases.
+    #[tracing::instrument(level = "debug", skip_all)]
     pub async fn update_one(
         &self,
         file: entities::FileEntityForUpdate,
         tags_for_creation: Vec<String>,
         tags_for_deletion: Vec<String>,
-    ) -> Result<Option<entities::FileEntity>, RepositoryError> {
+    ) -> Result<Option<(entities::FileEntity, bool)>, RepositoryError> {
         let mut tx = self.db_pool.begin().await?;
 
+        if file.superseded_by.is_some() {
+            lock_version_chains(&mut tx).await?;
+        }
+
         let file_id = file.id;
+        let before_update = sqlx::query_as!(
+            row_types::RawFileBeforeUpdate,
+            "
+SELECT name, size, mime_type, protected, superseded_by
+FROM files
+WHERE id = $1
+FOR UPDATE",
+            file_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let before_update = match before_update {
+            Some(before_update) => before_update,
+            None => {
+                return Ok(None);
+            }
+        };
+
+        if let Some(Some(superseded_by)) = file.superseded_by {
+            check_supersession(&mut tx, file_id, superseded_by).await?;
+        }
+
         let file = sqlx::query_as!(
             row_types::RawFileAfterUpdate,
             "
@@ -204,13 +735,20 @@ UPDATE files
 SET
     name = COALESCE($1, name),
     size = COALESCE($2, size),
-    mime_type = COALESCE($3, mime_type)
-WHERE id = $4
-RETURNING name, size, mime_type, uploaded_at",
-            file.name,
+    mime_type = COALESCE($3, mime_type),
+    protected = COALESCE($5, protected),
+    superseded_by = CASE WHEN $6 THEN $7 ELSE superseded_by END,
+    name_sort_key = COALESCE($8, name_sort_key)
+WHERE id = $4 AND (NOT is_ready OR ($2::BIGINT IS NULL AND $3::TEXT IS NULL))
+RETURNING name, size, mime_type, checksum, is_ready, protected, superseded_by, uploaded_at, bucket, region",
+            file.name.as_deref(),
             file.size.map(|size| size as i64),
             file.mime_type,
             file_id,
+            file.protected,
+            file.superseded_by.is_some(),
+            file.superseded_by.flatten(),
+            file.name.as_deref().map(name_sort_key),
         )
         .fetch_optional(&mut *tx)
         .await?;
@@ -232,6 +770,7 @@ ORDER BY tag",
         )
         .fetch_all(&mut *tx)
         .await?;
+        let tag_count_before_update = tags.len();
 
         if !tags_for_deletion.is_empty() {
             sqlx::query!(
@@ -248,7 +787,10 @@ WHERE file_id = $1 AND tag = ANY($2::text[])
             tags.retain(|tag| !tags_for_deletion.contains(&tag.tag));
         }
 
+        let tag_count_before_creation = tags.len();
+
         if !tags_for_creation.is_empty() {
+            check_tag_count(tags.iter().map(|tag| tag.tag.as_str()), &tags_for_creation)?;
             sqlx::query!(
                 "
 INSERT INTO file_tags (file_id, tag)
@@ -258,7 +800,13 @@ SELECT $1, UNNEST($2::text[])
                 &tags_for_creation
             )
             .execute(&mut *tx)
-            .await?;
+            .await
+            .map_err(|err| {
+                RepositoryError::from_sqlx_err(err, |_| {
+                    duplicate_tag(tags.iter().map(|tag| tag.tag.as_str()), &tags_for_creation)
+                        .unwrap_or_else(unknown_value)
+                })
+            })?;
 
             tags.extend(
                 tags_for_creation
@@ -267,45 +815,260 @@ SELECT $1, UNNEST($2::text[])
             );
         }
 
+        // tags are only ever removed or added, so a change shows in the count
+        let tags_changed = tag_count_before_update != tag_count_before_creation
+            || tag_count_before_creation != tags.len();
+
+        // a new name or new tags may collide within a collection the file is in now
+        if before_update.name != file.name || tags_changed {
+            check_unique_file_names(&mut tx, &[file_id]).await?;
+        }
+
         tx.commit().await?;
         tags.sort_unstable_by(|a, b| a.tag.cmp(&b.tag));
 
-        Ok(Some(entities::FileEntity {
-            id: file_id,
-            name: file.name,
-            size: file.size as usize,
-            mime_type: file.mime_type,
-            uploaded_at: file.uploaded_at.and_utc(),
-            tags: tags.into_iter().map(|raw| raw.tag).collect(),
-        }))
+        let changed = before_update.name != file.name
+            || before_update.size != file.size
+            || before_update.mime_type != file.mime_type
+            || before_update.protected != file.protected
+            || before_update.superseded_by != file.superseded_by
+            || tags_changed;
+
+        Ok(Some((
+            entities::FileEntity {
+                id: file_id,
+                name: file.name,
+                size: file.size as usize,
+                mime_type: file.mime_type,
+                checksum: file.checksum,
+                is_ready: file.is_ready,
+                protected: file.protected,
+                superseded_by: file.superseded_by,
+                uploaded_at: file.uploaded_at.and_utc(),
+                tags: tags.into_iter().map(|raw| raw.tag).collect(),
+                storage: row_types::storage(file.bucket, file.region),
+            },
+            changed,
+        )))
     }
 
-    pub async fn update_one_as_ready(
+    /// Adds `add` to and removes `remove` from every one of the files in a single transaction,
+    /// returning what changed on each of the files that exist, in id order. Tags a file has
+    /// already are not added again, and tags it lacks are not removed. Fails with
+    /// [`RepositoryError::TooManyTags`] if a file would end up with more than [`MAX_TAGS`] tags,
+    /// or with [`RepositoryError::NameConflict`] if the added tags bring two files of the same
+    /// name into a collection that requires unique names.
+    #[tracing::instrument(level = "debug", skip_all, fields(count = file_ids.len()))]
+    pub async fn update_tags_many(
         &self,
-        file_id: Uuid,
-    ) -> Result<Option<entities::FileEntity>, RepositoryError> {
-        let file = sqlx::query_as!(
-            row_types::RawFileAfterUpdate,
+        file_ids: &[Uuid],
+        add: &[String],
+        remove: &[String],
+    ) -> Result<Vec<entities::FileTagChangeEntity>, RepositoryError> {
+        let mut tx = self.db_pool.begin().await?;
+
+        // the same rows `update_one` locks, so that the tag counts below are not raced
+        let file_ids = sqlx::query_scalar!(
             "
-UPDATE files
-SET is_ready = TRUE
-WHERE id = $1
-RETURNING
-    name,
-    size,
-    mime_type,
-    uploaded_at",
-            file_id
+SELECT id
+FROM files
+WHERE id = ANY($1::uuid[])
+ORDER BY id
+FOR UPDATE",
+            file_ids
         )
-        .fetch_optional(&self.db_pool)
+        .fetch_all(&mut *tx)
         .await?;
-        let file = match file {
+
+        let removed = sqlx::query_as!(
+            row_types::RawFileTagWithFileId,
+            "
+DELETE FROM file_tags
+WHERE file_id = ANY($1::uuid[]) AND tag = ANY($2::text[])
+RETURNING file_id, tag",
+            &file_ids,
+            remove
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let added = sqlx::query_as!(
+            row_types::RawFileTagWithFileId,
+            "
+INSERT INTO file_tags (file_id, tag)
+SELECT file.id, tag.tag
+FROM UNNEST($1::uuid[]) AS file (id)
+CROSS JOIN UNNEST($2::text[]) AS tag (tag)
+ON CONFLICT DO NOTHING
+RETURNING file_id, tag",
+            &file_ids,
+            add
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if !added.is_empty() {
+            let attempted = sqlx::query_scalar!(
+                "
+SELECT MAX(tag_count) AS \"tag_count!\"
+FROM (
+    SELECT COUNT(*) AS tag_count
+    FROM file_tags
+    WHERE file_id = ANY($1::uuid[])
+    GROUP BY file_id
+) AS counts",
+                &file_ids
+            )
+            .fetch_one(&mut *tx)
+            .await? as usize;
+
+            if MAX_TAGS < attempted {
+                return Err(RepositoryError::TooManyTags {
+                    limit: MAX_TAGS,
+                    attempted,
+                });
+            }
+
+            let mut tagged_file_ids = Vec::from_iter(added.iter().map(|tag| tag.file_id));
+            tagged_file_ids.sort_unstable();
+            tagged_file_ids.dedup();
+
+            // removed tags only ever take a file out of a collection
+            check_unique_file_names(&mut tx, &tagged_file_ids).await?;
+        }
+
+        tx.commit().await?;
+
+        let mut changes = HashMap::<_, _>::from_iter(file_ids.iter().map(|&file_id| {
+            (
+                file_id,
+                entities::FileTagChangeEntity {
+                    file_id,
+                    added: Vec::new(),
+                    removed: Vec::new(),
+                },
+            )
+        }));
+
+        for tag in added {
+            if let Some(change) = changes.get_mut(&tag.file_id) {
+                change.added.push(tag.tag);
+            }
+        }
+
+        for tag in removed {
+            if let Some(change) = changes.get_mut(&tag.file_id) {
+                change.removed.push(tag.tag);
+            }
+        }
+
+        Ok(file_ids
+            .into_iter()
+            .filter_map(|file_id| changes.remove(&file_id))
+            .map(|mut change| {
+                change.added.sort_unstable();
+                change.removed.sort_unstable();
+                change
+            })
+            .collect())
+    }
+
+    /// Marks the file as ready and points the earlier versions of it to the latest ready version,
+    /// returning the file along with the other files that are superseded from now on.
+    #[tracing::instrument(level = "debug", skip_all, fields(file_id = %file_id))]
+    pub async fn update_one_as_ready(
+        &self,
+        file_id: Uuid,
+        upload_id: &str,
+    ) -> Result<Option<(entities::FileEntity, Vec<Uuid>)>, RepositoryError> {
+        let mut tx = self.db_pool.begin().await?;
+
+        lock_version_chains(&mut tx).await?;
+
+        let file = sqlx::query_as!(
+            row_types::RawFileAfterUpdate,
+            "
+UPDATE files
+SET is_ready = TRUE, upload_id = $2, active_upload_id = NULL
+WHERE id = $1
+RETURNING
+    name,
+    size,
+    mime_type,
+    checksum,
+    is_ready,
+    protected,
+    superseded_by,
+    uploaded_at,
+    bucket,
+    region",
+            file_id,
+            upload_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let mut file = match file {
             Some(file) => file,
             None => {
                 return Ok(None);
             }
         };
 
+        // the latest version may have been pointed into its own chain before it was ready
+        sqlx::query!(
+            "
+WITH chain AS (
+    SELECT COALESCE(version_of, id) AS id
+    FROM files
+    WHERE id = $1
+), latest AS (
+    SELECT files.id
+    FROM files, chain
+    WHERE COALESCE(files.version_of, files.id) = chain.id AND files.is_ready = TRUE
+    ORDER BY files.version_number DESC
+    LIMIT 1
+)
+UPDATE files
+SET superseded_by = NULL
+FROM latest, chain
+WHERE
+    files.id = latest.id
+    AND files.superseded_by IN (
+        SELECT id
+        FROM files
+        WHERE COALESCE(version_of, id) = chain.id
+    )",
+            file_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let superseded = sqlx::query_as!(
+            row_types::RawSupersededFile,
+            "
+WITH chain AS (
+    SELECT COALESCE(version_of, id) AS id
+    FROM files
+    WHERE id = $1
+), latest AS (
+    SELECT files.id, files.version_number
+    FROM files, chain
+    WHERE COALESCE(files.version_of, files.id) = chain.id AND files.is_ready = TRUE
+    ORDER BY files.version_number DESC
+    LIMIT 1
+)
+UPDATE files
+SET superseded_by = latest.id
+FROM latest, chain
+WHERE
+    COALESCE(files.version_of, files.id) = chain.id
+    AND files.version_number < latest.version_number
+    AND files.superseded_by IS DISTINCT FROM latest.id
+RETURNING files.id, latest.id AS superseded_by",
+            file_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
         let tags = sqlx::query_as!(
             row_types::RawFileTag,
             "
@@ -315,136 +1078,1319 @@ WHERE file_id = $1
 ORDER BY tag",
             file_id
         )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let mut superseded_file_ids = Vec::with_capacity(superseded.len());
+
+        for superseded in superseded {
+            // an earlier version completing after a later one is superseded right away
+            if superseded.id == file_id {
+                file.superseded_by = Some(superseded.superseded_by);
+            } else {
+                superseded_file_ids.push(superseded.id);
+            }
+        }
+
+        Ok(Some((
+            entities::FileEntity {
+                id: file_id,
+                name: file.name,
+                size: file.size as usize,
+                mime_type: file.mime_type,
+                checksum: file.checksum,
+                is_ready: file.is_ready,
+                protected: file.protected,
+                superseded_by: file.superseded_by,
+                uploaded_at: file.uploaded_at.and_utc(),
+                tags: tags.into_iter().map(|raw| raw.tag).collect(),
+                storage: row_types::storage(file.bucket, file.region),
+            },
+            superseded_file_ids,
+        )))
+    }
+
+    /// Lists the ids and readiness of the files stored in `bucket` whose id is in
+    /// `(after, until]`, in id order. Either bound may be left open.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn list_states_between(
+        &self,
+        after: Option<Uuid>,
+        until: Option<Uuid>,
+        bucket: &str,
+    ) -> Result<Vec<entities::FileStateEntity>, RepositoryError> {
+        let files = sqlx::query_as!(
+            row_types::RawFileState,
+            "
+SELECT id, is_ready
+FROM files
+WHERE
+    ($1::uuid IS NULL OR $1 < id)
+    AND ($2::uuid IS NULL OR id <= $2)
+    AND (bucket IS NULL OR bucket = $3)
+ORDER BY id ASC",
+            after,
+            until,
+            bucket
+        )
+        .fetch_all(&self.read_db_pool)
+        .await?;
+
+        Ok(files.into_iter().map(|raw| raw.into()).collect())
+    }
+
+    /// Lists the ready files without a checksum, with their storage, in id order, after `after` if
+    /// given.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn list_ready_without_checksum(
+        &self,
+        after: Option<Uuid>,
+        limit: usize,
+    ) -> Result<Vec<(Uuid, Option<FileStorage>)>, RepositoryError> {
+        let files = sqlx::query_as!(
+            row_types::RawFileStorage,
+            "
+SELECT id, bucket, region
+FROM files
+WHERE is_ready = TRUE AND checksum IS NULL AND ($1::uuid IS NULL OR $1 < id)
+ORDER BY id ASC
+LIMIT $2",
+            after,
+            limit as i64
+        )
+        .fetch_all(&self.read_db_pool)
+        .await?;
+
+        Ok(files
+            .into_iter()
+            .map(|raw| (raw.id, row_types::storage(raw.bucket, raw.region)))
+            .collect())
+    }
+
+    /// Sets the checksum of a file that has none. Returns `false` if the file is gone or has a
+    /// checksum already.
+    #[tracing::instrument(level = "debug", skip_all, fields(file_id = %file_id))]
+    pub async fn set_checksum(
+        &self,
+        file_id: Uuid,
+        checksum: &str,
+    ) -> Result<bool, RepositoryError> {
+        let result = sqlx::query!(
+            "
+UPDATE files
+SET checksum = $2
+WHERE id = $1 AND checksum IS NULL",
+            file_id,
+            checksum
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    /// Returns the storage of each of the given files that has one.
+    #[tracing::instrument(level = "debug", skip_all, fields(count = file_ids.len()))]
+    pub async fn find_storages(
+        &self,
+        file_ids: &[Uuid],
+    ) -> Result<Vec<(Uuid, FileStorage)>, RepositoryError> {
+        let files = sqlx::query_as!(
+            row_types::RawFileStorage,
+            "
+SELECT id, bucket, region
+FROM files
+WHERE id = ANY($1::uuid[])",
+            file_ids
+        )
         .fetch_all(&self.db_pool)
         .await?;
 
-        Ok(Some(entities::FileEntity {
-            id: file_id,
-            name: file.name,
-            size: file.size as usize,
-            mime_type: file.mime_type,
-            uploaded_at: file.uploaded_at.and_utc(),
-            tags: tags.into_iter().map(|raw| raw.tag).collect(),
-        }))
+        Ok(files
+            .into_iter()
+            .filter_map(|raw| Some((raw.id, row_types::storage(raw.bucket, raw.region)?)))
+            .collect())
+    }
+
+    /// Lists the collections each of the given files belongs to, those whose every tag the file
+    /// has, ordered by name. Only ready files belong to collections.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn find_collections(
+        &self,
+        file_ids: &[Uuid],
+    ) -> Result<Vec<(Uuid, FileCollection)>, RepositoryError> {
+        let collections = sqlx::query_as!(
+            row_types::RawFileCollection,
+            "
+SELECT
+    file_tags.file_id,
+    collections.id AS collection_id,
+    collections.name
+FROM file_tags
+JOIN files ON files.id = file_tags.file_id AND files.is_ready = TRUE
+JOIN collection_tags ON collection_tags.tag = file_tags.tag
+JOIN collections ON collections.id = collection_tags.collection_id
+WHERE file_tags.file_id = ANY($1::uuid[])
+GROUP BY file_tags.file_id, collections.id, collections.name
+HAVING COUNT(DISTINCT file_tags.tag) = (
+    SELECT COUNT(*)
+    FROM collection_tags all_tags
+    WHERE all_tags.collection_id = collections.id
+)
+ORDER BY LOWER(collections.name) ASC, collections.id ASC",
+            file_ids
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(collections
+            .into_iter()
+            .map(|raw| {
+                (
+                    raw.file_id,
+                    FileCollection {
+                        id: raw.collection_id,
+                        name: raw.name,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// Counts the files `tag` would be renamed or merged on, and whether any file has `new_name`
+    /// already, along with the names of up to `sample_limit` of the files, by name.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn preview_tag_rename(
+        &self,
+        tag: &str,
+        new_name: &str,
+        sample_limit: usize,
+    ) -> Result<(TagRenameImpact, bool), RepositoryError> {
+        let counts = sqlx::query!(
+            r#"
+SELECT
+    COUNT(*) FILTER (WHERE merged.file_id IS NULL) AS "renamed_count!",
+    COUNT(merged.file_id) AS "merged_count!",
+    EXISTS (SELECT 1 FROM file_tags WHERE tag = $2) AS "target_exists!"
+FROM file_tags
+LEFT JOIN file_tags merged ON merged.file_id = file_tags.file_id AND merged.tag = $2
+WHERE file_tags.tag = $1"#,
+            tag,
+            new_name
+        )
+        .fetch_one(&self.read_db_pool)
+        .await?;
+        let sample_names = sqlx::query_scalar!(
+            "
+SELECT files.name
+FROM file_tags
+JOIN files ON files.id = file_tags.file_id
+WHERE file_tags.tag = $1
+ORDER BY files.name ASC, files.id ASC
+LIMIT $2",
+            tag,
+            sample_limit as i64
+        )
+        .fetch_all(&self.read_db_pool)
+        .await?;
+
+        Ok((
+            TagRenameImpact {
+                renamed_count: counts.renamed_count as u64,
+                merged_count: counts.merged_count as u64,
+                sample_names,
+            },
+            counts.target_exists,
+        ))
+    }
+
+    /// Renames `tag` to `new_name` on up to `limit` files, merging it into `new_name` on files
+    /// that have both, and returns their ids. No id is returned once no file has `tag`.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn rename_tag_batch(
+        &self,
+        tag: &str,
+        new_name: &str,
+        limit: usize,
+    ) -> Result<Vec<Uuid>, RepositoryError> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let file_ids = sqlx::query_scalar!(
+            "
+SELECT file_id
+FROM file_tags
+WHERE tag = $1
+ORDER BY file_id ASC
+LIMIT $2
+FOR UPDATE",
+            tag,
+            limit as i64
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "
+INSERT INTO file_tags (file_id, tag)
+SELECT file_id, $2
+FROM UNNEST($1::uuid[]) AS file_id
+ON CONFLICT DO NOTHING",
+            &file_ids,
+            new_name
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "
+DELETE FROM file_tags
+WHERE file_id = ANY($1::uuid[]) AND tag = $2",
+            &file_ids,
+            tag
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(file_ids)
+    }
+
+    /// Lists the ready files tagged with every one of `tags`, in id order, after `after` if given.
+    /// No tags match no files, like a collection without tags.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn list_with_all_tags(
+        &self,
+        tags: &[String],
+        after: Option<Uuid>,
+        limit: usize,
+    ) -> Result<Vec<entities::FileEntity>, RepositoryError> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let files = sqlx::query_as!(
+            row_types::RawFile,
+            "
+SELECT
+    id,
+    name,
+    size,
+    mime_type,
+    checksum,
+    is_ready,
+    protected,
+    superseded_by,
+    uploaded_at,
+    bucket,
+    region
+FROM files
+WHERE id IN (
+    SELECT file_id
+    FROM file_tags
+    WHERE tag = ANY($1::text[])
+    GROUP BY file_id
+    HAVING COUNT(DISTINCT tag) = CARDINALITY($1::text[])
+) AND is_ready = TRUE AND ($2::uuid IS NULL OR $2 < id)
+ORDER BY id ASC
+LIMIT $3",
+            tags,
+            after,
+            limit as i64
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let tags = sqlx::query_as!(
+            row_types::RawFileTagWithFileId,
+            "
+SELECT file_id, tag
+FROM file_tags
+WHERE file_id = ANY($1::uuid[])",
+            &files.iter().map(|file| file.id).collect::<Vec<_>>()
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let mut files_map =
+            HashMap::<_, _>::from_iter(files.iter().map(|file| (file.id, Vec::with_capacity(10))));
+
+        for tag in tags {
+            files_map
+                .entry(tag.file_id)
+                .or_default()
+                .push(row_types::RawFileTag { tag: tag.tag });
+        }
+
+        Ok(files
+            .into_iter()
+            .map(|file| {
+                let mut tags = files_map.remove(&file.id).unwrap_or_default();
+                tags.sort_unstable_by(|a, b| a.tag.cmp(&b.tag));
+
+                (file, tags).into()
+            })
+            .collect())
     }
 
-    pub async fn delete_one(&self, file_id: Uuid) -> Result<(), RepositoryError> {
-        let mut tx = self.db_pool.begin().await?;
+    /// Sets the storage of at most `limit` of the files created before files recorded theirs,
+    /// returning how many were updated.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn backfill_storage(
+        &self,
+        storage: &FileStorage,
+        limit: usize,
+    ) -> Result<u64, RepositoryError> {
+        let result = sqlx::query!(
+            "
+UPDATE files
+SET bucket = $1, region = $2
+WHERE id IN (
+    SELECT id
+    FROM files
+    WHERE bucket IS NULL OR region IS NULL
+    LIMIT $3
+)",
+            &storage.bucket,
+            &storage.region,
+            limit as i64
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Marks the given files as not ready, returning the number of updated files.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn update_many_as_unready(&self, file_ids: &[Uuid]) -> Result<u64, RepositoryError> {
+        let result = sqlx::query!(
+            "
+UPDATE files
+SET is_ready = FALSE
+WHERE id = ANY($1::uuid[])",
+            file_ids
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Returns those of the given files that exist. Reads from the primary, so that a file created
+    /// just now is not taken for a deleted one.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn find_existing_ids(&self, file_ids: &[Uuid]) -> Result<Vec<Uuid>, RepositoryError> {
+        let file_ids = sqlx::query_scalar!(
+            "
+SELECT id
+FROM files
+WHERE id = ANY($1::uuid[])",
+            file_ids
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(file_ids)
+    }
+
+    /// Returns those of the given files that are protected from deletion.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn find_protected_ids(
+        &self,
+        file_ids: &[Uuid],
+    ) -> Result<Vec<Uuid>, RepositoryError> {
+        let file_ids = sqlx::query_scalar!(
+            "
+SELECT id
+FROM files
+WHERE id = ANY($1::uuid[]) AND protected = TRUE",
+            file_ids
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(file_ids)
+    }
+
+    /// Returns the files superseded by any of the given files, other than the given files
+    /// themselves. Deleting the given files makes them current again.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn find_superseded_ids(
+        &self,
+        file_ids: &[Uuid],
+    ) -> Result<Vec<Uuid>, RepositoryError> {
+        let file_ids = sqlx::query_scalar!(
+            "
+SELECT id
+FROM files
+WHERE superseded_by = ANY($1::uuid[]) AND id <> ALL($1::uuid[])",
+            file_ids
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(file_ids)
+    }
+
+    /// Deletes the file and its tags, unless it is protected.
+    #[tracing::instrument(level = "debug", skip_all, fields(file_id = %file_id))]
+    pub async fn delete_one(&self, file_id: Uuid) -> Result<(), RepositoryError> {
+        self.delete_many(&[file_id]).await?;
+
+        Ok(())
+    }
+
+    /// Deletes the given files and their tags, skipping protected files, and returns the number
+    /// of deleted files.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn delete_many(&self, file_ids: &[Uuid]) -> Result<u64, RepositoryError> {
+        let mut tx = self.db_pool.begin().await?;
+
+        // locked, so that the files cannot be protected before they are gone
+        let file_ids = sqlx::query_scalar!(
+            "
+SELECT id
+FROM files
+WHERE id = ANY($1::uuid[]) AND protected = FALSE
+FOR UPDATE",
+            file_ids
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "
+DELETE FROM file_tags
+WHERE file_id = ANY($1::uuid[])",
+            &file_ids
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query!(
+            "
+DELETE FROM files
+WHERE id = ANY($1::uuid[])",
+            &file_ids
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes the files never marked as ready, returning their ids, storages and active uploads,
+    /// along with the ids of the protected ones that were kept.
+    #[allow(clippy::type_complexity)]
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn delete_unready_many(
+        &self,
+        before_uploaded_at: DateTime<Utc>,
+    ) -> Result<(Vec<(Uuid, Option<FileStorage>, Option<String>)>, Vec<Uuid>), RepositoryError>
+    {
+        let mut tx = self.db_pool.begin().await?;
+
+        let (protected_files, file_ids): (Vec<_>, Vec<_>) = sqlx::query_as!(
+            row_types::RawUnreadyFile,
+            "
+SELECT id, bucket, region, protected, active_upload_id
+FROM files
+WHERE uploaded_at < $1 AND is_ready = FALSE
+FOR UPDATE",
+            before_uploaded_at.naive_utc()
+        )
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .partition(|file| file.protected);
+
+        sqlx::query!(
+            "
+DELETE FROM file_tags
+WHERE file_id = ANY($1::uuid[])",
+            &file_ids
+                .iter()
+                .map(|file_id| file_id.id)
+                .collect::<Vec<_>>()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "
+DELETE FROM files
+WHERE id = ANY($1::uuid[])",
+            &file_ids
+                .iter()
+                .map(|file_id| file_id.id)
+                .collect::<Vec<_>>()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok((
+            file_ids
+                .into_iter()
+                .map(|raw| {
+                    (
+                        raw.id,
+                        row_types::storage(raw.bucket, raw.region),
+                        raw.active_upload_id,
+                    )
+                })
+                .collect(),
+            protected_files.into_iter().map(|raw| raw.id).collect(),
+        ))
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(file_id = %file_id))]
+    pub async fn create_upload_record(
+        &self,
+        file_id: Uuid,
+        upload_id: &str,
+        part_count: u32,
+        total_bytes: usize,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query!(
+            "
+INSERT INTO upload_records (file_id, upload_id, part_count, total_bytes)
+VALUES ($1, $2, $3, $4)",
+            file_id,
+            upload_id,
+            part_count as i32,
+            total_bytes as i64
+        )
+        .execute(&self.db_pool)
+        .await
+        .map_err(|err| RepositoryError::from_sqlx_err(err, |_| upload_id.to_owned()))?;
+
+        Ok(())
+    }
+
+    /// Starts the upload as the active upload of the file, returning whether it did. The file
+    /// keeps the upload it has active already, if any, and no record is created then.
+    #[tracing::instrument(level = "debug", skip_all, fields(file_id = %file_id))]
+    pub async fn create_active_upload_record(
+        &self,
+        file_id: Uuid,
+        upload_id: &str,
+        part_count: u32,
+        total_bytes: usize,
+    ) -> Result<bool, RepositoryError> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+UPDATE files
+SET active_upload_id = $2
+WHERE id = $1 AND active_upload_id IS NULL",
+            file_id,
+            upload_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        sqlx::query!(
+            "
+INSERT INTO upload_records (file_id, upload_id, part_count, total_bytes)
+VALUES ($1, $2, $3, $4)",
+            file_id,
+            upload_id,
+            part_count as i32,
+            total_bytes as i64
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| RepositoryError::from_sqlx_err(err, |_| upload_id.to_owned()))?;
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Records the outcome of a pending upload, returning whether it was pending. An upload that
+    /// has an outcome already keeps it, and a file stops having it as its active upload.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn finish_upload_record(
+        &self,
+        upload_id: &str,
+        outcome: UploadOutcome,
+    ) -> Result<bool, RepositoryError> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let result = sqlx::query!(
+            "
+UPDATE upload_records
+SET outcome = $2, completed_at = CURRENT_TIMESTAMP
+WHERE upload_id = $1 AND outcome = 'pending'",
+            upload_id,
+            outcome as UploadOutcome
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "
+UPDATE files
+SET active_upload_id = NULL
+WHERE
+    id = (SELECT file_id FROM upload_records WHERE upload_id = $1)
+    AND active_upload_id = $1",
+            upload_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    /// Lists the uploads of the file, the latest first.
+    #[tracing::instrument(level = "debug", skip_all, fields(file_id = %file_id))]
+    pub async fn list_upload_records(
+        &self,
+        file_id: Uuid,
+    ) -> Result<Vec<entities::UploadRecordEntity>, RepositoryError> {
+        let records = sqlx::query_as!(
+            row_types::RawUploadRecord,
+            "
+SELECT
+    file_id,
+    upload_id,
+    part_count,
+    total_bytes,
+    outcome AS \"outcome: UploadOutcome\",
+    started_at,
+    completed_at
+FROM upload_records
+WHERE file_id = $1
+ORDER BY started_at DESC, id ASC",
+            file_id
+        )
+        .fetch_all(&self.read_db_pool)
+        .await?;
+
+        Ok(records.into_iter().map(|raw| raw.into()).collect())
+    }
+
+    /// Lists the uploads still pending that were started before `before_started_at`, with the
+    /// storage of their file if it still exists.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn list_stale_upload_records(
+        &self,
+        before_started_at: DateTime<Utc>,
+    ) -> Result<Vec<entities::StaleUploadEntity>, RepositoryError> {
+        let records = sqlx::query_as!(
+            row_types::RawStaleUpload,
+            "
+SELECT
+    upload_records.file_id,
+    upload_records.upload_id,
+    files.bucket AS \"bucket?\",
+    files.region AS \"region?\"
+FROM upload_records
+LEFT JOIN files ON files.id = upload_records.file_id
+WHERE upload_records.outcome = 'pending' AND upload_records.started_at < $1
+ORDER BY upload_records.started_at ASC",
+            before_started_at.naive_utc()
+        )
+        .fetch_all(&self.read_db_pool)
+        .await?;
+
+        Ok(records.into_iter().map(|raw| raw.into()).collect())
+    }
+
+    /// Deletes the records of the uploads that finished before `before_completed_at`, returning
+    /// how many were deleted. They are deleted [`PRUNE_CHUNK_SIZE`] at a time.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn delete_finished_upload_records(
+        &self,
+        before_completed_at: DateTime<Utc>,
+    ) -> Result<u64, RepositoryError> {
+        let mut deleted_count = 0;
+
+        loop {
+            let result = sqlx::query!(
+                "
+DELETE FROM upload_records
+WHERE id IN (
+    SELECT id
+    FROM upload_records
+    WHERE completed_at < $1
+    LIMIT $2
+)",
+                before_completed_at.naive_utc(),
+                PRUNE_CHUNK_SIZE
+            )
+            .execute(&self.db_pool)
+            .await?;
+
+            deleted_count += result.rows_affected();
+
+            if result.rows_affected() < PRUNE_CHUNK_SIZE as u64 {
+                return Ok(deleted_count);
+            }
+        }
+    }
+
+    /// Follows the versions of the file to the latest ready one, which is the file itself if no
+    /// ready file supersedes it. Returns `None` if there is no file with the id.
+    #[tracing::instrument(level = "debug", skip_all, fields(file_id = %file_id))]
+    pub async fn find_latest_version_id(
+        &self,
+        file_id: Uuid,
+    ) -> Result<Option<Uuid>, RepositoryError> {
+        let latest_id = sqlx::query_scalar!(
+            "
+WITH RECURSIVE chain (id, superseded_by, depth) AS (
+    SELECT id, superseded_by, 1
+    FROM files
+    WHERE id = $1
+    UNION ALL
+    SELECT files.id, files.superseded_by, chain.depth + 1
+    FROM chain
+    JOIN files ON files.id = chain.superseded_by AND files.is_ready = TRUE
+    WHERE chain.depth < $2
+)
+SELECT id AS \"id!\"
+FROM chain
+ORDER BY depth DESC
+LIMIT 1",
+            file_id,
+            MAX_VERSION_CHAIN_LENGTH
+        )
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(latest_id)
+    }
+
+    /// Sums up the sizes of the ready files, and of those of the `collection_limit` collections
+    /// holding the most bytes if given.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn cost_report(
+        &self,
+        collection_limit: Option<usize>,
+    ) -> Result<entities::FileUsageEntity, RepositoryError> {
+        let mut tx = self.read_db_pool.begin().await?;
+
+        let total = sqlx::query_as!(
+            row_types::RawFileUsage,
+            "
+SELECT COUNT(*) AS \"file_count!\", COALESCE(SUM(size), 0)::BIGINT AS \"bytes!\"
+FROM files
+WHERE is_ready = TRUE"
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let collections = match collection_limit {
+            Some(collection_limit) => {
+                sqlx::query_as!(
+                    row_types::RawCollectionUsage,
+                    "
+SELECT
+    collection.id,
+    collection.name,
+    COUNT(*) AS \"file_count!\",
+    SUM(file.size)::BIGINT AS \"bytes!\"
+FROM collections collection
+JOIN LATERAL (
+    SELECT t.file_id
+    FROM file_tags t
+    JOIN collection_tags c_tags ON c_tags.tag = t.tag AND c_tags.collection_id = collection.id
+    GROUP BY t.file_id
+    HAVING COUNT(
+        DISTINCT t.tag
+    ) = (
+        SELECT COUNT(c_tags.tag)
+        FROM collection_tags c_tags
+        WHERE c_tags.collection_id = collection.id
+    )
+) matched_file ON TRUE
+JOIN files file ON file.id = matched_file.file_id AND file.is_ready = TRUE
+GROUP BY collection.id
+ORDER BY SUM(file.size) DESC, collection.id ASC
+LIMIT $1",
+                    collection_limit as i64
+                )
+                .fetch_all(&mut *tx)
+                .await?
+            }
+            None => Vec::new(),
+        };
+
+        tx.commit().await?;
 
-        sqlx::query!(
-            "
-DELETE FROM file_tags
-WHERE file_id = $1",
-            file_id
-        )
-        .execute(&mut *tx)
-        .await?;
+        Ok(entities::FileUsageEntity {
+            file_count: total.file_count as u64,
+            bytes: total.bytes as u64,
+            collections: collections
+                .into_iter()
+                .map(|raw| entities::CollectionUsageEntity {
+                    id: raw.id,
+                    name: raw.name,
+                    file_count: raw.file_count as u64,
+                    bytes: raw.bytes as u64,
+                })
+                .collect(),
+        })
+    }
 
+    /// Records a view of the file by the admin, counting it and moving its last view to now.
+    #[tracing::instrument(level = "debug", skip_all, fields(admin_id = %admin_id, file_id = %file_id))]
+    pub async fn upsert_view(&self, admin_id: Uuid, file_id: Uuid) -> Result<(), RepositoryError> {
         sqlx::query!(
             "
-DELETE FROM files
-WHERE id = $1",
+INSERT INTO file_views (admin_id, file_id)
+VALUES ($1, $2)
+ON CONFLICT (admin_id, file_id) DO UPDATE
+SET view_count = file_views.view_count + 1, last_viewed_at = CURRENT_TIMESTAMP",
+            admin_id,
             file_id
         )
-        .execute(&mut *tx)
+        .execute(&self.db_pool)
         .await?;
 
-        tx.commit().await?;
-
         Ok(())
     }
 
-    pub async fn delete_unready_many(
+    /// Lists the ready files the admin viewed last, the latest viewed first.
+    #[tracing::instrument(level = "debug", skip_all, fields(admin_id = %admin_id))]
+    pub async fn list_recently_viewed(
         &self,
-        before_uploaded_at: DateTime<Utc>,
-    ) -> Result<(), RepositoryError> {
+        admin_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<entities::FileEntity>, RepositoryError> {
         let mut tx = self.db_pool.begin().await?;
 
-        let file_ids = sqlx::query_as!(
-            row_types::RawFileId,
+        let files = sqlx::query_as!(
+            row_types::RawFile,
             "
-SELECT id
-FROM files
-WHERE uploaded_at < $1 AND is_ready = FALSE",
-            before_uploaded_at.naive_utc()
+SELECT
+    files.id,
+    files.name,
+    files.size,
+    files.mime_type,
+    files.checksum,
+    files.is_ready,
+    files.protected,
+    files.superseded_by,
+    files.uploaded_at,
+    files.bucket,
+    files.region
+FROM file_views
+INNER JOIN files ON files.id = file_views.file_id
+WHERE file_views.admin_id = $1 AND files.is_ready = TRUE
+ORDER BY file_views.last_viewed_at DESC, files.id ASC
+LIMIT $2",
+            admin_id,
+            limit as i64
         )
         .fetch_all(&mut *tx)
         .await?;
 
-        sqlx::query!(
+        let tags = sqlx::query_as!(
+            row_types::RawFileTagWithFileId,
             "
-DELETE FROM file_tags
+SELECT file_id, tag
+FROM file_tags
 WHERE file_id = ANY($1::uuid[])",
-            &file_ids
-                .iter()
-                .map(|file_id| file_id.id)
-                .collect::<Vec<_>>()
+            &files.iter().map(|file| file.id).collect::<Vec<_>>()
         )
-        .execute(&mut *tx)
+        .fetch_all(&mut *tx)
         .await?;
 
-        sqlx::query!(
-            "
-DELETE FROM files
-WHERE id = ANY($1::uuid[])",
-            &file_ids
-                .iter()
-                .map(|file_id| file_id.id)
-                .collect::<Vec<_>>()
-        )
-        .execute(&mut *tx)
+        tx.commit().await?;
+
+        let mut files_map =
+            HashMap::<_, _>::from_iter(files.iter().map(|file| (file.id, Vec::new())));
+
+        for tag in tags {
+            files_map
+                .entry(tag.file_id)
+                .or_default()
+                .push(row_types::RawFileTag { tag: tag.tag });
+        }
+
+        Ok(files
+            .into_iter()
+            .map(|file| {
+                let mut tags = files_map.remove(&file.id).unwrap_or_default();
+                tags.sort_unstable_by(|a, b| a.tag.cmp(&b.tag));
+
+                (file, tags).into()
+            })
+            .collect())
+    }
+
+    /// Deletes the views last made before `before_viewed_at`, returning how many were deleted.
+    /// They are deleted [`PRUNE_CHUNK_SIZE`] at a time.
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn delete_stale_views(
+        &self,
+        before_viewed_at: DateTime<Utc>,
+    ) -> Result<u64, RepositoryError> {
+        let mut deleted_count = 0;
+
+        loop {
+            let result = sqlx::query!(
+                "
+DELETE FROM file_views
+WHERE (admin_id, file_id) IN (
+    SELECT admin_id, file_id
+    FROM file_views
+    WHERE last_viewed_at < $1
+    LIMIT $2
+)",
+                before_viewed_at.naive_utc(),
+                PRUNE_CHUNK_SIZE
+            )
+            .execute(&self.db_pool)
+            .await?;
+
+            deleted_count += result.rows_affected();
+
+            if result.rows_affected() < PRUNE_CHUNK_SIZE as u64 {
+                return Ok(deleted_count);
+            }
+        }
+    }
+}
+
+/// Attaches the tags of the files to them, keeping their order.
+async fn with_tags(
+    conn: &mut PgConnection,
+    files: Vec<row_types::RawFile>,
+) -> Result<Vec<entities::FileEntity>, RepositoryError> {
+    let tags = sqlx::query_as!(
+        row_types::RawFileTagWithFileId,
+        "
+SELECT file_id, tag
+FROM file_tags
+WHERE file_id = ANY($1::uuid[])",
+        &files.iter().map(|file| file.id).collect::<Vec<_>>()
+    )
+    .fetch_all(conn)
+    .await?;
+
+    let mut files_map =
+        HashMap::<_, _>::from_iter(files.iter().map(|file| (file.id, Vec::with_capacity(10))));
+
+    for tag in tags {
+        files_map
+            .entry(tag.file_id)
+            .or_default()
+            .push(row_types::RawFileTag { tag: tag.tag });
+    }
+
+    Ok(files
+        .into_iter()
+        .map(|file| {
+            let mut tags = files_map.remove(&file.id).unwrap_or_default();
+            tags.sort_unstable_by(|a, b| a.tag.cmp(&b.tag));
+
+            (file, tags).into()
+        })
+        .collect())
+}
+
+/// Serializes the changes to version chains until the end of the transaction, so that two of them
+/// cannot close a cycle together. Taken before any row lock, so that it is always taken in the same
+/// order.
+async fn lock_version_chains(conn: &mut PgConnection) -> Result<(), RepositoryError> {
+    sqlx::query!("SELECT pg_advisory_xact_lock(hashtext('file-supersession'))")
+        .execute(conn)
         .await?;
 
-        tx.commit().await?;
+    Ok(())
+}
 
-        Ok(())
+/// Fails with [`RepositoryError::InvalidSupersession`] unless `superseded_by` is a ready file
+/// that `file_id` may point to: one that is not `file_id` itself or any of its later versions, and
+/// that leaves every version chain through `file_id` within [`MAX_VERSION_CHAIN_LENGTH`] files.
+/// The version chains must be locked with [`lock_version_chains`].
+async fn check_supersession(
+    conn: &mut PgConnection,
+    file_id: Uuid,
+    superseded_by: Uuid,
+) -> Result<(), RepositoryError> {
+    let is_ready = sqlx::query_scalar!(
+        "
+SELECT is_ready
+FROM files
+WHERE id = $1",
+        superseded_by
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    if is_ready != Some(true) {
+        return Err(RepositoryError::InvalidSupersession {
+            reason: "must be a ready file".to_owned(),
+        });
+    }
+
+    // the chains are kept short and acyclic, so both walks end within the bound
+    let later = sqlx::query!(
+        r#"
+WITH RECURSIVE chain (id, superseded_by, depth) AS (
+    SELECT id, superseded_by, 1
+    FROM files
+    WHERE id = $1
+    UNION ALL
+    SELECT files.id, files.superseded_by, chain.depth + 1
+    FROM chain
+    JOIN files ON files.id = chain.superseded_by
+    WHERE chain.depth <= $3
+)
+SELECT COALESCE(MAX(depth), 0) AS "length!", COALESCE(BOOL_OR(id = $2), FALSE) AS "cycle!"
+FROM chain"#,
+        superseded_by,
+        file_id,
+        MAX_VERSION_CHAIN_LENGTH
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if later.cycle {
+        return Err(RepositoryError::InvalidSupersession {
+            reason: "must not be the file itself or one of the files it supersedes".to_owned(),
+        });
+    }
+
+    let earlier_length = sqlx::query_scalar!(
+        r#"
+WITH RECURSIVE chain (id, depth) AS (
+    SELECT id, 0
+    FROM files
+    WHERE id = $1
+    UNION ALL
+    SELECT files.id, chain.depth + 1
+    FROM chain
+    JOIN files ON files.superseded_by = chain.id
+    WHERE chain.depth <= $2
+)
+SELECT COALESCE(MAX(depth), 0) AS "length!"
+FROM chain"#,
+        file_id,
+        MAX_VERSION_CHAIN_LENGTH
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if MAX_VERSION_CHAIN_LENGTH < earlier_length + 1 + later.length {
+        return Err(RepositoryError::InvalidSupersession {
+            reason: format!(
+                "must not make a version chain longer than {MAX_VERSION_CHAIN_LENGTH} files"
+            ),
+        });
     }
+
+    Ok(())
 }
 
 pub mod row_types {
+    use crate::interfaces::files::{FileStorage, UploadOutcome};
     use chrono::NaiveDateTime;
     use uuid::Uuid;
 
+    /// The storage of a file, if both of its columns are set.
+    pub fn storage(bucket: Option<String>, region: Option<String>) -> Option<FileStorage> {
+        bucket
+            .zip(region)
+            .map(|(bucket, region)| FileStorage { bucket, region })
+    }
+
     pub struct RawFile {
         pub id: Uuid,
         pub name: String,
         pub size: i64,
         pub mime_type: String,
+        pub checksum: Option<String>,
+        pub is_ready: bool,
+        pub protected: bool,
+        pub superseded_by: Option<Uuid>,
+        pub uploaded_at: NaiveDateTime,
+        pub bucket: Option<String>,
+        pub region: Option<String>,
+    }
+
+    pub struct RawFileWithTags {
+        pub id: Uuid,
+        pub name: String,
+        pub size: i64,
+        pub mime_type: String,
+        pub checksum: Option<String>,
+        pub is_ready: bool,
+        pub protected: bool,
+        pub superseded_by: Option<Uuid>,
         pub uploaded_at: NaiveDateTime,
+        pub tags: Vec<String>,
     }
 
-    pub struct RawFileId {
+    pub struct RawFileState {
         pub id: Uuid,
+        pub is_ready: bool,
     }
 
     pub struct RawFileTag {
         pub tag: String,
     }
 
+    pub struct RawFileStorage {
+        pub id: Uuid,
+        pub bucket: Option<String>,
+        pub region: Option<String>,
+    }
+
+    pub struct RawUnreadyFile {
+        pub id: Uuid,
+        pub bucket: Option<String>,
+        pub region: Option<String>,
+        pub protected: bool,
+        pub active_upload_id: Option<String>,
+    }
+
     pub struct RawFileTagWithFileId {
         pub file_id: Uuid,
         pub tag: String,
     }
 
+    pub struct RawFileCollection {
+        pub file_id: Uuid,
+        pub collection_id: Uuid,
+        pub name: String,
+    }
+
     pub struct RawFileForUpload {
         pub size: i64,
         pub mime_type: String,
+        pub bucket: Option<String>,
+        pub region: Option<String>,
+        pub active_upload_id: Option<String>,
+    }
+
+    pub struct RawUploadingFile {
+        pub id: Uuid,
+        pub active_upload_id: String,
+        pub bucket: Option<String>,
+        pub region: Option<String>,
+    }
+
+    pub struct RawFileForCompletion {
+        pub is_ready: bool,
+        pub upload_id: Option<String>,
+        pub size: i64,
+        pub bucket: Option<String>,
+        pub region: Option<String>,
     }
 
     pub struct RawFileAfterCreation {
         pub id: Uuid,
+        pub is_ready: bool,
+        pub uploaded_at: NaiveDateTime,
+    }
+
+    pub struct RawFileVersionSource {
+        pub chain_id: Uuid,
+        pub name: String,
+    }
+
+    pub struct RawFileVersionAfterCreation {
+        pub id: Uuid,
+        pub is_ready: bool,
+        pub uploaded_at: NaiveDateTime,
+        pub version_number: i32,
+    }
+
+    pub struct RawFileVersion {
+        pub id: Uuid,
+        pub name: String,
+        pub size: i64,
+        pub mime_type: String,
+        pub checksum: Option<String>,
+        pub is_ready: bool,
+        pub protected: bool,
+        pub superseded_by: Option<Uuid>,
         pub uploaded_at: NaiveDateTime,
+        pub bucket: Option<String>,
+        pub region: Option<String>,
+        pub version_number: i32,
+        pub tags: Vec<String>,
+    }
+
+    pub struct RawFileBeforeUpdate {
+        pub name: String,
+        pub size: i64,
+        pub mime_type: String,
+        pub protected: bool,
+        pub superseded_by: Option<Uuid>,
     }
 
     pub struct RawFileAfterUpdate {
         pub name: String,
         pub size: i64,
         pub mime_type: String,
+        pub checksum: Option<String>,
+        pub is_ready: bool,
+        pub protected: bool,
+        pub superseded_by: Option<Uuid>,
         pub uploaded_at: NaiveDateTime,
+        pub bucket: Option<String>,
+        pub region: Option<String>,
+    }
+
+    pub struct RawFileUsage {
+        pub file_count: i64,
+        pub bytes: i64,
+    }
+
+    pub struct RawCollectionUsage {
+        pub id: Uuid,
+        pub name: String,
+        pub file_count: i64,
+        pub bytes: i64,
+    }
+
+    pub struct RawSupersededFile {
+        pub id: Uuid,
+        pub superseded_by: Uuid,
+    }
+
+    pub struct RawUploadRecord {
+        pub file_id: Uuid,
+        pub upload_id: String,
+        pub part_count: i32,
+        pub total_bytes: i64,
+        pub outcome: UploadOutcome,
+        pub started_at: NaiveDateTime,
+        pub completed_at: Option<NaiveDateTime>,
+    }
+
+    pub struct RawStaleUpload {
+        pub file_id: Uuid,
+        pub upload_id: String,
+        pub bucket: Option<String>,
+        pub region: Option<String>,
     }
 }
 
 pub mod entities {
+    use crate::interfaces::files::{FileStorage, UploadOutcome};
     use chrono::{DateTime, Utc};
     use serde::{Deserialize, Serialize};
     use uuid::Uuid;
@@ -455,8 +2401,15 @@ pub mod entities {
         pub name: String,
         pub size: usize,
         pub mime_type: String,
+        pub checksum: Option<String>,
+        pub is_ready: bool,
+        /// Whether the file is kept from being deleted.
+        pub protected: bool,
+        /// The newer version of the file, if it has been replaced by one.
+        pub superseded_by: Option<Uuid>,
         pub uploaded_at: DateTime<Utc>,
         pub tags: Vec<String>,
+        pub storage: Option<FileStorage>,
     }
 
     impl From<(super::row_types::RawFile, Vec<super::row_types::RawFileTag>)> for FileEntity {
@@ -468,8 +2421,32 @@ pub mod entities {
                 name: raw.name,
                 size: raw.size as usize,
                 mime_type: raw.mime_type,
+                checksum: raw.checksum,
+                is_ready: raw.is_ready,
+                protected: raw.protected,
+                superseded_by: raw.superseded_by,
                 uploaded_at: raw.uploaded_at.and_utc(),
                 tags: tags.into_iter().map(|raw| raw.tag).collect(),
+                storage: super::row_types::storage(raw.bucket, raw.region),
+            }
+        }
+    }
+
+    impl From<super::row_types::RawFileWithTags> for FileEntity {
+        fn from(raw: super::row_types::RawFileWithTags) -> Self {
+            Self {
+                id: raw.id,
+                name: raw.name,
+                size: raw.size as usize,
+                mime_type: raw.mime_type,
+                checksum: raw.checksum,
+                is_ready: raw.is_ready,
+                protected: raw.protected,
+                superseded_by: raw.superseded_by,
+                uploaded_at: raw.uploaded_at.and_utc(),
+                tags: raw.tags,
+                // the export stream does not need it
+                storage: None,
             }
         }
     }
@@ -491,8 +2468,56 @@ pub mod entities {
                 name: file.name,
                 size: file.size,
                 mime_type: file.mime_type,
+                checksum: file.checksum,
+                is_ready: raw.is_ready,
+                protected: false,
+                superseded_by: None,
                 uploaded_at: raw.uploaded_at.and_utc(),
                 tags: file.tags,
+                storage: Some(file.storage),
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct FileVersionEntity {
+        /// The position of the file among the versions, from 1 for the first one.
+        pub version_number: i32,
+        pub file: FileEntity,
+    }
+
+    impl From<super::row_types::RawFileVersion> for FileVersionEntity {
+        fn from(raw: super::row_types::RawFileVersion) -> Self {
+            Self {
+                version_number: raw.version_number,
+                file: FileEntity {
+                    id: raw.id,
+                    name: raw.name,
+                    size: raw.size as usize,
+                    mime_type: raw.mime_type,
+                    checksum: raw.checksum,
+                    is_ready: raw.is_ready,
+                    protected: raw.protected,
+                    superseded_by: raw.superseded_by,
+                    uploaded_at: raw.uploaded_at.and_utc(),
+                    tags: raw.tags,
+                    storage: super::row_types::storage(raw.bucket, raw.region),
+                },
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+    pub struct FileStateEntity {
+        pub id: Uuid,
+        pub is_ready: bool,
+    }
+
+    impl From<super::row_types::RawFileState> for FileStateEntity {
+        fn from(raw: super::row_types::RawFileState) -> Self {
+            Self {
+                id: raw.id,
+                is_ready: raw.is_ready,
             }
         }
     }
@@ -501,6 +2526,9 @@ pub mod entities {
     pub struct FileEntityForUpload {
         pub size: usize,
         pub mime_type: String,
+        pub storage: Option<FileStorage>,
+        /// The upload handed out for the file that is neither completed nor aborted yet.
+        pub active_upload_id: Option<String>,
     }
 
     impl From<super::row_types::RawFileForUpload> for FileEntityForUpload {
@@ -508,6 +2536,45 @@ pub mod entities {
             Self {
                 size: raw.size as usize,
                 mime_type: raw.mime_type,
+                storage: super::row_types::storage(raw.bucket, raw.region),
+                active_upload_id: raw.active_upload_id,
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct FileEntityForCompletion {
+        pub is_ready: bool,
+        /// The multipart upload that completed the file, if it is ready.
+        pub upload_id: Option<String>,
+        pub size: usize,
+        pub storage: Option<FileStorage>,
+    }
+
+    impl From<super::row_types::RawFileForCompletion> for FileEntityForCompletion {
+        fn from(raw: super::row_types::RawFileForCompletion) -> Self {
+            Self {
+                is_ready: raw.is_ready,
+                upload_id: raw.upload_id,
+                size: raw.size as usize,
+                storage: super::row_types::storage(raw.bucket, raw.region),
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct UploadingFileEntity {
+        pub id: Uuid,
+        pub active_upload_id: String,
+        pub storage: Option<FileStorage>,
+    }
+
+    impl From<super::row_types::RawUploadingFile> for UploadingFileEntity {
+        fn from(raw: super::row_types::RawUploadingFile) -> Self {
+            Self {
+                id: raw.id,
+                active_upload_id: raw.active_upload_id,
+                storage: super::row_types::storage(raw.bucket, raw.region),
             }
         }
     }
@@ -518,12 +2585,56 @@ pub mod entities {
         pub uploaded_at: DateTime<Utc>,
     }
 
+    /// The tags a bulk tag change added to and removed from a file.
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct FileTagChangeEntity {
+        pub file_id: Uuid,
+        pub added: Vec<String>,
+        pub removed: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct FileNameCursorEntity {
+        pub id: Uuid,
+        pub name: String,
+        /// The key of the name, see [`crate::db::repositories::name_sort_key`].
+        pub name_sort_key: String,
+    }
+
     #[derive(Serialize, Deserialize, Debug, Clone)]
     pub struct FileEntityForCreation {
         pub name: String,
         pub size: usize,
         pub mime_type: String,
+        pub checksum: Option<String>,
         pub tags: Vec<String>,
+        pub storage: FileStorage,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct FileUsageEntity {
+        pub file_count: u64,
+        pub bytes: u64,
+        /// Empty unless asked for.
+        pub collections: Vec<CollectionUsageEntity>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct CollectionUsageEntity {
+        pub id: Uuid,
+        pub name: String,
+        pub file_count: u64,
+        pub bytes: u64,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct FileEntityForVersion {
+        /// Keeps the name of the file if `None`.
+        pub name: Option<String>,
+        pub size: usize,
+        pub mime_type: String,
+        pub checksum: Option<String>,
+        pub storage: FileStorage,
     }
 
     #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -532,5 +2643,51 @@ pub mod entities {
         pub name: Option<String>,
         pub size: Option<usize>,
         pub mime_type: Option<String>,
+        pub protected: Option<bool>,
+        /// `Some(None)` marks the file as current again.
+        pub superseded_by: Option<Option<Uuid>>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct UploadRecordEntity {
+        pub file_id: Uuid,
+        pub upload_id: String,
+        pub part_count: u32,
+        pub total_bytes: usize,
+        pub outcome: UploadOutcome,
+        pub started_at: DateTime<Utc>,
+        pub completed_at: Option<DateTime<Utc>>,
+    }
+
+    impl From<super::row_types::RawUploadRecord> for UploadRecordEntity {
+        fn from(raw: super::row_types::RawUploadRecord) -> Self {
+            Self {
+                file_id: raw.file_id,
+                upload_id: raw.upload_id,
+                part_count: raw.part_count as u32,
+                total_bytes: raw.total_bytes as usize,
+                outcome: raw.outcome,
+                started_at: raw.started_at.and_utc(),
+                completed_at: raw.completed_at.map(|completed_at| completed_at.and_utc()),
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct StaleUploadEntity {
+        pub file_id: Uuid,
+        pub upload_id: String,
+        /// Absent if the file has been deleted since.
+        pub storage: Option<FileStorage>,
+    }
+
+    impl From<super::row_types::RawStaleUpload> for StaleUploadEntity {
+        fn from(raw: super::row_types::RawStaleUpload) -> Self {
+            Self {
+                file_id: raw.file_id,
+                upload_id: raw.upload_id,
+                storage: super::row_types::storage(raw.bucket, raw.region),
+            }
+        }
     }
 }
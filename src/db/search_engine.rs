@@ -1,4 +1,7 @@
-use meilisearch_sdk::{client::Client, indexes::Index};
+use crate::config::SearchEngineConfig;
+use meilisearch_sdk::{
+    client::Client, indexes::Index, settings::PaginationSetting, task_info::TaskInfo,
+};
 use thiserror::Error;
 
 pub const FILES_INDEX_UID: &str = "file-indexer-files";
@@ -7,19 +10,64 @@ pub const COLLECTIONS_INDEX_UID: &str = "file-indexer-collections";
 pub const FILES_PRIMARY_KEY: Option<&str> = Some("id");
 pub const COLLECTIONS_PRIMARY_KEY: Option<&str> = Some("id");
 
-#[derive(Error, Debug)]
-pub enum SearchEngineError {
-    #[error("environment variable `MEILISEARCH_URL` is unable to be retrieved: {0:#?}")]
-    RetrieveMeilisearchUrl(std::env::VarError),
+/// The attribute settings every index is expected to have. Index creation and the startup
+/// verification both go through these, so they cannot drift apart.
+pub struct IndexSchema {
+    pub uid: &'static str,
+    /// In ranking order.
+    pub searchable_attributes: &'static [&'static str],
+    pub filterable_attributes: &'static [&'static str],
+    pub sortable_attributes: &'static [&'static str],
+    /// The most hits a search can reach, through paging or counting. A higher limit set by hand
+    /// is kept.
+    pub max_total_hits: usize,
+}
 
-    #[error("environment variable `MEILISEARCH_API_KEY` is unable to be retrieved: {0:#?}")]
-    RetrieveMeilisearchApiKey(std::env::VarError),
+pub const FILES_INDEX_SCHEMA: IndexSchema = IndexSchema {
+    uid: FILES_INDEX_UID,
+    searchable_attributes: &["name", "tags"],
+    filterable_attributes: &[
+        "size",
+        "size_bucket",
+        "mime_type",
+        "tags",
+        "uploaded_at",
+        "checksum",
+        "is_ready",
+        "truncated_for_index",
+        "protected",
+        "superseded",
+    ],
+    sortable_attributes: &["uploaded_at"],
+    // bulk deletions page through up to 10,000 matching files, and look one past them to tell
+    // whether more matched; Meilisearch stops at 1000 by default
+    max_total_hits: 20_000,
+};
 
+pub const COLLECTIONS_INDEX_SCHEMA: IndexSchema = IndexSchema {
+    uid: COLLECTIONS_INDEX_UID,
+    searchable_attributes: &["name", "tags"],
+    filterable_attributes: &["size", "tags", "created_at"],
+    sortable_attributes: &[],
+    max_total_hits: 1000,
+};
+
+#[derive(Error, Debug)]
+pub enum SearchEngineError {
     #[error("meilisearch error: {0:#?}")]
     MeilisearchError(#[from] meilisearch_sdk::errors::Error),
 
     #[error("failed to create index: {0:#?}")]
     FailedToCreateIndex(meilisearch_sdk::errors::MeilisearchError),
+
+    #[error("failed to update index settings: {0:#?}")]
+    FailedToUpdateIndexSettings(meilisearch_sdk::errors::MeilisearchError),
+
+    #[error("index `{index_uid}` does not match the expected schema: {mismatches}")]
+    IndexSchemaMismatch {
+        index_uid: &'static str,
+        mismatches: String,
+    },
 }
 
 pub struct SearchEngine {
@@ -27,19 +75,10 @@ pub struct SearchEngine {
 }
 
 impl SearchEngine {
-    pub async fn init() -> Result<Self, SearchEngineError> {
-        let url =
-            std::env::var("MEILISEARCH_URL").map_err(SearchEngineError::RetrieveMeilisearchUrl)?;
-        let api_key = match std::env::var("MEILISEARCH_API_KEY") {
-            Ok(api_key) => Some(api_key),
-            Err(std::env::VarError::NotPresent) => None,
-            Err(err) => {
-                return Err(SearchEngineError::RetrieveMeilisearchApiKey(err));
-            }
-        };
-
-        let client = Client::new(url, api_key)?;
-        setup_index(&client).await?;
+    pub async fn init(config: &SearchEngineConfig) -> Result<Self, SearchEngineError> {
+        let client = Client::new(&config.url, config.api_key.as_deref())?;
+        setup_index(&client, &FILES_INDEX_SCHEMA, config.check_only).await?;
+        setup_index(&client, &COLLECTIONS_INDEX_SCHEMA, config.check_only).await?;
 
         Ok(Self { client })
     }
@@ -49,60 +88,237 @@ impl SearchEngine {
     }
 }
 
-async fn setup_index(client: &Client) -> Result<(), SearchEngineError> {
-    match client.get_index(FILES_INDEX_UID).await {
-        Ok(_) => {}
+/// Creates the index if it is missing, then makes sure its settings match the schema.
+///
+/// In check-only mode, mismatches on an existing index are reported as an error instead of being
+/// fixed.
+async fn setup_index(
+    client: &Client,
+    schema: &IndexSchema,
+    check_only: bool,
+) -> Result<(), SearchEngineError> {
+    let index = match client.get_index(schema.uid).await {
+        Ok(index) => index,
         Err(meilisearch_sdk::errors::Error::Meilisearch(err))
             if err.error_code == meilisearch_sdk::errors::ErrorCode::IndexNotFound =>
         {
-            create_file_index(client).await?;
+            let index = create_index(client, schema).await?;
+            apply_index_schema(client, &index, schema, &IndexSchemaDiff::everything(schema))
+                .await?;
+            tracing::info!(index = schema.uid, "created index");
+            return Ok(());
         }
         Err(err) => {
             return Err(SearchEngineError::MeilisearchError(err));
         }
+    };
+
+    let diff = IndexSchemaDiff::compute(&index, schema).await?;
+
+    if diff.is_empty() {
+        tracing::info!(index = schema.uid, "index matches the expected schema");
+        return Ok(());
     }
 
-    match client.get_index(COLLECTIONS_INDEX_UID).await {
-        Ok(_) => {}
-        Err(meilisearch_sdk::errors::Error::Meilisearch(err))
-            if err.error_code == meilisearch_sdk::errors::ErrorCode::IndexNotFound =>
-        {
-            create_collection_index(client).await?;
-        }
-        Err(err) => {
-            return Err(SearchEngineError::MeilisearchError(err));
-        }
+    if check_only {
+        return Err(SearchEngineError::IndexSchemaMismatch {
+            index_uid: schema.uid,
+            mismatches: diff.summary(),
+        });
     }
 
+    apply_index_schema(client, &index, schema, &diff).await?;
+    tracing::warn!(
+        index = schema.uid,
+        mismatches = %diff.summary(),
+        "index did not match the expected schema and has been updated"
+    );
+
     Ok(())
 }
 
-async fn create_file_index(client: &Client) -> Result<Index, SearchEngineError> {
-    let task = client.create_index(FILES_INDEX_UID, None).await?;
+async fn create_index(client: &Client, schema: &IndexSchema) -> Result<Index, SearchEngineError> {
+    let task = client.create_index(schema.uid, None).await?;
     let task = task.wait_for_completion(client, None, None).await?;
     let index = task
         .try_make_index(client)
         .map_err(|task| SearchEngineError::FailedToCreateIndex(task.unwrap_failure()))?;
 
-    index.set_searchable_attributes(&["name", "tags"]).await?;
-    index
-        .set_filterable_attributes(&["size", "mime_type", "tags", "uploaded_at"])
-        .await?;
-
     Ok(index)
 }
 
-async fn create_collection_index(client: &Client) -> Result<Index, SearchEngineError> {
-    let task = client.create_index(COLLECTIONS_INDEX_UID, None).await?;
+/// The expected attributes an index lacks, per setting.
+struct IndexSchemaDiff {
+    searchable_attributes: Vec<String>,
+    filterable_attributes: Vec<String>,
+    sortable_attributes: Vec<String>,
+    /// The expected limit, if the current one is lower.
+    max_total_hits: Option<usize>,
+    current_searchable_attributes: Vec<String>,
+    current_filterable_attributes: Vec<String>,
+    current_sortable_attributes: Vec<String>,
+    current_max_total_hits: usize,
+}
+
+impl IndexSchemaDiff {
+    fn everything(schema: &IndexSchema) -> Self {
+        Self {
+            searchable_attributes: to_owned_attributes(schema.searchable_attributes),
+            filterable_attributes: to_owned_attributes(schema.filterable_attributes),
+            sortable_attributes: to_owned_attributes(schema.sortable_attributes),
+            max_total_hits: Some(schema.max_total_hits),
+            current_searchable_attributes: Vec::new(),
+            current_filterable_attributes: Vec::new(),
+            current_sortable_attributes: Vec::new(),
+            current_max_total_hits: 0,
+        }
+    }
+
+    async fn compute(index: &Index, schema: &IndexSchema) -> Result<Self, SearchEngineError> {
+        let current_searchable_attributes = index.get_searchable_attributes().await?;
+        let current_filterable_attributes = index.get_filterable_attributes().await?;
+        let current_sortable_attributes = index.get_sortable_attributes().await?;
+        let current_max_total_hits = index.get_pagination().await?.max_total_hits;
+
+        // the searchable attributes also decide the ranking, so their order has to match as well
+        let searchable_attributes = if current_searchable_attributes == schema.searchable_attributes
+        {
+            Vec::new()
+        } else {
+            to_owned_attributes(schema.searchable_attributes)
+        };
+
+        Ok(Self {
+            searchable_attributes,
+            filterable_attributes: missing_attributes(
+                schema.filterable_attributes,
+                &current_filterable_attributes,
+            ),
+            sortable_attributes: missing_attributes(
+                schema.sortable_attributes,
+                &current_sortable_attributes,
+            ),
+            max_total_hits: (current_max_total_hits < schema.max_total_hits)
+                .then_some(schema.max_total_hits),
+            current_searchable_attributes,
+            current_filterable_attributes,
+            current_sortable_attributes,
+            current_max_total_hits,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.searchable_attributes.is_empty()
+            && self.filterable_attributes.is_empty()
+            && self.sortable_attributes.is_empty()
+            && self.max_total_hits.is_none()
+    }
+
+    fn summary(&self) -> String {
+        let mut mismatches = Vec::new();
+
+        if !self.searchable_attributes.is_empty() {
+            mismatches.push(format!(
+                "searchable attributes are {:?} instead of {:?}",
+                self.current_searchable_attributes, self.searchable_attributes
+            ));
+        }
+
+        if !self.filterable_attributes.is_empty() {
+            mismatches.push(format!(
+                "missing filterable attributes {:?}",
+                self.filterable_attributes
+            ));
+        }
+
+        if !self.sortable_attributes.is_empty() {
+            mismatches.push(format!(
+                "missing sortable attributes {:?}",
+                self.sortable_attributes
+            ));
+        }
+
+        if let Some(max_total_hits) = self.max_total_hits {
+            mismatches.push(format!(
+                "max total hits is {} instead of {max_total_hits}",
+                self.current_max_total_hits
+            ));
+        }
+
+        mismatches.join(", ")
+    }
+}
+
+/// Applies the settings listed in the diff and waits for them to take effect. Filterable and
+/// sortable attributes added by hand are kept.
+async fn apply_index_schema(
+    client: &Client,
+    index: &Index,
+    schema: &IndexSchema,
+    diff: &IndexSchemaDiff,
+) -> Result<(), SearchEngineError> {
+    if !diff.searchable_attributes.is_empty() {
+        let task = index
+            .set_searchable_attributes(schema.searchable_attributes)
+            .await?;
+        wait_for_settings_task(client, task).await?;
+    }
+
+    if !diff.filterable_attributes.is_empty() {
+        let task = index
+            .set_filterable_attributes(merge_attributes(
+                &diff.current_filterable_attributes,
+                &diff.filterable_attributes,
+            ))
+            .await?;
+        wait_for_settings_task(client, task).await?;
+    }
+
+    if !diff.sortable_attributes.is_empty() {
+        let task = index
+            .set_sortable_attributes(merge_attributes(
+                &diff.current_sortable_attributes,
+                &diff.sortable_attributes,
+            ))
+            .await?;
+        wait_for_settings_task(client, task).await?;
+    }
+
+    if let Some(max_total_hits) = diff.max_total_hits {
+        let task = index
+            .set_pagination(PaginationSetting { max_total_hits })
+            .await?;
+        wait_for_settings_task(client, task).await?;
+    }
+
+    Ok(())
+}
+
+async fn wait_for_settings_task(client: &Client, task: TaskInfo) -> Result<(), SearchEngineError> {
     let task = task.wait_for_completion(client, None, None).await?;
-    let index = task
-        .try_make_index(client)
-        .map_err(|task| SearchEngineError::FailedToCreateIndex(task.unwrap_failure()))?;
 
-    index.set_searchable_attributes(&["name", "tags"]).await?;
-    index
-        .set_filterable_attributes(&["size", "tags", "created_at"])
-        .await?;
+    if task.is_failure() {
+        return Err(SearchEngineError::FailedToUpdateIndexSettings(
+            task.unwrap_failure(),
+        ));
+    }
 
-    Ok(index)
+    Ok(())
+}
+
+fn to_owned_attributes(attributes: &[&str]) -> Vec<String> {
+    Vec::from_iter(attributes.iter().map(|attribute| (*attribute).to_owned()))
+}
+
+fn missing_attributes(expected: &[&str], current: &[String]) -> Vec<String> {
+    Vec::from_iter(
+        expected
+            .iter()
+            .filter(|attribute| !current.iter().any(|current| current == *attribute))
+            .map(|attribute| (*attribute).to_owned()),
+    )
+}
+
+fn merge_attributes(current: &[String], missing: &[String]) -> Vec<String> {
+    Vec::from_iter(current.iter().chain(missing).cloned())
 }
@@ -1,3 +1,5 @@
 pub mod cors;
 pub mod file_gc;
-pub mod re_indexer;
+pub mod index_flusher;
+pub mod request_tracing;
+pub mod task_runner;
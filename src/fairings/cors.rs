@@ -1,3 +1,4 @@
+use crate::config::CorsConfig;
 use rocket::{
     async_trait,
     fairing::{Fairing, Info, Kind},
@@ -5,7 +6,17 @@ use rocket::{
     Request, Response,
 };
 
-pub struct Cors;
+pub struct Cors {
+    allowed_origins: Option<Vec<String>>,
+}
+
+impl Cors {
+    pub fn new(config: CorsConfig) -> Self {
+        Self {
+            allowed_origins: config.allowed_origins,
+        }
+    }
+}
 
 #[async_trait]
 impl Fairing for Cors {
@@ -16,8 +27,32 @@ impl Fairing for Cors {
         }
     }
 
-    async fn on_response<'r>(&self, _request: &'r Request<'_>, response: &mut Response<'r>) {
-        response.set_header(Header::new("Access-Control-Allow-Origin", "*"));
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        match &self.allowed_origins {
+            None => {
+                response.set_header(Header::new("Access-Control-Allow-Origin", "*"));
+            }
+            Some(allowed_origins) => {
+                // the allowed origin depends on the request, so caches must keep them apart
+                response.adjoin_header(Header::new("Vary", "Origin"));
+
+                let origin = request.headers().get_one("Origin");
+                let origin = match origin {
+                    Some(origin) if allowed_origins.iter().any(|allowed| allowed == origin) => {
+                        origin
+                    }
+                    _ => {
+                        return;
+                    }
+                };
+
+                response.set_header(Header::new(
+                    "Access-Control-Allow-Origin",
+                    origin.to_owned(),
+                ));
+            }
+        }
+
         response.set_header(Header::new(
             "Access-Control-Allow-Methods",
             "GET, POST, PUT, PATCH, DELETE, OPTIONS",
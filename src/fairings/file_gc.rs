@@ -1,31 +1,59 @@
 use crate::{
-    interfaces::admins::{AdminTaskInitiator, AdminTaskStatus},
+    config::{FileGcConfig, HousekeepingConfig},
+    interfaces::{
+        admins::{AdminTaskInitiator, AdminTaskStatus, TaskKind},
+        files::{FileStorage, UploadOutcome},
+    },
     services::{
-        admin_task_service::{AdminTaskService, FILE_GC_TASK_NAME},
+        admin_service::AdminService,
+        admin_task_service::AdminTaskService,
         file_service::FileService,
+        index_service::IndexService,
+        s3_service::{AbortedUploads, ObjectStorage, UploadAbortFailure},
     },
+    telemetry::ErrorChain,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use rocket::{
     async_trait,
     fairing::{Fairing, Info, Kind},
     Orbit, Rocket,
 };
-use std::time::Duration;
+use serde_json::{Map, Value};
+use std::{error::Error, sync::Arc, time::Duration};
 use tokio::sync::Mutex;
+use uuid::Uuid;
 
 pub struct FileGc {
+    admin_service: AdminService,
     admin_task_service: AdminTaskService,
     file_service: FileService,
+    index_service: IndexService,
+    object_storage: Arc<dyn ObjectStorage>,
+    config: FileGcConfig,
+    housekeeping_config: HousekeepingConfig,
     stop_signal: Mutex<Option<tokio::sync::mpsc::Sender<()>>>,
     task_handle: Mutex<Option<tokio::task::JoinHandle<()>>>,
 }
 
 impl FileGc {
-    pub fn new(admin_task_service: AdminTaskService, file_service: FileService) -> Self {
+    pub fn new(
+        admin_service: AdminService,
+        admin_task_service: AdminTaskService,
+        file_service: FileService,
+        index_service: IndexService,
+        object_storage: Arc<dyn ObjectStorage>,
+        config: FileGcConfig,
+        housekeeping_config: HousekeepingConfig,
+    ) -> Self {
         Self {
+            admin_service,
             admin_task_service,
             file_service,
+            index_service,
+            object_storage,
+            config,
+            housekeeping_config,
             stop_signal: Mutex::new(None),
             task_handle: Mutex::new(None),
         }
@@ -35,8 +63,13 @@ impl FileGc {
         let (tx, rx) = tokio::sync::mpsc::channel(1);
         let task_handle = tokio::spawn(file_gc_task(
             rx,
+            self.admin_service.clone(),
             self.admin_task_service.clone(),
             self.file_service.clone(),
+            self.index_service.clone(),
+            self.object_storage.clone(),
+            self.config,
+            self.housekeeping_config,
         ));
 
         *self.stop_signal.lock().await = Some(tx);
@@ -49,7 +82,7 @@ impl Fairing for FileGc {
     fn info(&self) -> Info {
         Info {
             name: "file_gc",
-            kind: Kind::Ignite | Kind::Shutdown,
+            kind: Kind::Liftoff | Kind::Shutdown,
         }
     }
 
@@ -60,30 +93,34 @@ impl Fairing for FileGc {
     async fn on_shutdown(&self, _rocket: &Rocket<Orbit>) {
         if let Some(tx) = self.stop_signal.lock().await.take() {
             if let Err(err) = tx.send(()).await {
-                log::warn!("failed to send stop signal to file gc task: {err:#?}");
+                tracing::warn!(error = %ErrorChain(&err), "failed to send stop signal to file gc task");
                 return;
             }
         }
 
         if let Some(task_handle) = self.task_handle.lock().await.take() {
             if let Err(err) = task_handle.await {
-                log::warn!("failed to wait for file gc task to finish: {err:#?}");
+                tracing::warn!(error = %ErrorChain(&err), "failed to wait for file gc task to finish");
             }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn file_gc_task(
     mut stop_signal: tokio::sync::mpsc::Receiver<()>,
+    admin_service: AdminService,
     admin_task_service: AdminTaskService,
     file_service: FileService,
+    index_service: IndexService,
+    object_storage: Arc<dyn ObjectStorage>,
+    config: FileGcConfig,
+    housekeeping_config: HousekeepingConfig,
 ) {
-    // 6 hours
-    let duration_secs = 60 * 60 * 6;
+    let mut timer = tokio::time::interval(config.interval);
+    let mut housekeeping_timer = tokio::time::interval(housekeeping_config.interval);
 
     loop {
-        let mut timer = tokio::time::interval(Duration::from_secs(duration_secs));
-
         tokio::select! {
             _ = stop_signal.recv() => {
                 return;
@@ -92,36 +129,247 @@ async fn file_gc_task(
                 file_gc_task_on_tick(
                     &admin_task_service,
                     &file_service,
+                    &index_service,
+                    object_storage.as_ref(),
+                    config.unready_file_ttl,
+                ).await;
+            }
+            _ = housekeeping_timer.tick() => {
+                housekeeping_on_tick(
+                    &admin_service,
+                    &admin_task_service,
+                    &file_service,
+                    housekeeping_config,
                 ).await;
             }
         }
     }
 }
 
-async fn file_gc_task_on_tick(admin_task_service: &AdminTaskService, file_service: &FileService) {
-    // 2 hours
-    let duration_secs = 60 * 60 * 2;
-    let before_uploaded_at = Utc::now() - Duration::from_secs(duration_secs);
+async fn file_gc_task_on_tick(
+    admin_task_service: &AdminTaskService,
+    file_service: &FileService,
+    index_service: &IndexService,
+    object_storage: &dyn ObjectStorage,
+    unready_file_ttl: Duration,
+) {
+    let before_uploaded_at = Utc::now() - unready_file_ttl;
+    let mut aborted_upload_count =
+        abort_stale_uploads(file_service, object_storage, before_uploaded_at).await;
 
     let result = file_service.delete_unready_files(before_uploaded_at).await;
-    let metadata = match result {
-        Ok(_) => serde_json::json!({
-            "success": true,
-        }),
+    let mut upload_abort_failures = Vec::new();
+    let mut metadata = match result {
+        Ok((files, protected_file_ids)) => {
+            let aborted_uploads =
+                abort_purged_file_uploads(file_service, object_storage, &files).await;
+            aborted_upload_count += aborted_uploads.upload_ids.len();
+            upload_abort_failures = aborted_uploads.failures;
+
+            let file_ids = Vec::from_iter(files.into_iter().map(|(file_id, _, _)| file_id));
+            // unready files may have been indexed by a metadata update
+            let meili_task_uids = match index_service.delete_files(&file_ids).await {
+                Ok(meili_task_uids) => meili_task_uids,
+                Err(err) => {
+                    tracing::warn!(error = %ErrorChain(&err), "failed to delete purged files from the index");
+                    Vec::new()
+                }
+            };
+
+            serde_json::json!({
+                "success": true,
+                "deleted_count": file_ids.len(),
+                "meili_task_uids": meili_task_uids,
+                // unready files that are protected are kept until an admin unprotects them
+                "protected_count": protected_file_ids.len(),
+                "protected_file_ids": protected_file_ids,
+            })
+        }
         Err(err) => serde_json::json!({ "success": false, "error": err.to_string() }),
     };
+    metadata["aborted_upload_count"] = aborted_upload_count.into();
+    metadata["upload_abort_failures"] = serde_json::json!(upload_abort_failures);
 
     let result = admin_task_service
         .enqueue_task(
             AdminTaskInitiator::System,
-            FILE_GC_TASK_NAME.to_owned(),
+            None,
+            TaskKind::FileGc,
             metadata,
             Some(AdminTaskStatus::Completed),
             false,
+            None,
+        )
+        .await;
+
+    if let Err(err) = result {
+        tracing::warn!(error = %ErrorChain(&err), "failed to enqueue file gc task");
+    }
+}
+
+/// Aborts the multipart uploads left pending since before `before_started_at`, found through
+/// their upload records, and records them as expired. Returns how many were aborted; an upload
+/// that fails to be aborted stays pending and is retried on the next tick.
+async fn abort_stale_uploads(
+    file_service: &FileService,
+    object_storage: &dyn ObjectStorage,
+    before_started_at: DateTime<Utc>,
+) -> usize {
+    let uploads = match file_service.list_stale_uploads(before_started_at).await {
+        Ok(uploads) => uploads,
+        Err(err) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to list stale uploads");
+            return 0;
+        }
+    };
+    let mut aborted_count = 0;
+
+    for (file_id, upload_id, storage) in uploads {
+        // an upload that is gone from S3 already only needs its record closed
+        if let Err(err) = object_storage
+            .abort_multipart_upload(file_id, upload_id.clone(), storage.as_ref())
+            .await
+        {
+            tracing::warn!(error = %ErrorChain(&err), "failed to abort stale upload `{upload_id}`");
+            continue;
+        }
+
+        match file_service
+            .record_upload_finished(&upload_id, UploadOutcome::Expired)
+            .await
+        {
+            Ok(_) => {
+                aborted_count += 1;
+            }
+            Err(err) => {
+                tracing::warn!(error = %ErrorChain(&err), "failed to record stale upload `{upload_id}` as expired");
+            }
+        }
+    }
+
+    aborted_count
+}
+
+/// Aborts the active upload of each purged file, as recorded on its row, and records it as
+/// expired. Files without an active upload have nothing left to abort.
+async fn abort_purged_file_uploads(
+    file_service: &FileService,
+    object_storage: &dyn ObjectStorage,
+    files: &[(Uuid, Option<FileStorage>, Option<String>)],
+) -> AbortedUploads {
+    let mut aborted = AbortedUploads::default();
+
+    for (file_id, storage, active_upload_id) in files {
+        let Some(upload_id) = active_upload_id else {
+            continue;
+        };
+
+        // an upload that is gone from S3 already only needs its record closed
+        if let Err(err) = object_storage
+            .abort_multipart_upload(*file_id, upload_id.clone(), storage.as_ref())
+            .await
+        {
+            tracing::warn!(error = %ErrorChain(&err), "failed to abort upload `{upload_id}` of purged file `{file_id}`");
+            aborted.failures.push(UploadAbortFailure {
+                upload_id: upload_id.clone(),
+                error: err.to_string(),
+            });
+            continue;
+        }
+
+        if let Err(err) = file_service
+            .record_upload_finished(upload_id, UploadOutcome::Expired)
+            .await
+        {
+            tracing::warn!(error = %ErrorChain(&err), "failed to record upload `{upload_id}` as expired");
+        }
+
+        aborted.upload_ids.push(upload_id.clone());
+    }
+
+    aborted
+}
+
+/// Prunes each auxiliary table by its retention rule, recording how many rows were deleted from
+/// each table in a `housekeeping` admin task. A table that fails to be pruned does not stop the
+/// others.
+async fn housekeeping_on_tick(
+    admin_service: &AdminService,
+    admin_task_service: &AdminTaskService,
+    file_service: &FileService,
+    config: HousekeepingConfig,
+) {
+    let mut tables = Map::new();
+
+    tables.insert(
+        "admin_sessions".to_owned(),
+        pruned_table(
+            "admin_sessions",
+            admin_service.purge_expired_sessions().await,
+        ),
+    );
+    tables.insert(
+        "admin_password_resets".to_owned(),
+        pruned_table(
+            "admin_password_resets",
+            admin_service.purge_stale_password_resets().await,
+        ),
+    );
+    tables.insert(
+        "admin_tasks".to_owned(),
+        pruned_table(
+            "admin_tasks",
+            admin_task_service
+                .delete_finished_tasks(Utc::now() - config.admin_task_retention)
+                .await,
+        ),
+    );
+    tables.insert(
+        "upload_records".to_owned(),
+        pruned_table(
+            "upload_records",
+            file_service
+                .delete_finished_upload_records(Utc::now() - config.upload_record_retention)
+                .await,
+        ),
+    );
+    tables.insert(
+        "file_views".to_owned(),
+        pruned_table(
+            "file_views",
+            file_service
+                .delete_stale_file_views(Utc::now() - config.file_view_retention)
+                .await,
+        ),
+    );
+
+    let result = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::System,
+            None,
+            TaskKind::Housekeeping,
+            serde_json::json!({ "tables": tables }),
+            Some(AdminTaskStatus::Completed),
+            false,
+            None,
         )
         .await;
 
     if let Err(err) = result {
-        log::warn!("failed to enqueue file gc task: {err:#?}");
+        tracing::warn!(error = %ErrorChain(&err), "failed to enqueue housekeeping task");
+    }
+}
+
+/// The metadata entry of a pruned table: its deleted count, or the error that stopped it.
+fn pruned_table(table: &str, result: Result<u64, impl Error + 'static>) -> Value {
+    match result {
+        Ok(deleted_count) => {
+            tracing::info!(table, deleted_count, "pruned table");
+            serde_json::json!({ "deleted_count": deleted_count })
+        }
+        Err(err) => {
+            tracing::warn!(table, error = %ErrorChain(&err), "failed to prune table");
+            serde_json::json!({ "error": err.to_string() })
+        }
     }
 }
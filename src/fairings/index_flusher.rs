@@ -0,0 +1,266 @@
+use crate::{
+    config::IndexSweepConfig,
+    interfaces::admins::{AdminTaskInitiator, TaskKind},
+    services::{
+        admin_task_service::AdminTaskService, file_service::FileService,
+        index_service::IndexService,
+    },
+    telemetry::ErrorChain,
+};
+use rocket::{
+    async_trait,
+    fairing::{Fairing, Info, Kind},
+    Orbit, Rocket,
+};
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// How often the write queue is checked for files whose window has passed.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+/// Number of documents a sweep lists, and checks against the database, at a time.
+const SWEEP_PAGE_SIZE: usize = 100;
+
+/// Indexes the files queued with [`IndexService::queue_file_index`] once their window has passed,
+/// and every file still queued on shutdown.
+///
+/// Every [`IndexSweepConfig::interval`], it also sweeps the latest file documents for files that
+/// no longer exist, e.g. because their rows were deleted by hand. Older documents are left to a
+/// re-index.
+pub struct IndexFlusher {
+    admin_task_service: AdminTaskService,
+    file_service: FileService,
+    index_service: IndexService,
+    sweep_config: IndexSweepConfig,
+    stop_signal: Mutex<Option<tokio::sync::mpsc::Sender<()>>>,
+    task_handle: Mutex<Option<tokio::task::JoinHandle<()>>>,
+}
+
+impl IndexFlusher {
+    pub fn new(
+        admin_task_service: AdminTaskService,
+        file_service: FileService,
+        index_service: IndexService,
+        sweep_config: IndexSweepConfig,
+    ) -> Self {
+        Self {
+            admin_task_service,
+            file_service,
+            index_service,
+            sweep_config,
+            stop_signal: Mutex::new(None),
+            task_handle: Mutex::new(None),
+        }
+    }
+
+    async fn create_index_flusher_task(&self) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        let task_handle = tokio::spawn(index_flusher_task(
+            rx,
+            self.admin_task_service.clone(),
+            self.file_service.clone(),
+            self.index_service.clone(),
+            self.sweep_config,
+        ));
+
+        *self.stop_signal.lock().await = Some(tx);
+        *self.task_handle.lock().await = Some(task_handle);
+    }
+}
+
+#[async_trait]
+impl Fairing for IndexFlusher {
+    fn info(&self) -> Info {
+        Info {
+            name: "index_flusher",
+            kind: Kind::Liftoff | Kind::Shutdown,
+        }
+    }
+
+    async fn on_liftoff(&self, _rocket: &Rocket<Orbit>) {
+        self.create_index_flusher_task().await;
+    }
+
+    async fn on_shutdown(&self, _rocket: &Rocket<Orbit>) {
+        if let Some(tx) = self.stop_signal.lock().await.take() {
+            if let Err(err) = tx.send(()).await {
+                tracing::warn!(error = %ErrorChain(&err), "failed to send stop signal to index flusher task");
+                return;
+            }
+        }
+
+        if let Some(task_handle) = self.task_handle.lock().await.take() {
+            if let Err(err) = task_handle.await {
+                tracing::warn!(error = %ErrorChain(&err), "failed to wait for index flusher task to finish");
+            }
+        }
+    }
+}
+
+async fn index_flusher_task(
+    mut stop_signal: tokio::sync::mpsc::Receiver<()>,
+    admin_task_service: AdminTaskService,
+    file_service: FileService,
+    index_service: IndexService,
+    sweep_config: IndexSweepConfig,
+) {
+    let mut timer = tokio::time::interval(FLUSH_INTERVAL);
+    let sweep_ticks = (sweep_config.interval.as_millis() / FLUSH_INTERVAL.as_millis()).max(1);
+    let mut ticks = 0;
+
+    loop {
+        tokio::select! {
+            _ = stop_signal.recv() => {
+                // drain, so that no queued write is lost on shutdown
+                let file_ids = index_service.take_queued_files(Instant::now(), true);
+                flush(&admin_task_service, &file_service, &index_service, file_ids).await;
+                return;
+            }
+            _ = timer.tick() => {
+                let file_ids = index_service.take_queued_files(Instant::now(), false);
+                flush(&admin_task_service, &file_service, &index_service, file_ids).await;
+
+                ticks += 1;
+
+                if ticks == sweep_ticks {
+                    ticks = 0;
+                    sweep(&file_service, &index_service, sweep_config.sample_size).await;
+                }
+            }
+        }
+    }
+}
+
+/// Indexes the files as they are now. The documents of files deleted or unready by now are
+/// deleted instead, so that they do not stay searchable. Files that could not be indexed are
+/// handed to a `retry-index` task.
+#[tracing::instrument(skip_all, fields(count = file_ids.len()))]
+async fn flush(
+    admin_task_service: &AdminTaskService,
+    file_service: &FileService,
+    index_service: &IndexService,
+    file_ids: Vec<Uuid>,
+) {
+    if file_ids.is_empty() {
+        return;
+    }
+
+    let files = match file_service.get_files(&file_ids).await {
+        Ok(files) => Vec::from_iter(files.into_iter().filter(|file| file.is_ready)),
+        Err(err) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to get queued files");
+            defer(admin_task_service, &file_ids).await;
+            return;
+        }
+    };
+
+    let ready_ids = HashSet::<Uuid>::from_iter(files.iter().map(|file| file.id));
+    let gone_ids = Vec::from_iter(
+        file_ids
+            .iter()
+            .copied()
+            .filter(|file_id| !ready_ids.contains(file_id)),
+    );
+
+    if !gone_ids.is_empty() {
+        if let Err(err) = index_service.delete_files(&gone_ids).await {
+            tracing::warn!(error = %ErrorChain(&err), count = gone_ids.len(), "failed to delete documents of queued files that are deleted or unready");
+        }
+    }
+
+    if files.is_empty() {
+        return;
+    }
+
+    let ready_ids = Vec::from_iter(files.iter().map(|file| file.id));
+    let collections = match file_service.get_file_collections(&ready_ids).await {
+        Ok(collections) => collections,
+        Err(err) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to get collections of queued files");
+            defer(admin_task_service, &ready_ids).await;
+            return;
+        }
+    };
+
+    if let Err(err) = index_service.index_files(&files, &collections).await {
+        tracing::warn!(error = %ErrorChain(&err), "failed to index queued files");
+        defer(admin_task_service, &ready_ids).await;
+    }
+}
+
+/// Deletes the documents of files that no longer exist from among the `sample_size` latest
+/// uploaded ones. The documents are only deleted once every page is listed, so that the deletions
+/// do not shift the pages.
+#[tracing::instrument(skip_all)]
+async fn sweep(file_service: &FileService, index_service: &IndexService, sample_size: usize) {
+    let mut sampled_count = 0;
+    let mut ghosts = Vec::new();
+
+    while sampled_count < sample_size {
+        let limit = SWEEP_PAGE_SIZE.min(sample_size - sampled_count);
+        let file_ids = match index_service.latest_file_ids(sampled_count, limit).await {
+            Ok(file_ids) => file_ids,
+            Err(err) => {
+                tracing::warn!(error = %ErrorChain(&err), "failed to list latest indexed files");
+                return;
+            }
+        };
+        let existing_ids = match file_service.find_existing_file_ids(&file_ids).await {
+            Ok(existing_ids) => HashSet::<Uuid>::from_iter(existing_ids),
+            Err(err) => {
+                tracing::warn!(error = %ErrorChain(&err), "failed to look up latest indexed files");
+                return;
+            }
+        };
+
+        sampled_count += file_ids.len();
+        ghosts.extend(
+            file_ids
+                .iter()
+                .filter(|file_id| !existing_ids.contains(file_id)),
+        );
+
+        if file_ids.len() < limit {
+            break;
+        }
+    }
+
+    if ghosts.is_empty() {
+        tracing::debug!(sampled_count, "no documents of deleted files found");
+        return;
+    }
+
+    match index_service.delete_files(&ghosts).await {
+        Ok(_) => {
+            tracing::info!(
+                sampled_count,
+                deleted_count = ghosts.len(),
+                "deleted documents of files that no longer exist"
+            );
+        }
+        Err(err) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to delete documents of files that no longer exist");
+        }
+    }
+}
+
+async fn defer(admin_task_service: &AdminTaskService, file_ids: &[Uuid]) {
+    let result = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::System,
+            None,
+            TaskKind::RetryIndex,
+            serde_json::json!({ "file_ids_to_index": file_ids }),
+            None,
+            false,
+            None,
+        )
+        .await;
+
+    if let Err(err) = result {
+        tracing::error!(error = %ErrorChain(&err), "failed to enqueue deferred indexing of queued files");
+    }
+}
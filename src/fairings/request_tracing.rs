@@ -0,0 +1,84 @@
+use crate::{
+    guards::{client_ip::ClientIp, request_span::RequestSpan},
+    telemetry::ErrorChain,
+};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use rocket::{
+    async_trait,
+    fairing::{Fairing, Info, Kind},
+    http::Header,
+    Data, Orbit, Request, Response, Rocket,
+};
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Opens a span for every request, tagged with a request id. The id is taken from the
+/// `X-Request-Id` header if the client sent a sane one, and is echoed back in the response. The
+/// span also records the client, as resolved by [`ClientIp`].
+pub struct RequestTracing {
+    tracer_provider: Option<SdkTracerProvider>,
+}
+
+impl RequestTracing {
+    pub fn new(tracer_provider: Option<SdkTracerProvider>) -> Self {
+        Self { tracer_provider }
+    }
+}
+
+#[async_trait]
+impl Fairing for RequestTracing {
+    fn info(&self) -> Info {
+        Info {
+            name: "request-tracing",
+            kind: Kind::Request | Kind::Response | Kind::Shutdown,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _data: &mut Data<'_>) {
+        let request_id = request
+            .headers()
+            .get_one(REQUEST_ID_HEADER)
+            .filter(|request_id| is_request_id_valid(request_id))
+            .map(|request_id| request_id.to_owned())
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let span = tracing::info_span!(
+            "request",
+            request_id = %request_id,
+            method = %request.method(),
+            uri = %request.uri(),
+            client_ip = %ClientIp::of(request),
+            status = tracing::field::Empty,
+        );
+
+        request.local_cache(|| RequestSpan { span, request_id });
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let request_span = request.local_cache(RequestSpan::disabled);
+        request_span.span.record("status", response.status().code);
+
+        if !request_span.request_id.is_empty() {
+            response.set_header(Header::new(
+                REQUEST_ID_HEADER,
+                request_span.request_id.clone(),
+            ));
+        }
+    }
+
+    async fn on_shutdown(&self, _rocket: &Rocket<Orbit>) {
+        if let Some(tracer_provider) = &self.tracer_provider {
+            if let Err(err) = tracer_provider.shutdown() {
+                tracing::warn!(error = %ErrorChain(&err), "failed to flush spans");
+            }
+        }
+    }
+}
+
+fn is_request_id_valid(request_id: &str) -> bool {
+    !request_id.is_empty()
+        && request_id.len() <= 128
+        && request_id
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.'))
+}
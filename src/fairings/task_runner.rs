@@ -0,0 +1,338 @@
+pub mod backfill_checksums;
+pub mod backfill_storage;
+pub mod bulk_delete_files;
+pub mod bundle_collection;
+pub mod collection_download_manifest;
+pub mod export_files;
+pub mod re_index_collection_files;
+pub mod re_index_collections;
+pub mod re_index_files;
+pub mod reconcile_storage;
+pub mod rename_tag;
+pub mod retry_index;
+
+use crate::{
+    interfaces::admins::{AdminTaskInitiator, AdminTaskStatus, TaskKind},
+    services::{
+        admin_task_service::{AdminTaskService, AdminTaskServiceError},
+        scheduled_task_service::ScheduledTaskService,
+    },
+    telemetry::ErrorChain,
+};
+use chrono::Utc;
+use rocket::{
+    async_trait,
+    fairing::{Fairing, Info, Kind},
+    Orbit, Rocket,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{sync::Arc, time::Duration};
+use thiserror::Error;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum TaskRunnerError {
+    #[error("admin task service failure: {0:#?}")]
+    AdminTask(#[from] AdminTaskServiceError),
+    #[error("scheduled task service failure: {0:#?}")]
+    ScheduledTask(#[from] crate::services::scheduled_task_service::ScheduledTaskServiceError),
+    #[error("failed to serialize or deserialize admin task metadata: {0:#?}")]
+    MetadataSerde(#[from] serde_json::Error),
+    #[error("task handler failure: {0:#?}")]
+    Handler(Box<dyn std::error::Error + Send + Sync>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskStep {
+    NotCompleted,
+    Completed,
+    /// Nothing could be done for now, e.g. while a dependency is down. The task is stepped again
+    /// on a later tick, without hurrying the runner like [`TaskStep::NotCompleted`] does.
+    Postponed,
+}
+
+/// Processes the admin tasks of a single name, one batch per [`TaskHandler::step`].
+///
+/// The runner persists the metadata after every step, so a task resumes from where it left off
+/// after a restart or when it is picked up again on the next tick.
+#[async_trait]
+pub trait TaskHandler: Send + Sync {
+    type Metadata: Serialize + DeserializeOwned + Send;
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    fn task_kind(&self) -> TaskKind;
+
+    async fn step(
+        &self,
+        task_id: Uuid,
+        metadata: &mut Self::Metadata,
+    ) -> Result<TaskStep, Self::Error>;
+}
+
+/// Object-safe counterpart of [`TaskHandler`] that works on raw metadata.
+#[async_trait]
+trait ErasedTaskHandler: Send + Sync {
+    fn task_kind(&self) -> TaskKind;
+
+    async fn step(
+        &self,
+        task_id: Uuid,
+        metadata: Value,
+    ) -> Result<(TaskStep, Value), TaskRunnerError>;
+}
+
+#[async_trait]
+impl<T> ErasedTaskHandler for T
+where
+    T: TaskHandler,
+{
+    fn task_kind(&self) -> TaskKind {
+        TaskHandler::task_kind(self)
+    }
+
+    async fn step(
+        &self,
+        task_id: Uuid,
+        metadata: Value,
+    ) -> Result<(TaskStep, Value), TaskRunnerError> {
+        let mut metadata: T::Metadata = serde_json::from_value(metadata)?;
+        let step = TaskHandler::step(self, task_id, &mut metadata)
+            .await
+            .map_err(|err| TaskRunnerError::Handler(Box::new(err)))?;
+
+        Ok((step, serde_json::to_value(metadata)?))
+    }
+}
+
+pub struct TaskRunner {
+    admin_task_service: AdminTaskService,
+    scheduled_task_service: ScheduledTaskService,
+    handlers: Vec<Arc<dyn ErasedTaskHandler>>,
+    stop_signal: Mutex<Option<tokio::sync::mpsc::Sender<()>>>,
+    task_handle: Mutex<Option<tokio::task::JoinHandle<()>>>,
+}
+
+impl TaskRunner {
+    pub fn new(
+        admin_task_service: AdminTaskService,
+        scheduled_task_service: ScheduledTaskService,
+    ) -> Self {
+        Self {
+            admin_task_service,
+            scheduled_task_service,
+            handlers: Vec::new(),
+            stop_signal: Mutex::new(None),
+            task_handle: Mutex::new(None),
+        }
+    }
+
+    pub fn with_handler(mut self, handler: impl TaskHandler + 'static) -> Self {
+        self.handlers.push(Arc::new(handler));
+        self
+    }
+
+    async fn create_runner_task(&self) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        let task_handle = tokio::spawn(runner_task(
+            rx,
+            self.admin_task_service.clone(),
+            self.scheduled_task_service.clone(),
+            self.handlers.clone(),
+        ));
+
+        *self.stop_signal.lock().await = Some(tx);
+        *self.task_handle.lock().await = Some(task_handle);
+    }
+}
+
+#[async_trait]
+impl Fairing for TaskRunner {
+    fn info(&self) -> Info {
+        Info {
+            name: "task-runner",
+            kind: Kind::Liftoff | Kind::Shutdown,
+        }
+    }
+
+    async fn on_liftoff(&self, _rocket: &Rocket<Orbit>) {
+        self.create_runner_task().await;
+    }
+
+    async fn on_shutdown(&self, _rocket: &Rocket<Orbit>) {
+        if let Some(tx) = self.stop_signal.lock().await.take() {
+            if let Err(err) = tx.send(()).await {
+                tracing::warn!(error = %ErrorChain(&err), "failed to send stop signal to task runner");
+                return;
+            }
+        }
+
+        if let Some(task_handle) = self.task_handle.lock().await.take() {
+            if let Err(err) = task_handle.await {
+                tracing::warn!(error = %ErrorChain(&err), "failed to wait for task runner to finish");
+            }
+        }
+    }
+}
+
+async fn runner_task(
+    mut stop_signal: tokio::sync::mpsc::Receiver<()>,
+    admin_task_service: AdminTaskService,
+    scheduled_task_service: ScheduledTaskService,
+    handlers: Vec<Arc<dyn ErasedTaskHandler>>,
+) {
+    let mut duration_secs = 10;
+
+    loop {
+        tokio::select! {
+            _ = stop_signal.recv() => {
+                return;
+            }
+            _ = tokio::time::sleep(Duration::from_secs(duration_secs)) => {
+                duration_secs = 10;
+
+                let result = enqueue_due_scheduled_tasks(
+                    &admin_task_service,
+                    &scheduled_task_service,
+                ).await;
+
+                if let Err(err) = result {
+                    tracing::error!(error = %ErrorChain(&err), "task runner on tick for scheduled tasks error");
+                }
+
+                let result = admin_task_service
+                    .cancel_tasks_with_unsuccessful_dependencies()
+                    .await;
+
+                if let Err(err) = result {
+                    tracing::error!(error = %ErrorChain(&err), "task runner on tick for task dependencies error");
+                }
+
+                for handler in &handlers {
+                    let result = runner_task_on_tick(&admin_task_service, handler.as_ref()).await;
+
+                    match result {
+                        Ok(Some(TaskStep::NotCompleted)) => {
+                            // keep going quickly while there is work left
+                            duration_secs = 1;
+                        }
+                        Ok(_) => {}
+                        Err(err) => {
+                            tracing::error!(
+                                task_name = %handler.task_kind(),
+                                error = %ErrorChain(&err),
+                                "task runner on tick failed"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn enqueue_due_scheduled_tasks(
+    admin_task_service: &AdminTaskService,
+    scheduled_task_service: &ScheduledTaskService,
+) -> Result<(), TaskRunnerError> {
+    let scheduled_tasks = scheduled_task_service
+        .claim_due_scheduled_tasks(Utc::now())
+        .await?;
+
+    for scheduled_task in scheduled_tasks {
+        if let TaskKind::Unknown(name) = &scheduled_task.name {
+            tracing::warn!(
+                scheduled_task_id = %scheduled_task.id,
+                "skipping scheduled task with unknown task name `{name}`"
+            );
+            continue;
+        }
+
+        let result = admin_task_service
+            .enqueue_task(
+                AdminTaskInitiator::System,
+                None,
+                scheduled_task.name,
+                scheduled_task.metadata,
+                None,
+                false,
+                None,
+            )
+            .await;
+
+        match result {
+            Ok(_) => {}
+            // the task already queued covers this run
+            Err(AdminTaskServiceError::AlreadyQueued { task_id }) => {
+                tracing::info!(
+                    scheduled_task_id = %scheduled_task.id,
+                    queued_task_id = %task_id,
+                    "skipping scheduled task whose kind is already queued"
+                );
+            }
+            Err(err) => {
+                return Err(err.into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[tracing::instrument(skip_all, fields(task_name = %handler.task_kind(), task_id = tracing::field::Empty))]
+async fn runner_task_on_tick(
+    admin_task_service: &AdminTaskService,
+    handler: &dyn ErasedTaskHandler,
+) -> Result<Option<TaskStep>, TaskRunnerError> {
+    let task = admin_task_service
+        .get_last_active_task(&handler.task_kind())
+        .await?;
+    let task = match task {
+        Some(admin_task) => admin_task,
+        None => {
+            return Ok(None);
+        }
+    };
+    let task_id = task.id;
+    tracing::Span::current().record("task_id", tracing::field::display(task_id));
+
+    admin_task_service
+        .update_task_status(task_id, AdminTaskStatus::InProgress)
+        .await?;
+
+    let (step, metadata) = match handler.step(task_id, task.metadata).await {
+        Ok(result) => result,
+        Err(err) => {
+            admin_task_service
+                .fail_task(task_id, &err.to_string())
+                .await?;
+            return Err(err);
+        }
+    };
+
+    match admin_task_service
+        .update_task_metadata(task_id, metadata)
+        .await
+    {
+        Ok(()) => {}
+        // the task could not resume from metadata it cannot keep
+        Err(err @ AdminTaskServiceError::MetadataTooLarge { .. }) => {
+            admin_task_service
+                .fail_task(task_id, &err.to_string())
+                .await?;
+            return Err(err.into());
+        }
+        Err(err) => {
+            return Err(err.into());
+        }
+    }
+
+    if step == TaskStep::Completed {
+        admin_task_service
+            .update_task_status(task_id, AdminTaskStatus::Completed)
+            .await?;
+    }
+
+    Ok(Some(step))
+}
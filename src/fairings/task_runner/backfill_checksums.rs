@@ -0,0 +1,209 @@
+use super::{TaskHandler, TaskStep};
+use crate::{
+    interfaces::admins::TaskKind,
+    services::{
+        file_service::FileService,
+        index_service::IndexService,
+        s3_service::{ObjectChecksum, ObjectStorage, S3ServiceError},
+    },
+};
+use rocket::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Most files checked per step; slower throttles check fewer, so that a step lasts about a second.
+const BATCH_SIZE: u32 = 100;
+
+#[derive(Error, Debug)]
+pub enum BackfillChecksumsError {
+    #[error("file service failure: {0:#?}")]
+    File(#[from] crate::services::file_service::FileServiceError),
+    #[error("index service failure: {0:#?}")]
+    Index(#[from] crate::services::index_service::IndexServiceError),
+    #[error("s3 service failure: {0:#?}")]
+    S3(#[from] Box<S3ServiceError>),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BackfillChecksumsMetadata {
+    files_per_second: u32,
+    #[serde(default)]
+    last_file_id: Option<Uuid>,
+    /// Files whose checksum S3 had stored.
+    #[serde(default)]
+    stored_count: u64,
+    /// Files whose object was downloaded and hashed.
+    #[serde(default)]
+    hashed_count: u64,
+    /// Files without an object; they keep lacking a checksum.
+    #[serde(default)]
+    unavailable_count: u64,
+    /// The counts of the last batch.
+    #[serde(default)]
+    last_batch: BatchCounts,
+    #[serde(default)]
+    meili_task_uids: Vec<u32>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy)]
+pub struct BatchCounts {
+    stored_count: u64,
+    hashed_count: u64,
+    unavailable_count: u64,
+}
+
+/// Pages through the ready files without a checksum in id order, fills in the checksum of each
+/// from its object, and re-indexes the files that got one.
+///
+/// At most `files_per_second` files are checked per second. A step that S3 fails transiently is
+/// postponed; the files it already checked are done, since only files without a checksum are
+/// listed.
+pub struct BackfillChecksumsHandler {
+    file_service: FileService,
+    index_service: IndexService,
+    object_storage: Arc<dyn ObjectStorage>,
+}
+
+impl BackfillChecksumsHandler {
+    pub fn new(
+        file_service: FileService,
+        index_service: IndexService,
+        object_storage: Arc<dyn ObjectStorage>,
+    ) -> Self {
+        Self {
+            file_service,
+            index_service,
+            object_storage,
+        }
+    }
+}
+
+#[async_trait]
+impl TaskHandler for BackfillChecksumsHandler {
+    type Metadata = BackfillChecksumsMetadata;
+    type Error = BackfillChecksumsError;
+
+    fn task_kind(&self) -> TaskKind {
+        TaskKind::BackfillChecksums
+    }
+
+    async fn step(
+        &self,
+        _task_id: Uuid,
+        metadata: &mut Self::Metadata,
+    ) -> Result<TaskStep, Self::Error> {
+        let started_at = Instant::now();
+        let files_per_second = metadata.files_per_second.max(1);
+        let files = self
+            .file_service
+            .list_files_without_checksum(
+                metadata.last_file_id,
+                files_per_second.min(BATCH_SIZE) as usize,
+            )
+            .await?;
+
+        if files.is_empty() {
+            return Ok(TaskStep::Completed);
+        }
+
+        let mut batch = BatchCounts::default();
+        let mut checksummed_file_ids = Vec::new();
+        let mut postponed = false;
+
+        for (file_id, storage) in &files {
+            let checksum = match self
+                .object_storage
+                .object_checksum(*file_id, storage.as_ref())
+                .await
+            {
+                Ok(checksum) => checksum,
+                Err(S3ServiceError::Transient { operation, error }) => {
+                    tracing::warn!(
+                        file_id = %file_id,
+                        operation,
+                        error = ?error,
+                        "failed to read object checksum; postponing checksum backfill"
+                    );
+                    postponed = true;
+                    break;
+                }
+                Err(err) => return Err(Box::new(err).into()),
+            };
+            let checksum = match checksum {
+                ObjectChecksum::Stored(checksum) => {
+                    batch.stored_count += 1;
+                    checksum
+                }
+                ObjectChecksum::Hashed(checksum) => {
+                    batch.hashed_count += 1;
+                    checksum
+                }
+                ObjectChecksum::Missing => {
+                    batch.unavailable_count += 1;
+                    metadata.last_file_id = Some(*file_id);
+                    continue;
+                }
+            };
+
+            if self
+                .file_service
+                .set_file_checksum(*file_id, &checksum)
+                .await?
+            {
+                checksummed_file_ids.push(*file_id);
+            }
+
+            metadata.last_file_id = Some(*file_id);
+        }
+
+        if !checksummed_file_ids.is_empty() {
+            let mut checksummed_files = Vec::with_capacity(checksummed_file_ids.len());
+
+            for file_id in &checksummed_file_ids {
+                if let Some(file) = self.file_service.get_file(*file_id).await? {
+                    checksummed_files.push(file);
+                }
+            }
+
+            let collections = self
+                .file_service
+                .get_file_collections(&checksummed_file_ids)
+                .await?;
+            let meili_task_uid = self
+                .index_service
+                .index_files(&checksummed_files, &collections)
+                .await?;
+            metadata.meili_task_uids.push(meili_task_uid);
+        }
+
+        metadata.stored_count += batch.stored_count;
+        metadata.hashed_count += batch.hashed_count;
+        metadata.unavailable_count += batch.unavailable_count;
+        metadata.last_batch = batch;
+
+        tracing::info!(
+            stored_count = batch.stored_count,
+            hashed_count = batch.hashed_count,
+            unavailable_count = batch.unavailable_count,
+            "backfilled a batch of checksums"
+        );
+
+        if postponed {
+            return Ok(TaskStep::Postponed);
+        }
+
+        let checked_count = files.len() as u32;
+        let budget = Duration::from_secs(1) * checked_count / files_per_second;
+
+        if let Some(remaining) = budget.checked_sub(started_at.elapsed()) {
+            tokio::time::sleep(remaining).await;
+        }
+
+        Ok(TaskStep::NotCompleted)
+    }
+}
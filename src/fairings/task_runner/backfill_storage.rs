@@ -0,0 +1,63 @@
+use super::{TaskHandler, TaskStep};
+use crate::{interfaces::admins::TaskKind, services::file_service::FileService};
+use rocket::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Number of files updated per step, so that no single update locks every file at once.
+const BATCH_SIZE: usize = 1000;
+
+#[derive(Error, Debug)]
+pub enum BackfillStorageError {
+    #[error("file service failure: {0:#?}")]
+    File(#[from] crate::services::file_service::FileServiceError),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BackfillStorageMetadata {
+    #[serde(default)]
+    backfilled_count: u64,
+}
+
+/// Records the configured bucket and region on the files that have no storage, i.e. those
+/// created before files recorded where they are stored, in batches until none is left.
+pub struct BackfillStorageHandler {
+    file_service: FileService,
+}
+
+impl BackfillStorageHandler {
+    pub fn new(file_service: FileService) -> Self {
+        Self { file_service }
+    }
+}
+
+#[async_trait]
+impl TaskHandler for BackfillStorageHandler {
+    type Metadata = BackfillStorageMetadata;
+    type Error = BackfillStorageError;
+
+    fn task_kind(&self) -> TaskKind {
+        TaskKind::BackfillStorage
+    }
+
+    async fn step(
+        &self,
+        _task_id: Uuid,
+        metadata: &mut Self::Metadata,
+    ) -> Result<TaskStep, Self::Error> {
+        let backfilled_count = self.file_service.backfill_storage(BATCH_SIZE).await?;
+        metadata.backfilled_count += backfilled_count;
+
+        tracing::info!(
+            count = backfilled_count,
+            "recorded storage on a batch of files"
+        );
+
+        if backfilled_count < BATCH_SIZE as u64 {
+            Ok(TaskStep::Completed)
+        } else {
+            Ok(TaskStep::NotCompleted)
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use super::{TaskHandler, TaskStep};
+use crate::{
+    interfaces::{
+        admins::{AdminTaskInitiator, TaskKind},
+        files::{FileStorage, UploadOutcome},
+        IndexingStatus,
+    },
+    services::{
+        admin_task_service::AdminTaskService, file_service::FileService,
+        index_service::IndexService, s3_service::ObjectStorage,
+    },
+    telemetry::ErrorChain,
+};
+use futures::{stream, StreamExt, TryStreamExt};
+use rocket::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Number of files deleted per step. Progress is persisted after every step, so a canceled task
+/// stops after the batch in flight.
+const BATCH_SIZE: usize = 500;
+/// Maximum number of files whose open uploads are aborted at the same time.
+const ABORT_UPLOADS_CONCURRENCY: usize = 16;
+/// Maximum number of protected files listed in the metadata; the rest are only counted.
+pub const MAX_RECORDED_PROTECTED_FILES: usize = 100;
+
+#[derive(Error, Debug)]
+pub enum BulkDeleteFilesError {
+    #[error("admin task service failure: {0:#?}")]
+    AdminTask(#[from] crate::services::admin_task_service::AdminTaskServiceError),
+    #[error("file service failure: {0:#?}")]
+    File(#[from] crate::services::file_service::FileServiceError),
+    #[error("index service failure: {0:#?}")]
+    Index(#[from] crate::services::index_service::IndexServiceError),
+    #[error("s3 service failure: {0:#?}")]
+    S3(#[from] Box<crate::services::s3_service::S3ServiceError>),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BulkDeleteFilesMetadata {
+    /// The number of files the deletion took on. The files themselves are queued in
+    /// `admin_task_queued_files` until processed.
+    file_count: usize,
+    /// Whether more files matched the query of the deletion than it took on.
+    #[serde(default)]
+    matches_truncated: bool,
+    processed_count: usize,
+    deleted_count: u64,
+    /// The number of files skipped because they are protected from deletion.
+    #[serde(default)]
+    protected_count: u64,
+    /// The first [`MAX_RECORDED_PROTECTED_FILES`] of the protected files.
+    #[serde(default)]
+    protected_file_ids: Vec<Uuid>,
+    /// The multipart uploads still open for the deleted files, aborted along with them.
+    #[serde(default)]
+    aborted_upload_count: u64,
+    #[serde(default)]
+    meili_task_uids: Vec<u32>,
+    /// Deferred once a batch could not be deleted from the index.
+    #[serde(default)]
+    indexing: IndexingStatus,
+    /// The `retry-index` tasks deleting the deferred batches from the index.
+    #[serde(default)]
+    retry_task_ids: Vec<Uuid>,
+}
+
+/// Deletes files in batches, skipping protected files and aborting the uploads still open for the
+/// deleted ones. A batch Meilisearch fails to delete, or
+/// that is skipped because Meilisearch is considered down, is still deleted from the bucket and
+/// the database; its index deletion is handed to a `retry-index` task.
+pub struct BulkDeleteFilesHandler {
+    admin_task_service: AdminTaskService,
+    file_service: FileService,
+    index_service: IndexService,
+    object_storage: Arc<dyn ObjectStorage>,
+}
+
+impl BulkDeleteFilesHandler {
+    pub fn new(
+        admin_task_service: AdminTaskService,
+        file_service: FileService,
+        index_service: IndexService,
+        object_storage: Arc<dyn ObjectStorage>,
+    ) -> Self {
+        Self {
+            admin_task_service,
+            file_service,
+            index_service,
+            object_storage,
+        }
+    }
+}
+
+#[async_trait]
+impl TaskHandler for BulkDeleteFilesHandler {
+    type Metadata = BulkDeleteFilesMetadata;
+    type Error = BulkDeleteFilesError;
+
+    fn task_kind(&self) -> TaskKind {
+        TaskKind::BulkDeleteFiles
+    }
+
+    async fn step(
+        &self,
+        task_id: Uuid,
+        metadata: &mut Self::Metadata,
+    ) -> Result<TaskStep, Self::Error> {
+        let queued_file_ids = self
+            .admin_task_service
+            .get_queued_file_ids(task_id, BATCH_SIZE)
+            .await?;
+        let batch = queued_file_ids.as_slice();
+
+        if batch.is_empty() {
+            return Ok(TaskStep::Completed);
+        }
+
+        tracing::info!(batch_size = batch.len(), "deleting batch of files");
+
+        let protected_file_ids = self.file_service.get_protected_file_ids(batch).await?;
+        let batch = Vec::from_iter(
+            batch
+                .iter()
+                .copied()
+                .filter(|file_id| !protected_file_ids.contains(file_id)),
+        );
+        let batch = batch.as_slice();
+
+        if !protected_file_ids.is_empty() {
+            tracing::info!(
+                count = protected_file_ids.len(),
+                "skipping protected files of batch"
+            );
+        }
+
+        // files created under an earlier configuration may live in another bucket
+        let storages = self.file_service.get_storages(batch).await?;
+        let mut storage_groups = HashMap::<Option<&FileStorage>, Vec<Uuid>>::new();
+
+        for file_id in batch {
+            storage_groups
+                .entry(storages.get(file_id))
+                .or_default()
+                .push(*file_id);
+        }
+
+        // uploads still open for the files would otherwise keep their parts in the bucket for good
+        let aborts = Vec::from_iter(batch.iter().map(|file_id| {
+            self.object_storage
+                .abort_all_uploads_for_key(*file_id, storages.get(file_id))
+        }));
+        let aborted_uploads = stream::iter(aborts)
+            .buffer_unordered(ABORT_UPLOADS_CONCURRENCY)
+            .try_collect::<Vec<_>>()
+            .await
+            .map_err(Box::new)?;
+
+        for aborted_uploads in aborted_uploads {
+            for failure in &aborted_uploads.failures {
+                tracing::warn!(error = %failure.error, "failed to abort upload `{}`", failure.upload_id);
+            }
+
+            for upload_id in &aborted_uploads.upload_ids {
+                self.file_service
+                    .record_upload_finished(upload_id, UploadOutcome::Aborted)
+                    .await?;
+            }
+
+            metadata.aborted_upload_count += aborted_uploads.upload_ids.len() as u64;
+        }
+
+        for (storage, file_ids) in storage_groups {
+            self.object_storage
+                .delete_files(&file_ids, storage)
+                .await
+                .map_err(Box::new)?;
+        }
+        let meili_task_uids = match self.index_service.delete_files(batch).await {
+            Ok(meili_task_uids) => meili_task_uids,
+            Err(err) => {
+                tracing::warn!(error = %ErrorChain(&err), "deferring index deletion of batch");
+
+                let retry_task = self
+                    .admin_task_service
+                    .enqueue_task(
+                        AdminTaskInitiator::System,
+                        None,
+                        TaskKind::RetryIndex,
+                        serde_json::json!({ "file_ids_to_delete": batch }),
+                        None,
+                        false,
+                        None,
+                    )
+                    .await?;
+                metadata.indexing = IndexingStatus::Deferred;
+                metadata.retry_task_ids.push(retry_task.id);
+
+                Vec::new()
+            }
+        };
+        // the earlier versions of the files become current again once they are gone
+        let superseded_file_ids = self.file_service.get_superseded_file_ids(batch).await?;
+        let deleted_count = self.file_service.delete_files(batch).await?;
+
+        for file_id in superseded_file_ids {
+            self.index_service.queue_file_index(file_id);
+        }
+
+        self.admin_task_service
+            .dequeue_file_ids(task_id, &queued_file_ids)
+            .await?;
+
+        metadata.processed_count += queued_file_ids.len();
+        metadata.deleted_count += deleted_count;
+        metadata.protected_count += protected_file_ids.len() as u64;
+        metadata.protected_file_ids.extend(
+            protected_file_ids.into_iter().take(
+                MAX_RECORDED_PROTECTED_FILES.saturating_sub(metadata.protected_file_ids.len()),
+            ),
+        );
+        metadata.meili_task_uids.extend(meili_task_uids);
+
+        if queued_file_ids.len() < BATCH_SIZE {
+            Ok(TaskStep::Completed)
+        } else {
+            Ok(TaskStep::NotCompleted)
+        }
+    }
+}
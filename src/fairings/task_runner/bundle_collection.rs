@@ -0,0 +1,218 @@
+use super::{TaskHandler, TaskStep};
+use crate::{
+    config::UploadConfig,
+    interfaces::{
+        admins::TaskKind,
+        files::{CreatingFile, File, UploadOutcome},
+    },
+    services::{
+        bundle_service::{BundleService, BUNDLE_TAG},
+        file_service::FileService,
+        index_service::IndexService,
+        s3_service::{ObjectStorage, S3ServiceError},
+    },
+};
+use rocket::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum BundleCollectionError {
+    #[error("bundle service failure: {0:#?}")]
+    Bundle(#[from] crate::services::bundle_service::BundleServiceError),
+    #[error("file service failure: {0:#?}")]
+    File(#[from] crate::services::file_service::FileServiceError),
+    #[error("index service failure: {0:#?}")]
+    Index(#[from] crate::services::index_service::IndexServiceError),
+    #[error("s3 service failure: {0:#?}")]
+    S3(#[from] Box<S3ServiceError>),
+    #[error("file `{0}` is no longer ready")]
+    FileNotReady(Uuid),
+    #[error("upload `{0}` of the bundle is gone")]
+    UploadGone(String),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BundleCollectionMetadata {
+    collection_id: Uuid,
+    /// The name of the bundle file.
+    name: String,
+    /// The files to bundle, as they were when the bundle was requested.
+    file_ids: Vec<Uuid>,
+    /// The file the bundle is stored as, for the client to download once the task completes.
+    #[serde(default)]
+    bundle_file_id: Option<Uuid>,
+    #[serde(default)]
+    upload_id: Option<String>,
+    #[serde(default)]
+    uploaded: bool,
+    #[serde(default)]
+    meili_task_uids: Vec<u32>,
+}
+
+/// Creates an unready file tagged `bundle` and starts its upload, then writes the zip of the files
+/// to that upload and marks the file as ready, and finally indexes it. A bundle that fails before
+/// it is ready stays an unready file, which the file GC purges.
+pub struct BundleCollectionHandler {
+    bundle_service: BundleService,
+    file_service: FileService,
+    index_service: IndexService,
+    object_storage: Arc<dyn ObjectStorage>,
+    upload: UploadConfig,
+}
+
+impl BundleCollectionHandler {
+    pub fn new(
+        bundle_service: BundleService,
+        file_service: FileService,
+        index_service: IndexService,
+        object_storage: Arc<dyn ObjectStorage>,
+        upload: UploadConfig,
+    ) -> Self {
+        Self {
+            bundle_service,
+            file_service,
+            index_service,
+            object_storage,
+            upload,
+        }
+    }
+
+    /// Gets the files to bundle, which must all still be ready.
+    async fn get_files(&self, file_ids: &[Uuid]) -> Result<Vec<File>, BundleCollectionError> {
+        let mut files = Vec::with_capacity(file_ids.len());
+
+        for &file_id in file_ids {
+            match self.file_service.get_file(file_id).await? {
+                Some(file) if file.is_ready => files.push(file),
+                _ => {
+                    return Err(BundleCollectionError::FileNotReady(file_id));
+                }
+            }
+        }
+
+        Ok(files)
+    }
+}
+
+#[async_trait]
+impl TaskHandler for BundleCollectionHandler {
+    type Metadata = BundleCollectionMetadata;
+    type Error = BundleCollectionError;
+
+    fn task_kind(&self) -> TaskKind {
+        TaskKind::BundleCollection
+    }
+
+    async fn step(
+        &self,
+        _task_id: Uuid,
+        metadata: &mut Self::Metadata,
+    ) -> Result<TaskStep, Self::Error> {
+        let (bundle_file_id, upload_id) = match (metadata.bundle_file_id, &metadata.upload_id) {
+            (Some(bundle_file_id), Some(upload_id)) => (bundle_file_id, upload_id.clone()),
+            _ => {
+                let files = self.get_files(&metadata.file_ids).await?;
+                let size = BundleService::bundle_size(&files);
+                let bundle_file = self
+                    .file_service
+                    .create_file(CreatingFile {
+                        name: metadata.name.clone(),
+                        size,
+                        mime_type: "application/zip".to_owned(),
+                        checksum: None,
+                        tags: Some(vec![BUNDLE_TAG.to_owned()]),
+                        allow_duplicate: false,
+                    })
+                    .await?;
+                let upload_id = self
+                    .object_storage
+                    .create_multipart_upload(
+                        bundle_file.id,
+                        &bundle_file.mime_type,
+                        bundle_file.storage.as_ref(),
+                    )
+                    .await
+                    .map_err(Box::new)?;
+
+                self.file_service
+                    .record_upload_started(
+                        bundle_file.id,
+                        &upload_id,
+                        self.upload.part_count(size),
+                        size,
+                    )
+                    .await?;
+
+                metadata.bundle_file_id = Some(bundle_file.id);
+                metadata.upload_id = Some(upload_id);
+
+                return Ok(TaskStep::NotCompleted);
+            }
+        };
+
+        if !metadata.uploaded {
+            let bundle_file = match self.file_service.get_file(bundle_file_id).await? {
+                Some(file) => file,
+                None => {
+                    return Err(BundleCollectionError::UploadGone(upload_id));
+                }
+            };
+            let files = self.get_files(&metadata.file_ids).await?;
+            let parts = self
+                .bundle_service
+                .write_bundle(
+                    bundle_file.id,
+                    &upload_id,
+                    bundle_file.storage.as_ref(),
+                    &files,
+                )
+                .await?;
+
+            match self
+                .object_storage
+                .complete_multipart_upload(
+                    bundle_file.id,
+                    upload_id.clone(),
+                    &parts,
+                    bundle_file.storage.as_ref(),
+                )
+                .await
+                .map_err(Box::new)?
+            {
+                Some(()) => {}
+                None => {
+                    return Err(BundleCollectionError::UploadGone(upload_id));
+                }
+            }
+
+            self.file_service
+                .mark_file_as_ready(bundle_file.id, &upload_id)
+                .await?;
+            self.file_service
+                .record_upload_finished(&upload_id, UploadOutcome::Completed)
+                .await?;
+
+            metadata.uploaded = true;
+
+            return Ok(TaskStep::NotCompleted);
+        }
+
+        if let Some(bundle_file) = self.file_service.get_file(bundle_file_id).await? {
+            let mut collections = self
+                .file_service
+                .get_file_collections(&[bundle_file.id])
+                .await?;
+            let collections = collections.remove(&bundle_file.id).unwrap_or_default();
+            let meili_task_uid = self
+                .index_service
+                .index_file(&bundle_file, &collections)
+                .await?;
+            metadata.meili_task_uids.push(meili_task_uid);
+        }
+
+        Ok(TaskStep::Completed)
+    }
+}
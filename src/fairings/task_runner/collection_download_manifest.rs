@@ -0,0 +1,76 @@
+use super::{TaskHandler, TaskStep};
+use crate::{interfaces::admins::TaskKind, services::export_service::ExportService};
+use rocket::async_trait;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum CollectionDownloadManifestError {
+    #[error("export service failure: {0:#?}")]
+    Export(#[from] crate::services::export_service::ExportServiceError),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CollectionDownloadManifestMetadata {
+    collection_id: Uuid,
+    /// The tags of the collection when the manifest was requested.
+    tags: Vec<String>,
+    #[serde(default)]
+    object_key: Option<String>,
+    #[serde(default)]
+    file_count: Option<u64>,
+    #[serde(default)]
+    missing_count: Option<u64>,
+}
+
+/// Writes the download manifest of a collection and uploads it, like an export, so that it can be
+/// downloaded through a single presigned URL. Its file URLs expire after the download URL
+/// duration, counted from when the task runs.
+pub struct CollectionDownloadManifestHandler {
+    export_service: ExportService,
+    download_url_duration: Duration,
+}
+
+impl CollectionDownloadManifestHandler {
+    pub fn new(export_service: ExportService, download_url_duration: Duration) -> Self {
+        Self {
+            export_service,
+            download_url_duration,
+        }
+    }
+}
+
+#[async_trait]
+impl TaskHandler for CollectionDownloadManifestHandler {
+    type Metadata = CollectionDownloadManifestMetadata;
+    type Error = CollectionDownloadManifestError;
+
+    fn task_kind(&self) -> TaskKind {
+        TaskKind::CollectionDownloadManifest
+    }
+
+    async fn step(
+        &self,
+        task_id: Uuid,
+        metadata: &mut Self::Metadata,
+    ) -> Result<TaskStep, Self::Error> {
+        let object_key = ExportService::manifest_object_key(task_id);
+        let (file_count, missing_count) = self
+            .export_service
+            .export_manifest_to_object(
+                metadata.collection_id,
+                &metadata.tags,
+                self.download_url_duration,
+                &object_key,
+            )
+            .await?;
+
+        metadata.object_key = Some(object_key);
+        metadata.file_count = Some(file_count);
+        metadata.missing_count = Some(missing_count);
+
+        Ok(TaskStep::Completed)
+    }
+}
@@ -0,0 +1,61 @@
+use super::{TaskHandler, TaskStep};
+use crate::{
+    interfaces::{admins::TaskKind, files::FileExportFormat},
+    services::export_service::ExportService,
+};
+use rocket::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum ExportFilesError {
+    #[error("export service failure: {0:#?}")]
+    Export(#[from] crate::services::export_service::ExportServiceError),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExportFilesMetadata {
+    format: FileExportFormat,
+    #[serde(default)]
+    object_key: Option<String>,
+    #[serde(default)]
+    file_count: Option<usize>,
+}
+
+pub struct ExportFilesHandler {
+    export_service: ExportService,
+}
+
+impl ExportFilesHandler {
+    pub fn new(export_service: ExportService) -> Self {
+        Self { export_service }
+    }
+}
+
+#[async_trait]
+impl TaskHandler for ExportFilesHandler {
+    type Metadata = ExportFilesMetadata;
+    type Error = ExportFilesError;
+
+    fn task_kind(&self) -> TaskKind {
+        TaskKind::ExportFiles
+    }
+
+    async fn step(
+        &self,
+        task_id: Uuid,
+        metadata: &mut Self::Metadata,
+    ) -> Result<TaskStep, Self::Error> {
+        let object_key = ExportService::object_key(task_id, metadata.format);
+        let file_count = self
+            .export_service
+            .export_files_to_object(metadata.format, &object_key)
+            .await?;
+
+        metadata.object_key = Some(object_key);
+        metadata.file_count = Some(file_count);
+
+        Ok(TaskStep::Completed)
+    }
+}
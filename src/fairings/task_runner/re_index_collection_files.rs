@@ -0,0 +1,125 @@
+use super::{TaskHandler, TaskStep};
+use crate::{
+    interfaces::admins::TaskKind,
+    services::{
+        file_service::FileService,
+        index_service::{IndexService, RejectedFile, MAX_RECORDED_REJECTED_FILES},
+    },
+};
+use rocket::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Number of files re-indexed per step.
+const BATCH_SIZE: usize = 500;
+
+#[derive(Error, Debug)]
+pub enum ReIndexCollectionFilesError {
+    #[error("file service failure: {0:#?}")]
+    File(#[from] crate::services::file_service::FileServiceError),
+    #[error("index service failure: {0:#?}")]
+    Index(#[from] crate::services::index_service::IndexServiceError),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReIndexCollectionFilesMetadata {
+    collection_id: Uuid,
+    /// The tags of the collection before and after the change. The files tagged with every tag of
+    /// either set are re-indexed, which covers both the files that left the collection and those
+    /// that joined it.
+    tag_sets: Vec<Vec<String>>,
+    /// Index into `tag_sets` of the set whose files are being re-indexed.
+    #[serde(default)]
+    tag_set_index: usize,
+    #[serde(default)]
+    last_file_id: Option<Uuid>,
+    #[serde(default)]
+    indexed_count: u64,
+    #[serde(default)]
+    meili_task_uids: Vec<u32>,
+    /// The files Meilisearch rejected and that were skipped, only the first few of them listed.
+    #[serde(default)]
+    rejected_count: u64,
+    #[serde(default)]
+    rejected_files: Vec<RejectedFile>,
+}
+
+/// Re-indexes the files a created, updated or deleted collection gained or lost, along with the
+/// collections each of them belongs to now.
+pub struct ReIndexCollectionFilesHandler {
+    file_service: FileService,
+    index_service: IndexService,
+}
+
+impl ReIndexCollectionFilesHandler {
+    pub fn new(file_service: FileService, index_service: IndexService) -> Self {
+        Self {
+            file_service,
+            index_service,
+        }
+    }
+}
+
+#[async_trait]
+impl TaskHandler for ReIndexCollectionFilesHandler {
+    type Metadata = ReIndexCollectionFilesMetadata;
+    type Error = ReIndexCollectionFilesError;
+
+    fn task_kind(&self) -> TaskKind {
+        TaskKind::ReIndexCollectionFiles
+    }
+
+    async fn step(
+        &self,
+        _task_id: Uuid,
+        metadata: &mut Self::Metadata,
+    ) -> Result<TaskStep, Self::Error> {
+        let tags = match metadata.tag_sets.get(metadata.tag_set_index) {
+            Some(tags) => tags,
+            None => {
+                return Ok(TaskStep::Completed);
+            }
+        };
+
+        let files = match tags.is_empty() {
+            // a collection without tags has no files
+            true => Vec::new(),
+            false => {
+                self.file_service
+                    .list_files_with_all_tags(tags, metadata.last_file_id, BATCH_SIZE)
+                    .await?
+            }
+        };
+        let last_file = match files.last() {
+            Some(file) => file,
+            None => {
+                metadata.tag_set_index += 1;
+                metadata.last_file_id = None;
+
+                return Ok(TaskStep::NotCompleted);
+            }
+        };
+
+        let file_ids = Vec::from_iter(files.iter().map(|file| file.id));
+        let collections = self.file_service.get_file_collections(&file_ids).await?;
+        let indexing = self
+            .index_service
+            .index_files_skipping_rejected(&files, &collections)
+            .await?;
+        let rejected_count = indexing.rejected_files.len();
+
+        metadata.indexed_count += (files.len() - rejected_count) as u64;
+        metadata.rejected_count += rejected_count as u64;
+        metadata.rejected_files.extend(
+            indexing
+                .rejected_files
+                .into_iter()
+                .take(MAX_RECORDED_REJECTED_FILES.saturating_sub(metadata.rejected_files.len())),
+        );
+        metadata.last_file_id = Some(last_file.id);
+        metadata.meili_task_uids.extend(indexing.meili_task_uids);
+
+        Ok(TaskStep::NotCompleted)
+    }
+}
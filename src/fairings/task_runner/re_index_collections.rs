@@ -0,0 +1,104 @@
+use super::{TaskHandler, TaskStep};
+use crate::{
+    interfaces::{admins::TaskKind, collections::CollectionCursor},
+    services::{collection_service::CollectionService, index_service::IndexService},
+};
+use rocket::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum ReIndexCollectionsError {
+    #[error("collection service failure: {0:#?}")]
+    Collection(#[from] crate::services::collection_service::CollectionServiceError),
+    #[error("index service failure: {0:#?}")]
+    Index(#[from] crate::services::index_service::IndexServiceError),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReIndexCollectionsMetadata {
+    /// Whether the collections index was emptied, so a resumed task does not empty it again.
+    /// Tasks that do not say, e.g. scheduled ones, keep the index as it is.
+    #[serde(default = "default_index_cleared")]
+    index_cleared: bool,
+    last_collection_id: Option<Uuid>,
+    last_collection_name: Option<String>,
+    #[serde(default)]
+    indexed_count: u64,
+    #[serde(default)]
+    meili_task_uids: Vec<u32>,
+}
+
+fn default_index_cleared() -> bool {
+    true
+}
+
+pub struct ReIndexCollectionsHandler {
+    collection_service: CollectionService,
+    index_service: IndexService,
+}
+
+impl ReIndexCollectionsHandler {
+    pub fn new(collection_service: CollectionService, index_service: IndexService) -> Self {
+        Self {
+            collection_service,
+            index_service,
+        }
+    }
+}
+
+#[async_trait]
+impl TaskHandler for ReIndexCollectionsHandler {
+    type Metadata = ReIndexCollectionsMetadata;
+    type Error = ReIndexCollectionsError;
+
+    fn task_kind(&self) -> TaskKind {
+        TaskKind::ReIndexCollections
+    }
+
+    async fn step(
+        &self,
+        _task_id: Uuid,
+        metadata: &mut Self::Metadata,
+    ) -> Result<TaskStep, Self::Error> {
+        if !metadata.index_cleared {
+            // Meilisearch processes the tasks of an index in order, so the deletion precedes the
+            // documents added by the next steps
+            let meili_task_uid = self.index_service.empty_collections_index().await?;
+            metadata.index_cleared = true;
+            metadata.meili_task_uids.push(meili_task_uid);
+
+            return Ok(TaskStep::NotCompleted);
+        }
+
+        let cursor = match (metadata.last_collection_id, &metadata.last_collection_name) {
+            (Some(last_collection_id), Some(last_collection_name)) => Some(CollectionCursor {
+                id: last_collection_id,
+                name: last_collection_name.clone(),
+            }),
+            _ => None,
+        };
+
+        let collections = self
+            .collection_service
+            .list_collections_with_file_counts(1000, cursor)
+            .await?;
+        let last_collection = match collections.last() {
+            Some((collection, _)) => collection,
+            None => {
+                // no more collections to index; task is completed
+                return Ok(TaskStep::Completed);
+            }
+        };
+
+        let meili_task_uid = self.index_service.index_collections(&collections).await?;
+
+        metadata.indexed_count += collections.len() as u64;
+        metadata.last_collection_id = Some(last_collection.id);
+        metadata.last_collection_name = Some(last_collection.name.clone());
+        metadata.meili_task_uids.push(meili_task_uid);
+
+        Ok(TaskStep::NotCompleted)
+    }
+}
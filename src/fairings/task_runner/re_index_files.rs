@@ -0,0 +1,132 @@
+use super::{TaskHandler, TaskStep};
+use crate::{
+    interfaces::{admins::TaskKind, files::FileCursor},
+    services::{
+        file_service::FileService,
+        index_service::{IndexService, RejectedFile, MAX_RECORDED_REJECTED_FILES},
+    },
+};
+use chrono::{DateTime, Utc};
+use rocket::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum ReIndexFilesError {
+    #[error("file service failure: {0:#?}")]
+    File(#[from] crate::services::file_service::FileServiceError),
+    #[error("index service failure: {0:#?}")]
+    Index(#[from] crate::services::index_service::IndexServiceError),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReIndexFilesMetadata {
+    /// Whether the files index was emptied, so a resumed task does not empty it again. Tasks that
+    /// do not say, e.g. scheduled ones, keep the index as it is.
+    #[serde(default = "default_index_cleared")]
+    index_cleared: bool,
+    last_file_id: Option<Uuid>,
+    last_file_uploaded_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    indexed_count: u64,
+    #[serde(default)]
+    meili_task_uids: Vec<u32>,
+    /// The files Meilisearch rejected and that were skipped, only the first few of them listed.
+    #[serde(default)]
+    rejected_count: u64,
+    #[serde(default)]
+    rejected_files: Vec<RejectedFile>,
+}
+
+fn default_index_cleared() -> bool {
+    true
+}
+
+/// Re-indexes every ready file into the live files index.
+///
+/// Uploads completing meanwhile index their file into the same index, so nothing needs to be
+/// caught up afterwards: a file indexed before the emptying step is listed again by the steps
+/// that follow it, since it is ready by then, and one indexed after it stays.
+pub struct ReIndexFilesHandler {
+    file_service: FileService,
+    index_service: IndexService,
+}
+
+impl ReIndexFilesHandler {
+    pub fn new(file_service: FileService, index_service: IndexService) -> Self {
+        Self {
+            file_service,
+            index_service,
+        }
+    }
+}
+
+#[async_trait]
+impl TaskHandler for ReIndexFilesHandler {
+    type Metadata = ReIndexFilesMetadata;
+    type Error = ReIndexFilesError;
+
+    fn task_kind(&self) -> TaskKind {
+        TaskKind::ReIndexFiles
+    }
+
+    async fn step(
+        &self,
+        _task_id: Uuid,
+        metadata: &mut Self::Metadata,
+    ) -> Result<TaskStep, Self::Error> {
+        if !metadata.index_cleared {
+            // Meilisearch processes the tasks of an index in order, so the deletion precedes the
+            // documents added by the next steps
+            let meili_task_uid = self.index_service.empty_files_index().await?;
+            metadata.index_cleared = true;
+            metadata.meili_task_uids.push(meili_task_uid);
+
+            return Ok(TaskStep::NotCompleted);
+        }
+
+        let cursor = match (metadata.last_file_id, metadata.last_file_uploaded_at) {
+            (Some(last_file_id), Some(last_file_uploaded_at)) => Some(FileCursor {
+                id: last_file_id,
+                uploaded_at: last_file_uploaded_at,
+            }),
+            _ => None,
+        };
+
+        // the cursor may point at a file deleted since the last step; paging on past it is fine
+        let files = self
+            .file_service
+            .list_files(1000, cursor, false, None)
+            .await?;
+        let last_file = match files.last() {
+            Some(file) => file,
+            None => {
+                // no more files to index; task is completed
+                return Ok(TaskStep::Completed);
+            }
+        };
+
+        let file_ids = Vec::from_iter(files.iter().map(|file| file.id));
+        let collections = self.file_service.get_file_collections(&file_ids).await?;
+        let indexing = self
+            .index_service
+            .index_files_skipping_rejected(&files, &collections)
+            .await?;
+        let rejected_count = indexing.rejected_files.len();
+
+        metadata.indexed_count += (files.len() - rejected_count) as u64;
+        metadata.rejected_count += rejected_count as u64;
+        metadata.rejected_files.extend(
+            indexing
+                .rejected_files
+                .into_iter()
+                .take(MAX_RECORDED_REJECTED_FILES.saturating_sub(metadata.rejected_files.len())),
+        );
+        metadata.last_file_id = Some(last_file.id);
+        metadata.last_file_uploaded_at = Some(last_file.uploaded_at);
+        metadata.meili_task_uids.extend(indexing.meili_task_uids);
+
+        Ok(TaskStep::NotCompleted)
+    }
+}
@@ -0,0 +1,166 @@
+use super::{TaskHandler, TaskStep};
+use crate::{
+    interfaces::admins::TaskKind,
+    services::{file_service::FileService, index_service::IndexService, s3_service::ObjectStorage},
+};
+use rocket::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, sync::Arc};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Number of orphan objects and missing files listed in the metadata; the rest are only counted.
+const MAX_RECORDED: usize = 1000;
+
+#[derive(Error, Debug)]
+pub enum ReconcileStorageError {
+    #[error("file service failure: {0:#?}")]
+    File(#[from] crate::services::file_service::FileServiceError),
+    #[error("index service failure: {0:#?}")]
+    Index(#[from] crate::services::index_service::IndexServiceError),
+    #[error("s3 service failure: {0:#?}")]
+    S3(#[from] Box<crate::services::s3_service::S3ServiceError>),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReconcileStorageMetadata {
+    #[serde(default)]
+    delete_orphans: bool,
+    #[serde(default)]
+    mark_missing_unready: bool,
+    #[serde(default)]
+    continuation_token: Option<String>,
+    #[serde(default)]
+    last_file_id: Option<Uuid>,
+    #[serde(default)]
+    scanned_count: u64,
+    #[serde(default)]
+    orphan_count: u64,
+    #[serde(default)]
+    missing_count: u64,
+    #[serde(default)]
+    orphan_objects: Vec<Uuid>,
+    #[serde(default)]
+    missing_file_ids: Vec<Uuid>,
+    #[serde(default)]
+    meili_task_uids: Vec<u32>,
+}
+
+/// Cross-checks the file objects in the bucket against the files table.
+///
+/// Object keys and file ids sort the same way, so each page of listed objects is compared with
+/// the files whose ids fall into the same key range. Orphan objects have no file row; missing
+/// files are ready rows without an object. Keys that are not file ids (e.g. exports) are skipped.
+pub struct ReconcileStorageHandler {
+    file_service: FileService,
+    index_service: IndexService,
+    object_storage: Arc<dyn ObjectStorage>,
+}
+
+impl ReconcileStorageHandler {
+    pub fn new(
+        file_service: FileService,
+        index_service: IndexService,
+        object_storage: Arc<dyn ObjectStorage>,
+    ) -> Self {
+        Self {
+            file_service,
+            index_service,
+            object_storage,
+        }
+    }
+}
+
+#[async_trait]
+impl TaskHandler for ReconcileStorageHandler {
+    type Metadata = ReconcileStorageMetadata;
+    type Error = ReconcileStorageError;
+
+    fn task_kind(&self) -> TaskKind {
+        TaskKind::ReconcileStorage
+    }
+
+    async fn step(
+        &self,
+        _task_id: Uuid,
+        metadata: &mut Self::Metadata,
+    ) -> Result<TaskStep, Self::Error> {
+        let page = self
+            .object_storage
+            .list_objects(None, metadata.continuation_token.clone())
+            .await
+            .map_err(Box::new)?;
+
+        let mut object_ids =
+            Vec::from_iter(page.keys.iter().filter_map(|key| Uuid::parse_str(key).ok()));
+        object_ids.sort_unstable();
+
+        // the last page also covers every file after the last listed object
+        let until = match (&page.continuation_token, object_ids.last()) {
+            (Some(_), Some(last_object_id)) => Some(Some(*last_object_id)),
+            (Some(_), None) => None,
+            (None, _) => Some(None),
+        };
+
+        if let Some(until) = until {
+            let files = self
+                .file_service
+                .list_file_states(metadata.last_file_id, until)
+                .await?;
+            let file_ids = HashSet::<Uuid>::from_iter(files.iter().map(|(id, _)| *id));
+            let object_id_set = HashSet::<Uuid>::from_iter(object_ids.iter().copied());
+
+            let orphan_objects = Vec::from_iter(
+                object_ids
+                    .iter()
+                    .copied()
+                    .filter(|object_id| !file_ids.contains(object_id)),
+            );
+            let missing_file_ids = Vec::from_iter(
+                files
+                    .iter()
+                    .filter(|(id, is_ready)| *is_ready && !object_id_set.contains(id))
+                    .map(|(id, _)| *id),
+            );
+
+            if metadata.delete_orphans && !orphan_objects.is_empty() {
+                self.object_storage
+                    .delete_files(&orphan_objects, None)
+                    .await
+                    .map_err(Box::new)?;
+            }
+
+            if metadata.mark_missing_unready && !missing_file_ids.is_empty() {
+                self.file_service
+                    .mark_files_as_unready(&missing_file_ids)
+                    .await?;
+                // unready files must not stay searchable
+                let meili_task_uids = self.index_service.delete_files(&missing_file_ids).await?;
+                metadata.meili_task_uids.extend(meili_task_uids);
+            }
+
+            metadata.orphan_count += orphan_objects.len() as u64;
+            metadata.missing_count += missing_file_ids.len() as u64;
+            record(&mut metadata.orphan_objects, orphan_objects);
+            record(&mut metadata.missing_file_ids, missing_file_ids);
+
+            if let Some(until) = until {
+                metadata.last_file_id = Some(until);
+            }
+        }
+
+        metadata.scanned_count += page.keys.len() as u64;
+        metadata.continuation_token = page.continuation_token;
+
+        if metadata.continuation_token.is_none() {
+            Ok(TaskStep::Completed)
+        } else {
+            Ok(TaskStep::NotCompleted)
+        }
+    }
+}
+
+fn record(recorded: &mut Vec<Uuid>, ids: Vec<Uuid>) {
+    let room = MAX_RECORDED.saturating_sub(recorded.len());
+    recorded.extend(ids.into_iter().take(room));
+}
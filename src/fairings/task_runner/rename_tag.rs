@@ -0,0 +1,159 @@
+use super::{TaskHandler, TaskStep};
+use crate::{
+    interfaces::admins::TaskKind,
+    services::{
+        collection_service::CollectionService, file_service::FileService,
+        index_service::IndexService,
+    },
+};
+use rocket::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Number of files renamed, and then re-indexed, per step.
+const BATCH_SIZE: usize = 500;
+
+#[derive(Error, Debug)]
+pub enum RenameTagError {
+    #[error("collection service failure: {0:#?}")]
+    Collection(#[from] crate::services::collection_service::CollectionServiceError),
+    #[error("file service failure: {0:#?}")]
+    File(#[from] crate::services::file_service::FileServiceError),
+    #[error("index service failure: {0:#?}")]
+    Index(#[from] crate::services::index_service::IndexServiceError),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RenameTagMetadata {
+    tag: String,
+    new_name: String,
+    /// What was previewed when the task was enqueued, kept for its summary.
+    #[serde(default)]
+    target_exists: bool,
+    #[serde(default)]
+    file_count: u64,
+    #[serde(default)]
+    merged_file_count: u64,
+    #[serde(default)]
+    collection_count: u64,
+    #[serde(default)]
+    merged_collection_count: u64,
+    #[serde(default)]
+    collections_renamed: bool,
+    #[serde(default)]
+    files_renamed: bool,
+    #[serde(default)]
+    renamed_file_count: u64,
+    #[serde(default)]
+    last_file_id: Option<Uuid>,
+    #[serde(default)]
+    indexed_count: u64,
+    #[serde(default)]
+    meili_task_uids: Vec<u32>,
+}
+
+/// Renames a tag on the collections at once, then on the files batch by batch, and finally
+/// re-indexes every file with the new name. Files that had the new name already are re-indexed
+/// too, since a merge may have made them members of collections that had the old one.
+pub struct RenameTagHandler {
+    collection_service: CollectionService,
+    file_service: FileService,
+    index_service: IndexService,
+}
+
+impl RenameTagHandler {
+    pub fn new(
+        collection_service: CollectionService,
+        file_service: FileService,
+        index_service: IndexService,
+    ) -> Self {
+        Self {
+            collection_service,
+            file_service,
+            index_service,
+        }
+    }
+}
+
+#[async_trait]
+impl TaskHandler for RenameTagHandler {
+    type Metadata = RenameTagMetadata;
+    type Error = RenameTagError;
+
+    fn task_kind(&self) -> TaskKind {
+        TaskKind::RenameTag
+    }
+
+    async fn step(
+        &self,
+        _task_id: Uuid,
+        metadata: &mut Self::Metadata,
+    ) -> Result<TaskStep, Self::Error> {
+        if !metadata.collections_renamed {
+            let collection_ids = self
+                .collection_service
+                .rename_tag(&metadata.tag, &metadata.new_name)
+                .await?;
+            let mut collections = Vec::with_capacity(collection_ids.len());
+
+            for collection_id in collection_ids {
+                if let Some(collection) = self
+                    .collection_service
+                    .get_collection_with_file_count(collection_id)
+                    .await?
+                {
+                    collections.push(collection);
+                }
+            }
+
+            if !collections.is_empty() {
+                let meili_task_uid = self.index_service.index_collections(&collections).await?;
+                metadata.meili_task_uids.push(meili_task_uid);
+            }
+
+            metadata.collections_renamed = true;
+
+            return Ok(TaskStep::NotCompleted);
+        }
+
+        if !metadata.files_renamed {
+            let file_ids = self
+                .file_service
+                .rename_tag_batch(&metadata.tag, &metadata.new_name, BATCH_SIZE)
+                .await?;
+
+            match file_ids.is_empty() {
+                true => metadata.files_renamed = true,
+                false => metadata.renamed_file_count += file_ids.len() as u64,
+            }
+
+            return Ok(TaskStep::NotCompleted);
+        }
+
+        let files = self
+            .file_service
+            .list_files_with_all_tags(
+                std::slice::from_ref(&metadata.new_name),
+                metadata.last_file_id,
+                BATCH_SIZE,
+            )
+            .await?;
+        let last_file = match files.last() {
+            Some(file) => file,
+            None => {
+                return Ok(TaskStep::Completed);
+            }
+        };
+
+        let file_ids = Vec::from_iter(files.iter().map(|file| file.id));
+        let collections = self.file_service.get_file_collections(&file_ids).await?;
+        let meili_task_uid = self.index_service.index_files(&files, &collections).await?;
+
+        metadata.indexed_count += files.len() as u64;
+        metadata.last_file_id = Some(last_file.id);
+        metadata.meili_task_uids.push(meili_task_uid);
+
+        Ok(TaskStep::NotCompleted)
+    }
+}
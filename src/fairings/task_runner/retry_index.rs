@@ -0,0 +1,175 @@
+use super::{TaskHandler, TaskStep};
+use crate::{
+    interfaces::admins::TaskKind,
+    services::{
+        collection_service::{CollectionService, CollectionServiceError},
+        file_service::{FileService, FileServiceError},
+        index_service::{IndexService, IndexServiceError},
+    },
+};
+use rocket::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Failed attempts after which the task fails instead of waiting for Meilisearch again. Attempts
+/// skipped while Meilisearch is considered down do not count.
+const MAX_ATTEMPTS: u64 = 20;
+
+#[derive(Error, Debug)]
+pub enum RetryIndexError {
+    #[error("index service failure: {0:#?}")]
+    Index(#[from] IndexServiceError),
+    #[error("file service failure: {0:#?}")]
+    File(#[from] FileServiceError),
+    #[error("collection service failure: {0:#?}")]
+    Collection(#[from] CollectionServiceError),
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RetryIndexMetadata {
+    /// Files to index as they are when the task runs; files deleted or unready by then are skipped.
+    #[serde(default)]
+    file_ids_to_index: Vec<Uuid>,
+    #[serde(default)]
+    file_ids_to_delete: Vec<Uuid>,
+    /// Collections to index as they are when the task runs; deleted ones are skipped.
+    #[serde(default)]
+    collection_ids_to_index: Vec<Uuid>,
+    #[serde(default)]
+    collection_ids_to_delete: Vec<Uuid>,
+    /// Whether the index changes were accepted by Meilisearch.
+    #[serde(default)]
+    applied: bool,
+    #[serde(default)]
+    attempt_count: u64,
+    #[serde(default)]
+    last_error: Option<String>,
+    #[serde(default)]
+    meili_task_uids: Vec<u32>,
+}
+
+/// Applies the index changes a change deferred while Meilisearch was down, postponing itself
+/// until Meilisearch is reachable again.
+pub struct RetryIndexHandler {
+    collection_service: CollectionService,
+    file_service: FileService,
+    index_service: IndexService,
+}
+
+impl RetryIndexHandler {
+    pub fn new(
+        collection_service: CollectionService,
+        file_service: FileService,
+        index_service: IndexService,
+    ) -> Self {
+        Self {
+            collection_service,
+            file_service,
+            index_service,
+        }
+    }
+
+    /// Applies every change, returning the uids of the Meilisearch tasks. The changes are
+    /// idempotent, so a retry after a partial failure applies them all again.
+    async fn apply(&self, metadata: &RetryIndexMetadata) -> Result<Vec<u32>, RetryIndexError> {
+        let mut meili_task_uids = Vec::new();
+
+        if !metadata.file_ids_to_index.is_empty() {
+            let mut files = Vec::with_capacity(metadata.file_ids_to_index.len());
+
+            for &file_id in &metadata.file_ids_to_index {
+                if let Some(file) = self.file_service.get_file(file_id).await? {
+                    if file.is_ready {
+                        files.push(file);
+                    }
+                }
+            }
+
+            if !files.is_empty() {
+                let file_ids = Vec::from_iter(files.iter().map(|file| file.id));
+                let collections = self.file_service.get_file_collections(&file_ids).await?;
+                meili_task_uids.push(self.index_service.index_files(&files, &collections).await?);
+            }
+        }
+
+        if !metadata.file_ids_to_delete.is_empty() {
+            meili_task_uids.extend(
+                self.index_service
+                    .delete_files(&metadata.file_ids_to_delete)
+                    .await?,
+            );
+        }
+
+        if !metadata.collection_ids_to_index.is_empty() {
+            let mut collections = Vec::with_capacity(metadata.collection_ids_to_index.len());
+
+            for &collection_id in &metadata.collection_ids_to_index {
+                if let Some(collection) = self
+                    .collection_service
+                    .get_collection_with_file_count(collection_id)
+                    .await?
+                {
+                    collections.push(collection);
+                }
+            }
+
+            if !collections.is_empty() {
+                meili_task_uids.push(self.index_service.index_collections(&collections).await?);
+            }
+        }
+
+        if !metadata.collection_ids_to_delete.is_empty() {
+            meili_task_uids.extend(
+                self.index_service
+                    .delete_collections(&metadata.collection_ids_to_delete)
+                    .await?,
+            );
+        }
+
+        Ok(meili_task_uids)
+    }
+}
+
+#[async_trait]
+impl TaskHandler for RetryIndexHandler {
+    type Metadata = RetryIndexMetadata;
+    type Error = RetryIndexError;
+
+    fn task_kind(&self) -> TaskKind {
+        TaskKind::RetryIndex
+    }
+
+    async fn step(
+        &self,
+        _task_id: Uuid,
+        metadata: &mut Self::Metadata,
+    ) -> Result<TaskStep, Self::Error> {
+        if metadata.applied {
+            return Ok(TaskStep::Completed);
+        }
+
+        match self.apply(metadata).await {
+            Ok(meili_task_uids) => {
+                metadata.applied = true;
+                metadata.meili_task_uids.extend(meili_task_uids);
+
+                Ok(TaskStep::Completed)
+            }
+            Err(RetryIndexError::Index(IndexServiceError::CircuitOpen { .. })) => {
+                Ok(TaskStep::Postponed)
+            }
+            Err(err) => {
+                metadata.attempt_count += 1;
+
+                if MAX_ATTEMPTS <= metadata.attempt_count {
+                    return Err(err);
+                }
+
+                metadata.last_error = Some(err.to_string());
+
+                Ok(TaskStep::Postponed)
+            }
+        }
+    }
+}
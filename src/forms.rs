@@ -1 +1,3 @@
 pub mod date_time_utc;
+pub mod json_object;
+pub mod part_range;
@@ -3,6 +3,7 @@ use rocket::{
     data::ToByteUnit,
     form::{DataField, Error, FromFormField, Result, ValueField},
 };
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DateTimeUtcFormField {
@@ -34,3 +35,17 @@ impl<'v> FromFormField<'v> for DateTimeUtcFormField {
         None
     }
 }
+
+impl JsonSchema for DateTimeUtcFormField {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        DateTime::<Utc>::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        DateTime::<Utc>::json_schema(gen)
+    }
+}
@@ -0,0 +1,88 @@
+use rocket::form::{Error, FromFormField, Result, ValueField};
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Metadata, Schema, SchemaObject, StringValidation},
+    JsonSchema,
+};
+use serde_json::{Map, Value};
+
+/// Maximum length of a [`JsonObjectFormField`] in bytes.
+pub const JSON_OBJECT_MAX_LENGTH: usize = 4096;
+/// Maximum nesting depth of a [`JsonObjectFormField`], counting the object itself.
+pub const JSON_OBJECT_MAX_DEPTH: usize = 4;
+
+/// A JSON object passed as a form value, e.g. `{"file_id":"..."}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonObjectFormField {
+    pub object: Map<String, Value>,
+}
+
+impl JsonObjectFormField {
+    /// Parses a JSON object, answering why the value is not one otherwise.
+    pub fn parse(value: &str) -> std::result::Result<Self, String> {
+        if JSON_OBJECT_MAX_LENGTH < value.len() {
+            return Err(format!(
+                "json object must be at most {JSON_OBJECT_MAX_LENGTH} bytes long"
+            ));
+        }
+
+        let value =
+            serde_json::from_str::<Value>(value).map_err(|e| format!("invalid json: {}", e))?;
+
+        if JSON_OBJECT_MAX_DEPTH < depth(&value) {
+            return Err(format!(
+                "json object must be nested at most {JSON_OBJECT_MAX_DEPTH} levels deep"
+            ));
+        }
+
+        match value {
+            Value::Object(object) => Ok(Self { object }),
+            _ => Err("must be a json object".to_owned()),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'v> FromFormField<'v> for JsonObjectFormField {
+    fn from_value(field: ValueField<'v>) -> Result<'v, Self> {
+        Ok(Self::parse(field.value).map_err(Error::validation)?)
+    }
+
+    fn default() -> Option<Self> {
+        None
+    }
+}
+
+fn depth(value: &Value) -> usize {
+    match value {
+        Value::Object(object) => 1 + object.values().map(depth).max().unwrap_or(0),
+        Value::Array(array) => 1 + array.iter().map(depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+impl JsonSchema for JsonObjectFormField {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        "JsonObject".to_owned()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                description: Some("A JSON object, e.g. `{\"file_id\":\"...\"}`.".to_owned()),
+                ..Metadata::default()
+            })),
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                max_length: Some(JSON_OBJECT_MAX_LENGTH as u32),
+                ..StringValidation::default()
+            })),
+            ..SchemaObject::default()
+        }
+        .into()
+    }
+}
@@ -0,0 +1,69 @@
+use rocket::form::{Error, FromFormField, Result, ValueField};
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Schema, SchemaObject, StringValidation},
+    JsonSchema,
+};
+
+/// An inclusive range of multipart upload part numbers, written as `<first>-<last>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PartRangeFormField {
+    pub first: u32,
+    pub last: u32,
+}
+
+impl PartRangeFormField {
+    /// Parses `<first>-<last>`, answering why the value is not a part range otherwise.
+    pub fn parse(value: &str) -> std::result::Result<Self, String> {
+        let (first, last) = value
+            .split_once('-')
+            .ok_or_else(|| "part range must be `<first>-<last>`".to_owned())?;
+        let first = first
+            .trim()
+            .parse::<u32>()
+            .map_err(|e| format!("invalid first part number: {}", e))?;
+        let last = last
+            .trim()
+            .parse::<u32>()
+            .map_err(|e| format!("invalid last part number: {}", e))?;
+
+        if first == 0 || last < first {
+            return Err("part range must start at 1 or later and must not be reversed".to_owned());
+        }
+
+        Ok(Self { first, last })
+    }
+}
+
+#[rocket::async_trait]
+impl<'v> FromFormField<'v> for PartRangeFormField {
+    fn from_value(field: ValueField<'v>) -> Result<'v, Self> {
+        Ok(Self::parse(field.value).map_err(Error::validation)?)
+    }
+
+    fn default() -> Option<Self> {
+        None
+    }
+}
+
+impl JsonSchema for PartRangeFormField {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        "PartRange".to_owned()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                pattern: Some("^[0-9]+-[0-9]+$".to_owned()),
+                ..StringValidation::default()
+            })),
+            ..SchemaObject::default()
+        }
+        .into()
+    }
+}
@@ -0,0 +1,4 @@
+pub mod authenticated_admin;
+pub mod batch_json;
+pub mod client_ip;
+pub mod request_span;
@@ -0,0 +1,162 @@
+use crate::{
+    interfaces::admins::{Admin, AdminRole},
+    services::admin_service::AdminService,
+    telemetry::ErrorChain,
+};
+use rocket::{
+    http::Status,
+    request::{FromRequest, Outcome},
+    Request,
+};
+use rocket_okapi::{
+    gen::OpenApiGenerator,
+    okapi::openapi3::{Object, SecurityRequirement, SecurityScheme, SecuritySchemeData},
+    request::{OpenApiFromRequest, RequestHeaderInput},
+};
+use std::ops::Deref;
+
+const SECURITY_SCHEME_NAME: &str = "AdminSession";
+
+/// Guards a route behind an admin session of any role, passed as `Authorization: Bearer <token>`.
+/// Missing, unknown and expired tokens are rejected with `401 Unauthorized`.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedAdmin {
+    pub admin: Admin,
+    pub token: String,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuthenticatedAdmin {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let token = match req
+            .headers()
+            .get_one("Authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+        {
+            Some(token) => token.trim(),
+            None => {
+                return Outcome::Error((Status::Unauthorized, ()));
+            }
+        };
+
+        let admin_service = match req.rocket().state::<AdminService>() {
+            Some(admin_service) => admin_service,
+            None => {
+                tracing::error!("admin service is not managed");
+                return Outcome::Error((Status::InternalServerError, ()));
+            }
+        };
+
+        match admin_service.authenticate(token).await {
+            Ok(Some(admin)) => Outcome::Success(Self {
+                admin,
+                token: token.to_owned(),
+            }),
+            Ok(None) => Outcome::Error((Status::Unauthorized, ())),
+            Err(err) => {
+                tracing::error!(error = %ErrorChain(&err), "failed to authenticate admin");
+                Outcome::Error((Status::InternalServerError, ()))
+            }
+        }
+    }
+}
+
+impl<'r> OpenApiFromRequest<'r> for AuthenticatedAdmin {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        let security_scheme = SecurityScheme {
+            description: Some("Session token issued by `POST /admins/sessions`.".to_owned()),
+            data: SecuritySchemeData::Http {
+                scheme: "bearer".to_owned(),
+                bearer_format: None,
+            },
+            extensions: Object::default(),
+        };
+        let mut security_requirement = SecurityRequirement::new();
+        security_requirement.insert(SECURITY_SCHEME_NAME.to_owned(), Vec::new());
+
+        Ok(RequestHeaderInput::Security(
+            SECURITY_SCHEME_NAME.to_owned(),
+            security_scheme,
+            security_requirement,
+        ))
+    }
+}
+
+/// Guards a route behind an admin session of the editor role or above. Sessions of lower roles
+/// are rejected with `403 Forbidden`.
+#[derive(Debug, Clone)]
+pub struct RequireEditor(pub AuthenticatedAdmin);
+
+/// Guards a route behind an admin session of the admin role. Sessions of lower roles are
+/// rejected with `403 Forbidden`.
+#[derive(Debug, Clone)]
+pub struct RequireAdmin(pub AuthenticatedAdmin);
+
+impl Deref for RequireEditor {
+    type Target = AuthenticatedAdmin;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Deref for RequireAdmin {
+    type Target = AuthenticatedAdmin;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+async fn require_role(req: &Request<'_>, role: AdminRole) -> Outcome<AuthenticatedAdmin, ()> {
+    match req.guard::<AuthenticatedAdmin>().await {
+        Outcome::Success(admin) if role <= admin.admin.role => Outcome::Success(admin),
+        Outcome::Success(_) => Outcome::Error((Status::Forbidden, ())),
+        Outcome::Error(err) => Outcome::Error(err),
+        Outcome::Forward(status) => Outcome::Forward(status),
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequireEditor {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        require_role(req, AdminRole::Editor).await.map(Self)
+    }
+}
+
+impl<'r> OpenApiFromRequest<'r> for RequireEditor {
+    fn from_request_input(
+        gen: &mut OpenApiGenerator,
+        name: String,
+        required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        AuthenticatedAdmin::from_request_input(gen, name, required)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequireAdmin {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        require_role(req, AdminRole::Admin).await.map(Self)
+    }
+}
+
+impl<'r> OpenApiFromRequest<'r> for RequireAdmin {
+    fn from_request_input(
+        gen: &mut OpenApiGenerator,
+        name: String,
+        required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        AuthenticatedAdmin::from_request_input(gen, name, required)
+    }
+}
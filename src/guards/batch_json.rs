@@ -0,0 +1,81 @@
+use rocket::{
+    data::{ByteUnit, Data, FromData, Limits, Outcome},
+    http::Status,
+    request::local_cache_once,
+    serde::json::{Error, Json},
+    Request,
+};
+use rocket_okapi::{gen::OpenApiGenerator, okapi::openapi3::RequestBody, request::OpenApiFromData};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::io;
+
+/// The data limit of plain [`Json`] bodies.
+pub const JSON_LIMIT: ByteUnit = ByteUnit::Mebibyte(1);
+/// The data limit of [`BatchJson`] bodies.
+pub const BATCH_JSON_LIMIT: ByteUnit = ByteUnit::Mebibyte(16);
+/// The name [`BATCH_JSON_LIMIT`] is configured under in the Rocket limits.
+pub const BATCH_JSON_LIMIT_NAME: &str = "batch-json";
+
+/// The data limit the body of the request was read under, for the `413` catcher to report.
+pub struct BodyLimit(pub ByteUnit);
+
+impl BodyLimit {
+    /// The limit recorded by a [`BatchJson`] guard, or the plain JSON limit otherwise.
+    pub fn of(req: &Request<'_>) -> ByteUnit {
+        req.local_cache(|| BodyLimit(req.limits().get("json").unwrap_or(Limits::JSON)))
+            .0
+    }
+}
+
+/// A JSON body like [`Json`], read under the larger [`BATCH_JSON_LIMIT`], for the routes that take
+/// many items at once.
+#[derive(Debug)]
+pub struct BatchJson<T>(pub T);
+
+impl<T> BatchJson<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: Deserialize<'r>> FromData<'r> for BatchJson<T> {
+    type Error = Error<'r>;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let limit = req
+            .limits()
+            .get(BATCH_JSON_LIMIT_NAME)
+            .unwrap_or(BATCH_JSON_LIMIT);
+        req.local_cache(|| BodyLimit(limit));
+
+        // answers like `Json` does: `413` past the limit, `422` for a body of the wrong shape and
+        // `400` for anything else
+        let string = match data.open(limit).into_string().await {
+            Ok(string) if string.is_complete() => string.into_inner(),
+            Ok(_) => {
+                let err = io::Error::new(io::ErrorKind::UnexpectedEof, "data limit exceeded");
+                return Outcome::Error((Status::PayloadTooLarge, Error::Io(err)));
+            }
+            Err(err) => {
+                return Outcome::Error((Status::BadRequest, Error::Io(err)));
+            }
+        };
+        let string = local_cache_once!(req, string);
+
+        match serde_json::from_str(string) {
+            Ok(value) => Outcome::Success(BatchJson(value)),
+            Err(err) if err.classify() == serde_json::error::Category::Data => {
+                Outcome::Error((Status::UnprocessableEntity, Error::Parse(string, err)))
+            }
+            Err(err) => Outcome::Error((Status::BadRequest, Error::Parse(string, err))),
+        }
+    }
+}
+
+impl<'r, T: JsonSchema + Deserialize<'r>> OpenApiFromData<'r> for BatchJson<T> {
+    fn request_body(gen: &mut OpenApiGenerator) -> rocket_okapi::Result<RequestBody> {
+        Json::<T>::request_body(gen)
+    }
+}
@@ -0,0 +1,165 @@
+use crate::config::ProxyConfig;
+use rocket::{
+    request::{FromRequest, Outcome},
+    Request,
+};
+use rocket_okapi::{
+    gen::OpenApiGenerator,
+    request::{OpenApiFromRequest, RequestHeaderInput},
+};
+use std::{
+    fmt::{self, Display, Formatter},
+    net::{IpAddr, SocketAddr},
+};
+
+/// The address of the client that sent the request. Behind trusted proxies it is taken from the
+/// `Forwarded` or `X-Forwarded-For` header, otherwise it is the address of the peer. It is `None`
+/// only if the peer is unknown, as for requests dispatched by a local client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl ClientIp {
+    /// Resolves the client of the request once, caching it for the rest of the request.
+    pub fn of(request: &Request<'_>) -> Self {
+        *request.local_cache(|| {
+            let peer = match request.remote() {
+                Some(remote) => remote.ip(),
+                None => {
+                    return ClientIp(None);
+                }
+            };
+            let proxy = match request.rocket().state::<ProxyConfig>() {
+                Some(proxy) => proxy,
+                None => {
+                    return ClientIp(Some(peer));
+                }
+            };
+            let headers = request.headers();
+
+            ClientIp(Some(resolve_client_ip(
+                proxy,
+                peer,
+                headers.get("Forwarded"),
+                headers.get("X-Forwarded-For"),
+            )))
+        })
+    }
+}
+
+impl Display for ClientIp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(address) => address.fmt(f),
+            None => f.write_str("unknown"),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ClientIp {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(ClientIp::of(req))
+    }
+}
+
+impl<'r> OpenApiFromRequest<'r> for ClientIp {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        Ok(RequestHeaderInput::None)
+    }
+}
+
+/// Resolves the client of a request that came from `peer`, given the values of its `Forwarded`
+/// and `X-Forwarded-For` headers. `Forwarded` wins if both are present.
+///
+/// Every proxy appends the address it got the request from, so only the hops appended by trusted
+/// proxies can be believed. Walking the hops from the nearest one, the first address that is not
+/// a trusted proxy is the client; anything further out may be made up by the client itself. A hop
+/// that is obfuscated or garbled cannot be followed, so the proxy that appended it is taken as the
+/// client then.
+pub fn resolve_client_ip<'h>(
+    proxy: &ProxyConfig,
+    peer: IpAddr,
+    forwarded: impl IntoIterator<Item = &'h str>,
+    x_forwarded_for: impl IntoIterator<Item = &'h str>,
+) -> IpAddr {
+    if !proxy.is_trusted(peer) {
+        return peer;
+    }
+
+    let forwarded = Vec::from_iter(forwarded);
+    let hops = if forwarded.is_empty() {
+        Vec::from_iter(
+            x_forwarded_for
+                .into_iter()
+                .flat_map(|value| value.split(','))
+                .map(parse_node),
+        )
+    } else {
+        Vec::from_iter(
+            forwarded
+                .into_iter()
+                .flat_map(|value| value.split(','))
+                .map(forwarded_for),
+        )
+    };
+    let mut client = peer;
+
+    for hop in hops.into_iter().rev() {
+        match hop {
+            Some(address) if proxy.is_trusted(address) => {
+                client = address;
+            }
+            Some(address) => {
+                return address;
+            }
+            None => {
+                return client;
+            }
+        }
+    }
+
+    client
+}
+
+/// The `for` parameter of an element of the `Forwarded` header, e.g.
+/// `for="[2001:db8::17]:4711";proto=https`.
+fn forwarded_for(element: &str) -> Option<IpAddr> {
+    element.split(';').find_map(|pair| {
+        let (name, value) = pair.split_once('=')?;
+
+        if !name.trim().eq_ignore_ascii_case("for") {
+            return None;
+        }
+
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value);
+
+        parse_node(value)
+    })
+}
+
+/// An address with an optional port, IPv6 addresses in brackets if they have one. Obfuscated
+/// identifiers such as `unknown` or `_hidden` are not addresses.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim();
+
+    if let Ok(address) = node.parse() {
+        return Some(address);
+    }
+
+    if let Ok(address) = node.parse::<SocketAddr>() {
+        return Some(address.ip());
+    }
+
+    // a bracketed IPv6 address without a port
+    node.strip_prefix('[')?.strip_suffix(']')?.parse().ok()
+}
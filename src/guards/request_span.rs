@@ -0,0 +1,47 @@
+use rocket::{
+    request::{FromRequest, Outcome},
+    Request,
+};
+use rocket_okapi::{
+    gen::OpenApiGenerator,
+    request::{OpenApiFromRequest, RequestHeaderInput},
+};
+use tracing::Span;
+
+/// The span of the request being handled, opened by the
+/// [`RequestTracing`](crate::fairings::request_tracing::RequestTracing) fairing. Routes use it as
+/// the parent of their own span, so everything they call is traced under the request.
+#[derive(Debug, Clone)]
+pub struct RequestSpan {
+    pub span: Span,
+    pub request_id: String,
+}
+
+impl RequestSpan {
+    /// Used when the fairing is not attached.
+    pub fn disabled() -> Self {
+        Self {
+            span: Span::none(),
+            request_id: String::new(),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequestSpan {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(req.local_cache(RequestSpan::disabled).clone())
+    }
+}
+
+impl<'r> OpenApiFromRequest<'r> for RequestSpan {
+    fn from_request_input(
+        _gen: &mut OpenApiGenerator,
+        _name: String,
+        _required: bool,
+    ) -> rocket_okapi::Result<RequestHeaderInput> {
+        Ok(RequestHeaderInput::None)
+    }
+}
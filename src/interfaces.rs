@@ -1,11 +1,282 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
 
 pub mod admins;
 pub mod collections;
 pub mod files;
+#[cfg(feature = "testing")]
+pub mod fixtures;
+pub mod tags;
+pub mod timestamp;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SimpleOk {
     pub ok: bool,
 }
+
+/// The result of a mutation along with the admin task that records it.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WithAdminTask<T> {
+    #[serde(flatten)]
+    pub result: T,
+    /// Null if the task could not be enqueued or the mutation changed nothing.
+    pub admin_task_id: Option<Uuid>,
+}
+
+/// Whether the search index reflects a bulk change yet. Bulk changes commit to the database even
+/// if Meilisearch is down, in which case their index changes are deferred to `retry-index` admin
+/// tasks.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum IndexingStatus {
+    #[default]
+    Done,
+    Deferred,
+    /// Queued to be indexed together with further changes to the same document.
+    Queued,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Health {
+    pub search_engine: SearchEngineCircuit,
+    pub index_write_queue: IndexWriteQueue,
+}
+
+/// File index writes waiting to be sent to Meilisearch together.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexWriteQueue {
+    /// Files waiting to be indexed.
+    pub queued_count: usize,
+    /// Writes merged into a write already queued for the same file since startup.
+    pub coalesced_count: u64,
+}
+
+/// Whether Meilisearch is considered down after consecutive failed calls.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchEngineCircuit {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    /// Seconds until the next call probes Meilisearch again, while the circuit is open.
+    pub retry_after_secs: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum CircuitState {
+    /// Calls are attempted.
+    Closed,
+    /// Calls fail without being attempted.
+    Open,
+    /// A single call is attempted to probe Meilisearch, while the others still fail.
+    HalfOpen,
+}
+
+/// A request field rejected by validation.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    /// Path of the field in the request body, e.g. `filters[0]`.
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+pub const SEARCH_MIN_LIMIT: i64 = 1;
+pub const SEARCH_MAX_LIMIT: i64 = 100;
+pub const SEARCH_MAX_OFFSET: i64 = 10_000;
+pub const SEARCH_MAX_QUERY_LENGTH: usize = 1024;
+
+/// Deserializes a field of an update that may be absent, `null` or set, for use with
+/// `deserialize_with` and `default` on an `Option<Option<T>>`: absent leaves it `None`, while
+/// `null` makes it `Some(None)` to clear the value.
+pub fn deserialize_nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Validates the paging and query text shared by the search queries.
+pub fn validate_search_query(q: &str, limit: i64, offset: i64) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+
+    if SEARCH_MAX_QUERY_LENGTH < q.chars().count() {
+        errors.push(FieldError::new(
+            "q",
+            format!("must be at most {SEARCH_MAX_QUERY_LENGTH} characters long"),
+        ));
+    }
+
+    if !(SEARCH_MIN_LIMIT..=SEARCH_MAX_LIMIT).contains(&limit) {
+        errors.push(FieldError::new(
+            "limit",
+            format!("must be between {SEARCH_MIN_LIMIT} and {SEARCH_MAX_LIMIT}"),
+        ));
+    }
+
+    if !(0..=SEARCH_MAX_OFFSET).contains(&offset) {
+        errors.push(FieldError::new(
+            "offset",
+            format!("must be between 0 and {SEARCH_MAX_OFFSET}"),
+        ));
+    }
+
+    errors
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchMatchingStrategy {
+    /// Drops query words from the end until documents match.
+    Last,
+    /// Only matches documents containing every query word.
+    All,
+}
+
+/// The searchable attributes shared by the file and collection indexes.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchAttribute {
+    Name,
+    Tags,
+}
+
+impl SearchAttribute {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            SearchAttribute::Name => "name",
+            SearchAttribute::Tags => "tags",
+        }
+    }
+}
+
+/// Highlighted and cropped copies of the searchable attributes of a hit.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHighlights {
+    pub name: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// The subset of the search index settings managed through the API, for both indexes.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSettings {
+    pub files: SearchIndexSettings,
+    pub collections: SearchIndexSettings,
+}
+
+/// Indexes left out are not touched.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatingIndexSettings {
+    pub files: Option<SearchIndexSettings>,
+    pub collections: Option<SearchIndexSettings>,
+}
+
+impl UpdatingIndexSettings {
+    pub fn is_valid(&self) -> bool {
+        [&self.files, &self.collections]
+            .into_iter()
+            .flatten()
+            .all(|settings| settings.is_valid())
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchIndexSettings {
+    /// Maps a word to the words treated as equivalent to it.
+    pub synonyms: BTreeMap<String, Vec<String>>,
+    pub stop_words: Vec<String>,
+    pub typo_tolerance: TypoTolerance,
+    /// Must list every ranking rule exactly once.
+    pub ranking_rules: Vec<RankingRule>,
+}
+
+impl SearchIndexSettings {
+    pub fn is_valid(&self) -> bool {
+        let ranking_rules = HashSet::<RankingRule>::from_iter(self.ranking_rules.iter().copied());
+        let is_ranking_rules_valid = self.ranking_rules.len() == RankingRule::ALL.len()
+            && ranking_rules.len() == RankingRule::ALL.len();
+        let is_words_valid = self
+            .stop_words
+            .iter()
+            .chain(self.synonyms.keys())
+            .chain(self.synonyms.values().flatten())
+            .all(|word| !word.trim().is_empty());
+
+        is_ranking_rules_valid && is_words_valid && self.typo_tolerance.is_valid()
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct TypoTolerance {
+    pub enabled: bool,
+    /// Minimum word length accepting one typo.
+    pub min_word_size_for_one_typo: u8,
+    /// Minimum word length accepting two typos. Must not be less than the one for one typo.
+    pub min_word_size_for_two_typos: u8,
+}
+
+impl TypoTolerance {
+    pub fn is_valid(&self) -> bool {
+        self.min_word_size_for_one_typo <= self.min_word_size_for_two_typos
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum RankingRule {
+    Words,
+    Typo,
+    Proximity,
+    Attribute,
+    Sort,
+    Exactness,
+}
+
+impl RankingRule {
+    pub const ALL: [RankingRule; 6] = [
+        RankingRule::Words,
+        RankingRule::Typo,
+        RankingRule::Proximity,
+        RankingRule::Attribute,
+        RankingRule::Sort,
+        RankingRule::Exactness,
+    ];
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            RankingRule::Words => "words",
+            RankingRule::Typo => "typo",
+            RankingRule::Proximity => "proximity",
+            RankingRule::Attribute => "attribute",
+            RankingRule::Sort => "sort",
+            RankingRule::Exactness => "exactness",
+        }
+    }
+
+    pub fn parse(rule: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|ranking_rule| ranking_rule.to_str() == rule)
+    }
+}
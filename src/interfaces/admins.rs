@@ -1,55 +1,266 @@
+use super::FieldError;
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use rocket::FromFormField;
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Schema, SchemaObject},
+    JsonSchema,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+use thiserror::Error;
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Admin {
     pub id: Uuid,
     pub username: String,
     pub email: String,
+    pub role: AdminRole,
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
     pub joined_at: DateTime<Utc>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// What an admin may do. Each role can do everything the roles before it can.
+#[derive(
+    sqlx::Type,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Default,
+)]
+#[serde(rename_all = "camelCase")]
+#[sqlx(type_name = "admin_role")]
+#[sqlx(rename_all = "snake_case")]
+pub enum AdminRole {
+    /// Reads files, collections and tasks.
+    #[default]
+    Viewer,
+    /// Also creates, updates and deletes files and collections.
+    Editor,
+    /// Also runs admin tasks and manages admins.
+    Admin,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CreatingAdmin {
     pub username: String,
     pub password: String,
     pub email: String,
+    #[serde(default)]
+    pub role: AdminRole,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BootstrappingAdmin {
+    pub username: String,
+    pub password: String,
+    pub email: String,
+    /// The `BOOTSTRAP_TOKEN` of the server, needed only if there already are admins.
+    pub bootstrap_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatingAdmin {
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub email: Option<String>,
+    pub role: Option<AdminRole>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminLogin {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminSession {
+    pub token: String,
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
+    pub logined_at: DateTime<Utc>,
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
+    pub last_used_at: DateTime<Utc>,
+    /// When the session expires unless it is used again.
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
+    pub idle_expired_at: DateTime<Utc>,
+    /// When the session expires regardless of use.
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
+    pub expired_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestingPasswordReset {
+    pub email: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResettingPassword {
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct AdminTaskPreview {
     pub id: Uuid,
     pub initiator: AdminTaskInitiator,
-    pub name: String,
+    /// The admin whose request enqueued the task; absent for system tasks and older tasks.
+    pub initiated_by: Option<AdminTaskInitiatedBy>,
+    pub name: TaskKind,
+    /// What the task did or is doing, in a line; see [`TaskKind::summarize`].
+    pub summary: Option<String>,
     pub status: AdminTaskStatus,
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
     pub enqueued_at: DateTime<Utc>,
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminTaskInitiatedBy {
+    pub id: Uuid,
+    pub username: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ReIndexAdminTask {
     pub file_task: AdminTask,
     pub collection_task: AdminTask,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcilingStorage {
+    /// Deletes the objects that have no file.
+    #[serde(default)]
+    pub delete_orphans: bool,
+    /// Marks the ready files without an object as not ready and drops them from the index.
+    #[serde(default)]
+    pub mark_missing_unready: bool,
+}
+
+/// Default throttle of checksum backfills.
+pub const CHECKSUM_BACKFILL_DEFAULT_FILES_PER_SECOND: u32 = 20;
+pub const CHECKSUM_BACKFILL_MAX_FILES_PER_SECOND: u32 = 1000;
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BackfillingChecksums {
+    /// How many files to check per second at most, so that the backfill does not saturate S3.
+    #[serde(default = "checksum_backfill_default_files_per_second")]
+    pub files_per_second: u32,
+}
+
+impl Default for BackfillingChecksums {
+    fn default() -> Self {
+        Self {
+            files_per_second: CHECKSUM_BACKFILL_DEFAULT_FILES_PER_SECOND,
+        }
+    }
+}
+
+impl BackfillingChecksums {
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        if !(1..=CHECKSUM_BACKFILL_MAX_FILES_PER_SECOND).contains(&self.files_per_second) {
+            errors.push(FieldError::new(
+                "filesPerSecond",
+                format!("must be between 1 and {CHECKSUM_BACKFILL_MAX_FILES_PER_SECOND}"),
+            ));
+        }
+
+        errors
+    }
+}
+
+fn checksum_backfill_default_files_per_second() -> u32 {
+    CHECKSUM_BACKFILL_DEFAULT_FILES_PER_SECOND
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct AdminTask {
     pub id: Uuid,
     pub initiator: AdminTaskInitiator,
-    pub name: String,
+    /// The admin whose request enqueued the task; absent for system tasks and older tasks.
+    pub initiated_by: Option<AdminTaskInitiatedBy>,
+    pub name: TaskKind,
     pub metadata: serde_json::Value,
     pub status: AdminTaskStatus,
+    pub depends_on: Option<Uuid>,
+    pub error: Option<String>,
+    #[serde(
+        default,
+        serialize_with = "crate::interfaces::timestamp::serialize_option",
+        deserialize_with = "crate::interfaces::timestamp::deserialize_option"
+    )]
+    pub failed_at: Option<DateTime<Utc>>,
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
     pub enqueued_at: DateTime<Utc>,
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(
+    FromFormField, Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum AdminTaskExportFormat {
+    Ndjson,
+}
+
+#[derive(
+    sqlx::Type, Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash,
+)]
 #[serde(rename_all = "camelCase")]
 #[sqlx(type_name = "admin_task_initiator")]
 #[sqlx(rename_all = "snake_case")]
@@ -58,7 +269,9 @@ pub enum AdminTaskInitiator {
     System,
 }
 
-#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(
+    sqlx::Type, Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash,
+)]
 #[serde(rename_all = "camelCase")]
 #[sqlx(type_name = "admin_task_status")]
 #[sqlx(rename_all = "snake_case")]
@@ -69,3 +282,1012 @@ pub enum AdminTaskStatus {
     Completed,
     Failed,
 }
+
+/// What an admin task does, stored as its `name`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TaskKind {
+    ReIndexFiles,
+    ReIndexCollections,
+    UploadFile,
+    UpdateFile,
+    DeleteFile,
+    BulkDeleteFiles,
+    /// Adds and removes tags on many files at once.
+    BulkTagFiles,
+    ExportFiles,
+    ReconcileStorage,
+    CreateCollection,
+    UpdateCollection,
+    DeleteCollection,
+    FileGc,
+    Housekeeping,
+    /// Applies index changes that a change deferred while Meilisearch was down.
+    RetryIndex,
+    /// Re-indexes the files a collection change affects, so that their search hits show the
+    /// collections they belong to.
+    ReIndexCollectionFiles,
+    /// Renames a tag on every file and collection, merging it into the new name where both are
+    /// present.
+    RenameTag,
+    /// Fills in the checksums of ready files that lack one, from S3 or by hashing their objects.
+    BackfillChecksums,
+    /// Records the configured bucket and region on the files created before files recorded where
+    /// they are stored.
+    BackfillStorage,
+    /// Uploads a download manifest of a collection too large to generate one right away.
+    CollectionDownloadManifest,
+    /// Packs the files of a collection into a zip, stored as a new file tagged `bundle`.
+    BundleCollection,
+    /// A name stored in the database that no kind matches, e.g. one written by another version.
+    /// Such tasks are listed but never enqueued or processed.
+    Unknown(String),
+}
+
+impl TaskKind {
+    /// Every kind but [`TaskKind::Unknown`].
+    pub const ALL: [TaskKind; 21] = [
+        TaskKind::ReIndexFiles,
+        TaskKind::ReIndexCollections,
+        TaskKind::UploadFile,
+        TaskKind::UpdateFile,
+        TaskKind::DeleteFile,
+        TaskKind::BulkDeleteFiles,
+        TaskKind::BulkTagFiles,
+        TaskKind::ExportFiles,
+        TaskKind::ReconcileStorage,
+        TaskKind::CreateCollection,
+        TaskKind::UpdateCollection,
+        TaskKind::DeleteCollection,
+        TaskKind::FileGc,
+        TaskKind::Housekeeping,
+        TaskKind::RetryIndex,
+        TaskKind::ReIndexCollectionFiles,
+        TaskKind::RenameTag,
+        TaskKind::BackfillChecksums,
+        TaskKind::BackfillStorage,
+        TaskKind::CollectionDownloadManifest,
+        TaskKind::BundleCollection,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            TaskKind::ReIndexFiles => "re-index-files",
+            TaskKind::ReIndexCollections => "re-index-collections",
+            TaskKind::UploadFile => "upload-file",
+            TaskKind::UpdateFile => "update-file",
+            TaskKind::DeleteFile => "delete-file",
+            TaskKind::BulkDeleteFiles => "bulk-delete-files",
+            TaskKind::BulkTagFiles => "bulk-tag-files",
+            TaskKind::ExportFiles => "export-files",
+            TaskKind::ReconcileStorage => "reconcile-storage",
+            TaskKind::CreateCollection => "create-collection",
+            TaskKind::UpdateCollection => "update-collection",
+            TaskKind::DeleteCollection => "delete-collection",
+            TaskKind::FileGc => "file-gc",
+            TaskKind::Housekeeping => "housekeeping",
+            TaskKind::RetryIndex => "retry-index",
+            TaskKind::ReIndexCollectionFiles => "re-index-collection-files",
+            TaskKind::RenameTag => "rename-tag",
+            TaskKind::BackfillChecksums => "backfill-checksums",
+            TaskKind::BackfillStorage => "backfill-storage",
+            TaskKind::CollectionDownloadManifest => "collection-download-manifest",
+            TaskKind::BundleCollection => "bundle-collection",
+            TaskKind::Unknown(name) => name,
+        }
+    }
+
+    /// Whether at most one task of this kind may be pending or in progress at a time. These kinds
+    /// work on everything at once, so a second one queued behind the first would only redo it.
+    pub fn is_singleton(&self) -> bool {
+        matches!(
+            self,
+            TaskKind::ReIndexFiles
+                | TaskKind::ReIndexCollections
+                | TaskKind::ReconcileStorage
+                | TaskKind::BackfillChecksums
+                | TaskKind::BackfillStorage
+        )
+    }
+
+    /// Reads a name stored in the database, keeping unknown names as [`TaskKind::Unknown`].
+    pub fn from_name(name: String) -> Self {
+        name.parse().unwrap_or(TaskKind::Unknown(name))
+    }
+
+    /// Describes a task of this kind from its metadata in a line, e.g. `Deleted file <id>`.
+    /// Metadata missing what the summary needs, and unknown kinds, yield `None`.
+    pub fn summarize(&self, metadata: &serde_json::Value) -> Option<String> {
+        let str_field = |key: &str| metadata.get(key)?.as_str();
+        let u64_field = |key: &str| metadata.get(key)?.as_u64();
+
+        let summary = match self {
+            TaskKind::ReIndexFiles => {
+                format!(
+                    "Re-indexed {} files{}",
+                    format_count(u64_field("indexed_count")?),
+                    format_rejected(u64_field("rejected_count").unwrap_or(0))
+                )
+            }
+            TaskKind::ReIndexCollections => format!(
+                "Re-indexed {} collections",
+                format_count(u64_field("indexed_count")?)
+            ),
+            TaskKind::UploadFile => match (str_field("file_name"), u64_field("size")) {
+                (Some(name), Some(size)) => {
+                    format!("Indexed file '{name}' ({})", format_size(size))
+                }
+                _ => format!("Indexed file {}", str_field("file_id")?),
+            },
+            TaskKind::UpdateFile => {
+                let file_id = str_field("file_id")?;
+
+                match metadata
+                    .pointer("/delta/name")
+                    .and_then(|name| name.as_str())
+                {
+                    Some(name) => format!("Updated file {file_id}, renamed to '{name}'"),
+                    None => format!("Updated file {file_id}"),
+                }
+            }
+            TaskKind::DeleteFile => format!("Deleted file {}", str_field("file_id")?),
+            TaskKind::BulkDeleteFiles => {
+                let total = u64_field("file_count")?;
+                let protected_count = metadata
+                    .get("protected_count")
+                    .and_then(|count| count.as_u64())
+                    .unwrap_or(0);
+                let summary = format!(
+                    "Deleted {} files, {}/{} processed",
+                    format_count(u64_field("deleted_count")?),
+                    format_count(u64_field("processed_count")?),
+                    format_count(total)
+                );
+
+                let summary = match protected_count {
+                    0 => summary,
+                    _ => format!(
+                        "{summary}, {} protected files skipped",
+                        format_count(protected_count)
+                    ),
+                };
+
+                match metadata
+                    .get("matches_truncated")
+                    .and_then(|value| value.as_bool())
+                {
+                    Some(true) => format!("{summary}, more files matched than were deleted"),
+                    _ => summary,
+                }
+            }
+            TaskKind::BulkTagFiles => format!(
+                "Updated the tags of {} files, {} tags added, {} removed",
+                format_count(u64_field("updated_count")?),
+                format_count(u64_field("added_count")?),
+                format_count(u64_field("removed_count")?)
+            ),
+            TaskKind::ExportFiles => {
+                let format = str_field("format")?;
+
+                match u64_field("file_count") {
+                    Some(file_count) => {
+                        format!("Exported {} files as {format}", format_count(file_count))
+                    }
+                    None => format!("Exporting files as {format}"),
+                }
+            }
+            TaskKind::ReconcileStorage => format!(
+                "Scanned {} objects, {} orphaned, {} files missing",
+                format_count(u64_field("scanned_count")?),
+                format_count(u64_field("orphan_count")?),
+                format_count(u64_field("missing_count")?)
+            ),
+            TaskKind::CreateCollection => format!(
+                "Created collection '{}'",
+                metadata.pointer("/content/name")?.as_str()?
+            ),
+            TaskKind::UpdateCollection => {
+                let collection_id = str_field("collection_id")?;
+
+                match metadata
+                    .pointer("/delta/name")
+                    .and_then(|name| name.as_str())
+                {
+                    Some(name) => {
+                        format!("Updated collection {collection_id}, renamed to '{name}'")
+                    }
+                    None => format!("Updated collection {collection_id}"),
+                }
+            }
+            TaskKind::DeleteCollection => {
+                format!("Deleted collection {}", str_field("collection_id")?)
+            }
+            TaskKind::FileGc => match metadata.get("success")?.as_bool()? {
+                true => match u64_field("protected_count").unwrap_or(0) {
+                    0 => format!(
+                        "Purged {} unready files",
+                        format_count(u64_field("deleted_count")?)
+                    ),
+                    protected_count => format!(
+                        "Purged {} unready files, {} protected files skipped",
+                        format_count(u64_field("deleted_count")?),
+                        format_count(protected_count)
+                    ),
+                },
+                false => "Failed to purge unready files".to_owned(),
+            },
+            TaskKind::Housekeeping => {
+                let tables = metadata.get("tables")?.as_object()?;
+                let deleted_count: u64 = tables
+                    .values()
+                    .filter_map(|table| table.get("deleted_count")?.as_u64())
+                    .sum();
+                let failed_count = tables
+                    .values()
+                    .filter(|table| table.get("error").is_some())
+                    .count();
+
+                match failed_count {
+                    0 => format!("Pruned {} rows", format_count(deleted_count)),
+                    _ => format!(
+                        "Pruned {} rows, failed to prune {failed_count} tables",
+                        format_count(deleted_count)
+                    ),
+                }
+            }
+            TaskKind::RetryIndex => {
+                let change_count = [
+                    "file_ids_to_index",
+                    "file_ids_to_delete",
+                    "collection_ids_to_index",
+                    "collection_ids_to_delete",
+                ]
+                .into_iter()
+                .filter_map(|key| metadata.get(key)?.as_array())
+                .map(|ids| ids.len() as u64)
+                .sum::<u64>();
+                let applied = metadata
+                    .get("applied")
+                    .and_then(|applied| applied.as_bool());
+
+                match applied.unwrap_or(false) {
+                    true => format!(
+                        "Applied {} deferred index changes",
+                        format_count(change_count)
+                    ),
+                    false => format!(
+                        "Applying {} deferred index changes, {} failed attempts",
+                        format_count(change_count),
+                        format_count(u64_field("attempt_count").unwrap_or(0))
+                    ),
+                }
+            }
+            TaskKind::ReIndexCollectionFiles => format!(
+                "Re-indexed {} files of collection {}{}",
+                format_count(u64_field("indexed_count")?),
+                str_field("collection_id")?,
+                format_rejected(u64_field("rejected_count").unwrap_or(0))
+            ),
+            TaskKind::RenameTag => format!(
+                "Renamed tag '{}' to '{}' on {} files, merged with existing tag on {}",
+                str_field("tag")?,
+                str_field("new_name")?,
+                format_count(u64_field("file_count")?),
+                format_count(u64_field("merged_file_count")?)
+            ),
+            TaskKind::BackfillChecksums => format!(
+                "Backfilled checksums of {} files, {} hashed, {} objects unavailable",
+                format_count(u64_field("stored_count")? + u64_field("hashed_count")?),
+                format_count(u64_field("hashed_count")?),
+                format_count(u64_field("unavailable_count")?)
+            ),
+            TaskKind::BackfillStorage => format!(
+                "Recorded storage on {} files",
+                format_count(u64_field("backfilled_count")?)
+            ),
+            TaskKind::CollectionDownloadManifest => {
+                let collection_id = str_field("collection_id")?;
+
+                match u64_field("file_count") {
+                    Some(file_count) => format!(
+                        "Generated download manifest of {} files of collection {collection_id}",
+                        format_count(file_count)
+                    ),
+                    None => format!("Generating download manifest of collection {collection_id}"),
+                }
+            }
+            TaskKind::BundleCollection => {
+                let collection_id = str_field("collection_id")?;
+                let file_count = metadata.get("file_ids")?.as_array()?.len() as u64;
+
+                let uploaded = metadata
+                    .get("uploaded")
+                    .and_then(|uploaded| uploaded.as_bool());
+
+                match str_field("bundle_file_id") {
+                    Some(bundle_file_id) if uploaded.unwrap_or(false) => format!(
+                        "Bundled {} files of collection {collection_id} into file {bundle_file_id}",
+                        format_count(file_count)
+                    ),
+                    _ => format!(
+                        "Bundling {} files of collection {collection_id}",
+                        format_count(file_count)
+                    ),
+                }
+            }
+            TaskKind::Unknown(_) => return None,
+        };
+
+        // any change that handed its index update to a retry task says so
+        match str_field("indexing") {
+            Some("deferred") => Some(format!("{summary}, indexing deferred")),
+            _ => Some(summary),
+        }
+    }
+}
+
+/// `, N rejected` for re-index tasks that skipped files Meilisearch rejected, otherwise nothing.
+fn format_rejected(rejected_count: u64) -> String {
+    match rejected_count {
+        0 => String::new(),
+        count => format!(", {} rejected", format_count(count)),
+    }
+}
+
+/// Formats a count with thousands separators, e.g. `4,200`.
+fn format_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+
+    for (index, digit) in digits.chars().enumerate() {
+        if index != 0 && (digits.len() - index).is_multiple_of(3) {
+            formatted.push(',');
+        }
+
+        formatted.push(digit);
+    }
+
+    formatted
+}
+
+/// Formats a byte size with one decimal in the largest fitting unit, e.g. `3.2 MB`.
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["KB", "MB", "GB", "TB", "PB"];
+
+    if size < 1000 {
+        return format!("{size} B");
+    }
+
+    let mut size = size as f64 / 1000.0;
+    let mut unit = UNITS[0];
+
+    for next_unit in &UNITS[1..] {
+        if size < 1000.0 {
+            break;
+        }
+
+        size /= 1000.0;
+        unit = next_unit;
+    }
+
+    format!("{size:.1} {unit}")
+}
+
+impl Display for TaskKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parses only the known kinds; an unknown name is an error rather than [`TaskKind::Unknown`].
+impl FromStr for TaskKind {
+    type Err = UnknownTaskKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TaskKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| UnknownTaskKind(s.to_owned()))
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("unknown task name `{0}`")]
+pub struct UnknownTaskKind(pub String);
+
+impl Serialize for TaskKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for TaskKind {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(de::Error::custom)
+    }
+}
+
+impl JsonSchema for TaskKind {
+    fn schema_name() -> String {
+        "TaskKind".to_owned()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            enum_values: Some(
+                TaskKind::ALL
+                    .iter()
+                    .map(|kind| kind.as_str().into())
+                    .collect(),
+            ),
+            ..SchemaObject::default()
+        }
+        .into()
+    }
+}
+
+/// A document exactly as the search index holds it, to compare against the database.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexDocument {
+    pub index_uid: String,
+    pub document: serde_json::Map<String, serde_json::Value>,
+    /// When the document was indexed, null for documents that do not record it, such as
+    /// collections and files indexed by earlier versions.
+    #[serde(
+        default,
+        serialize_with = "crate::interfaces::timestamp::serialize_option",
+        deserialize_with = "crate::interfaces::timestamp::deserialize_option"
+    )]
+    pub indexed_at: Option<DateTime<Utc>>,
+}
+
+/// The deletion of a single document from the search index.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedIndexDocument {
+    /// The Meilisearch task deleting the document; the document is gone once it succeeded.
+    pub meili_task_uid: u32,
+}
+
+/// A Meilisearch task enqueued on behalf of an admin task.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MeiliTask {
+    pub uid: u32,
+    pub index_uid: Option<String>,
+    pub status: MeiliTaskStatus,
+    pub error: Option<String>,
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
+    pub enqueued_at: DateTime<Utc>,
+    #[serde(
+        default,
+        serialize_with = "crate::interfaces::timestamp::serialize_option",
+        deserialize_with = "crate::interfaces::timestamp::deserialize_option"
+    )]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(
+        default,
+        serialize_with = "crate::interfaces::timestamp::serialize_option",
+        deserialize_with = "crate::interfaces::timestamp::deserialize_option"
+    )]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum MeiliTaskStatus {
+    Enqueued,
+    Processing,
+    Failed,
+    Succeeded,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledTask {
+    pub id: Uuid,
+    pub name: TaskKind,
+    pub cron_expression: String,
+    pub metadata: serde_json::Value,
+    pub enabled: bool,
+    #[serde(
+        default,
+        serialize_with = "crate::interfaces::timestamp::serialize_option",
+        deserialize_with = "crate::interfaces::timestamp::deserialize_option"
+    )]
+    pub last_run_at: Option<DateTime<Utc>>,
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatingScheduledTask {
+    pub name: TaskKind,
+    pub cron_expression: String,
+    #[serde(default = "scheduled_task_default_metadata")]
+    pub metadata: serde_json::Value,
+    #[serde(default = "scheduled_task_default_enabled")]
+    pub enabled: bool,
+}
+
+fn scheduled_task_default_metadata() -> serde_json::Value {
+    serde_json::json!({})
+}
+
+fn scheduled_task_default_enabled() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatingScheduledTask {
+    pub cron_expression: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Migration {
+    pub version: i64,
+    pub description: String,
+    pub status: MigrationStatus,
+    /// Hex-encoded checksum of the migration embedded in this build.
+    pub checksum: Option<String>,
+    /// Hex-encoded checksum recorded when the migration was applied.
+    pub applied_checksum: Option<String>,
+    #[serde(
+        default,
+        serialize_with = "crate::interfaces::timestamp::serialize_option",
+        deserialize_with = "crate::interfaces::timestamp::deserialize_option"
+    )]
+    pub installed_on: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum MigrationStatus {
+    Applied,
+    Pending,
+    ChecksumMismatch,
+    Failed,
+    Unknown,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_count, format_size, TaskKind};
+    use serde_json::{json, Value};
+    use std::collections::HashSet;
+
+    /// The name of every kind, spelled out once more. Matching without a wildcard makes a new kind
+    /// fail to compile until its name is listed here.
+    fn expected_name(kind: &TaskKind) -> &str {
+        match kind {
+            TaskKind::ReIndexFiles => "re-index-files",
+            TaskKind::ReIndexCollections => "re-index-collections",
+            TaskKind::UploadFile => "upload-file",
+            TaskKind::UpdateFile => "update-file",
+            TaskKind::DeleteFile => "delete-file",
+            TaskKind::BulkDeleteFiles => "bulk-delete-files",
+            TaskKind::BulkTagFiles => "bulk-tag-files",
+            TaskKind::ExportFiles => "export-files",
+            TaskKind::ReconcileStorage => "reconcile-storage",
+            TaskKind::CreateCollection => "create-collection",
+            TaskKind::UpdateCollection => "update-collection",
+            TaskKind::DeleteCollection => "delete-collection",
+            TaskKind::FileGc => "file-gc",
+            TaskKind::Housekeeping => "housekeeping",
+            TaskKind::RetryIndex => "retry-index",
+            TaskKind::ReIndexCollectionFiles => "re-index-collection-files",
+            TaskKind::RenameTag => "rename-tag",
+            TaskKind::BackfillChecksums => "backfill-checksums",
+            TaskKind::BackfillStorage => "backfill-storage",
+            TaskKind::CollectionDownloadManifest => "collection-download-manifest",
+            TaskKind::BundleCollection => "bundle-collection",
+            TaskKind::Unknown(name) => name,
+        }
+    }
+
+    #[test]
+    fn every_kind_round_trips_through_its_name() {
+        let mut names = HashSet::new();
+
+        for kind in TaskKind::ALL {
+            let name = expected_name(&kind);
+            assert!(names.insert(name.to_owned()), "{name} is listed twice");
+
+            assert_eq!(kind.to_string(), name);
+            assert_eq!(kind.as_str(), name);
+            assert_eq!(name.parse::<TaskKind>().unwrap(), kind);
+            assert_eq!(TaskKind::from_name(name.to_owned()), kind);
+            assert_eq!(serde_json::to_value(&kind).unwrap(), json!(name));
+            assert_eq!(
+                serde_json::from_value::<TaskKind>(json!(name)).unwrap(),
+                kind
+            );
+            assert!(!matches!(kind, TaskKind::Unknown(_)));
+        }
+    }
+
+    #[test]
+    fn unknown_kinds_keep_their_name_but_are_not_parsed() {
+        let kind = TaskKind::from_name("from-the-future".to_owned());
+        assert_eq!(kind, TaskKind::Unknown("from-the-future".to_owned()));
+        assert_eq!(expected_name(&kind), "from-the-future");
+
+        assert_eq!(kind.to_string(), "from-the-future");
+        assert_eq!(
+            serde_json::to_value(&kind).unwrap(),
+            json!("from-the-future")
+        );
+        assert!("from-the-future".parse::<TaskKind>().is_err());
+        assert!(serde_json::from_value::<TaskKind>(json!("from-the-future")).is_err());
+        assert!(!TaskKind::ALL.contains(&kind));
+    }
+
+    fn check(cases: Vec<(TaskKind, Value, Option<&str>)>) {
+        for (kind, metadata, expected) in cases {
+            assert_eq!(
+                kind.summarize(&metadata).as_deref(),
+                expected,
+                "{kind} {metadata}"
+            );
+        }
+    }
+
+    #[test]
+    fn counts_have_thousands_separators() {
+        let cases = [
+            (0, "0"),
+            (999, "999"),
+            (1000, "1,000"),
+            (4200, "4,200"),
+            (999_999, "999,999"),
+            (1_000_000, "1,000,000"),
+            (u64::MAX, "18,446,744,073,709,551,615"),
+        ];
+
+        for (count, expected) in cases {
+            assert_eq!(format_count(count), expected);
+        }
+    }
+
+    #[test]
+    fn sizes_are_given_in_the_largest_fitting_unit() {
+        let cases = [
+            (0, "0 B"),
+            (999, "999 B"),
+            (1000, "1.0 KB"),
+            (3_200_000, "3.2 MB"),
+            (1_000_000_000, "1.0 GB"),
+            (2_500_000_000_000, "2.5 TB"),
+            (7_000_000_000_000_000, "7.0 PB"),
+            (7_000_000_000_000_000_000, "7000.0 PB"),
+        ];
+
+        for (size, expected) in cases {
+            assert_eq!(format_size(size), expected);
+        }
+    }
+
+    #[test]
+    fn file_tasks_are_summarized() {
+        // ids are only echoed, so any string stands in for one
+        let file_id = "file-1";
+
+        check(vec![
+            (
+                TaskKind::ReIndexFiles,
+                json!({ "indexed_count": 4200 }),
+                Some("Re-indexed 4,200 files"),
+            ),
+            (
+                TaskKind::ReIndexFiles,
+                json!({ "indexed_count": 4200, "rejected_count": 3 }),
+                Some("Re-indexed 4,200 files, 3 rejected"),
+            ),
+            (TaskKind::ReIndexFiles, json!({}), None),
+            (
+                TaskKind::UploadFile,
+                json!({ "file_id": file_id, "file_name": "report.pdf", "size": 3_200_000 }),
+                Some("Indexed file 'report.pdf' (3.2 MB)"),
+            ),
+            (
+                TaskKind::UploadFile,
+                json!({ "file_id": file_id }),
+                Some("Indexed file file-1"),
+            ),
+            (
+                TaskKind::UploadFile,
+                json!({ "file_id": file_id, "indexing": "deferred" }),
+                Some("Indexed file file-1, indexing deferred"),
+            ),
+            (TaskKind::UploadFile, json!({ "size": "large" }), None),
+            (
+                TaskKind::UpdateFile,
+                json!({ "file_id": file_id, "delta": { "name": "final.pdf" } }),
+                Some("Updated file file-1, renamed to 'final.pdf'"),
+            ),
+            (
+                TaskKind::UpdateFile,
+                json!({ "file_id": file_id, "delta": { "tags": ["a"] } }),
+                Some("Updated file file-1"),
+            ),
+            (TaskKind::UpdateFile, json!({ "file_id": 3 }), None),
+            (
+                TaskKind::DeleteFile,
+                json!({ "file_id": file_id }),
+                Some("Deleted file file-1"),
+            ),
+            (TaskKind::DeleteFile, json!(null), None),
+        ]);
+    }
+
+    #[test]
+    fn bulk_file_tasks_are_summarized() {
+        check(vec![
+            (
+                TaskKind::BulkDeleteFiles,
+                json!({ "file_count": 10_000, "deleted_count": 4200, "processed_count": 4300 }),
+                Some("Deleted 4,200 files, 4,300/10,000 processed"),
+            ),
+            (
+                TaskKind::BulkDeleteFiles,
+                json!({
+                    "file_count": 10,
+                    "deleted_count": 8,
+                    "processed_count": 10,
+                    "protected_count": 2,
+                    "matches_truncated": true,
+                }),
+                Some(
+                    "Deleted 8 files, 10/10 processed, 2 protected files skipped, \
+                     more files matched than were deleted",
+                ),
+            ),
+            (
+                TaskKind::BulkDeleteFiles,
+                json!({ "file_count": 10, "deleted_count": 8 }),
+                None,
+            ),
+            (
+                TaskKind::BulkTagFiles,
+                json!({ "updated_count": 1200, "added_count": 2400, "removed_count": 0 }),
+                Some("Updated the tags of 1,200 files, 2,400 tags added, 0 removed"),
+            ),
+            (TaskKind::BulkTagFiles, json!({ "updated_count": -1 }), None),
+            (
+                TaskKind::ExportFiles,
+                json!({ "format": "csv", "file_count": 1500 }),
+                Some("Exported 1,500 files as csv"),
+            ),
+            (
+                TaskKind::ExportFiles,
+                json!({ "format": "ndjson" }),
+                Some("Exporting files as ndjson"),
+            ),
+            (TaskKind::ExportFiles, json!({ "file_count": 1 }), None),
+            (
+                TaskKind::RenameTag,
+                json!({
+                    "tag": "draft",
+                    "new_name": "wip",
+                    "file_count": 1000,
+                    "merged_file_count": 12,
+                }),
+                Some("Renamed tag 'draft' to 'wip' on 1,000 files, merged with existing tag on 12"),
+            ),
+            (TaskKind::RenameTag, json!({ "tag": "draft" }), None),
+        ]);
+    }
+
+    #[test]
+    fn collection_tasks_are_summarized() {
+        let collection_id = "collection-1";
+
+        check(vec![
+            (
+                TaskKind::ReIndexCollections,
+                json!({ "indexed_count": 12 }),
+                Some("Re-indexed 12 collections"),
+            ),
+            (
+                TaskKind::ReIndexCollections,
+                json!({ "indexed_count": "12" }),
+                None,
+            ),
+            (
+                TaskKind::CreateCollection,
+                json!({ "content": { "name": "Invoices" } }),
+                Some("Created collection 'Invoices'"),
+            ),
+            (
+                TaskKind::CreateCollection,
+                json!({ "name": "Invoices" }),
+                None,
+            ),
+            (
+                TaskKind::UpdateCollection,
+                json!({ "collection_id": collection_id, "delta": { "name": "Bills" } }),
+                Some("Updated collection collection-1, renamed to 'Bills'"),
+            ),
+            (
+                TaskKind::UpdateCollection,
+                json!({ "collection_id": collection_id }),
+                Some("Updated collection collection-1"),
+            ),
+            (TaskKind::UpdateCollection, json!({}), None),
+            (
+                TaskKind::DeleteCollection,
+                json!({ "collection_id": collection_id }),
+                Some("Deleted collection collection-1"),
+            ),
+            (TaskKind::DeleteCollection, json!([collection_id]), None),
+            (
+                TaskKind::ReIndexCollectionFiles,
+                json!({
+                    "collection_id": collection_id,
+                    "indexed_count": 2000,
+                    "rejected_count": 1,
+                }),
+                Some("Re-indexed 2,000 files of collection collection-1, 1 rejected"),
+            ),
+            (
+                TaskKind::ReIndexCollectionFiles,
+                json!({ "indexed_count": 2000 }),
+                None,
+            ),
+            (
+                TaskKind::CollectionDownloadManifest,
+                json!({ "collection_id": collection_id, "file_count": 5000 }),
+                Some("Generated download manifest of 5,000 files of collection collection-1"),
+            ),
+            (
+                TaskKind::CollectionDownloadManifest,
+                json!({ "collection_id": collection_id }),
+                Some("Generating download manifest of collection collection-1"),
+            ),
+            (TaskKind::CollectionDownloadManifest, json!({}), None),
+            (
+                TaskKind::BundleCollection,
+                json!({
+                    "collection_id": collection_id,
+                    "file_ids": ["a", "b"],
+                    "bundle_file_id": "bundle",
+                    "uploaded": true,
+                }),
+                Some("Bundled 2 files of collection collection-1 into file bundle"),
+            ),
+            (
+                TaskKind::BundleCollection,
+                json!({
+                    "collection_id": collection_id,
+                    "file_ids": ["a", "b"],
+                    "bundle_file_id": "bundle",
+                }),
+                Some("Bundling 2 files of collection collection-1"),
+            ),
+            (
+                TaskKind::BundleCollection,
+                json!({ "collection_id": collection_id, "file_ids": 2 }),
+                None,
+            ),
+        ]);
+    }
+
+    #[test]
+    fn maintenance_tasks_are_summarized() {
+        check(vec![
+            (
+                TaskKind::ReconcileStorage,
+                json!({ "scanned_count": 12_000, "orphan_count": 3, "missing_count": 1 }),
+                Some("Scanned 12,000 objects, 3 orphaned, 1 files missing"),
+            ),
+            (
+                TaskKind::ReconcileStorage,
+                json!({ "scanned_count": 1 }),
+                None,
+            ),
+            (
+                TaskKind::FileGc,
+                json!({ "success": true, "deleted_count": 7 }),
+                Some("Purged 7 unready files"),
+            ),
+            (
+                TaskKind::FileGc,
+                json!({ "success": true, "deleted_count": 7, "protected_count": 2 }),
+                Some("Purged 7 unready files, 2 protected files skipped"),
+            ),
+            (
+                TaskKind::FileGc,
+                json!({ "success": false, "error": "down" }),
+                Some("Failed to purge unready files"),
+            ),
+            (TaskKind::FileGc, json!({ "success": "yes" }), None),
+            (
+                TaskKind::Housekeeping,
+                json!({ "tables": {
+                    "admin_tasks": { "deleted_count": 1000 },
+                    "file_views": { "deleted_count": 500 },
+                } }),
+                Some("Pruned 1,500 rows"),
+            ),
+            (
+                TaskKind::Housekeeping,
+                json!({ "tables": {
+                    "admin_tasks": { "deleted_count": 10 },
+                    "file_views": { "error": "timeout" },
+                } }),
+                Some("Pruned 10 rows, failed to prune 1 tables"),
+            ),
+            (TaskKind::Housekeeping, json!({ "tables": [] }), None),
+            (
+                TaskKind::RetryIndex,
+                json!({
+                    "file_ids_to_index": ["a", "b"],
+                    "collection_ids_to_delete": ["c"],
+                    "applied": true,
+                }),
+                Some("Applied 3 deferred index changes"),
+            ),
+            (
+                TaskKind::RetryIndex,
+                json!({ "file_ids_to_delete": ["a"], "attempt_count": 4 }),
+                Some("Applying 1 deferred index changes, 4 failed attempts"),
+            ),
+            (
+                TaskKind::RetryIndex,
+                json!({}),
+                Some("Applying 0 deferred index changes, 0 failed attempts"),
+            ),
+            (
+                TaskKind::BackfillChecksums,
+                json!({ "stored_count": 900, "hashed_count": 100, "unavailable_count": 2 }),
+                Some("Backfilled checksums of 1,000 files, 100 hashed, 2 objects unavailable"),
+            ),
+            (
+                TaskKind::BackfillChecksums,
+                json!({ "stored_count": 900 }),
+                None,
+            ),
+            (
+                TaskKind::BackfillStorage,
+                json!({ "backfilled_count": 1_000_000 }),
+                Some("Recorded storage on 1,000,000 files"),
+            ),
+            (
+                TaskKind::BackfillStorage,
+                json!({ "backfilled_count": 1.5 }),
+                None,
+            ),
+        ]);
+    }
+
+    #[test]
+    fn unknown_kinds_have_no_summary() {
+        check(vec![
+            (
+                TaskKind::Unknown("from-the-future".to_owned()),
+                json!({ "indexed_count": 1 }),
+                None,
+            ),
+            (TaskKind::Unknown(String::new()), json!({}), None),
+        ]);
+    }
+}
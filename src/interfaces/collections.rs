@@ -1,53 +1,166 @@
+use super::{
+    tags::TagDefinition, validate_search_query, FieldError, SearchAttribute, SearchHighlights,
+    SearchMatchingStrategy,
+};
 use chrono::{DateTime, Utc};
+use rocket::FromFormField;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Collection {
     pub id: Uuid,
     pub name: String,
+    /// Whether no two files of the collection may share a name.
+    #[serde(default)]
+    pub unique_names: bool,
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
     pub created_at: DateTime<Utc>,
     pub tags: Vec<String>,
+    /// The definitions of those tags that have one, in the order of `tags`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_definitions: Vec<TagDefinition>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CollectionCursor {
     pub id: Uuid,
     pub name: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CollectionFileCursor {
     pub id: Uuid,
     pub name: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// The order the files of a collection are listed in.
+#[derive(
+    FromFormField, Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum CollectionFileListSort {
+    /// By name, ignoring case.
+    #[field(value = "name")]
+    Name,
+    /// By name, comparing runs of digits by their value and ignoring case, so that `img2.png`
+    /// comes before `img10.png`.
+    #[field(value = "name-natural")]
+    NameNatural,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CreatingCollection {
     pub name: String,
+    /// Whether no two files of the collection may share a name; `false` if omitted.
+    #[serde(default)]
+    pub unique_names: bool,
     pub tags: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdatingCollection {
     pub name: Option<String>,
+    pub unique_names: Option<bool>,
     pub tags_for_creation: Option<Vec<String>>,
     pub tags_for_deletion: Option<Vec<String>>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+impl UpdatingCollection {
+    /// Whether the update names no field at all.
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.unique_names.is_none()
+            && self
+                .tags_for_creation
+                .as_ref()
+                .is_none_or(|tags| tags.is_empty())
+            && self
+                .tags_for_deletion
+                .as_ref()
+                .is_none_or(|tags| tags.is_empty())
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CollectionSearchQuery {
     pub q: String,
     #[serde(default = "collection_search_query_default_limit")]
-    pub limit: usize,
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+    #[serde(default)]
+    pub matching_strategy: Option<SearchMatchingStrategy>,
+    #[serde(default)]
+    pub attributes_to_highlight: Option<Vec<SearchAttribute>>,
+    /// Crops the highlighted attributes (or every searchable attribute if none are given) to
+    /// this many words around the matches.
+    #[serde(default)]
+    pub crop_length: Option<usize>,
+}
+
+impl CollectionSearchQuery {
+    pub fn validate(&self) -> Vec<FieldError> {
+        validate_search_query(&self.q, self.limit, self.offset)
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionSearchHit {
+    #[serde(flatten)]
+    pub collection: Collection,
+    /// Present only if highlighting or cropping was requested.
+    pub highlights: Option<SearchHighlights>,
+}
+
+/// Presigned download URLs for the ready files of a collection, in id order.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionDownloadManifest {
+    pub collection_id: Uuid,
+    /// When the earliest of the URLs expires; every URL is valid until then.
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
+    pub expires_at: DateTime<Utc>,
+    /// Number of ready files left out because their object is missing.
+    pub missing_count: u64,
+    pub files: Vec<CollectionDownloadManifestFile>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionDownloadManifestFile {
+    pub id: Uuid,
+    pub name: String,
+    pub size: usize,
+    pub url: String,
+}
+
+/// A download manifest generated right away, or the admin task generating it for a collection
+/// too large for that.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedCollectionDownloadManifest {
+    /// Null if the manifest is generated by the admin task.
+    pub manifest: Option<CollectionDownloadManifest>,
+    /// The `collection-download-manifest` task that uploads the manifest, if the collection was
+    /// too large to generate it right away.
+    pub admin_task_id: Option<Uuid>,
 }
 
-fn collection_search_query_default_limit() -> usize {
+fn collection_search_query_default_limit() -> i64 {
     25
 }
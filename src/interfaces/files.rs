@@ -1,50 +1,225 @@
+use super::{
+    tags::TagDefinition, validate_search_query, FieldError, SearchAttribute, SearchHighlights,
+    SearchMatchingStrategy,
+};
 use chrono::{DateTime, Utc};
+use rocket::FromFormField;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct File {
     pub id: Uuid,
     pub name: String,
     pub size: usize,
     pub mime_type: String,
+    /// Lowercase hex SHA-256 digest of the content, if known.
+    pub checksum: Option<String>,
+    /// Whether the upload has completed. Only ready files are searchable.
+    pub is_ready: bool,
+    /// Whether the file is kept from being deleted, e.g. for a legal hold. Only admins may change
+    /// it.
+    pub protected: bool,
+    /// The newer version that replaced the file, if any. Searches leave superseded files out
+    /// unless asked to include them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub superseded_by: Option<Uuid>,
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
     pub uploaded_at: DateTime<Utc>,
     pub tags: Vec<String>,
+    /// Where the object of the file is stored. Only shown to admins.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<FileStorage>,
+    /// The definitions of those tags that have one, in the order of `tags`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_definitions: Vec<TagDefinition>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// The bucket, and its region, that an object is stored in.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStorage {
+    pub bucket: String,
+    pub region: String,
+}
+
+/// A collection a file belongs to.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FileCollection {
+    pub id: Uuid,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct FileCursor {
     pub id: Uuid,
     pub uploaded_at: DateTime<Utc>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileNameCursor {
+    pub id: Uuid,
+    pub name: String,
+}
+
+/// The order the files are listed in.
+#[derive(
+    FromFormField, Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileListSort {
+    /// The latest upload first.
+    #[field(value = "uploaded-at")]
+    UploadedAt,
+    /// By name, comparing runs of digits by their value and ignoring case, so that `img2.png`
+    /// comes before `img10.png`.
+    #[field(value = "name-natural")]
+    NameNatural,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct FileDownloadUrl {
     pub url: String,
+    /// The file the URL downloads, if it is a later version than the one requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<Uuid>,
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
     pub expires_at: DateTime<Utc>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CreatingFile {
     pub name: String,
     pub size: usize,
     pub mime_type: String,
+    /// Hex SHA-256 digest of the content.
+    #[serde(default)]
+    pub checksum: Option<String>,
     pub tags: Option<Vec<String>>,
+    /// Creates the file even if an identical one is being uploaded already, instead of answering
+    /// `409` with the file to resume. Only `POST /files` checks for such a file.
+    #[serde(default)]
+    pub allow_duplicate: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatingFiles {
+    pub files: Vec<CreatingFile>,
+}
+
+impl CreatingFile {
+    pub fn is_checksum_valid(&self) -> bool {
+        is_checksum_valid(self.checksum.as_deref())
+    }
+}
+
+/// The next version of a file, uploaded like a new file. It takes over the tags of the file.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatingFileVersion {
+    /// Keeps the name of the file if absent.
+    #[serde(default)]
+    pub name: Option<String>,
+    pub size: usize,
+    pub mime_type: String,
+    /// Hex SHA-256 digest of the content.
+    #[serde(default)]
+    pub checksum: Option<String>,
+}
+
+impl CreatingFileVersion {
+    pub fn is_checksum_valid(&self) -> bool {
+        is_checksum_valid(self.checksum.as_deref())
+    }
+}
+
+fn is_checksum_valid(checksum: Option<&str>) -> bool {
+    match checksum {
+        Some(checksum) => {
+            checksum.len() == 64 && checksum.bytes().all(|byte| byte.is_ascii_hexdigit())
+        }
+        None => true,
+    }
+}
+
+/// A file among the versions of a file.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileVersion {
+    /// The position of the file among the versions, from 1 for the first one.
+    pub version_number: i32,
+    #[serde(flatten)]
+    pub file: File,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct FileUploadUrl {
     pub id: String,
+    /// Number of parts of the whole upload, which may be more than the parts listed here.
+    pub part_count: u32,
     pub parts: Vec<FileUploadUrlPart>,
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
     pub expires_at: DateTime<Utc>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// A file fetched by `GET /files/<file_id>?include-index-status=true`.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileWithIndexStatus {
+    #[serde(flatten)]
+    pub file: File,
+    /// Null if the search engine could not be asked.
+    pub index_status: Option<FileIndexStatus>,
+}
+
+/// How the search index holds a file.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileIndexStatus {
+    /// Whether the search index holds a document of the file.
+    pub indexed: bool,
+    /// When the document was indexed, null if there is none or it was indexed by an earlier
+    /// version that did not record it.
+    #[serde(
+        default,
+        serialize_with = "crate::interfaces::timestamp::serialize_option",
+        deserialize_with = "crate::interfaces::timestamp::deserialize_option"
+    )]
+    pub indexed_at: Option<DateTime<Utc>>,
+}
+
+/// A file created by `POST /files?include-upload-urls=true`, along with the upload of its content.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedFile {
+    pub file: File,
+    /// Null if the upload could not be started, in which case it can be started again with
+    /// `POST /files/<file_id>/upload-urls`.
+    pub upload: Option<FileUploadUrl>,
+    /// Why the upload could not be started, as the `code` of the error it failed with.
+    pub upload_error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct FileUploadUrlPart {
     pub part_number: u32,
@@ -53,20 +228,189 @@ pub struct FileUploadUrlPart {
     pub size: u64,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// One multipart upload of a file, from its upload URLs to its completion or abortion.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileUploadRecord {
+    pub upload_id: String,
+    pub part_count: u32,
+    pub total_bytes: usize,
+    pub outcome: UploadOutcome,
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
+    pub started_at: DateTime<Utc>,
+    /// When the upload was completed, aborted or expired; absent while it is pending.
+    #[serde(
+        default,
+        serialize_with = "crate::interfaces::timestamp::serialize_option",
+        deserialize_with = "crate::interfaces::timestamp::deserialize_option"
+    )]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// How much of a file its pending multipart upload has received, or all of it once it is ready.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileUploadProgress {
+    pub bytes_received: u64,
+    pub total_bytes: u64,
+    pub parts_received: u32,
+    pub parts_total: u32,
+    /// `bytesReceived` in percent of `totalBytes`, from 0 to 100.
+    pub percent: f64,
+}
+
+#[derive(
+    sqlx::Type, Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "camelCase")]
+#[sqlx(type_name = "upload_outcome")]
+#[sqlx(rename_all = "snake_case")]
+pub enum UploadOutcome {
+    Pending,
+    Completed,
+    Aborted,
+    /// Aborted by the file gc after being left pending for too long.
+    Expired,
+}
+
+/// The S3 storage class of an object. Uploads do not pick one, so every object is in the
+/// standard class.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageClass {
+    Standard,
+}
+
+/// The estimated monthly cost of storing the ready files.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileCostReport {
+    pub file_count: u64,
+    pub bytes: u64,
+    pub cost: f64,
+    pub storage_classes: Vec<StorageClassCost>,
+    /// The collections holding the most bytes, the largest first. Present only if asked for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collections: Option<Vec<CollectionCost>>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageClassCost {
+    pub storage_class: StorageClass,
+    pub rate_per_gb_month: f64,
+    pub file_count: u64,
+    pub bytes: u64,
+    pub cost: f64,
+}
+
+/// The files of a collection also count towards any other collection they belong to.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionCost {
+    pub id: Uuid,
+    pub name: String,
+    pub file_count: u64,
+    pub bytes: u64,
+    pub cost: f64,
+}
+
+#[derive(
+    FromFormField, Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum FileExportFormat {
+    Ndjson,
+    Csv,
+}
+
+impl FileExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            FileExportFormat::Ndjson => "application/x-ndjson",
+            FileExportFormat::Csv => "text/csv",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            FileExportFormat::Ndjson => "ndjson",
+            FileExportFormat::Csv => "csv",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatingFileExport {
+    pub format: FileExportFormat,
+}
+
+/// Either an explicit list of file ids or a search query selecting the files to delete.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkDeletingFiles {
+    pub file_ids: Option<Vec<Uuid>>,
+    pub query: Option<FileSearchQuery>,
+}
+
+/// Tags to add to and remove from many files at once.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkTaggingFiles {
+    pub file_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub add: Vec<String>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkTaggedFiles {
+    /// What happened to each file, in the order of `fileIds`, once per file.
+    pub files: Vec<BulkTaggedFile>,
+    pub updated_count: usize,
+    pub not_found_count: usize,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkTaggedFile {
+    pub file_id: Uuid,
+    pub outcome: BulkTagOutcome,
+    /// The tags added, leaving out those the file had already.
+    pub added: Vec<String>,
+    /// The tags removed, leaving out those the file did not have.
+    pub removed: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum BulkTagOutcome {
+    Updated,
+    /// The file had every tag added already and none of the tags removed.
+    Unchanged,
+    NotFound,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UploadedParts {
     pub parts: Vec<UploadedPart>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UploadedPart {
     pub part_number: u32,
     pub e_tag: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdatingFile {
     pub name: Option<String>,
@@ -74,23 +418,217 @@ pub struct UpdatingFile {
     pub mime_type: Option<String>,
     pub tags_for_creation: Option<Vec<String>>,
     pub tags_for_deletion: Option<Vec<String>>,
+    /// Protects the file from deletion, or lifts the protection. Requires the `admin` role.
+    pub protected: Option<bool>,
+    /// Marks the file as replaced by the given ready file, or as current again with `null`.
+    #[serde(
+        default,
+        deserialize_with = "crate::interfaces::deserialize_nullable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub superseded_by: Option<Option<Uuid>>,
+}
+
+impl UpdatingFile {
+    /// Whether the update names no field at all.
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.size.is_none()
+            && self.mime_type.is_none()
+            && self.protected.is_none()
+            && self.superseded_by.is_none()
+            && self
+                .tags_for_creation
+                .as_ref()
+                .is_none_or(|tags| tags.is_empty())
+            && self
+                .tags_for_deletion
+                .as_ref()
+                .is_none_or(|tags| tags.is_empty())
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct FileSearchQuery {
     pub q: String,
     #[serde(default = "file_search_query_default_limit")]
-    pub limit: usize,
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+    /// Filters in conjunctive normal form: the elements of each inner group are joined with `OR`,
+    /// and the groups are joined with `AND`. Empty groups are ignored.
     #[serde(default)]
     pub filters: Vec<Vec<FileSearchQueryFilter>>,
+    #[serde(default)]
+    pub matching_strategy: Option<SearchMatchingStrategy>,
+    #[serde(default)]
+    pub attributes_to_highlight: Option<Vec<SearchAttribute>>,
+    /// Crops the highlighted attributes (or every searchable attribute if none are given) to
+    /// this many words around the matches.
+    #[serde(default)]
+    pub crop_length: Option<usize>,
+    /// Collapses hits sharing the same value of the attribute into one.
+    #[serde(default)]
+    pub distinct: Option<FileSearchDistinct>,
+    /// Also matches files whose upload has not completed. Searches require an admin session
+    /// for this.
+    #[serde(default)]
+    pub include_unready: bool,
+    /// Also matches files replaced by a newer version.
+    #[serde(default)]
+    pub include_superseded: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum FileSearchDistinct {
+    Checksum,
+}
+
+impl FileSearchQuery {
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut errors = validate_search_query(&self.q, self.limit, self.offset);
+
+        for (group_index, filters) in self.filters.iter().enumerate() {
+            for (index, filter) in filters.iter().enumerate() {
+                match filter {
+                    FileSearchQueryFilter::Exists { field }
+                    | FileSearchQueryFilter::NotExists { field }
+                        if !FILE_SEARCH_OPTIONAL_FIELDS.contains(&field.as_str()) =>
+                    {
+                        errors.push(FieldError::new(
+                            format!("filters[{group_index}][{index}].field"),
+                            format!("must be one of {}", FILE_SEARCH_OPTIONAL_FIELDS.join(", ")),
+                        ));
+                    }
+                    FileSearchQueryFilter::SizeBucket { value }
+                        if !FILE_SIZE_BUCKETS.iter().any(|bucket| bucket.name == value) =>
+                    {
+                        errors.push(FieldError::new(
+                            format!("filters[{group_index}][{index}].value"),
+                            format!(
+                                "must be one of {}",
+                                Vec::from_iter(FILE_SIZE_BUCKETS.iter().map(|bucket| bucket.name))
+                                    .join(", ")
+                            ),
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSearchHit {
+    #[serde(flatten)]
+    pub file: File,
+    /// Present only if highlighting or cropping was requested.
+    pub highlights: Option<SearchHighlights>,
+    /// Number of matching files sharing the checksum of this hit, including itself. Present only
+    /// for distinct searches, and only if the search engine reported it.
+    pub duplicate_count: Option<usize>,
+    /// The collections the file belonged to when it was last indexed, by name.
+    pub collections: Vec<FileCollection>,
+    /// Whether the file had too many tags or collections to index them all, in which case `tags`
+    /// and `collections` list only the first ones. Fetching the file gives all of its tags.
+    pub truncated_for_index: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSearchResult {
+    pub hits: Vec<FileSearchHit>,
+    /// Number of matching files left out because their search documents could not be read. They
+    /// are re-indexed in the background, and found again once repaired.
+    pub skipped_hits: usize,
+}
+
+/// The optional attributes of indexed files that `exists` and `notExists` filters may name. File
+/// documents leave them out while they are unset, instead of indexing `null`.
+pub const FILE_SEARCH_OPTIONAL_FIELDS: &[&str] = &["checksum"];
+
+/// A range of file sizes, indexed with each file so that searches can filter and facet by it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileSizeBucket {
+    pub name: &'static str,
+    /// The smallest size in bytes in the bucket. A bucket reaches up to the next one.
+    pub min_size: usize,
+}
+
+/// The size buckets, smallest first. Sizes count in decimal units, like the task summaries.
+pub const FILE_SIZE_BUCKETS: &[FileSizeBucket] = &[
+    FileSizeBucket {
+        name: "<1MB",
+        min_size: 0,
+    },
+    FileSizeBucket {
+        name: "1-10MB",
+        min_size: 1_000_000,
+    },
+    FileSizeBucket {
+        name: "10-100MB",
+        min_size: 10_000_000,
+    },
+    FileSizeBucket {
+        name: "100MB-1GB",
+        min_size: 100_000_000,
+    },
+    FileSizeBucket {
+        name: ">1GB",
+        min_size: 1_000_000_000,
+    },
+];
+
+/// The bucket of [`FILE_SIZE_BUCKETS`] a file of `size` bytes falls in. A size on a boundary
+/// falls in the larger bucket, e.g. exactly 1 MB in `1-10MB`.
+pub fn file_size_bucket(size: usize) -> &'static str {
+    FILE_SIZE_BUCKETS
+        .iter()
+        .rev()
+        .find(|bucket| bucket.min_size <= size)
+        .map_or(FILE_SIZE_BUCKETS[0].name, |bucket| bucket.name)
 }
 
-fn file_search_query_default_limit() -> usize {
+/// The number of files matching a search in each size bucket.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSearchFacets {
+    /// Every bucket of [`FILE_SIZE_BUCKETS`] in order, empty ones included.
+    pub size_bucket: Vec<FileSearchFacetCount>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSearchFacetCount {
+    pub value: String,
+    pub count: usize,
+}
+
+impl FileSearchFacets {
+    /// Builds the facets from the counts of the size buckets that have files.
+    pub fn from_size_bucket_counts(counts: &HashMap<String, usize>) -> Self {
+        Self {
+            size_bucket: Vec::from_iter(FILE_SIZE_BUCKETS.iter().map(|bucket| {
+                FileSearchFacetCount {
+                    value: bucket.name.to_owned(),
+                    count: counts.get(bucket.name).copied().unwrap_or(0),
+                }
+            })),
+        }
+    }
+}
+
+fn file_search_query_default_limit() -> i64 {
     25
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 #[serde(tag = "type")]
 pub enum FileSearchQueryFilter {
@@ -101,18 +639,51 @@ pub enum FileSearchQueryFilter {
     MimeType {
         value: String,
     },
+    /// Matches files whose mime type is anything but `value`.
+    NotMimeType {
+        value: String,
+    },
     Tag {
         value: String,
     },
+    /// Matches files that do not have the tag `value`, including files without any tags.
+    NotTag {
+        value: String,
+    },
     TagIsEmpty,
     TagIsNotEmpty,
+    /// Matches files that have a value for `field`, one of [`FILE_SEARCH_OPTIONAL_FIELDS`].
+    Exists {
+        field: String,
+    },
+    /// Matches files without a value for `field`, one of [`FILE_SEARCH_OPTIONAL_FIELDS`].
+    NotExists {
+        field: String,
+    },
+    /// Matches files whose tags or collections were truncated to fit in the index, or, with
+    /// `false`, those that were not.
+    TruncatedForIndex {
+        value: bool,
+    },
+    /// Matches files protected from deletion, or, with `false`, those that are not.
+    Protected {
+        value: bool,
+    },
+    /// Matches files whose size falls in the bucket `value`, one of [`FILE_SIZE_BUCKETS`].
+    SizeBucket {
+        value: String,
+    },
     UploadedAt {
         operator: FileSearchQueryFilterOperator,
+        #[serde(
+            serialize_with = "crate::interfaces::timestamp::serialize",
+            deserialize_with = "crate::interfaces::timestamp::deserialize"
+        )]
         value: DateTime<Utc>,
     },
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum FileSearchQueryFilterOperator {
     Eq,
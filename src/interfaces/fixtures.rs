@@ -0,0 +1,1090 @@
+//! A sample of every DTO, for `tests/serde_fixtures.rs` to compare with the JSON fixtures
+//! committed under `tests/fixtures`. A renamed field or a changed variant tag changes the JSON a
+//! sample serializes to, and the committed JSON no longer reads back unchanged. Built with the
+//! `testing` feature only.
+
+use super::{
+    admins::{
+        Admin, AdminLogin, AdminRole, AdminSession, AdminTask, AdminTaskExportFormat,
+        AdminTaskInitiatedBy, AdminTaskInitiator, AdminTaskPreview, AdminTaskStatus,
+        BackfillingChecksums, BootstrappingAdmin, CreatingAdmin, CreatingScheduledTask,
+        DeletedIndexDocument, IndexDocument, MeiliTask, MeiliTaskStatus, Migration,
+        MigrationStatus, ReIndexAdminTask, ReconcilingStorage, RequestingPasswordReset,
+        ResettingPassword, ScheduledTask, TaskKind, UpdatingAdmin, UpdatingScheduledTask,
+    },
+    collections::{
+        Collection, CollectionCursor, CollectionDownloadManifest, CollectionDownloadManifestFile,
+        CollectionFileCursor, CollectionFileListSort, CollectionSearchHit, CollectionSearchQuery,
+        CreatedCollectionDownloadManifest, CreatingCollection, UpdatingCollection,
+    },
+    files::{
+        BulkDeletingFiles, BulkTagOutcome, BulkTaggedFile, BulkTaggedFiles, BulkTaggingFiles,
+        CollectionCost, CreatedFile, CreatingFile, CreatingFileExport, CreatingFileVersion,
+        CreatingFiles, File, FileCollection, FileCostReport, FileCursor, FileDownloadUrl,
+        FileExportFormat, FileIndexStatus, FileListSort, FileNameCursor, FileSearchDistinct,
+        FileSearchFacetCount, FileSearchFacets, FileSearchHit, FileSearchQuery,
+        FileSearchQueryFilter, FileSearchQueryFilterOperator, FileSearchResult, FileStorage,
+        FileUploadProgress, FileUploadRecord, FileUploadUrl, FileUploadUrlPart, FileVersion,
+        FileWithIndexStatus, StorageClass, StorageClassCost, UpdatingFile, UploadOutcome,
+        UploadedPart, UploadedParts,
+    },
+    tags::{
+        CreatingTagDefinition, RenamingTag, TagDefinition, TagRenameImpact, TagRenamePreview,
+        UpdatingTagDefinition,
+    },
+    CircuitState, FieldError, Health, IndexSettings, IndexWriteQueue, IndexingStatus, RankingRule,
+    SearchAttribute, SearchEngineCircuit, SearchHighlights, SearchIndexSettings,
+    SearchMatchingStrategy, SimpleOk, TypoTolerance, UpdatingIndexSettings, WithAdminTask,
+};
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// A sample DTO along with the JSON it serializes to.
+pub struct Fixture {
+    /// Unique within its group, e.g. `File` or `FileSearchQueryFilter::Size`.
+    pub name: String,
+    pub json: Value,
+    read: fn(Value) -> serde_json::Result<Value>,
+}
+
+impl Fixture {
+    pub fn new<T: Serialize + DeserializeOwned>(name: impl Into<String>, sample: T) -> Self {
+        Self {
+            name: name.into(),
+            json: serde_json::to_value(sample).expect("failed to serialize fixture"),
+            read: read_as::<T>,
+        }
+    }
+
+    /// Deserializes `json` as the DTO of the fixture and serializes it again.
+    pub fn round_trip(&self, json: Value) -> serde_json::Result<Value> {
+        (self.read)(json)
+    }
+}
+
+fn read_as<T: Serialize + DeserializeOwned>(json: Value) -> serde_json::Result<Value> {
+    serde_json::to_value(serde_json::from_value::<T>(json)?)
+}
+
+/// The fixtures by the module of their DTOs, each group kept in `tests/fixtures/<group>.json`.
+pub fn groups() -> Vec<(&'static str, Vec<Fixture>)> {
+    vec![
+        ("common", common()),
+        ("admins", admins()),
+        ("collections", collections()),
+        ("files", files()),
+        ("tags", tags()),
+    ]
+}
+
+fn id(n: u128) -> Uuid {
+    Uuid::from_u128(0x0194_f0c2_7b3a_7000_8000_0000_0000_0000 + n)
+}
+
+fn at(timestamp: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .expect("invalid fixture timestamp")
+        .to_utc()
+}
+
+fn tag_definition() -> TagDefinition {
+    TagDefinition {
+        name: "invoice".to_owned(),
+        color: Some("#1f77b4".to_owned()),
+        description: Some("Bills sent to customers".to_owned()),
+        created_at: at("2025-01-02T03:04:05.678Z"),
+    }
+}
+
+fn file() -> File {
+    File {
+        id: id(1),
+        name: "report.pdf".to_owned(),
+        size: 1_048_576,
+        mime_type: "application/pdf".to_owned(),
+        checksum: Some(
+            "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08".to_owned(),
+        ),
+        is_ready: true,
+        protected: false,
+        superseded_by: None,
+        uploaded_at: at("2025-03-01T12:34:56.789Z"),
+        tags: vec!["invoice".to_owned(), "2025".to_owned()],
+        storage: None,
+        tag_definitions: Vec::new(),
+    }
+}
+
+/// A file with every optional field set.
+fn full_file() -> File {
+    File {
+        protected: true,
+        superseded_by: Some(id(2)),
+        storage: Some(FileStorage {
+            bucket: "file-indexer".to_owned(),
+            region: "us-east-1".to_owned(),
+        }),
+        tag_definitions: vec![tag_definition()],
+        ..file()
+    }
+}
+
+fn collection() -> Collection {
+    Collection {
+        id: id(10),
+        name: "Invoices".to_owned(),
+        unique_names: true,
+        created_at: at("2025-02-03T04:05:06.007Z"),
+        tags: vec!["invoice".to_owned()],
+        tag_definitions: vec![tag_definition()],
+    }
+}
+
+fn admin_task() -> AdminTask {
+    AdminTask {
+        id: id(20),
+        initiator: AdminTaskInitiator::User,
+        initiated_by: Some(AdminTaskInitiatedBy {
+            id: id(30),
+            username: "admin".to_owned(),
+        }),
+        name: TaskKind::UploadFile,
+        metadata: json!({ "file_id": id(1), "meili_task_uids": [42] }),
+        status: AdminTaskStatus::Failed,
+        depends_on: Some(id(21)),
+        error: Some("search engine failure".to_owned()),
+        failed_at: Some(at("2025-03-01T12:35:00.000Z")),
+        enqueued_at: at("2025-03-01T12:34:57.000Z"),
+        updated_at: at("2025-03-01T12:35:00.000Z"),
+    }
+}
+
+fn highlights() -> SearchHighlights {
+    SearchHighlights {
+        name: Some("<em>report</em>.pdf".to_owned()),
+        tags: Some(vec!["invoice".to_owned()]),
+    }
+}
+
+fn search_index_settings() -> SearchIndexSettings {
+    SearchIndexSettings {
+        synonyms: BTreeMap::from([("invoice".to_owned(), vec!["bill".to_owned()])]),
+        stop_words: vec!["the".to_owned()],
+        typo_tolerance: TypoTolerance {
+            enabled: true,
+            min_word_size_for_one_typo: 5,
+            min_word_size_for_two_typos: 9,
+        },
+        ranking_rules: RankingRule::ALL.to_vec(),
+    }
+}
+
+fn file_search_query(filters: Vec<Vec<FileSearchQueryFilter>>) -> FileSearchQuery {
+    FileSearchQuery {
+        q: "report".to_owned(),
+        limit: 25,
+        offset: 0,
+        filters,
+        matching_strategy: None,
+        attributes_to_highlight: None,
+        crop_length: None,
+        distinct: None,
+        include_unready: false,
+        include_superseded: false,
+    }
+}
+
+fn common() -> Vec<Fixture> {
+    let mut fixtures = vec![
+        Fixture::new("SimpleOk", SimpleOk { ok: true }),
+        Fixture::new(
+            "WithAdminTask<File>",
+            WithAdminTask {
+                result: file(),
+                admin_task_id: Some(id(20)),
+            },
+        ),
+        Fixture::new(
+            "WithAdminTask<Collection>",
+            WithAdminTask {
+                result: collection(),
+                admin_task_id: None,
+            },
+        ),
+        Fixture::new(
+            "Health",
+            Health {
+                search_engine: SearchEngineCircuit {
+                    state: CircuitState::Open,
+                    consecutive_failures: 5,
+                    retry_after_secs: Some(30),
+                },
+                index_write_queue: IndexWriteQueue {
+                    queued_count: 3,
+                    coalesced_count: 12,
+                },
+            },
+        ),
+        Fixture::new(
+            "FieldError",
+            FieldError::new("filters[0][1].value", "must be one of <1MB, 1-10MB"),
+        ),
+        Fixture::new("SearchHighlights", highlights()),
+        Fixture::new(
+            "IndexSettings",
+            IndexSettings {
+                files: search_index_settings(),
+                collections: search_index_settings(),
+            },
+        ),
+        Fixture::new(
+            "UpdatingIndexSettings",
+            UpdatingIndexSettings {
+                files: Some(search_index_settings()),
+                collections: None,
+            },
+        ),
+    ];
+
+    for (name, status) in [
+        ("Done", IndexingStatus::Done),
+        ("Deferred", IndexingStatus::Deferred),
+        ("Queued", IndexingStatus::Queued),
+    ] {
+        fixtures.push(Fixture::new(format!("IndexingStatus::{name}"), status));
+    }
+
+    for (name, state) in [
+        ("Closed", CircuitState::Closed),
+        ("Open", CircuitState::Open),
+        ("HalfOpen", CircuitState::HalfOpen),
+    ] {
+        fixtures.push(Fixture::new(format!("CircuitState::{name}"), state));
+    }
+
+    for (name, strategy) in [
+        ("Last", SearchMatchingStrategy::Last),
+        ("All", SearchMatchingStrategy::All),
+    ] {
+        fixtures.push(Fixture::new(
+            format!("SearchMatchingStrategy::{name}"),
+            strategy,
+        ));
+    }
+
+    for (name, attribute) in [
+        ("Name", SearchAttribute::Name),
+        ("Tags", SearchAttribute::Tags),
+    ] {
+        fixtures.push(Fixture::new(format!("SearchAttribute::{name}"), attribute));
+    }
+
+    for rule in RankingRule::ALL {
+        fixtures.push(Fixture::new(format!("RankingRule::{rule:?}"), rule));
+    }
+
+    fixtures
+}
+
+fn admins() -> Vec<Fixture> {
+    let mut fixtures = vec![
+        Fixture::new(
+            "Admin",
+            Admin {
+                id: id(30),
+                username: "admin".to_owned(),
+                email: "admin@example.com".to_owned(),
+                role: AdminRole::Admin,
+                joined_at: at("2024-12-31T23:59:59.999Z"),
+            },
+        ),
+        Fixture::new(
+            "CreatingAdmin",
+            CreatingAdmin {
+                username: "editor".to_owned(),
+                password: "editor-password".to_owned(),
+                email: "editor@example.com".to_owned(),
+                role: AdminRole::Editor,
+            },
+        ),
+        Fixture::new(
+            "BootstrappingAdmin",
+            BootstrappingAdmin {
+                username: "admin".to_owned(),
+                password: "admin-password".to_owned(),
+                email: "admin@example.com".to_owned(),
+                bootstrap_token: Some("bootstrap-token".to_owned()),
+            },
+        ),
+        Fixture::new(
+            "UpdatingAdmin",
+            UpdatingAdmin {
+                username: None,
+                password: Some("new-password".to_owned()),
+                email: None,
+                role: Some(AdminRole::Viewer),
+            },
+        ),
+        Fixture::new(
+            "AdminLogin",
+            AdminLogin {
+                username: "admin".to_owned(),
+                password: "admin-password".to_owned(),
+            },
+        ),
+        Fixture::new(
+            "AdminSession",
+            AdminSession {
+                token: "session-token".to_owned(),
+                logined_at: at("2025-03-01T00:00:00.000Z"),
+                last_used_at: at("2025-03-01T06:00:00.000Z"),
+                idle_expired_at: at("2025-03-01T18:00:00.000Z"),
+                expired_at: at("2025-03-08T00:00:00.000Z"),
+            },
+        ),
+        Fixture::new(
+            "RequestingPasswordReset",
+            RequestingPasswordReset {
+                email: "admin@example.com".to_owned(),
+            },
+        ),
+        Fixture::new(
+            "ResettingPassword",
+            ResettingPassword {
+                password: "new-password".to_owned(),
+            },
+        ),
+        Fixture::new(
+            "AdminTaskPreview",
+            AdminTaskPreview {
+                id: id(20),
+                initiator: AdminTaskInitiator::System,
+                initiated_by: None,
+                name: TaskKind::DeleteFile,
+                summary: Some(format!("Deleted file {}", id(1))),
+                status: AdminTaskStatus::Completed,
+                enqueued_at: at("2025-03-01T12:34:57.000Z"),
+                updated_at: at("2025-03-01T12:35:00.000Z"),
+            },
+        ),
+        Fixture::new("AdminTask", admin_task()),
+        Fixture::new(
+            "ReIndexAdminTask",
+            ReIndexAdminTask {
+                file_task: AdminTask {
+                    name: TaskKind::ReIndexFiles,
+                    status: AdminTaskStatus::Pending,
+                    depends_on: None,
+                    error: None,
+                    failed_at: None,
+                    ..admin_task()
+                },
+                collection_task: AdminTask {
+                    id: id(22),
+                    name: TaskKind::ReIndexCollections,
+                    status: AdminTaskStatus::InProgress,
+                    depends_on: None,
+                    error: None,
+                    failed_at: None,
+                    ..admin_task()
+                },
+            },
+        ),
+        Fixture::new(
+            "ReconcilingStorage",
+            ReconcilingStorage {
+                delete_orphans: true,
+                mark_missing_unready: false,
+            },
+        ),
+        Fixture::new(
+            "BackfillingChecksums",
+            BackfillingChecksums {
+                files_per_second: 50,
+            },
+        ),
+        Fixture::new(
+            "IndexDocument",
+            IndexDocument {
+                index_uid: "files".to_owned(),
+                document: json!({ "id": id(1), "name": "report.pdf" })
+                    .as_object()
+                    .cloned()
+                    .unwrap_or_default(),
+                indexed_at: Some(at("2025-03-01T12:34:58.123Z")),
+            },
+        ),
+        Fixture::new(
+            "DeletedIndexDocument",
+            DeletedIndexDocument { meili_task_uid: 42 },
+        ),
+        Fixture::new(
+            "MeiliTask",
+            MeiliTask {
+                uid: 42,
+                index_uid: Some("files".to_owned()),
+                status: MeiliTaskStatus::Failed,
+                error: Some("invalid document".to_owned()),
+                enqueued_at: at("2025-03-01T12:34:57.000Z"),
+                started_at: Some(at("2025-03-01T12:34:57.100Z")),
+                finished_at: None,
+            },
+        ),
+        Fixture::new(
+            "ScheduledTask",
+            ScheduledTask {
+                id: id(40),
+                name: TaskKind::Housekeeping,
+                cron_expression: "0 3 * * *".to_owned(),
+                metadata: json!({}),
+                enabled: true,
+                last_run_at: Some(at("2025-03-01T03:00:00.000Z")),
+                created_at: at("2025-01-01T00:00:00.000Z"),
+            },
+        ),
+        Fixture::new(
+            "CreatingScheduledTask",
+            CreatingScheduledTask {
+                name: TaskKind::FileGc,
+                cron_expression: "0 0 4 * * * *".to_owned(),
+                metadata: json!({ "dry_run": true }),
+                enabled: false,
+            },
+        ),
+        Fixture::new(
+            "UpdatingScheduledTask",
+            UpdatingScheduledTask {
+                cron_expression: Some("*/5 * * * *".to_owned()),
+                metadata: None,
+                enabled: Some(true),
+            },
+        ),
+        Fixture::new(
+            "Migration",
+            Migration {
+                version: 20_250_328_100_000,
+                description: "table-admin-task-queued-files".to_owned(),
+                status: MigrationStatus::ChecksumMismatch,
+                checksum: Some("0a1b".to_owned()),
+                applied_checksum: Some("2c3d".to_owned()),
+                installed_on: Some(at("2025-03-28T10:00:00.000Z")),
+            },
+        ),
+    ];
+
+    for (name, role) in [
+        ("Viewer", AdminRole::Viewer),
+        ("Editor", AdminRole::Editor),
+        ("Admin", AdminRole::Admin),
+    ] {
+        fixtures.push(Fixture::new(format!("AdminRole::{name}"), role));
+    }
+
+    fixtures.push(Fixture::new(
+        "AdminTaskExportFormat::Ndjson",
+        AdminTaskExportFormat::Ndjson,
+    ));
+
+    for (name, initiator) in [
+        ("User", AdminTaskInitiator::User),
+        ("System", AdminTaskInitiator::System),
+    ] {
+        fixtures.push(Fixture::new(
+            format!("AdminTaskInitiator::{name}"),
+            initiator,
+        ));
+    }
+
+    for (name, status) in [
+        ("Pending", AdminTaskStatus::Pending),
+        ("InProgress", AdminTaskStatus::InProgress),
+        ("Canceled", AdminTaskStatus::Canceled),
+        ("Completed", AdminTaskStatus::Completed),
+        ("Failed", AdminTaskStatus::Failed),
+    ] {
+        fixtures.push(Fixture::new(format!("AdminTaskStatus::{name}"), status));
+    }
+
+    for kind in TaskKind::ALL {
+        fixtures.push(Fixture::new(format!("TaskKind::{kind:?}"), kind));
+    }
+
+    for (name, status) in [
+        ("Enqueued", MeiliTaskStatus::Enqueued),
+        ("Processing", MeiliTaskStatus::Processing),
+        ("Failed", MeiliTaskStatus::Failed),
+        ("Succeeded", MeiliTaskStatus::Succeeded),
+    ] {
+        fixtures.push(Fixture::new(format!("MeiliTaskStatus::{name}"), status));
+    }
+
+    for (name, status) in [
+        ("Applied", MigrationStatus::Applied),
+        ("Pending", MigrationStatus::Pending),
+        ("ChecksumMismatch", MigrationStatus::ChecksumMismatch),
+        ("Failed", MigrationStatus::Failed),
+        ("Unknown", MigrationStatus::Unknown),
+    ] {
+        fixtures.push(Fixture::new(format!("MigrationStatus::{name}"), status));
+    }
+
+    fixtures
+}
+
+fn collections() -> Vec<Fixture> {
+    let manifest = CollectionDownloadManifest {
+        collection_id: id(10),
+        expires_at: at("2025-03-01T13:34:56.789Z"),
+        missing_count: 1,
+        files: vec![CollectionDownloadManifestFile {
+            id: id(1),
+            name: "report.pdf".to_owned(),
+            size: 1_048_576,
+            url: "https://file-indexer.s3.amazonaws.com/report".to_owned(),
+        }],
+    };
+
+    vec![
+        Fixture::new("Collection", collection()),
+        Fixture::new(
+            "CollectionCursor",
+            CollectionCursor {
+                id: id(10),
+                name: "Invoices".to_owned(),
+            },
+        ),
+        Fixture::new(
+            "CollectionFileCursor",
+            CollectionFileCursor {
+                id: id(1),
+                name: "report.pdf".to_owned(),
+            },
+        ),
+        Fixture::new("CollectionFileListSort::Name", CollectionFileListSort::Name),
+        Fixture::new(
+            "CollectionFileListSort::NameNatural",
+            CollectionFileListSort::NameNatural,
+        ),
+        Fixture::new(
+            "CreatingCollection",
+            CreatingCollection {
+                name: "Invoices".to_owned(),
+                unique_names: true,
+                tags: vec!["invoice".to_owned()],
+            },
+        ),
+        Fixture::new(
+            "UpdatingCollection",
+            UpdatingCollection {
+                name: Some("Bills".to_owned()),
+                unique_names: None,
+                tags_for_creation: Some(vec!["bill".to_owned()]),
+                tags_for_deletion: Some(vec!["invoice".to_owned()]),
+            },
+        ),
+        Fixture::new(
+            "CollectionSearchQuery",
+            CollectionSearchQuery {
+                q: "invoices".to_owned(),
+                limit: 10,
+                offset: 20,
+                matching_strategy: Some(SearchMatchingStrategy::All),
+                attributes_to_highlight: Some(vec![SearchAttribute::Name]),
+                crop_length: Some(8),
+            },
+        ),
+        Fixture::new(
+            "CollectionSearchHit",
+            CollectionSearchHit {
+                collection: collection(),
+                highlights: Some(highlights()),
+            },
+        ),
+        Fixture::new("CollectionDownloadManifest", manifest.clone()),
+        Fixture::new(
+            "CreatedCollectionDownloadManifest::Inline",
+            CreatedCollectionDownloadManifest {
+                manifest: Some(manifest),
+                admin_task_id: None,
+            },
+        ),
+        Fixture::new(
+            "CreatedCollectionDownloadManifest::Task",
+            CreatedCollectionDownloadManifest {
+                manifest: None,
+                admin_task_id: Some(id(20)),
+            },
+        ),
+    ]
+}
+
+fn files() -> Vec<Fixture> {
+    let upload = FileUploadUrl {
+        id: "upload-1".to_owned(),
+        part_count: 2,
+        parts: vec![FileUploadUrlPart {
+            part_number: 1,
+            url: "https://file-indexer.s3.amazonaws.com/part-1".to_owned(),
+            offset: 0,
+            size: 8_388_608,
+        }],
+        expires_at: at("2025-03-01T13:34:56.789Z"),
+    };
+    let creating_file = CreatingFile {
+        name: "report.pdf".to_owned(),
+        size: 1_048_576,
+        mime_type: "application/pdf".to_owned(),
+        checksum: None,
+        tags: Some(vec!["invoice".to_owned()]),
+        allow_duplicate: true,
+    };
+    let filters = vec![
+        (
+            "Size",
+            FileSearchQueryFilter::Size {
+                operator: FileSearchQueryFilterOperator::Gte,
+                value: 1024,
+            },
+        ),
+        (
+            "MimeType",
+            FileSearchQueryFilter::MimeType {
+                value: "application/pdf".to_owned(),
+            },
+        ),
+        (
+            "NotMimeType",
+            FileSearchQueryFilter::NotMimeType {
+                value: "image/png".to_owned(),
+            },
+        ),
+        (
+            "Tag",
+            FileSearchQueryFilter::Tag {
+                value: "invoice".to_owned(),
+            },
+        ),
+        (
+            "NotTag",
+            FileSearchQueryFilter::NotTag {
+                value: "draft".to_owned(),
+            },
+        ),
+        ("TagIsEmpty", FileSearchQueryFilter::TagIsEmpty),
+        ("TagIsNotEmpty", FileSearchQueryFilter::TagIsNotEmpty),
+        (
+            "Exists",
+            FileSearchQueryFilter::Exists {
+                field: "checksum".to_owned(),
+            },
+        ),
+        (
+            "NotExists",
+            FileSearchQueryFilter::NotExists {
+                field: "checksum".to_owned(),
+            },
+        ),
+        (
+            "TruncatedForIndex",
+            FileSearchQueryFilter::TruncatedForIndex { value: true },
+        ),
+        (
+            "Protected",
+            FileSearchQueryFilter::Protected { value: false },
+        ),
+        (
+            "SizeBucket",
+            FileSearchQueryFilter::SizeBucket {
+                value: "1-10MB".to_owned(),
+            },
+        ),
+        (
+            "UploadedAt",
+            FileSearchQueryFilter::UploadedAt {
+                operator: FileSearchQueryFilterOperator::Lt,
+                value: at("2025-03-01T12:34:56.789Z"),
+            },
+        ),
+    ];
+
+    let mut fixtures = vec![
+        Fixture::new("File", file()),
+        Fixture::new("File::Full", full_file()),
+        Fixture::new(
+            "FileCollection",
+            FileCollection {
+                id: id(10),
+                name: "Invoices".to_owned(),
+            },
+        ),
+        Fixture::new(
+            "FileCursor",
+            FileCursor {
+                id: id(1),
+                uploaded_at: at("2025-03-01T12:34:56.789Z"),
+            },
+        ),
+        Fixture::new(
+            "FileCursor::WholeSeconds",
+            FileCursor {
+                id: id(1),
+                uploaded_at: at("2025-03-01T12:34:56Z"),
+            },
+        ),
+        Fixture::new(
+            "FileNameCursor",
+            FileNameCursor {
+                id: id(1),
+                name: "report.pdf".to_owned(),
+            },
+        ),
+        Fixture::new("FileListSort::UploadedAt", FileListSort::UploadedAt),
+        Fixture::new("FileListSort::NameNatural", FileListSort::NameNatural),
+        Fixture::new(
+            "FileDownloadUrl",
+            FileDownloadUrl {
+                url: "https://file-indexer.s3.amazonaws.com/report".to_owned(),
+                file_id: Some(id(2)),
+                expires_at: at("2025-03-01T13:34:56.789Z"),
+            },
+        ),
+        Fixture::new("CreatingFile", creating_file.clone()),
+        Fixture::new(
+            "CreatingFiles",
+            CreatingFiles {
+                files: vec![creating_file],
+            },
+        ),
+        Fixture::new(
+            "CreatingFileVersion",
+            CreatingFileVersion {
+                name: None,
+                size: 2_097_152,
+                mime_type: "application/pdf".to_owned(),
+                checksum: Some(
+                    "60303ae22b998861bce3b28f33eec1be758a213c86c93c076dbe9f558c11c752".to_owned(),
+                ),
+            },
+        ),
+        Fixture::new(
+            "FileVersion",
+            FileVersion {
+                version_number: 2,
+                file: file(),
+            },
+        ),
+        Fixture::new("FileUploadUrl", upload.clone()),
+        Fixture::new(
+            "FileWithIndexStatus",
+            FileWithIndexStatus {
+                file: file(),
+                index_status: Some(FileIndexStatus {
+                    indexed: true,
+                    indexed_at: Some(at("2025-03-01T12:34:58.123Z")),
+                }),
+            },
+        ),
+        Fixture::new(
+            "CreatedFile",
+            CreatedFile {
+                file: file(),
+                upload: Some(upload),
+                upload_error: None,
+            },
+        ),
+        Fixture::new(
+            "CreatedFile::UploadFailed",
+            CreatedFile {
+                file: file(),
+                upload: None,
+                upload_error: Some("storage_unavailable".to_owned()),
+            },
+        ),
+        Fixture::new(
+            "FileUploadRecord",
+            FileUploadRecord {
+                upload_id: "upload-1".to_owned(),
+                part_count: 2,
+                total_bytes: 9_437_184,
+                outcome: UploadOutcome::Completed,
+                started_at: at("2025-03-01T12:30:00.000Z"),
+                completed_at: Some(at("2025-03-01T12:34:56.789Z")),
+            },
+        ),
+        Fixture::new(
+            "FileUploadProgress",
+            FileUploadProgress {
+                bytes_received: 8_388_608,
+                total_bytes: 9_437_184,
+                parts_received: 1,
+                parts_total: 2,
+                percent: 88.89,
+            },
+        ),
+        Fixture::new(
+            "FileCostReport",
+            FileCostReport {
+                file_count: 3,
+                bytes: 3_000_000_000,
+                cost: 0.069,
+                storage_classes: vec![StorageClassCost {
+                    storage_class: StorageClass::Standard,
+                    rate_per_gb_month: 0.023,
+                    file_count: 3,
+                    bytes: 3_000_000_000,
+                    cost: 0.069,
+                }],
+                collections: Some(vec![CollectionCost {
+                    id: id(10),
+                    name: "Invoices".to_owned(),
+                    file_count: 1,
+                    bytes: 1_000_000_000,
+                    cost: 0.023,
+                }]),
+            },
+        ),
+        Fixture::new(
+            "CreatingFileExport",
+            CreatingFileExport {
+                format: FileExportFormat::Csv,
+            },
+        ),
+        Fixture::new(
+            "BulkDeletingFiles::FileIds",
+            BulkDeletingFiles {
+                file_ids: Some(vec![id(1), id(2)]),
+                query: None,
+            },
+        ),
+        Fixture::new(
+            "BulkDeletingFiles::Query",
+            BulkDeletingFiles {
+                file_ids: None,
+                query: Some(file_search_query(vec![vec![FileSearchQueryFilter::Tag {
+                    value: "draft".to_owned(),
+                }]])),
+            },
+        ),
+        Fixture::new(
+            "BulkTaggingFiles",
+            BulkTaggingFiles {
+                file_ids: vec![id(1), id(2)],
+                add: vec!["archived".to_owned()],
+                remove: vec!["draft".to_owned()],
+            },
+        ),
+        Fixture::new(
+            "BulkTaggedFiles",
+            BulkTaggedFiles {
+                files: vec![
+                    BulkTaggedFile {
+                        file_id: id(1),
+                        outcome: BulkTagOutcome::Updated,
+                        added: vec!["archived".to_owned()],
+                        removed: vec!["draft".to_owned()],
+                    },
+                    BulkTaggedFile {
+                        file_id: id(2),
+                        outcome: BulkTagOutcome::NotFound,
+                        added: Vec::new(),
+                        removed: Vec::new(),
+                    },
+                ],
+                updated_count: 1,
+                not_found_count: 1,
+            },
+        ),
+        Fixture::new(
+            "UploadedParts",
+            UploadedParts {
+                parts: vec![UploadedPart {
+                    part_number: 1,
+                    e_tag: "\"9b2cf535f27731c974343645a3985328\"".to_owned(),
+                }],
+            },
+        ),
+        Fixture::new(
+            "UpdatingFile",
+            UpdatingFile {
+                name: Some("report-final.pdf".to_owned()),
+                size: None,
+                mime_type: None,
+                tags_for_creation: Some(vec!["final".to_owned()]),
+                tags_for_deletion: None,
+                protected: Some(true),
+                superseded_by: Some(Some(id(2))),
+            },
+        ),
+        Fixture::new(
+            "UpdatingFile::ClearingSupersededBy",
+            UpdatingFile {
+                name: None,
+                size: None,
+                mime_type: None,
+                tags_for_creation: None,
+                tags_for_deletion: None,
+                protected: None,
+                superseded_by: Some(None),
+            },
+        ),
+        Fixture::new(
+            "FileSearchQuery",
+            FileSearchQuery {
+                limit: 50,
+                offset: 100,
+                matching_strategy: Some(SearchMatchingStrategy::Last),
+                attributes_to_highlight: Some(vec![SearchAttribute::Name, SearchAttribute::Tags]),
+                crop_length: Some(10),
+                distinct: Some(FileSearchDistinct::Checksum),
+                include_unready: true,
+                include_superseded: true,
+                ..file_search_query(vec![
+                    vec![
+                        FileSearchQueryFilter::Tag {
+                            value: "invoice".to_owned(),
+                        },
+                        FileSearchQueryFilter::TagIsEmpty,
+                    ],
+                    vec![FileSearchQueryFilter::Size {
+                        operator: FileSearchQueryFilterOperator::Lt,
+                        value: 1_000_000,
+                    }],
+                ])
+            },
+        ),
+        Fixture::new(
+            "FileSearchHit",
+            FileSearchHit {
+                file: file(),
+                highlights: Some(highlights()),
+                duplicate_count: Some(2),
+                collections: vec![FileCollection {
+                    id: id(10),
+                    name: "Invoices".to_owned(),
+                }],
+                truncated_for_index: false,
+            },
+        ),
+        Fixture::new(
+            "FileSearchResult",
+            FileSearchResult {
+                hits: vec![FileSearchHit {
+                    file: file(),
+                    highlights: None,
+                    duplicate_count: None,
+                    collections: Vec::new(),
+                    truncated_for_index: true,
+                }],
+                skipped_hits: 1,
+            },
+        ),
+        Fixture::new(
+            "FileSearchFacets",
+            FileSearchFacets {
+                size_bucket: vec![FileSearchFacetCount {
+                    value: "<1MB".to_owned(),
+                    count: 4,
+                }],
+            },
+        ),
+    ];
+
+    for (name, filter) in filters {
+        fixtures.push(Fixture::new(
+            format!("FileSearchQueryFilter::{name}"),
+            filter,
+        ));
+    }
+
+    for (name, operator) in [
+        ("Eq", FileSearchQueryFilterOperator::Eq),
+        ("Neq", FileSearchQueryFilterOperator::Neq),
+        ("Gt", FileSearchQueryFilterOperator::Gt),
+        ("Gte", FileSearchQueryFilterOperator::Gte),
+        ("Lt", FileSearchQueryFilterOperator::Lt),
+        ("Lte", FileSearchQueryFilterOperator::Lte),
+    ] {
+        fixtures.push(Fixture::new(
+            format!("FileSearchQueryFilterOperator::{name}"),
+            operator,
+        ));
+    }
+
+    for (name, outcome) in [
+        ("Pending", UploadOutcome::Pending),
+        ("Completed", UploadOutcome::Completed),
+        ("Aborted", UploadOutcome::Aborted),
+        ("Expired", UploadOutcome::Expired),
+    ] {
+        fixtures.push(Fixture::new(format!("UploadOutcome::{name}"), outcome));
+    }
+
+    for (name, outcome) in [
+        ("Updated", BulkTagOutcome::Updated),
+        ("Unchanged", BulkTagOutcome::Unchanged),
+        ("NotFound", BulkTagOutcome::NotFound),
+    ] {
+        fixtures.push(Fixture::new(format!("BulkTagOutcome::{name}"), outcome));
+    }
+
+    fixtures.push(Fixture::new(
+        "StorageClass::Standard",
+        StorageClass::Standard,
+    ));
+    fixtures.push(Fixture::new(
+        "FileExportFormat::Ndjson",
+        FileExportFormat::Ndjson,
+    ));
+    fixtures.push(Fixture::new("FileExportFormat::Csv", FileExportFormat::Csv));
+    fixtures.push(Fixture::new(
+        "FileSearchDistinct::Checksum",
+        FileSearchDistinct::Checksum,
+    ));
+
+    fixtures
+}
+
+fn tags() -> Vec<Fixture> {
+    vec![
+        Fixture::new("TagDefinition", tag_definition()),
+        Fixture::new(
+            "CreatingTagDefinition",
+            CreatingTagDefinition {
+                name: "invoice".to_owned(),
+                color: Some("#1F77B4".to_owned()),
+                description: None,
+            },
+        ),
+        Fixture::new(
+            "UpdatingTagDefinition",
+            UpdatingTagDefinition {
+                name: None,
+                color: Some(String::new()),
+                description: Some("Bills".to_owned()),
+            },
+        ),
+        Fixture::new(
+            "RenamingTag",
+            RenamingTag {
+                new_name: "bill".to_owned(),
+            },
+        ),
+        Fixture::new(
+            "TagRenamePreview",
+            TagRenamePreview {
+                tag: "invoice".to_owned(),
+                new_name: "bill".to_owned(),
+                target_exists: true,
+                files: TagRenameImpact {
+                    renamed_count: 4312,
+                    merged_count: 77,
+                    sample_names: vec!["report.pdf".to_owned()],
+                },
+                collections: TagRenameImpact::default(),
+            },
+        ),
+    ]
+}
@@ -0,0 +1,180 @@
+use super::FieldError;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub const TAG_NAME_MAX_LENGTH: usize = 256;
+pub const TAG_DESCRIPTION_MAX_LENGTH: usize = 1024;
+
+/// How a tag is presented across the app. A tag does not need a definition to be used.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagDefinition {
+    pub name: String,
+    /// Lowercase `#rrggbb` hex color.
+    pub color: Option<String>,
+    pub description: Option<String>,
+    #[serde(
+        serialize_with = "crate::interfaces::timestamp::serialize",
+        deserialize_with = "crate::interfaces::timestamp::deserialize"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatingTagDefinition {
+    /// Surrounding whitespace is trimmed.
+    pub name: String,
+    /// `#rrggbb` hex color, in either case.
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl CreatingTagDefinition {
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        validate_name("name", &self.name, &mut errors);
+        validate_color(self.color.as_deref(), &mut errors);
+        validate_description(self.description.as_deref(), &mut errors);
+
+        errors
+    }
+}
+
+/// Fields left out are not touched. An empty `color` or `description` clears it.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatingTagDefinition {
+    /// Renames the definition only; the tags in use keep their name.
+    pub name: Option<String>,
+    pub color: Option<String>,
+    pub description: Option<String>,
+}
+
+impl UpdatingTagDefinition {
+    /// Whether the update names no field at all.
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.color.is_none() && self.description.is_none()
+    }
+
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        if let Some(name) = &self.name {
+            validate_name("name", name, &mut errors);
+        }
+
+        validate_color(
+            self.color.as_deref().filter(|color| !color.is_empty()),
+            &mut errors,
+        );
+        validate_description(self.description.as_deref(), &mut errors);
+
+        errors
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamingTag {
+    /// Surrounding whitespace is trimmed.
+    pub new_name: String,
+}
+
+impl RenamingTag {
+    pub fn validate(&self, tag: &str) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        validate_name("newName", &self.new_name, &mut errors);
+
+        if self.new_name.trim() == tag {
+            errors.push(FieldError::new("newName", "must differ from the tag"));
+        }
+
+        errors
+    }
+}
+
+/// What renaming a tag would change, counted without changing anything.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TagRenamePreview {
+    pub tag: String,
+    pub new_name: String,
+    /// Whether a file or a collection has the new name already, in which case the tag is merged
+    /// into it where both are present.
+    pub target_exists: bool,
+    pub files: TagRenameImpact,
+    pub collections: TagRenameImpact,
+}
+
+/// How renaming a tag affects the files or the collections that have it.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TagRenameImpact {
+    /// Those with the tag but not the new name, on which the tag is renamed.
+    pub renamed_count: u64,
+    /// Those with both, on which the tag is merged into the new name.
+    pub merged_count: u64,
+    /// The names of a few of either, by name.
+    pub sample_names: Vec<String>,
+}
+
+/// Checks the tags given to a file or a collection, each under `field[index]`. Tags are stored as
+/// they are given, so only the rules of [`tag_name_error`] apply.
+pub fn validate_tags(field: &str, tags: &[String]) -> Vec<FieldError> {
+    Vec::from_iter(tags.iter().enumerate().filter_map(|(index, tag)| {
+        tag_name_error(tag).map(|message| FieldError::new(format!("{field}[{index}]"), message))
+    }))
+}
+
+fn validate_name(field: &str, name: &str, errors: &mut Vec<FieldError>) {
+    if let Some(message) = tag_name_error(name.trim()) {
+        errors.push(FieldError::new(field, message));
+    }
+}
+
+/// Why `name` cannot name a tag, if it cannot. Tags are filterable attributes of the search
+/// index, where huge values or line breaks have made whole batches of documents fail, so they are
+/// kept short and printable.
+fn tag_name_error(name: &str) -> Option<String> {
+    if name.trim().is_empty() {
+        Some("must not be blank".to_owned())
+    } else if TAG_NAME_MAX_LENGTH < name.chars().count() {
+        Some(format!(
+            "must be at most {TAG_NAME_MAX_LENGTH} characters long"
+        ))
+    } else if name.chars().any(char::is_control) {
+        Some("must not contain control characters, such as line breaks".to_owned())
+    } else {
+        None
+    }
+}
+
+fn validate_color(color: Option<&str>, errors: &mut Vec<FieldError>) {
+    let Some(color) = color else {
+        return;
+    };
+    let is_valid = color
+        .strip_prefix('#')
+        .is_some_and(|hex| hex.len() == 6 && hex.bytes().all(|byte| byte.is_ascii_hexdigit()));
+
+    if !is_valid {
+        errors.push(FieldError::new("color", "must be a `#rrggbb` hex color"));
+    }
+}
+
+fn validate_description(description: Option<&str>, errors: &mut Vec<FieldError>) {
+    if description
+        .is_some_and(|description| TAG_DESCRIPTION_MAX_LENGTH < description.chars().count())
+    {
+        errors.push(FieldError::new(
+            "description",
+            format!("must be at most {TAG_DESCRIPTION_MAX_LENGTH} characters long"),
+        ));
+    }
+}
@@ -0,0 +1,56 @@
+//! Serde functions for the timestamps of the DTOs, for use with `serialize_with` and
+//! `deserialize_with`. Timestamps are written as RFC 3339 in UTC with millisecond precision and a
+//! `Z` suffix, e.g. `2025-03-01T12:34:56.789Z`, so that an instant reads the same in every
+//! response whether it came from the database or the search index. Any RFC 3339 offset, at any
+//! precision, is accepted on input.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{de, Deserialize, Deserializer, Serializer};
+use std::borrow::Cow;
+
+pub fn serialize<S>(date_time: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&date_time.to_rfc3339_opts(SecondsFormat::Millis, true))
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Cow::<str>::deserialize(deserializer)?;
+
+    DateTime::parse_from_rfc3339(&value)
+        .map(|date_time| date_time.with_timezone(&Utc))
+        .map_err(de::Error::custom)
+}
+
+pub fn serialize_option<S>(
+    date_time: &Option<DateTime<Utc>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match date_time {
+        Some(date_time) => serialize(date_time, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Pair with `#[serde(default)]`, so that a missing field reads as `None` as well.
+pub fn deserialize_option<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<Cow<str>>::deserialize(deserializer)?;
+
+    value
+        .map(|value| {
+            DateTime::parse_from_rfc3339(&value)
+                .map(|date_time| date_time.with_timezone(&Utc))
+                .map_err(de::Error::custom)
+        })
+        .transpose()
+}
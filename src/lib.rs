@@ -0,0 +1,179 @@
+#![forbid(unsafe_code)]
+
+pub mod config;
+pub mod db;
+pub mod fairings;
+pub mod forms;
+pub mod guards;
+pub mod interfaces;
+pub mod routes;
+pub mod services;
+pub mod telemetry;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+use config::Config;
+use fairings::{
+    cors::Cors,
+    file_gc::FileGc,
+    index_flusher::IndexFlusher,
+    request_tracing::RequestTracing,
+    task_runner::{
+        backfill_checksums::BackfillChecksumsHandler, backfill_storage::BackfillStorageHandler,
+        bulk_delete_files::BulkDeleteFilesHandler, bundle_collection::BundleCollectionHandler,
+        collection_download_manifest::CollectionDownloadManifestHandler,
+        export_files::ExportFilesHandler, re_index_collection_files::ReIndexCollectionFilesHandler,
+        re_index_collections::ReIndexCollectionsHandler, re_index_files::ReIndexFilesHandler,
+        reconcile_storage::ReconcileStorageHandler, rename_tag::RenameTagHandler,
+        retry_index::RetryIndexHandler, TaskRunner,
+    },
+};
+use guards::batch_json::{BATCH_JSON_LIMIT, BATCH_JSON_LIMIT_NAME, JSON_LIMIT};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use rocket::{data::Limits, Build, Rocket};
+use services::Services;
+
+/// Builds a server ready to launch on the address and port of the configuration, serving the API
+/// at the root. Telemetry is left to the caller, which hands over its tracer provider, if any, to
+/// be flushed on shutdown.
+pub fn build_rocket(
+    config: Config,
+    services: Services,
+    tracer_provider: Option<SdkTracerProvider>,
+) -> Rocket<Build> {
+    let rocket_config = rocket::Config {
+        address: config.server.address,
+        port: config.server.port,
+        limits: Limits::default()
+            .limit("json", JSON_LIMIT)
+            .limit(BATCH_JSON_LIMIT_NAME, BATCH_JSON_LIMIT),
+        ..rocket::Config::default()
+    };
+
+    mount(
+        rocket::custom(&rocket_config),
+        "/",
+        config,
+        services,
+        tracer_provider,
+    )
+}
+
+/// Mounts the API under `base` of a server built elsewhere, along with the background fairings
+/// and the state it needs. The server configuration of `config` is not applied; the body limits
+/// fall back to [`JSON_LIMIT`] and [`BATCH_JSON_LIMIT`] unless the server configures them.
+pub fn mount(
+    rocket: Rocket<Build>,
+    base: &str,
+    config: Config,
+    services: Services,
+    tracer_provider: Option<SdkTracerProvider>,
+) -> Rocket<Build> {
+    let Services {
+        admin_service,
+        admin_task_service,
+        bundle_service,
+        collection_service,
+        export_service,
+        file_service,
+        index_service,
+        migration_service,
+        object_storage,
+        scheduled_task_service,
+        tag_definition_service,
+        token_service,
+    } = services;
+
+    let file_gc = FileGc::new(
+        admin_service.clone(),
+        admin_task_service.clone(),
+        file_service.clone(),
+        index_service.clone(),
+        object_storage.clone(),
+        config.file_gc,
+        config.housekeeping,
+    );
+    let index_flusher = IndexFlusher::new(
+        admin_task_service.clone(),
+        file_service.clone(),
+        index_service.clone(),
+        config.index_sweep,
+    );
+    let task_runner = TaskRunner::new(admin_task_service.clone(), scheduled_task_service.clone())
+        .with_handler(ReIndexFilesHandler::new(
+            file_service.clone(),
+            index_service.clone(),
+        ))
+        .with_handler(ReIndexCollectionsHandler::new(
+            collection_service.clone(),
+            index_service.clone(),
+        ))
+        .with_handler(ReIndexCollectionFilesHandler::new(
+            file_service.clone(),
+            index_service.clone(),
+        ))
+        .with_handler(RenameTagHandler::new(
+            collection_service.clone(),
+            file_service.clone(),
+            index_service.clone(),
+        ))
+        .with_handler(ExportFilesHandler::new(export_service.clone()))
+        .with_handler(CollectionDownloadManifestHandler::new(
+            export_service.clone(),
+            config.presign.download_url,
+        ))
+        .with_handler(BundleCollectionHandler::new(
+            bundle_service,
+            file_service.clone(),
+            index_service.clone(),
+            object_storage.clone(),
+            config.upload,
+        ))
+        .with_handler(BulkDeleteFilesHandler::new(
+            admin_task_service.clone(),
+            file_service.clone(),
+            index_service.clone(),
+            object_storage.clone(),
+        ))
+        .with_handler(ReconcileStorageHandler::new(
+            file_service.clone(),
+            index_service.clone(),
+            object_storage.clone(),
+        ))
+        .with_handler(RetryIndexHandler::new(
+            collection_service.clone(),
+            file_service.clone(),
+            index_service.clone(),
+        ))
+        .with_handler(BackfillChecksumsHandler::new(
+            file_service.clone(),
+            index_service.clone(),
+            object_storage.clone(),
+        ))
+        .with_handler(BackfillStorageHandler::new(file_service.clone()));
+
+    let rocket = rocket
+        .attach(RequestTracing::new(tracer_provider))
+        .attach(Cors::new(config.cors))
+        .attach(file_gc)
+        .attach(index_flusher)
+        .attach(task_runner)
+        .manage(admin_service)
+        .manage(admin_task_service)
+        .manage(collection_service)
+        .manage(export_service)
+        .manage(file_service)
+        .manage(index_service)
+        .manage(migration_service)
+        .manage(config.proxy)
+        .manage(config.presign)
+        .manage(config.upload)
+        .manage(config.storage_cost)
+        .manage(config.route_timeouts)
+        .manage(object_storage)
+        .manage(scheduled_task_service)
+        .manage(tag_definition_service)
+        .manage(token_service);
+
+    routes::register(rocket, base)
+}
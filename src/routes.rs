@@ -1,36 +1,846 @@
 mod admin_tasks;
+mod admins;
 mod collections;
 mod files;
+mod health;
+mod scheduled_tasks;
 mod searches;
+mod tags;
 
+use crate::{
+    guards::{authenticated_admin::AuthenticatedAdmin, batch_json::BodyLimit},
+    interfaces::{
+        admins::{AdminRole, AdminTaskInitiator, TaskKind},
+        collections::Collection,
+        files::{File, FileUploadProgress},
+        tags::TagDefinition,
+        FieldError,
+    },
+    services::{
+        admin_task_service::AdminTaskService, s3_service::S3ServiceError,
+        tag_definition_service::TagDefinitionService,
+    },
+    telemetry::ErrorChain,
+};
 use rocket::{
-    catch, catchers, http::Status, options, routes, serde::json::Json, Build, Request, Rocket,
+    catch, catchers,
+    http::Status,
+    options,
+    response::{self, Responder},
+    routes,
+    serde::json::Json,
+    Build, Request, Response, Rocket,
+};
+use rocket_okapi::{
+    gen::OpenApiGenerator,
+    get_openapi_route,
+    okapi::{
+        merge::marge_spec_list,
+        openapi3::{self, MediaType, OpenApi, RefOr, Responses},
+    },
+    response::OpenApiResponderInner,
+    settings::OpenApiSettings,
 };
+use schemars::{gen::SchemaGenerator, JsonSchema};
 use serde::Serialize;
+use std::{collections::HashSet, future::Future, time::Duration};
+use uuid::Uuid;
+
+/// Largest page the list routes serve to callers below the admin role.
+pub const LIST_MAX_LIMIT: usize = 100;
+/// Largest page the list routes serve to admins, for batch jobs that page through everything.
+/// The list forms accept up to this much, and [`check_list_limit`] narrows it for everyone else.
+pub const LIST_ADMIN_MAX_LIMIT: usize = 1000;
+
+/// Rejects pages larger than [`LIST_MAX_LIMIT`] unless the caller has the admin role.
+pub fn check_list_limit(
+    limit: usize,
+    admin: Option<&AuthenticatedAdmin>,
+) -> Result<(), RouteError> {
+    let is_admin = admin.is_some_and(|admin| admin.admin.role == AdminRole::Admin);
+
+    if LIST_MAX_LIMIT < limit && !is_admin {
+        return Err(RouteError::LimitNotAllowed);
+    }
+
+    Ok(())
+}
+
+/// Clears where the files are stored unless the caller has the admin role.
+pub fn redact_storage<'a>(
+    files: impl IntoIterator<Item = &'a mut File>,
+    admin: Option<&AuthenticatedAdmin>,
+) {
+    if admin.is_some_and(|admin| admin.admin.role == AdminRole::Admin) {
+        return;
+    }
+
+    for file in files {
+        file.storage = None;
+    }
+}
+
+/// A response item that carries tags, along with the definitions of those tags.
+pub trait WithTagDefinitions {
+    fn tags(&self) -> &[String];
+    fn set_tag_definitions(&mut self, tag_definitions: Vec<TagDefinition>);
+}
+
+impl WithTagDefinitions for File {
+    fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    fn set_tag_definitions(&mut self, tag_definitions: Vec<TagDefinition>) {
+        self.tag_definitions = tag_definitions;
+    }
+}
+
+impl WithTagDefinitions for Collection {
+    fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    fn set_tag_definitions(&mut self, tag_definitions: Vec<TagDefinition>) {
+        self.tag_definitions = tag_definitions;
+    }
+}
+
+/// Attaches the definitions of the tags of the items, looked up in one query for the distinct
+/// tags of all of them. The items are left without definitions if the lookup fails.
+pub async fn attach_tag_definitions<'a, T>(
+    tag_definition_service: &TagDefinitionService,
+    items: impl IntoIterator<Item = &'a mut T>,
+) where
+    T: WithTagDefinitions + 'a,
+{
+    let items = Vec::from_iter(items);
+    let tags = Vec::from_iter(
+        items
+            .iter()
+            .flat_map(|item| item.tags())
+            .cloned()
+            .collect::<HashSet<_>>(),
+    );
+    let tag_definitions = match tag_definition_service.find_tag_definitions(&tags).await {
+        Ok(tag_definitions) => tag_definitions,
+        Err(err) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to look up tag definitions");
+            return;
+        }
+    };
+
+    if tag_definitions.is_empty() {
+        return;
+    }
 
-pub fn register_root(rocket: Rocket<Build>) -> Rocket<Build> {
-    rocket
-        .register("/", catchers![default])
-        .mount("/", routes![all_options])
-        .mount("/admin-tasks", admin_tasks::routes())
-        .mount("/collections", collections::routes())
-        .mount("/files", files::routes())
-        .mount("/searches", searches::routes())
+    for item in items {
+        let item_tag_definitions = Vec::from_iter(
+            item.tags()
+                .iter()
+                .filter_map(|tag| tag_definitions.get(tag).cloned()),
+        );
+        item.set_tag_definitions(item_tag_definitions);
+    }
+}
+
+/// How long the routes wait for each dependency before answering `504 Gateway Timeout`. The
+/// search engine timeouts are applied by the `IndexService`, which counts them as outages.
+#[derive(Debug, Clone, Copy)]
+pub struct RouteTimeouts {
+    /// Searches and other reads from the search engine.
+    pub search: Duration,
+    /// Writes to the search engine.
+    pub index: Duration,
+    /// S3 calls, including whole batches of presigned URLs.
+    pub storage: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dependency {
+    SearchEngine,
+    Storage,
+}
+
+impl Dependency {
+    fn timeout_code(self) -> &'static str {
+        match self {
+            Dependency::SearchEngine => "search_engine_timeout",
+            Dependency::Storage => "storage_timeout",
+        }
+    }
+}
+
+/// Awaits a call to a dependency for at most `duration`. On timeout the call is dropped, which
+/// cancels whatever it still had in flight.
+pub async fn with_timeout<F>(
+    dependency: Dependency,
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, RouteError>
+where
+    F: Future,
+{
+    match tokio::time::timeout(duration, future).await {
+        Ok(output) => Ok(output),
+        Err(_) => {
+            tracing::warn!("{dependency:?} did not respond within {duration:?}");
+            Err(RouteError::Timeout(dependency))
+        }
+    }
+}
+
+/// Hands index changes the search engine did not take to a `retry-index` admin task, which applies
+/// them once the search engine is reachable again. Returns the id of the task, or `None` if it
+/// could not be enqueued.
+pub async fn defer_indexing(
+    admin_task_service: &AdminTaskService,
+    changes: serde_json::Value,
+) -> Option<Uuid> {
+    let result = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::System,
+            None,
+            TaskKind::RetryIndex,
+            changes,
+            None,
+            false,
+            None,
+        )
+        .await;
+
+    match result {
+        Ok(task) => Some(task.id),
+        Err(err) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to enqueue deferred indexing");
+            None
+        }
+    }
+}
+
+/// Registers the routes and catchers under `base`, with the OpenAPI document at
+/// `<base>/openapi.json`.
+pub fn register(rocket: Rocket<Build>, base: &str) -> Rocket<Build> {
+    let settings = OpenApiSettings::default();
+    let base = base.trim_end_matches('/');
+    let root = if base.is_empty() { "/" } else { base };
+    let mut rocket = rocket
+        .register(root, catchers![payload_too_large, default])
+        .mount(root, routes![all_options]);
+    let mut specs = Vec::new();
+
+    for (path, (routes, spec)) in [
+        ("/admin-tasks", admin_tasks::routes(&settings)),
+        ("/admin-tasks/schedules", scheduled_tasks::routes(&settings)),
+        ("/admins", admins::routes(&settings)),
+        ("/collections", collections::routes(&settings)),
+        ("/files", files::routes(&settings)),
+        ("/health", health::routes(&settings)),
+        ("/searches", searches::routes(&settings)),
+        ("/tags", tags::routes(&settings)),
+    ] {
+        let path = format!("{base}{path}");
+        rocket = rocket.mount(path.as_str(), routes);
+        specs.push((path, spec));
+    }
+
+    let spec = match marge_spec_list(&specs) {
+        Ok(spec) => spec,
+        Err(err) => panic!("failed to merge openapi specs: {err}"),
+    };
+
+    rocket.mount(
+        root,
+        vec![get_openapi_route(finalize_spec(spec), &settings)],
+    )
+}
+
+/// Fills in the document info and attaches the error body schema to every `default` response,
+/// since the error statuses are only known at runtime.
+fn finalize_spec(mut spec: OpenApi) -> OpenApi {
+    spec.info.title = env!("CARGO_PKG_NAME").to_owned();
+    spec.info.version = env!("CARGO_PKG_VERSION").to_owned();
+
+    let mut gen = SchemaGenerator::new(OpenApiSettings::default().schema_settings);
+    let error_schema = gen.subschema_for::<ErrorBody>().into_object();
+    let components = spec.components.get_or_insert_with(Default::default);
+    components.schemas.extend(
+        gen.take_definitions()
+            .into_iter()
+            .map(|(name, schema)| (name, schema.into_object())),
+    );
+
+    for path in spec.paths.values_mut() {
+        let operations = [
+            &mut path.get,
+            &mut path.put,
+            &mut path.post,
+            &mut path.delete,
+            &mut path.options,
+            &mut path.head,
+            &mut path.patch,
+            &mut path.trace,
+        ];
+
+        for operation in operations.into_iter().flatten() {
+            let response = operation
+                .responses
+                .responses
+                .entry("default".to_owned())
+                .or_insert_with(|| openapi3::Response::default().into());
+
+            if let RefOr::Object(response) = response {
+                response.description = "Error response.".to_owned();
+                response.content.insert(
+                    "application/json".to_owned(),
+                    MediaType {
+                        schema: Some(error_schema.clone()),
+                        ..MediaType::default()
+                    },
+                );
+            }
+        }
+    }
+
+    spec
 }
 
 #[options("/<_..>")]
 fn all_options() {}
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
 struct ErrorBody<'a> {
     pub status: u16,
     pub message: Option<&'a str>,
+    /// Machine-readable reason, for errors a status alone does not explain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<&'a str>,
+    /// The rejected fields, for validation failures.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+    /// The task already pending or in progress, for `task_already_queued` conflicts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<Uuid>,
+    /// The data limit the request body exceeded, in bytes, for `payload_too_large` errors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_bytes: Option<u64>,
+    /// The file names taken more than once, for `name_conflict` conflicts.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub names: Vec<String>,
+    /// What is taken already, for `conflict` conflicts: the unique key, and its value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// The file to resume instead, for `duplicate_upload` conflicts, along with the progress of its
+    /// upload if it could be listed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<FileUploadProgress>,
+}
+
+/// Error of a route that validates its input beyond what deserialization checks.
+pub enum RouteError {
+    Status(Status),
+    /// Responds with `422 Unprocessable Entity`, listing the rejected fields in the body.
+    InvalidFields(Vec<FieldError>),
+    /// Responds with `504 Gateway Timeout`, naming the dependency in the `code` of the body.
+    Timeout(Dependency),
+    /// Responds with `403 Forbidden`, for a page larger than the caller may request.
+    LimitNotAllowed,
+    /// Responds with `422 Unprocessable Entity` and the `no_fields` code, for an update that
+    /// names no field.
+    NoFields,
+    /// Responds with `422 Unprocessable Entity` and the `invalid_cursor` code, for a pagination
+    /// cursor that no longer matches a row, so the client restarts from the first page.
+    InvalidCursor,
+    /// Responds with `503 Service Unavailable`, the `search_engine_unavailable` code and a
+    /// `Retry-After` header, while Meilisearch is considered down.
+    SearchEngineUnavailable {
+        retry_after: Duration,
+    },
+    /// Responds with `404 Not Found` and the `file_not_found` code, for a file id no file has.
+    FileNotFound,
+    /// Responds with `422 Unprocessable Entity` and the `collection_too_large` code, for a
+    /// collection with more files or bytes than a bundle may hold.
+    CollectionTooLargeToBundle,
+    /// Responds with `423 Locked` and the `file_protected` code, for a deletion of a file that is
+    /// protected from deletion.
+    FileProtected,
+    /// Responds with `409 Conflict` and the `file_not_ready` code, for a file whose upload has not
+    /// been completed.
+    FileNotReady,
+    /// Responds with `502 Bad Gateway` and the `object_missing` code, for a ready file whose object
+    /// is gone from the bucket.
+    ObjectMissing,
+    /// Responds with `409 Conflict`, the `task_already_queued` code and the id of the task, for a
+    /// task whose kind allows only one pending or in-progress task at a time.
+    TaskAlreadyQueued {
+        task_id: Uuid,
+    },
+    /// Responds with `409 Conflict`, the `name_conflict` code and the names, for files that would
+    /// share a name within a collection that requires unique names.
+    NameConflict {
+        names: Vec<String>,
+    },
+    /// Responds with `409 Conflict`, the `conflict` code, and the key and the value, for an entity
+    /// that would take a value of a unique key that another one has, e.g. a tag a file has
+    /// already.
+    Conflict {
+        key: String,
+        value: String,
+    },
+    /// Responds with `409 Conflict`, the `duplicate_upload` code, and the id and the upload progress
+    /// of the file, for a file whose content is being uploaded to an identical file already.
+    DuplicateUpload {
+        file_id: Uuid,
+        progress: Option<FileUploadProgress>,
+    },
+    /// Responds with `502 Bad Gateway` and the `storage_permission_denied` code, for a request the
+    /// bucket refused to authorize.
+    StoragePermissionDenied,
+    /// Responds with `503 Service Unavailable` and the `storage_unavailable` code, for a request
+    /// to the bucket that failed in a way retrying may fix.
+    StorageUnavailable,
+}
+
+/// Answers a failed S3 call: denied and transient failures get their own codes, anything else is
+/// a plain `500`.
+pub fn storage_error(err: &S3ServiceError) -> RouteError {
+    match err {
+        S3ServiceError::PermissionDenied { .. } => RouteError::StoragePermissionDenied,
+        S3ServiceError::Transient { .. } => RouteError::StorageUnavailable,
+        _ => Status::InternalServerError.into(),
+    }
+}
+
+impl RouteError {
+    /// The machine-readable reason the error body carries, if any.
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            RouteError::Status(_) | RouteError::InvalidFields(_) => None,
+            RouteError::Timeout(dependency) => Some(dependency.timeout_code()),
+            RouteError::NoFields => Some("no_fields"),
+            RouteError::InvalidCursor => Some("invalid_cursor"),
+            RouteError::SearchEngineUnavailable { .. } => Some("search_engine_unavailable"),
+            RouteError::FileNotFound => Some("file_not_found"),
+            RouteError::CollectionTooLargeToBundle => Some("collection_too_large"),
+            RouteError::FileProtected => Some("file_protected"),
+            RouteError::FileNotReady => Some("file_not_ready"),
+            RouteError::ObjectMissing => Some("object_missing"),
+            RouteError::TaskAlreadyQueued { .. } => Some("task_already_queued"),
+            RouteError::NameConflict { .. } => Some("name_conflict"),
+            RouteError::Conflict { .. } => Some("conflict"),
+            RouteError::DuplicateUpload { .. } => Some("duplicate_upload"),
+            RouteError::StoragePermissionDenied => Some("storage_permission_denied"),
+            RouteError::StorageUnavailable => Some("storage_unavailable"),
+            RouteError::LimitNotAllowed => Some("limit_requires_admin"),
+        }
+    }
+}
+
+impl From<Status> for RouteError {
+    fn from(status: Status) -> Self {
+        Self::Status(status)
+    }
+}
+
+impl<'r> Responder<'r, 'static> for RouteError {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let code = self.code();
+
+        match self {
+            RouteError::Status(status) => Err(status),
+            RouteError::InvalidFields(fields) => {
+                let status = Status::UnprocessableEntity;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: status.reason(),
+                    code,
+                    fields,
+                    task_id: None,
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: None,
+                    value: None,
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+            RouteError::Timeout(_) => {
+                let status = Status::GatewayTimeout;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: status.reason(),
+                    code,
+                    fields: Vec::new(),
+                    task_id: None,
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: None,
+                    value: None,
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+            RouteError::NoFields => {
+                let status = Status::UnprocessableEntity;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: Some("the update names no field"),
+                    code,
+                    fields: Vec::new(),
+                    task_id: None,
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: None,
+                    value: None,
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+            RouteError::InvalidCursor => {
+                let status = Status::UnprocessableEntity;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: Some("the cursor does not match any row; restart from the first page"),
+                    code,
+                    fields: Vec::new(),
+                    task_id: None,
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: None,
+                    value: None,
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+            RouteError::SearchEngineUnavailable { retry_after } => {
+                let status = Status::ServiceUnavailable;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: Some("the search engine is considered down; retry later"),
+                    code,
+                    fields: Vec::new(),
+                    task_id: None,
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: None,
+                    value: None,
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .raw_header("Retry-After", retry_after.as_secs().max(1).to_string())
+                    .ok()
+            }
+            RouteError::FileNotFound => {
+                let status = Status::NotFound;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: Some("no file has this id"),
+                    code,
+                    fields: Vec::new(),
+                    task_id: None,
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: None,
+                    value: None,
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+            RouteError::CollectionTooLargeToBundle => {
+                let status = Status::UnprocessableEntity;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: Some(
+                        "the collection has too many files to bundle; request a download manifest instead",
+                    ),
+                    code,
+                    fields: Vec::new(),
+                    task_id: None,
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: None,
+                    value: None,
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+            RouteError::FileProtected => {
+                let status = Status::Locked;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: Some("the file is protected from deletion"),
+                    code,
+                    fields: Vec::new(),
+                    task_id: None,
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: None,
+                    value: None,
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+            RouteError::FileNotReady => {
+                let status = Status::Conflict;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: Some("the upload of the file has not been completed"),
+                    code,
+                    fields: Vec::new(),
+                    task_id: None,
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: None,
+                    value: None,
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+            RouteError::ObjectMissing => {
+                let status = Status::BadGateway;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: Some("the object of the file is missing from storage"),
+                    code,
+                    fields: Vec::new(),
+                    task_id: None,
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: None,
+                    value: None,
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+            RouteError::TaskAlreadyQueued { task_id } => {
+                let status = Status::Conflict;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: Some("a task of this kind is already pending or in progress"),
+                    code,
+                    fields: Vec::new(),
+                    task_id: Some(task_id),
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: None,
+                    value: None,
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+            RouteError::NameConflict { names } => {
+                let status = Status::Conflict;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: Some("files would share a name within a collection"),
+                    code,
+                    fields: Vec::new(),
+                    task_id: None,
+                    limit_bytes: None,
+                    names,
+                    key: None,
+                    value: None,
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+            RouteError::Conflict { key, value } => {
+                let status = Status::Conflict;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: Some("the value is taken already"),
+                    code,
+                    fields: Vec::new(),
+                    task_id: None,
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: Some(key),
+                    value: Some(value),
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+            RouteError::DuplicateUpload { file_id, progress } => {
+                let status = Status::Conflict;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: Some("an identical file is being uploaded already; resume its upload"),
+                    code,
+                    fields: Vec::new(),
+                    task_id: None,
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: None,
+                    value: None,
+                    file_id: Some(file_id),
+                    progress,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+            RouteError::StoragePermissionDenied => {
+                let status = Status::BadGateway;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: Some("the storage denied access; check its credentials and policy"),
+                    code,
+                    fields: Vec::new(),
+                    task_id: None,
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: None,
+                    value: None,
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+            RouteError::StorageUnavailable => {
+                let status = Status::ServiceUnavailable;
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: Some("the storage could not be reached; retry later"),
+                    code,
+                    fields: Vec::new(),
+                    task_id: None,
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: None,
+                    value: None,
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+            RouteError::LimitNotAllowed => {
+                let status = Status::Forbidden;
+                let message = format!(
+                    "limit above {LIST_MAX_LIMIT} requires the admin role, up to {LIST_ADMIN_MAX_LIMIT}"
+                );
+                let body = Json(ErrorBody {
+                    status: status.code,
+                    message: Some(&message),
+                    code,
+                    fields: Vec::new(),
+                    task_id: None,
+                    limit_bytes: None,
+                    names: Vec::new(),
+                    key: None,
+                    value: None,
+                    file_id: None,
+                    progress: None,
+                });
+
+                Response::build_from(body.respond_to(req)?)
+                    .status(status)
+                    .ok()
+            }
+        }
+    }
+}
+
+impl OpenApiResponderInner for RouteError {
+    fn responses(_gen: &mut OpenApiGenerator) -> rocket_okapi::Result<Responses> {
+        // the error body is attached to every operation in `finalize_spec`
+        Ok(Responses::default())
+    }
+}
+
+#[catch(413)]
+fn payload_too_large(req: &Request) -> Json<ErrorBody<'static>> {
+    Json(ErrorBody {
+        status: Status::PayloadTooLarge.code,
+        message: Some("the request body exceeds the data limit of the route"),
+        code: Some("payload_too_large"),
+        fields: Vec::new(),
+        task_id: None,
+        limit_bytes: Some(BodyLimit::of(req).as_u64()),
+        names: Vec::new(),
+        key: None,
+        value: None,
+        file_id: None,
+        progress: None,
+    })
 }
 
+/// Answers with the reason phrase of the status only, so that no internal detail leaks.
 #[catch(default)]
 fn default(status: Status, _req: &Request) -> Json<ErrorBody<'static>> {
     Json(ErrorBody {
         status: status.code,
         message: status.reason(),
+        code: None,
+        fields: Vec::new(),
+        task_id: None,
+        limit_bytes: None,
+        names: Vec::new(),
+        key: None,
+        value: None,
+        file_id: None,
+        progress: None,
     })
 }
@@ -1,24 +1,125 @@
 use crate::{
-    interfaces::admins::{AdminTask, AdminTaskInitiator, AdminTaskPreview, ReIndexAdminTask},
-    services::{
-        admin_task_service::{
-            AdminTaskCursor, AdminTaskService, RE_INDEX_COLLECTIONS_TASK_NAME,
-            RE_INDEX_FILES_TASK_NAME,
+    forms::json_object::JsonObjectFormField,
+    guards::{authenticated_admin::RequireAdmin, request_span::RequestSpan},
+    interfaces::{
+        admins::{
+            AdminTask, AdminTaskExportFormat, AdminTaskInitiator, AdminTaskPreview,
+            BackfillingChecksums, DeletedIndexDocument, IndexDocument, MeiliTask, Migration,
+            ReIndexAdminTask, ReconcilingStorage, TaskKind,
         },
-        index_service::IndexService,
+        FieldError, IndexSettings, UpdatingIndexSettings,
+    },
+    routes::{Dependency, RouteError},
+    services::{
+        admin_task_service::{AdminTaskCursor, AdminTaskService, AdminTaskServiceError},
+        index_service::{document_indexed_at, DocumentIndex, IndexService, IndexServiceError},
+        migration_service::MigrationService,
     },
+    telemetry::ErrorChain,
+};
+use chrono::TimeDelta;
+use futures::{future::ready, stream::BoxStream, StreamExt};
+use rocket::{
+    delete, get,
+    http::{ContentType, Status},
+    post, put,
+    response::stream::TextStream,
+    serde::json::Json,
+    Route, State,
+};
+use rocket_okapi::{
+    okapi::openapi3::OpenApi, openapi, openapi_get_routes_spec, settings::OpenApiSettings,
 };
-use rocket::{get, http::Status, post, routes, serde::json::Json, Route, State};
 use uuid::Uuid;
 
-pub fn routes() -> Vec<Route> {
-    routes![admin_tasks_list, admin_tasks_get, admin_tasks_re_index,]
+/// Longest time range an export of the task history covers.
+const EXPORT_MAX_RANGE: TimeDelta = TimeDelta::days(31);
+
+pub fn routes(settings: &OpenApiSettings) -> (Vec<Route>, OpenApi) {
+    openapi_get_routes_spec![settings:
+        admin_tasks_list,
+        admin_tasks_search,
+        admin_tasks_export,
+        admin_tasks_get,
+        admin_tasks_list_meili_tasks,
+        admin_tasks_cancel,
+        admin_tasks_re_index,
+        admin_tasks_reconcile_storage,
+        admin_tasks_backfill_checksums,
+        admin_tasks_backfill_storage,
+        admin_tasks_get_index_settings,
+        admin_tasks_update_index_settings,
+        admin_tasks_get_file_index_document,
+        admin_tasks_delete_file_index_document,
+        admin_tasks_get_collection_index_document,
+        admin_tasks_delete_collection_index_document,
+        admin_tasks_list_migrations,
+    ]
 }
 
+#[openapi(tag = "Admin Tasks")]
 #[get("/?<query..>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
 async fn admin_tasks_list(
+    request_span: RequestSpan,
     admin_task_service: &State<AdminTaskService>,
+    _admin: RequireAdmin,
     query: forms::ListQuery,
+) -> Result<Json<Vec<AdminTaskPreview>>, RouteError> {
+    let cursor = match (query.last_admin_task_id, query.last_admin_task_updated_at) {
+        (Some(last_admin_task_id), Some(last_admin_task_updated_at)) => Some(AdminTaskCursor {
+            id: last_admin_task_id,
+            updated_at: last_admin_task_updated_at.date_time,
+        }),
+        _ => None,
+    };
+
+    let metadata_contains = match query
+        .metadata_contains
+        .as_deref()
+        .map(JsonObjectFormField::parse)
+    {
+        Some(Ok(metadata_contains)) => Some(serde_json::Value::Object(metadata_contains.object)),
+        Some(Err(reason)) => {
+            return Err(RouteError::InvalidFields(vec![FieldError::new(
+                "metadata-contains",
+                reason,
+            )]));
+        }
+        None => None,
+    };
+
+    let tasks = match admin_task_service
+        .list_tasks(
+            query.limit,
+            cursor,
+            query.verify_cursor,
+            metadata_contains,
+            query.initiated_by,
+        )
+        .await
+    {
+        Ok(tasks) => tasks,
+        Err(AdminTaskServiceError::InvalidCursor) => {
+            return Err(RouteError::InvalidCursor);
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to list admin tasks");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    Ok(Json(tasks))
+}
+
+#[openapi(tag = "Admin Tasks")]
+#[get("/search?<query..>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admin_tasks_search(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    _admin: RequireAdmin,
+    query: forms::SearchQuery,
 ) -> Result<Json<Vec<AdminTaskPreview>>, Status> {
     let cursor = match (query.last_admin_task_id, query.last_admin_task_updated_at) {
         (Some(last_admin_task_id), Some(last_admin_task_updated_at)) => Some(AdminTaskCursor {
@@ -28,10 +129,13 @@ async fn admin_tasks_list(
         _ => None,
     };
 
-    let tasks = match admin_task_service.list_tasks(query.limit, cursor).await {
+    let tasks = match admin_task_service
+        .search_tasks(&query.q, query.limit, cursor)
+        .await
+    {
         Ok(tasks) => tasks,
         Err(err) => {
-            log::error!("failed to list admin tasks: {err:#?}");
+            tracing::error!(error = %ErrorChain(&err), "failed to search admin tasks");
             return Err(Status::InternalServerError);
         }
     };
@@ -39,9 +143,55 @@ async fn admin_tasks_list(
     Ok(Json(tasks))
 }
 
+#[openapi(tag = "Admin Tasks")]
+#[get("/export?<query..>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+fn admin_tasks_export(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    _admin: RequireAdmin,
+    query: forms::ExportQuery,
+) -> Result<(ContentType, TextStream<BoxStream<'_, String>>), RouteError> {
+    let from = query.from.date_time;
+    let to = query.to.date_time;
+
+    if to <= from || EXPORT_MAX_RANGE < to - from {
+        return Err(RouteError::InvalidFields(vec![FieldError::new(
+            "to",
+            format!(
+                "must be after `from`, by at most {} days",
+                EXPORT_MAX_RANGE.num_days()
+            ),
+        )]));
+    }
+
+    let content_type = match query.format {
+        AdminTaskExportFormat::Ndjson => ContentType::new("application", "x-ndjson"),
+    };
+
+    // the status line is already sent once streaming starts, so a failure can only cut it short
+    let stream = admin_task_service
+        .stream_tasks_as_ndjson(from, to)
+        .take_while(|line| {
+            if let Err(err) = line {
+                tracing::error!(error = %ErrorChain(err), "failed to export admin tasks");
+            }
+
+            ready(line.is_ok())
+        })
+        .filter_map(|line| ready(line.ok()))
+        .boxed();
+
+    Ok((content_type, TextStream(stream)))
+}
+
+#[openapi(tag = "Admin Tasks")]
 #[get("/<task_id>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(task_id = %task_id))]
 async fn admin_tasks_get(
+    request_span: RequestSpan,
     admin_task_service: &State<AdminTaskService>,
+    _admin: RequireAdmin,
     task_id: Uuid,
 ) -> Result<Json<AdminTask>, Status> {
     let task = match admin_task_service.get_task(task_id).await {
@@ -50,7 +200,7 @@ async fn admin_tasks_get(
             return Err(Status::NotFound);
         }
         Err(err) => {
-            log::error!("failed to get admin task: {err:#?}");
+            tracing::error!(error = %ErrorChain(&err), "failed to get admin task");
             return Err(Status::InternalServerError);
         }
     };
@@ -58,81 +208,530 @@ async fn admin_tasks_get(
     Ok(Json(task))
 }
 
-#[post("/re-index")]
-async fn admin_tasks_re_index(
+#[openapi(tag = "Admin Tasks")]
+#[get("/<task_id>/meili-tasks")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(task_id = %task_id))]
+async fn admin_tasks_list_meili_tasks(
+    request_span: RequestSpan,
     admin_task_service: &State<AdminTaskService>,
     index_service: &State<IndexService>,
-) -> Result<Json<ReIndexAdminTask>, Status> {
-    if let Err(err) = index_service.empty_index().await {
-        log::error!("failed to empty index: {err:#?}");
-        return Err(Status::InternalServerError);
+    _admin: RequireAdmin,
+    task_id: Uuid,
+) -> Result<Json<Vec<MeiliTask>>, RouteError> {
+    let task = match admin_task_service.get_task(task_id).await {
+        Ok(Some(task)) => task,
+        Ok(None) => {
+            return Err(Status::NotFound.into());
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to get admin task");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    let meili_task_uids = task
+        .metadata
+        .get("meili_task_uids")
+        .and_then(|uids| serde_json::from_value::<Vec<u32>>(uids.clone()).ok())
+        .unwrap_or_default();
+    let result = index_service.get_tasks(&meili_task_uids).await;
+
+    match result {
+        Ok(meili_tasks) => Ok(Json(meili_tasks)),
+        Err(IndexServiceError::Timeout { .. }) => {
+            Err(RouteError::Timeout(Dependency::SearchEngine))
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to get meilisearch tasks");
+            Err(Status::InternalServerError.into())
+        }
     }
+}
 
+#[openapi(tag = "Admin Tasks")]
+#[post("/<task_id>/cancel")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(task_id = %task_id))]
+async fn admin_tasks_cancel(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    _admin: RequireAdmin,
+    task_id: Uuid,
+) -> Result<Json<AdminTask>, Status> {
+    let task = match admin_task_service.cancel_task(task_id).await {
+        Ok(Some(task)) => task,
+        Ok(None) => {
+            // distinguish a missing task from one that has already finished
+            return match admin_task_service.get_task(task_id).await {
+                Ok(Some(_)) => Err(Status::Conflict),
+                Ok(None) => Err(Status::NotFound),
+                Err(err) => {
+                    tracing::error!(error = %ErrorChain(&err), "failed to get admin task");
+                    Err(Status::InternalServerError)
+                }
+            };
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to cancel admin task");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    Ok(Json(task))
+}
+
+#[openapi(tag = "Admin Tasks")]
+#[post("/re-index?<query..>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admin_tasks_re_index(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    admin: RequireAdmin,
+    query: forms::EnqueueQuery,
+) -> Result<Json<ReIndexAdminTask>, RouteError> {
+    // the tasks empty the indexes themselves, so nothing is lost if enqueueing fails
     let file_task = admin_task_service
         .enqueue_task(
             AdminTaskInitiator::User,
-            RE_INDEX_FILES_TASK_NAME.to_owned(),
+            Some(admin.admin.id),
+            TaskKind::ReIndexFiles,
             serde_json::json!({
+                "index_cleared": false,
                 "last_file_id": serde_json::Value::Null,
                 "last_file_uploaded_at": serde_json::Value::Null,
             }),
             None,
-            true,
+            query.replace,
+            None,
         )
         .await;
+    let file_task = match file_task {
+        Ok(file_task) => file_task,
+        Err(AdminTaskServiceError::AlreadyQueued { task_id }) => {
+            return Err(RouteError::TaskAlreadyQueued { task_id });
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to enqueue admin task for files");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    // collections are indexed only after the files, to keep the load on the index steady
     let collection_task = admin_task_service
         .enqueue_task(
             AdminTaskInitiator::User,
-            RE_INDEX_COLLECTIONS_TASK_NAME.to_owned(),
+            Some(admin.admin.id),
+            TaskKind::ReIndexCollections,
             serde_json::json!({
+                "index_cleared": false,
                 "last_collection_id": serde_json::Value::Null,
                 "last_collection_name": serde_json::Value::Null,
             }),
             None,
-            true,
+            query.replace,
+            Some(file_task.id),
         )
         .await;
+    let collection_task = match collection_task {
+        Ok(collection_task) => collection_task,
+        Err(err) => {
+            // a files task alone would leave the collections index stale
+            if let Err(err) = admin_task_service.cancel_task(file_task.id).await {
+                tracing::warn!(error = %ErrorChain(&err), "failed to cancel admin task for files");
+            }
 
-    let file_task = match file_task {
-        Ok(file_task) => file_task,
+            if let AdminTaskServiceError::AlreadyQueued { task_id } = err {
+                return Err(RouteError::TaskAlreadyQueued { task_id });
+            }
+
+            tracing::error!(error = %ErrorChain(&err), "failed to enqueue admin task for collections");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    Ok(Json(ReIndexAdminTask {
+        file_task,
+        collection_task,
+    }))
+}
+
+#[openapi(tag = "Admin Tasks")]
+#[post("/reconcile-storage?<query..>", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admin_tasks_reconcile_storage(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    admin: RequireAdmin,
+    query: forms::EnqueueQuery,
+    body: Option<Json<ReconcilingStorage>>,
+) -> Result<Json<AdminTask>, RouteError> {
+    let body = body.map(|body| body.into_inner()).unwrap_or_default();
+    let task = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::User,
+            Some(admin.admin.id),
+            TaskKind::ReconcileStorage,
+            serde_json::json!({
+                "delete_orphans": body.delete_orphans,
+                "mark_missing_unready": body.mark_missing_unready,
+            }),
+            None,
+            query.replace,
+            None,
+        )
+        .await;
+    let task = match task {
+        Ok(task) => task,
+        Err(AdminTaskServiceError::AlreadyQueued { task_id }) => {
+            return Err(RouteError::TaskAlreadyQueued { task_id });
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to enqueue admin task for storage reconciliation");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    Ok(Json(task))
+}
+
+#[openapi(tag = "Admin Tasks")]
+#[post("/backfill-checksums?<query..>", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admin_tasks_backfill_checksums(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    admin: RequireAdmin,
+    query: forms::EnqueueQuery,
+    body: Option<Json<BackfillingChecksums>>,
+) -> Result<Json<AdminTask>, RouteError> {
+    let body = body.map(|body| body.into_inner()).unwrap_or_default();
+    let errors = body.validate();
+
+    if !errors.is_empty() {
+        return Err(RouteError::InvalidFields(errors));
+    }
+
+    let task = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::User,
+            Some(admin.admin.id),
+            TaskKind::BackfillChecksums,
+            serde_json::json!({
+                "files_per_second": body.files_per_second,
+                "last_file_id": serde_json::Value::Null,
+            }),
+            None,
+            query.replace,
+            None,
+        )
+        .await;
+    let task = match task {
+        Ok(task) => task,
+        Err(AdminTaskServiceError::AlreadyQueued { task_id }) => {
+            return Err(RouteError::TaskAlreadyQueued { task_id });
+        }
         Err(err) => {
-            log::error!("failed to enqueue admin task for files: {err:#?}");
+            tracing::error!(error = %ErrorChain(&err), "failed to enqueue admin task for checksum backfill");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    Ok(Json(task))
+}
+
+#[openapi(tag = "Admin Tasks")]
+#[post("/backfill-storage?<query..>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admin_tasks_backfill_storage(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    admin: RequireAdmin,
+    query: forms::EnqueueQuery,
+) -> Result<Json<AdminTask>, RouteError> {
+    let task = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::User,
+            Some(admin.admin.id),
+            TaskKind::BackfillStorage,
+            serde_json::json!({ "backfilled_count": 0 }),
+            None,
+            query.replace,
+            None,
+        )
+        .await;
+    let task = match task {
+        Ok(task) => task,
+        Err(AdminTaskServiceError::AlreadyQueued { task_id }) => {
+            return Err(RouteError::TaskAlreadyQueued { task_id });
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to enqueue admin task for storage backfill");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    Ok(Json(task))
+}
+
+#[openapi(tag = "Admin Tasks")]
+#[get("/index-settings")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admin_tasks_get_index_settings(
+    request_span: RequestSpan,
+    index_service: &State<IndexService>,
+    _admin: RequireAdmin,
+) -> Result<Json<IndexSettings>, Status> {
+    let settings = match index_service.get_index_settings().await {
+        Ok(settings) => settings,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to get index settings");
             return Err(Status::InternalServerError);
         }
     };
 
-    let collection_task = match collection_task {
-        Ok(collection_task) => collection_task,
+    Ok(Json(settings))
+}
+
+/// Returns the search document of the file exactly as Meilisearch holds it.
+#[openapi(tag = "Admin Tasks")]
+#[get("/index-documents/files/<file_id>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(file_id = %file_id))]
+async fn admin_tasks_get_file_index_document(
+    request_span: RequestSpan,
+    index_service: &State<IndexService>,
+    _admin: RequireAdmin,
+    file_id: Uuid,
+) -> Result<Json<IndexDocument>, RouteError> {
+    get_index_document(index_service, DocumentIndex::Files, file_id).await
+}
+
+/// Deletes the search document of the file, e.g. one left behind by a file deleted from the
+/// database by hand. The file itself is left as it is.
+#[openapi(tag = "Admin Tasks")]
+#[delete("/index-documents/files/<file_id>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(file_id = %file_id))]
+async fn admin_tasks_delete_file_index_document(
+    request_span: RequestSpan,
+    index_service: &State<IndexService>,
+    _admin: RequireAdmin,
+    file_id: Uuid,
+) -> Result<Json<DeletedIndexDocument>, RouteError> {
+    delete_index_document(index_service, DocumentIndex::Files, file_id).await
+}
+
+/// Returns the search document of the collection exactly as Meilisearch holds it.
+#[openapi(tag = "Admin Tasks")]
+#[get("/index-documents/collections/<collection_id>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(collection_id = %collection_id))]
+async fn admin_tasks_get_collection_index_document(
+    request_span: RequestSpan,
+    index_service: &State<IndexService>,
+    _admin: RequireAdmin,
+    collection_id: Uuid,
+) -> Result<Json<IndexDocument>, RouteError> {
+    get_index_document(index_service, DocumentIndex::Collections, collection_id).await
+}
+
+/// Deletes the search document of the collection. The collection itself is left as it is.
+#[openapi(tag = "Admin Tasks")]
+#[delete("/index-documents/collections/<collection_id>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(collection_id = %collection_id))]
+async fn admin_tasks_delete_collection_index_document(
+    request_span: RequestSpan,
+    index_service: &State<IndexService>,
+    _admin: RequireAdmin,
+    collection_id: Uuid,
+) -> Result<Json<DeletedIndexDocument>, RouteError> {
+    delete_index_document(index_service, DocumentIndex::Collections, collection_id).await
+}
+
+async fn get_index_document(
+    index_service: &IndexService,
+    index: DocumentIndex,
+    id: Uuid,
+) -> Result<Json<IndexDocument>, RouteError> {
+    let document = index_service.get_raw_document(index, id).await;
+
+    match document {
+        Ok(Some(document)) => Ok(Json(IndexDocument {
+            index_uid: index.uid().to_owned(),
+            indexed_at: document_indexed_at(&document),
+            document,
+        })),
+        Ok(None) => Err(Status::NotFound.into()),
+        Err(IndexServiceError::Timeout { .. }) => {
+            Err(RouteError::Timeout(Dependency::SearchEngine))
+        }
+        Err(IndexServiceError::CircuitOpen { retry_after }) => {
+            Err(RouteError::SearchEngineUnavailable { retry_after })
+        }
         Err(err) => {
-            log::error!("failed to enqueue admin task for collections: {err:#?}");
+            tracing::error!(error = %ErrorChain(&err), "failed to get index document");
+            Err(Status::InternalServerError.into())
+        }
+    }
+}
+
+/// Deletes the document, answering `404` if the index has none with the id.
+async fn delete_index_document(
+    index_service: &IndexService,
+    index: DocumentIndex,
+    id: Uuid,
+) -> Result<Json<DeletedIndexDocument>, RouteError> {
+    get_index_document(index_service, index, id).await?;
+
+    let task_uids = match index {
+        DocumentIndex::Files => index_service.delete_files(&[id]).await,
+        DocumentIndex::Collections => index_service.delete_collections(&[id]).await,
+    };
+
+    match task_uids.as_deref() {
+        Ok([meili_task_uid]) => {
+            tracing::info!(index_uid = index.uid(), %id, meili_task_uid, "deleted index document");
+            Ok(Json(DeletedIndexDocument {
+                meili_task_uid: *meili_task_uid,
+            }))
+        }
+        Ok(task_uids) => {
+            tracing::error!(
+                ?task_uids,
+                "deleting a single document did not enqueue one task"
+            );
+            Err(Status::InternalServerError.into())
+        }
+        Err(IndexServiceError::Timeout { .. }) => {
+            Err(RouteError::Timeout(Dependency::SearchEngine))
+        }
+        Err(IndexServiceError::CircuitOpen { retry_after }) => {
+            Err(RouteError::SearchEngineUnavailable {
+                retry_after: *retry_after,
+            })
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(err), "failed to delete index document");
+            Err(Status::InternalServerError.into())
+        }
+    }
+}
+
+#[openapi(tag = "Admin Tasks")]
+#[put("/index-settings", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admin_tasks_update_index_settings(
+    request_span: RequestSpan,
+    index_service: &State<IndexService>,
+    _admin: RequireAdmin,
+    body: Json<UpdatingIndexSettings>,
+) -> Result<Json<IndexSettings>, Status> {
+    if !body.is_valid() {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    let settings = match index_service.update_index_settings(&body).await {
+        Ok(settings) => settings,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to update index settings");
             return Err(Status::InternalServerError);
         }
     };
 
-    Ok(Json(ReIndexAdminTask {
-        file_task,
-        collection_task,
-    }))
+    Ok(Json(settings))
+}
+
+#[openapi(tag = "Admin Tasks")]
+#[get("/migrations")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admin_tasks_list_migrations(
+    request_span: RequestSpan,
+    migration_service: &State<MigrationService>,
+    _admin: RequireAdmin,
+) -> Result<Json<Vec<Migration>>, Status> {
+    let migrations = match migration_service.list_migrations().await {
+        Ok(migrations) => migrations,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to list migrations");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    Ok(Json(migrations))
 }
 
 mod forms {
-    use crate::forms::date_time_utc::DateTimeUtcFormField;
+    use crate::{
+        forms::{date_time_utc::DateTimeUtcFormField, json_object::JsonObjectFormField},
+        interfaces::admins::AdminTaskExportFormat,
+    };
     use rocket::{
         form::{Error, Result},
         FromForm,
     };
+    use schemars::JsonSchema;
     use uuid::Uuid;
 
-    #[derive(FromForm, Debug)]
+    #[derive(FromForm, JsonSchema, Debug)]
     pub struct ListQuery {
-        #[field(name = uncased("limit"), default = 25, validate = range(1..=100))]
+        #[schemars(default = "default_limit", range(min = 1, max = 1000))]
+        #[field(name = uncased("limit"), default = default_limit(), validate = range(1..=1000))]
+        pub limit: usize,
+        #[schemars(rename = "last-admin-task-id")]
+        #[field(name = uncased("last-admin-task-id"), validate = is_last_admin_task_id_valid(&self.last_admin_task_updated_at))]
+        pub last_admin_task_id: Option<Uuid>,
+        #[schemars(rename = "last-admin-task-updated-at")]
+        #[field(name = uncased("last-admin-task-updated-at"), validate = is_last_admin_task_updated_at_valid(&self.last_admin_task_id))]
+        pub last_admin_task_updated_at: Option<DateTimeUtcFormField>,
+        #[schemars(rename = "verify-cursor", default = "default_verify_cursor")]
+        #[field(name = uncased("verify-cursor"), default = default_verify_cursor())]
+        pub verify_cursor: bool,
+        // parsed by the route, as a malformed object would be taken for no filter at all here
+        #[schemars(rename = "metadata-contains", with = "Option<JsonObjectFormField>")]
+        #[field(name = uncased("metadata-contains"))]
+        pub metadata_contains: Option<String>,
+        #[schemars(rename = "initiated-by")]
+        #[field(name = uncased("initiated-by"))]
+        pub initiated_by: Option<Uuid>,
+    }
+
+    #[derive(FromForm, JsonSchema, Debug)]
+    pub struct SearchQuery {
+        #[schemars(length(min = 1, max = 256))]
+        #[field(name = uncased("q"), validate = len(1..=256))]
+        pub q: String,
+        #[schemars(default = "default_limit", range(min = 1, max = 1000))]
+        #[field(name = uncased("limit"), default = default_limit(), validate = range(1..=1000))]
         pub limit: usize,
+        #[schemars(rename = "last-admin-task-id")]
         #[field(name = uncased("last-admin-task-id"), validate = is_last_admin_task_id_valid(&self.last_admin_task_updated_at))]
         pub last_admin_task_id: Option<Uuid>,
+        #[schemars(rename = "last-admin-task-updated-at")]
         #[field(name = uncased("last-admin-task-updated-at"), validate = is_last_admin_task_updated_at_valid(&self.last_admin_task_id))]
         pub last_admin_task_updated_at: Option<DateTimeUtcFormField>,
     }
 
+    #[derive(FromForm, JsonSchema, Debug)]
+    pub struct ExportQuery {
+        #[field(name = uncased("from"))]
+        pub from: DateTimeUtcFormField,
+        #[field(name = uncased("to"))]
+        pub to: DateTimeUtcFormField,
+        #[field(name = uncased("format"))]
+        pub format: AdminTaskExportFormat,
+    }
+
+    #[derive(FromForm, JsonSchema, Debug)]
+    pub struct EnqueueQuery {
+        #[schemars(default)]
+        #[field(name = uncased("replace"), default = false)]
+        pub replace: bool,
+    }
+
+    fn default_limit() -> usize {
+        25
+    }
+
+    fn default_verify_cursor() -> bool {
+        true
+    }
+
     fn is_last_admin_task_id_valid<'v>(
         this: &Option<Uuid>,
         last_admin_task_updated_at: &Option<DateTimeUtcFormField>,
@@ -0,0 +1,243 @@
+use crate::{
+    guards::{
+        authenticated_admin::{AuthenticatedAdmin, RequireAdmin},
+        client_ip::ClientIp,
+        request_span::RequestSpan,
+    },
+    interfaces::{
+        admins::{
+            Admin, AdminLogin, AdminSession, BootstrappingAdmin, CreatingAdmin,
+            RequestingPasswordReset, ResettingPassword, UpdatingAdmin,
+        },
+        SimpleOk,
+    },
+    services::admin_service::{AdminService, AdminServiceError},
+    telemetry::ErrorChain,
+};
+use rocket::{delete, get, http::Status, patch, post, serde::json::Json, Route, State};
+use rocket_okapi::{
+    okapi::openapi3::OpenApi, openapi, openapi_get_routes_spec, settings::OpenApiSettings,
+};
+use uuid::Uuid;
+
+pub fn routes(settings: &OpenApiSettings) -> (Vec<Route>, OpenApi) {
+    openapi_get_routes_spec![settings:
+        admins_me,
+        admins_login,
+        admins_refresh,
+        admins_logout,
+        admins_request_password_reset,
+        admins_reset_password,
+        admins_bootstrap,
+        admins_create,
+        admins_update,
+    ]
+}
+
+#[openapi(tag = "Admins")]
+#[get("/me")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admins_me(request_span: RequestSpan, admin: AuthenticatedAdmin) -> Json<Admin> {
+    Json(admin.admin)
+}
+
+#[openapi(tag = "Admins")]
+#[post("/sessions", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admins_login(
+    request_span: RequestSpan,
+    admin_service: &State<AdminService>,
+    client_ip: ClientIp,
+    body: Json<AdminLogin>,
+) -> Result<Json<AdminSession>, Status> {
+    let session = match admin_service.login(body.into_inner()).await {
+        Ok(Some(session)) => session,
+        Ok(None) => {
+            tracing::warn!(client_ip = %client_ip, "rejected admin login");
+            return Err(Status::Unauthorized);
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to login admin");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    Ok(Json(session))
+}
+
+#[openapi(tag = "Admins")]
+#[post("/sessions/refresh")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admins_refresh(
+    request_span: RequestSpan,
+    admin_service: &State<AdminService>,
+    admin: AuthenticatedAdmin,
+) -> Result<Json<AdminSession>, Status> {
+    let session = match admin_service.refresh(&admin.token).await {
+        Ok(Some(session)) => session,
+        Ok(None) => {
+            return Err(Status::Unauthorized);
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to refresh admin session");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    Ok(Json(session))
+}
+
+#[openapi(tag = "Admins")]
+#[delete("/sessions/current")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admins_logout(
+    request_span: RequestSpan,
+    admin_service: &State<AdminService>,
+    admin: AuthenticatedAdmin,
+) -> Result<Json<SimpleOk>, Status> {
+    if let Err(err) = admin_service.logout(&admin.token).await {
+        tracing::error!(error = %ErrorChain(&err), "failed to logout admin");
+        return Err(Status::InternalServerError);
+    }
+
+    Ok(Json(SimpleOk { ok: true }))
+}
+
+#[openapi(tag = "Admins")]
+#[post("/password-resets", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admins_request_password_reset(
+    request_span: RequestSpan,
+    admin_service: &State<AdminService>,
+    body: Json<RequestingPasswordReset>,
+) -> Result<Json<SimpleOk>, Status> {
+    // the answer is the same whether the email is known or not
+    if let Err(err) = admin_service.request_password_reset(&body.email).await {
+        tracing::error!(error = %ErrorChain(&err), "failed to request password reset");
+        return Err(Status::InternalServerError);
+    }
+
+    Ok(Json(SimpleOk { ok: true }))
+}
+
+#[openapi(tag = "Admins")]
+#[post("/password-resets/<token>", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admins_reset_password(
+    request_span: RequestSpan,
+    admin_service: &State<AdminService>,
+    token: &str,
+    body: Json<ResettingPassword>,
+) -> Result<Json<SimpleOk>, Status> {
+    if body.password.is_empty() {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    match admin_service.reset_password(token, &body.password).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err(Status::NotFound);
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to reset password");
+            return Err(Status::InternalServerError);
+        }
+    }
+
+    Ok(Json(SimpleOk { ok: true }))
+}
+
+#[openapi(tag = "Admins")]
+#[post("/bootstrap", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admins_bootstrap(
+    request_span: RequestSpan,
+    admin_service: &State<AdminService>,
+    client_ip: ClientIp,
+    body: Json<BootstrappingAdmin>,
+) -> Result<Json<Admin>, Status> {
+    if body.username.is_empty() || body.email.is_empty() || body.password.is_empty() {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    let admin = match admin_service.bootstrap_admin(body.into_inner()).await {
+        Ok(Some(admin)) => admin,
+        Ok(None) | Err(AdminServiceError::Conflict { .. }) => {
+            return Err(Status::Conflict);
+        }
+        Err(AdminServiceError::BootstrapTokenMismatch) => {
+            return Err(Status::Unauthorized);
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to bootstrap admin");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    tracing::info!(admin_id = %admin.id, client_ip = %client_ip, "bootstrapped admin");
+
+    Ok(Json(admin))
+}
+
+#[openapi(tag = "Admins")]
+#[post("/", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn admins_create(
+    request_span: RequestSpan,
+    admin_service: &State<AdminService>,
+    _admin: RequireAdmin,
+    body: Json<CreatingAdmin>,
+) -> Result<Json<Admin>, Status> {
+    if body.username.is_empty() || body.email.is_empty() || body.password.is_empty() {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    let admin = match admin_service.create_admin(body.into_inner()).await {
+        Ok(admin) => admin,
+        Err(AdminServiceError::Conflict { .. }) => {
+            return Err(Status::Conflict);
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to create admin");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    Ok(Json(admin))
+}
+
+#[openapi(tag = "Admins")]
+#[patch("/<admin_id>", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(admin_id = %admin_id))]
+async fn admins_update(
+    request_span: RequestSpan,
+    admin_service: &State<AdminService>,
+    _admin: RequireAdmin,
+    admin_id: Uuid,
+    body: Json<UpdatingAdmin>,
+) -> Result<Json<Admin>, Status> {
+    let is_empty = |value: &Option<String>| value.as_ref().is_some_and(|value| value.is_empty());
+
+    if is_empty(&body.username) || is_empty(&body.email) || is_empty(&body.password) {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    let admin = match admin_service
+        .update_admin(admin_id, body.into_inner())
+        .await
+    {
+        Ok(Some(admin)) => admin,
+        Ok(None) => {
+            return Err(Status::NotFound);
+        }
+        Err(AdminServiceError::Conflict { .. }) => {
+            return Err(Status::Conflict);
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to update admin");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    Ok(Json(admin))
+}
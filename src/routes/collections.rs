@@ -1,41 +1,73 @@
 use crate::{
+    config::{PresignConfig, UploadConfig},
+    guards::{
+        authenticated_admin::{AuthenticatedAdmin, RequireEditor},
+        request_span::RequestSpan,
+    },
     interfaces::{
-        admins::{AdminTaskInitiator, AdminTaskStatus},
+        admins::{AdminTask, AdminTaskInitiator, AdminTaskStatus, TaskKind},
         collections::{
-            Collection, CollectionCursor, CollectionFileCursor, CreatingCollection,
-            UpdatingCollection,
+            Collection, CollectionCursor, CollectionDownloadManifest, CollectionFileCursor,
+            CreatedCollectionDownloadManifest, CreatingCollection, UpdatingCollection,
         },
-        files::File,
-        SimpleOk,
+        files::{File, FileDownloadUrl},
+        tags::validate_tags,
+        FieldError, IndexingStatus, SimpleOk, WithAdminTask,
+    },
+    routes::{
+        attach_tag_definitions, check_list_limit, defer_indexing, redact_storage, storage_error,
+        with_timeout, Dependency, RouteError, RouteTimeouts,
     },
     services::{
-        admin_task_service::{
-            AdminTaskService, CREATE_COLLECTION_TASK_NAME, DELETE_COLLECTION_TASK_NAME,
-            UPDATE_COLLECTION_TASK_NAME,
-        },
-        collection_service::CollectionService,
-        index_service::IndexService,
+        admin_task_service::AdminTaskService,
+        bundle_service::{BundleService, BUNDLE_MAX_BYTES, BUNDLE_MAX_FILES},
+        collection_service::{CollectionService, CollectionServiceError},
+        export_service::{ExportService, ExportServiceError},
+        file_service::FileService,
+        index_service::{IndexService, IndexServiceError},
+        s3_service::ObjectStorage,
+        tag_definition_service::TagDefinitionService,
     },
+    telemetry::ErrorChain,
 };
-use rocket::{delete, get, http::Status, patch, post, routes, serde::json::Json, Route, State};
+use rocket::{delete, get, http::Status, patch, post, serde::json::Json, Route, State};
+use rocket_okapi::{
+    okapi::openapi3::OpenApi, openapi, openapi_get_routes_spec, settings::OpenApiSettings,
+};
+use std::sync::Arc;
 use uuid::Uuid;
 
-pub fn routes() -> Vec<Route> {
-    routes![
+pub fn routes(settings: &OpenApiSettings) -> (Vec<Route>, OpenApi) {
+    openapi_get_routes_spec![settings:
         collections_list,
         collections_get,
         collections_list_files,
+        collections_create_download_manifest,
+        collections_create_download_manifest_download_url,
+        collections_create_bundle,
         collections_create,
         collections_update,
         collections_delete,
     ]
 }
 
+/// Collections with more ready files than this get their download manifest from an admin task.
+const DOWNLOAD_MANIFEST_INLINE_MAX_FILES: u64 = 5000;
+/// Number of files listed per page while sizing a bundle.
+const BUNDLE_LIST_PAGE_SIZE: usize = 500;
+
+#[openapi(tag = "Collections")]
 #[get("/?<query..>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
 async fn collections_list(
+    request_span: RequestSpan,
     collection_service: &State<CollectionService>,
+    tag_definition_service: &State<TagDefinitionService>,
+    admin: Option<AuthenticatedAdmin>,
     query: forms::CollectionListQuery,
-) -> Result<Json<Vec<Collection>>, Status> {
+) -> Result<Json<Vec<Collection>>, RouteError> {
+    check_list_limit(query.limit, admin.as_ref())?;
+
     let cursor = match (query.last_collection_id, query.last_collection_name) {
         (Some(last_collection_id), Some(last_collection_name)) => Some(CollectionCursor {
             id: last_collection_id,
@@ -44,45 +76,61 @@ async fn collections_list(
         _ => None,
     };
 
-    let collections = match collection_service
-        .list_collections(query.limit, cursor)
+    let mut collections = match collection_service
+        .list_collections(query.limit, cursor, query.verify_cursor, &query.tags)
         .await
     {
         Ok(collections) => collections,
+        Err(CollectionServiceError::InvalidCursor) => {
+            return Err(RouteError::InvalidCursor);
+        }
         Err(err) => {
-            log::error!("failed to list collections: {err:#?}");
-            return Err(Status::InternalServerError);
+            tracing::error!(error = %ErrorChain(&err), "failed to list collections");
+            return Err(Status::InternalServerError.into());
         }
     };
 
+    attach_tag_definitions(tag_definition_service, &mut collections).await;
+
     Ok(Json(collections))
 }
 
+#[openapi(tag = "Collections")]
 #[get("/<collection_id>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(collection_id = %collection_id))]
 async fn collections_get(
+    request_span: RequestSpan,
     collection_service: &State<CollectionService>,
+    tag_definition_service: &State<TagDefinitionService>,
     collection_id: Uuid,
 ) -> Result<Json<Collection>, Status> {
-    let collection = match collection_service.get_collection(collection_id).await {
+    let mut collection = match collection_service.get_collection(collection_id).await {
         Ok(Some(collection)) => collection,
         Ok(None) => {
             return Err(Status::NotFound);
         }
         Err(err) => {
-            log::error!("failed to get collection: {err:#?}");
+            tracing::error!(error = %ErrorChain(&err), "failed to get collection");
             return Err(Status::InternalServerError);
         }
     };
 
+    attach_tag_definitions(tag_definition_service, [&mut collection]).await;
+
     Ok(Json(collection))
 }
 
+#[openapi(tag = "Collections")]
 #[get("/<collection_id>/files?<query..>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(collection_id = %collection_id))]
 async fn collections_list_files(
+    request_span: RequestSpan,
     collection_service: &State<CollectionService>,
+    tag_definition_service: &State<TagDefinitionService>,
+    admin: Option<AuthenticatedAdmin>,
     collection_id: Uuid,
     query: forms::CollectionFileListQuery,
-) -> Result<Json<Vec<File>>, Status> {
+) -> Result<Json<Vec<File>>, RouteError> {
     let cursor = match (query.last_file_id, query.last_file_name) {
         (Some(last_file_id), Some(last_file_name)) => Some(CollectionFileCursor {
             id: last_file_id,
@@ -91,161 +139,702 @@ async fn collections_list_files(
         _ => None,
     };
 
-    let files = match collection_service
-        .list_collection_files(collection_id, query.limit, cursor)
+    let mut files = match collection_service
+        .list_collection_files(
+            collection_id,
+            query.limit,
+            cursor,
+            query.sort,
+            query.verify_cursor,
+        )
         .await
     {
         Ok(files) => files,
+        Err(CollectionServiceError::InvalidCursor) => {
+            return Err(RouteError::InvalidCursor);
+        }
         Err(err) => {
-            log::error!("failed to list collection files: {err:#?}");
-            return Err(Status::InternalServerError);
+            tracing::error!(error = %ErrorChain(&err), "failed to list collection files");
+            return Err(Status::InternalServerError.into());
         }
     };
 
+    redact_storage(&mut files, admin.as_ref());
+    attach_tag_definitions(tag_definition_service, &mut files).await;
+
     Ok(Json(files))
 }
 
+/// Presigns download URLs for every ready file of the collection. A collection with more than
+/// [`DOWNLOAD_MANIFEST_INLINE_MAX_FILES`] files gets a `collection-download-manifest` admin task
+/// instead, whose manifest is downloaded through
+/// [`collections_create_download_manifest_download_url`] once it completes.
+#[allow(clippy::too_many_arguments)]
+#[openapi(tag = "Collections")]
+#[post("/<collection_id>/download-manifests")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(collection_id = %collection_id))]
+async fn collections_create_download_manifest(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    collection_service: &State<CollectionService>,
+    export_service: &State<ExportService>,
+    timeouts: &State<RouteTimeouts>,
+    presign: &State<PresignConfig>,
+    admin: AuthenticatedAdmin,
+    collection_id: Uuid,
+) -> Result<Json<CreatedCollectionDownloadManifest>, RouteError> {
+    let (collection, file_count) = match collection_service
+        .get_collection_with_file_count(collection_id)
+        .await
+    {
+        Ok(Some(collection)) => collection,
+        Ok(None) => {
+            return Err(Status::NotFound.into());
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to get collection");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    if DOWNLOAD_MANIFEST_INLINE_MAX_FILES < file_count {
+        let task = admin_task_service
+            .enqueue_task(
+                AdminTaskInitiator::User,
+                Some(admin.admin.id),
+                TaskKind::CollectionDownloadManifest,
+                serde_json::json!({
+                    "collection_id": collection.id,
+                    "tags": collection.tags,
+                }),
+                None,
+                false,
+                None,
+            )
+            .await;
+        let task = match task {
+            Ok(task) => task,
+            Err(err) => {
+                tracing::error!(error = %ErrorChain(&err), "failed to enqueue admin task for download manifest");
+                return Err(Status::InternalServerError.into());
+            }
+        };
+
+        return Ok(Json(CreatedCollectionDownloadManifest {
+            manifest: None,
+            admin_task_id: Some(task.id),
+        }));
+    }
+
+    // the first URL is presigned after this, so none expires before it
+    let expires_at = chrono::Utc::now() + presign.download_url;
+    let mut manifest = CollectionDownloadManifest {
+        collection_id: collection.id,
+        expires_at,
+        missing_count: 0,
+        files: Vec::with_capacity(file_count as usize),
+    };
+    let mut after = None;
+
+    // a collection without tags has no files
+    while !collection.tags.is_empty() {
+        let page = with_timeout(
+            Dependency::Storage,
+            timeouts.storage,
+            export_service.presign_manifest_page(&collection.tags, after, presign.download_url),
+        )
+        .await?;
+        let page = match page {
+            Ok(page) => page,
+            Err(ExportServiceError::S3(err)) => {
+                tracing::error!(error = %ErrorChain(&err), "failed to generate presigned urls for download manifest");
+                return Err(storage_error(&err));
+            }
+            Err(err) => {
+                tracing::error!(error = %ErrorChain(&err), "failed to list files for download manifest");
+                return Err(Status::InternalServerError.into());
+            }
+        };
+
+        manifest.files.extend(page.files);
+        manifest.missing_count += page.missing_count;
+
+        match page.last_file_id {
+            Some(last_file_id) => after = Some(last_file_id),
+            None => break,
+        }
+    }
+
+    Ok(Json(CreatedCollectionDownloadManifest {
+        manifest: Some(manifest),
+        admin_task_id: None,
+    }))
+}
+
+#[allow(clippy::too_many_arguments)]
+#[openapi(tag = "Collections")]
+#[post("/<collection_id>/download-manifests/<task_id>/download-urls")]
+#[tracing::instrument(
+    parent = &request_span.span,
+    skip_all,
+    fields(collection_id = %collection_id, task_id = %task_id)
+)]
+async fn collections_create_download_manifest_download_url(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    object_storage: &State<Arc<dyn ObjectStorage>>,
+    timeouts: &State<RouteTimeouts>,
+    presign: &State<PresignConfig>,
+    _admin: AuthenticatedAdmin,
+    collection_id: Uuid,
+    task_id: Uuid,
+) -> Result<Json<FileDownloadUrl>, RouteError> {
+    let task = match admin_task_service.get_task(task_id).await {
+        Ok(Some(task))
+            if task.name == TaskKind::CollectionDownloadManifest
+                && task.metadata.get("collection_id")
+                    == Some(&serde_json::json!(collection_id)) =>
+        {
+            task
+        }
+        Ok(_) => {
+            return Err(Status::NotFound.into());
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to get admin task");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    if task.status != AdminTaskStatus::Completed {
+        return Err(Status::Conflict.into());
+    }
+
+    let now = chrono::Utc::now();
+    let url = with_timeout(
+        Dependency::Storage,
+        timeouts.storage,
+        object_storage.generate_presigned_url_for_object_download(
+            &ExportService::manifest_object_key(task.id),
+            presign.download_url,
+        ),
+    )
+    .await?;
+    let url = match url {
+        Ok(Some(url)) => url,
+        Ok(None) => {
+            return Err(Status::NotFound.into());
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to generate presigned url for download manifest");
+            return Err(storage_error(&err));
+        }
+    };
+
+    Ok(Json(FileDownloadUrl {
+        url,
+        file_id: None,
+        expires_at: now + presign.download_url,
+    }))
+}
+
+/// Enqueues a `bundle-collection` admin task that packs the ready files of the collection into a
+/// zip, stored as a new file tagged `bundle` whose id the task metadata records as
+/// `bundle_file_id`. Collections with more than [`BUNDLE_MAX_FILES`] files, or whose bundle would
+/// exceed [`BUNDLE_MAX_BYTES`] or the maximum file size, are rejected.
+#[openapi(tag = "Collections")]
+#[post("/<collection_id>/bundles")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(collection_id = %collection_id))]
+async fn collections_create_bundle(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    collection_service: &State<CollectionService>,
+    file_service: &State<FileService>,
+    upload: &State<UploadConfig>,
+    admin: RequireEditor,
+    collection_id: Uuid,
+) -> Result<Json<AdminTask>, RouteError> {
+    let collection = match collection_service.get_collection(collection_id).await {
+        Ok(Some(collection)) => collection,
+        Ok(None) => {
+            return Err(Status::NotFound.into());
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to get collection");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    let mut files = Vec::new();
+
+    // a collection without tags has no files
+    while !collection.tags.is_empty() {
+        let after = files.last().map(|file: &File| file.id);
+        let page = match file_service
+            .list_files_with_all_tags(&collection.tags, after, BUNDLE_LIST_PAGE_SIZE)
+            .await
+        {
+            Ok(page) => page,
+            Err(err) => {
+                tracing::error!(error = %ErrorChain(&err), "failed to list collection files");
+                return Err(Status::InternalServerError.into());
+            }
+        };
+        let is_last_page = page.len() < BUNDLE_LIST_PAGE_SIZE;
+        files.extend(page);
+
+        if BUNDLE_MAX_FILES < files.len() {
+            return Err(RouteError::CollectionTooLargeToBundle);
+        }
+
+        if is_last_page {
+            break;
+        }
+    }
+
+    if BUNDLE_MAX_BYTES.min(upload.max_file_size) < BundleService::bundle_size(&files) {
+        return Err(RouteError::CollectionTooLargeToBundle);
+    }
+
+    let task = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::User,
+            Some(admin.admin.id),
+            TaskKind::BundleCollection,
+            serde_json::json!({
+                "collection_id": collection.id,
+                "name": format!("{}.zip", collection.name),
+                "file_ids": Vec::from_iter(files.iter().map(|file| file.id)),
+            }),
+            None,
+            false,
+            None,
+        )
+        .await;
+    let task = match task {
+        Ok(task) => task,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to enqueue admin task for bundle");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    Ok(Json(task))
+}
+
+#[allow(clippy::too_many_arguments)]
+#[openapi(tag = "Collections")]
 #[post("/", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
 async fn collections_create(
+    request_span: RequestSpan,
     admin_task_service: &State<AdminTaskService>,
     collection_service: &State<CollectionService>,
     index_service: &State<IndexService>,
+    tag_definition_service: &State<TagDefinitionService>,
+    admin: RequireEditor,
     body: Json<CreatingCollection>,
-) -> Result<Json<Collection>, Status> {
+) -> Result<Json<WithAdminTask<Collection>>, RouteError> {
     let body = body.into_inner();
-    let collection = match collection_service.create_collection(body.clone()).await {
-        Ok(collection) => collection,
+    let errors = validate_tags("tags", &body.tags);
+
+    if !errors.is_empty() {
+        return Err(RouteError::InvalidFields(errors));
+    }
+
+    let (mut collection, file_count) =
+        match collection_service.create_collection(body.clone()).await {
+            Ok(created) => created,
+            Err(CollectionServiceError::TooManyTags { limit, attempted }) => {
+                return Err(RouteError::InvalidFields(vec![FieldError::new(
+                    "tags",
+                    format!("must contain at most {limit} tags, got {attempted}"),
+                )]));
+            }
+            Err(CollectionServiceError::NameConflict { names }) => {
+                return Err(RouteError::NameConflict { names });
+            }
+            Err(CollectionServiceError::Conflict { key, value }) => {
+                return Err(RouteError::Conflict { key, value });
+            }
+            Err(err) => {
+                tracing::error!(error = %ErrorChain(&err), "failed to create collection");
+                return Err(Status::InternalServerError.into());
+            }
+        };
+
+    let result = index_service
+        .index_collection(&collection, file_count)
+        .await;
+    let (status, error, meili_task_uid) = match result {
+        Ok(meili_task_uid) => (AdminTaskStatus::Completed, None, Some(meili_task_uid)),
         Err(err) => {
-            log::error!("failed to create collection: {err:#?}");
-            return Err(Status::InternalServerError);
+            tracing::warn!(
+                collection_id = %collection.id,
+                error = %ErrorChain(&err),
+                "failed to index collection"
+            );
+            (AdminTaskStatus::InProgress, Some(err.to_string()), None)
         }
     };
-
-    let status = match index_service.index_collection(&collection).await {
-        Ok(()) => AdminTaskStatus::Completed,
-        Err(err) => {
-            log::warn!("failed to index collection `{}`: {err:#?}", collection.id);
-            AdminTaskStatus::Failed
+    let retry_task_id = match error {
+        Some(_) => {
+            let changes = serde_json::json!({ "collection_ids_to_index": [collection.id] });
+            defer_indexing(admin_task_service, changes).await
         }
+        None => None,
+    };
+    // the retry task takes over the indexing, so the change itself went through
+    let (status, error, indexing) = match retry_task_id {
+        Some(_) => (AdminTaskStatus::Completed, None, IndexingStatus::Deferred),
+        None => (status, error, IndexingStatus::Done),
     };
+    let file_re_index_task_id =
+        re_index_collection_files(admin_task_service, collection.id, &[&collection.tags]).await;
 
     let result = admin_task_service
         .enqueue_task(
             AdminTaskInitiator::User,
-            CREATE_COLLECTION_TASK_NAME.to_owned(),
-            serde_json::json!({ "collection_id": collection.id, "content": body }),
+            Some(admin.admin.id),
+            TaskKind::CreateCollection,
+            serde_json::json!({
+                "collection_id": collection.id,
+                "content": body,
+                "meili_task_uids": Vec::from_iter(meili_task_uid),
+                "indexing": indexing,
+                "retry_task_ids": Vec::from_iter(retry_task_id),
+                "file_re_index_task_id": file_re_index_task_id,
+            }),
             Some(status),
             false,
+            None,
         )
         .await;
 
-    if let Err(err) = result {
-        log::warn!("failed to enqueue admin task: {err:#?}");
-    }
+    let admin_task_id = match (result, error) {
+        (Ok(task), Some(error)) => {
+            if let Err(err) = admin_task_service.fail_task(task.id, &error).await {
+                tracing::warn!(error = %ErrorChain(&err), "failed to mark admin task as failed");
+            }
 
-    Ok(Json(collection))
+            Some(task.id)
+        }
+        (Ok(task), None) => Some(task.id),
+        (Err(err), _) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to enqueue admin task");
+            None
+        }
+    };
+
+    attach_tag_definitions(tag_definition_service, [&mut collection]).await;
+
+    Ok(Json(WithAdminTask {
+        result: collection,
+        admin_task_id,
+    }))
 }
 
+#[allow(clippy::too_many_arguments)]
+#[openapi(tag = "Collections")]
 #[patch("/<collection_id>", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(collection_id = %collection_id))]
 async fn collections_update(
+    request_span: RequestSpan,
     admin_task_service: &State<AdminTaskService>,
     collection_service: &State<CollectionService>,
     index_service: &State<IndexService>,
+    tag_definition_service: &State<TagDefinitionService>,
+    admin: RequireEditor,
     collection_id: Uuid,
     body: Json<UpdatingCollection>,
-) -> Result<Json<Collection>, Status> {
+) -> Result<Json<WithAdminTask<Collection>>, RouteError> {
     let body = body.into_inner();
-    let collection = match collection_service
+
+    if body.is_empty() {
+        return Err(RouteError::NoFields);
+    }
+
+    let errors = validate_tags(
+        "tagsForCreation",
+        body.tags_for_creation.as_deref().unwrap_or_default(),
+    );
+
+    if !errors.is_empty() {
+        return Err(RouteError::InvalidFields(errors));
+    }
+
+    let (mut collection, file_count, tags_before_update) = match collection_service
         .update_collection(collection_id, body.clone())
         .await
     {
-        Ok(Some(collection)) => collection,
+        Ok(Some((collection, file_count, true, tags_before_update))) => {
+            (collection, file_count, tags_before_update)
+        }
+        Ok(Some((mut collection, _, false, _))) => {
+            attach_tag_definitions(tag_definition_service, [&mut collection]).await;
+
+            // nothing to re-index or to record
+            return Ok(Json(WithAdminTask {
+                result: collection,
+                admin_task_id: None,
+            }));
+        }
         Ok(None) => {
-            return Err(Status::NotFound);
+            return Err(Status::NotFound.into());
+        }
+        Err(CollectionServiceError::TooManyTags { limit, attempted }) => {
+            return Err(RouteError::InvalidFields(vec![FieldError::new(
+                "tagsForCreation",
+                format!("would leave the collection with {attempted} tags, more than {limit}"),
+            )]));
+        }
+        Err(CollectionServiceError::NameConflict { names }) => {
+            return Err(RouteError::NameConflict { names });
+        }
+        Err(CollectionServiceError::Conflict { key, value }) => {
+            return Err(RouteError::Conflict { key, value });
         }
         Err(err) => {
-            log::error!("failed to update collection: {err:#?}");
-            return Err(Status::InternalServerError);
+            tracing::error!(error = %ErrorChain(&err), "failed to update collection");
+            return Err(Status::InternalServerError.into());
         }
     };
 
-    let status = match index_service.index_collection(&collection).await {
-        Ok(()) => AdminTaskStatus::Completed,
+    let result = index_service
+        .index_collection(&collection, file_count)
+        .await;
+    let (status, error, meili_task_uid) = match result {
+        Ok(meili_task_uid) => (AdminTaskStatus::Completed, None, Some(meili_task_uid)),
         Err(err) => {
-            log::warn!("failed to index collection `{}`: {err:#?}", collection.id);
-            AdminTaskStatus::Failed
+            tracing::warn!(
+                collection_id = %collection.id,
+                error = %ErrorChain(&err),
+                "failed to index collection"
+            );
+            (AdminTaskStatus::InProgress, Some(err.to_string()), None)
         }
     };
+    let retry_task_id = match error {
+        Some(_) => {
+            let changes = serde_json::json!({ "collection_ids_to_index": [collection.id] });
+            defer_indexing(admin_task_service, changes).await
+        }
+        None => None,
+    };
+    // the retry task takes over the indexing, so the change itself went through
+    let (status, error, indexing) = match retry_task_id {
+        Some(_) => (AdminTaskStatus::Completed, None, IndexingStatus::Deferred),
+        None => (status, error, IndexingStatus::Done),
+    };
+    // a rename shows on the files of the collection, a tag change also on those it gained or lost
+    let file_re_index_task_id = re_index_collection_files(
+        admin_task_service,
+        collection_id,
+        &[&tags_before_update, &collection.tags],
+    )
+    .await;
 
     let result = admin_task_service
         .enqueue_task(
             AdminTaskInitiator::User,
-            UPDATE_COLLECTION_TASK_NAME.to_owned(),
-            serde_json::json!({ "collection_id": collection_id, "delta": body }),
+            Some(admin.admin.id),
+            TaskKind::UpdateCollection,
+            serde_json::json!({
+                "collection_id": collection_id,
+                "delta": body,
+                "meili_task_uids": Vec::from_iter(meili_task_uid),
+                "indexing": indexing,
+                "retry_task_ids": Vec::from_iter(retry_task_id),
+                "file_re_index_task_id": file_re_index_task_id,
+            }),
             Some(status),
             false,
+            None,
         )
         .await;
 
-    if let Err(err) = result {
-        log::warn!("failed to enqueue admin task: {err:#?}");
-    }
+    let admin_task_id = match (result, error) {
+        (Ok(task), Some(error)) => {
+            if let Err(err) = admin_task_service.fail_task(task.id, &error).await {
+                tracing::warn!(error = %ErrorChain(&err), "failed to mark admin task as failed");
+            }
 
-    Ok(Json(collection))
+            Some(task.id)
+        }
+        (Ok(task), None) => Some(task.id),
+        (Err(err), _) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to enqueue admin task");
+            None
+        }
+    };
+
+    attach_tag_definitions(tag_definition_service, [&mut collection]).await;
+
+    Ok(Json(WithAdminTask {
+        result: collection,
+        admin_task_id,
+    }))
 }
 
+#[openapi(tag = "Collections")]
 #[delete("/<collection_id>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(collection_id = %collection_id))]
 async fn collections_delete(
+    request_span: RequestSpan,
     admin_task_service: &State<AdminTaskService>,
     collection_service: &State<CollectionService>,
     index_service: &State<IndexService>,
+    admin: RequireEditor,
     collection_id: Uuid,
-) -> Result<Json<SimpleOk>, Status> {
-    if let Err(err) = collection_service.delete_collection(collection_id).await {
-        log::error!("failed to delete collection from index: {err:#?}");
-        return Err(Status::InternalServerError);
-    }
+) -> Result<Json<WithAdminTask<SimpleOk>>, RouteError> {
+    let tags = match collection_service.delete_collection(collection_id).await {
+        Ok(tags) => tags,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to delete collection from index");
+            return Err(Status::InternalServerError.into());
+        }
+    };
 
-    let status = match index_service.delete_collection(collection_id).await {
-        Ok(()) => AdminTaskStatus::Completed,
+    let result = index_service.delete_collections(&[collection_id]).await;
+    let deletion = match result {
+        Ok(meili_task_uids) => Ok(meili_task_uids),
+        Err(IndexServiceError::Timeout { .. }) => {
+            Err(RouteError::Timeout(Dependency::SearchEngine))
+        }
         Err(err) => {
-            log::error!("failed to delete collection: {err:#?}");
-            return Err(Status::InternalServerError);
+            tracing::warn!(error = %ErrorChain(&err), "failed to delete collection from index");
+            Err(RouteError::from(Status::InternalServerError))
+        }
+    };
+    let (meili_task_uids, retry_task_id) = match deletion {
+        Ok(meili_task_uids) => (meili_task_uids, None),
+        Err(route_error) => {
+            let changes = serde_json::json!({ "collection_ids_to_delete": [collection_id] });
+
+            match defer_indexing(admin_task_service, changes).await {
+                Some(retry_task_id) => (Vec::new(), Some(retry_task_id)),
+                None => return Err(route_error),
+            }
         }
     };
+    let indexing = match retry_task_id {
+        Some(_) => IndexingStatus::Deferred,
+        None => IndexingStatus::Done,
+    };
+    let file_re_index_task_id =
+        re_index_collection_files(admin_task_service, collection_id, &[&tags]).await;
 
     let result = admin_task_service
         .enqueue_task(
             AdminTaskInitiator::User,
-            DELETE_COLLECTION_TASK_NAME.to_owned(),
-            serde_json::json!({ "collection_id": collection_id }),
-            Some(status),
+            Some(admin.admin.id),
+            TaskKind::DeleteCollection,
+            serde_json::json!({
+                "collection_id": collection_id,
+                "meili_task_uids": meili_task_uids,
+                "indexing": indexing,
+                "retry_task_ids": Vec::from_iter(retry_task_id),
+                "file_re_index_task_id": file_re_index_task_id,
+            }),
+            Some(AdminTaskStatus::Completed),
             false,
+            None,
         )
         .await;
 
-    if let Err(err) = result {
-        log::warn!("failed to enqueue admin task: {err:#?}");
+    let admin_task_id = match result {
+        Ok(task) => Some(task.id),
+        Err(err) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to enqueue admin task");
+            None
+        }
+    };
+
+    Ok(Json(WithAdminTask {
+        result: SimpleOk { ok: true },
+        admin_task_id,
+    }))
+}
+
+/// Enqueues a `re-index-collection-files` task for the files tagged with every tag of one of the
+/// tag sets, so that their search hits show the collections they belong to now. Returns the id of
+/// the task, or `None` if no file can be affected or it could not be enqueued.
+async fn re_index_collection_files(
+    admin_task_service: &AdminTaskService,
+    collection_id: Uuid,
+    tag_sets: &[&Vec<String>],
+) -> Option<Uuid> {
+    let mut affected_tag_sets = Vec::with_capacity(tag_sets.len());
+
+    for &tags in tag_sets {
+        // a collection without tags has no files
+        if !tags.is_empty() && !affected_tag_sets.contains(&tags) {
+            affected_tag_sets.push(tags);
+        }
+    }
+
+    if affected_tag_sets.is_empty() {
+        return None;
     }
 
-    Ok(Json(SimpleOk { ok: true }))
+    let result = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::System,
+            None,
+            TaskKind::ReIndexCollectionFiles,
+            serde_json::json!({
+                "collection_id": collection_id,
+                "tag_sets": affected_tag_sets,
+            }),
+            None,
+            false,
+            None,
+        )
+        .await;
+
+    match result {
+        Ok(task) => Some(task.id),
+        Err(err) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to enqueue re-indexing of collection files");
+            None
+        }
+    }
 }
 
 mod forms {
+    use crate::interfaces::collections::CollectionFileListSort;
     use rocket::{
         form::{Error, Result},
         FromForm,
     };
+    use schemars::JsonSchema;
     use uuid::Uuid;
 
-    #[derive(FromForm, Debug)]
+    #[derive(FromForm, JsonSchema, Debug)]
     pub struct CollectionListQuery {
-        #[field(name = uncased("limit"), default = 25, validate = range(1..=100))]
+        #[schemars(default = "default_limit", range(min = 1, max = 1000))]
+        #[field(name = uncased("limit"), default = default_limit(), validate = range(1..=1000))]
         pub limit: usize,
+        #[schemars(rename = "last-collection-id")]
         #[field(name = uncased("last-collection-id"), validate = __collection_list_query_is_last_collection_id_valid(&self.last_collection_name))]
         pub last_collection_id: Option<Uuid>,
+        #[schemars(rename = "last-collection-name")]
         #[field(name = uncased("last-collection-name"), validate = __collection_list_query_is_last_collection_name_valid(&self.last_collection_id))]
         pub last_collection_name: Option<String>,
+        #[schemars(rename = "verify-cursor", default = "default_verify_cursor")]
+        #[field(name = uncased("verify-cursor"), default = default_verify_cursor())]
+        pub verify_cursor: bool,
+        #[schemars(rename = "tag")]
+        #[field(name = uncased("tag"))]
+        pub tags: Vec<String>,
+    }
+
+    fn default_limit() -> usize {
+        25
+    }
+
+    fn default_verify_cursor() -> bool {
+        true
     }
 
     fn __collection_list_query_is_last_collection_id_valid<'v>(
@@ -274,14 +863,27 @@ mod forms {
         Ok(())
     }
 
-    #[derive(FromForm, Debug)]
+    #[derive(FromForm, JsonSchema, Debug)]
     pub struct CollectionFileListQuery {
-        #[field(name = uncased("limit"), default = 25, validate = range(1..=100))]
+        #[schemars(default = "default_limit", range(min = 1, max = 100))]
+        #[field(name = uncased("limit"), default = default_limit(), validate = range(1..=100))]
         pub limit: usize,
+        #[schemars(default = "default_file_sort")]
+        #[field(name = uncased("sort"), default = default_file_sort())]
+        pub sort: CollectionFileListSort,
+        #[schemars(rename = "last-file-id")]
         #[field(name = uncased("last-file-id"), validate = __collection_file_list_query_is_last_file_id_valid(&self.last_file_name))]
         pub last_file_id: Option<Uuid>,
+        #[schemars(rename = "last-file-name")]
         #[field(name = uncased("last-file-name"), validate = __collection_file_list_query_is_last_file_name_valid(&self.last_file_id))]
         pub last_file_name: Option<String>,
+        #[schemars(rename = "verify-cursor", default = "default_verify_cursor")]
+        #[field(name = uncased("verify-cursor"), default = default_verify_cursor())]
+        pub verify_cursor: bool,
+    }
+
+    fn default_file_sort() -> CollectionFileListSort {
+        CollectionFileListSort::Name
     }
 
     fn __collection_file_list_query_is_last_file_id_valid<'v>(
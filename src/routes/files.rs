@@ -1,440 +1,2498 @@
 use crate::{
+    config::{PresignConfig, StorageCostConfig, UploadConfig},
+    forms::part_range::PartRangeFormField,
+    guards::{
+        authenticated_admin::{AuthenticatedAdmin, RequireAdmin, RequireEditor},
+        batch_json::BatchJson,
+        request_span::RequestSpan,
+    },
     interfaces::{
-        admins::{AdminTaskInitiator, AdminTaskStatus},
+        admins::AdminTask,
+        admins::{AdminRole, AdminTaskInitiator, AdminTaskPreview, AdminTaskStatus, TaskKind},
         files::{
-            CreatingFile, File, FileCursor, FileDownloadUrl, FileUploadUrl, FileUploadUrlPart,
-            UpdatingFile, UploadedParts,
+            BulkDeletingFiles, BulkTagOutcome, BulkTaggedFiles, BulkTaggingFiles, CreatedFile,
+            CreatingFile, CreatingFileExport, CreatingFileVersion, CreatingFiles, File,
+            FileCostReport, FileCursor, FileDownloadUrl, FileExportFormat, FileIndexStatus,
+            FileListSort, FileNameCursor, FileStorage, FileUploadProgress, FileUploadRecord,
+            FileUploadUrl, FileUploadUrlPart, FileVersion, FileWithIndexStatus, UpdatingFile,
+            UploadOutcome, UploadedParts,
         },
-        SimpleOk,
+        tags::validate_tags,
+        FieldError, IndexingStatus, SimpleOk, WithAdminTask,
+    },
+    routes::{
+        attach_tag_definitions, check_list_limit, defer_indexing, redact_storage, storage_error,
+        with_timeout, Dependency, RouteError, RouteTimeouts,
     },
     services::{
-        admin_task_service::{
-            AdminTaskService, DELETE_FILE_TASK_NAME, UPDATE_FILE_TASK_NAME, UPLOAD_FILE_TASK_NAME,
-        },
-        file_service::FileService,
-        index_service::IndexService,
-        s3_service::S3Service,
+        admin_task_service::{AdminTaskService, AdminTaskServiceError},
+        export_service::ExportService,
+        file_service::{FileService, FileServiceError},
+        index_service::{document_indexed_at, DocumentIndex, IndexService, IndexServiceError},
+        s3_service::ObjectStorage,
+        tag_definition_service::TagDefinitionService,
     },
+    telemetry::ErrorChain,
+};
+use futures::{
+    future::ready,
+    stream::{self, BoxStream},
+    StreamExt, TryStreamExt,
 };
-use futures::future::try_join_all;
-use rocket::{delete, get, http::Status, patch, post, routes, serde::json::Json, Route, State};
-use std::{time::Duration, vec};
+use rocket::{
+    delete, get,
+    http::{ContentType, Status},
+    patch, post,
+    response::stream::TextStream,
+    serde::json::Json,
+    Either, Route, State,
+};
+use rocket_okapi::{
+    okapi::openapi3::OpenApi, openapi, openapi_get_routes_spec, settings::OpenApiSettings,
+};
+use std::{collections::HashSet, ops::RangeInclusive, sync::Arc, time::Duration, vec};
 use uuid::Uuid;
 
-/// 1 hour
-const DOWNLOAD_URL_DURATION: Duration = Duration::from_secs(60 * 60);
-/// 1 hour
-const UPLOAD_URL_DURATION: Duration = Duration::from_secs(60 * 60);
+/// Maximum number of parts a single `part-range` may span.
+const UPLOAD_URL_MAX_PARTS_PER_REQUEST: usize = 1000;
+/// Maximum number of part URLs presigned at the same time.
+const UPLOAD_URL_PRESIGN_CONCURRENCY: usize = 32;
+/// Maximum number of files a single batch creation may register. Their request body stays well
+/// below the default JSON limit of 1 MiB at common name and tag lengths.
+const BATCH_CREATE_MAX_FILES: usize = 500;
+/// Maximum number of files a single bulk deletion may target.
+const BULK_DELETE_MAX_FILES: usize = 10_000;
+/// Maximum number of files a single bulk tag change may target.
+const BULK_TAG_MAX_FILES: usize = 1000;
+/// Number of files a bulk tag change re-indexes with a single Meilisearch call.
+const BULK_TAG_INDEX_BATCH_SIZE: usize = 250;
+/// Number of the latest admin tasks listed for a file.
+const FILE_ADMIN_TASKS_LIMIT: usize = 100;
+/// Number of the files an admin viewed last that are listed.
+const RECENTLY_VIEWED_FILES_LIMIT: usize = 50;
+/// Number of the collections holding the most bytes that a cost report breaks down.
+const COST_REPORT_COLLECTIONS_LIMIT: usize = 100;
 
-pub fn routes() -> Vec<Route> {
-    routes![
+pub fn routes(settings: &OpenApiSettings) -> (Vec<Route>, OpenApi) {
+    openapi_get_routes_spec![settings:
         files_list,
+        files_export,
+        files_create_export,
+        files_create_export_download_url,
+        files_list_recently_viewed,
+        files_get_cost_report,
         files_get,
+        files_list_admin_tasks,
+        files_list_uploads,
+        files_get_upload_progress,
         files_create_download_url,
         files_create,
+        files_create_batch,
+        files_list_versions,
+        files_create_version,
         files_create_upload_urls,
+        files_create_upload_part_urls,
         files_complete_upload,
         files_abort_upload,
         files_update,
         files_delete,
+        files_bulk_delete,
+        files_bulk_tag,
     ]
 }
 
+#[openapi(tag = "Files")]
 #[get("/?<query..>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
 async fn files_list(
+    request_span: RequestSpan,
     file_service: &State<FileService>,
+    tag_definition_service: &State<TagDefinitionService>,
+    admin: Option<AuthenticatedAdmin>,
     query: forms::ListQuery,
-) -> Result<Json<Vec<File>>, Status> {
-    let cursor = match (query.last_file_id, query.last_file_uploaded_at) {
-        (Some(last_file_id), Some(last_file_uploaded_at)) => Some(FileCursor {
-            id: last_file_id,
-            uploaded_at: last_file_uploaded_at.date_time,
-        }),
-        _ => None,
-    };
+) -> Result<Json<Vec<File>>, RouteError> {
+    check_list_limit(query.limit, admin.as_ref())?;
+
+    let files = match query.sort {
+        FileListSort::UploadedAt => {
+            let cursor = match (query.last_file_id, query.last_file_uploaded_at) {
+                (Some(last_file_id), Some(last_file_uploaded_at)) => Some(FileCursor {
+                    id: last_file_id,
+                    uploaded_at: last_file_uploaded_at.date_time,
+                }),
+                _ => None,
+            };
+
+            file_service
+                .list_files(
+                    query.limit,
+                    cursor,
+                    query.verify_cursor,
+                    query.name_prefix.as_deref(),
+                )
+                .await
+        }
+        FileListSort::NameNatural => {
+            let cursor = match (query.last_file_id, query.last_file_name) {
+                (Some(last_file_id), Some(last_file_name)) => Some(FileNameCursor {
+                    id: last_file_id,
+                    name: last_file_name,
+                }),
+                _ => None,
+            };
 
-    let files = match file_service.list_files(query.limit, cursor).await {
+            file_service
+                .list_files_by_name(
+                    query.limit,
+                    cursor,
+                    query.verify_cursor,
+                    query.name_prefix.as_deref(),
+                )
+                .await
+        }
+    };
+    let mut files = match files {
         Ok(files) => files,
+        Err(FileServiceError::InvalidCursor) => {
+            return Err(RouteError::InvalidCursor);
+        }
         Err(err) => {
-            log::error!("failed to list files: {err:#?}");
-            return Err(Status::InternalServerError);
+            tracing::error!(error = %ErrorChain(&err), "failed to list files");
+            return Err(Status::InternalServerError.into());
         }
     };
 
+    redact_storage(&mut files, admin.as_ref());
+    attach_tag_definitions(tag_definition_service, &mut files).await;
+
     Ok(Json(files))
 }
 
-#[get("/<file_id>")]
-async fn files_get(file_service: &State<FileService>, file_id: Uuid) -> Result<Json<File>, Status> {
-    let file = match file_service.get_file(file_id).await {
-        Ok(Some(file)) => file,
-        Ok(None) => {
-            return Err(Status::NotFound);
-        }
+#[openapi(tag = "Files")]
+#[get("/export?<query..>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+fn files_export(
+    request_span: RequestSpan,
+    export_service: &State<ExportService>,
+    _admin: AuthenticatedAdmin,
+    query: forms::ExportQuery,
+) -> (ContentType, TextStream<BoxStream<'_, String>>) {
+    let format = query.format;
+    let content_type = match format {
+        FileExportFormat::Ndjson => ContentType::new("application", "x-ndjson"),
+        FileExportFormat::Csv => ContentType::CSV,
+    };
+
+    // the status line is already sent once streaming starts, so a failure can only cut it short
+    let stream = export_service
+        .stream_files(format)
+        .take_while(|row| {
+            if let Err(err) = row {
+                tracing::error!(error = %ErrorChain(err), "failed to export files");
+            }
+
+            ready(row.is_ok())
+        })
+        .filter_map(|row| ready(row.ok()))
+        .boxed();
+
+    (content_type, TextStream(stream))
+}
+
+#[openapi(tag = "Files")]
+#[post("/exports", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn files_create_export(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    admin: AuthenticatedAdmin,
+    body: Json<CreatingFileExport>,
+) -> Result<Json<AdminTask>, Status> {
+    let task = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::User,
+            Some(admin.admin.id),
+            TaskKind::ExportFiles,
+            serde_json::json!({ "format": body.format }),
+            None,
+            false,
+            None,
+        )
+        .await;
+    let task = match task {
+        Ok(task) => task,
         Err(err) => {
-            log::error!("failed to get file: {err:#?}");
+            tracing::error!(error = %ErrorChain(&err), "failed to enqueue admin task for export");
             return Err(Status::InternalServerError);
         }
     };
 
-    Ok(Json(file))
+    Ok(Json(task))
 }
 
-#[post("/<file_id>/download-urls")]
-async fn files_create_download_url(
-    s3_service: &State<S3Service>,
-    file_id: Uuid,
-) -> Result<Json<FileDownloadUrl>, Status> {
+#[openapi(tag = "Files")]
+#[post("/exports/<task_id>/download-urls")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(task_id = %task_id))]
+async fn files_create_export_download_url(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    object_storage: &State<Arc<dyn ObjectStorage>>,
+    timeouts: &State<RouteTimeouts>,
+    presign: &State<PresignConfig>,
+    _admin: AuthenticatedAdmin,
+    task_id: Uuid,
+) -> Result<Json<FileDownloadUrl>, RouteError> {
+    let task = match admin_task_service.get_task(task_id).await {
+        Ok(Some(task)) if task.name == TaskKind::ExportFiles => task,
+        Ok(_) => {
+            return Err(Status::NotFound.into());
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to get admin task");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    if task.status != AdminTaskStatus::Completed {
+        return Err(Status::Conflict.into());
+    }
+
+    let format = task
+        .metadata
+        .get("format")
+        .cloned()
+        .and_then(|format| serde_json::from_value::<FileExportFormat>(format).ok());
+    let format = match format {
+        Some(format) => format,
+        None => {
+            tracing::error!("export task `{}` has no valid format", task.id);
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
     let now = chrono::Utc::now();
-    let url = s3_service
-        .generate_presigned_url_for_download(file_id, DOWNLOAD_URL_DURATION)
-        .await;
+    let url = with_timeout(
+        Dependency::Storage,
+        timeouts.storage,
+        object_storage.generate_presigned_url_for_object_download(
+            &ExportService::object_key(task.id, format),
+            presign.download_url,
+        ),
+    )
+    .await?;
     let url = match url {
         Ok(Some(url)) => url,
         Ok(None) => {
-            return Err(Status::NotFound);
+            return Err(Status::NotFound.into());
         }
         Err(err) => {
-            log::error!("failed to generate presigned url for download: {err:#?}");
-            return Err(Status::InternalServerError);
+            tracing::error!(error = %ErrorChain(&err), "failed to generate presigned url for export download");
+            return Err(storage_error(&err));
         }
     };
-    let expires_at = now + DOWNLOAD_URL_DURATION;
 
-    Ok(Json(FileDownloadUrl { url, expires_at }))
+    Ok(Json(FileDownloadUrl {
+        url,
+        file_id: None,
+        expires_at: now + presign.download_url,
+    }))
 }
 
-#[post("/", data = "<body>")]
-async fn files_create(
+#[openapi(tag = "Files")]
+#[get("/recent")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn files_list_recently_viewed(
+    request_span: RequestSpan,
     file_service: &State<FileService>,
-    body: Json<CreatingFile>,
-) -> Result<Json<File>, Status> {
-    let file = match file_service.create_file(body.into_inner()).await {
-        Ok(file) => file,
+    tag_definition_service: &State<TagDefinitionService>,
+    admin: AuthenticatedAdmin,
+) -> Result<Json<Vec<File>>, Status> {
+    let mut files = match file_service
+        .list_recently_viewed_files(admin.admin.id, RECENTLY_VIEWED_FILES_LIMIT)
+        .await
+    {
+        Ok(files) => files,
         Err(err) => {
-            log::error!("failed to create file: {err:#?}");
+            tracing::error!(error = %ErrorChain(&err), "failed to list recently viewed files");
             return Err(Status::InternalServerError);
         }
     };
 
-    Ok(Json(file))
+    redact_storage(&mut files, Some(&admin));
+    attach_tag_definitions(tag_definition_service, &mut files).await;
+
+    Ok(Json(files))
 }
 
-#[post("/<file_id>/upload-urls")]
-async fn files_create_upload_urls(
+#[openapi(tag = "Files")]
+#[get("/cost-report?<query..>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn files_get_cost_report(
+    request_span: RequestSpan,
     file_service: &State<FileService>,
-    s3_service: &State<S3Service>,
-    file_id: Uuid,
-) -> Result<Json<FileUploadUrl>, Status> {
-    let (size, mime_type) = match file_service.get_file_for_upload(file_id).await {
-        Ok(Some((size, mime_type))) => (size, mime_type),
-        Ok(None) => {
-            log::info!("file `{}` not found", file_id);
-            return Err(Status::NotFound);
-        }
+    storage_cost: &State<StorageCostConfig>,
+    _admin: RequireAdmin,
+    query: forms::CostReportQuery,
+) -> Result<Json<FileCostReport>, Status> {
+    let collection_limit = query.by_collection.then_some(COST_REPORT_COLLECTIONS_LIMIT);
+    let report = match file_service
+        .get_cost_report(storage_cost, collection_limit)
+        .await
+    {
+        Ok(report) => report,
         Err(err) => {
-            log::error!("failed to get file for upload: {err:#?}");
+            tracing::error!(error = %ErrorChain(&err), "failed to get cost report");
             return Err(Status::InternalServerError);
         }
     };
 
-    /// 5 TiB
-    const MAX_FILE_SIZE: usize = 1024 * 1024 * 1024 * 1024 * 5;
+    Ok(Json(report))
+}
 
-    if MAX_FILE_SIZE < size {
-        return Err(Status::UnprocessableEntity);
+#[allow(clippy::too_many_arguments)]
+#[openapi(tag = "Files")]
+#[get("/<file_id>?<query..>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(file_id = %file_id))]
+async fn files_get(
+    request_span: RequestSpan,
+    file_service: &State<FileService>,
+    index_service: &State<IndexService>,
+    tag_definition_service: &State<TagDefinitionService>,
+    admin: Option<AuthenticatedAdmin>,
+    file_id: Uuid,
+    query: forms::GetQuery,
+) -> Result<Either<Json<File>, Json<FileWithIndexStatus>>, Status> {
+    if query.include_index_status && admin.is_none() {
+        return Err(Status::Unauthorized);
     }
 
-    let id = s3_service.create_multipart_upload(file_id, mime_type).await;
-    let id = match id {
-        Ok(id) => id,
+    let mut file = match file_service.get_file(file_id).await {
+        Ok(Some(file)) => file,
+        Ok(None) => {
+            return Err(Status::NotFound);
+        }
         Err(err) => {
-            log::error!("failed to create multipart upload: {err:#?}");
+            tracing::error!(error = %ErrorChain(&err), "failed to get file");
             return Err(Status::InternalServerError);
         }
     };
 
-    log::info!("created multipart upload for file `{}`: {}", file_id, id);
-
-    /// 64 MiB
-    const PART_SIZE: usize = 1024 * 1024 * 64;
-
-    let count: u32 = if size <= PART_SIZE {
-        1
-    } else {
-        (size / PART_SIZE) as u32
-    };
-
-    if 10000 <= count {
-        return Err(Status::UnprocessableEntity);
+    if let Some(admin) = &admin {
+        file_service.record_view(admin.admin.id, file.id);
     }
 
-    let now = chrono::Utc::now();
-    let mut presigned_url_tasks = Vec::with_capacity(count as usize);
+    redact_storage([&mut file], admin.as_ref());
+    attach_tag_definitions(tag_definition_service, [&mut file]).await;
 
-    for part_number in 1..=count {
-        presigned_url_tasks.push(s3_service.generate_presigned_url_for_upload(
-            file_id,
-            &id,
-            part_number,
-            UPLOAD_URL_DURATION,
-        ));
+    if !query.include_index_status {
+        return Ok(Either::Left(Json(file)));
     }
 
-    let urls = try_join_all(presigned_url_tasks).await;
-    let urls = match urls {
-        Ok(urls) => urls,
+    let document = index_service
+        .get_raw_document(DocumentIndex::Files, file.id)
+        .await;
+    // the file is answered either way; the index status is only left out
+    let index_status = match document {
+        Ok(document) => Some(FileIndexStatus {
+            indexed: document.is_some(),
+            indexed_at: document.as_ref().and_then(document_indexed_at),
+        }),
         Err(err) => {
-            log::error!("failed to generate presigned urls for upload: {err:#?}");
-            return Err(Status::InternalServerError);
+            tracing::warn!(error = %ErrorChain(&err), "failed to get index document of file");
+            None
         }
     };
 
-    let mut offset = 0;
-    let mut parts = Vec::with_capacity(count as usize);
-
-    for (url, part_number) in urls.into_iter().zip(1..=count) {
-        let size = if part_number == count {
-            size - offset
-        } else {
-            PART_SIZE
-        };
+    Ok(Either::Right(Json(FileWithIndexStatus {
+        file,
+        index_status,
+    })))
+}
 
-        parts.push(FileUploadUrlPart {
-            part_number,
-            url,
-            offset: offset as u64,
-            size: size as u64,
-        });
-        offset += size;
-    }
+#[openapi(tag = "Files")]
+#[get("/<file_id>/admin-tasks")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(file_id = %file_id))]
+async fn files_list_admin_tasks(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    _admin: RequireAdmin,
+    file_id: Uuid,
+) -> Result<Json<Vec<AdminTaskPreview>>, Status> {
+    let tasks = match admin_task_service
+        .list_tasks_for_file(file_id, FILE_ADMIN_TASKS_LIMIT)
+        .await
+    {
+        Ok(tasks) => tasks,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to list admin tasks of file");
+            return Err(Status::InternalServerError);
+        }
+    };
 
-    Ok(Json(FileUploadUrl {
-        id,
-        parts,
-        expires_at: now + UPLOAD_URL_DURATION,
-    }))
+    Ok(Json(tasks))
 }
 
-#[post("/<file_id>/upload-urls/<upload_id>/completes", data = "<body>")]
-async fn files_complete_upload(
-    admin_task_service: &State<AdminTaskService>,
+#[openapi(tag = "Files")]
+#[get("/<file_id>/uploads")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(file_id = %file_id))]
+async fn files_list_uploads(
+    request_span: RequestSpan,
     file_service: &State<FileService>,
-    index_service: &State<IndexService>,
-    s3_service: &State<S3Service>,
+    _admin: RequireAdmin,
     file_id: Uuid,
-    upload_id: &str,
-    body: Json<UploadedParts>,
-) -> Result<Option<Json<File>>, Status> {
-    let body = body.into_inner();
-    let file = match file_service.mark_file_as_ready(file_id).await {
-        Ok(Some(file)) => file,
+) -> Result<Json<Vec<FileUploadRecord>>, Status> {
+    match file_service.get_file_for_upload(file_id).await {
+        Ok(Some(_)) => {}
         Ok(None) => {
             return Err(Status::NotFound);
         }
         Err(err) => {
-            log::error!("failed to mark file as ready: {err:#?}");
+            tracing::error!(error = %ErrorChain(&err), "failed to get file for upload");
+            return Err(Status::InternalServerError);
+        }
+    }
+
+    let records = match file_service.list_upload_records(file_id).await {
+        Ok(records) => records,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to list uploads of file");
             return Err(Status::InternalServerError);
         }
     };
 
-    let parts = body
-        .parts
-        .iter()
-        .map(|part| (part.part_number, part.e_tag.clone()))
-        .collect::<Vec<_>>();
+    Ok(Json(records))
+}
 
-    match s3_service
-        .complete_multipart_upload(file_id, upload_id.to_owned(), &parts)
-        .await
-    {
-        Ok(Some(())) => {}
+#[openapi(tag = "Files")]
+#[get("/<file_id>/upload-progress")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(file_id = %file_id))]
+async fn files_get_upload_progress(
+    request_span: RequestSpan,
+    file_service: &State<FileService>,
+    object_storage: &State<Arc<dyn ObjectStorage>>,
+    timeouts: &State<RouteTimeouts>,
+    upload: &State<UploadConfig>,
+    _admin: RequireEditor,
+    file_id: Uuid,
+) -> Result<Json<FileUploadProgress>, RouteError> {
+    let (is_ready, size, storage) = match file_service.get_file_for_completion(file_id).await {
+        Ok(Some((is_ready, _, size, storage))) => (is_ready, size, storage),
         Ok(None) => {
-            return Err(Status::NotFound);
+            return Err(Status::NotFound.into());
         }
         Err(err) => {
-            log::error!("failed to complete upload: {err:#?}");
-            return Err(Status::InternalServerError);
+            tracing::error!(error = %ErrorChain(&err), "failed to get file for completion");
+            return Err(Status::InternalServerError.into());
         }
     };
 
-    let status = match index_service.index_file(&file).await {
-        Ok(()) => AdminTaskStatus::Completed,
+    let parts_total = upload.part_count(size);
+
+    if is_ready {
+        return Ok(Json(FileUploadProgress {
+            bytes_received: size as u64,
+            total_bytes: size as u64,
+            parts_received: parts_total,
+            parts_total,
+            percent: 100.0,
+        }));
+    }
+
+    let record = match file_service.get_pending_upload_record(file_id).await {
+        Ok(Some(record)) => record,
+        Ok(None) => {
+            tracing::info!("file `{}` has no upload in progress", file_id);
+            return Err(Status::NotFound.into());
+        }
         Err(err) => {
-            log::warn!("failed to index file `{}`: {err:#?}", file.id);
-            AdminTaskStatus::Failed
+            tracing::error!(error = %ErrorChain(&err), "failed to get pending upload of file");
+            return Err(Status::InternalServerError.into());
         }
     };
 
-    let result = admin_task_service
-        .enqueue_task(
-            AdminTaskInitiator::User,
-            UPLOAD_FILE_TASK_NAME.to_owned(),
-            serde_json::json!({ "file_id": file.id, "content": body }),
-            Some(status),
-            false,
-        )
-        .await;
+    let progress = upload_progress(
+        object_storage.as_ref(),
+        timeouts,
+        upload,
+        file_id,
+        &record.upload_id,
+        size,
+        storage.as_ref(),
+    )
+    .await?;
 
-    if let Err(err) = result {
-        log::warn!("failed to enqueue admin task: {err:#?}");
+    match progress {
+        Some(progress) => Ok(Json(progress)),
+        None => Err(Status::NotFound.into()),
     }
-
-    Ok(Some(Json(file)))
 }
 
-#[delete("/<file_id>/upload-urls/<upload_id>")]
-async fn files_abort_upload(
-    s3_service: &State<S3Service>,
+/// Sums the parts the pending upload of the file has received so far, or answers `None` if the
+/// upload is gone from the storage.
+async fn upload_progress(
+    object_storage: &dyn ObjectStorage,
+    timeouts: &RouteTimeouts,
+    upload: &UploadConfig,
     file_id: Uuid,
     upload_id: &str,
-) -> Result<Json<SimpleOk>, Status> {
-    let result = s3_service
-        .abort_multipart_upload(file_id, upload_id.to_owned())
-        .await;
-    let result = match result {
-        Ok(Some(())) => SimpleOk { ok: true },
+    size: usize,
+    storage: Option<&FileStorage>,
+) -> Result<Option<FileUploadProgress>, RouteError> {
+    let parts = with_timeout(
+        Dependency::Storage,
+        timeouts.storage,
+        object_storage.list_parts(file_id, upload_id, storage),
+    )
+    .await?;
+    let parts = match parts {
+        Ok(Some(parts)) => parts,
         Ok(None) => {
-            return Err(Status::NotFound);
+            tracing::info!("upload `{}` is gone from the storage", upload_id);
+            return Ok(None);
         }
         Err(err) => {
-            log::error!("failed to abort multipart upload: {err:#?}");
-            return Err(Status::InternalServerError);
+            tracing::error!(error = %ErrorChain(&err), "failed to list parts of upload");
+            return Err(storage_error(&err));
         }
     };
+    let parts_total = upload.part_count(size);
 
-    Ok(Json(result))
+    // only the parts of the plan the upload URLs were issued for count, each up to its planned
+    // size, so that a stray part never pushes the progress past the file
+    let (parts_received, bytes_received) = parts
+        .iter()
+        .filter(|part| (1..=parts_total).contains(&part.part_number))
+        .fold((0, 0), |(count, bytes), part| {
+            let (_, planned_size) = upload.part_range(size, part.part_number);
+            (count + 1, bytes + part.size.min(planned_size as u64))
+        });
+    let percent = match size {
+        0 if parts_received == parts_total => 100.0,
+        0 => 0.0,
+        size => bytes_received as f64 * 100.0 / size as f64,
+    };
+
+    Ok(Some(FileUploadProgress {
+        bytes_received,
+        total_bytes: size as u64,
+        parts_received,
+        parts_total,
+        percent,
+    }))
 }
 
-#[patch("/<file_id>", data = "<body>")]
-async fn files_update(
+#[allow(clippy::too_many_arguments)]
+#[openapi(tag = "Files")]
+#[post("/<file_id>/download-urls?<query..>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(file_id = %file_id))]
+async fn files_create_download_url(
+    request_span: RequestSpan,
     admin_task_service: &State<AdminTaskService>,
     file_service: &State<FileService>,
-    index_service: &State<IndexService>,
+    object_storage: &State<Arc<dyn ObjectStorage>>,
+    timeouts: &State<RouteTimeouts>,
+    presign: &State<PresignConfig>,
+    _admin: AuthenticatedAdmin,
     file_id: Uuid,
-    body: Json<UpdatingFile>,
-) -> Result<Json<File>, Status> {
-    let body = body.into_inner();
-    let file = match file_service.update_file(file_id, body.clone()).await {
+    query: forms::DownloadUrlQuery,
+) -> Result<Json<FileDownloadUrl>, RouteError> {
+    let file = match query.latest {
+        true => file_service.get_latest_file(file_id).await,
+        false => file_service.get_file(file_id).await,
+    };
+    let file = match file {
         Ok(Some(file)) => file,
         Ok(None) => {
-            return Err(Status::NotFound);
+            // only ready files are gotten, so tell an unready file apart from an unknown one
+            return Err(match file_service.get_file_for_upload(file_id).await {
+                Ok(Some(_)) => RouteError::FileNotReady,
+                Ok(None) => RouteError::FileNotFound,
+                Err(err) => {
+                    tracing::error!(error = %ErrorChain(&err), "failed to get file for upload");
+                    Status::InternalServerError.into()
+                }
+            });
         }
         Err(err) => {
-            log::error!("failed to update file: {err:#?}");
-            return Err(Status::InternalServerError);
+            tracing::error!(error = %ErrorChain(&err), "failed to get file");
+            return Err(Status::InternalServerError.into());
         }
     };
 
-    let status = match index_service.index_file(&file).await {
-        Ok(()) => AdminTaskStatus::Completed,
+    let now = chrono::Utc::now();
+    let url = with_timeout(
+        Dependency::Storage,
+        timeouts.storage,
+        object_storage.generate_presigned_url_for_download(
+            file.id,
+            presign.download_url,
+            file.storage.as_ref(),
+        ),
+    )
+    .await?;
+    let url = match url {
+        Ok(Some(url)) => url,
+        Ok(None) => {
+            tracing::warn!("object of ready file `{}` is missing", file.id);
+            enqueue_storage_reconciliation(admin_task_service).await;
+            return Err(RouteError::ObjectMissing);
+        }
         Err(err) => {
-            log::warn!("failed to index file `{}`: {err:#?}", file.id);
-            AdminTaskStatus::Failed
+            tracing::error!(error = %ErrorChain(&err), "failed to generate presigned url for download");
+            return Err(storage_error(&err));
         }
     };
+    let expires_at = now + presign.download_url;
+
+    Ok(Json(FileDownloadUrl {
+        url,
+        file_id: (file.id != file_id).then_some(file.id),
+        expires_at,
+    }))
+}
+
+/// Answers [`RouteError::FileProtected`] if the file is protected from deletion.
+async fn check_not_protected(file_service: &FileService, file_id: Uuid) -> Result<(), RouteError> {
+    match file_service.get_protected_file_ids(&[file_id]).await {
+        Ok(file_ids) if file_ids.is_empty() => Ok(()),
+        Ok(_) => Err(RouteError::FileProtected),
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to check file protection");
+            Err(Status::InternalServerError.into())
+        }
+    }
+}
 
+/// Enqueues a storage reconciliation that only reports, unless one is already pending or in
+/// progress, so that the missing objects show up in its metadata.
+async fn enqueue_storage_reconciliation(admin_task_service: &AdminTaskService) {
     let result = admin_task_service
         .enqueue_task(
-            AdminTaskInitiator::User,
-            UPDATE_FILE_TASK_NAME.to_owned(),
-            serde_json::json!({ "file_id": file_id, "delta": body }),
-            Some(status),
+            AdminTaskInitiator::System,
+            None,
+            TaskKind::ReconcileStorage,
+            serde_json::json!({
+                "delete_orphans": false,
+                "mark_missing_unready": false,
+            }),
+            None,
             false,
+            None,
         )
         .await;
 
-    if let Err(err) = result {
-        log::warn!("failed to enqueue admin task: {err:#?}");
+    match result {
+        Ok(_) | Err(AdminTaskServiceError::AlreadyQueued { .. }) => {}
+        Err(err) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to enqueue storage reconciliation");
+        }
     }
-
-    Ok(Json(file))
 }
 
-#[delete("/<file_id>")]
-async fn files_delete(
-    admin_task_service: &State<AdminTaskService>,
+#[allow(clippy::too_many_arguments)]
+#[openapi(tag = "Files")]
+#[post("/?<query..>", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn files_create(
+    request_span: RequestSpan,
     file_service: &State<FileService>,
-    index_service: &State<IndexService>,
-    s3_service: &State<S3Service>,
-    file_id: Uuid,
-) -> Result<Json<SimpleOk>, Status> {
-    if let Err(err) = s3_service.delete_file(file_id).await {
-        log::error!("failed to delete file from s3: {err:#?}");
-        return Err(Status::InternalServerError);
+    tag_definition_service: &State<TagDefinitionService>,
+    object_storage: &State<Arc<dyn ObjectStorage>>,
+    timeouts: &State<RouteTimeouts>,
+    presign: &State<PresignConfig>,
+    upload: &State<UploadConfig>,
+    admin: RequireEditor,
+    query: forms::CreateQuery,
+    body: Json<CreatingFile>,
+) -> Result<Either<Json<File>, Json<CreatedFile>>, RouteError> {
+    if !body.is_checksum_valid() {
+        return Err(RouteError::InvalidFields(vec![FieldError::new(
+            "checksum",
+            "must be a hex SHA-256 digest",
+        )]));
+    }
+
+    if upload.max_file_size < body.size {
+        return Err(RouteError::InvalidFields(vec![FieldError::new(
+            "size",
+            format!("must be at most {} bytes", upload.max_file_size),
+        )]));
     }
 
-    let status = match index_service.delete_file(file_id).await {
-        Ok(()) => AdminTaskStatus::Completed,
+    let errors = validate_tags("tags", body.tags.as_deref().unwrap_or_default());
+
+    if !errors.is_empty() {
+        return Err(RouteError::InvalidFields(errors));
+    }
+
+    if !body.allow_duplicate {
+        check_not_uploading(
+            file_service,
+            object_storage.as_ref(),
+            timeouts,
+            upload,
+            &body,
+        )
+        .await?;
+    }
+
+    let mut file = match file_service.create_file(body.into_inner()).await {
+        Ok(file) => file,
+        Err(FileServiceError::TooManyTags { limit, attempted }) => {
+            return Err(RouteError::InvalidFields(vec![FieldError::new(
+                "tags",
+                format!("must contain at most {limit} tags, got {attempted}"),
+            )]));
+        }
+        Err(FileServiceError::NameConflict { names }) => {
+            return Err(RouteError::NameConflict { names });
+        }
+        Err(FileServiceError::Conflict { key, value }) => {
+            return Err(RouteError::Conflict { key, value });
+        }
         Err(err) => {
-            log::error!("failed to delete file from index: {err:#?}");
-            return Err(Status::InternalServerError);
+            tracing::error!(error = %ErrorChain(&err), "failed to create file");
+            return Err(Status::InternalServerError.into());
         }
     };
 
-    let result = admin_task_service
-        .enqueue_task(
-            AdminTaskInitiator::User,
-            DELETE_FILE_TASK_NAME.to_owned(),
-            serde_json::json!({ "file_id": file_id }),
-            Some(status),
-            false,
-        )
-        .await;
+    if !query.include_upload_urls {
+        redact_storage([&mut file], Some(&admin));
+        attach_tag_definitions(tag_definition_service, [&mut file]).await;
 
-    if let Err(err) = result {
-        log::warn!("failed to enqueue admin task: {err:#?}");
+        return Ok(Either::Left(Json(file)));
     }
 
-    if let Err(err) = file_service.delete_file(file_id).await {
-        log::error!("failed to delete file: {err:#?}");
-        return Err(Status::InternalServerError);
-    }
+    let result = start_upload(
+        file_service,
+        object_storage.as_ref(),
+        timeouts,
+        presign,
+        upload,
+        file.id,
+        file.size,
+        &file.mime_type,
+        file.storage.as_ref(),
+        None,
+        false,
+        None,
+    )
+    .await;
+    // the file is created either way, so a failed upload is reported next to it rather than
+    // failing the request; the client retries with the upload urls route
+    let (upload, upload_error) = match result {
+        Ok(upload) => (Some(upload), None),
+        Err(err) => (None, Some(err.code().unwrap_or("upload_not_started"))),
+    };
 
-    Ok(Json(SimpleOk { ok: true }))
+    redact_storage([&mut file], Some(&admin));
+    attach_tag_definitions(tag_definition_service, [&mut file]).await;
+
+    Ok(Either::Right(Json(CreatedFile {
+        file,
+        upload,
+        upload_error: upload_error.map(|code| code.to_owned()),
+    })))
 }
 
-mod forms {
-    use crate::forms::date_time_utc::DateTimeUtcFormField;
-    use rocket::{
-        form::{Error, Result},
-        FromForm,
+/// Refuses a file whose content is being uploaded to an identical file already, answering with
+/// that file and the progress of its upload so that the client resumes it instead.
+async fn check_not_uploading(
+    file_service: &FileService,
+    object_storage: &dyn ObjectStorage,
+    timeouts: &RouteTimeouts,
+    upload: &UploadConfig,
+    file: &CreatingFile,
+) -> Result<(), RouteError> {
+    let (file_id, upload_id, storage) = match file_service.find_uploading_duplicate(file).await {
+        Ok(Some(duplicate)) => duplicate,
+        Ok(None) => {
+            return Ok(());
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to find duplicate upload");
+            return Err(Status::InternalServerError.into());
+        }
     };
-    use uuid::Uuid;
 
-    #[derive(FromForm, Debug)]
-    pub struct ListQuery {
-        #[field(name = uncased("limit"), default = 25, validate = range(1..=100))]
-        pub limit: usize,
-        #[field(name = uncased("last-file-id"), validate = is_last_file_id_valid(&self.last_file_uploaded_at))]
-        pub last_file_id: Option<Uuid>,
-        #[field(name = uncased("last-file-uploaded-at"), validate = is_last_file_uploaded_at_valid(&self.last_file_id))]
-        pub last_file_uploaded_at: Option<DateTimeUtcFormField>,
-    }
+    // the duplicate is refused either way; the progress only spares the client a request
+    let progress = upload_progress(
+        object_storage,
+        timeouts,
+        upload,
+        file_id,
+        &upload_id,
+        file.size,
+        storage.as_ref(),
+    )
+    .await
+    .ok()
+    .flatten();
 
-    fn is_last_file_id_valid<'v>(
-        this: &Option<Uuid>,
-        last_file_uploaded_at: &Option<DateTimeUtcFormField>,
-    ) -> Result<'v, ()> {
-        if this.is_some() && last_file_uploaded_at.is_none() {
-            Err(Error::validation(
-                "`last-file-uploaded-at` must be provided if `last-file-id` is provided",
-            ))?;
-        }
+    tracing::info!(
+        "file `{}` is being uploaded already: {}",
+        file_id,
+        upload_id
+    );
 
-        Ok(())
+    Err(RouteError::DuplicateUpload { file_id, progress })
+}
+
+#[openapi(tag = "Files")]
+#[post("/batch", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn files_create_batch(
+    request_span: RequestSpan,
+    file_service: &State<FileService>,
+    tag_definition_service: &State<TagDefinitionService>,
+    upload: &State<UploadConfig>,
+    admin: RequireEditor,
+    body: BatchJson<CreatingFiles>,
+) -> Result<Json<Vec<File>>, RouteError> {
+    let body = body.into_inner();
+
+    if body.files.is_empty() || BATCH_CREATE_MAX_FILES < body.files.len() {
+        return Err(RouteError::InvalidFields(vec![FieldError::new(
+            "files",
+            format!("must contain between 1 and {BATCH_CREATE_MAX_FILES} files"),
+        )]));
     }
 
-    fn is_last_file_uploaded_at_valid<'v>(
-        this: &Option<DateTimeUtcFormField>,
-        last_file_id: &Option<Uuid>,
-    ) -> Result<'v, ()> {
-        if this.is_some() && last_file_id.is_none() {
-            Err(Error::validation(
-                "`last-file-id` must be provided if `last-file-uploaded-at` is provided",
-            ))?;
-        }
+    // the batch is created as a whole, so every invalid entry is reported at once
+    let errors = Vec::from_iter(body.files.iter().enumerate().flat_map(|(index, file)| {
+        let checksum = (!file.is_checksum_valid()).then(|| {
+            FieldError::new(
+                format!("files[{index}].checksum"),
+                "must be a hex SHA-256 digest",
+            )
+        });
+        let size = (upload.max_file_size < file.size).then(|| {
+            FieldError::new(
+                format!("files[{index}].size"),
+                format!("must be at most {} bytes", upload.max_file_size),
+            )
+        });
 
-        Ok(())
+        let tags = validate_tags(
+            &format!("files[{index}].tags"),
+            file.tags.as_deref().unwrap_or_default(),
+        );
+
+        checksum.into_iter().chain(size).chain(tags)
+    }));
+
+    if !errors.is_empty() {
+        return Err(RouteError::InvalidFields(errors));
+    }
+
+    let mut files = match file_service.create_files(body.files).await {
+        Ok(files) => files,
+        Err(FileServiceError::TooManyTags { limit, attempted }) => {
+            return Err(RouteError::InvalidFields(vec![FieldError::new(
+                "files",
+                format!("each file must have at most {limit} tags, one has {attempted}"),
+            )]));
+        }
+        Err(FileServiceError::NameConflict { names }) => {
+            return Err(RouteError::NameConflict { names });
+        }
+        Err(FileServiceError::Conflict { key, value }) => {
+            return Err(RouteError::Conflict { key, value });
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to create files");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    redact_storage(&mut files, Some(&admin));
+    attach_tag_definitions(tag_definition_service, &mut files).await;
+
+    Ok(Json(files))
+}
+
+#[openapi(tag = "Files")]
+#[get("/<file_id>/versions")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(file_id = %file_id))]
+async fn files_list_versions(
+    request_span: RequestSpan,
+    file_service: &State<FileService>,
+    tag_definition_service: &State<TagDefinitionService>,
+    admin: Option<AuthenticatedAdmin>,
+    file_id: Uuid,
+) -> Result<Json<Vec<FileVersion>>, RouteError> {
+    let mut versions = match file_service.list_file_versions(file_id).await {
+        Ok(Some(versions)) => versions,
+        Ok(None) => {
+            return Err(RouteError::FileNotFound);
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to list file versions");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    redact_storage(
+        versions.iter_mut().map(|version| &mut version.file),
+        admin.as_ref(),
+    );
+    attach_tag_definitions(
+        tag_definition_service,
+        versions.iter_mut().map(|version| &mut version.file),
+    )
+    .await;
+
+    Ok(Json(versions))
+}
+
+#[openapi(tag = "Files")]
+#[post("/<file_id>/versions", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(file_id = %file_id))]
+async fn files_create_version(
+    request_span: RequestSpan,
+    file_service: &State<FileService>,
+    tag_definition_service: &State<TagDefinitionService>,
+    upload: &State<UploadConfig>,
+    admin: RequireEditor,
+    file_id: Uuid,
+    body: Json<CreatingFileVersion>,
+) -> Result<Json<FileVersion>, RouteError> {
+    if !body.is_checksum_valid() {
+        return Err(RouteError::InvalidFields(vec![FieldError::new(
+            "checksum",
+            "must be a hex SHA-256 digest",
+        )]));
+    }
+
+    if upload.max_file_size < body.size {
+        return Err(RouteError::InvalidFields(vec![FieldError::new(
+            "size",
+            format!("must be at most {} bytes", upload.max_file_size),
+        )]));
+    }
+
+    let mut version = match file_service
+        .create_file_version(file_id, body.into_inner())
+        .await
+    {
+        Ok(Some(version)) => version,
+        Ok(None) => {
+            return Err(RouteError::FileNotFound);
+        }
+        Err(FileServiceError::NameConflict { names }) => {
+            return Err(RouteError::NameConflict { names });
+        }
+        Err(FileServiceError::Conflict { key, value }) => {
+            return Err(RouteError::Conflict { key, value });
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to create file version");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    redact_storage([&mut version.file], Some(&admin));
+    attach_tag_definitions(tag_definition_service, [&mut version.file]).await;
+
+    Ok(Json(version))
+}
+
+#[allow(clippy::too_many_arguments)]
+#[openapi(tag = "Files")]
+#[post("/<file_id>/upload-urls?<query..>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(file_id = %file_id))]
+async fn files_create_upload_urls(
+    request_span: RequestSpan,
+    file_service: &State<FileService>,
+    object_storage: &State<Arc<dyn ObjectStorage>>,
+    timeouts: &State<RouteTimeouts>,
+    presign: &State<PresignConfig>,
+    upload: &State<UploadConfig>,
+    _admin: RequireEditor,
+    file_id: Uuid,
+    query: forms::StartUploadQuery,
+) -> Result<Json<FileUploadUrl>, RouteError> {
+    let (size, mime_type, storage, active_upload_id) =
+        match file_service.get_file_for_upload(file_id).await {
+            Ok(Some(file)) => file,
+            Ok(None) => {
+                tracing::info!("file `{}` not found", file_id);
+                return Err(Status::NotFound.into());
+            }
+            Err(err) => {
+                tracing::error!(error = %ErrorChain(&err), "failed to get file for upload");
+                return Err(Status::InternalServerError.into());
+            }
+        };
+
+    // files created before the limit was lowered may still be too large
+    if upload.max_file_size < size {
+        return Err(RouteError::InvalidFields(vec![FieldError::new(
+            "size",
+            format!("must be at most {} bytes", upload.max_file_size),
+        )]));
+    }
+
+    let upload_url = start_upload(
+        file_service,
+        object_storage.as_ref(),
+        timeouts,
+        presign,
+        upload,
+        file_id,
+        size,
+        &mime_type,
+        storage.as_ref(),
+        active_upload_id,
+        query.replace,
+        query.part_range.as_deref(),
+    )
+    .await?;
+
+    Ok(Json(upload_url))
+}
+
+/// Starts a multipart upload of the file and presigns the URLs of the requested parts. The upload
+/// is aborted again if it cannot be handed out.
+///
+/// A file has at most one active upload. If it has one already, its URLs are handed out again
+/// instead, unless `replace` is set, in which case it is aborted before starting a new one.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all, fields(file_id = %file_id))]
+async fn start_upload(
+    file_service: &FileService,
+    object_storage: &dyn ObjectStorage,
+    timeouts: &RouteTimeouts,
+    presign: &PresignConfig,
+    upload: &UploadConfig,
+    file_id: Uuid,
+    size: usize,
+    mime_type: &str,
+    storage: Option<&FileStorage>,
+    active_upload_id: Option<String>,
+    replace: bool,
+    part_range: Option<&str>,
+) -> Result<FileUploadUrl, RouteError> {
+    let part_count = upload.part_count(size);
+
+    let part_numbers = upload_part_numbers(part_count, part_range)?;
+
+    if let Some(active_upload_id) = active_upload_id {
+        if !replace {
+            return hand_out_upload(
+                object_storage,
+                timeouts,
+                presign,
+                upload,
+                file_id,
+                active_upload_id,
+                storage,
+                size,
+                part_numbers,
+            )
+            .await;
+        }
+
+        replace_upload(
+            file_service,
+            object_storage,
+            timeouts,
+            file_id,
+            &active_upload_id,
+            storage,
+        )
+        .await?;
+    }
+
+    let id = with_timeout(
+        Dependency::Storage,
+        timeouts.storage,
+        object_storage.create_multipart_upload(file_id, mime_type, storage),
+    )
+    .await?;
+    let id = match id {
+        Ok(id) => id,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to create multipart upload");
+            return Err(storage_error(&err));
+        }
+    };
+
+    tracing::info!("created multipart upload for file `{}`: {}", file_id, id);
+
+    let now = chrono::Utc::now();
+    let parts = presign_upload_parts(
+        object_storage,
+        timeouts,
+        upload,
+        presign.upload_url,
+        file_id,
+        &id,
+        storage,
+        size,
+        part_numbers.clone(),
+    )
+    .await;
+    let parts = match parts {
+        Ok(parts) => parts,
+        Err(err) => {
+            // nobody can upload to it, so do not leave it lingering in S3
+            abort_multipart_upload(object_storage, timeouts, file_id, &id, storage).await;
+            return Err(err);
+        }
+    };
+
+    // the record is what lets the gc abort the upload if it is abandoned, so do not hand out an
+    // upload without one
+    let started = file_service
+        .record_active_upload_started(file_id, &id, part_count, size)
+        .await;
+    let started = match started {
+        Ok(started) => started,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to record upload");
+            abort_multipart_upload(object_storage, timeouts, file_id, &id, storage).await;
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    if !started {
+        // another request started an upload of the file meanwhile, so hand out that one instead
+        abort_multipart_upload(object_storage, timeouts, file_id, &id, storage).await;
+
+        let active_upload_id = match file_service.get_file_for_upload(file_id).await {
+            Ok(Some((_, _, _, active_upload_id))) => active_upload_id,
+            Ok(None) => {
+                return Err(Status::NotFound.into());
+            }
+            Err(err) => {
+                tracing::error!(error = %ErrorChain(&err), "failed to get file for upload");
+                return Err(Status::InternalServerError.into());
+            }
+        };
+        // the other upload may have finished already as well
+        let Some(active_upload_id) = active_upload_id else {
+            return Err(Status::Conflict.into());
+        };
+
+        return hand_out_upload(
+            object_storage,
+            timeouts,
+            presign,
+            upload,
+            file_id,
+            active_upload_id,
+            storage,
+            size,
+            part_numbers,
+        )
+        .await;
+    }
+
+    Ok(FileUploadUrl {
+        id,
+        part_count,
+        parts,
+        expires_at: now + presign.upload_url,
+    })
+}
+
+/// Presigns the URLs of the requested parts of an upload that was started already.
+#[allow(clippy::too_many_arguments)]
+async fn hand_out_upload(
+    object_storage: &dyn ObjectStorage,
+    timeouts: &RouteTimeouts,
+    presign: &PresignConfig,
+    upload: &UploadConfig,
+    file_id: Uuid,
+    upload_id: String,
+    storage: Option<&FileStorage>,
+    size: usize,
+    part_numbers: RangeInclusive<u32>,
+) -> Result<FileUploadUrl, RouteError> {
+    tracing::info!(
+        "handing out multipart upload of file `{}`: {}",
+        file_id,
+        upload_id
+    );
+
+    let now = chrono::Utc::now();
+    let parts = presign_upload_parts(
+        object_storage,
+        timeouts,
+        upload,
+        presign.upload_url,
+        file_id,
+        &upload_id,
+        storage,
+        size,
+        part_numbers,
+    )
+    .await?;
+
+    Ok(FileUploadUrl {
+        id: upload_id,
+        part_count: upload.part_count(size),
+        parts,
+        expires_at: now + presign.upload_url,
+    })
+}
+
+/// Aborts the active upload of the file so that a new one can be started. An upload that is gone
+/// from the storage already only has its record finished.
+async fn replace_upload(
+    file_service: &FileService,
+    object_storage: &dyn ObjectStorage,
+    timeouts: &RouteTimeouts,
+    file_id: Uuid,
+    upload_id: &str,
+    storage: Option<&FileStorage>,
+) -> Result<(), RouteError> {
+    let result = with_timeout(
+        Dependency::Storage,
+        timeouts.storage,
+        object_storage.abort_multipart_upload(file_id, upload_id.to_owned(), storage),
+    )
+    .await?;
+
+    if let Err(err) = result {
+        tracing::error!(error = %ErrorChain(&err), "failed to abort multipart upload");
+        return Err(storage_error(&err));
+    }
+
+    tracing::info!(
+        "aborted multipart upload of file `{}` to replace it: {}",
+        file_id,
+        upload_id
+    );
+
+    // finishing the record is what frees the file for a new upload
+    if let Err(err) = file_service
+        .record_upload_finished(upload_id, UploadOutcome::Aborted)
+        .await
+    {
+        tracing::error!(error = %ErrorChain(&err), "failed to record upload abortion");
+        return Err(Status::InternalServerError.into());
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+#[openapi(tag = "Files")]
+#[post("/<file_id>/upload-urls/<upload_id>/parts?<query..>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(file_id = %file_id))]
+async fn files_create_upload_part_urls(
+    request_span: RequestSpan,
+    file_service: &State<FileService>,
+    object_storage: &State<Arc<dyn ObjectStorage>>,
+    timeouts: &State<RouteTimeouts>,
+    presign: &State<PresignConfig>,
+    upload: &State<UploadConfig>,
+    _admin: RequireEditor,
+    file_id: Uuid,
+    upload_id: &str,
+    query: forms::UploadUrlsQuery,
+) -> Result<Json<FileUploadUrl>, RouteError> {
+    let (size, storage) = match file_service.get_file_for_upload(file_id).await {
+        Ok(Some((size, _, storage, active_upload_id))) => {
+            // an aborted, completed or replaced upload must not be presigned any further
+            if active_upload_id.as_deref() != Some(upload_id) {
+                return Err(Status::Conflict.into());
+            }
+
+            (size, storage)
+        }
+        Ok(None) => {
+            return Err(Status::NotFound.into());
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to get file for upload");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    let part_count = upload.part_count(size);
+    let part_numbers = upload_part_numbers(part_count, query.part_range.as_deref())?;
+
+    let now = chrono::Utc::now();
+    let parts = presign_upload_parts(
+        object_storage.as_ref(),
+        timeouts,
+        upload,
+        presign.upload_url,
+        file_id,
+        upload_id,
+        storage.as_ref(),
+        size,
+        part_numbers,
+    )
+    .await?;
+
+    Ok(Json(FileUploadUrl {
+        id: upload_id.to_owned(),
+        part_count,
+        parts,
+        expires_at: now + presign.upload_url,
+    }))
+}
+
+/// Resolves the requested part range against the parts of the upload. Without a range, every
+/// part is requested; a range may span at most [`UPLOAD_URL_MAX_PARTS_PER_REQUEST`] parts.
+fn upload_part_numbers(
+    part_count: u32,
+    part_range: Option<&str>,
+) -> Result<RangeInclusive<u32>, RouteError> {
+    let part_range = match part_range.map(PartRangeFormField::parse) {
+        Some(Ok(part_range)) => part_range,
+        Some(Err(reason)) => {
+            return Err(RouteError::InvalidFields(vec![FieldError::new(
+                "part-range",
+                reason,
+            )]));
+        }
+        None => {
+            return Ok(1..=part_count);
+        }
+    };
+    let part_numbers = part_range.first..=part_range.last.min(part_count);
+
+    if part_count < *part_numbers.start() {
+        return Err(RouteError::InvalidFields(vec![FieldError::new(
+            "part-range",
+            format!("the upload only has {part_count} parts"),
+        )]));
+    }
+
+    if UPLOAD_URL_MAX_PARTS_PER_REQUEST < part_numbers.clone().count() {
+        return Err(RouteError::InvalidFields(vec![FieldError::new(
+            "part-range",
+            format!(
+                "at most {UPLOAD_URL_MAX_PARTS_PER_REQUEST} parts can be requested at once; \
+                 request the rest with further part ranges"
+            ),
+        )]));
+    }
+
+    Ok(part_numbers)
+}
+
+/// Presigns the upload URLs of the given parts, at most [`UPLOAD_URL_PRESIGN_CONCURRENCY`] at a
+/// time. The first failure drops the presigns still in flight, and so does the timeout.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(
+    skip_all,
+    fields(file_id = %file_id, size = size, parts = part_numbers.clone().count())
+)]
+async fn presign_upload_parts(
+    object_storage: &dyn ObjectStorage,
+    timeouts: &RouteTimeouts,
+    upload: &UploadConfig,
+    duration: Duration,
+    file_id: Uuid,
+    upload_id: &str,
+    storage: Option<&FileStorage>,
+    size: usize,
+    part_numbers: RangeInclusive<u32>,
+) -> Result<Vec<FileUploadUrlPart>, RouteError> {
+    let urls = stream::iter(part_numbers.clone())
+        .map(|part_number| {
+            object_storage.generate_presigned_url_for_upload(
+                file_id,
+                upload_id,
+                part_number,
+                duration,
+                storage,
+            )
+        })
+        .buffered(UPLOAD_URL_PRESIGN_CONCURRENCY)
+        .try_collect::<Vec<_>>();
+    let urls = with_timeout(Dependency::Storage, timeouts.storage, urls).await?;
+    let urls = match urls {
+        Ok(urls) => urls,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to generate presigned urls for upload");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    Ok(urls
+        .into_iter()
+        .zip(part_numbers)
+        .map(|(url, part_number)| {
+            let (offset, size) = upload.part_range(size, part_number);
+
+            FileUploadUrlPart {
+                part_number,
+                url,
+                offset: offset as u64,
+                size: size as u64,
+            }
+        })
+        .collect())
+}
+
+/// Best-effort cleanup of a multipart upload whose URLs could not be handed out.
+async fn abort_multipart_upload(
+    object_storage: &dyn ObjectStorage,
+    timeouts: &RouteTimeouts,
+    file_id: Uuid,
+    upload_id: &str,
+    storage: Option<&FileStorage>,
+) {
+    let result = with_timeout(
+        Dependency::Storage,
+        timeouts.storage,
+        object_storage.abort_multipart_upload(file_id, upload_id.to_owned(), storage),
+    )
+    .await;
+
+    match result {
+        Ok(Ok(_)) | Err(_) => {}
+        Ok(Err(err)) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to abort multipart upload `{upload_id}`");
+        }
+    }
+}
+
+/// Assembles the uploaded parts into the object of the file.
+async fn complete_multipart_upload(
+    object_storage: &dyn ObjectStorage,
+    timeouts: &RouteTimeouts,
+    file_id: Uuid,
+    upload_id: &str,
+    parts: &[(u32, String)],
+    storage: Option<&FileStorage>,
+) -> Result<(), RouteError> {
+    let result = with_timeout(
+        Dependency::Storage,
+        timeouts.storage,
+        object_storage.complete_multipart_upload(file_id, upload_id.to_owned(), parts, storage),
+    )
+    .await?;
+
+    match result {
+        Ok(Some(())) => Ok(()),
+        Ok(None) => Err(Status::NotFound.into()),
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to complete upload");
+            Err(storage_error(&err))
+        }
+    }
+}
+
+#[openapi(tag = "Files")]
+#[post("/<file_id>/upload-urls/<upload_id>/completes", data = "<body>")]
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(file_id = %file_id))]
+async fn files_complete_upload(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    file_service: &State<FileService>,
+    index_service: &State<IndexService>,
+    tag_definition_service: &State<TagDefinitionService>,
+    object_storage: &State<Arc<dyn ObjectStorage>>,
+    upload: &State<UploadConfig>,
+    timeouts: &State<RouteTimeouts>,
+    admin: RequireEditor,
+    file_id: Uuid,
+    upload_id: &str,
+    body: BatchJson<UploadedParts>,
+) -> Result<Option<Json<WithAdminTask<File>>>, RouteError> {
+    let body = body.into_inner();
+    let (is_ready, completed_upload_id, size, storage) =
+        match file_service.get_file_for_completion(file_id).await {
+            Ok(Some(completion)) => completion,
+            Ok(None) => {
+                return Err(Status::NotFound.into());
+            }
+            Err(err) => {
+                tracing::error!(error = %ErrorChain(&err), "failed to get file for completion");
+                return Err(Status::InternalServerError.into());
+            }
+        };
+
+    // S3 would assemble whatever parts are listed, so a missing part must not slip through
+    let part_count = upload.part_count(size);
+
+    if !body
+        .parts
+        .iter()
+        .map(|part| part.part_number)
+        .eq(1..=part_count)
+    {
+        return Err(RouteError::InvalidFields(vec![FieldError::new(
+            "parts",
+            format!("must list each of the {part_count} parts of the upload once, in order"),
+        )]));
+    }
+
+    let parts = body
+        .parts
+        .iter()
+        .map(|part| (part.part_number, part.e_tag.clone()))
+        .collect::<Vec<_>>();
+
+    let mut file = if is_ready {
+        // only a retry of the upload that made the file ready may complete it again
+        if completed_upload_id.as_deref() != Some(upload_id) {
+            return Err(Status::Conflict.into());
+        }
+
+        let exists = with_timeout(
+            Dependency::Storage,
+            timeouts.storage,
+            object_storage.object_exists(file_id, storage.as_ref()),
+        )
+        .await?;
+
+        match exists {
+            Ok(true) => {
+                // the previous attempt went through; answer it the same way again
+                return match file_service.get_file(file_id).await {
+                    Ok(Some(mut file)) => {
+                        redact_storage([&mut file], Some(&admin));
+                        attach_tag_definitions(tag_definition_service, [&mut file]).await;
+
+                        Ok(Some(Json(WithAdminTask {
+                            result: file,
+                            admin_task_id: None,
+                        })))
+                    }
+                    Ok(None) => Err(Status::NotFound.into()),
+                    Err(err) => {
+                        tracing::error!(error = %ErrorChain(&err), "failed to get file");
+                        Err(Status::InternalServerError.into())
+                    }
+                };
+            }
+            Ok(false) => {}
+            Err(err) => {
+                tracing::error!(error = %ErrorChain(&err), "failed to check object existence");
+                return Err(storage_error(&err));
+            }
+        }
+
+        complete_multipart_upload(
+            object_storage.inner().as_ref(),
+            timeouts,
+            file_id,
+            upload_id,
+            &parts,
+            storage.as_ref(),
+        )
+        .await?;
+
+        match file_service.get_file(file_id).await {
+            Ok(Some(file)) => file,
+            Ok(None) => {
+                return Err(Status::NotFound.into());
+            }
+            Err(err) => {
+                tracing::error!(error = %ErrorChain(&err), "failed to get file");
+                return Err(Status::InternalServerError.into());
+            }
+        }
+    } else {
+        // the file is only marked as ready once its object exists, so that a failed completion
+        // leaves it unready
+        complete_multipart_upload(
+            object_storage.inner().as_ref(),
+            timeouts,
+            file_id,
+            upload_id,
+            &parts,
+            storage.as_ref(),
+        )
+        .await?;
+
+        match file_service.mark_file_as_ready(file_id, upload_id).await {
+            Ok(Some((file, superseded_file_ids))) => {
+                // the earlier versions drop out of searches once re-indexed
+                for superseded_file_id in superseded_file_ids {
+                    index_service.queue_file_index(superseded_file_id);
+                }
+
+                file
+            }
+            Ok(None) => {
+                return Err(Status::NotFound.into());
+            }
+            Err(err) => {
+                tracing::error!(error = %ErrorChain(&err), "failed to mark file as ready");
+                return Err(Status::InternalServerError.into());
+            }
+        }
+    };
+
+    if let Err(err) = file_service
+        .record_upload_finished(upload_id, UploadOutcome::Completed)
+        .await
+    {
+        tracing::warn!(error = %ErrorChain(&err), "failed to record upload completion");
+    }
+
+    let (status, error, meili_task_uid) = match file_service.get_file_collections(&[file.id]).await
+    {
+        Ok(mut collections) => {
+            let collections = collections.remove(&file.id).unwrap_or_default();
+            let result = index_service.index_file(&file, &collections).await;
+
+            match result {
+                Ok(meili_task_uid) => (AdminTaskStatus::Completed, None, Some(meili_task_uid)),
+                Err(err) => {
+                    tracing::warn!(
+                        file_id = %file.id,
+                        error = %ErrorChain(&err),
+                        "failed to index file"
+                    );
+                    (AdminTaskStatus::InProgress, Some(err.to_string()), None)
+                }
+            }
+        }
+        // the retry task looks the collections up again
+        Err(err) => {
+            tracing::warn!(
+                file_id = %file.id,
+                error = %ErrorChain(&err),
+                "failed to get collections of file"
+            );
+            (AdminTaskStatus::InProgress, Some(err.to_string()), None)
+        }
+    };
+    let retry_task_id = match error {
+        Some(_) => {
+            let changes = serde_json::json!({ "file_ids_to_index": [file.id] });
+            defer_indexing(admin_task_service, changes).await
+        }
+        None => None,
+    };
+    // the retry task takes over the indexing, so the change itself went through
+    let (status, error, indexing) = match retry_task_id {
+        Some(_) => (AdminTaskStatus::Completed, None, IndexingStatus::Deferred),
+        None => (status, error, IndexingStatus::Done),
+    };
+
+    let result = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::User,
+            Some(admin.admin.id),
+            TaskKind::UploadFile,
+            serde_json::json!({
+                "file_id": file.id,
+                "file_name": file.name,
+                "size": file.size,
+                "upload_id": upload_id,
+                "meili_task_uids": Vec::from_iter(meili_task_uid),
+                "indexing": indexing,
+                "retry_task_ids": Vec::from_iter(retry_task_id),
+            }),
+            Some(status),
+            false,
+            None,
+        )
+        .await;
+
+    let admin_task_id = match (result, error) {
+        (Ok(task), Some(error)) => {
+            if let Err(err) = admin_task_service.fail_task(task.id, &error).await {
+                tracing::warn!(error = %ErrorChain(&err), "failed to mark admin task as failed");
+            }
+
+            Some(task.id)
+        }
+        (Ok(task), None) => Some(task.id),
+        (Err(err), _) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to enqueue admin task");
+            None
+        }
+    };
+
+    redact_storage([&mut file], Some(&admin));
+    attach_tag_definitions(tag_definition_service, [&mut file]).await;
+
+    Ok(Some(Json(WithAdminTask {
+        result: file,
+        admin_task_id,
+    })))
+}
+
+#[allow(clippy::too_many_arguments)]
+#[openapi(tag = "Files")]
+#[delete("/<file_id>/upload-urls/<upload_id>?<query..>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(file_id = %file_id))]
+async fn files_abort_upload(
+    request_span: RequestSpan,
+    file_service: &State<FileService>,
+    index_service: &State<IndexService>,
+    object_storage: &State<Arc<dyn ObjectStorage>>,
+    timeouts: &State<RouteTimeouts>,
+    _admin: RequireEditor,
+    file_id: Uuid,
+    upload_id: &str,
+    query: forms::AbortUploadQuery,
+) -> Result<Json<SimpleOk>, RouteError> {
+    if query.delete_file {
+        check_not_protected(file_service, file_id).await?;
+    }
+
+    let storage = match file_service.get_file_for_completion(file_id).await {
+        // only a file whose upload never completed may go along with the upload
+        Ok(Some((true, _, _, _))) if query.delete_file => {
+            return Err(Status::Conflict.into());
+        }
+        Ok(Some((_, _, _, storage))) => storage,
+        Ok(None) if query.delete_file => {
+            return Err(Status::NotFound.into());
+        }
+        // the upload of a deleted file can still be aborted, from the default storage
+        Ok(None) => None,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to get file for completion");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    let result = with_timeout(
+        Dependency::Storage,
+        timeouts.storage,
+        object_storage.abort_multipart_upload(file_id, upload_id.to_owned(), storage.as_ref()),
+    )
+    .await?;
+    let result = match result {
+        Ok(Some(())) => SimpleOk { ok: true },
+        // the upload is gone already, but the file row can still be cleaned up
+        Ok(None) if query.delete_file => SimpleOk { ok: true },
+        Ok(None) => {
+            return Err(Status::NotFound.into());
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to abort multipart upload");
+            return Err(storage_error(&err));
+        }
+    };
+
+    if let Err(err) = file_service
+        .record_upload_finished(upload_id, UploadOutcome::Aborted)
+        .await
+    {
+        tracing::warn!(error = %ErrorChain(&err), "failed to record upload abortion");
+    }
+
+    // the file stays unready, so it must not linger in the index after a metadata update
+    if let Err(err) = index_service.delete_files(&[file_id]).await {
+        tracing::warn!(error = %ErrorChain(&err), "failed to delete file `{file_id}` from the index");
+    }
+
+    if query.delete_file {
+        if let Err(err) = file_service.delete_file(file_id).await {
+            tracing::error!(error = %ErrorChain(&err), "failed to delete file");
+            return Err(Status::InternalServerError.into());
+        }
+    }
+
+    Ok(Json(result))
+}
+
+#[allow(clippy::too_many_arguments)]
+#[openapi(tag = "Files")]
+#[patch("/<file_id>", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(file_id = %file_id))]
+async fn files_update(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    file_service: &State<FileService>,
+    index_service: &State<IndexService>,
+    tag_definition_service: &State<TagDefinitionService>,
+    upload: &State<UploadConfig>,
+    admin: RequireEditor,
+    file_id: Uuid,
+    body: Json<UpdatingFile>,
+) -> Result<Json<WithAdminTask<File>>, RouteError> {
+    let body = body.into_inner();
+
+    if body.is_empty() {
+        return Err(RouteError::NoFields);
+    }
+
+    if body.protected.is_some() && admin.admin.role < AdminRole::Admin {
+        return Err(Status::Forbidden.into());
+    }
+
+    if body.size.is_some_and(|size| upload.max_file_size < size) {
+        return Err(RouteError::InvalidFields(vec![FieldError::new(
+            "size",
+            format!("must be at most {} bytes", upload.max_file_size),
+        )]));
+    }
+
+    let errors = validate_tags(
+        "tagsForCreation",
+        body.tags_for_creation.as_deref().unwrap_or_default(),
+    );
+
+    if !errors.is_empty() {
+        return Err(RouteError::InvalidFields(errors));
+    }
+
+    let mut file = match file_service.update_file(file_id, body.clone()).await {
+        Ok(Some((file, true))) => file,
+        Ok(Some((mut file, false))) => {
+            redact_storage([&mut file], Some(&admin));
+            attach_tag_definitions(tag_definition_service, [&mut file]).await;
+
+            // nothing to re-index or to record
+            return Ok(Json(WithAdminTask {
+                result: file,
+                admin_task_id: None,
+            }));
+        }
+        Ok(None) => {
+            return Err(Status::NotFound.into());
+        }
+        Err(FileServiceError::ReadyFileContentChange) => {
+            return Err(Status::Conflict.into());
+        }
+        Err(FileServiceError::TooManyTags { limit, attempted }) => {
+            return Err(RouteError::InvalidFields(vec![FieldError::new(
+                "tagsForCreation",
+                format!("would leave the file with {attempted} tags, more than {limit}"),
+            )]));
+        }
+        Err(FileServiceError::NameConflict { names }) => {
+            return Err(RouteError::NameConflict { names });
+        }
+        Err(FileServiceError::Conflict { key, value }) => {
+            return Err(RouteError::Conflict { key, value });
+        }
+        Err(FileServiceError::InvalidSupersession { reason }) => {
+            return Err(RouteError::InvalidFields(vec![FieldError::new(
+                "supersededBy",
+                reason,
+            )]));
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to update file");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    // the index flusher indexes the file once no other write to it is likely to follow, so a burst
+    // of updates reaches Meilisearch once
+    index_service.queue_file_index(file.id);
+
+    let result = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::User,
+            Some(admin.admin.id),
+            TaskKind::UpdateFile,
+            serde_json::json!({
+                "file_id": file_id,
+                "delta": body,
+                "indexing": IndexingStatus::Queued,
+            }),
+            Some(AdminTaskStatus::Completed),
+            false,
+            None,
+        )
+        .await;
+
+    let admin_task_id = match result {
+        Ok(task) => Some(task.id),
+        Err(err) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to enqueue admin task");
+            None
+        }
+    };
+
+    redact_storage([&mut file], Some(&admin));
+    attach_tag_definitions(tag_definition_service, [&mut file]).await;
+
+    Ok(Json(WithAdminTask {
+        result: file,
+        admin_task_id,
+    }))
+}
+
+#[allow(clippy::too_many_arguments)]
+#[openapi(tag = "Files")]
+#[delete("/<file_id>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(file_id = %file_id))]
+async fn files_delete(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    file_service: &State<FileService>,
+    index_service: &State<IndexService>,
+    object_storage: &State<Arc<dyn ObjectStorage>>,
+    timeouts: &State<RouteTimeouts>,
+    admin: RequireEditor,
+    file_id: Uuid,
+) -> Result<Json<SimpleOk>, RouteError> {
+    check_not_protected(file_service, file_id).await?;
+
+    let storage = match file_service.get_storages(&[file_id]).await {
+        Ok(mut storages) => storages.remove(&file_id),
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to get file storage");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    // uploads still open for the file would otherwise keep their parts in the bucket for good
+    let result = with_timeout(
+        Dependency::Storage,
+        timeouts.storage,
+        object_storage.abort_all_uploads_for_key(file_id, storage.as_ref()),
+    )
+    .await?;
+    let aborted_uploads = match result {
+        Ok(aborted_uploads) => aborted_uploads,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to abort uploads of file");
+            return Err(storage_error(&err));
+        }
+    };
+
+    for failure in &aborted_uploads.failures {
+        tracing::warn!(error = %failure.error, "failed to abort upload `{}`", failure.upload_id);
+    }
+
+    for upload_id in &aborted_uploads.upload_ids {
+        if let Err(err) = file_service
+            .record_upload_finished(upload_id, UploadOutcome::Aborted)
+            .await
+        {
+            tracing::warn!(error = %ErrorChain(&err), "failed to record upload `{upload_id}` as aborted");
+        }
+    }
+
+    let result = with_timeout(
+        Dependency::Storage,
+        timeouts.storage,
+        object_storage.delete_file(file_id, storage.as_ref()),
+    )
+    .await?;
+
+    if let Err(err) = result {
+        tracing::error!(error = %ErrorChain(&err), "failed to delete file from s3");
+        return Err(storage_error(&err));
+    }
+
+    let result = index_service.delete_files(&[file_id]).await;
+    let deletion = match result {
+        Ok(meili_task_uids) => Ok(meili_task_uids),
+        Err(IndexServiceError::Timeout { .. }) => {
+            Err(RouteError::Timeout(Dependency::SearchEngine))
+        }
+        Err(err) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to delete file from index");
+            Err(RouteError::from(Status::InternalServerError))
+        }
+    };
+    let (meili_task_uids, retry_task_id) = match deletion {
+        Ok(meili_task_uids) => (meili_task_uids, None),
+        Err(route_error) => {
+            let changes = serde_json::json!({ "file_ids_to_delete": [file_id] });
+
+            match defer_indexing(admin_task_service, changes).await {
+                Some(retry_task_id) => (Vec::new(), Some(retry_task_id)),
+                None => return Err(route_error),
+            }
+        }
+    };
+    let indexing = match retry_task_id {
+        Some(_) => IndexingStatus::Deferred,
+        None => IndexingStatus::Done,
+    };
+
+    let result = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::User,
+            Some(admin.admin.id),
+            TaskKind::DeleteFile,
+            serde_json::json!({
+                "file_id": file_id,
+                "meili_task_uids": meili_task_uids,
+                "indexing": indexing,
+                "retry_task_ids": Vec::from_iter(retry_task_id),
+                "aborted_upload_ids": aborted_uploads.upload_ids,
+                "upload_abort_failures": aborted_uploads.failures,
+            }),
+            Some(AdminTaskStatus::Completed),
+            false,
+            None,
+        )
+        .await;
+
+    if let Err(err) = result {
+        tracing::warn!(error = %ErrorChain(&err), "failed to enqueue admin task");
+    }
+
+    // the earlier versions of the file become current again once it is gone
+    let superseded_file_ids = match file_service.get_superseded_file_ids(&[file_id]).await {
+        Ok(file_ids) => file_ids,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to get superseded files");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    if let Err(err) = file_service.delete_file(file_id).await {
+        tracing::error!(error = %ErrorChain(&err), "failed to delete file");
+        return Err(Status::InternalServerError.into());
+    }
+
+    for superseded_file_id in superseded_file_ids {
+        index_service.queue_file_index(superseded_file_id);
+    }
+
+    Ok(Json(SimpleOk { ok: true }))
+}
+
+#[openapi(tag = "Files")]
+#[post("/bulk-delete", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn files_bulk_delete(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    index_service: &State<IndexService>,
+    admin: RequireEditor,
+    body: BatchJson<BulkDeletingFiles>,
+) -> Result<Json<AdminTask>, RouteError> {
+    let body = body.into_inner();
+    // more files may match a query than are deleted at once
+    let (mut file_ids, matches_truncated) = match (body.file_ids, body.query) {
+        (Some(file_ids), None) => {
+            if BULK_DELETE_MAX_FILES < file_ids.len() {
+                return Err(RouteError::InvalidFields(vec![FieldError::new(
+                    "fileIds",
+                    format!("must contain at most {BULK_DELETE_MAX_FILES} files"),
+                )]));
+            }
+
+            (file_ids, false)
+        }
+        (None, Some(query)) => {
+            match index_service
+                .search_file_ids(&query, BULK_DELETE_MAX_FILES)
+                .await
+            {
+                Ok(matches) => (matches.file_ids, matches.truncated),
+                Err(IndexServiceError::Timeout { .. }) => {
+                    return Err(RouteError::Timeout(Dependency::SearchEngine));
+                }
+                Err(IndexServiceError::CircuitOpen { retry_after }) => {
+                    return Err(RouteError::SearchEngineUnavailable { retry_after });
+                }
+                Err(err) => {
+                    tracing::error!(error = %ErrorChain(&err), "failed to search files for bulk deletion");
+                    return Err(Status::InternalServerError.into());
+                }
+            }
+        }
+        _ => {
+            return Err(RouteError::InvalidFields(vec![
+                FieldError::new("fileIds", "must be given unless query is"),
+                FieldError::new("query", "must be given unless fileIds is"),
+            ]));
+        }
+    };
+
+    file_ids.sort_unstable();
+    file_ids.dedup();
+
+    let task = admin_task_service
+        .enqueue_task_with_queued_files(
+            AdminTaskInitiator::User,
+            Some(admin.admin.id),
+            TaskKind::BulkDeleteFiles,
+            serde_json::json!({
+                "file_count": file_ids.len(),
+                "processed_count": 0,
+                "deleted_count": 0,
+                "matches_truncated": matches_truncated,
+            }),
+            &file_ids,
+        )
+        .await;
+    let task = match task {
+        Ok(task) => task,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to enqueue admin task for bulk deletion");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    Ok(Json(task))
+}
+
+/// Adds and removes tags on many files in one transaction, then re-indexes the ready files that
+/// changed in batches. The change is recorded as a single `bulk-tag-files` admin task.
+#[allow(clippy::too_many_arguments)]
+#[openapi(tag = "Files")]
+#[post("/bulk-tags", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn files_bulk_tag(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    file_service: &State<FileService>,
+    index_service: &State<IndexService>,
+    admin: RequireEditor,
+    body: Json<BulkTaggingFiles>,
+) -> Result<Json<WithAdminTask<BulkTaggedFiles>>, RouteError> {
+    let body = body.into_inner();
+
+    if body.file_ids.is_empty() || BULK_TAG_MAX_FILES < body.file_ids.len() {
+        return Err(RouteError::InvalidFields(vec![FieldError::new(
+            "fileIds",
+            format!("must contain between 1 and {BULK_TAG_MAX_FILES} file ids"),
+        )]));
+    }
+
+    if body.add.is_empty() && body.remove.is_empty() {
+        return Err(RouteError::NoFields);
+    }
+
+    let errors = validate_tags("add", &body.add);
+
+    if !errors.is_empty() {
+        return Err(RouteError::InvalidFields(errors));
+    }
+
+    if let Some(tag) = body.add.iter().find(|tag| body.remove.contains(tag)) {
+        return Err(RouteError::InvalidFields(vec![FieldError::new(
+            "remove",
+            format!("must not contain `{tag}`, which is added"),
+        )]));
+    }
+
+    let mut seen = HashSet::new();
+    let file_ids = Vec::from_iter(body.file_ids.into_iter().filter(|id| seen.insert(*id)));
+    let mut add = body.add;
+    add.sort_unstable();
+    add.dedup();
+    let mut remove = body.remove;
+    remove.sort_unstable();
+    remove.dedup();
+
+    let results = match file_service
+        .update_tags_of_files(&file_ids, &add, &remove)
+        .await
+    {
+        Ok(results) => results,
+        Err(FileServiceError::TooManyTags { limit, attempted }) => {
+            return Err(RouteError::InvalidFields(vec![FieldError::new(
+                "add",
+                format!("would leave a file with {attempted} tags, more than {limit}"),
+            )]));
+        }
+        Err(FileServiceError::NameConflict { names }) => {
+            return Err(RouteError::NameConflict { names });
+        }
+        Err(FileServiceError::Conflict { key, value }) => {
+            return Err(RouteError::Conflict { key, value });
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to update tags of files");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    let updated_file_ids = Vec::from_iter(
+        results
+            .iter()
+            .filter(|result| result.outcome == BulkTagOutcome::Updated)
+            .map(|result| result.file_id),
+    );
+    let not_found_count = results
+        .iter()
+        .filter(|result| result.outcome == BulkTagOutcome::NotFound)
+        .count();
+    let added_count: usize = results.iter().map(|result| result.added.len()).sum();
+    let removed_count: usize = results.iter().map(|result| result.removed.len()).sum();
+    let result = BulkTaggedFiles {
+        files: results,
+        updated_count: updated_file_ids.len(),
+        not_found_count,
+    };
+
+    // nothing to re-index or to record
+    if updated_file_ids.is_empty() {
+        return Ok(Json(WithAdminTask {
+            result,
+            admin_task_id: None,
+        }));
+    }
+
+    let mut meili_task_uids = Vec::new();
+    let mut retry_task_ids = Vec::new();
+
+    for file_ids in updated_file_ids.chunks(BULK_TAG_INDEX_BATCH_SIZE) {
+        match index_files(file_service, index_service, file_ids).await {
+            Ok(Some(meili_task_uid)) => meili_task_uids.push(meili_task_uid),
+            Ok(None) => {}
+            Err(error) => {
+                tracing::warn!(error = %error, "failed to re-index files after a bulk tag change");
+                let changes = serde_json::json!({ "file_ids_to_index": file_ids });
+                retry_task_ids.extend(defer_indexing(admin_task_service, changes).await);
+            }
+        }
+    }
+
+    // the retry tasks take over the indexing, so the change itself went through
+    let indexing = match retry_task_ids.is_empty() {
+        true => IndexingStatus::Done,
+        false => IndexingStatus::Deferred,
+    };
+
+    let task = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::User,
+            Some(admin.admin.id),
+            TaskKind::BulkTagFiles,
+            serde_json::json!({
+                "file_ids": updated_file_ids,
+                "add": add,
+                "remove": remove,
+                "file_count": file_ids.len(),
+                "updated_count": result.updated_count,
+                "not_found_count": not_found_count,
+                "added_count": added_count,
+                "removed_count": removed_count,
+                "meili_task_uids": meili_task_uids,
+                "indexing": indexing,
+                "retry_task_ids": retry_task_ids,
+            }),
+            Some(AdminTaskStatus::Completed),
+            false,
+            None,
+        )
+        .await;
+
+    let admin_task_id = match task {
+        Ok(task) => Some(task.id),
+        Err(err) => {
+            tracing::warn!(error = %ErrorChain(&err), "failed to enqueue admin task");
+            None
+        }
+    };
+
+    Ok(Json(WithAdminTask {
+        result,
+        admin_task_id,
+    }))
+}
+
+/// Indexes the ready files among `file_ids` along with their collections, returning the uid of
+/// the Meilisearch task, or `None` if none of them is ready.
+async fn index_files(
+    file_service: &FileService,
+    index_service: &IndexService,
+    file_ids: &[Uuid],
+) -> Result<Option<u32>, String> {
+    let files = file_service
+        .get_files(file_ids)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    if files.is_empty() {
+        return Ok(None);
+    }
+
+    let collections = file_service
+        .get_file_collections(file_ids)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    index_service
+        .index_files(&files, &collections)
+        .await
+        .map(Some)
+        .map_err(|err| err.to_string())
+}
+
+mod forms {
+    use crate::{
+        forms::{date_time_utc::DateTimeUtcFormField, part_range::PartRangeFormField},
+        interfaces::files::{FileExportFormat, FileListSort},
+    };
+    use rocket::{
+        form::{Error, Result},
+        FromForm,
+    };
+    use schemars::JsonSchema;
+    use uuid::Uuid;
+
+    #[derive(FromForm, JsonSchema, Debug)]
+    pub struct GetQuery {
+        /// Whether to also report how the search index holds the file. Requires an admin
+        /// session.
+        #[schemars(rename = "include-index-status", default)]
+        #[field(name = uncased("include-index-status"), default = false)]
+        pub include_index_status: bool,
+    }
+
+    #[derive(FromForm, JsonSchema, Debug)]
+    pub struct CreateQuery {
+        /// Whether to also start the upload of the file, answering with its upload URLs.
+        #[schemars(rename = "include-upload-urls", default)]
+        #[field(name = uncased("include-upload-urls"), default = false)]
+        pub include_upload_urls: bool,
+    }
+
+    #[derive(FromForm, JsonSchema, Debug)]
+    pub struct StartUploadQuery {
+        // parsed by the route, as a malformed range would be taken for no range at all here
+        #[schemars(rename = "part-range", with = "Option<PartRangeFormField>")]
+        #[field(name = uncased("part-range"))]
+        pub part_range: Option<String>,
+        /// Whether to abort the active upload of the file and start a new one, instead of handing
+        /// out the active upload again.
+        #[schemars(default)]
+        #[field(default = false)]
+        pub replace: bool,
+    }
+
+    #[derive(FromForm, JsonSchema, Debug)]
+    pub struct UploadUrlsQuery {
+        // parsed by the route, as a malformed range would be taken for no range at all here
+        #[schemars(rename = "part-range", with = "Option<PartRangeFormField>")]
+        #[field(name = uncased("part-range"))]
+        pub part_range: Option<String>,
+    }
+
+    #[derive(FromForm, JsonSchema, Debug)]
+    pub struct CostReportQuery {
+        /// Whether to break the cost down by the collections holding the most bytes.
+        #[schemars(rename = "by-collection", default)]
+        #[field(name = uncased("by-collection"), default = false)]
+        pub by_collection: bool,
+    }
+
+    #[derive(FromForm, JsonSchema, Debug)]
+    pub struct AbortUploadQuery {
+        #[schemars(rename = "delete-file", default)]
+        #[field(name = uncased("delete-file"), default = false)]
+        pub delete_file: bool,
+    }
+
+    #[derive(FromForm, JsonSchema, Debug)]
+    pub struct DownloadUrlQuery {
+        /// Whether to download the latest ready version of the file instead of the file itself.
+        #[schemars(default)]
+        #[field(name = uncased("latest"), default = false)]
+        pub latest: bool,
+    }
+
+    #[derive(FromForm, JsonSchema, Debug)]
+    pub struct ListQuery {
+        #[schemars(default = "default_limit", range(min = 1, max = 1000))]
+        #[field(name = uncased("limit"), default = default_limit(), validate = range(1..=1000))]
+        pub limit: usize,
+        #[schemars(default = "default_sort")]
+        #[field(name = uncased("sort"), default = default_sort())]
+        pub sort: FileListSort,
+        #[schemars(rename = "last-file-id")]
+        #[field(name = uncased("last-file-id"), validate = is_last_file_id_valid(&self.sort, &self.last_file_uploaded_at, &self.last_file_name))]
+        pub last_file_id: Option<Uuid>,
+        /// The cursor of the `uploaded-at` sort.
+        #[schemars(rename = "last-file-uploaded-at")]
+        #[field(name = uncased("last-file-uploaded-at"), validate = is_last_file_uploaded_at_valid(&self.sort, &self.last_file_id))]
+        pub last_file_uploaded_at: Option<DateTimeUtcFormField>,
+        /// The cursor of the `name-natural` sort.
+        #[schemars(rename = "last-file-name")]
+        #[field(name = uncased("last-file-name"), validate = is_last_file_name_valid(&self.sort, &self.last_file_id))]
+        pub last_file_name: Option<String>,
+        #[schemars(rename = "verify-cursor", default = "default_verify_cursor")]
+        #[field(name = uncased("verify-cursor"), default = default_verify_cursor())]
+        pub verify_cursor: bool,
+        #[schemars(rename = "name-prefix")]
+        #[field(name = uncased("name-prefix"))]
+        pub name_prefix: Option<String>,
+    }
+
+    fn default_limit() -> usize {
+        25
+    }
+
+    fn default_verify_cursor() -> bool {
+        true
+    }
+
+    fn default_sort() -> FileListSort {
+        FileListSort::UploadedAt
+    }
+
+    fn is_last_file_id_valid<'v>(
+        this: &Option<Uuid>,
+        sort: &FileListSort,
+        last_file_uploaded_at: &Option<DateTimeUtcFormField>,
+        last_file_name: &Option<String>,
+    ) -> Result<'v, ()> {
+        if this.is_none() {
+            return Ok(());
+        }
+
+        match sort {
+            FileListSort::UploadedAt if last_file_uploaded_at.is_none() => {
+                Err(Error::validation(
+                    "`last-file-uploaded-at` must be provided if `last-file-id` is provided",
+                ))?;
+            }
+            FileListSort::NameNatural if last_file_name.is_none() => {
+                Err(Error::validation(
+                    "`last-file-name` must be provided if `last-file-id` is provided",
+                ))?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn is_last_file_uploaded_at_valid<'v>(
+        this: &Option<DateTimeUtcFormField>,
+        sort: &FileListSort,
+        last_file_id: &Option<Uuid>,
+    ) -> Result<'v, ()> {
+        if this.is_some() && *sort != FileListSort::UploadedAt {
+            Err(Error::validation(
+                "`last-file-uploaded-at` is only allowed with `sort=uploaded-at`",
+            ))?;
+        }
+
+        if this.is_some() && last_file_id.is_none() {
+            Err(Error::validation(
+                "`last-file-id` must be provided if `last-file-uploaded-at` is provided",
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    fn is_last_file_name_valid<'v>(
+        this: &Option<String>,
+        sort: &FileListSort,
+        last_file_id: &Option<Uuid>,
+    ) -> Result<'v, ()> {
+        if this.is_some() && *sort != FileListSort::NameNatural {
+            Err(Error::validation(
+                "`last-file-name` is only allowed with `sort=name-natural`",
+            ))?;
+        }
+
+        if this.is_some() && last_file_id.is_none() {
+            Err(Error::validation(
+                "`last-file-id` must be provided if `last-file-name` is provided",
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    #[derive(FromForm, JsonSchema, Debug)]
+    pub struct ExportQuery {
+        #[field(name = uncased("format"))]
+        pub format: FileExportFormat,
     }
 }
@@ -0,0 +1,21 @@
+use crate::{
+    guards::request_span::RequestSpan, interfaces::Health, services::index_service::IndexService,
+};
+use rocket::{get, serde::json::Json, Route, State};
+use rocket_okapi::{
+    okapi::openapi3::OpenApi, openapi, openapi_get_routes_spec, settings::OpenApiSettings,
+};
+
+pub fn routes(settings: &OpenApiSettings) -> (Vec<Route>, OpenApi) {
+    openapi_get_routes_spec![settings: health_get]
+}
+
+#[openapi(tag = "Health")]
+#[get("/")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+fn health_get(request_span: RequestSpan, index_service: &State<IndexService>) -> Json<Health> {
+    Json(Health {
+        search_engine: index_service.circuit_status(),
+        index_write_queue: index_service.write_queue_status(),
+    })
+}
@@ -0,0 +1,151 @@
+use crate::{
+    guards::{authenticated_admin::RequireAdmin, request_span::RequestSpan},
+    interfaces::{
+        admins::{CreatingScheduledTask, ScheduledTask, UpdatingScheduledTask},
+        SimpleOk,
+    },
+    services::scheduled_task_service::{ScheduledTaskService, ScheduledTaskServiceError},
+    telemetry::ErrorChain,
+};
+use rocket::{delete, get, http::Status, patch, post, serde::json::Json, Route, State};
+use rocket_okapi::{
+    okapi::openapi3::OpenApi, openapi, openapi_get_routes_spec, settings::OpenApiSettings,
+};
+use uuid::Uuid;
+
+pub fn routes(settings: &OpenApiSettings) -> (Vec<Route>, OpenApi) {
+    openapi_get_routes_spec![settings:
+        scheduled_tasks_list,
+        scheduled_tasks_get,
+        scheduled_tasks_create,
+        scheduled_tasks_update,
+        scheduled_tasks_delete,
+    ]
+}
+
+#[openapi(tag = "Admin Tasks")]
+#[get("/")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn scheduled_tasks_list(
+    request_span: RequestSpan,
+    scheduled_task_service: &State<ScheduledTaskService>,
+    _admin: RequireAdmin,
+) -> Result<Json<Vec<ScheduledTask>>, Status> {
+    let scheduled_tasks = match scheduled_task_service.list_scheduled_tasks().await {
+        Ok(scheduled_tasks) => scheduled_tasks,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to list scheduled tasks");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    Ok(Json(scheduled_tasks))
+}
+
+#[openapi(tag = "Admin Tasks")]
+#[get("/<scheduled_task_id>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(scheduled_task_id = %scheduled_task_id))]
+async fn scheduled_tasks_get(
+    request_span: RequestSpan,
+    scheduled_task_service: &State<ScheduledTaskService>,
+    _admin: RequireAdmin,
+    scheduled_task_id: Uuid,
+) -> Result<Json<ScheduledTask>, Status> {
+    let scheduled_task = match scheduled_task_service
+        .get_scheduled_task(scheduled_task_id)
+        .await
+    {
+        Ok(Some(scheduled_task)) => scheduled_task,
+        Ok(None) => {
+            return Err(Status::NotFound);
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to get scheduled task");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    Ok(Json(scheduled_task))
+}
+
+#[openapi(tag = "Admin Tasks")]
+#[post("/", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn scheduled_tasks_create(
+    request_span: RequestSpan,
+    scheduled_task_service: &State<ScheduledTaskService>,
+    _admin: RequireAdmin,
+    body: Json<CreatingScheduledTask>,
+) -> Result<Json<ScheduledTask>, Status> {
+    let scheduled_task = match scheduled_task_service
+        .create_scheduled_task(body.into_inner())
+        .await
+    {
+        Ok(scheduled_task) => scheduled_task,
+        Err(ScheduledTaskServiceError::InvalidCronExpression(_)) => {
+            return Err(Status::UnprocessableEntity);
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to create scheduled task");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    Ok(Json(scheduled_task))
+}
+
+#[openapi(tag = "Admin Tasks")]
+#[patch("/<scheduled_task_id>", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(scheduled_task_id = %scheduled_task_id))]
+async fn scheduled_tasks_update(
+    request_span: RequestSpan,
+    scheduled_task_service: &State<ScheduledTaskService>,
+    _admin: RequireAdmin,
+    scheduled_task_id: Uuid,
+    body: Json<UpdatingScheduledTask>,
+) -> Result<Json<ScheduledTask>, Status> {
+    let scheduled_task = match scheduled_task_service
+        .update_scheduled_task(scheduled_task_id, body.into_inner())
+        .await
+    {
+        Ok(Some(scheduled_task)) => scheduled_task,
+        Ok(None) => {
+            return Err(Status::NotFound);
+        }
+        Err(ScheduledTaskServiceError::InvalidCronExpression(_)) => {
+            return Err(Status::UnprocessableEntity);
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to update scheduled task");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    Ok(Json(scheduled_task))
+}
+
+#[openapi(tag = "Admin Tasks")]
+#[delete("/<scheduled_task_id>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(scheduled_task_id = %scheduled_task_id))]
+async fn scheduled_tasks_delete(
+    request_span: RequestSpan,
+    scheduled_task_service: &State<ScheduledTaskService>,
+    _admin: RequireAdmin,
+    scheduled_task_id: Uuid,
+) -> Result<Json<SimpleOk>, Status> {
+    match scheduled_task_service
+        .delete_scheduled_task(scheduled_task_id)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err(Status::NotFound);
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to delete scheduled task");
+            return Err(Status::InternalServerError);
+        }
+    }
+
+    Ok(Json(SimpleOk { ok: true }))
+}
@@ -1,44 +1,162 @@
 use crate::{
+    guards::{authenticated_admin::AuthenticatedAdmin, request_span::RequestSpan},
     interfaces::{
-        collections::{Collection, CollectionSearchQuery},
-        files::{File, FileSearchQuery},
+        collections::{CollectionSearchHit, CollectionSearchQuery},
+        files::{FileSearchFacets, FileSearchQuery, FileSearchResult},
     },
-    services::index_service::IndexService,
+    routes::{attach_tag_definitions, defer_indexing, Dependency, RouteError},
+    services::{
+        admin_task_service::AdminTaskService,
+        index_service::{IndexService, IndexServiceError},
+        tag_definition_service::TagDefinitionService,
+    },
+    telemetry::ErrorChain,
+};
+use rocket::{http::Status, post, serde::json::Json, Route, State};
+use rocket_okapi::{
+    okapi::openapi3::OpenApi, openapi, openapi_get_routes_spec, settings::OpenApiSettings,
 };
-use rocket::{http::Status, post, routes, serde::json::Json, Route, State};
 
-pub fn routes() -> Vec<Route> {
-    routes![searches_files, searches_collections]
+pub fn routes(settings: &OpenApiSettings) -> (Vec<Route>, OpenApi) {
+    openapi_get_routes_spec![settings:
+        searches_files,
+        searches_files_facets,
+        searches_collections,
+    ]
 }
 
+#[openapi(tag = "Searches")]
 #[post("/files", data = "<query>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
 async fn searches_files(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
     index_service: &State<IndexService>,
+    tag_definition_service: &State<TagDefinitionService>,
+    admin: Option<AuthenticatedAdmin>,
     query: Json<FileSearchQuery>,
-) -> Result<Json<Vec<File>>, Status> {
-    let files = match index_service.search_files(&query.into_inner()).await {
+) -> Result<Json<FileSearchResult>, RouteError> {
+    let errors = query.validate();
+
+    if !errors.is_empty() {
+        return Err(RouteError::InvalidFields(errors));
+    }
+
+    if query.include_unready && admin.is_none() {
+        return Err(Status::Unauthorized.into());
+    }
+
+    let files = index_service.search_files(&query).await;
+    let mut files = match files {
         Ok(files) => files,
+        Err(IndexServiceError::Timeout { .. }) => {
+            return Err(RouteError::Timeout(Dependency::SearchEngine));
+        }
+        Err(IndexServiceError::CircuitOpen { retry_after }) => {
+            return Err(RouteError::SearchEngineUnavailable { retry_after });
+        }
         Err(err) => {
-            log::error!("failed to search files: {err:#?}");
-            return Err(Status::InternalServerError);
+            tracing::error!(error = %ErrorChain(&err), "failed to search files");
+            return Err(Status::InternalServerError.into());
         }
     };
 
-    Ok(Json(files))
+    let file_ids_to_repair = index_service.file_ids_to_repair(&files);
+
+    if !file_ids_to_repair.is_empty() {
+        // re-indexing rewrites the documents in the current shape; the documents of files deleted
+        // meanwhile are left to the index sweep or a re-index
+        defer_indexing(
+            admin_task_service,
+            serde_json::json!({ "file_ids_to_index": file_ids_to_repair }),
+        )
+        .await;
+    }
+
+    attach_tag_definitions(
+        tag_definition_service,
+        files.hits.iter_mut().map(|hit| &mut hit.file),
+    )
+    .await;
+
+    Ok(Json(FileSearchResult {
+        hits: files.hits,
+        skipped_hits: files.skipped_hits,
+    }))
 }
 
+/// Counts the files matching the query by size bucket.
+#[openapi(tag = "Searches")]
+#[post("/files/facets", data = "<query>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn searches_files_facets(
+    request_span: RequestSpan,
+    index_service: &State<IndexService>,
+    admin: Option<AuthenticatedAdmin>,
+    query: Json<FileSearchQuery>,
+) -> Result<Json<FileSearchFacets>, RouteError> {
+    let errors = query.validate();
+
+    if !errors.is_empty() {
+        return Err(RouteError::InvalidFields(errors));
+    }
+
+    if query.include_unready && admin.is_none() {
+        return Err(Status::Unauthorized.into());
+    }
+
+    let facets = index_service.facet_files(&query).await;
+
+    match facets {
+        Ok(facets) => Ok(Json(facets)),
+        Err(IndexServiceError::Timeout { .. }) => {
+            Err(RouteError::Timeout(Dependency::SearchEngine))
+        }
+        Err(IndexServiceError::CircuitOpen { retry_after }) => {
+            Err(RouteError::SearchEngineUnavailable { retry_after })
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to facet files");
+            Err(Status::InternalServerError.into())
+        }
+    }
+}
+
+#[openapi(tag = "Searches")]
 #[post("/collections", data = "<query>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
 async fn searches_collections(
+    request_span: RequestSpan,
     index_service: &State<IndexService>,
+    tag_definition_service: &State<TagDefinitionService>,
     query: Json<CollectionSearchQuery>,
-) -> Result<Json<Vec<Collection>>, Status> {
-    let collections = match index_service.search_collections(&query.into_inner()).await {
+) -> Result<Json<Vec<CollectionSearchHit>>, RouteError> {
+    let errors = query.validate();
+
+    if !errors.is_empty() {
+        return Err(RouteError::InvalidFields(errors));
+    }
+
+    let collections = index_service.search_collections(&query).await;
+    let mut collections = match collections {
         Ok(collections) => collections,
+        Err(IndexServiceError::Timeout { .. }) => {
+            return Err(RouteError::Timeout(Dependency::SearchEngine));
+        }
+        Err(IndexServiceError::CircuitOpen { retry_after }) => {
+            return Err(RouteError::SearchEngineUnavailable { retry_after });
+        }
         Err(err) => {
-            log::error!("failed to search collections: {err:#?}");
-            return Err(Status::InternalServerError);
+            tracing::error!(error = %ErrorChain(&err), "failed to search collections");
+            return Err(Status::InternalServerError.into());
         }
     };
 
+    attach_tag_definitions(
+        tag_definition_service,
+        collections.iter_mut().map(|hit| &mut hit.collection),
+    )
+    .await;
+
     Ok(Json(collections))
 }
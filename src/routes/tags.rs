@@ -0,0 +1,274 @@
+use crate::{
+    guards::{authenticated_admin::RequireEditor, request_span::RequestSpan},
+    interfaces::{
+        admins::{AdminTaskInitiator, TaskKind},
+        tags::{
+            CreatingTagDefinition, RenamingTag, TagDefinition, TagRenamePreview,
+            UpdatingTagDefinition,
+        },
+        SimpleOk, WithAdminTask,
+    },
+    routes::RouteError,
+    services::{
+        admin_task_service::AdminTaskService,
+        collection_service::CollectionService,
+        file_service::FileService,
+        tag_definition_service::{TagDefinitionService, TagDefinitionServiceError},
+    },
+    telemetry::ErrorChain,
+};
+use rocket::{delete, get, http::Status, patch, post, serde::json::Json, Route, State};
+use rocket_okapi::{
+    okapi::openapi3::OpenApi, openapi, openapi_get_routes_spec, settings::OpenApiSettings,
+};
+
+pub fn routes(settings: &OpenApiSettings) -> (Vec<Route>, OpenApi) {
+    openapi_get_routes_spec![settings:
+        tag_definitions_list,
+        tag_definitions_get,
+        tag_definitions_create,
+        tag_definitions_update,
+        tag_definitions_delete,
+        tags_rename,
+    ]
+}
+
+/// Most names of affected files, and of affected collections, a rename preview lists.
+const RENAME_SAMPLE_SIZE: usize = 10;
+
+#[openapi(tag = "Tags")]
+#[get("/definitions")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn tag_definitions_list(
+    request_span: RequestSpan,
+    tag_definition_service: &State<TagDefinitionService>,
+) -> Result<Json<Vec<TagDefinition>>, Status> {
+    let tag_definitions = match tag_definition_service.list_tag_definitions().await {
+        Ok(tag_definitions) => tag_definitions,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to list tag definitions");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    Ok(Json(tag_definitions))
+}
+
+#[openapi(tag = "Tags")]
+#[get("/definitions/<name>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(name = %name))]
+async fn tag_definitions_get(
+    request_span: RequestSpan,
+    tag_definition_service: &State<TagDefinitionService>,
+    name: &str,
+) -> Result<Json<TagDefinition>, Status> {
+    let tag_definition = match tag_definition_service.get_tag_definition(name).await {
+        Ok(Some(tag_definition)) => tag_definition,
+        Ok(None) => {
+            return Err(Status::NotFound);
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to get tag definition");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    Ok(Json(tag_definition))
+}
+
+#[openapi(tag = "Tags")]
+#[post("/definitions", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all)]
+async fn tag_definitions_create(
+    request_span: RequestSpan,
+    tag_definition_service: &State<TagDefinitionService>,
+    _admin: RequireEditor,
+    body: Json<CreatingTagDefinition>,
+) -> Result<Json<TagDefinition>, RouteError> {
+    let body = body.into_inner();
+    let errors = body.validate();
+
+    if !errors.is_empty() {
+        return Err(RouteError::InvalidFields(errors));
+    }
+
+    let tag_definition = match tag_definition_service.create_tag_definition(body).await {
+        Ok(tag_definition) => tag_definition,
+        Err(TagDefinitionServiceError::AlreadyDefined(_)) => {
+            return Err(Status::Conflict.into());
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to create tag definition");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    Ok(Json(tag_definition))
+}
+
+#[openapi(tag = "Tags")]
+#[patch("/definitions/<name>", data = "<body>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(name = %name))]
+async fn tag_definitions_update(
+    request_span: RequestSpan,
+    tag_definition_service: &State<TagDefinitionService>,
+    _admin: RequireEditor,
+    name: &str,
+    body: Json<UpdatingTagDefinition>,
+) -> Result<Json<TagDefinition>, RouteError> {
+    let body = body.into_inner();
+
+    if body.is_empty() {
+        return Err(RouteError::NoFields);
+    }
+
+    let errors = body.validate();
+
+    if !errors.is_empty() {
+        return Err(RouteError::InvalidFields(errors));
+    }
+
+    let tag_definition = match tag_definition_service
+        .update_tag_definition(name, body)
+        .await
+    {
+        Ok(Some(tag_definition)) => tag_definition,
+        Ok(None) => {
+            return Err(Status::NotFound.into());
+        }
+        Err(TagDefinitionServiceError::AlreadyDefined(_)) => {
+            return Err(Status::Conflict.into());
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to update tag definition");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    Ok(Json(tag_definition))
+}
+
+#[openapi(tag = "Tags")]
+#[delete("/definitions/<name>")]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(name = %name))]
+async fn tag_definitions_delete(
+    request_span: RequestSpan,
+    tag_definition_service: &State<TagDefinitionService>,
+    _admin: RequireEditor,
+    name: &str,
+) -> Result<Json<SimpleOk>, Status> {
+    match tag_definition_service.delete_tag_definition(name).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err(Status::NotFound);
+        }
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to delete tag definition");
+            return Err(Status::InternalServerError);
+        }
+    }
+
+    Ok(Json(SimpleOk { ok: true }))
+}
+
+#[openapi(tag = "Tags")]
+#[post("/<tag>/rename?<query..>", data = "<body>")]
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(parent = &request_span.span, skip_all, fields(tag = %tag))]
+async fn tags_rename(
+    request_span: RequestSpan,
+    admin_task_service: &State<AdminTaskService>,
+    collection_service: &State<CollectionService>,
+    file_service: &State<FileService>,
+    admin: RequireEditor,
+    tag: &str,
+    query: forms::RenameQuery,
+    body: Json<RenamingTag>,
+) -> Result<Json<WithAdminTask<TagRenamePreview>>, RouteError> {
+    let errors = body.validate(tag);
+
+    if !errors.is_empty() {
+        return Err(RouteError::InvalidFields(errors));
+    }
+
+    let new_name = body.new_name.trim();
+    let (files, files_target_exists) = match file_service
+        .preview_tag_rename(tag, new_name, RENAME_SAMPLE_SIZE)
+        .await
+    {
+        Ok(preview) => preview,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to preview tag rename on files");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+    let (collections, collections_target_exists) = match collection_service
+        .preview_tag_rename(tag, new_name, RENAME_SAMPLE_SIZE)
+        .await
+    {
+        Ok(preview) => preview,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to preview tag rename on collections");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+    let preview = TagRenamePreview {
+        tag: tag.to_owned(),
+        new_name: new_name.to_owned(),
+        target_exists: files_target_exists || collections_target_exists,
+        files,
+        collections,
+    };
+
+    if query.dry_run {
+        return Ok(Json(WithAdminTask {
+            result: preview,
+            admin_task_id: None,
+        }));
+    }
+
+    let task = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::User,
+            Some(admin.admin.id),
+            TaskKind::RenameTag,
+            serde_json::json!({
+                "tag": preview.tag,
+                "new_name": preview.new_name,
+                "target_exists": preview.target_exists,
+                "file_count": preview.files.renamed_count,
+                "merged_file_count": preview.files.merged_count,
+                "collection_count": preview.collections.renamed_count,
+                "merged_collection_count": preview.collections.merged_count,
+            }),
+            None,
+            false,
+            None,
+        )
+        .await;
+    let task = match task {
+        Ok(task) => task,
+        Err(err) => {
+            tracing::error!(error = %ErrorChain(&err), "failed to enqueue admin task for tag rename");
+            return Err(Status::InternalServerError.into());
+        }
+    };
+
+    Ok(Json(WithAdminTask {
+        result: preview,
+        admin_task_id: Some(task.id),
+    }))
+}
+
+mod forms {
+    use rocket::FromForm;
+    use schemars::JsonSchema;
+
+    #[derive(FromForm, JsonSchema, Debug)]
+    pub struct RenameQuery {
+        /// Only counts what the rename would change, without renaming.
+        #[schemars(rename = "dry-run", default)]
+        #[field(name = uncased("dry-run"), default = false)]
+        pub dry_run: bool,
+    }
+}
@@ -1,7 +1,129 @@
 pub mod admin_service;
 pub mod admin_task_service;
+pub mod bundle_service;
 pub mod collection_service;
+pub mod export_service;
 pub mod file_service;
 pub mod index_service;
+pub mod mailer_service;
+pub mod migration_service;
 pub mod s3_service;
+pub mod scheduled_task_service;
+pub mod tag_definition_service;
 pub mod token_service;
+
+use crate::{
+    config::Config,
+    db::{
+        database::Database,
+        repositories::{
+            admin::AdminRepository, collection::CollectionRepository, file::FileRepository,
+        },
+    },
+    interfaces::files::FileStorage,
+};
+use admin_service::AdminService;
+use admin_task_service::AdminTaskService;
+use bundle_service::BundleService;
+use collection_service::CollectionService;
+use export_service::ExportService;
+use file_service::FileService;
+use index_service::{IndexService, IndexTimeouts, MeilisearchIndex, SearchIndex};
+use migration_service::MigrationService;
+use s3_service::{ObjectStorage, S3Service};
+use scheduled_task_service::ScheduledTaskService;
+use std::sync::Arc;
+use tag_definition_service::TagDefinitionService;
+use token_service::TokenService;
+
+/// The services the routes and background fairings share. Built by [`Services::init`] in
+/// production; any of them may be replaced before handing them to [`crate::build_rocket`].
+/// The object storage is shared by several services, and the search index is connected to on
+/// startup, so they are replaced with [`Services::init_with`] instead.
+pub struct Services {
+    pub admin_service: AdminService,
+    pub admin_task_service: AdminTaskService,
+    pub bundle_service: BundleService,
+    pub collection_service: CollectionService,
+    pub export_service: ExportService,
+    pub file_service: FileService,
+    pub index_service: IndexService,
+    pub migration_service: MigrationService,
+    pub object_storage: Arc<dyn ObjectStorage>,
+    pub scheduled_task_service: ScheduledTaskService,
+    pub tag_definition_service: TagDefinitionService,
+    pub token_service: TokenService,
+}
+
+impl Services {
+    /// Connects to the database, Meilisearch and S3 of the configuration, running the pending
+    /// migrations.
+    pub async fn init(config: &Config) -> Self {
+        let object_storage = S3Service::init(&config.s3)
+            .await
+            .expect("failed to initialize s3 service");
+        let search_index = MeilisearchIndex::init(&config.search_engine)
+            .await
+            .expect("failed to initialize search engine module");
+
+        Self::init_with(config, Arc::new(object_storage), Arc::new(search_index)).await
+    }
+
+    /// Like [`Services::init`], but keeps the objects in `object_storage` instead of S3 and
+    /// searches `search_index` instead of Meilisearch, e.g. the stand-ins of `crate::testing`
+    /// built with the `testing` feature.
+    pub async fn init_with(
+        config: &Config,
+        object_storage: Arc<dyn ObjectStorage>,
+        search_index: Arc<dyn SearchIndex>,
+    ) -> Self {
+        let database = Database::init(&config.database)
+            .await
+            .expect("failed to initialize database module");
+
+        let mailer_service =
+            mailer_service::init(&config.mailer).expect("failed to initialize mailer");
+        let admin_service = AdminService::new(
+            AdminRepository::new(database.pool()),
+            mailer_service,
+            config.mailer.password_reset_url.clone(),
+            config.admins.bootstrap_token.clone(),
+        );
+        let admin_task_service = AdminTaskService::new(database.pool());
+        let collection_service = CollectionService::new(CollectionRepository::new(
+            database.pool(),
+            database.read_pool(),
+        ));
+        let file_service = FileService::new(
+            FileRepository::new(database.pool(), database.read_pool()),
+            FileStorage {
+                bucket: config.s3.bucket_name.clone(),
+                region: config.s3.region.clone(),
+            },
+        );
+        let index_service = IndexService::new(
+            search_index,
+            IndexTimeouts {
+                search: config.route_timeouts.search,
+                index: config.route_timeouts.index,
+            },
+        );
+        let export_service = ExportService::new(file_service.clone(), object_storage.clone());
+        let bundle_service = BundleService::new(object_storage.clone(), config.upload);
+
+        Self {
+            admin_service,
+            admin_task_service,
+            bundle_service,
+            collection_service,
+            export_service,
+            file_service,
+            index_service,
+            migration_service: MigrationService::new(database.pool()),
+            object_storage,
+            scheduled_task_service: ScheduledTaskService::new(database.pool()),
+            tag_definition_service: TagDefinitionService::new(database.pool()),
+            token_service: TokenService::new(),
+        }
+    }
+}
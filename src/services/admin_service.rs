@@ -1,25 +1,112 @@
 use crate::{
     db::repositories::admin::{self, AdminRepository},
     interfaces::admins,
-    services::token_service::TokenService,
+    services::{
+        mailer_service::{Mail, MailerService},
+        token_service::TokenService,
+    },
+    telemetry::ErrorChain,
 };
+use chrono::{Duration, Utc};
+use std::sync::Arc;
 use thiserror::Error;
+use uuid::Uuid;
+
+/// How long a session token stays valid after logging in, however actively it is used.
+const SESSION_DURATION: Duration = Duration::days(7);
+/// How long a session token stays valid without being used.
+const SESSION_IDLE_TIMEOUT: Duration = Duration::hours(12);
+/// How often the last use of a session is recorded. Uses in between are not written, so a session
+/// may expire up to this much earlier than the idle timeout suggests.
+const SESSION_TOUCH_INTERVAL: Duration = Duration::minutes(1);
+/// How long a password reset token stays valid after being mailed.
+const PASSWORD_RESET_DURATION: Duration = Duration::hours(1);
 
 #[derive(Error, Debug)]
 pub enum AdminServiceError {
     #[error("repository error: {0:#?}")]
-    RepositoryError(#[from] crate::db::repositories::RepositoryError),
+    RepositoryError(crate::db::repositories::RepositoryError),
     #[error("password error: {0:#?}")]
     PwError(#[from] argon2::password_hash::Error),
+    #[error("failed to generate session token")]
+    TokenGeneration,
+    #[error("bootstrap token does not match")]
+    BootstrapTokenMismatch,
+    #[error("duplicated admin: `{key}` = `{value}`")]
+    Conflict { key: String, value: String },
 }
 
+impl From<crate::db::repositories::RepositoryError> for AdminServiceError {
+    fn from(err: crate::db::repositories::RepositoryError) -> Self {
+        match err {
+            crate::db::repositories::RepositoryError::Conflict { key, value } => {
+                Self::Conflict { key, value }
+            }
+            err => Self::RepositoryError(err),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct AdminService {
     admin_repository: AdminRepository,
+    mailer_service: Arc<dyn MailerService>,
+    password_reset_url: Option<String>,
+    bootstrap_token: Option<String>,
 }
 
 impl AdminService {
-    pub fn new(admin_repository: AdminRepository) -> Self {
-        Self { admin_repository }
+    pub fn new(
+        admin_repository: AdminRepository,
+        mailer_service: Arc<dyn MailerService>,
+        password_reset_url: Option<String>,
+        bootstrap_token: Option<String>,
+    ) -> Self {
+        Self {
+            admin_repository,
+            mailer_service,
+            password_reset_url,
+            bootstrap_token,
+        }
+    }
+
+    /// Creates the first admin, with the admin role. Without a bootstrap token this only works
+    /// while there is no admin; with the configured one it works once per token. Returns `None`
+    /// if bootstrapping is no longer possible.
+    pub async fn bootstrap_admin(
+        &self,
+        admin: admins::BootstrappingAdmin,
+    ) -> Result<Option<admins::Admin>, AdminServiceError> {
+        const TOKEN_SERVICE: TokenService = TokenService::new();
+
+        // digests are compared, so the comparison does not leak how much of the token matched
+        let token_hash = match (&admin.bootstrap_token, &self.bootstrap_token) {
+            (Some(token), Some(bootstrap_token))
+                if TOKEN_SERVICE.hash_token(token) == TOKEN_SERVICE.hash_token(bootstrap_token) =>
+            {
+                Some(TOKEN_SERVICE.hash_token(token))
+            }
+            (Some(_), _) => {
+                return Err(AdminServiceError::BootstrapTokenMismatch);
+            }
+            (None, _) => None,
+        };
+
+        let pw_hash = TOKEN_SERVICE.hash_password(&admin.password)?;
+        let admin = self
+            .admin_repository
+            .create_first_one(
+                admin::entities::AdminEntityForCreation {
+                    username: admin.username,
+                    email: admin.email,
+                    pw_hash,
+                    role: admins::AdminRole::Admin,
+                },
+                token_hash,
+            )
+            .await?;
+
+        Ok(admin.map(|admin| admin.into()))
     }
 
     pub async fn create_admin(
@@ -35,15 +122,263 @@ impl AdminService {
                 username: admin.username,
                 email: admin.email,
                 pw_hash,
+                role: admin.role,
+            })
+            .await?;
+
+        Ok(admin.into())
+    }
+
+    /// Returns `None` if there is no admin with the id. Changing the password revokes every
+    /// session of the admin.
+    pub async fn update_admin(
+        &self,
+        admin_id: Uuid,
+        admin: admins::UpdatingAdmin,
+    ) -> Result<Option<admins::Admin>, AdminServiceError> {
+        const TOKEN_SERVICE: TokenService = TokenService::new();
+
+        let pw_hash = match &admin.password {
+            Some(password) => Some(TOKEN_SERVICE.hash_password(password)?),
+            None => None,
+        };
+        let revokes_sessions = pw_hash.is_some();
+        let admin = match self
+            .admin_repository
+            .update_one(admin::entities::AdminEntityForUpdate {
+                id: admin_id,
+                username: admin.username,
+                email: admin.email,
+                pw_hash,
+                role: admin.role,
+            })
+            .await?
+        {
+            Some(admin) => admin,
+            None => {
+                return Ok(None);
+            }
+        };
+
+        if revokes_sessions {
+            self.admin_repository
+                .delete_sessions_of_admin(admin_id)
+                .await?;
+        }
+
+        Ok(Some(admin.into()))
+    }
+
+    /// Returns `None` if the username or the password is wrong.
+    pub async fn login(
+        &self,
+        login: admins::AdminLogin,
+    ) -> Result<Option<admins::AdminSession>, AdminServiceError> {
+        const TOKEN_SERVICE: TokenService = TokenService::new();
+
+        let admin = match self
+            .admin_repository
+            .find_one_by_username_for_login(&login.username)
+            .await?
+        {
+            Some(admin) => admin,
+            None => {
+                return Ok(None);
+            }
+        };
+
+        if !TOKEN_SERVICE.verify_password(&login.password, &admin.pw_hash)? {
+            return Ok(None);
+        }
+
+        let token = TOKEN_SERVICE
+            .generate_token()
+            .map_err(|_| AdminServiceError::TokenGeneration)?;
+        let session = self
+            .admin_repository
+            .create_session(admin.id, token, Utc::now() + SESSION_DURATION)
+            .await?;
+
+        Ok(Some(session.into()))
+    }
+
+    /// Resolves the admin owning the session token, recording the use of the session. Expired and
+    /// idle sessions resolve to `None`.
+    pub async fn authenticate(
+        &self,
+        token: &str,
+    ) -> Result<Option<admins::Admin>, AdminServiceError> {
+        let now = Utc::now();
+        let (admin, session) = match self
+            .admin_repository
+            .find_one_by_session_token(token, now - SESSION_IDLE_TIMEOUT)
+            .await?
+        {
+            Some(found) => found,
+            None => {
+                return Ok(None);
+            }
+        };
+
+        let used_since = now - SESSION_TOUCH_INTERVAL;
+
+        if session.last_used_at <= used_since {
+            self.admin_repository
+                .touch_session(token, used_since)
+                .await?;
+        }
+
+        Ok(Some(admin.into()))
+    }
+
+    /// Issues a new token for a live session and invalidates the old one. Returns `None` if the
+    /// session has expired or been rotated already.
+    pub async fn refresh(
+        &self,
+        token: &str,
+    ) -> Result<Option<admins::AdminSession>, AdminServiceError> {
+        const TOKEN_SERVICE: TokenService = TokenService::new();
+
+        let new_token = TOKEN_SERVICE
+            .generate_token()
+            .map_err(|_| AdminServiceError::TokenGeneration)?;
+        let session = self
+            .admin_repository
+            .rotate_session(token, new_token, Utc::now() - SESSION_IDLE_TIMEOUT)
+            .await?;
+
+        Ok(session.map(|session| session.into()))
+    }
+
+    /// Deletes the sessions that can no longer be used, returning how many were deleted.
+    pub async fn purge_expired_sessions(&self) -> Result<u64, AdminServiceError> {
+        Ok(self
+            .admin_repository
+            .delete_expired_sessions(Utc::now() - SESSION_IDLE_TIMEOUT)
+            .await?)
+    }
+
+    /// Deletes the password resets that can no longer be used, returning how many were deleted.
+    pub async fn purge_stale_password_resets(&self) -> Result<u64, AdminServiceError> {
+        Ok(self.admin_repository.delete_stale_password_resets().await?)
+    }
+
+    /// Mails a single-use password reset token to the admin with the email, if there is one.
+    /// The mail is sent in the background, so the caller cannot tell whether the email is known.
+    pub async fn request_password_reset(&self, email: &str) -> Result<(), AdminServiceError> {
+        const TOKEN_SERVICE: TokenService = TokenService::new();
+
+        let admin = match self
+            .admin_repository
+            .find_one_by_email_for_login(email)
+            .await?
+        {
+            Some(admin) => admin,
+            None => {
+                return Ok(());
+            }
+        };
+
+        let token = TOKEN_SERVICE
+            .generate_token()
+            .map_err(|_| AdminServiceError::TokenGeneration)?;
+        self.admin_repository
+            .create_password_reset(
+                admin.id,
+                TOKEN_SERVICE.hash_token(&token),
+                Utc::now() + PASSWORD_RESET_DURATION,
+            )
+            .await?;
+
+        let link = match &self.password_reset_url {
+            Some(password_reset_url) => password_reset_url.replace("{token}", &token),
+            None => token,
+        };
+        let mail = Mail {
+            to: email.to_owned(),
+            subject: "Reset your password".to_owned(),
+            body: format!(
+                "A password reset was requested for your account. Use the following within {} minutes to choose a new password:\n\n{link}\n\nIf you did not request it, you can ignore this mail.",
+                PASSWORD_RESET_DURATION.num_minutes()
+            ),
+        };
+        let mailer_service = self.mailer_service.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = mailer_service.send(mail).await {
+                tracing::warn!(error = %ErrorChain(&err), "failed to send password reset mail");
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Sets a new password with a password reset token and revokes every session of the admin.
+    /// Returns `false` if the token is unknown, expired or already used.
+    pub async fn reset_password(
+        &self,
+        token: &str,
+        password: &str,
+    ) -> Result<bool, AdminServiceError> {
+        const TOKEN_SERVICE: TokenService = TokenService::new();
+
+        // hashed first, so a failure does not use up the token
+        let pw_hash = TOKEN_SERVICE.hash_password(password)?;
+        let admin_id = match self
+            .admin_repository
+            .use_password_reset(TOKEN_SERVICE.hash_token(token))
+            .await?
+        {
+            Some(admin_id) => admin_id,
+            None => {
+                return Ok(false);
+            }
+        };
+
+        self.admin_repository
+            .update_one(admin::entities::AdminEntityForUpdate {
+                id: admin_id,
+                username: None,
+                email: None,
+                pw_hash: Some(pw_hash),
+                role: None,
             })
             .await?;
+        self.admin_repository
+            .delete_sessions_of_admin(admin_id)
+            .await?;
+
+        Ok(true)
+    }
 
-        Ok(admins::Admin {
+    pub async fn logout(&self, token: &str) -> Result<(), AdminServiceError> {
+        self.admin_repository.delete_session(token).await?;
+
+        Ok(())
+    }
+}
+
+impl From<admin::entities::AdminEntity> for admins::Admin {
+    fn from(admin: admin::entities::AdminEntity) -> Self {
+        Self {
             id: admin.id,
             username: admin.username,
             email: admin.email,
+            role: admin.role,
             joined_at: admin.joined_at,
-        })
+        }
+    }
+}
+
+impl From<admin::entities::AdminSessionEntity> for admins::AdminSession {
+    fn from(session: admin::entities::AdminSessionEntity) -> Self {
+        Self {
+            token: session.token,
+            logined_at: session.logined_at,
+            last_used_at: session.last_used_at,
+            idle_expired_at: session.last_used_at + SESSION_IDLE_TIMEOUT,
+            expired_at: session.expired_at,
+        }
     }
 }
 
@@ -56,6 +391,7 @@ mod row_types {
         pub id: Uuid,
         pub username: String,
         pub email: String,
+        pub role: admins::AdminRole,
         pub joined_at: NaiveDateTime,
     }
 
@@ -65,6 +401,7 @@ mod row_types {
                 id: admin.id,
                 username: admin.username,
                 email: admin.email,
+                role: admin.role,
                 joined_at: admin.joined_at.and_utc(),
             }
         }
@@ -1,27 +1,33 @@
-use crate::interfaces::admins;
+use crate::{
+    db::repositories::{escape_like, PRUNE_CHUNK_SIZE},
+    interfaces::admins::{self, TaskKind},
+};
 use chrono::{DateTime, Utc};
+use futures::{stream::BoxStream, StreamExt};
 use serde_json::Value;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::cmp::Reverse;
 use thiserror::Error;
 use uuid::Uuid;
 
-pub const RE_INDEX_FILES_TASK_NAME: &str = "re-index-files";
-pub const RE_INDEX_COLLECTIONS_TASK_NAME: &str = "re-index-collections";
-
-pub const UPLOAD_FILE_TASK_NAME: &str = "upload-file";
-pub const UPDATE_FILE_TASK_NAME: &str = "update-file";
-pub const DELETE_FILE_TASK_NAME: &str = "delete-file";
-
-pub const CREATE_COLLECTION_TASK_NAME: &str = "create-collection";
-pub const UPDATE_COLLECTION_TASK_NAME: &str = "update-collection";
-pub const DELETE_COLLECTION_TASK_NAME: &str = "delete-collection";
-
-pub const FILE_GC_TASK_NAME: &str = "file-gc";
+/// Largest serialized metadata an admin task keeps. Tasks keep their work queues elsewhere, like
+/// the files of a bulk deletion in `admin_task_queued_files`, and cap the lists they record.
+pub const MAX_METADATA_SIZE: usize = 64 * 1024;
 
 #[derive(Error, Debug)]
 pub enum AdminTaskServiceError {
     #[error("database error: {0:#?}")]
     DbError(#[from] sqlx::Error),
+    #[error("the cursor does not match any admin task")]
+    InvalidCursor,
+    #[error("unknown task name `{0}`")]
+    UnknownTaskKind(String),
+    #[error("metadata of {size} bytes exceeds the limit of {MAX_METADATA_SIZE} bytes")]
+    MetadataTooLarge { size: usize },
+    #[error("task `{task_id}` of the same kind is already pending or in progress")]
+    AlreadyQueued { task_id: Uuid },
+    #[error("failed to serialize admin task: {0:#?}")]
+    Serialize(#[from] serde_json::Error),
 }
 
 #[derive(Clone)]
@@ -34,6 +40,7 @@ impl AdminTaskService {
         Self { db_pool }
     }
 
+    #[tracing::instrument(skip_all, fields(task_id = %task_id))]
     pub async fn get_task(
         &self,
         task_id: Uuid,
@@ -44,9 +51,14 @@ impl AdminTaskService {
 SELECT
     id,
     initiator AS \"initiator:_\",
+    initiator_admin_id,
+    (SELECT username FROM admins WHERE admins.id = initiator_admin_id) AS \"initiator_admin_username?\",
     name,
     metadata,
     status AS \"status:_\",
+    depends_on,
+    error,
+    failed_at,
     enqueued_at,
     updated_at
 FROM admin_tasks
@@ -59,9 +71,10 @@ WHERE id = $1",
         Ok(task.map(|task| task.into()))
     }
 
+    #[tracing::instrument(skip_all)]
     pub async fn get_last_active_task(
         &self,
-        name: &str,
+        kind: &TaskKind,
     ) -> Result<Option<admins::AdminTask>, AdminTaskServiceError> {
         let task = sqlx::query_as!(
             row_types::AdminTask,
@@ -69,9 +82,14 @@ WHERE id = $1",
 SELECT
     id,
     initiator AS \"initiator:_\",
+    initiator_admin_id,
+    (SELECT username FROM admins WHERE admins.id = initiator_admin_id) AS \"initiator_admin_username?\",
     name,
     metadata,
     status AS \"status:_\",
+    depends_on,
+    error,
+    failed_at,
     enqueued_at,
     updated_at
 FROM admin_tasks
@@ -82,9 +100,18 @@ WHERE
         OR
         status = 'in_progress'
     )
+    AND (
+        depends_on IS NULL
+        OR
+        EXISTS (
+            SELECT 1
+            FROM admin_tasks AS dependencies
+            WHERE dependencies.id = admin_tasks.depends_on AND dependencies.status = 'completed'
+        )
+    )
 ORDER BY enqueued_at ASC
 LIMIT 1",
-            name
+            kind.as_str()
         )
         .fetch_optional(&self.db_pool)
         .await?;
@@ -92,24 +119,65 @@ LIMIT 1",
         Ok(task.map(|task| task.into()))
     }
 
+    /// With `verify_cursor`, a cursor whose task was deleted or has been updated since is
+    /// rejected instead of silently paging from wherever it lands. With `metadata_contains`, only
+    /// the tasks whose metadata contains it (as in the `@>` operator) are listed. With
+    /// `initiated_by`, only the tasks enqueued on a request of that admin are listed.
+    #[tracing::instrument(skip_all)]
     pub async fn list_tasks(
         &self,
         limit: usize,
         cursor: Option<AdminTaskCursor>,
+        verify_cursor: bool,
+        metadata_contains: Option<Value>,
+        initiated_by: Option<Uuid>,
     ) -> Result<Vec<admins::AdminTaskPreview>, AdminTaskServiceError> {
+        if let Some(cursor) = cursor.as_ref().filter(|_| verify_cursor) {
+            let matches = sqlx::query_scalar!(
+                "
+SELECT EXISTS (
+    SELECT 1
+    FROM admin_tasks
+    WHERE id = $1 AND updated_at = $2
+) AS \"matches!\"",
+                cursor.id,
+                cursor.updated_at.naive_utc()
+            )
+            .fetch_one(&self.db_pool)
+            .await?;
+
+            if !matches {
+                return Err(AdminTaskServiceError::InvalidCursor);
+            }
+        }
+
         let admin_tasks = match cursor {
             Some(cursor) => {
                 sqlx::query_as!(
                     row_types::AdminTaskPreview,
                     "
-SELECT id, initiator AS \"initiator:_\", name, status AS \"status:_\", enqueued_at, updated_at
+SELECT
+    id,
+    initiator AS \"initiator:_\",
+    initiator_admin_id,
+    (SELECT username FROM admins WHERE admins.id = initiator_admin_id) AS \"initiator_admin_username?\",
+    name,
+    metadata,
+    status AS \"status:_\",
+    enqueued_at,
+    updated_at
 FROM admin_tasks
-WHERE id > $1 AND updated_at <= $2
+WHERE
+    (updated_at < $2 OR (updated_at = $2 AND id > $1))
+    AND ($4::JSONB IS NULL OR metadata @> $4)
+    AND ($5::UUID IS NULL OR initiator_admin_id = $5)
 ORDER BY updated_at DESC, id ASC
 LIMIT $3",
                     cursor.id,
                     cursor.updated_at.naive_utc(),
-                    limit as i64
+                    limit as i64,
+                    metadata_contains,
+                    initiated_by
                 )
                 .fetch_all(&self.db_pool)
                 .await
@@ -118,11 +186,23 @@ LIMIT $3",
                 sqlx::query_as!(
                     row_types::AdminTaskPreview,
                     "
-SELECT id, initiator AS \"initiator:_\", name, status AS \"status:_\", enqueued_at, updated_at
+SELECT
+    id,
+    initiator AS \"initiator:_\",
+    initiator_admin_id,
+    (SELECT username FROM admins WHERE admins.id = initiator_admin_id) AS \"initiator_admin_username?\",
+    name,
+    metadata,
+    status AS \"status:_\",
+    enqueued_at,
+    updated_at
 FROM admin_tasks
+WHERE ($2::JSONB IS NULL OR metadata @> $2) AND ($3::UUID IS NULL OR initiator_admin_id = $3)
 ORDER BY updated_at DESC, id ASC
 LIMIT $1",
-                    limit as i64
+                    limit as i64,
+                    metadata_contains,
+                    initiated_by
                 )
                 .fetch_all(&self.db_pool)
                 .await
@@ -136,22 +216,294 @@ LIMIT $1",
         Ok(admin_tasks.into_iter().map(|task| task.into()).collect())
     }
 
+    /// Lists the tasks whose name, error or any metadata value, however deeply nested, contains
+    /// `q` regardless of case. Ordered like [`AdminTaskService::list_tasks`].
+    #[tracing::instrument(skip_all)]
+    pub async fn search_tasks(
+        &self,
+        q: &str,
+        limit: usize,
+        cursor: Option<AdminTaskCursor>,
+    ) -> Result<Vec<admins::AdminTaskPreview>, AdminTaskServiceError> {
+        let pattern = format!("%{}%", escape_like(q));
+        let admin_tasks = sqlx::query_as!(
+            row_types::AdminTaskPreview,
+            "
+SELECT
+    id,
+    initiator AS \"initiator:_\",
+    initiator_admin_id,
+    (SELECT username FROM admins WHERE admins.id = initiator_admin_id) AS \"initiator_admin_username?\",
+    name,
+    metadata,
+    status AS \"status:_\",
+    enqueued_at,
+    updated_at
+FROM admin_tasks
+WHERE
+    admin_task_search_text(name, metadata, error) ILIKE $1
+    AND ($2::UUID IS NULL OR updated_at < $3 OR (updated_at = $3 AND id > $2))
+ORDER BY updated_at DESC, id ASC
+LIMIT $4",
+            pattern,
+            cursor.as_ref().map(|cursor| cursor.id),
+            cursor.as_ref().map(|cursor| cursor.updated_at.naive_utc()),
+            limit as i64
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(admin_tasks.into_iter().map(|task| task.into()).collect())
+    }
+
+    /// Streams the tasks enqueued from `from` until before `to` as NDJSON, one full task per line,
+    /// in the order they were enqueued and without loading them all into memory.
+    pub fn stream_tasks_as_ndjson(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> BoxStream<'_, Result<String, AdminTaskServiceError>> {
+        sqlx::query_as!(
+            row_types::AdminTask,
+            "
+SELECT
+    id,
+    initiator AS \"initiator:_\",
+    initiator_admin_id,
+    (SELECT username FROM admins WHERE admins.id = initiator_admin_id) AS \"initiator_admin_username?\",
+    name,
+    metadata,
+    status AS \"status:_\",
+    depends_on,
+    error,
+    failed_at,
+    enqueued_at,
+    updated_at
+FROM admin_tasks
+WHERE enqueued_at >= $1 AND enqueued_at < $2
+ORDER BY enqueued_at ASC, id ASC",
+            from.naive_utc(),
+            to.naive_utc()
+        )
+        .fetch(&self.db_pool)
+        .map(|task| {
+            let task = admins::AdminTask::from(task?);
+
+            // escapes the line breaks inside strings, so that each task stays on its own line
+            Ok(format!("{}\n", serde_json::to_string(&task)?))
+        })
+        .boxed()
+    }
+
+    /// Lists the latest tasks whose metadata names the file as its `file_id`.
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    pub async fn list_tasks_for_file(
+        &self,
+        file_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<admins::AdminTaskPreview>, AdminTaskServiceError> {
+        let admin_tasks = sqlx::query_as!(
+            row_types::AdminTaskPreview,
+            "
+SELECT
+    id,
+    initiator AS \"initiator:_\",
+    initiator_admin_id,
+    (SELECT username FROM admins WHERE admins.id = initiator_admin_id) AS \"initiator_admin_username?\",
+    name,
+    metadata,
+    status AS \"status:_\",
+    enqueued_at,
+    updated_at
+FROM admin_tasks
+WHERE metadata @> jsonb_build_object('file_id', $1::UUID)
+ORDER BY enqueued_at DESC, id ASC
+LIMIT $2",
+            file_id,
+            limit as i64
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(admin_tasks.into_iter().map(|task| task.into()).collect())
+    }
+
+    /// With `mark_previous_tasks_as_canceled`, the tasks of the kind not canceled yet are marked
+    /// as canceled first. Otherwise a singleton kind (see [`TaskKind::is_singleton`]) that already
+    /// has a pending or in-progress task fails with [`AdminTaskServiceError::AlreadyQueued`].
+    ///
+    /// A task the runner resumes from its metadata fails with
+    /// [`AdminTaskServiceError::MetadataTooLarge`] if the metadata exceeds [`MAX_METADATA_SIZE`],
+    /// while a task recorded as already finished keeps what fits; see [`truncate_metadata`].
+    #[tracing::instrument(skip_all)]
+    #[allow(clippy::too_many_arguments)]
     pub async fn enqueue_task(
         &self,
         initiator: admins::AdminTaskInitiator,
-        name: String,
+        initiator_admin_id: Option<Uuid>,
+        kind: TaskKind,
         metadata: Value,
         status: Option<admins::AdminTaskStatus>,
         mark_previous_tasks_as_canceled: bool,
+        depends_on: Option<Uuid>,
+    ) -> Result<admins::AdminTask, AdminTaskServiceError> {
+        let mut tx = self.db_pool.begin().await?;
+        let task = Self::insert_task(
+            &mut tx,
+            initiator,
+            initiator_admin_id,
+            kind,
+            metadata,
+            status,
+            mark_previous_tasks_as_canceled,
+            depends_on,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(task)
+    }
+
+    /// Enqueues a pending task along with the files it works through, which it takes from the
+    /// queue with [`Self::get_queued_file_ids`] and [`Self::dequeue_file_ids`].
+    #[tracing::instrument(skip_all)]
+    pub async fn enqueue_task_with_queued_files(
+        &self,
+        initiator: admins::AdminTaskInitiator,
+        initiator_admin_id: Option<Uuid>,
+        kind: TaskKind,
+        metadata: Value,
+        file_ids: &[Uuid],
     ) -> Result<admins::AdminTask, AdminTaskServiceError> {
         let mut tx = self.db_pool.begin().await?;
+        let task = Self::insert_task(
+            &mut tx,
+            initiator,
+            initiator_admin_id,
+            kind,
+            metadata,
+            None,
+            false,
+            None,
+        )
+        .await?;
+
+        // in the same transaction, so that the runner never sees the task without its files
+        sqlx::query!(
+            "
+INSERT INTO admin_task_queued_files (task_id, file_id)
+SELECT $1, file_id FROM UNNEST($2::UUID[]) AS file_id
+ON CONFLICT DO NOTHING",
+            task.id,
+            file_ids
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(task)
+    }
+
+    /// Returns up to `limit` of the files still queued for the task, in a stable order.
+    #[tracing::instrument(skip_all, fields(task_id = %task_id))]
+    pub async fn get_queued_file_ids(
+        &self,
+        task_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<Uuid>, AdminTaskServiceError> {
+        let file_ids = sqlx::query_scalar!(
+            "
+SELECT file_id
+FROM admin_task_queued_files
+WHERE task_id = $1
+ORDER BY file_id ASC
+LIMIT $2",
+            task_id,
+            limit as i64
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(file_ids)
+    }
+
+    /// Removes processed files from the queue of the task.
+    #[tracing::instrument(skip_all, fields(task_id = %task_id))]
+    pub async fn dequeue_file_ids(
+        &self,
+        task_id: Uuid,
+        file_ids: &[Uuid],
+    ) -> Result<(), AdminTaskServiceError> {
+        sqlx::query!(
+            "DELETE FROM admin_task_queued_files WHERE task_id = $1 AND file_id = ANY($2)",
+            task_id,
+            file_ids
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn insert_task(
+        tx: &mut Transaction<'_, Postgres>,
+        initiator: admins::AdminTaskInitiator,
+        initiator_admin_id: Option<Uuid>,
+        kind: TaskKind,
+        metadata: Value,
+        status: Option<admins::AdminTaskStatus>,
+        mark_previous_tasks_as_canceled: bool,
+        depends_on: Option<Uuid>,
+    ) -> Result<admins::AdminTask, AdminTaskServiceError> {
+        if let TaskKind::Unknown(name) = kind {
+            // nothing would ever process it
+            return Err(AdminTaskServiceError::UnknownTaskKind(name));
+        }
+
+        let metadata = match status {
+            None
+            | Some(admins::AdminTaskStatus::Pending)
+            | Some(admins::AdminTaskStatus::InProgress) => {
+                check_metadata_size(&metadata)?;
+                metadata
+            }
+            // recorded as finished, so nothing resumes from it
+            Some(_) => truncate_metadata(metadata),
+        };
+
+        if kind.is_singleton() {
+            // serializes the enqueues of the kind, so that two of them cannot both see no task
+            sqlx::query!("SELECT pg_advisory_xact_lock(hashtext($1))", kind.as_str())
+                .execute(&mut **tx)
+                .await?;
+
+            if !mark_previous_tasks_as_canceled {
+                let queued_task_id = sqlx::query_scalar!(
+                    "
+SELECT id
+FROM admin_tasks
+WHERE name = $1 AND (status = 'pending' OR status = 'in_progress')
+ORDER BY enqueued_at ASC
+LIMIT 1",
+                    kind.as_str()
+                )
+                .fetch_optional(&mut **tx)
+                .await?;
+
+                if let Some(task_id) = queued_task_id {
+                    return Err(AdminTaskServiceError::AlreadyQueued { task_id });
+                }
+            }
+        }
 
         if mark_previous_tasks_as_canceled {
             sqlx::query!(
                 "UPDATE admin_tasks SET status = 'canceled' WHERE name = $1 AND status != 'canceled'",
-                name
+                kind.as_str()
             )
-            .execute(&mut *tx)
+            .execute(&mut **tx)
             .await?;
         }
 
@@ -160,55 +512,107 @@ LIMIT $1",
                 sqlx::query_as!(
                     row_types::CreatingAdminTask,
                     "
-INSERT INTO admin_tasks (initiator, name, metadata, status)
-VALUES ($1, $2, $3, $4)
-RETURNING id, status AS \"status:_\", enqueued_at, updated_at
+INSERT INTO admin_tasks (initiator, initiator_admin_id, name, metadata, status, depends_on)
+VALUES ($1, $2, $3, $4, $5, $6)
+RETURNING id, (SELECT username FROM admins WHERE admins.id = initiator_admin_id) AS \"initiator_admin_username?\", status AS \"status:_\", enqueued_at, updated_at
 ",
                     initiator as _,
-                    &name,
+                    initiator_admin_id,
+                    kind.as_str(),
                     &metadata,
                     status as _,
+                    depends_on,
                 )
-                .fetch_one(&mut *tx)
+                .fetch_one(&mut **tx)
                 .await?
             }
             None => {
                 sqlx::query_as!(
                     row_types::CreatingAdminTask,
                     "
-INSERT INTO admin_tasks (initiator, name, metadata)
-VALUES ($1, $2, $3)
-RETURNING id, status AS \"status:_\", enqueued_at, updated_at
+INSERT INTO admin_tasks (initiator, initiator_admin_id, name, metadata, depends_on)
+VALUES ($1, $2, $3, $4, $5)
+RETURNING id, (SELECT username FROM admins WHERE admins.id = initiator_admin_id) AS \"initiator_admin_username?\", status AS \"status:_\", enqueued_at, updated_at
 ",
                     initiator as _,
-                    &name,
+                    initiator_admin_id,
+                    kind.as_str(),
                     &metadata,
+                    depends_on,
                 )
-                .fetch_one(&mut *tx)
+                .fetch_one(&mut **tx)
                 .await?
             }
         };
 
-        tx.commit().await?;
-
         Ok(admins::AdminTask {
             id: creating_admin_task.id,
             initiator,
-            name: name.to_string(),
+            initiated_by: row_types::initiated_by(
+                initiator_admin_id,
+                creating_admin_task.initiator_admin_username,
+            ),
+            name: kind,
             metadata,
             status: creating_admin_task.status,
+            depends_on,
+            error: None,
+            failed_at: None,
             enqueued_at: creating_admin_task.enqueued_at.and_utc(),
             updated_at: creating_admin_task.updated_at.and_utc(),
         })
     }
 
+    /// Cancels the task if it is still pending or in progress.
+    /// Returns `None` if there is no such task in those states.
+    #[tracing::instrument(skip_all, fields(task_id = %task_id))]
+    pub async fn cancel_task(
+        &self,
+        task_id: Uuid,
+    ) -> Result<Option<admins::AdminTask>, AdminTaskServiceError> {
+        let task = sqlx::query_as!(
+            row_types::AdminTask,
+            "
+UPDATE admin_tasks
+SET status = 'canceled'
+WHERE
+    id = $1
+    AND (
+        status = 'pending'
+        OR
+        status = 'in_progress'
+    )
+RETURNING
+    id,
+    initiator AS \"initiator:_\",
+    initiator_admin_id,
+    (SELECT username FROM admins WHERE admins.id = initiator_admin_id) AS \"initiator_admin_username?\",
+    name,
+    metadata,
+    status AS \"status:_\",
+    depends_on,
+    error,
+    failed_at,
+    enqueued_at,
+    updated_at",
+            task_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(task.map(|task| task.into()))
+    }
+
+    /// Updates the status of the task. Canceled tasks stay canceled, so that a worker finishing
+    /// its current step cannot resurrect a task canceled in the meantime.
+    #[tracing::instrument(skip_all, fields(task_id = %task_id))]
     pub async fn update_task_status(
         &self,
         task_id: Uuid,
         status: admins::AdminTaskStatus,
     ) -> Result<(), AdminTaskServiceError> {
         sqlx::query!(
-            "UPDATE admin_tasks SET status = $1 WHERE id = $2",
+            "UPDATE admin_tasks SET status = $1 WHERE id = $2 AND status != 'canceled'",
             status as _,
             task_id
         )
@@ -218,11 +622,119 @@ RETURNING id, status AS \"status:_\", enqueued_at, updated_at
         Ok(())
     }
 
+    /// Marks the task as failed, recording the error and when it happened.
+    #[tracing::instrument(skip_all, fields(task_id = %task_id))]
+    pub async fn fail_task(&self, task_id: Uuid, error: &str) -> Result<(), AdminTaskServiceError> {
+        sqlx::query!(
+            "
+UPDATE admin_tasks
+SET status = 'failed', error = $1, failed_at = CURRENT_TIMESTAMP
+WHERE id = $2 AND status != 'canceled'",
+            error,
+            task_id
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Cancels every pending task whose dependency has failed or was canceled, transitively.
+    /// The dependency that caused the cancellation is recorded in the task metadata under
+    /// `canceled_by_dependency`.
+    #[tracing::instrument(skip_all)]
+    pub async fn cancel_tasks_with_unsuccessful_dependencies(
+        &self,
+    ) -> Result<u64, AdminTaskServiceError> {
+        let mut canceled_count = 0;
+
+        loop {
+            let result = sqlx::query!(
+                "
+UPDATE admin_tasks
+SET
+    status = 'canceled',
+    metadata = admin_tasks.metadata || jsonb_build_object(
+        'canceled_by_dependency',
+        jsonb_build_object('id', dependencies.id, 'status', dependencies.status::text)
+    )
+FROM admin_tasks AS dependencies
+WHERE
+    dependencies.id = admin_tasks.depends_on
+    AND admin_tasks.status = 'pending'
+    AND (
+        dependencies.status = 'failed'
+        OR
+        dependencies.status = 'canceled'
+    )"
+            )
+            .execute(&self.db_pool)
+            .await?;
+
+            // each round cancels one level of the dependency chain
+            if result.rows_affected() == 0 {
+                break;
+            }
+
+            canceled_count += result.rows_affected();
+        }
+
+        Ok(canceled_count)
+    }
+
+    /// Deletes the canceled, completed and failed tasks last updated before `finished_before`,
+    /// returning how many were deleted. Tasks that a pending or in-progress task depends on are
+    /// kept, so that their outcome still decides whether the dependent runs.
+    #[tracing::instrument(skip_all)]
+    pub async fn delete_finished_tasks(
+        &self,
+        finished_before: DateTime<Utc>,
+    ) -> Result<u64, AdminTaskServiceError> {
+        let mut deleted_count = 0;
+
+        loop {
+            let result = sqlx::query!(
+                "
+DELETE FROM admin_tasks
+WHERE id IN (
+    SELECT id
+    FROM admin_tasks
+    WHERE
+        status IN ('canceled', 'completed', 'failed')
+        AND updated_at < $1
+        AND NOT EXISTS (
+            SELECT 1
+            FROM admin_tasks AS dependents
+            WHERE
+                dependents.depends_on = admin_tasks.id
+                AND dependents.status IN ('pending', 'in_progress')
+        )
+    LIMIT $2
+)",
+                finished_before.naive_utc(),
+                PRUNE_CHUNK_SIZE
+            )
+            .execute(&self.db_pool)
+            .await?;
+
+            deleted_count += result.rows_affected();
+
+            if result.rows_affected() < PRUNE_CHUNK_SIZE as u64 {
+                return Ok(deleted_count);
+            }
+        }
+    }
+
+    /// Fails with [`AdminTaskServiceError::MetadataTooLarge`] if the metadata exceeds
+    /// [`MAX_METADATA_SIZE`], since the task resumes from it.
+    #[tracing::instrument(skip_all, fields(task_id = %task_id))]
     pub async fn update_task_metadata(
         &self,
         task_id: Uuid,
         metadata: Value,
     ) -> Result<(), AdminTaskServiceError> {
+        check_metadata_size(&metadata)?;
+
         sqlx::query!(
             "UPDATE admin_tasks SET metadata = $1 WHERE id = $2",
             metadata,
@@ -235,20 +747,80 @@ RETURNING id, status AS \"status:_\", enqueued_at, updated_at
     }
 }
 
+fn metadata_size(metadata: &Value) -> usize {
+    metadata.to_string().len()
+}
+
+fn check_metadata_size(metadata: &Value) -> Result<(), AdminTaskServiceError> {
+    let size = metadata_size(metadata);
+
+    if MAX_METADATA_SIZE < size {
+        return Err(AdminTaskServiceError::MetadataTooLarge { size });
+    }
+
+    Ok(())
+}
+
+/// Drops the largest top-level fields of metadata above [`MAX_METADATA_SIZE`] until it fits, and
+/// marks what is left with `truncated: true`. Metadata that is not an object is dropped entirely.
+/// Only for the metadata of finished tasks, as a task could not resume from what is left.
+pub fn truncate_metadata(metadata: Value) -> Value {
+    let mut size = metadata_size(&metadata);
+
+    if size <= MAX_METADATA_SIZE {
+        return metadata;
+    }
+
+    let Value::Object(mut fields) = metadata else {
+        return serde_json::json!({ "truncated": true });
+    };
+
+    let mut field_sizes = fields
+        .iter()
+        .map(|(key, value)| (key.clone(), metadata_size(value)))
+        .collect::<Vec<_>>();
+    field_sizes.sort_unstable_by_key(|(_, size)| Reverse(*size));
+
+    for (key, field_size) in field_sizes {
+        if size <= MAX_METADATA_SIZE {
+            break;
+        }
+
+        fields.remove(&key);
+        size -= field_size;
+    }
+
+    fields.insert("truncated".to_owned(), Value::Bool(true));
+    Value::Object(fields)
+}
+
 pub struct AdminTaskCursor {
     pub id: Uuid,
     pub updated_at: DateTime<Utc>,
 }
 
 mod row_types {
-    use crate::interfaces::admins;
+    use crate::interfaces::admins::{self, TaskKind};
     use chrono::NaiveDateTime;
     use uuid::Uuid;
 
+    /// Pairs the id of the initiating admin with the username looked up for it.
+    pub fn initiated_by(
+        admin_id: Option<Uuid>,
+        username: Option<String>,
+    ) -> Option<admins::AdminTaskInitiatedBy> {
+        admin_id
+            .zip(username)
+            .map(|(id, username)| admins::AdminTaskInitiatedBy { id, username })
+    }
+
     pub struct AdminTaskPreview {
         pub id: Uuid,
         pub initiator: admins::AdminTaskInitiator,
+        pub initiator_admin_id: Option<Uuid>,
+        pub initiator_admin_username: Option<String>,
         pub name: String,
+        pub metadata: serde_json::Value,
         pub status: admins::AdminTaskStatus,
         pub enqueued_at: NaiveDateTime,
         pub updated_at: NaiveDateTime,
@@ -256,10 +828,15 @@ mod row_types {
 
     impl From<AdminTaskPreview> for admins::AdminTaskPreview {
         fn from(task: AdminTaskPreview) -> Self {
+            let name = TaskKind::from_name(task.name);
+            let summary = name.summarize(&task.metadata);
+
             Self {
                 id: task.id,
                 initiator: task.initiator,
-                name: task.name,
+                initiated_by: initiated_by(task.initiator_admin_id, task.initiator_admin_username),
+                name,
+                summary,
                 status: task.status,
                 enqueued_at: task.enqueued_at.and_utc(),
                 updated_at: task.updated_at.and_utc(),
@@ -270,9 +847,14 @@ mod row_types {
     pub struct AdminTask {
         pub id: Uuid,
         pub initiator: admins::AdminTaskInitiator,
+        pub initiator_admin_id: Option<Uuid>,
+        pub initiator_admin_username: Option<String>,
         pub name: String,
         pub metadata: serde_json::Value,
         pub status: admins::AdminTaskStatus,
+        pub depends_on: Option<Uuid>,
+        pub error: Option<String>,
+        pub failed_at: Option<NaiveDateTime>,
         pub enqueued_at: NaiveDateTime,
         pub updated_at: NaiveDateTime,
     }
@@ -282,9 +864,13 @@ mod row_types {
             Self {
                 id: task.id,
                 initiator: task.initiator,
-                name: task.name,
+                initiated_by: initiated_by(task.initiator_admin_id, task.initiator_admin_username),
+                name: TaskKind::from_name(task.name),
                 metadata: task.metadata,
                 status: task.status,
+                depends_on: task.depends_on,
+                error: task.error,
+                failed_at: task.failed_at.map(|failed_at| failed_at.and_utc()),
                 enqueued_at: task.enqueued_at.and_utc(),
                 updated_at: task.updated_at.and_utc(),
             }
@@ -293,6 +879,7 @@ mod row_types {
 
     pub struct CreatingAdminTask {
         pub id: Uuid,
+        pub initiator_admin_username: Option<String>,
         pub status: admins::AdminTaskStatus,
         pub enqueued_at: NaiveDateTime,
         pub updated_at: NaiveDateTime,
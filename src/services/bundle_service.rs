@@ -0,0 +1,338 @@
+use crate::{
+    config::UploadConfig,
+    interfaces::files::{File, FileStorage},
+    services::s3_service::{ObjectStorage, S3ServiceError},
+};
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use std::{collections::HashSet, sync::Arc};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Most files a bundle may hold.
+pub const BUNDLE_MAX_FILES: usize = 1000;
+/// Largest bundle, in bytes. It keeps every offset of the zip within 32 bits, so no ZIP64 records
+/// are needed.
+pub const BUNDLE_MAX_BYTES: usize = 2 * 1024 * 1024 * 1024;
+/// The tag of the files that bundles are stored as.
+pub const BUNDLE_TAG: &str = "bundle";
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x08074b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+const LOCAL_HEADER_LEN: usize = 30;
+const DATA_DESCRIPTOR_LEN: usize = 16;
+const CENTRAL_HEADER_LEN: usize = 46;
+const END_OF_CENTRAL_DIRECTORY_LEN: usize = 22;
+/// Version 2.0, the first to support data descriptors.
+const ZIP_VERSION: u16 = 20;
+/// Longest entry name, in bytes; longer file names are cut to fit.
+const ENTRY_NAME_MAX_BYTES: usize = 255;
+/// Bit 3: the CRC and sizes follow the data in a descriptor; bit 11: names are UTF-8.
+const ZIP_FLAGS: u16 = 0x0808;
+
+#[derive(Error, Debug)]
+pub enum BundleServiceError {
+    #[error("s3 service failure: {0:#?}")]
+    S3(Box<S3ServiceError>),
+    #[error("object of file `{0}` is missing")]
+    ObjectMissing(Uuid),
+    #[error("object of file `{file_id}` has {actual} bytes instead of {expected}")]
+    SizeMismatch {
+        file_id: Uuid,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl From<S3ServiceError> for BundleServiceError {
+    fn from(err: S3ServiceError) -> Self {
+        Self::S3(Box::new(err))
+    }
+}
+
+/// Packs the objects of files into a zip, stored without compression, and uploads it as the
+/// object of another file. The objects are streamed through, so only a part of the upload is held
+/// in memory at a time.
+#[derive(Clone)]
+pub struct BundleService {
+    object_storage: Arc<dyn ObjectStorage>,
+    upload: UploadConfig,
+}
+
+impl BundleService {
+    pub fn new(object_storage: Arc<dyn ObjectStorage>, upload: UploadConfig) -> Self {
+        Self {
+            object_storage,
+            upload,
+        }
+    }
+
+    /// Returns the size of the bundle of the given files, in bytes.
+    pub fn bundle_size(files: &[File]) -> usize {
+        let entries = entry_names(files)
+            .iter()
+            .zip(files)
+            .map(|(name, file)| {
+                LOCAL_HEADER_LEN
+                    + name.len()
+                    + file.size
+                    + DATA_DESCRIPTOR_LEN
+                    + CENTRAL_HEADER_LEN
+                    + name.len()
+            })
+            .sum::<usize>();
+
+        entries + END_OF_CENTRAL_DIRECTORY_LEN
+    }
+
+    /// Writes the bundle of the given files to the multipart upload of `file_id`, which must be
+    /// [`Self::bundle_size`] bytes long, in the parts [`UploadConfig`] splits it into. Returns the
+    /// part numbers and ETags to complete the upload with.
+    #[tracing::instrument(skip_all, fields(file_id = %file_id, file_count = files.len()))]
+    pub async fn write_bundle(
+        &self,
+        file_id: Uuid,
+        upload_id: &str,
+        storage: Option<&FileStorage>,
+        files: &[File],
+    ) -> Result<Vec<(u32, String)>, BundleServiceError> {
+        let size = Self::bundle_size(files);
+        let mut writer = PartWriter {
+            object_storage: self.object_storage.as_ref(),
+            file_id,
+            upload_id,
+            storage,
+            part_size: self.upload.part_size,
+            part_count: self.upload.part_count(size),
+            buffer: Vec::with_capacity(self.upload.part_size),
+            parts: Vec::new(),
+        };
+        let mut central_directory = Vec::new();
+        let mut offset = 0;
+
+        for (name, file) in entry_names(files).iter().zip(files) {
+            let mut body = match self
+                .object_storage
+                .get_file_body(file.id, file.storage.as_ref())
+                .await?
+            {
+                Some(body) => body,
+                None => {
+                    return Err(BundleServiceError::ObjectMissing(file.id));
+                }
+            };
+            let (time, date) = dos_date_time(file.uploaded_at);
+
+            let mut header = Vec::with_capacity(LOCAL_HEADER_LEN + name.len());
+            put_u32(&mut header, LOCAL_HEADER_SIGNATURE);
+            put_u16(&mut header, ZIP_VERSION);
+            put_u16(&mut header, ZIP_FLAGS);
+            // stored, without compression
+            put_u16(&mut header, 0);
+            put_u16(&mut header, time);
+            put_u16(&mut header, date);
+            // the CRC and sizes, given by the data descriptor instead
+            put_u32(&mut header, 0);
+            put_u32(&mut header, 0);
+            put_u32(&mut header, 0);
+            put_u16(&mut header, name.len() as u16);
+            put_u16(&mut header, 0);
+            header.extend_from_slice(name.as_bytes());
+            writer.write(&header).await?;
+
+            let mut hasher = crc32fast::Hasher::new();
+            let mut written = 0;
+
+            while let Some(bytes) = body
+                .try_next()
+                .await
+                .map_err(S3ServiceError::ReadObjectBody)?
+            {
+                hasher.update(&bytes);
+                written += bytes.len();
+                writer.write(&bytes).await?;
+            }
+
+            if written != file.size {
+                return Err(BundleServiceError::SizeMismatch {
+                    file_id: file.id,
+                    expected: file.size,
+                    actual: written,
+                });
+            }
+
+            let crc = hasher.finalize();
+            let mut descriptor = Vec::with_capacity(DATA_DESCRIPTOR_LEN);
+            put_u32(&mut descriptor, DATA_DESCRIPTOR_SIGNATURE);
+            put_u32(&mut descriptor, crc);
+            put_u32(&mut descriptor, written as u32);
+            put_u32(&mut descriptor, written as u32);
+            writer.write(&descriptor).await?;
+
+            put_u32(&mut central_directory, CENTRAL_HEADER_SIGNATURE);
+            put_u16(&mut central_directory, ZIP_VERSION);
+            put_u16(&mut central_directory, ZIP_VERSION);
+            put_u16(&mut central_directory, ZIP_FLAGS);
+            put_u16(&mut central_directory, 0);
+            put_u16(&mut central_directory, time);
+            put_u16(&mut central_directory, date);
+            put_u32(&mut central_directory, crc);
+            put_u32(&mut central_directory, written as u32);
+            put_u32(&mut central_directory, written as u32);
+            put_u16(&mut central_directory, name.len() as u16);
+            // extra field, comment, disk number, internal and external attributes
+            put_u16(&mut central_directory, 0);
+            put_u16(&mut central_directory, 0);
+            put_u16(&mut central_directory, 0);
+            put_u16(&mut central_directory, 0);
+            put_u32(&mut central_directory, 0);
+            put_u32(&mut central_directory, offset as u32);
+            central_directory.extend_from_slice(name.as_bytes());
+
+            offset += header.len() + written + descriptor.len();
+        }
+
+        let mut end = Vec::with_capacity(END_OF_CENTRAL_DIRECTORY_LEN);
+        put_u32(&mut end, END_OF_CENTRAL_DIRECTORY_SIGNATURE);
+        // disk numbers
+        put_u16(&mut end, 0);
+        put_u16(&mut end, 0);
+        put_u16(&mut end, files.len() as u16);
+        put_u16(&mut end, files.len() as u16);
+        put_u32(&mut end, central_directory.len() as u32);
+        put_u32(&mut end, offset as u32);
+        // comment
+        put_u16(&mut end, 0);
+
+        writer.write(&central_directory).await?;
+        writer.write(&end).await?;
+
+        Ok(writer.finish().await?)
+    }
+}
+
+/// Buffers the bundle into the parts of its upload and uploads each once it is full. Every part
+/// but the last has the part size; the last one takes the remainder too, as for client uploads.
+struct PartWriter<'a> {
+    object_storage: &'a dyn ObjectStorage,
+    file_id: Uuid,
+    upload_id: &'a str,
+    storage: Option<&'a FileStorage>,
+    part_size: usize,
+    part_count: u32,
+    buffer: Vec<u8>,
+    parts: Vec<(u32, String)>,
+}
+
+impl PartWriter<'_> {
+    async fn write(&mut self, mut bytes: &[u8]) -> Result<(), S3ServiceError> {
+        while !bytes.is_empty() {
+            let is_last_part = self.parts.len() as u32 + 1 == self.part_count;
+            let len = match is_last_part {
+                true => bytes.len(),
+                false => bytes.len().min(self.part_size - self.buffer.len()),
+            };
+
+            self.buffer.extend_from_slice(&bytes[..len]);
+            bytes = &bytes[len..];
+
+            if !is_last_part && self.buffer.len() == self.part_size {
+                self.upload_part().await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn finish(mut self) -> Result<Vec<(u32, String)>, S3ServiceError> {
+        if !self.buffer.is_empty() || self.parts.is_empty() {
+            self.upload_part().await?;
+        }
+
+        Ok(self.parts)
+    }
+
+    async fn upload_part(&mut self) -> Result<(), S3ServiceError> {
+        let part_number = self.parts.len() as u32 + 1;
+        let body = std::mem::replace(&mut self.buffer, Vec::with_capacity(self.part_size));
+        let e_tag = self
+            .object_storage
+            .upload_part(
+                self.file_id,
+                self.upload_id,
+                part_number,
+                body,
+                self.storage,
+            )
+            .await?;
+
+        self.parts.push((part_number, e_tag));
+
+        Ok(())
+    }
+}
+
+/// Names the entries of the files after the files, replacing path separators, cutting long names
+/// and numbering repeated names like `name (2).ext` so that no entry overwrites another when
+/// extracted.
+fn entry_names(files: &[File]) -> Vec<String> {
+    let mut taken = HashSet::with_capacity(files.len());
+
+    files
+        .iter()
+        .map(|file| {
+            let mut name = file.name.replace(['/', '\\'], "_");
+
+            if ENTRY_NAME_MAX_BYTES < name.len() {
+                let mut len = ENTRY_NAME_MAX_BYTES;
+
+                while !name.is_char_boundary(len) {
+                    len -= 1;
+                }
+
+                name.truncate(len);
+            }
+
+            let name = match name.trim() {
+                "" | "." | ".." => file.id.to_string(),
+                _ => name,
+            };
+            let (stem, extension) = match name.rfind('.') {
+                Some(index) if index != 0 => name.split_at(index),
+                _ => (name.as_str(), ""),
+            };
+            let mut candidate = name.clone();
+            let mut number = 2;
+
+            while !taken.insert(candidate.clone()) {
+                candidate = format!("{stem} ({number}){extension}");
+                number += 1;
+            }
+
+            candidate
+        })
+        .collect()
+}
+
+/// Encodes a timestamp as MS-DOS time and date, which start in 1980.
+fn dos_date_time(date_time: DateTime<Utc>) -> (u16, u16) {
+    if date_time.year() < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+
+    let time = (date_time.hour() << 11) | (date_time.minute() << 5) | (date_time.second() / 2);
+    let date = ((date_time.year() as u32 - 1980).min(127) << 9)
+        | (date_time.month() << 5)
+        | date_time.day();
+
+    (time as u16, date as u16)
+}
+
+fn put_u16(buffer: &mut Vec<u8>, value: u16) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
@@ -1,6 +1,9 @@
 use crate::{
-    db::repositories::collection::{self, CollectionRepository},
-    interfaces::{collections, files},
+    db::repositories::{
+        collection::{self, CollectionRepository},
+        name_sort_key,
+    },
+    interfaces::{collections, files, tags},
 };
 use thiserror::Error;
 use uuid::Uuid;
@@ -8,7 +11,32 @@ use uuid::Uuid;
 #[derive(Error, Debug)]
 pub enum CollectionServiceError {
     #[error("repository error: {0:#?}")]
-    RepositoryError(#[from] crate::db::repositories::RepositoryError),
+    RepositoryError(crate::db::repositories::RepositoryError),
+    #[error("the cursor does not match any row")]
+    InvalidCursor,
+    #[error("too many tags: {attempted} of at most {limit}")]
+    TooManyTags { limit: usize, attempted: usize },
+    #[error("file names shared within a collection that requires unique names: {names:?}")]
+    NameConflict { names: Vec<String> },
+    #[error("duplicated entity: `{key}` = `{value}`")]
+    Conflict { key: String, value: String },
+}
+
+impl From<crate::db::repositories::RepositoryError> for CollectionServiceError {
+    fn from(err: crate::db::repositories::RepositoryError) -> Self {
+        match err {
+            crate::db::repositories::RepositoryError::TooManyTags { limit, attempted } => {
+                Self::TooManyTags { limit, attempted }
+            }
+            crate::db::repositories::RepositoryError::NameConflict { names } => {
+                Self::NameConflict { names }
+            }
+            crate::db::repositories::RepositoryError::Conflict { key, value } => {
+                Self::Conflict { key, value }
+            }
+            err => Self::RepositoryError(err),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -35,29 +63,104 @@ impl CollectionService {
         Ok(collection.map(|collection| collections::Collection {
             id: collection.id,
             name: collection.name,
+            unique_names: collection.unique_names,
             created_at: collection.created_at,
             tags: collection.tags,
+            tag_definitions: Vec::new(),
+        }))
+    }
+
+    /// Returns the collection along with how many files it has.
+    pub async fn get_collection_with_file_count(
+        &self,
+        collection_id: Uuid,
+    ) -> Result<Option<(collections::Collection, u64)>, CollectionServiceError> {
+        let collection = self
+            .collection_repository
+            .find_one_with_file_count_by_id(collection_id)
+            .await?;
+
+        Ok(collection.map(|(collection, file_count)| {
+            (
+                collections::Collection {
+                    id: collection.id,
+                    name: collection.name,
+                    unique_names: collection.unique_names,
+                    created_at: collection.created_at,
+                    tags: collection.tags,
+                    tag_definitions: Vec::new(),
+                },
+                file_count,
+            )
         }))
     }
 
+    /// With `verify_cursor`, a cursor whose collection was deleted or renamed is rejected.
+    /// Non-empty `tags` only lists the collections that have all of them.
     pub async fn list_collections(
         &self,
         limit: usize,
         cursor: Option<collections::CollectionCursor>,
+        verify_cursor: bool,
+        tags: &[String],
     ) -> Result<Vec<collections::Collection>, CollectionServiceError> {
         let cursor = cursor.map(|cursor| collection::entities::CollectionCursorEntity {
             id: cursor.id,
+            name_key: cursor.name.to_lowercase(),
             name: cursor.name,
         });
-        let collections = self.collection_repository.list(limit, cursor).await?;
+
+        if let Some(cursor) = cursor.as_ref().filter(|_| verify_cursor) {
+            if !self.collection_repository.cursor_matches(cursor).await? {
+                return Err(CollectionServiceError::InvalidCursor);
+            }
+        }
+
+        let collections = self.collection_repository.list(limit, cursor, tags).await?;
 
         Ok(collections
             .into_iter()
             .map(|collection| collections::Collection {
                 id: collection.id,
                 name: collection.name,
+                unique_names: collection.unique_names,
                 created_at: collection.created_at,
                 tags: collection.tags,
+                tag_definitions: Vec::new(),
+            })
+            .collect())
+    }
+
+    /// Lists collections along with how many files each has, for indexing them.
+    pub async fn list_collections_with_file_counts(
+        &self,
+        limit: usize,
+        cursor: Option<collections::CollectionCursor>,
+    ) -> Result<Vec<(collections::Collection, u64)>, CollectionServiceError> {
+        let cursor = cursor.map(|cursor| collection::entities::CollectionCursorEntity {
+            id: cursor.id,
+            name_key: cursor.name.to_lowercase(),
+            name: cursor.name,
+        });
+        let collections = self
+            .collection_repository
+            .list_with_file_counts(limit, cursor)
+            .await?;
+
+        Ok(collections
+            .into_iter()
+            .map(|(collection, file_count)| {
+                (
+                    collections::Collection {
+                        id: collection.id,
+                        name: collection.name,
+                        unique_names: collection.unique_names,
+                        created_at: collection.created_at,
+                        tags: collection.tags,
+                        tag_definitions: Vec::new(),
+                    },
+                    file_count,
+                )
             })
             .collect())
     }
@@ -67,14 +170,31 @@ impl CollectionService {
         collection_id: Uuid,
         limit: usize,
         cursor: Option<collections::CollectionFileCursor>,
+        sort: collections::CollectionFileListSort,
+        verify_cursor: bool,
     ) -> Result<Vec<files::File>, CollectionServiceError> {
         let cursor = cursor.map(|cursor| collection::entities::CollectionFileCursorEntity {
             id: cursor.id,
+            name_key: match sort {
+                collections::CollectionFileListSort::Name => cursor.name.to_lowercase(),
+                collections::CollectionFileListSort::NameNatural => name_sort_key(&cursor.name),
+            },
             name: cursor.name,
         });
+
+        if let Some(cursor) = cursor.as_ref().filter(|_| verify_cursor) {
+            if !self
+                .collection_repository
+                .file_cursor_matches(cursor)
+                .await?
+            {
+                return Err(CollectionServiceError::InvalidCursor);
+            }
+        }
+
         let files = self
             .collection_repository
-            .list_files(collection_id, limit, cursor)
+            .list_files(collection_id, limit, cursor, sort)
             .await?;
 
         Ok(files
@@ -84,63 +204,114 @@ impl CollectionService {
                 name: file.name,
                 size: file.size,
                 mime_type: file.mime_type,
+                checksum: file.checksum,
+                is_ready: file.is_ready,
+                protected: file.protected,
+                superseded_by: file.superseded_by,
                 uploaded_at: file.uploaded_at,
                 tags: file.tags,
+                storage: file.storage,
+                tag_definitions: Vec::new(),
             })
             .collect())
     }
 
+    /// Returns the created collection along with how many files it has.
     pub async fn create_collection(
         &self,
         collection: collections::CreatingCollection,
-    ) -> Result<collections::Collection, CollectionServiceError> {
-        let collection = self
+    ) -> Result<(collections::Collection, u64), CollectionServiceError> {
+        let (collection, file_count) = self
             .collection_repository
             .create_one(collection::entities::CollectionEntityForCreation {
                 name: collection.name,
+                unique_names: collection.unique_names,
                 tags: collection.tags,
             })
             .await?;
 
-        Ok(collections::Collection {
-            id: collection.id,
-            name: collection.name,
-            created_at: collection.created_at,
-            tags: collection.tags,
-        })
+        Ok((
+            collections::Collection {
+                id: collection.id,
+                name: collection.name,
+                unique_names: collection.unique_names,
+                created_at: collection.created_at,
+                tags: collection.tags,
+                tag_definitions: Vec::new(),
+            },
+            file_count,
+        ))
     }
 
+    /// Returns the updated collection along with how many files it has, whether anything about it
+    /// changed and the tags it had before, or `None` if there is no collection with the id.
+    #[allow(clippy::type_complexity)]
     pub async fn update_collection(
         &self,
         collection_id: Uuid,
         collection: collections::UpdatingCollection,
-    ) -> Result<Option<collections::Collection>, CollectionServiceError> {
+    ) -> Result<Option<(collections::Collection, u64, bool, Vec<String>)>, CollectionServiceError>
+    {
         let collection = self
             .collection_repository
             .update_one(
                 collection::entities::CollectionEntityForUpdate {
                     id: collection_id,
                     name: collection.name,
+                    unique_names: collection.unique_names,
                 },
                 collection.tags_for_creation.unwrap_or_default(),
                 collection.tags_for_deletion.unwrap_or_default(),
             )
             .await?;
 
-        Ok(collection.map(|collection| collections::Collection {
-            id: collection.id,
-            name: collection.name,
-            created_at: collection.created_at,
-            tags: collection.tags,
-        }))
+        Ok(
+            collection.map(|(collection, file_count, changed, tags_before_update)| {
+                (
+                    collections::Collection {
+                        id: collection.id,
+                        name: collection.name,
+                        unique_names: collection.unique_names,
+                        created_at: collection.created_at,
+                        tags: collection.tags,
+                        tag_definitions: Vec::new(),
+                    },
+                    file_count,
+                    changed,
+                    tags_before_update,
+                )
+            }),
+        )
     }
 
+    /// Returns how renaming `tag` to `new_name` would affect collections, and whether any
+    /// collection has `new_name` already.
+    pub async fn preview_tag_rename(
+        &self,
+        tag: &str,
+        new_name: &str,
+        sample_limit: usize,
+    ) -> Result<(tags::TagRenameImpact, bool), CollectionServiceError> {
+        Ok(self
+            .collection_repository
+            .preview_tag_rename(tag, new_name, sample_limit)
+            .await?)
+    }
+
+    /// Renames `tag` on every collection, returning their ids.
+    pub async fn rename_tag(
+        &self,
+        tag: &str,
+        new_name: &str,
+    ) -> Result<Vec<Uuid>, CollectionServiceError> {
+        Ok(self.collection_repository.rename_tag(tag, new_name).await?)
+    }
+
+    /// Returns the tags the collection had.
     pub async fn delete_collection(
         &self,
         collection_id: Uuid,
-    ) -> Result<(), CollectionServiceError> {
-        self.collection_repository.delete_one(collection_id).await?;
-
-        Ok(())
+    ) -> Result<Vec<String>, CollectionServiceError> {
+        Ok(self.collection_repository.delete_one(collection_id).await?)
     }
 }
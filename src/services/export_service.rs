@@ -0,0 +1,316 @@
+use crate::{
+    interfaces::{
+        collections::CollectionDownloadManifestFile,
+        files::{File, FileExportFormat},
+    },
+    services::{
+        file_service::{FileService, FileServiceError},
+        s3_service::{ObjectStorage, S3ServiceError},
+    },
+    telemetry::ErrorChain,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt, TryStreamExt,
+};
+use std::{sync::Arc, time::Duration};
+use thiserror::Error;
+use tokio::io::{AsyncWriteExt, BufWriter};
+use uuid::Uuid;
+
+const CSV_HEADER: &str = "id,name,size,mime_type,tags,uploaded_at\n";
+const CSV_TAG_SEPARATOR: &str = ";";
+/// Number of files listed, and then presigned, per page of a download manifest.
+const MANIFEST_PAGE_SIZE: usize = 500;
+/// Maximum number of download URLs presigned at the same time for a download manifest.
+const MANIFEST_PRESIGN_CONCURRENCY: usize = 32;
+
+#[derive(Error, Debug)]
+pub enum ExportServiceError {
+    #[error("file service failure: {0:#?}")]
+    File(#[from] FileServiceError),
+    #[error("s3 service failure: {0:#?}")]
+    S3(Box<S3ServiceError>),
+    #[error("failed to serialize file: {0:#?}")]
+    Serialize(#[from] serde_json::Error),
+    #[error("failed to write export to disk: {0:#?}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A page of the files of a download manifest.
+pub struct ManifestPage {
+    pub files: Vec<CollectionDownloadManifestFile>,
+    /// Number of files of the page left out because their object is missing.
+    pub missing_count: u64,
+    /// The id of the last file listed, to continue after; `None` once no file is left.
+    pub last_file_id: Option<Uuid>,
+}
+
+#[derive(Clone)]
+pub struct ExportService {
+    file_service: FileService,
+    object_storage: Arc<dyn ObjectStorage>,
+}
+
+impl ExportService {
+    pub fn new(file_service: FileService, object_storage: Arc<dyn ObjectStorage>) -> Self {
+        Self {
+            file_service,
+            object_storage,
+        }
+    }
+
+    /// Returns the object key that the export of the given admin task is stored at.
+    pub fn object_key(task_id: Uuid, format: FileExportFormat) -> String {
+        format!("exports/{}.{}", task_id, format.extension())
+    }
+
+    /// Returns the object key that the download manifest of the given admin task is stored at.
+    pub fn manifest_object_key(task_id: Uuid) -> String {
+        format!("manifests/{task_id}.json")
+    }
+
+    /// Presigns the download URLs of the next page of ready files tagged with every one of
+    /// `tags`, after `after` if given, at most [`MANIFEST_PRESIGN_CONCURRENCY`] at a time.
+    #[tracing::instrument(skip_all)]
+    pub async fn presign_manifest_page(
+        &self,
+        tags: &[String],
+        after: Option<Uuid>,
+        expires_in: Duration,
+    ) -> Result<ManifestPage, ExportServiceError> {
+        let files = self
+            .file_service
+            .list_files_with_all_tags(tags, after, MANIFEST_PAGE_SIZE)
+            .await?;
+        // collected up front: a stream mapping to them trips the `Send` checks of the callers
+        let presigns = Vec::from_iter(files.iter().map(|file| {
+            self.object_storage.generate_presigned_url_for_download(
+                file.id,
+                expires_in,
+                file.storage.as_ref(),
+            )
+        }));
+        let urls = stream::iter(presigns)
+            .buffered(MANIFEST_PRESIGN_CONCURRENCY)
+            .try_collect::<Vec<_>>()
+            .await
+            .map_err(|err| ExportServiceError::S3(Box::new(err)))?;
+
+        let mut page = ManifestPage {
+            files: Vec::with_capacity(files.len()),
+            missing_count: 0,
+            last_file_id: files.last().map(|file| file.id),
+        };
+
+        for (file, url) in files.into_iter().zip(urls) {
+            match url {
+                Some(url) => page.files.push(CollectionDownloadManifestFile {
+                    id: file.id,
+                    name: file.name,
+                    size: file.size,
+                    url,
+                }),
+                None => {
+                    tracing::warn!("object of ready file `{}` is missing", file.id);
+                    page.missing_count += 1;
+                }
+            }
+        }
+
+        Ok(page)
+    }
+
+    /// Writes the download manifest of a collection with the given tags to a temporary file, page
+    /// by page, and uploads it to `key`. Returns the numbers of listed and of missing files.
+    pub async fn export_manifest_to_object(
+        &self,
+        collection_id: Uuid,
+        tags: &[String],
+        expires_in: Duration,
+        key: &str,
+    ) -> Result<(u64, u64), ExportServiceError> {
+        let path =
+            std::env::temp_dir().join(format!("file-indexer-manifest-{}.json", Uuid::new_v4()));
+
+        let result = self
+            .write_manifest_to_path(collection_id, tags, expires_in, &path)
+            .await;
+        let result = match result {
+            Ok(counts) => self
+                .object_storage
+                .put_object_from_path(key, "application/json", &path)
+                .await
+                .map(|_| counts)
+                .map_err(|err| ExportServiceError::S3(Box::new(err))),
+            Err(err) => Err(err),
+        };
+
+        if let Err(err) = tokio::fs::remove_file(&path).await {
+            tracing::warn!(
+                error = %ErrorChain(&err),
+                path = %path.display(),
+                "failed to remove temporary manifest"
+            );
+        }
+
+        result
+    }
+
+    /// Writes a [`CollectionDownloadManifest`](crate::interfaces::collections::CollectionDownloadManifest)
+    /// without holding it in memory; the files are written as they are presigned.
+    async fn write_manifest_to_path(
+        &self,
+        collection_id: Uuid,
+        tags: &[String],
+        expires_in: Duration,
+        path: &std::path::Path,
+    ) -> Result<(u64, u64), ExportServiceError> {
+        let mut writer = BufWriter::new(tokio::fs::File::create(path).await?);
+        // the first URL is presigned after this, so none expires before it
+        let expires_at: DateTime<Utc> = Utc::now() + expires_in;
+        let header = format!(
+            "{{\"collectionId\":{},\"expiresAt\":{},\"files\":[",
+            serde_json::to_string(&collection_id)?,
+            serde_json::to_string(&expires_at.to_rfc3339_opts(SecondsFormat::Millis, true))?,
+        );
+        writer.write_all(header.as_bytes()).await?;
+
+        let mut file_count = 0;
+        let mut missing_count = 0;
+        let mut after = None;
+
+        loop {
+            let page = self.presign_manifest_page(tags, after, expires_in).await?;
+
+            for file in &page.files {
+                if file_count != 0 {
+                    writer.write_all(b",").await?;
+                }
+
+                writer
+                    .write_all(serde_json::to_string(file)?.as_bytes())
+                    .await?;
+                file_count += 1;
+            }
+
+            missing_count += page.missing_count;
+
+            match page.last_file_id {
+                Some(last_file_id) => after = Some(last_file_id),
+                None => break,
+            }
+        }
+
+        writer
+            .write_all(format!("],\"missingCount\":{missing_count}}}").as_bytes())
+            .await?;
+        writer.flush().await?;
+
+        Ok((file_count, missing_count))
+    }
+
+    /// Streams the ready-file catalog encoded in the given format, one line per item.
+    /// For csv, the first line is the header.
+    pub fn stream_files(
+        &self,
+        format: FileExportFormat,
+    ) -> BoxStream<'_, Result<String, ExportServiceError>> {
+        let rows = self.file_service.stream_ready_files().map(move |file| {
+            let file = file?;
+
+            match format {
+                FileExportFormat::Ndjson => Ok(format!("{}\n", serde_json::to_string(&file)?)),
+                FileExportFormat::Csv => Ok(csv_row(&file)),
+            }
+        });
+
+        match format {
+            FileExportFormat::Ndjson => rows.boxed(),
+            FileExportFormat::Csv => futures::stream::once(async { Ok(CSV_HEADER.to_owned()) })
+                .chain(rows)
+                .boxed(),
+        }
+    }
+
+    /// Writes the whole catalog to a temporary file and uploads it to `key`.
+    /// Returns the number of exported files.
+    pub async fn export_files_to_object(
+        &self,
+        format: FileExportFormat,
+        key: &str,
+    ) -> Result<usize, ExportServiceError> {
+        let path = std::env::temp_dir().join(format!(
+            "file-indexer-export-{}.{}",
+            Uuid::new_v4(),
+            format.extension()
+        ));
+
+        let result = self.write_files_to_path(format, &path).await;
+        let result = match result {
+            Ok(count) => self
+                .object_storage
+                .put_object_from_path(key, format.content_type(), &path)
+                .await
+                .map(|_| count)
+                .map_err(|err| ExportServiceError::S3(Box::new(err))),
+            Err(err) => Err(err),
+        };
+
+        if let Err(err) = tokio::fs::remove_file(&path).await {
+            tracing::warn!(
+                error = %ErrorChain(&err),
+                path = %path.display(),
+                "failed to remove temporary export"
+            );
+        }
+
+        result
+    }
+
+    async fn write_files_to_path(
+        &self,
+        format: FileExportFormat,
+        path: &std::path::Path,
+    ) -> Result<usize, ExportServiceError> {
+        let mut writer = BufWriter::new(tokio::fs::File::create(path).await?);
+        let mut rows = self.stream_files(format);
+        let mut count = 0;
+
+        while let Some(row) = rows.next().await {
+            writer.write_all(row?.as_bytes()).await?;
+            count += 1;
+        }
+
+        writer.flush().await?;
+
+        if format == FileExportFormat::Csv {
+            // the header is not a file
+            count -= 1;
+        }
+
+        Ok(count)
+    }
+}
+
+fn csv_row(file: &File) -> String {
+    format!(
+        "{},{},{},{},{},{}\n",
+        file.id,
+        escape_csv_field(&file.name),
+        file.size,
+        escape_csv_field(&file.mime_type),
+        escape_csv_field(&file.tags.join(CSV_TAG_SEPARATOR)),
+        file.uploaded_at.to_rfc3339(),
+    )
+}
+
+/// Quotes a field if it contains a separator, quote, or line break, doubling embedded quotes.
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
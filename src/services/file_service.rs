@@ -1,28 +1,104 @@
 use crate::{
-    db::repositories::file::{self, FileRepository},
-    interfaces::files,
+    config::StorageCostConfig,
+    db::repositories::{
+        file::{self, FileRepository},
+        name_sort_key,
+    },
+    interfaces::{files, tags},
+    telemetry::ErrorChain,
 };
 use chrono::DateTime;
+use futures::{stream::BoxStream, StreamExt};
 use sqlx::types::chrono::Utc;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 use thiserror::Error;
 use uuid::Uuid;
 
+/// How long further views of a file by the same admin count as the view already recorded.
+const VIEW_COALESCE_WINDOW: Duration = Duration::from_secs(60);
+
 #[derive(Error, Debug)]
 pub enum FileServiceError {
     #[error("repository error: {0:#?}")]
-    RepositoryError(#[from] crate::db::repositories::RepositoryError),
+    RepositoryError(crate::db::repositories::RepositoryError),
+    #[error("the size and the mime type of a ready file cannot be changed")]
+    ReadyFileContentChange,
+    #[error("the cursor does not match any file")]
+    InvalidCursor,
+    #[error("too many tags: {attempted} of at most {limit}")]
+    TooManyTags { limit: usize, attempted: usize },
+    #[error("file names shared within a collection that requires unique names: {names:?}")]
+    NameConflict { names: Vec<String> },
+    #[error("duplicated entity: `{key}` = `{value}`")]
+    Conflict { key: String, value: String },
+    #[error("invalid superseding file: {reason}")]
+    InvalidSupersession { reason: String },
+}
+
+impl From<crate::db::repositories::RepositoryError> for FileServiceError {
+    fn from(err: crate::db::repositories::RepositoryError) -> Self {
+        match err {
+            crate::db::repositories::RepositoryError::TooManyTags { limit, attempted } => {
+                Self::TooManyTags { limit, attempted }
+            }
+            crate::db::repositories::RepositoryError::NameConflict { names } => {
+                Self::NameConflict { names }
+            }
+            crate::db::repositories::RepositoryError::Conflict { key, value } => {
+                Self::Conflict { key, value }
+            }
+            crate::db::repositories::RepositoryError::InvalidSupersession { reason } => {
+                Self::InvalidSupersession { reason }
+            }
+            err => Self::RepositoryError(err),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct FileService {
     file_repository: FileRepository,
+    /// Where new files are stored.
+    storage: files::FileStorage,
+    /// When each admin last had a view of each file recorded, within [`VIEW_COALESCE_WINDOW`].
+    recorded_views: Arc<Mutex<HashMap<(Uuid, Uuid), Instant>>>,
 }
 
 impl FileService {
-    pub fn new(file_repository: FileRepository) -> Self {
-        Self { file_repository }
+    pub fn new(file_repository: FileRepository, storage: files::FileStorage) -> Self {
+        Self {
+            file_repository,
+            storage,
+            recorded_views: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Records the current storage on at most `limit` of the files that have none, i.e. those
+    /// created before files recorded where they are stored. Returns how many were updated.
+    #[tracing::instrument(skip_all)]
+    pub async fn backfill_storage(&self, limit: usize) -> Result<u64, FileServiceError> {
+        Ok(self
+            .file_repository
+            .backfill_storage(&self.storage, limit)
+            .await?)
+    }
+
+    /// Returns the storage of each of the given files that exists and has one.
+    #[tracing::instrument(skip_all)]
+    pub async fn get_storages(
+        &self,
+        file_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, files::FileStorage>, FileServiceError> {
+        let storages = self.file_repository.find_storages(file_ids).await?;
+
+        Ok(storages.into_iter().collect())
     }
 
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
     pub async fn get_file(&self, file_id: Uuid) -> Result<Option<files::File>, FileServiceError> {
         let file = self.file_repository.find_one_by_id(file_id).await?;
 
@@ -31,30 +107,148 @@ impl FileService {
             name: file.name,
             size: file.size,
             mime_type: file.mime_type,
+            checksum: file.checksum,
+            is_ready: file.is_ready,
+            protected: file.protected,
+            superseded_by: file.superseded_by,
             uploaded_at: file.uploaded_at,
             tags: file.tags,
+            storage: file.storage,
+            tag_definitions: Vec::new(),
         }))
     }
 
+    /// Gets the ready files among `file_ids`, in id order.
+    #[tracing::instrument(skip_all, fields(count = file_ids.len()))]
+    pub async fn get_files(&self, file_ids: &[Uuid]) -> Result<Vec<files::File>, FileServiceError> {
+        let files = self.file_repository.find_many_by_ids(file_ids).await?;
+
+        Ok(files
+            .into_iter()
+            .map(|file| files::File {
+                id: file.id,
+                name: file.name,
+                size: file.size,
+                mime_type: file.mime_type,
+                checksum: file.checksum,
+                is_ready: file.is_ready,
+                protected: file.protected,
+                superseded_by: file.superseded_by,
+                uploaded_at: file.uploaded_at,
+                tags: file.tags,
+                storage: file.storage,
+                tag_definitions: Vec::new(),
+            })
+            .collect())
+    }
+
+    /// Gets the latest ready version of the file, following what supersedes it. Returns the file
+    /// itself if nothing ready supersedes it.
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    pub async fn get_latest_file(
+        &self,
+        file_id: Uuid,
+    ) -> Result<Option<files::File>, FileServiceError> {
+        match self.file_repository.find_latest_version_id(file_id).await? {
+            Some(latest_id) => self.get_file(latest_id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the size, MIME type and storage of the file, along with the upload it has active.
+    #[allow(clippy::type_complexity)]
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
     pub async fn get_file_for_upload(
         &self,
         file_id: Uuid,
-    ) -> Result<Option<(usize, String)>, FileServiceError> {
+    ) -> Result<Option<(usize, String, Option<files::FileStorage>, Option<String>)>, FileServiceError>
+    {
         let result = self.file_repository.find_one_for_upload(file_id).await?;
 
-        Ok(result.map(|result| (result.size, result.mime_type)))
+        Ok(result.map(|result| {
+            (
+                result.size,
+                result.mime_type,
+                result.storage,
+                result.active_upload_id,
+            )
+        }))
+    }
+
+    /// Returns the id, the active upload and the storage of an unready file identical to the one
+    /// being created, as long as its upload is active. Files without a checksum have no duplicates.
+    #[allow(clippy::type_complexity)]
+    #[tracing::instrument(skip_all)]
+    pub async fn find_uploading_duplicate(
+        &self,
+        file: &files::CreatingFile,
+    ) -> Result<Option<(Uuid, String, Option<files::FileStorage>)>, FileServiceError> {
+        let checksum = match &file.checksum {
+            Some(checksum) => checksum,
+            None => {
+                return Ok(None);
+            }
+        };
+        let duplicate = self
+            .file_repository
+            .find_one_uploading_duplicate(&file.name, file.size, checksum)
+            .await?;
+
+        Ok(
+            duplicate
+                .map(|duplicate| (duplicate.id, duplicate.active_upload_id, duplicate.storage)),
+        )
     }
 
+    /// Returns whether the file is ready along with the multipart upload that completed it, the
+    /// size of the file and its storage.
+    #[allow(clippy::type_complexity)]
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    pub async fn get_file_for_completion(
+        &self,
+        file_id: Uuid,
+    ) -> Result<Option<(bool, Option<String>, usize, Option<files::FileStorage>)>, FileServiceError>
+    {
+        let result = self
+            .file_repository
+            .find_one_for_completion(file_id)
+            .await?;
+
+        Ok(result.map(|result| {
+            (
+                result.is_ready,
+                result.upload_id,
+                result.size,
+                result.storage,
+            )
+        }))
+    }
+
+    /// With `verify_cursor`, a cursor whose file was deleted or whose `uploaded_at` differs is
+    /// rejected instead of silently paging from wherever it lands.
+    #[tracing::instrument(skip_all)]
     pub async fn list_files(
         &self,
         limit: usize,
         cursor: Option<files::FileCursor>,
+        verify_cursor: bool,
+        name_prefix: Option<&str>,
     ) -> Result<Vec<files::File>, FileServiceError> {
         let cursor = cursor.map(|cursor| file::entities::FileCursorEntity {
             id: cursor.id,
             uploaded_at: cursor.uploaded_at,
         });
-        let files = self.file_repository.list(limit, cursor).await?;
+
+        if let Some(cursor) = cursor.as_ref().filter(|_| verify_cursor) {
+            if !self.file_repository.cursor_matches(cursor).await? {
+                return Err(FileServiceError::InvalidCursor);
+            }
+        }
+
+        let files = self
+            .file_repository
+            .list(limit, cursor, name_prefix)
+            .await?;
 
         Ok(files
             .into_iter()
@@ -63,12 +257,204 @@ impl FileService {
                 name: file.name,
                 size: file.size,
                 mime_type: file.mime_type,
+                checksum: file.checksum,
+                is_ready: file.is_ready,
+                protected: file.protected,
+                superseded_by: file.superseded_by,
                 uploaded_at: file.uploaded_at,
                 tags: file.tags,
+                storage: file.storage,
+                tag_definitions: Vec::new(),
             })
             .collect())
     }
 
+    /// Lists the files in the natural order of their names, see [`name_sort_key`]. With
+    /// `verify_cursor`, a cursor whose file was deleted or renamed is rejected.
+    #[tracing::instrument(skip_all)]
+    pub async fn list_files_by_name(
+        &self,
+        limit: usize,
+        cursor: Option<files::FileNameCursor>,
+        verify_cursor: bool,
+        name_prefix: Option<&str>,
+    ) -> Result<Vec<files::File>, FileServiceError> {
+        let cursor = cursor.map(|cursor| file::entities::FileNameCursorEntity {
+            id: cursor.id,
+            name_sort_key: name_sort_key(&cursor.name),
+            name: cursor.name,
+        });
+
+        if let Some(cursor) = cursor.as_ref().filter(|_| verify_cursor) {
+            if !self.file_repository.name_cursor_matches(cursor).await? {
+                return Err(FileServiceError::InvalidCursor);
+            }
+        }
+
+        let files = self
+            .file_repository
+            .list_by_name(limit, cursor, name_prefix)
+            .await?;
+
+        Ok(files
+            .into_iter()
+            .map(|file| files::File {
+                id: file.id,
+                name: file.name,
+                size: file.size,
+                mime_type: file.mime_type,
+                checksum: file.checksum,
+                is_ready: file.is_ready,
+                protected: file.protected,
+                superseded_by: file.superseded_by,
+                uploaded_at: file.uploaded_at,
+                tags: file.tags,
+                storage: file.storage,
+                tag_definitions: Vec::new(),
+            })
+            .collect())
+    }
+
+    /// Lists the ready files tagged with every one of `tags`, in id order, after `after` if given.
+    #[tracing::instrument(skip_all)]
+    pub async fn list_files_with_all_tags(
+        &self,
+        tags: &[String],
+        after: Option<Uuid>,
+        limit: usize,
+    ) -> Result<Vec<files::File>, FileServiceError> {
+        let files = self
+            .file_repository
+            .list_with_all_tags(tags, after, limit)
+            .await?;
+
+        Ok(files
+            .into_iter()
+            .map(|file| files::File {
+                id: file.id,
+                name: file.name,
+                size: file.size,
+                mime_type: file.mime_type,
+                checksum: file.checksum,
+                is_ready: file.is_ready,
+                protected: file.protected,
+                superseded_by: file.superseded_by,
+                uploaded_at: file.uploaded_at,
+                tags: file.tags,
+                storage: file.storage,
+                tag_definitions: Vec::new(),
+            })
+            .collect())
+    }
+
+    /// Lists the ready files without a checksum, with their storage, in id order, after `after` if
+    /// given.
+    #[tracing::instrument(skip_all)]
+    pub async fn list_files_without_checksum(
+        &self,
+        after: Option<Uuid>,
+        limit: usize,
+    ) -> Result<Vec<(Uuid, Option<files::FileStorage>)>, FileServiceError> {
+        Ok(self
+            .file_repository
+            .list_ready_without_checksum(after, limit)
+            .await?)
+    }
+
+    /// Sets the checksum of a file that has none. Returns `false` if the file is gone or has a
+    /// checksum already.
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    pub async fn set_file_checksum(
+        &self,
+        file_id: Uuid,
+        checksum: &str,
+    ) -> Result<bool, FileServiceError> {
+        Ok(self.file_repository.set_checksum(file_id, checksum).await?)
+    }
+
+    /// Returns those of the given files that exist.
+    #[tracing::instrument(skip_all, fields(count = file_ids.len()))]
+    pub async fn find_existing_file_ids(
+        &self,
+        file_ids: &[Uuid],
+    ) -> Result<Vec<Uuid>, FileServiceError> {
+        Ok(self.file_repository.find_existing_ids(file_ids).await?)
+    }
+
+    /// Returns the collections each of the given files belongs to, ordered by name. Files that
+    /// belong to none are left out.
+    #[tracing::instrument(skip_all)]
+    pub async fn get_file_collections(
+        &self,
+        file_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<files::FileCollection>>, FileServiceError> {
+        let collections = self.file_repository.find_collections(file_ids).await?;
+        let mut file_collections = HashMap::<_, Vec<_>>::new();
+
+        for (file_id, collection) in collections {
+            file_collections
+                .entry(file_id)
+                .or_default()
+                .push(collection);
+        }
+
+        Ok(file_collections)
+    }
+
+    /// Returns how renaming `tag` to `new_name` would affect files, and whether any file has
+    /// `new_name` already.
+    #[tracing::instrument(skip_all)]
+    pub async fn preview_tag_rename(
+        &self,
+        tag: &str,
+        new_name: &str,
+        sample_limit: usize,
+    ) -> Result<(tags::TagRenameImpact, bool), FileServiceError> {
+        Ok(self
+            .file_repository
+            .preview_tag_rename(tag, new_name, sample_limit)
+            .await?)
+    }
+
+    /// Renames `tag` on up to `limit` files, returning their ids.
+    #[tracing::instrument(skip_all)]
+    pub async fn rename_tag_batch(
+        &self,
+        tag: &str,
+        new_name: &str,
+        limit: usize,
+    ) -> Result<Vec<Uuid>, FileServiceError> {
+        Ok(self
+            .file_repository
+            .rename_tag_batch(tag, new_name, limit)
+            .await?)
+    }
+
+    pub fn stream_ready_files(&self) -> BoxStream<'_, Result<files::File, FileServiceError>> {
+        self.file_repository
+            .stream_ready()
+            .map(|result| {
+                result
+                    .map(|file| files::File {
+                        id: file.id,
+                        name: file.name,
+                        size: file.size,
+                        mime_type: file.mime_type,
+                        checksum: file.checksum,
+                        is_ready: file.is_ready,
+                        protected: file.protected,
+                        superseded_by: file.superseded_by,
+                        uploaded_at: file.uploaded_at,
+                        tags: file.tags,
+                        storage: file.storage,
+                        tag_definitions: Vec::new(),
+                    })
+                    .map_err(|err| err.into())
+            })
+            .boxed()
+    }
+
+    #[tracing::instrument(skip_all)]
     pub async fn create_file(
         &self,
         file: files::CreatingFile,
@@ -79,7 +465,9 @@ impl FileService {
                 name: file.name,
                 size: file.size,
                 mime_type: file.mime_type,
+                checksum: file.checksum.map(|checksum| checksum.to_ascii_lowercase()),
                 tags: file.tags.unwrap_or_default(),
+                storage: self.storage.clone(),
             })
             .await?;
 
@@ -88,16 +476,145 @@ impl FileService {
             name: file.name,
             size: file.size,
             mime_type: file.mime_type,
+            checksum: file.checksum,
+            is_ready: file.is_ready,
+            protected: file.protected,
+            superseded_by: file.superseded_by,
             uploaded_at: file.uploaded_at,
             tags: file.tags,
+            storage: file.storage,
+            tag_definitions: Vec::new(),
         })
     }
 
+    /// Creates the next version of the file, to be uploaded like a new file. Returns `None` if
+    /// there is no file with the id.
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    pub async fn create_file_version(
+        &self,
+        file_id: Uuid,
+        version: files::CreatingFileVersion,
+    ) -> Result<Option<files::FileVersion>, FileServiceError> {
+        let version = self
+            .file_repository
+            .create_version(
+                file_id,
+                file::entities::FileEntityForVersion {
+                    name: version.name,
+                    size: version.size,
+                    mime_type: version.mime_type,
+                    checksum: version
+                        .checksum
+                        .map(|checksum| checksum.to_ascii_lowercase()),
+                    storage: self.storage.clone(),
+                },
+            )
+            .await?;
+
+        Ok(version.map(|version| version.into()))
+    }
+
+    /// Lists every version of the file by version number, the file itself included. Returns
+    /// `None` if there is no file with the id.
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    pub async fn list_file_versions(
+        &self,
+        file_id: Uuid,
+    ) -> Result<Option<Vec<files::FileVersion>>, FileServiceError> {
+        let versions = self.file_repository.find_versions(file_id).await?;
+
+        Ok(versions.map(|versions| versions.into_iter().map(|version| version.into()).collect()))
+    }
+
+    /// Creates every file or none of them, returning them in the order given.
+    #[tracing::instrument(skip_all)]
+    pub async fn create_files(
+        &self,
+        files: Vec<files::CreatingFile>,
+    ) -> Result<Vec<files::File>, FileServiceError> {
+        let files = self
+            .file_repository
+            .create_many(Vec::from_iter(files.into_iter().map(|file| {
+                file::entities::FileEntityForCreation {
+                    name: file.name,
+                    size: file.size,
+                    mime_type: file.mime_type,
+                    checksum: file.checksum.map(|checksum| checksum.to_ascii_lowercase()),
+                    tags: file.tags.unwrap_or_default(),
+                    storage: self.storage.clone(),
+                }
+            })))
+            .await?;
+
+        Ok(files
+            .into_iter()
+            .map(|file| files::File {
+                id: file.id,
+                name: file.name,
+                size: file.size,
+                mime_type: file.mime_type,
+                checksum: file.checksum,
+                is_ready: file.is_ready,
+                protected: file.protected,
+                superseded_by: file.superseded_by,
+                uploaded_at: file.uploaded_at,
+                tags: file.tags,
+                storage: file.storage,
+                tag_definitions: Vec::new(),
+            })
+            .collect())
+    }
+
+    /// Adds `add` to and removes `remove` from every one of the files at once, reporting what
+    /// happened to each of them in the order of `file_ids`.
+    #[tracing::instrument(skip_all, fields(count = file_ids.len()))]
+    pub async fn update_tags_of_files(
+        &self,
+        file_ids: &[Uuid],
+        add: &[String],
+        remove: &[String],
+    ) -> Result<Vec<files::BulkTaggedFile>, FileServiceError> {
+        let changes = self
+            .file_repository
+            .update_tags_many(file_ids, add, remove)
+            .await?;
+        let mut changes =
+            HashMap::<_, _>::from_iter(changes.into_iter().map(|change| (change.file_id, change)));
+
+        Ok(file_ids
+            .iter()
+            .map(|&file_id| match changes.remove(&file_id) {
+                Some(change) => files::BulkTaggedFile {
+                    file_id,
+                    outcome: if change.added.is_empty() && change.removed.is_empty() {
+                        files::BulkTagOutcome::Unchanged
+                    } else {
+                        files::BulkTagOutcome::Updated
+                    },
+                    added: change.added,
+                    removed: change.removed,
+                },
+                None => files::BulkTaggedFile {
+                    file_id,
+                    outcome: files::BulkTagOutcome::NotFound,
+                    added: Vec::new(),
+                    removed: Vec::new(),
+                },
+            })
+            .collect())
+    }
+
+    /// Returns the updated file along with whether anything about it changed, or `None` if there
+    /// is no file with the id. The size and the MIME type can only be changed until the upload
+    /// completes; changing them afterwards fails as a whole with
+    /// [`FileServiceError::ReadyFileContentChange`].
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
     pub async fn update_file(
         &self,
         file_id: Uuid,
         file: files::UpdatingFile,
-    ) -> Result<Option<files::File>, FileServiceError> {
+    ) -> Result<Option<(files::File, bool)>, FileServiceError> {
+        let changes_content = file.size.is_some() || file.mime_type.is_some();
         let file = self
             .file_repository
             .update_one(
@@ -106,52 +623,401 @@ impl FileService {
                     name: file.name,
                     size: file.size,
                     mime_type: file.mime_type,
+                    protected: file.protected,
+                    superseded_by: file.superseded_by,
                 },
                 file.tags_for_creation.unwrap_or_default(),
                 file.tags_for_deletion.unwrap_or_default(),
             )
             .await?;
 
-        Ok(file.map(|file| files::File {
-            id: file.id,
-            name: file.name,
-            size: file.size,
-            mime_type: file.mime_type,
-            uploaded_at: file.uploaded_at,
-            tags: file.tags,
+        // the update is refused for a ready file as well as for a missing one
+        if file.is_none()
+            && changes_content
+            && self
+                .file_repository
+                .find_one_by_id(file_id)
+                .await?
+                .is_some()
+        {
+            return Err(FileServiceError::ReadyFileContentChange);
+        }
+
+        Ok(file.map(|(file, changed)| {
+            (
+                files::File {
+                    id: file.id,
+                    name: file.name,
+                    size: file.size,
+                    mime_type: file.mime_type,
+                    checksum: file.checksum,
+                    is_ready: file.is_ready,
+                    protected: file.protected,
+                    superseded_by: file.superseded_by,
+                    uploaded_at: file.uploaded_at,
+                    tags: file.tags,
+                    storage: file.storage,
+                    tag_definitions: Vec::new(),
+                },
+                changed,
+            )
         }))
     }
 
+    /// Marks the file as ready, returning it along with the earlier versions of it that it
+    /// supersedes from now on, which have to be re-indexed.
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
     pub async fn mark_file_as_ready(
         &self,
         file_id: Uuid,
-    ) -> Result<Option<files::File>, FileServiceError> {
-        let file = self.file_repository.update_one_as_ready(file_id).await?;
+        upload_id: &str,
+    ) -> Result<Option<(files::File, Vec<Uuid>)>, FileServiceError> {
+        let file = self
+            .file_repository
+            .update_one_as_ready(file_id, upload_id)
+            .await?;
 
-        Ok(file.map(|file| files::File {
-            id: file.id,
-            name: file.name,
-            size: file.size,
-            mime_type: file.mime_type,
-            uploaded_at: file.uploaded_at,
-            tags: file.tags,
+        Ok(file.map(|(file, superseded_file_ids)| {
+            (
+                files::File {
+                    id: file.id,
+                    name: file.name,
+                    size: file.size,
+                    mime_type: file.mime_type,
+                    checksum: file.checksum,
+                    is_ready: file.is_ready,
+                    protected: file.protected,
+                    superseded_by: file.superseded_by,
+                    uploaded_at: file.uploaded_at,
+                    tags: file.tags,
+                    storage: file.storage,
+                    tag_definitions: Vec::new(),
+                },
+                superseded_file_ids,
+            )
         }))
     }
 
+    /// Lists the ids of the files whose id is in `(after, until]`, in id order, with whether each
+    /// is ready. Files stored outside the default bucket are left out.
+    #[tracing::instrument(skip_all)]
+    pub async fn list_file_states(
+        &self,
+        after: Option<Uuid>,
+        until: Option<Uuid>,
+    ) -> Result<Vec<(Uuid, bool)>, FileServiceError> {
+        let files = self
+            .file_repository
+            .list_states_between(after, until, &self.storage.bucket)
+            .await?;
+
+        Ok(files
+            .into_iter()
+            .map(|file| (file.id, file.is_ready))
+            .collect())
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn mark_files_as_unready(&self, file_ids: &[Uuid]) -> Result<u64, FileServiceError> {
+        Ok(self
+            .file_repository
+            .update_many_as_unready(file_ids)
+            .await?)
+    }
+
+    /// Returns those of the given files that are protected from deletion.
+    #[tracing::instrument(skip_all, fields(count = file_ids.len()))]
+    pub async fn get_protected_file_ids(
+        &self,
+        file_ids: &[Uuid],
+    ) -> Result<Vec<Uuid>, FileServiceError> {
+        Ok(self.file_repository.find_protected_ids(file_ids).await?)
+    }
+
+    /// Returns the files superseded by any of the given files, which have to be re-indexed once
+    /// the given files are deleted.
+    #[tracing::instrument(skip_all, fields(count = file_ids.len()))]
+    pub async fn get_superseded_file_ids(
+        &self,
+        file_ids: &[Uuid],
+    ) -> Result<Vec<Uuid>, FileServiceError> {
+        Ok(self.file_repository.find_superseded_ids(file_ids).await?)
+    }
+
+    /// Deletes the file, unless it is protected.
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
     pub async fn delete_file(&self, file_id: Uuid) -> Result<(), FileServiceError> {
         self.file_repository.delete_one(file_id).await?;
 
         Ok(())
     }
 
+    /// Deletes the files that are not protected, returning how many were deleted.
+    #[tracing::instrument(skip_all, fields(count = file_ids.len()))]
+    pub async fn delete_files(&self, file_ids: &[Uuid]) -> Result<u64, FileServiceError> {
+        Ok(self.file_repository.delete_many(file_ids).await?)
+    }
+
+    /// Returns the ids, storages and active uploads of the deleted files, and the ids of the
+    /// protected files that were kept.
+    #[allow(clippy::type_complexity)]
+    #[tracing::instrument(skip_all)]
     pub async fn delete_unready_files(
         &self,
         before_uploaded_at: DateTime<Utc>,
-    ) -> Result<(), FileServiceError> {
-        self.file_repository
+    ) -> Result<
+        (
+            Vec<(Uuid, Option<files::FileStorage>, Option<String>)>,
+            Vec<Uuid>,
+        ),
+        FileServiceError,
+    > {
+        Ok(self
+            .file_repository
             .delete_unready_many(before_uploaded_at)
+            .await?)
+    }
+
+    /// Records that a multipart upload of the file has started.
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    pub async fn record_upload_started(
+        &self,
+        file_id: Uuid,
+        upload_id: &str,
+        part_count: u32,
+        total_bytes: usize,
+    ) -> Result<(), FileServiceError> {
+        Ok(self
+            .file_repository
+            .create_upload_record(file_id, upload_id, part_count, total_bytes)
+            .await?)
+    }
+
+    /// Records that a multipart upload of the file has started as its active upload, returning
+    /// whether it did. Nothing is recorded if the file has an active upload already.
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    pub async fn record_active_upload_started(
+        &self,
+        file_id: Uuid,
+        upload_id: &str,
+        part_count: u32,
+        total_bytes: usize,
+    ) -> Result<bool, FileServiceError> {
+        Ok(self
+            .file_repository
+            .create_active_upload_record(file_id, upload_id, part_count, total_bytes)
+            .await?)
+    }
+
+    /// Records the outcome of a pending upload, returning whether it was pending.
+    #[tracing::instrument(skip_all)]
+    pub async fn record_upload_finished(
+        &self,
+        upload_id: &str,
+        outcome: files::UploadOutcome,
+    ) -> Result<bool, FileServiceError> {
+        Ok(self
+            .file_repository
+            .finish_upload_record(upload_id, outcome)
+            .await?)
+    }
+
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    pub async fn list_upload_records(
+        &self,
+        file_id: Uuid,
+    ) -> Result<Vec<files::FileUploadRecord>, FileServiceError> {
+        let records = self.file_repository.list_upload_records(file_id).await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| files::FileUploadRecord {
+                upload_id: record.upload_id,
+                part_count: record.part_count,
+                total_bytes: record.total_bytes,
+                outcome: record.outcome,
+                started_at: record.started_at,
+                completed_at: record.completed_at,
+            })
+            .collect())
+    }
+
+    /// Returns the upload of the file started last, if it is still pending.
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    pub async fn get_pending_upload_record(
+        &self,
+        file_id: Uuid,
+    ) -> Result<Option<files::FileUploadRecord>, FileServiceError> {
+        let records = self.list_upload_records(file_id).await?;
+
+        Ok(records
+            .into_iter()
+            .next()
+            .filter(|record| record.outcome == files::UploadOutcome::Pending))
+    }
+
+    /// Returns the file id, the upload id and the file's storage of each upload still pending
+    /// that was started before `before_started_at`.
+    #[tracing::instrument(skip_all)]
+    pub async fn list_stale_uploads(
+        &self,
+        before_started_at: DateTime<Utc>,
+    ) -> Result<Vec<(Uuid, String, Option<files::FileStorage>)>, FileServiceError> {
+        let records = self
+            .file_repository
+            .list_stale_upload_records(before_started_at)
             .await?;
 
-        Ok(())
+        Ok(records
+            .into_iter()
+            .map(|record| (record.file_id, record.upload_id, record.storage))
+            .collect())
+    }
+
+    /// Returns how many upload records were deleted.
+    #[tracing::instrument(skip_all)]
+    pub async fn delete_finished_upload_records(
+        &self,
+        before_completed_at: DateTime<Utc>,
+    ) -> Result<u64, FileServiceError> {
+        Ok(self
+            .file_repository
+            .delete_finished_upload_records(before_completed_at)
+            .await?)
+    }
+
+    /// Records a view of the file by the admin in the background, without waiting for it.
+    /// Views repeated within [`VIEW_COALESCE_WINDOW`] of the recorded one are not counted again.
+    pub fn record_view(&self, admin_id: Uuid, file_id: Uuid) {
+        let now = Instant::now();
+
+        {
+            let mut recorded_views = self.recorded_views.lock().unwrap();
+
+            recorded_views.retain(|_, recorded_at| {
+                now.saturating_duration_since(*recorded_at) < VIEW_COALESCE_WINDOW
+            });
+
+            if recorded_views.contains_key(&(admin_id, file_id)) {
+                return;
+            }
+
+            recorded_views.insert((admin_id, file_id), now);
+        }
+
+        let file_repository = self.file_repository.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = file_repository.upsert_view(admin_id, file_id).await {
+                tracing::warn!(error = %ErrorChain(&err), "failed to record view of file `{file_id}`");
+            }
+        });
+    }
+
+    /// Lists the ready files the admin viewed last, the latest viewed first.
+    #[tracing::instrument(skip_all, fields(admin_id = %admin_id))]
+    pub async fn list_recently_viewed_files(
+        &self,
+        admin_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<files::File>, FileServiceError> {
+        let files = self
+            .file_repository
+            .list_recently_viewed(admin_id, limit)
+            .await?;
+
+        Ok(files
+            .into_iter()
+            .map(|file| files::File {
+                id: file.id,
+                name: file.name,
+                size: file.size,
+                mime_type: file.mime_type,
+                checksum: file.checksum,
+                is_ready: file.is_ready,
+                protected: file.protected,
+                superseded_by: file.superseded_by,
+                uploaded_at: file.uploaded_at,
+                tags: file.tags,
+                storage: file.storage,
+                tag_definitions: Vec::new(),
+            })
+            .collect())
+    }
+
+    /// Estimates the monthly cost of storing the ready files at the given rates, broken down by
+    /// the `collection_limit` collections holding the most bytes if given.
+    #[tracing::instrument(skip_all)]
+    pub async fn get_cost_report(
+        &self,
+        storage_cost: &StorageCostConfig,
+        collection_limit: Option<usize>,
+    ) -> Result<files::FileCostReport, FileServiceError> {
+        let usage = self.file_repository.cost_report(collection_limit).await?;
+        // every object is in the standard class, see `files::StorageClass`
+        let storage_class = files::StorageClass::Standard;
+        let cost = storage_cost.monthly_cost(storage_class, usage.bytes);
+
+        Ok(files::FileCostReport {
+            file_count: usage.file_count,
+            bytes: usage.bytes,
+            cost,
+            storage_classes: vec![files::StorageClassCost {
+                storage_class,
+                rate_per_gb_month: storage_cost.rate(storage_class),
+                file_count: usage.file_count,
+                bytes: usage.bytes,
+                cost,
+            }],
+            collections: collection_limit.map(|_| {
+                usage
+                    .collections
+                    .into_iter()
+                    .map(|collection| files::CollectionCost {
+                        id: collection.id,
+                        name: collection.name,
+                        file_count: collection.file_count,
+                        bytes: collection.bytes,
+                        cost: storage_cost.monthly_cost(storage_class, collection.bytes),
+                    })
+                    .collect()
+            }),
+        })
+    }
+
+    /// Returns how many file views last made before `before_viewed_at` were deleted.
+    #[tracing::instrument(skip_all)]
+    pub async fn delete_stale_file_views(
+        &self,
+        before_viewed_at: DateTime<Utc>,
+    ) -> Result<u64, FileServiceError> {
+        Ok(self
+            .file_repository
+            .delete_stale_views(before_viewed_at)
+            .await?)
+    }
+}
+
+impl From<file::entities::FileVersionEntity> for files::FileVersion {
+    fn from(version: file::entities::FileVersionEntity) -> Self {
+        let file = version.file;
+
+        Self {
+            version_number: version.version_number,
+            file: files::File {
+                id: file.id,
+                name: file.name,
+                size: file.size,
+                mime_type: file.mime_type,
+                checksum: file.checksum,
+                is_ready: file.is_ready,
+                protected: file.protected,
+                superseded_by: file.superseded_by,
+                uploaded_at: file.uploaded_at,
+                tags: file.tags,
+                storage: file.storage,
+                tag_definitions: Vec::new(),
+            },
+        }
     }
 }
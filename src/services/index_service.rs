@@ -1,273 +1,1686 @@
 use crate::{
+    config::SearchEngineConfig,
     db::search_engine::{
-        COLLECTIONS_INDEX_UID, COLLECTIONS_PRIMARY_KEY, FILES_INDEX_UID, FILES_PRIMARY_KEY,
+        SearchEngine, SearchEngineError, COLLECTIONS_INDEX_UID, COLLECTIONS_PRIMARY_KEY,
+        FILES_INDEX_UID, FILES_PRIMARY_KEY,
     },
     interfaces::{
-        collections::{Collection, CollectionSearchQuery},
-        files::{File, FileSearchQuery},
+        admins::{MeiliTask, MeiliTaskStatus},
+        collections::{Collection, CollectionSearchHit, CollectionSearchQuery},
+        files::{
+            file_size_bucket, File, FileCollection, FileSearchDistinct, FileSearchFacets,
+            FileSearchHit, FileSearchQuery,
+        },
+        CircuitState, IndexSettings, IndexWriteQueue, RankingRule, SearchAttribute,
+        SearchEngineCircuit, SearchHighlights, SearchIndexSettings, SearchMatchingStrategy,
+        TypoTolerance, UpdatingIndexSettings, SEARCH_MAX_LIMIT, SEARCH_MAX_OFFSET,
+        SEARCH_MIN_LIMIT,
     },
 };
 use chrono::{DateTime, Utc};
 use meilisearch_sdk::{
     client::Client,
-    search::{SearchResults, Selectors},
+    request::HttpClient,
+    search::{MatchingStrategies, SearchQuery, SearchResults, Selectors},
+    settings::{MinWordSizeForTypos, Settings, TypoToleranceSettings},
+    tasks::{Task, TasksSearchQuery},
 };
+use rocket::async_trait;
 use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 use thiserror::Error;
 use uuid::Uuid;
 
+/// How long to wait for Meilisearch to apply a settings update.
+const SETTINGS_TASK_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long to wait for Meilisearch to apply a batch of documents whose rejection is handled.
+const INDEX_TASK_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long a malformed file document found by a search is considered being repaired, during
+/// which further searches finding it do not ask for another repair.
+const MALFORMED_REPAIR_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Number of documents deleted per Meilisearch task.
+const DELETE_BATCH_SIZE: usize = 1000;
+/// Consecutive failed calls after which Meilisearch is considered down.
+pub const CIRCUIT_FAILURE_THRESHOLD: u32 = 5;
+/// How long calls are skipped once Meilisearch is considered down, before one is let through to
+/// probe it again.
+pub const CIRCUIT_OPEN_DURATION: Duration = Duration::from_secs(30);
+/// How long other calls are told to wait while a probe is in flight.
+pub const CIRCUIT_PROBE_RETRY_AFTER: Duration = Duration::from_secs(1);
+/// How long a file queued with [`IndexService::queue_file_index`] waits for further writes to it
+/// before it is indexed.
+pub const INDEX_WRITE_WINDOW: Duration = Duration::from_secs(2);
+/// Largest size of a file document, beyond which its tags and collections are truncated. Up to
+/// 500 files are indexed per call, which keeps a call well under the 100 MB payload limit of
+/// Meilisearch.
+const FILE_DOCUMENT_MAX_BYTES: usize = 128 * 1024;
+
 #[derive(Error, Debug)]
 pub enum IndexServiceError {
     #[error("meilisearch error: {0:#?}")]
     MeilisearchError(#[from] meilisearch_sdk::errors::Error),
+    #[error("meilisearch task failed: {0:#?}")]
+    TaskFailed(meilisearch_sdk::errors::MeilisearchError),
+    #[error("meilisearch circuit is open; retry after {retry_after:?}")]
+    CircuitOpen { retry_after: Duration },
+    #[error("meilisearch did not respond within {after:?}")]
+    Timeout { after: Duration },
+}
+
+/// How long guarded calls wait for the search index before failing with
+/// [`IndexServiceError::Timeout`].
+#[derive(Debug, Clone, Copy)]
+pub struct IndexTimeouts {
+    /// Searches and other reads.
+    pub search: Duration,
+    /// Writes, including clearing an index and applying its settings.
+    pub index: Duration,
 }
 
 #[derive(Clone)]
 pub struct IndexService {
-    client: Client,
+    index: Arc<dyn SearchIndex>,
+    timeouts: IndexTimeouts,
+    circuit: Arc<Mutex<Circuit>>,
+    write_queue: Arc<Mutex<WriteQueue>>,
+    /// When the repair of each malformed file document was last asked for.
+    repairs_requested_at: Arc<Mutex<HashMap<Uuid, Instant>>>,
+}
+
+/// Tracks consecutive Meilisearch outages. Once [`CIRCUIT_FAILURE_THRESHOLD`] calls in a row
+/// failed, the circuit opens and guarded calls fail with [`IndexServiceError::CircuitOpen`]
+/// without being attempted, until [`CIRCUIT_OPEN_DURATION`] has passed. A single call is then let
+/// through to probe Meilisearch, while the others keep failing; the probe closes the circuit on
+/// success and reopens it on failure.
+#[derive(Default)]
+pub struct Circuit {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// Whether the probe of the half-open circuit is in flight.
+    probing: bool,
+}
+
+impl Circuit {
+    /// Returns how long calls are still skipped, if the circuit is open at `now` or its probe is
+    /// in flight.
+    pub fn retry_after(&self, now: Instant) -> Option<Duration> {
+        if self.probing {
+            return Some(CIRCUIT_PROBE_RETRY_AFTER);
+        }
+
+        self.opened_at
+            .and_then(|opened_at| {
+                CIRCUIT_OPEN_DURATION.checked_sub(now.saturating_duration_since(opened_at))
+            })
+            .filter(|retry_after| !retry_after.is_zero())
+    }
+
+    pub fn state(&self, now: Instant) -> CircuitState {
+        match (self.opened_at, self.probing, self.retry_after(now)) {
+            (None, _, _) => CircuitState::Closed,
+            (Some(_), true, _) | (Some(_), false, None) => CircuitState::HalfOpen,
+            (Some(_), false, Some(_)) => CircuitState::Open,
+        }
+    }
+
+    /// Lets a call through at `now`, failing with how long to wait while calls are skipped.
+    /// Answers whether the call is the probe of a half-open circuit, which has to be recorded or
+    /// given up with [`Self::end_probe`] before another call is let through.
+    pub fn admit(&mut self, now: Instant) -> Result<bool, Duration> {
+        if let Some(retry_after) = self.retry_after(now) {
+            return Err(retry_after);
+        }
+
+        self.probing = self.opened_at.is_some();
+
+        Ok(self.probing)
+    }
+
+    /// Gives up the probe in flight without an outcome, e.g. because its caller was cancelled.
+    pub fn end_probe(&mut self) {
+        self.probing = false;
+    }
+
+    /// Records the outcome of a call that finished at `now`.
+    pub fn record(&mut self, outage: bool, now: Instant) {
+        if !outage {
+            if self.opened_at.is_some() {
+                tracing::info!("meilisearch is reachable again");
+            }
+
+            *self = Circuit::default();
+            return;
+        }
+
+        self.consecutive_failures += 1;
+        self.probing = false;
+
+        if CIRCUIT_FAILURE_THRESHOLD <= self.consecutive_failures {
+            if self.opened_at.is_none() {
+                tracing::warn!(
+                    consecutive_failures = self.consecutive_failures,
+                    "meilisearch is considered down"
+                );
+            }
+
+            self.opened_at = Some(now);
+        }
+    }
+}
+
+/// Ends the probe of its circuit when dropped, so that a probe whose caller is cancelled before
+/// it is recorded does not keep every other call out.
+struct ProbeGuard<'a>(&'a Mutex<Circuit>);
+
+impl Drop for ProbeGuard<'_> {
+    fn drop(&mut self) {
+        self.0.lock().unwrap().end_probe();
+    }
+}
+
+/// Files waiting to be indexed. A file queued again before it is taken is indexed once, as it is
+/// when it is taken.
+#[derive(Default)]
+struct WriteQueue {
+    /// When each file was first queued.
+    queued_at: HashMap<Uuid, Instant>,
+    coalesced_count: u64,
 }
 
 impl IndexService {
-    pub fn new(client: Client) -> Self {
-        Self { client }
+    pub fn new(index: Arc<dyn SearchIndex>, timeouts: IndexTimeouts) -> Self {
+        Self {
+            index,
+            timeouts,
+            circuit: Arc::new(Mutex::new(Circuit::default())),
+            write_queue: Arc::new(Mutex::new(WriteQueue::default())),
+            repairs_requested_at: Arc::new(Mutex::new(HashMap::new())),
+        }
     }
 
-    pub async fn empty_index(&self) -> Result<(), IndexServiceError> {
-        self.client
-            .index(FILES_INDEX_UID)
-            .delete_all_documents()
+    /// Picks the malformed documents of the search hits whose repair has not been asked for in
+    /// the last [`MALFORMED_REPAIR_INTERVAL`], taking them as asked for now. A document that many
+    /// searches find is thus repaired once, rather than once per search.
+    pub fn file_ids_to_repair(&self, hits: &FileSearchHits) -> Vec<Uuid> {
+        let mut repairs_requested_at = self.repairs_requested_at.lock().unwrap();
+        let now = Instant::now();
+        repairs_requested_at.retain(|_, requested_at| {
+            now.duration_since(*requested_at) < MALFORMED_REPAIR_INTERVAL
+        });
+
+        Vec::from_iter(
+            hits.malformed_file_ids
+                .iter()
+                .copied()
+                .filter(|file_id| repairs_requested_at.insert(*file_id, now).is_none()),
+        )
+    }
+
+    /// Queues the file to be indexed by the index flusher within [`INDEX_WRITE_WINDOW`], together
+    /// with any other write to it in the meantime.
+    pub fn queue_file_index(&self, file_id: Uuid) {
+        let mut write_queue = self.write_queue.lock().unwrap();
+
+        match write_queue.queued_at.entry(file_id) {
+            Entry::Occupied(_) => {
+                write_queue.coalesced_count += 1;
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(Instant::now());
+            }
+        }
+    }
+
+    /// Takes the queued files whose window has passed at `now`, or every queued file with `all`.
+    pub fn take_queued_files(&self, now: Instant, all: bool) -> Vec<Uuid> {
+        let mut write_queue = self.write_queue.lock().unwrap();
+        let mut file_ids = Vec::new();
+
+        write_queue.queued_at.retain(|file_id, queued_at| {
+            let due = all || INDEX_WRITE_WINDOW <= now.saturating_duration_since(*queued_at);
+
+            if due {
+                file_ids.push(*file_id);
+            }
+
+            !due
+        });
+
+        file_ids
+    }
+
+    pub fn write_queue_status(&self) -> IndexWriteQueue {
+        let write_queue = self.write_queue.lock().unwrap();
+
+        IndexWriteQueue {
+            queued_count: write_queue.queued_at.len(),
+            coalesced_count: write_queue.coalesced_count,
+        }
+    }
+
+    pub fn circuit_status(&self) -> SearchEngineCircuit {
+        let circuit = self.circuit.lock().unwrap();
+        let now = Instant::now();
+        let retry_after = circuit.retry_after(now);
+
+        SearchEngineCircuit {
+            state: circuit.state(now),
+            consecutive_failures: circuit.consecutive_failures,
+            retry_after_secs: retry_after.map(|retry_after| retry_after.as_secs().max(1)),
+        }
+    }
+
+    /// Runs a call to the search index through the circuit, failing with
+    /// [`IndexServiceError::CircuitOpen`] without attempting it while the circuit is open or
+    /// another call probes it, and with [`IndexServiceError::Timeout`] once it took longer than
+    /// `timeout`. Only outages, timeouts included, count as failures; an error Meilisearch
+    /// answered with, e.g. for an invalid request, shows that it is up.
+    async fn guarded<T>(
+        &self,
+        timeout: Duration,
+        call: impl Future<Output = Result<T, IndexServiceError>>,
+    ) -> Result<T, IndexServiceError> {
+        let is_probe = match self.circuit.lock().unwrap().admit(Instant::now()) {
+            Ok(is_probe) => is_probe,
+            Err(retry_after) => {
+                return Err(IndexServiceError::CircuitOpen { retry_after });
+            }
+        };
+        let _probe = is_probe.then(|| ProbeGuard(&self.circuit));
+
+        let result = match tokio::time::timeout(timeout, call).await {
+            Ok(result) => result,
+            Err(_) => {
+                tracing::warn!("search engine did not respond within {timeout:?}");
+                Err(IndexServiceError::Timeout { after: timeout })
+            }
+        };
+        self.circuit.lock().unwrap().record(
+            matches!(&result, Err(err) if is_outage(err)),
+            Instant::now(),
+        );
+
+        result
+    }
+
+    /// Returns the task uid of the deletion.
+    #[tracing::instrument(skip_all)]
+    pub async fn empty_files_index(&self) -> Result<u32, IndexServiceError> {
+        self.guarded(self.timeouts.index, self.index.empty_files_index())
+            .await
+    }
+
+    /// Returns the task uid of the deletion.
+    #[tracing::instrument(skip_all)]
+    pub async fn empty_collections_index(&self) -> Result<u32, IndexServiceError> {
+        self.guarded(self.timeouts.index, self.index.empty_collections_index())
+            .await
+    }
+
+    /// Returns the document with the id as it is stored in the index, `None` if there is none.
+    #[tracing::instrument(skip(self))]
+    pub async fn get_raw_document(
+        &self,
+        index: DocumentIndex,
+        id: Uuid,
+    ) -> Result<Option<serde_json::Map<String, serde_json::Value>>, IndexServiceError> {
+        self.guarded(self.timeouts.search, self.index.get_raw_document(index, id))
+            .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn get_index_settings(&self) -> Result<IndexSettings, IndexServiceError> {
+        self.guarded(self.timeouts.search, self.index.get_index_settings())
+            .await
+    }
+
+    /// Applies the settings and waits for the search index to finish processing them.
+    #[tracing::instrument(skip_all)]
+    pub async fn update_index_settings(
+        &self,
+        settings: &UpdatingIndexSettings,
+    ) -> Result<IndexSettings, IndexServiceError> {
+        self.guarded(
+            SETTINGS_TASK_TIMEOUT,
+            self.index.update_index_settings(settings),
+        )
+        .await
+    }
+
+    /// Indexes the file along with the collections it belongs to, which search hits report.
+    #[tracing::instrument(skip_all, fields(task_uid = tracing::field::Empty))]
+    pub async fn index_file(
+        &self,
+        file: &File,
+        collections: &[FileCollection],
+    ) -> Result<u32, IndexServiceError> {
+        let collections = HashMap::from([(file.id, collections.to_vec())]);
+        let task_uid = self
+            .guarded(
+                self.timeouts.index,
+                self.index
+                    .index_files(std::slice::from_ref(file), &collections),
+            )
             .await?;
-        self.client
-            .index(COLLECTIONS_INDEX_UID)
-            .delete_all_documents()
+        record_task_uid(task_uid);
+
+        Ok(task_uid)
+    }
+
+    #[tracing::instrument(skip_all, fields(task_uid = tracing::field::Empty))]
+    pub async fn index_collection(
+        &self,
+        collection: &Collection,
+        file_count: u64,
+    ) -> Result<u32, IndexServiceError> {
+        let task_uid = self
+            .guarded(
+                self.timeouts.index,
+                self.index
+                    .index_collections(&[(collection.clone(), file_count)]),
+            )
             .await?;
+        record_task_uid(task_uid);
 
-        Ok(())
+        Ok(task_uid)
     }
 
-    pub async fn index_file(&self, file: &File) -> Result<(), IndexServiceError> {
-        #[derive(Serialize)]
-        struct IndexingFile<'a> {
-            id: Uuid,
-            name: &'a str,
-            size: usize,
-            mime_type: &'a str,
-            tags: &'a [String],
-            uploaded_at: i64,
+    /// Indexes the files along with the collections each belongs to; files missing from
+    /// `collections` belong to none.
+    #[tracing::instrument(skip_all, fields(count = files.len(), task_uid = tracing::field::Empty))]
+    pub async fn index_files(
+        &self,
+        files: &[File],
+        collections: &HashMap<Uuid, Vec<FileCollection>>,
+    ) -> Result<u32, IndexServiceError> {
+        let task_uid = self
+            .guarded(
+                self.timeouts.index,
+                self.index.index_files(files, collections),
+            )
+            .await?;
+        record_task_uid(task_uid);
+
+        Ok(task_uid)
+    }
+
+    /// Indexes the files like [`IndexService::index_files`], but waits for Meilisearch to apply
+    /// them. A batch rejected for its documents is bisected with [`bisect_rejected`], so that the
+    /// files Meilisearch rejects on their own are skipped and reported while the others are
+    /// indexed. Outages and other failures fail the call as before.
+    #[tracing::instrument(skip_all, fields(count = files.len(), task_uids = tracing::field::Empty))]
+    pub async fn index_files_skipping_rejected(
+        &self,
+        files: &[File],
+        collections: &HashMap<Uuid, Vec<FileCollection>>,
+    ) -> Result<FileIndexing, IndexServiceError> {
+        let bisection = bisect_rejected(files, |batch| async move {
+            let result = async {
+                let task_uid = self
+                    .guarded(
+                        self.timeouts.index,
+                        self.index.index_files(batch, collections),
+                    )
+                    .await?;
+                self.guarded(
+                    INDEX_TASK_TIMEOUT,
+                    self.index.wait_for_task(task_uid, INDEX_TASK_TIMEOUT),
+                )
+                .await?;
+
+                Ok(task_uid)
+            }
+            .await;
+
+            match result {
+                Ok(task_uid) => Ok(BatchOutcome::Accepted(task_uid)),
+                Err(err) => match rejection(&err) {
+                    Some(rejection) => Ok(BatchOutcome::Rejected(rejection.to_string())),
+                    None => Err(err),
+                },
+            }
+        })
+        .await?;
+        record_task_uids(&bisection.accepted);
+
+        for (file, error) in &bisection.rejected {
+            tracing::warn!(file_id = %file.id, error, "meilisearch rejected the file document");
         }
 
-        self.client
-            .index(FILES_INDEX_UID)
-            .add_or_update(
-                &[IndexingFile {
-                    id: file.id,
-                    name: &file.name,
-                    size: file.size,
-                    mime_type: &file.mime_type,
-                    tags: &file.tags,
-                    uploaded_at: file.uploaded_at.timestamp(),
-                }],
-                FILES_PRIMARY_KEY,
+        Ok(FileIndexing {
+            meili_task_uids: bisection.accepted,
+            rejected_files: Vec::from_iter(bisection.rejected.into_iter().map(|(file, error)| {
+                RejectedFile {
+                    file_id: file.id,
+                    error,
+                }
+            })),
+        })
+    }
+
+    #[tracing::instrument(skip_all, fields(count = collections.len(), task_uid = tracing::field::Empty))]
+    pub async fn index_collections(
+        &self,
+        collections: &[(Collection, u64)],
+    ) -> Result<u32, IndexServiceError> {
+        let task_uid = self
+            .guarded(
+                self.timeouts.index,
+                self.index.index_collections(collections),
+            )
+            .await?;
+        record_task_uid(task_uid);
+
+        Ok(task_uid)
+    }
+
+    /// Deletes the files from the index. Returns the uids of the enqueued tasks, none if there is
+    /// nothing to delete.
+    #[tracing::instrument(skip_all, fields(count = file_ids.len(), task_uids = tracing::field::Empty))]
+    pub async fn delete_files(&self, file_ids: &[Uuid]) -> Result<Vec<u32>, IndexServiceError> {
+        let task_uids = self
+            .guarded(self.timeouts.index, self.index.delete_files(file_ids))
+            .await?;
+        record_task_uids(&task_uids);
+
+        Ok(task_uids)
+    }
+
+    /// Deletes the collections from the index. Returns the uids of the enqueued tasks, none if
+    /// there is nothing to delete.
+    #[tracing::instrument(skip_all, fields(count = collection_ids.len(), task_uids = tracing::field::Empty))]
+    pub async fn delete_collections(
+        &self,
+        collection_ids: &[Uuid],
+    ) -> Result<Vec<u32>, IndexServiceError> {
+        let task_uids = self
+            .guarded(
+                self.timeouts.index,
+                self.index.delete_collections(collection_ids),
             )
             .await?;
+        record_task_uids(&task_uids);
+
+        Ok(task_uids)
+    }
+
+    /// Fetches the current state of search index tasks. Tasks the search index no longer knows
+    /// are left out.
+    #[tracing::instrument(skip_all, fields(count = task_uids.len()))]
+    pub async fn get_tasks(&self, task_uids: &[u32]) -> Result<Vec<MeiliTask>, IndexServiceError> {
+        if task_uids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.guarded(self.timeouts.search, self.index.get_tasks(task_uids))
+            .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn search_files(
+        &self,
+        q: &FileSearchQuery,
+    ) -> Result<FileSearchHits, IndexServiceError> {
+        self.guarded(self.timeouts.search, self.index.search_files(q))
+            .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn facet_files(
+        &self,
+        q: &FileSearchQuery,
+    ) -> Result<FileSearchFacets, IndexServiceError> {
+        self.guarded(self.timeouts.search, self.index.facet_files(q))
+            .await
+    }
+
+    /// Resolves the ids of the files matching the query, ignoring its limit.
+    /// At most `max` ids are returned.
+    #[tracing::instrument(skip_all)]
+    pub async fn search_file_ids(
+        &self,
+        q: &FileSearchQuery,
+        max: usize,
+    ) -> Result<FileIdMatches, IndexServiceError> {
+        self.guarded(self.timeouts.search, self.index.search_file_ids(q, max))
+            .await
+    }
+
+    /// Lists the ids of indexed files, the latest uploaded first, whether they are ready or not.
+    #[tracing::instrument(skip_all)]
+    pub async fn latest_file_ids(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Uuid>, IndexServiceError> {
+        self.guarded(
+            self.timeouts.search,
+            self.index.latest_file_ids(offset, limit),
+        )
+        .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn search_collections(
+        &self,
+        q: &CollectionSearchQuery,
+    ) -> Result<Vec<CollectionSearchHit>, IndexServiceError> {
+        self.guarded(self.timeouts.search, self.index.search_collections(q))
+            .await
+    }
+}
+
+/// The indexes searches run against: [`MeilisearchIndex`] in production, or a stand-in such as
+/// `crate::testing::search_index::InMemorySearchIndex` of the `testing` feature. Writes return
+/// the uids of the tasks applying them, whose state [`SearchIndex::get_tasks`] reports.
+#[async_trait]
+pub trait SearchIndex: Send + Sync {
+    /// Returns the task uid of the deletion.
+    async fn empty_files_index(&self) -> Result<u32, IndexServiceError>;
+
+    /// Returns the task uid of the deletion.
+    async fn empty_collections_index(&self) -> Result<u32, IndexServiceError>;
+
+    async fn get_index_settings(&self) -> Result<IndexSettings, IndexServiceError>;
+
+    /// Returns the document with the id as it is stored, `None` if the index has none.
+    async fn get_raw_document(
+        &self,
+        index: DocumentIndex,
+        id: Uuid,
+    ) -> Result<Option<serde_json::Map<String, serde_json::Value>>, IndexServiceError>;
+
+    /// Applies the settings and waits for them to take effect.
+    async fn update_index_settings(
+        &self,
+        settings: &UpdatingIndexSettings,
+    ) -> Result<IndexSettings, IndexServiceError>;
+
+    /// Indexes the files along with the collections each belongs to; files missing from
+    /// `collections` belong to none.
+    async fn index_files(
+        &self,
+        files: &[File],
+        collections: &HashMap<Uuid, Vec<FileCollection>>,
+    ) -> Result<u32, IndexServiceError>;
+
+    async fn index_collections(
+        &self,
+        collections: &[(Collection, u64)],
+    ) -> Result<u32, IndexServiceError>;
+
+    /// Waits up to `timeout` for the task to finish, failing with
+    /// [`IndexServiceError::TaskFailed`] if it failed.
+    async fn wait_for_task(
+        &self,
+        task_uid: u32,
+        timeout: Duration,
+    ) -> Result<(), IndexServiceError>;
+
+    /// Returns the uids of the enqueued tasks, none if there is nothing to delete.
+    async fn delete_files(&self, file_ids: &[Uuid]) -> Result<Vec<u32>, IndexServiceError>;
+
+    /// Returns the uids of the enqueued tasks, none if there is nothing to delete.
+    async fn delete_collections(
+        &self,
+        collection_ids: &[Uuid],
+    ) -> Result<Vec<u32>, IndexServiceError>;
+
+    /// Tasks the search index no longer knows are left out.
+    async fn get_tasks(&self, task_uids: &[u32]) -> Result<Vec<MeiliTask>, IndexServiceError>;
+
+    /// Matches the files against the query, restricted by
+    /// [`filters::build_file_filters`]. Hits whose documents cannot be read are skipped and
+    /// reported instead of failing the search.
+    async fn search_files(&self, q: &FileSearchQuery) -> Result<FileSearchHits, IndexServiceError>;
+
+    /// Counts the files matching the query like [`SearchIndex::search_files`] by size bucket,
+    /// ignoring its limit, offset and `distinct`.
+    async fn facet_files(&self, q: &FileSearchQuery)
+        -> Result<FileSearchFacets, IndexServiceError>;
+
+    /// Resolves the ids of the files matching the query, ignoring its limit.
+    /// At most `max` ids are returned.
+    async fn search_file_ids(
+        &self,
+        q: &FileSearchQuery,
+        max: usize,
+    ) -> Result<FileIdMatches, IndexServiceError>;
+
+    /// Lists the ids of indexed files, the latest uploaded first, whether they are ready or not.
+    async fn latest_file_ids(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Uuid>, IndexServiceError>;
+
+    async fn search_collections(
+        &self,
+        q: &CollectionSearchQuery,
+    ) -> Result<Vec<CollectionSearchHit>, IndexServiceError>;
+}
+
+/// Serves the indexes from Meilisearch.
+pub struct MeilisearchIndex {
+    client: Client,
+}
+
+impl MeilisearchIndex {
+    /// Connects to Meilisearch and sets up its indexes, as [`SearchEngine::init`] does.
+    pub async fn init(config: &SearchEngineConfig) -> Result<Self, SearchEngineError> {
+        let search_engine = SearchEngine::init(config).await?;
+
+        Ok(Self::new(search_engine.into_client()))
+    }
+
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    async fn get_search_index_settings(
+        &self,
+        index_uid: &str,
+    ) -> Result<SearchIndexSettings, IndexServiceError> {
+        let settings = self.client.index(index_uid).get_settings().await?;
+        let typo_tolerance = settings.typo_tolerance.unwrap_or_default();
+        let min_word_size_for_typos = typo_tolerance.min_word_size_for_typos.unwrap_or_default();
+
+        Ok(SearchIndexSettings {
+            synonyms: settings.synonyms.unwrap_or_default().into_iter().collect(),
+            stop_words: settings.stop_words.unwrap_or_default(),
+            typo_tolerance: TypoTolerance {
+                enabled: typo_tolerance.enabled.unwrap_or(true),
+                min_word_size_for_one_typo: min_word_size_for_typos.one_typo.unwrap_or(5),
+                min_word_size_for_two_typos: min_word_size_for_typos.two_typos.unwrap_or(9),
+            },
+            // custom ranking rules (e.g. `uploaded_at:desc`) are not part of the managed subset
+            ranking_rules: settings
+                .ranking_rules
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|rule| RankingRule::parse(rule))
+                .collect(),
+        })
+    }
+
+    async fn set_search_index_settings(
+        &self,
+        index_uid: &str,
+        settings: &SearchIndexSettings,
+    ) -> Result<(), IndexServiceError> {
+        let settings = Settings::new()
+            .with_synonyms(settings.synonyms.clone().into_iter().collect())
+            .with_stop_words(&settings.stop_words)
+            .with_typo_tolerance(TypoToleranceSettings {
+                enabled: Some(settings.typo_tolerance.enabled),
+                disable_on_attributes: None,
+                disable_on_words: None,
+                min_word_size_for_typos: Some(MinWordSizeForTypos {
+                    one_typo: Some(settings.typo_tolerance.min_word_size_for_one_typo),
+                    two_typos: Some(settings.typo_tolerance.min_word_size_for_two_typos),
+                }),
+            })
+            .with_ranking_rules(
+                settings
+                    .ranking_rules
+                    .iter()
+                    .map(|ranking_rule| ranking_rule.to_str()),
+            );
+
+        let task = self
+            .client
+            .index(index_uid)
+            .set_settings(&settings)
+            .await?
+            .wait_for_completion(&self.client, None, Some(SETTINGS_TASK_TIMEOUT))
+            .await?;
+
+        if task.is_failure() {
+            return Err(IndexServiceError::TaskFailed(task.unwrap_failure()));
+        }
 
         Ok(())
     }
 
-    pub async fn index_collection(&self, collection: &Collection) -> Result<(), IndexServiceError> {
-        #[derive(Serialize)]
-        struct IndexingCollection<'a> {
-            id: Uuid,
-            name: &'a str,
-            tags: &'a [String],
-            created_at: i64,
+    /// Deletes the documents, at most [`DELETE_BATCH_SIZE`] per Meilisearch task.
+    /// Counts the documents matching the search with each of the checksums, i.e. the sizes of the
+    /// groups a search distinct by checksum collapsed them into. The facet values are narrowed
+    /// down to the checksums, which a page never has more of than [`SEARCH_MAX_LIMIT`], so that
+    /// none is cut off by `maxValuesPerFacet` (100 by default).
+    async fn count_checksums(
+        &self,
+        q: &FileSearchQuery,
+        filter: &[&str],
+        checksums: &[&str],
+    ) -> Result<Option<HashMap<String, usize>>, IndexServiceError> {
+        if checksums.is_empty() {
+            return Ok(None);
         }
 
-        self.client
+        let checksum_filter = filters::build_checksum_filter(checksums);
+        let filter = Vec::from_iter(filter.iter().copied().chain([checksum_filter.as_str()]));
+        let index = self.client.index(FILES_INDEX_UID);
+        let mut query = index.search();
+        query
+            .with_query(&q.q)
+            .with_limit(0)
+            .with_facets(Selectors::Some(&["checksum"]))
+            .with_array_filter(filter);
+
+        // the same words have to match as in the search
+        if let Some(matching_strategy) = q.matching_strategy {
+            query.with_matching_strategy(match matching_strategy {
+                SearchMatchingStrategy::Last => MatchingStrategies::LAST,
+                SearchMatchingStrategy::All => MatchingStrategies::ALL,
+            });
+        }
+
+        let result: SearchResults<serde_json::Value> = query.execute().await?;
+
+        Ok(result
+            .facet_distribution
+            .and_then(|mut facet_distribution| facet_distribution.remove("checksum")))
+    }
+
+    async fn delete_documents(
+        &self,
+        index_uid: &str,
+        ids: &[Uuid],
+    ) -> Result<Vec<u32>, IndexServiceError> {
+        let index = self.client.index(index_uid);
+        let mut task_uids = Vec::with_capacity(ids.len().div_ceil(DELETE_BATCH_SIZE));
+
+        for chunk in ids.chunks(DELETE_BATCH_SIZE) {
+            let task = index.delete_documents(chunk).await?;
+            task_uids.push(task.get_task_uid());
+        }
+
+        Ok(task_uids)
+    }
+}
+
+#[async_trait]
+impl SearchIndex for MeilisearchIndex {
+    async fn empty_files_index(&self) -> Result<u32, IndexServiceError> {
+        let task = self
+            .client
+            .index(FILES_INDEX_UID)
+            .delete_all_documents()
+            .await?;
+
+        Ok(task.get_task_uid())
+    }
+
+    async fn empty_collections_index(&self) -> Result<u32, IndexServiceError> {
+        let task = self
+            .client
             .index(COLLECTIONS_INDEX_UID)
-            .add_or_update(
-                &[IndexingCollection {
-                    id: collection.id,
-                    name: &collection.name,
-                    tags: &collection.tags,
-                    created_at: collection.created_at.timestamp(),
-                }],
-                COLLECTIONS_PRIMARY_KEY,
-            )
+            .delete_all_documents()
             .await?;
 
-        Ok(())
+        Ok(task.get_task_uid())
     }
 
-    pub async fn index_files(&self, files: &[File]) -> Result<(), IndexServiceError> {
-        #[derive(Serialize)]
-        struct IndexingFile<'a> {
-            id: Uuid,
-            name: &'a str,
-            size: usize,
-            mime_type: &'a str,
-            tags: &'a [String],
-            uploaded_at: i64,
+    async fn get_raw_document(
+        &self,
+        index: DocumentIndex,
+        id: Uuid,
+    ) -> Result<Option<serde_json::Map<String, serde_json::Value>>, IndexServiceError> {
+        use meilisearch_sdk::errors::{Error, ErrorCode};
+
+        let result = self
+            .client
+            .index(index.uid())
+            .get_document(&id.to_string())
+            .await;
+
+        match result {
+            Ok(document) => Ok(Some(document)),
+            Err(Error::Meilisearch(err))
+                if matches!(
+                    err.error_code,
+                    ErrorCode::DocumentNotFound | ErrorCode::IndexNotFound
+                ) =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err.into()),
         }
+    }
 
+    async fn get_index_settings(&self) -> Result<IndexSettings, IndexServiceError> {
+        Ok(IndexSettings {
+            files: self.get_search_index_settings(FILES_INDEX_UID).await?,
+            collections: self
+                .get_search_index_settings(COLLECTIONS_INDEX_UID)
+                .await?,
+        })
+    }
+
+    async fn update_index_settings(
+        &self,
+        settings: &UpdatingIndexSettings,
+    ) -> Result<IndexSettings, IndexServiceError> {
+        if let Some(files) = &settings.files {
+            self.set_search_index_settings(FILES_INDEX_UID, files)
+                .await?;
+        }
+
+        if let Some(collections) = &settings.collections {
+            self.set_search_index_settings(COLLECTIONS_INDEX_UID, collections)
+                .await?;
+        }
+
+        self.get_index_settings().await
+    }
+
+    async fn index_files(
+        &self,
+        files: &[File],
+        collections: &HashMap<Uuid, Vec<FileCollection>>,
+    ) -> Result<u32, IndexServiceError> {
         let indexing_files = files
             .iter()
-            .map(|file| IndexingFile {
-                id: file.id,
-                name: &file.name,
-                size: file.size,
-                mime_type: &file.mime_type,
-                tags: &file.tags,
-                uploaded_at: file.uploaded_at.timestamp(),
+            .map(|file| {
+                IndexingFile::new(
+                    file,
+                    collections
+                        .get(&file.id)
+                        .map(|collections| collections.as_slice())
+                        .unwrap_or_default(),
+                )
             })
             .collect::<Vec<_>>();
 
-        self.client
+        let task = self
+            .client
             .index(FILES_INDEX_UID)
             .add_or_update(&indexing_files, FILES_PRIMARY_KEY)
             .await?;
 
-        Ok(())
+        Ok(task.get_task_uid())
     }
 
-    pub async fn index_collections(
+    async fn index_collections(
         &self,
-        collections: &[Collection],
-    ) -> Result<(), IndexServiceError> {
-        #[derive(Serialize)]
-        struct IndexingCollection<'a> {
-            id: Uuid,
-            name: &'a str,
-            tags: &'a [String],
-            created_at: i64,
-        }
-
+        collections: &[(Collection, u64)],
+    ) -> Result<u32, IndexServiceError> {
         let indexing_collections = collections
             .iter()
-            .map(|collection| IndexingCollection {
-                id: collection.id,
-                name: &collection.name,
-                tags: &collection.tags,
-                created_at: collection.created_at.timestamp(),
-            })
+            .map(|(collection, file_count)| IndexingCollection::new(collection, *file_count))
             .collect::<Vec<_>>();
 
-        self.client
+        let task = self
+            .client
             .index(COLLECTIONS_INDEX_UID)
             .add_or_update(&indexing_collections, COLLECTIONS_PRIMARY_KEY)
             .await?;
 
-        Ok(())
+        Ok(task.get_task_uid())
     }
 
-    pub async fn delete_file(&self, file_id: Uuid) -> Result<(), IndexServiceError> {
-        self.client
-            .index(FILES_INDEX_UID)
-            .delete_document(file_id)
+    async fn wait_for_task(
+        &self,
+        task_uid: u32,
+        timeout: Duration,
+    ) -> Result<(), IndexServiceError> {
+        let task = self
+            .client
+            .wait_for_task(TaskUid(task_uid), None, Some(timeout))
             .await?;
 
+        if task.is_failure() {
+            return Err(IndexServiceError::TaskFailed(task.unwrap_failure()));
+        }
+
         Ok(())
     }
 
-    pub async fn delete_collection(&self, collection_id: Uuid) -> Result<(), IndexServiceError> {
-        self.client
-            .index(COLLECTIONS_INDEX_UID)
-            .delete_document(collection_id)
-            .await?;
+    async fn delete_files(&self, file_ids: &[Uuid]) -> Result<Vec<u32>, IndexServiceError> {
+        self.delete_documents(FILES_INDEX_UID, file_ids).await
+    }
 
-        Ok(())
+    async fn delete_collections(
+        &self,
+        collection_ids: &[Uuid],
+    ) -> Result<Vec<u32>, IndexServiceError> {
+        self.delete_documents(COLLECTIONS_INDEX_UID, collection_ids)
+            .await
+    }
+
+    async fn get_tasks(&self, task_uids: &[u32]) -> Result<Vec<MeiliTask>, IndexServiceError> {
+        let mut query = TasksSearchQuery::new(&self.client);
+        query
+            .with_uids(task_uids)
+            .with_limit(task_uids.len() as u32);
+        let tasks = self.client.get_tasks_with(&query).await?;
+
+        Ok(tasks.results.into_iter().map(to_meili_task).collect())
     }
 
-    pub async fn search_files(&self, q: &FileSearchQuery) -> Result<Vec<File>, IndexServiceError> {
+    async fn search_files(&self, q: &FileSearchQuery) -> Result<FileSearchHits, IndexServiceError> {
         let index = self.client.index(FILES_INDEX_UID);
+        let options = SearchOptions::new(
+            q.matching_strategy,
+            q.attributes_to_highlight.as_deref(),
+            q.crop_length,
+        );
 
         let mut query = index.search();
         query.with_query(&q.q);
-        query.with_limit(q.limit);
-        query.with_attributes_to_highlight(Selectors::Some(&[]));
+        // the routes validate these already; clamp anyway so the engine never sees other values
+        query.with_limit(q.limit.clamp(SEARCH_MIN_LIMIT, SEARCH_MAX_LIMIT) as usize);
+        query.with_offset(q.offset.clamp(0, SEARCH_MAX_OFFSET) as usize);
+        options.apply(&mut query);
 
-        let filter = if q.filters.is_empty() {
-            vec![]
-        } else {
-            Vec::from_iter(
-                q.filters
-                    .iter()
-                    .filter_map(|filters| filters::build_file_filter(filters)),
-            )
+        let filter = filters::build_file_filters(q);
+        let filter = Vec::from_iter(filter.iter().map(|filter| filter.as_str()));
+
+        if let Some(distinct) = q.distinct {
+            let attribute = match distinct {
+                FileSearchDistinct::Checksum => "checksum",
+            };
+            query.with_distinct(attribute);
+        }
+
+        // each hit is read on its own, so that a malformed document does not fail the search
+        let result: SearchResults<serde_json::Map<String, serde_json::Value>> = query
+            .with_array_filter(filter.clone())
+            .build()
+            .execute()
+            .await?;
+        let mut files = Vec::with_capacity(result.hits.len());
+        let mut hits = FileSearchHits::default();
+
+        for hit in result.hits {
+            match SearchedFile::from_document(hit.result) {
+                Ok(file) => files.push((file, hit.formatted_result)),
+                Err(file_id) => hits.skip(file_id),
+            }
+        }
+
+        let checksum_counts = match q.distinct {
+            Some(FileSearchDistinct::Checksum) => {
+                let checksums = Vec::from_iter(
+                    files
+                        .iter()
+                        .filter_map(|(file, _)| file.checksum.as_deref()),
+                );
+                self.count_checksums(q, &filter, &checksums).await?
+            }
+            None => None,
         };
+
+        for (file, formatted_result) in files {
+            let duplicate_count = match (&checksum_counts, &file.checksum) {
+                (Some(checksum_counts), Some(checksum)) => checksum_counts.get(checksum).copied(),
+                _ => None,
+            };
+
+            hits.hits
+                .push(file.into_hit(options.highlights(formatted_result), duplicate_count));
+        }
+
+        Ok(hits)
+    }
+
+    async fn facet_files(
+        &self,
+        q: &FileSearchQuery,
+    ) -> Result<FileSearchFacets, IndexServiceError> {
+        let index = self.client.index(FILES_INDEX_UID);
+        let filter = filters::build_file_filters(q);
+        let filter = Vec::from_iter(filter.iter().map(|filter| filter.as_str()));
+
+        let result: SearchResults<serde_json::Value> = index
+            .search()
+            .with_query(&q.q)
+            .with_limit(0)
+            .with_facets(Selectors::Some(&["size_bucket"]))
+            .with_array_filter(filter)
+            .build()
+            .execute()
+            .await?;
+        let counts = result
+            .facet_distribution
+            .and_then(|mut facet_distribution| facet_distribution.remove("size_bucket"))
+            .unwrap_or_default();
+
+        Ok(FileSearchFacets::from_size_bucket_counts(&counts))
+    }
+
+    async fn search_file_ids(
+        &self,
+        q: &FileSearchQuery,
+        max: usize,
+    ) -> Result<FileIdMatches, IndexServiceError> {
+        const PAGE_SIZE: usize = 1000;
+
+        let index = self.client.index(FILES_INDEX_UID);
+        let filter = filters::build_file_filters(q);
         let filter = Vec::from_iter(filter.iter().map(|filter| filter.as_str()));
 
         #[derive(Deserialize)]
-        struct SearchedFile {
+        struct SearchedFileId {
             id: Uuid,
-            name: String,
-            size: usize,
-            mime_type: String,
-            tags: Vec<String>,
-            uploaded_at: i64,
         }
 
-        let result: SearchResults<SearchedFile> =
-            query.with_array_filter(filter).build().execute().await?;
+        let mut ids = Vec::new();
 
-        Ok(result
-            .hits
-            .into_iter()
-            .map(|hit| File {
-                id: hit.result.id,
-                name: hit.result.name,
-                size: hit.result.size,
-                mime_type: hit.result.mime_type,
-                tags: hit.result.tags,
-                uploaded_at: DateTime::<Utc>::from_timestamp(hit.result.uploaded_at, 0)
-                    .unwrap_or_default(),
-            })
-            .collect())
+        // one past `max` tells whether more files matched
+        while ids.len() <= max {
+            let limit = PAGE_SIZE.min(max + 1 - ids.len());
+            let result: SearchResults<SearchedFileId> = index
+                .search()
+                .with_query(&q.q)
+                .with_offset(ids.len())
+                .with_limit(limit)
+                .with_attributes_to_retrieve(Selectors::Some(&["id"]))
+                .with_array_filter(filter.clone())
+                .build()
+                .execute()
+                .await?;
+            let count = result.hits.len();
+
+            ids.extend(result.hits.into_iter().map(|hit| hit.result.id));
+
+            if count < limit {
+                break;
+            }
+        }
+
+        Ok(FileIdMatches::new(ids, max))
     }
 
-    pub async fn search_collections(
+    async fn latest_file_ids(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Uuid>, IndexServiceError> {
+        #[derive(Deserialize)]
+        struct SearchedFileId {
+            id: Uuid,
+        }
+
+        let result: SearchResults<SearchedFileId> = self
+            .client
+            .index(FILES_INDEX_UID)
+            .search()
+            .with_offset(offset)
+            .with_limit(limit)
+            .with_sort(&["uploaded_at:desc"])
+            .with_attributes_to_retrieve(Selectors::Some(&["id"]))
+            .build()
+            .execute()
+            .await?;
+
+        Ok(result.hits.into_iter().map(|hit| hit.result.id).collect())
+    }
+
+    async fn search_collections(
         &self,
         q: &CollectionSearchQuery,
-    ) -> Result<Vec<Collection>, IndexServiceError> {
+    ) -> Result<Vec<CollectionSearchHit>, IndexServiceError> {
         let index = self.client.index(COLLECTIONS_INDEX_UID);
+        let options = SearchOptions::new(
+            q.matching_strategy,
+            q.attributes_to_highlight.as_deref(),
+            q.crop_length,
+        );
 
         let mut query = index.search();
         query.with_query(&q.q);
-        query.with_limit(q.limit);
-
-        #[derive(Deserialize)]
-        struct SearchedCollection {
-            id: Uuid,
-            name: String,
-            created_at: i64,
-            tags: Vec<String>,
-        }
+        // the routes validate these already; clamp anyway so the engine never sees other values
+        query.with_limit(q.limit.clamp(SEARCH_MIN_LIMIT, SEARCH_MAX_LIMIT) as usize);
+        query.with_offset(q.offset.clamp(0, SEARCH_MAX_OFFSET) as usize);
+        options.apply(&mut query);
 
         let result: SearchResults<SearchedCollection> = query.build().execute().await?;
 
         Ok(result
             .hits
             .into_iter()
-            .map(|hit| Collection {
-                id: hit.result.id,
-                name: hit.result.name,
-                created_at: DateTime::<Utc>::from_timestamp(hit.result.created_at, 0)
-                    .unwrap_or_default(),
-                tags: hit.result.tags,
+            .map(|hit| {
+                hit.result
+                    .into_hit(options.highlights(hit.formatted_result))
             })
             .collect())
     }
 }
 
-mod filters {
-    use crate::interfaces::files::FileSearchQueryFilter;
+/// Matching strategy, highlighting and cropping options of a search, in the shape the query
+/// builder borrows them.
+pub(crate) struct SearchOptions {
+    matching_strategy: Option<SearchMatchingStrategy>,
+    attributes_to_highlight: Vec<&'static str>,
+    attributes_to_crop: Vec<(&'static str, Option<usize>)>,
+    crop_length: Option<usize>,
+}
 
+impl SearchOptions {
+    pub(crate) fn new(
+        matching_strategy: Option<SearchMatchingStrategy>,
+        attributes_to_highlight: Option<&[SearchAttribute]>,
+        crop_length: Option<usize>,
+    ) -> Self {
+        let attributes_to_highlight = Vec::from_iter(
+            attributes_to_highlight
+                .unwrap_or_default()
+                .iter()
+                .map(|attribute| attribute.to_str()),
+        );
+        let attributes_to_crop = match crop_length {
+            Some(_) if attributes_to_highlight.is_empty() => vec![
+                (SearchAttribute::Name.to_str(), None),
+                (SearchAttribute::Tags.to_str(), None),
+            ],
+            Some(_) => Vec::from_iter(
+                attributes_to_highlight
+                    .iter()
+                    .map(|attribute| (*attribute, None)),
+            ),
+            None => vec![],
+        };
+
+        Self {
+            matching_strategy,
+            attributes_to_highlight,
+            attributes_to_crop,
+            crop_length,
+        }
+    }
+
+    fn apply<'a>(&'a self, query: &mut SearchQuery<'a, impl HttpClient>) {
+        query.with_attributes_to_highlight(Selectors::Some(&self.attributes_to_highlight));
+
+        if let Some(matching_strategy) = self.matching_strategy {
+            query.with_matching_strategy(match matching_strategy {
+                SearchMatchingStrategy::Last => MatchingStrategies::LAST,
+                SearchMatchingStrategy::All => MatchingStrategies::ALL,
+            });
+        }
+
+        if let Some(crop_length) = self.crop_length {
+            query.with_attributes_to_crop(Selectors::Some(&self.attributes_to_crop));
+            query.with_crop_length(crop_length);
+        }
+    }
+
+    pub(crate) fn highlights(
+        &self,
+        formatted: Option<serde_json::Map<String, serde_json::Value>>,
+    ) -> Option<SearchHighlights> {
+        if self.attributes_to_highlight.is_empty() && self.crop_length.is_none() {
+            return None;
+        }
+
+        let mut formatted = formatted?;
+
+        Some(SearchHighlights {
+            name: formatted
+                .remove("name")
+                .and_then(|name| serde_json::from_value(name).ok()),
+            tags: formatted
+                .remove("tags")
+                .and_then(|tags| serde_json::from_value(tags).ok()),
+        })
+    }
+}
+
+/// A file as it is indexed.
+#[derive(Serialize)]
+pub(crate) struct IndexingFile<'a> {
+    id: Uuid,
+    name: &'a str,
+    size: usize,
+    /// One of [`crate::interfaces::files::FILE_SIZE_BUCKETS`], for filtering and faceting.
+    size_bucket: &'static str,
+    mime_type: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checksum: Option<&'a str>,
+    is_ready: bool,
+    protected: bool,
+    /// Whether the file was replaced by a newer version, for searches to leave it out.
+    superseded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    superseded_by: Option<Uuid>,
+    tags: &'a [String],
+    uploaded_at: i64,
+    collections: &'a [FileCollection],
+    /// Whether `tags` or `collections` were cut short to fit in [`FILE_DOCUMENT_MAX_BYTES`].
+    truncated_for_index: bool,
+    /// When the document was built, in milliseconds, to tell how old the indexed state of the
+    /// file is.
+    indexed_at: i64,
+}
+
+impl<'a> IndexingFile<'a> {
+    /// Builds the document of the file. A document over [`FILE_DOCUMENT_MAX_BYTES`] keeps only
+    /// the longest prefix of its tags, and then of its collections, that fits. Both come in a
+    /// stable order, so a file is always truncated the same way.
+    pub(crate) fn new(file: &'a File, collections: &'a [FileCollection]) -> Self {
+        let mut document = Self {
+            id: file.id,
+            name: &file.name,
+            size: file.size,
+            size_bucket: file_size_bucket(file.size),
+            mime_type: &file.mime_type,
+            checksum: file.checksum.as_deref(),
+            is_ready: file.is_ready,
+            protected: file.protected,
+            superseded: file.superseded_by.is_some(),
+            superseded_by: file.superseded_by,
+            tags: &file.tags,
+            uploaded_at: file.uploaded_at.timestamp_millis(),
+            collections,
+            truncated_for_index: false,
+            indexed_at: Utc::now().timestamp_millis(),
+        };
+        let document_len = serialized_len(&document);
+
+        if document_len <= FILE_DOCUMENT_MAX_BYTES {
+            return document;
+        }
+
+        document.tags = &[];
+        document.collections = &[];
+        document.truncated_for_index = true;
+
+        let mut budget = FILE_DOCUMENT_MAX_BYTES.saturating_sub(serialized_len(&document));
+        let tag_count = fitting_prefix_len(&file.tags, &mut budget);
+        let collection_count = fitting_prefix_len(collections, &mut budget);
+
+        document.tags = &file.tags[..tag_count];
+        document.collections = &collections[..collection_count];
+
+        tracing::warn!(
+            file_id = %file.id,
+            document_len,
+            tag_count = file.tags.len(),
+            indexed_tag_count = tag_count,
+            collection_count = collections.len(),
+            indexed_collection_count = collection_count,
+            "file document is too large; indexing it truncated"
+        );
+
+        document
+    }
+}
+
+/// A collection as it is indexed.
+#[derive(Serialize)]
+pub(crate) struct IndexingCollection<'a> {
+    id: Uuid,
+    name: &'a str,
+    unique_names: bool,
+    tags: &'a [String],
+    created_at: i64,
+    file_count: u64,
+}
+
+impl<'a> IndexingCollection<'a> {
+    pub(crate) fn new(collection: &'a Collection, file_count: u64) -> Self {
+        Self {
+            id: collection.id,
+            name: &collection.name,
+            unique_names: collection.unique_names,
+            tags: &collection.tags,
+            created_at: collection.created_at.timestamp_millis(),
+            file_count,
+        }
+    }
+}
+
+/// A file as search hits return it, tolerating documents indexed by earlier versions.
+#[derive(Deserialize)]
+pub(crate) struct SearchedFile {
+    id: Uuid,
+    name: String,
+    size: usize,
+    mime_type: String,
+    #[serde(default)]
+    pub(crate) checksum: Option<String>,
+    #[serde(default)]
+    is_ready: bool,
+    /// Absent on documents indexed before files could be protected.
+    #[serde(default)]
+    protected: bool,
+    #[serde(default)]
+    superseded_by: Option<Uuid>,
+    tags: Vec<String>,
+    uploaded_at: i64,
+    /// Absent on documents indexed before files carried their collections.
+    #[serde(default)]
+    collections: Vec<FileCollection>,
+    #[serde(default)]
+    truncated_for_index: bool,
+}
+
+impl SearchedFile {
+    /// Reads the document of a hit. A document that cannot be read is logged, and its file id
+    /// returned if it has a readable one.
+    pub(crate) fn from_document(
+        document: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<Self, Option<Uuid>> {
+        let file_id = document
+            .get("id")
+            .and_then(|id| id.as_str())
+            .and_then(|id| id.parse::<Uuid>().ok());
+
+        serde_json::from_value(serde_json::Value::Object(document)).map_err(|err| {
+            tracing::warn!(
+                file_id = file_id.map(tracing::field::display),
+                error = %err,
+                "skipping malformed file document in search hits"
+            );
+            file_id
+        })
+    }
+
+    pub(crate) fn into_hit(
+        self,
+        highlights: Option<SearchHighlights>,
+        duplicate_count: Option<usize>,
+    ) -> FileSearchHit {
+        FileSearchHit {
+            file: File {
+                id: self.id,
+                name: self.name,
+                size: self.size,
+                mime_type: self.mime_type,
+                checksum: self.checksum,
+                is_ready: self.is_ready,
+                protected: self.protected,
+                superseded_by: self.superseded_by,
+                tags: self.tags,
+                uploaded_at: DateTime::<Utc>::from_timestamp_millis(self.uploaded_at)
+                    .unwrap_or_default(),
+                // the search index does not hold where files are stored
+                storage: None,
+                tag_definitions: Vec::new(),
+            },
+            highlights,
+            duplicate_count,
+            collections: self.collections,
+            truncated_for_index: self.truncated_for_index,
+        }
+    }
+}
+
+/// A collection as search hits return it.
+#[derive(Deserialize)]
+pub(crate) struct SearchedCollection {
+    id: Uuid,
+    name: String,
+    #[serde(default)]
+    unique_names: bool,
+    created_at: i64,
+    tags: Vec<String>,
+}
+
+impl SearchedCollection {
+    pub(crate) fn into_hit(self, highlights: Option<SearchHighlights>) -> CollectionSearchHit {
+        CollectionSearchHit {
+            collection: Collection {
+                id: self.id,
+                name: self.name,
+                unique_names: self.unique_names,
+                created_at: DateTime::<Utc>::from_timestamp_millis(self.created_at)
+                    .unwrap_or_default(),
+                tags: self.tags,
+                tag_definitions: Vec::new(),
+            },
+            highlights,
+        }
+    }
+}
+
+/// Counts the bytes of the JSON serialization of the value, without keeping them.
+fn serialized_len(value: &impl Serialize) -> usize {
+    struct Counter(usize);
+
+    impl std::io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    // the documents hold nothing that could fail to serialize
+    let _ = serde_json::to_writer(&mut counter, value);
+
+    counter.0
+}
+
+/// Returns how many leading items fit in the budget as array elements, taking their size, with
+/// a separating comma each, off the budget.
+fn fitting_prefix_len<T: Serialize>(items: &[T], budget: &mut usize) -> usize {
+    let mut count = 0;
+
+    for item in items {
+        let len = serialized_len(item) + 1;
+
+        if *budget < len {
+            break;
+        }
+
+        *budget -= len;
+        count += 1;
+    }
+
+    count
+}
+
+/// When the document was indexed, if it says; documents indexed before they recorded it do not.
+pub fn document_indexed_at(
+    document: &serde_json::Map<String, serde_json::Value>,
+) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(document.get("indexed_at")?.as_i64()?)
+}
+
+/// The index a document belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentIndex {
+    Files,
+    Collections,
+}
+
+impl DocumentIndex {
+    pub fn uid(self) -> &'static str {
+        match self {
+            DocumentIndex::Files => FILES_INDEX_UID,
+            DocumentIndex::Collections => COLLECTIONS_INDEX_UID,
+        }
+    }
+}
+
+/// The ids of the files matching a query, up to a maximum.
+#[derive(Debug, Default)]
+pub struct FileIdMatches {
+    pub file_ids: Vec<Uuid>,
+    /// Whether more files matched than the maximum, which were left out.
+    pub truncated: bool,
+}
+
+impl FileIdMatches {
+    /// Keeps the first `max` of `file_ids`, taking any more as a sign that the matches were cut
+    /// short.
+    pub(crate) fn new(mut file_ids: Vec<Uuid>, max: usize) -> Self {
+        let truncated = max < file_ids.len();
+        file_ids.truncate(max);
+
+        Self {
+            file_ids,
+            truncated,
+        }
+    }
+}
+
+/// The hits of a file search.
+#[derive(Debug, Default)]
+pub struct FileSearchHits {
+    pub hits: Vec<FileSearchHit>,
+    /// Number of hits left out because their documents could not be read, e.g. ones indexed by
+    /// an earlier version without a field now required.
+    pub skipped_hits: usize,
+    /// The files of the skipped hits, except those whose id could not be read either.
+    pub malformed_file_ids: Vec<Uuid>,
+}
+
+impl FileSearchHits {
+    pub(crate) fn skip(&mut self, file_id: Option<Uuid>) {
+        self.skipped_hits += 1;
+        self.malformed_file_ids.extend(file_id);
+    }
+}
+
+/// The uid of a task, as the Meilisearch client takes it.
+struct TaskUid(u32);
+
+impl AsRef<u32> for TaskUid {
+    fn as_ref(&self) -> &u32 {
+        &self.0
+    }
+}
+
+/// The files [`IndexService::index_files_skipping_rejected`] indexed and skipped.
+#[derive(Debug, Clone, Default)]
+pub struct FileIndexing {
+    /// The uids of the tasks that indexed the files, one per batch Meilisearch took.
+    pub meili_task_uids: Vec<u32>,
+    pub rejected_files: Vec<RejectedFile>,
+}
+
+/// Number of rejected files a task records in its metadata; the others are only counted.
+pub const MAX_RECORDED_REJECTED_FILES: usize = 100;
+
+/// A file whose document Meilisearch rejects, and why.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RejectedFile {
+    pub file_id: Uuid,
+    pub error: String,
+}
+
+/// How the search index took a batch of documents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOutcome<T, E> {
+    Accepted(T),
+    /// Rejected for one or more of its documents, so that none of the batch was applied.
+    Rejected(E),
+}
+
+/// The batches [`bisect_rejected`] got accepted, and the documents rejected on their own.
+#[derive(Debug)]
+pub struct Bisection<'a, D, T, E> {
+    pub accepted: Vec<T>,
+    /// In the order of the documents.
+    pub rejected: Vec<(&'a D, E)>,
+}
+
+/// Hands `documents` to `index` as a single batch. A rejected batch is split in halves, which are
+/// handed over in turn, until every document is either in an accepted batch or rejected on its
+/// own. Each rejected document costs about two calls per halving, so a few of them among many
+/// documents are isolated in a few dozen calls. An error of `index` stops the bisection.
+pub async fn bisect_rejected<'a, D, T, E, Err, F, Fut>(
+    documents: &'a [D],
+    mut index: F,
+) -> Result<Bisection<'a, D, T, E>, Err>
+where
+    F: FnMut(&'a [D]) -> Fut,
+    Fut: Future<Output = Result<BatchOutcome<T, E>, Err>>,
+{
+    let mut bisection = Bisection {
+        accepted: Vec::new(),
+        rejected: Vec::new(),
+    };
+    // the batches still to hand over, the next one last
+    let mut batches = vec![documents];
+
+    while let Some(batch) = batches.pop() {
+        if batch.is_empty() {
+            continue;
+        }
+
+        match index(batch).await? {
+            BatchOutcome::Accepted(accepted) => {
+                bisection.accepted.push(accepted);
+            }
+            BatchOutcome::Rejected(error) => match batch {
+                [document] => {
+                    bisection.rejected.push((document, error));
+                }
+                _ => {
+                    let (first, second) = batch.split_at(batch.len() / 2);
+                    batches.push(second);
+                    batches.push(first);
+                }
+            },
+        }
+    }
+
+    Ok(bisection)
+}
+
+/// The error Meilisearch rejected a write for, if it is about the request itself, such as an
+/// invalid document, rather than an outage or a misconfiguration.
+fn rejection(err: &IndexServiceError) -> Option<&meilisearch_sdk::errors::MeilisearchError> {
+    use meilisearch_sdk::errors::{Error, ErrorType};
+
+    match err {
+        IndexServiceError::MeilisearchError(Error::Meilisearch(err))
+        | IndexServiceError::TaskFailed(err)
+            if err.error_type == ErrorType::InvalidRequest =>
+        {
+            Some(err)
+        }
+        _ => None,
+    }
+}
+
+pub mod filters {
+    use crate::interfaces::files::{FileSearchQuery, FileSearchQueryFilter};
+
+    /// Builds the filter groups of a query, restricted to ready and current files unless the query
+    /// opts out.
+    pub fn build_file_filters(q: &FileSearchQuery) -> Vec<String> {
+        let mut filters = Vec::from_iter(
+            q.filters
+                .iter()
+                .filter_map(|filters| build_file_filter(filters)),
+        );
+
+        if !q.include_unready {
+            filters.push("is_ready = true".to_owned());
+        }
+
+        if !q.include_superseded {
+            filters.push("NOT superseded = true".to_owned());
+        }
+
+        filters
+    }
+
+    /// Builds the expression of a single `OR` group. The group is parenthesized, so that it keeps
+    /// its meaning when it is combined with the other groups.
     pub fn build_file_filter(filters: &[FileSearchQueryFilter]) -> Option<String> {
         if filters.is_empty() {
             return None;
         }
 
-        Some(Vec::from_iter(filters.iter().map(build_file_filter_element)).join(" OR "))
+        Some(format!(
+            "({})",
+            Vec::from_iter(filters.iter().map(build_file_filter_element)).join(" OR ")
+        ))
     }
 
     fn build_file_filter_element(filter: &FileSearchQueryFilter) -> String {
@@ -278,18 +1691,134 @@ mod filters {
             FileSearchQueryFilter::MimeType { value } => {
                 format!("mime_type = '{}'", escape_str(value))
             }
+            FileSearchQueryFilter::NotMimeType { value } => {
+                format!("NOT mime_type = '{}'", escape_str(value))
+            }
             FileSearchQueryFilter::Tag { value } => {
                 format!("tags = '{}'", escape_str(value))
             }
+            // `tags != 'x'` would also do, but for array attributes it reads as "some other tag";
+            // `NOT` makes the complement explicit: no element of `tags` equals the value
+            FileSearchQueryFilter::NotTag { value } => {
+                format!("NOT tags = '{}'", escape_str(value))
+            }
             FileSearchQueryFilter::TagIsEmpty => "tags IS EMPTY".to_owned(),
             FileSearchQueryFilter::TagIsNotEmpty => "tags IS NOT EMPTY".to_owned(),
+            // the field is checked against the whitelist on validation, and the whitelisted
+            // attributes are left out of documents while unset, so `EXISTS` means "has a value"
+            FileSearchQueryFilter::Exists { field } => format!("{field} EXISTS"),
+            FileSearchQueryFilter::NotExists { field } => format!("{field} NOT EXISTS"),
+            // documents indexed before the flag existed lack it and were not truncated
+            FileSearchQueryFilter::TruncatedForIndex { value: true } => {
+                "truncated_for_index = true".to_owned()
+            }
+            FileSearchQueryFilter::TruncatedForIndex { value: false } => {
+                "NOT truncated_for_index = true".to_owned()
+            }
+            // documents indexed before files could be protected have no value, and are not
+            FileSearchQueryFilter::Protected { value: true } => "protected = true".to_owned(),
+            FileSearchQueryFilter::Protected { value: false } => "NOT protected = true".to_owned(),
+            // the bucket is checked against the known ones on validation
+            FileSearchQueryFilter::SizeBucket { value } => {
+                format!("size_bucket = '{}'", escape_str(value))
+            }
             FileSearchQueryFilter::UploadedAt { operator, value } => {
-                format!("uploaded_at {} {}", operator.to_str(), value.timestamp())
+                format!(
+                    "uploaded_at {} {}",
+                    operator.to_str(),
+                    value.timestamp_millis()
+                )
             }
         }
     }
 
+    /// Selects the documents with any of the checksums.
+    pub fn build_checksum_filter(checksums: &[&str]) -> String {
+        format!(
+            "checksum IN [{}]",
+            Vec::from_iter(
+                checksums
+                    .iter()
+                    .map(|checksum| format!("'{}'", escape_str(checksum)))
+            )
+            .join(", ")
+        )
+    }
+
+    /// Escapes a value for use inside a single-quoted string. Backslashes go first, so that a
+    /// trailing backslash cannot escape the closing quote.
     fn escape_str(s: &str) -> String {
-        s.replace('\'', "\\'")
+        s.replace('\\', "\\\\").replace('\'', "\\'")
+    }
+}
+
+/// Whether the error means Meilisearch could not serve the call, as opposed to rejecting it.
+fn is_outage(err: &IndexServiceError) -> bool {
+    use meilisearch_sdk::errors::{Error, ErrorType};
+
+    match err {
+        IndexServiceError::MeilisearchError(Error::Meilisearch(err)) => {
+            err.error_type == ErrorType::Internal
+        }
+        IndexServiceError::MeilisearchError(
+            Error::MeilisearchCommunication(_) | Error::HttpError(_) | Error::Timeout,
+        ) => true,
+        IndexServiceError::Timeout { .. } => true,
+        _ => false,
+    }
+}
+
+/// Records the uid of an enqueued task on the current span.
+fn record_task_uid(task_uid: u32) {
+    tracing::Span::current().record("task_uid", task_uid);
+}
+
+/// Records the uids of enqueued tasks on the current span.
+fn record_task_uids(task_uids: &[u32]) {
+    tracing::Span::current().record("task_uids", tracing::field::debug(task_uids));
+}
+
+fn to_meili_task(task: Task) -> MeiliTask {
+    fn to_utc(unix_timestamp_nanos: i128) -> DateTime<Utc> {
+        DateTime::from_timestamp_nanos(unix_timestamp_nanos as i64)
+    }
+
+    match task {
+        Task::Enqueued { content } => MeiliTask {
+            uid: content.uid,
+            index_uid: content.index_uid,
+            status: MeiliTaskStatus::Enqueued,
+            error: None,
+            enqueued_at: to_utc(content.enqueued_at.unix_timestamp_nanos()),
+            started_at: None,
+            finished_at: None,
+        },
+        Task::Processing { content } => MeiliTask {
+            uid: content.uid,
+            index_uid: content.index_uid,
+            status: MeiliTaskStatus::Processing,
+            error: None,
+            enqueued_at: to_utc(content.enqueued_at.unix_timestamp_nanos()),
+            started_at: Some(to_utc(content.started_at.unix_timestamp_nanos())),
+            finished_at: None,
+        },
+        Task::Failed { content } => MeiliTask {
+            uid: content.task.uid,
+            index_uid: content.task.index_uid,
+            status: MeiliTaskStatus::Failed,
+            error: Some(content.error.to_string()),
+            enqueued_at: to_utc(content.task.enqueued_at.unix_timestamp_nanos()),
+            started_at: Some(to_utc(content.task.started_at.unix_timestamp_nanos())),
+            finished_at: Some(to_utc(content.task.finished_at.unix_timestamp_nanos())),
+        },
+        Task::Succeeded { content } => MeiliTask {
+            uid: content.uid,
+            index_uid: content.index_uid,
+            status: MeiliTaskStatus::Succeeded,
+            error: None,
+            enqueued_at: to_utc(content.enqueued_at.unix_timestamp_nanos()),
+            started_at: Some(to_utc(content.started_at.unix_timestamp_nanos())),
+            finished_at: Some(to_utc(content.finished_at.unix_timestamp_nanos())),
+        },
     }
 }
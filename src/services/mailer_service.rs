@@ -0,0 +1,92 @@
+use crate::config::{MailerConfig, SmtpConfig};
+use lettre::{
+    message::Mailbox, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+    AsyncTransport, Message, Tokio1Executor,
+};
+use rocket::async_trait;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MailerServiceError {
+    #[error("invalid mail address: {0:#?}")]
+    InvalidAddress(#[from] lettre::address::AddressError),
+    #[error("failed to build mail: {0:#?}")]
+    BuildMail(#[from] lettre::error::Error),
+    #[error("smtp failure: {0:#?}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+}
+
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivers mails. [`SmtpMailerService`] is used when SMTP is configured, and
+/// [`LogMailerService`] otherwise, which is handy in development.
+#[async_trait]
+pub trait MailerService: Send + Sync {
+    async fn send(&self, mail: Mail) -> Result<(), MailerServiceError>;
+}
+
+pub fn init(config: &MailerConfig) -> Result<Arc<dyn MailerService>, MailerServiceError> {
+    Ok(match &config.smtp {
+        Some(smtp) => Arc::new(SmtpMailerService::new(smtp)?),
+        None => Arc::new(LogMailerService),
+    })
+}
+
+pub struct SmtpMailerService {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailerService {
+    pub fn new(config: &SmtpConfig) -> Result<Self, MailerServiceError> {
+        let mut transport =
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?.port(config.port);
+
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(Self {
+            transport: transport.build(),
+            from: config.from.parse()?,
+        })
+    }
+}
+
+#[async_trait]
+impl MailerService for SmtpMailerService {
+    async fn send(&self, mail: Mail) -> Result<(), MailerServiceError> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(mail.to.parse()?)
+            .subject(mail.subject)
+            .body(mail.body)?;
+        self.transport.send(message).await?;
+
+        Ok(())
+    }
+}
+
+/// Logs mails instead of sending them. The log contains the whole body, secrets included, so it
+/// must not be used in production.
+pub struct LogMailerService;
+
+#[async_trait]
+impl MailerService for LogMailerService {
+    async fn send(&self, mail: Mail) -> Result<(), MailerServiceError> {
+        tracing::info!(
+            to = %mail.to,
+            subject = %mail.subject,
+            "mail body:\n{}",
+            mail.body
+        );
+
+        Ok(())
+    }
+}
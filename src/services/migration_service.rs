@@ -0,0 +1,57 @@
+use crate::{
+    db::database::{self, MigrationEntry, MigrationState},
+    interfaces::admins::{Migration, MigrationStatus},
+};
+use sqlx::PgPool;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MigrationServiceError {
+    #[error("database error: {0:#?}")]
+    DbError(#[from] sqlx::Error),
+}
+
+#[derive(Clone)]
+pub struct MigrationService {
+    db_pool: PgPool,
+}
+
+impl MigrationService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    pub async fn list_migrations(&self) -> Result<Vec<Migration>, MigrationServiceError> {
+        let migrations = database::list_migrations(&self.db_pool).await?;
+
+        Ok(migrations
+            .into_iter()
+            .map(|migration| migration.into())
+            .collect())
+    }
+}
+
+impl From<MigrationEntry> for Migration {
+    fn from(migration: MigrationEntry) -> Self {
+        Self {
+            version: migration.version,
+            description: migration.description,
+            status: match migration.state {
+                MigrationState::Applied => MigrationStatus::Applied,
+                MigrationState::Pending => MigrationStatus::Pending,
+                MigrationState::ChecksumMismatch => MigrationStatus::ChecksumMismatch,
+                MigrationState::Failed => MigrationStatus::Failed,
+                MigrationState::Unknown => MigrationStatus::Unknown,
+            },
+            checksum: migration.checksum.map(|checksum| to_hex(&checksum)),
+            applied_checksum: migration
+                .applied_checksum
+                .map(|applied_checksum| to_hex(&applied_checksum)),
+            installed_on: migration.installed_on,
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
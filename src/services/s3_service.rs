@@ -1,20 +1,24 @@
+use crate::{
+    config::{S3Config, MAX_PRESIGN_DURATION},
+    interfaces::files::FileStorage,
+};
 use aws_config::{meta::region::RegionProviderChain, Region};
 use aws_sdk_s3::{
+    config::http::HttpResponse,
+    error::{ProvideErrorMetadata, SdkError},
     presigning::PresigningConfig,
-    types::{CompletedMultipartUpload, CompletedPart},
+    primitives::ByteStream,
+    types::{ChecksumMode, CompletedMultipartUpload, CompletedPart, Delete, ObjectIdentifier},
 };
-use std::time::Duration;
+use base64::Engine;
+use rocket::async_trait;
+use serde::Serialize;
+use std::{error::Error, path::Path, time::Duration};
 use thiserror::Error;
 use uuid::Uuid;
 
 #[derive(Error, Debug)]
 pub enum S3ServiceError {
-    #[error("environment variable `AWS_REGION` is unable to be retrieved: {0:#?}")]
-    RetrieveAwsRegion(std::env::VarError),
-
-    #[error("environment variable `AWS_S3_BUCKET_NAME` is unable to be retrieved: {0:#?}")]
-    RetrieveAwsS3BucketName(std::env::VarError),
-
     #[error("failed to create multipart upload: {0:#?}")]
     CreateMultipartUpload(
         aws_sdk_s3::error::SdkError<
@@ -36,9 +40,19 @@ pub enum S3ServiceError {
         >,
     ),
 
+    #[error("failed to list multipart uploads: {0:#?}")]
+    ListMultipartUploads(
+        aws_sdk_s3::error::SdkError<
+            aws_sdk_s3::operation::list_multipart_uploads::ListMultipartUploadsError,
+        >,
+    ),
+
     #[error("missing multipart upload id")]
     MissingMultipartUploadId,
 
+    #[error(transparent)]
+    InvalidPresignDuration(#[from] InvalidPresignDuration),
+
     #[error("failed to create presigned url for upload: {0:#?}")]
     CreatePresignedUrlForUpload(
         aws_sdk_s3::error::SdkError<aws_sdk_s3::operation::upload_part::UploadPartError>,
@@ -49,71 +63,510 @@ pub enum S3ServiceError {
         aws_sdk_s3::error::SdkError<aws_sdk_s3::operation::get_object::GetObjectError>,
     ),
 
+    #[error("failed to upload part: {0:#?}")]
+    UploadPart(aws_sdk_s3::error::SdkError<aws_sdk_s3::operation::upload_part::UploadPartError>),
+
+    #[error("missing etag of uploaded part")]
+    MissingPartETag,
+
+    #[error("failed to read object body: {0:#?}")]
+    ReadObjectBody(aws_sdk_s3::primitives::ByteStreamError),
+
+    #[error("failed to put object: {0:#?}")]
+    PutObject(aws_sdk_s3::error::SdkError<aws_sdk_s3::operation::put_object::PutObjectError>),
+
     #[error("failed to delete file: {0:#?}")]
     DeleteFile(
         aws_sdk_s3::error::SdkError<aws_sdk_s3::operation::delete_object::DeleteObjectError>,
     ),
+
+    #[error("failed to delete files: {0:#?}")]
+    DeleteFiles(
+        aws_sdk_s3::error::SdkError<aws_sdk_s3::operation::delete_objects::DeleteObjectsError>,
+    ),
+
+    #[error("failed to list objects: {0:#?}")]
+    ListObjects(
+        aws_sdk_s3::error::SdkError<aws_sdk_s3::operation::list_objects_v2::ListObjectsV2Error>,
+    ),
+
+    #[error("failed to build request: {0:#?}")]
+    BuildRequest(#[from] aws_sdk_s3::error::BuildError),
+
+    /// The bucket denied the request, e.g. for expired credentials or a bucket policy.
+    #[error("{operation} was denied: {error:#?}")]
+    PermissionDenied {
+        operation: &'static str,
+        error: Box<dyn Error + Send + Sync>,
+    },
+
+    /// The request failed in a way that says nothing about the bucket, e.g. a timeout, a dropped
+    /// connection or a server error, so retrying may succeed.
+    #[error("{operation} failed transiently: {error:#?}")]
+    Transient {
+        operation: &'static str,
+        error: Box<dyn Error + Send + Sync>,
+    },
+}
+
+/// A presigned URL duration that is zero or longer than S3 lets presigned URLs stay valid.
+#[derive(Error, Debug)]
+#[error("invalid presigned url duration: {0:?}")]
+pub struct InvalidPresignDuration(pub Duration);
+
+/// Checks that a presigned URL may stay valid for `expires_in`, which must be at least a second
+/// and at most [`MAX_PRESIGN_DURATION`].
+pub fn check_presign_duration(expires_in: Duration) -> Result<(), InvalidPresignDuration> {
+    if expires_in < Duration::from_secs(1) || MAX_PRESIGN_DURATION < expires_in {
+        return Err(InvalidPresignDuration(expires_in));
+    }
+
+    Ok(())
+}
+
+/// Builds the config of a presigned URL valid for `expires_in`; see [`check_presign_duration`].
+fn presigning_config(expires_in: Duration) -> Result<PresigningConfig, InvalidPresignDuration> {
+    check_presign_duration(expires_in)?;
+
+    PresigningConfig::builder()
+        .expires_in(expires_in)
+        .build()
+        .map_err(|_| InvalidPresignDuration(expires_in))
+}
+
+/// Error codes S3 answers for requests it refuses to authorize.
+const PERMISSION_ERROR_CODES: [&str; 6] = [
+    "AccessDenied",
+    "AllAccessDisabled",
+    "ExpiredToken",
+    "InvalidAccessKeyId",
+    "InvalidToken",
+    "SignatureDoesNotMatch",
+];
+
+/// Classifies the error of an existence check. Only a genuine `404` or one of `not_found_codes`
+/// means the resource does not exist, which is `None`; a denied request is
+/// [`S3ServiceError::PermissionDenied`] and any other failure is [`S3ServiceError::Transient`].
+pub fn existence_error<E>(
+    operation: &'static str,
+    err: SdkError<E, HttpResponse>,
+    not_found_codes: &[&str],
+) -> Option<S3ServiceError>
+where
+    E: ProvideErrorMetadata + Error + Send + Sync + 'static,
+{
+    let status = err
+        .raw_response()
+        .map(|response| response.status().as_u16());
+    let code = err.code();
+
+    if status == Some(404) || code.is_some_and(|code| not_found_codes.contains(&code)) {
+        return None;
+    }
+
+    let is_denied =
+        status == Some(403) || code.is_some_and(|code| PERMISSION_ERROR_CODES.contains(&code));
+    let error = Box::new(err);
+
+    if is_denied {
+        Some(S3ServiceError::PermissionDenied { operation, error })
+    } else {
+        Some(S3ServiceError::Transient { operation, error })
+    }
+}
+
+/// The SHA-256 checksum of an object, as lowercase hex.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjectChecksum {
+    /// S3 had the checksum stored with the object.
+    Stored(String),
+    /// The object was downloaded and hashed.
+    Hashed(String),
+    /// There is no object.
+    Missing,
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// A page of object keys, in ascending order.
+#[derive(Debug, Clone)]
+pub struct ObjectPage {
+    pub keys: Vec<String>,
+    /// Present if there are more objects to list.
+    pub continuation_token: Option<String>,
+}
+
+/// A part received by a multipart upload, listed by [`ObjectStorage::list_parts`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListedPart {
+    pub part_number: u32,
+    pub size: u64,
+    pub e_tag: String,
+}
+
+/// The multipart uploads of a key, aborted by [`ObjectStorage::abort_all_uploads_for_key`].
+#[derive(Debug, Clone, Default)]
+pub struct AbortedUploads {
+    /// The uploads that were aborted, or were gone by the time they were aborted.
+    pub upload_ids: Vec<String>,
+    pub failures: Vec<UploadAbortFailure>,
+}
+
+/// A multipart upload that failed to be aborted.
+#[derive(Serialize, Debug, Clone)]
+pub struct UploadAbortFailure {
+    pub upload_id: String,
+    pub error: String,
+}
+
+/// Stores the objects of files, and of exports and bundles, by key. The key of a file's object is
+/// its id. Methods taking a [`FileStorage`] look the object up in that bucket, or in the
+/// configured one if it is `None`.
+///
+/// [`S3Service`] stores them in S3 or an S3-compatible storage;
+/// `crate::testing::object_storage::InMemoryObjectStorage`, built with the `testing` feature,
+/// keeps them in memory for tests.
+#[async_trait]
+pub trait ObjectStorage: Send + Sync {
+    async fn object_exists(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<bool, S3ServiceError>;
+
+    /// Returns the SHA-256 checksum of the object of a file.
+    async fn object_checksum(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<ObjectChecksum, S3ServiceError>;
+
+    /// Opens the object of a file for streaming, or returns `None` if there is no object.
+    async fn get_file_body(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<Option<ByteStream>, S3ServiceError>;
+
+    /// Starts a multipart upload of the object of a file, returning the upload id.
+    async fn create_multipart_upload(
+        &self,
+        file_id: Uuid,
+        mime_type: &str,
+        storage: Option<&FileStorage>,
+    ) -> Result<String, S3ServiceError>;
+
+    /// Uploads a part of a multipart upload from memory, returning its ETag.
+    async fn upload_part(
+        &self,
+        file_id: Uuid,
+        upload_id: &str,
+        part_number: u32,
+        body: Vec<u8>,
+        storage: Option<&FileStorage>,
+    ) -> Result<String, S3ServiceError>;
+
+    /// Assembles the object from the given parts, by part number and ETag. Returns `None` if
+    /// the upload does not exist, e.g. because it was completed or aborted already.
+    async fn complete_multipart_upload(
+        &self,
+        file_id: Uuid,
+        upload_id: String,
+        parts: &[(u32, String)],
+        storage: Option<&FileStorage>,
+    ) -> Result<Option<()>, S3ServiceError>;
+
+    /// Discards a multipart upload and its parts. Returns `None` if the upload does not exist.
+    async fn abort_multipart_upload(
+        &self,
+        file_id: Uuid,
+        upload_id: String,
+        storage: Option<&FileStorage>,
+    ) -> Result<Option<()>, S3ServiceError>;
+
+    /// Lists the parts a multipart upload received so far, by part number. Returns `None` if the
+    /// upload does not exist.
+    async fn list_parts(
+        &self,
+        file_id: Uuid,
+        upload_id: &str,
+        storage: Option<&FileStorage>,
+    ) -> Result<Option<Vec<ListedPart>>, S3ServiceError>;
+
+    /// Aborts every multipart upload still open for the file's key, whoever started it. An upload
+    /// that fails to be aborted is reported in [`AbortedUploads::failures`] rather than failing
+    /// the others; only a failure to list the uploads is an error.
+    async fn abort_all_uploads_for_key(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<AbortedUploads, S3ServiceError>;
+
+    /// Generates a URL a client uploads a part of a multipart upload to with a `PUT`.
+    async fn generate_presigned_url_for_upload(
+        &self,
+        file_id: Uuid,
+        upload_id: &str,
+        part_number: u32,
+        expires_in: Duration,
+        storage: Option<&FileStorage>,
+    ) -> Result<String, S3ServiceError>;
+
+    /// Generates a download URL for the object of a file, or returns `None` if there is no
+    /// object.
+    async fn generate_presigned_url_for_download(
+        &self,
+        file_id: Uuid,
+        expires_in: Duration,
+        storage: Option<&FileStorage>,
+    ) -> Result<Option<String>, S3ServiceError>;
+
+    /// Generates a download URL for an arbitrary object key in the configured bucket, e.g. an
+    /// export produced by an admin task.
+    async fn generate_presigned_url_for_object_download(
+        &self,
+        key: &str,
+        expires_in: Duration,
+    ) -> Result<Option<String>, S3ServiceError>;
+
+    /// Uploads a local file as a single object in the configured bucket.
+    async fn put_object_from_path(
+        &self,
+        key: &str,
+        content_type: &str,
+        path: &Path,
+    ) -> Result<(), S3ServiceError>;
+
+    /// Deletes the object of a file, if there is one.
+    async fn delete_file(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<(), S3ServiceError>;
+
+    /// Deletes the objects of the given files, all kept in `storage`. Keys that do not exist are
+    /// treated as deleted.
+    async fn delete_files(
+        &self,
+        file_ids: &[Uuid],
+        storage: Option<&FileStorage>,
+    ) -> Result<(), S3ServiceError>;
+
+    /// Lists up to 1000 object keys of the configured bucket, continuing after the page the token
+    /// was returned with.
+    async fn list_objects(
+        &self,
+        prefix: Option<&str>,
+        continuation_token: Option<String>,
+    ) -> Result<ObjectPage, S3ServiceError>;
 }
 
+/// Stores the objects in S3, or in an S3-compatible storage if an endpoint is configured.
 #[derive(Clone)]
 pub struct S3Service {
     client: aws_sdk_s3::Client,
     bucket_name: String,
+    region: String,
 }
 
 impl S3Service {
-    pub async fn init() -> Result<Self, S3ServiceError> {
-        let region = std::env::var("AWS_REGION").map_err(S3ServiceError::RetrieveAwsRegion)?;
-        let bucket_name =
-            std::env::var("AWS_S3_BUCKET_NAME").map_err(S3ServiceError::RetrieveAwsS3BucketName)?;
-
-        let region_provider = RegionProviderChain::first_try(Region::new(region.clone()));
+    #[tracing::instrument(skip_all)]
+    pub async fn init(config: &S3Config) -> Result<Self, S3ServiceError> {
+        let region_provider = RegionProviderChain::first_try(Region::new(config.region.clone()));
         let shared_config = aws_config::from_env().region(region_provider).load().await;
-        let client = aws_sdk_s3::Client::new(&shared_config);
+        let mut client_config = aws_sdk_s3::config::Builder::from(&shared_config);
+
+        if let Some(endpoint_url) = &config.endpoint_url {
+            // S3-compatible storages rarely resolve buckets as subdomains
+            client_config = client_config
+                .endpoint_url(endpoint_url)
+                .force_path_style(true);
+        }
+
+        let client = aws_sdk_s3::Client::from_conf(client_config.build());
 
         Ok(Self {
             client,
-            bucket_name,
+            bucket_name: config.bucket_name.clone(),
+            region: config.region.clone(),
         })
     }
 
-    async fn check_file_exists(&self, file_id: Uuid) -> Result<bool, S3ServiceError> {
-        Ok(self
-            .client
-            .head_object()
-            .bucket(&self.bucket_name)
-            .key(file_id)
-            .send()
-            .await
-            .is_ok())
+    /// Returns the client and the bucket that reach the objects of `storage`, or the configured
+    /// ones if the file has no storage recorded.
+    fn locate<'a>(&'a self, storage: Option<&'a FileStorage>) -> (aws_sdk_s3::Client, &'a str) {
+        match storage {
+            Some(storage) if storage.region != self.region => {
+                let config = self
+                    .client
+                    .config()
+                    .to_builder()
+                    .region(Region::new(storage.region.clone()))
+                    .build();
+
+                (aws_sdk_s3::Client::from_conf(config), &storage.bucket)
+            }
+            Some(storage) => (self.client.clone(), &storage.bucket),
+            None => (self.client.clone(), &self.bucket_name),
+        }
+    }
+
+    async fn check_file_exists(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<bool, S3ServiceError> {
+        self.check_object_exists(file_id, storage).await
+    }
+
+    async fn check_object_exists(
+        &self,
+        key: impl Into<String>,
+        storage: Option<&FileStorage>,
+    ) -> Result<bool, S3ServiceError> {
+        let (client, bucket) = self.locate(storage);
+        let result = client.head_object().bucket(bucket).key(key).send().await;
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(err) => match existence_error("head object", err, &["NotFound", "NoSuchKey"]) {
+                Some(err) => Err(err),
+                None => Ok(false),
+            },
+        }
     }
 
     async fn check_multipart_upload_exists(
         &self,
         file_id: Uuid,
         upload_id: &str,
+        storage: Option<&FileStorage>,
     ) -> Result<bool, S3ServiceError> {
-        Ok(self
-            .client
+        let (client, bucket) = self.locate(storage);
+        let result = client
             .list_parts()
-            .bucket(&self.bucket_name)
+            .bucket(bucket)
             .key(file_id)
             .upload_id(upload_id)
             .max_parts(0)
             .send()
+            .await;
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(err) => match existence_error("list parts", err, &["NoSuchUpload"]) {
+                Some(err) => Err(err),
+                None => Ok(false),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for S3Service {
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    async fn object_exists(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<bool, S3ServiceError> {
+        self.check_file_exists(file_id, storage).await
+    }
+
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    async fn object_checksum(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<ObjectChecksum, S3ServiceError> {
+        let (client, bucket) = self.locate(storage);
+        let result = client
+            .head_object()
+            .bucket(bucket)
+            .key(file_id)
+            .checksum_mode(ChecksumMode::Enabled)
+            .send()
+            .await;
+        let head = match result {
+            Ok(head) => head,
+            Err(err) => {
+                return match existence_error("head object", err, &["NotFound", "NoSuchKey"]) {
+                    Some(err) => Err(err),
+                    None => Ok(ObjectChecksum::Missing),
+                };
+            }
+        };
+
+        // the checksum stored on upload is used if there is one for the whole object; otherwise the
+        // object is downloaded and hashed. checksums of multipart uploads are checksums of the part checksums, suffixed with `-N`
+        let stored = head
+            .checksum_sha256()
+            .filter(|checksum| !checksum.contains('-'))
+            .and_then(|checksum| {
+                base64::engine::general_purpose::STANDARD
+                    .decode(checksum)
+                    .ok()
+            })
+            .filter(|digest| digest.len() == 32);
+
+        if let Some(digest) = stored {
+            return Ok(ObjectChecksum::Stored(to_hex(&digest)));
+        }
+
+        let result = client.get_object().bucket(bucket).key(file_id).send().await;
+        let mut body = match result {
+            Ok(object) => object.body,
+            Err(err) => {
+                return match existence_error("get object", err, &["NoSuchKey"]) {
+                    Some(err) => Err(err),
+                    None => Ok(ObjectChecksum::Missing),
+                };
+            }
+        };
+        let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+
+        while let Some(bytes) = body
+            .try_next()
             .await
-            .is_ok())
+            .map_err(S3ServiceError::ReadObjectBody)?
+        {
+            context.update(&bytes);
+        }
+
+        Ok(ObjectChecksum::Hashed(to_hex(context.finish().as_ref())))
+    }
+
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    async fn get_file_body(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<Option<ByteStream>, S3ServiceError> {
+        let (client, bucket) = self.locate(storage);
+        let result = client.get_object().bucket(bucket).key(file_id).send().await;
+
+        match result {
+            Ok(object) => Ok(Some(object.body)),
+            Err(err) => match existence_error("get object", err, &["NoSuchKey"]) {
+                Some(err) => Err(err),
+                None => Ok(None),
+            },
+        }
     }
 
-    pub async fn create_multipart_upload(
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    async fn create_multipart_upload(
         &self,
         file_id: Uuid,
-        mime_type: impl Into<String>,
+        mime_type: &str,
+        storage: Option<&FileStorage>,
     ) -> Result<String, S3ServiceError> {
-        let response = self
-            .client
+        let (client, bucket) = self.locate(storage);
+        let response = client
             .create_multipart_upload()
-            .bucket(&self.bucket_name)
+            .bucket(bucket)
             .key(file_id)
             .content_type(mime_type)
             .send()
@@ -126,14 +579,43 @@ impl S3Service {
         }
     }
 
-    pub async fn complete_multipart_upload(
+    #[tracing::instrument(skip_all, fields(file_id = %file_id, part_number = part_number))]
+    async fn upload_part(
+        &self,
+        file_id: Uuid,
+        upload_id: &str,
+        part_number: u32,
+        body: Vec<u8>,
+        storage: Option<&FileStorage>,
+    ) -> Result<String, S3ServiceError> {
+        let (client, bucket) = self.locate(storage);
+        let response = client
+            .upload_part()
+            .bucket(bucket)
+            .key(file_id)
+            .upload_id(upload_id)
+            .part_number(part_number as i32)
+            .body(ByteStream::from(body))
+            .send()
+            .await
+            .map_err(S3ServiceError::UploadPart)?;
+
+        match response.e_tag() {
+            Some(e_tag) => Ok(e_tag.to_owned()),
+            None => Err(S3ServiceError::MissingPartETag),
+        }
+    }
+
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    async fn complete_multipart_upload(
         &self,
         file_id: Uuid,
         upload_id: String,
         parts: &[(u32, String)],
+        storage: Option<&FileStorage>,
     ) -> Result<Option<()>, S3ServiceError> {
         if !self
-            .check_multipart_upload_exists(file_id, &upload_id)
+            .check_multipart_upload_exists(file_id, &upload_id, storage)
             .await?
         {
             return Ok(None);
@@ -150,9 +632,10 @@ impl S3Service {
             );
         }
 
-        self.client
+        let (client, bucket) = self.locate(storage);
+        client
             .complete_multipart_upload()
-            .bucket(&self.bucket_name)
+            .bucket(bucket)
             .key(file_id)
             .upload_id(upload_id)
             .multipart_upload(upload.build())
@@ -163,21 +646,24 @@ impl S3Service {
         Ok(Some(()))
     }
 
-    pub async fn abort_multipart_upload(
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    async fn abort_multipart_upload(
         &self,
         file_id: Uuid,
         upload_id: String,
+        storage: Option<&FileStorage>,
     ) -> Result<Option<()>, S3ServiceError> {
         if !self
-            .check_multipart_upload_exists(file_id, &upload_id)
+            .check_multipart_upload_exists(file_id, &upload_id, storage)
             .await?
         {
             return Ok(None);
         }
 
-        self.client
+        let (client, bucket) = self.locate(storage);
+        client
             .abort_multipart_upload()
-            .bucket(&self.bucket_name)
+            .bucket(bucket)
             .key(file_id)
             .upload_id(upload_id)
             .send()
@@ -187,38 +673,178 @@ impl S3Service {
         Ok(Some(()))
     }
 
-    pub async fn generate_presigned_url_for_upload(
+    #[tracing::instrument(skip_all, fields(file_id = %file_id, upload_id = %upload_id))]
+    async fn list_parts(
+        &self,
+        file_id: Uuid,
+        upload_id: &str,
+        storage: Option<&FileStorage>,
+    ) -> Result<Option<Vec<ListedPart>>, S3ServiceError> {
+        let (client, bucket) = self.locate(storage);
+        let mut parts = Vec::new();
+        let mut part_number_marker = None;
+
+        loop {
+            let result = client
+                .list_parts()
+                .bucket(bucket)
+                .key(file_id)
+                .upload_id(upload_id)
+                .set_part_number_marker(part_number_marker)
+                .send()
+                .await;
+            let output = match result {
+                Ok(output) => output,
+                Err(err) => match existence_error("list parts", err, &["NoSuchUpload"]) {
+                    Some(err) => return Err(err),
+                    None => return Ok(None),
+                },
+            };
+
+            parts.extend(output.parts().iter().filter_map(|part| {
+                Some(ListedPart {
+                    part_number: part.part_number()? as u32,
+                    size: part.size()? as u64,
+                    e_tag: part.e_tag()?.to_owned(),
+                })
+            }));
+
+            if output.is_truncated() != Some(true) {
+                break;
+            }
+
+            part_number_marker = output
+                .next_part_number_marker()
+                .map(|marker| marker.to_owned());
+        }
+
+        Ok(Some(parts))
+    }
+
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    async fn abort_all_uploads_for_key(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<AbortedUploads, S3ServiceError> {
+        let (client, bucket) = self.locate(storage);
+        let key = file_id.to_string();
+        let mut upload_ids = Vec::new();
+        let mut key_marker = None;
+        let mut upload_id_marker = None;
+
+        loop {
+            let output = client
+                .list_multipart_uploads()
+                .bucket(bucket)
+                .prefix(&key)
+                .set_key_marker(key_marker)
+                .set_upload_id_marker(upload_id_marker)
+                .send()
+                .await
+                .map_err(S3ServiceError::ListMultipartUploads)?;
+
+            upload_ids.extend(
+                output
+                    .uploads()
+                    .iter()
+                    .filter(|upload| upload.key() == Some(key.as_str()))
+                    .filter_map(|upload| upload.upload_id().map(|upload_id| upload_id.to_owned())),
+            );
+
+            if output.is_truncated() != Some(true) {
+                break;
+            }
+
+            key_marker = output.next_key_marker().map(|marker| marker.to_owned());
+            upload_id_marker = output
+                .next_upload_id_marker()
+                .map(|marker| marker.to_owned());
+        }
+
+        let mut aborted = AbortedUploads::default();
+
+        for upload_id in upload_ids {
+            let result = client
+                .abort_multipart_upload()
+                .bucket(bucket)
+                .key(&key)
+                .upload_id(&upload_id)
+                .send()
+                .await;
+
+            // an upload completed or aborted since it was listed is gone all the same
+            match result
+                .map_err(|err| existence_error("abort multipart upload", err, &["NoSuchUpload"]))
+            {
+                Ok(_) | Err(None) => {
+                    aborted.upload_ids.push(upload_id);
+                }
+                Err(Some(err)) => {
+                    aborted.failures.push(UploadAbortFailure {
+                        upload_id,
+                        error: err.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(aborted)
+    }
+
+    #[tracing::instrument(skip_all, fields(file_id = %file_id, upload_id = %upload_id, part_number = part_number))]
+    async fn generate_presigned_url_for_upload(
         &self,
         file_id: Uuid,
         upload_id: &str,
         part_number: u32,
         expires_in: Duration,
+        storage: Option<&FileStorage>,
     ) -> Result<String, S3ServiceError> {
-        let request = self
-            .client
+        let (client, bucket) = self.locate(storage);
+        let request = client
             .upload_part()
-            .bucket(&self.bucket_name)
+            .bucket(bucket)
             .key(file_id)
             .upload_id(upload_id)
             .part_number(part_number as i32)
-            .presigned(
-                PresigningConfig::builder()
-                    .expires_in(expires_in)
-                    .build()
-                    .unwrap(),
-            )
+            .presigned(presigning_config(expires_in)?)
             .await
             .map_err(S3ServiceError::CreatePresignedUrlForUpload)?;
 
         Ok(request.uri().to_owned())
     }
 
-    pub async fn generate_presigned_url_for_download(
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    async fn generate_presigned_url_for_download(
         &self,
         file_id: Uuid,
         expires_in: Duration,
+        storage: Option<&FileStorage>,
     ) -> Result<Option<String>, S3ServiceError> {
-        if !self.check_file_exists(file_id).await? {
+        if !self.check_file_exists(file_id, storage).await? {
+            return Ok(None);
+        }
+
+        let (client, bucket) = self.locate(storage);
+        let request = client
+            .get_object()
+            .bucket(bucket)
+            .key(file_id)
+            .presigned(presigning_config(expires_in)?)
+            .await
+            .map_err(S3ServiceError::CreatePresignedUrlForDownload)?;
+
+        Ok(Some(request.uri().to_owned()))
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn generate_presigned_url_for_object_download(
+        &self,
+        key: &str,
+        expires_in: Duration,
+    ) -> Result<Option<String>, S3ServiceError> {
+        if !self.check_object_exists(key, None).await? {
             return Ok(None);
         }
 
@@ -226,27 +852,52 @@ impl S3Service {
             .client
             .get_object()
             .bucket(&self.bucket_name)
-            .key(file_id)
-            .presigned(
-                PresigningConfig::builder()
-                    .expires_in(expires_in)
-                    .build()
-                    .unwrap(),
-            )
+            .key(key)
+            .presigned(presigning_config(expires_in)?)
             .await
             .map_err(S3ServiceError::CreatePresignedUrlForDownload)?;
 
         Ok(Some(request.uri().to_owned()))
     }
 
-    pub async fn delete_file(&self, file_id: Uuid) -> Result<(), S3ServiceError> {
-        if !self.check_file_exists(file_id).await? {
+    #[tracing::instrument(skip_all)]
+    async fn put_object_from_path(
+        &self,
+        key: &str,
+        content_type: &str,
+        path: &Path,
+    ) -> Result<(), S3ServiceError> {
+        let body = ByteStream::from_path(path)
+            .await
+            .map_err(S3ServiceError::ReadObjectBody)?;
+
+        self.client
+            .put_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .content_type(content_type)
+            .body(body)
+            .send()
+            .await
+            .map_err(S3ServiceError::PutObject)?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all, fields(file_id = %file_id))]
+    async fn delete_file(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<(), S3ServiceError> {
+        if !self.check_file_exists(file_id, storage).await? {
             return Ok(());
         }
 
-        self.client
+        let (client, bucket) = self.locate(storage);
+        client
             .delete_object()
-            .bucket(&self.bucket_name)
+            .bucket(bucket)
             .key(file_id)
             .send()
             .await
@@ -254,4 +905,71 @@ impl S3Service {
 
         Ok(())
     }
+
+    #[tracing::instrument(skip_all, fields(count = file_ids.len()))]
+    async fn delete_files(
+        &self,
+        file_ids: &[Uuid],
+        storage: Option<&FileStorage>,
+    ) -> Result<(), S3ServiceError> {
+        let (client, bucket) = self.locate(storage);
+
+        // up to 1000 keys per request
+        for chunk in file_ids.chunks(1000) {
+            let objects = chunk
+                .iter()
+                .map(|file_id| ObjectIdentifier::builder().key(file_id.to_string()).build())
+                .collect::<Result<Vec<_>, _>>()?;
+            let delete = Delete::builder()
+                .set_objects(Some(objects))
+                .quiet(true)
+                .build()?;
+
+            let output = client
+                .delete_objects()
+                .bucket(bucket)
+                .delete(delete)
+                .send()
+                .await
+                .map_err(S3ServiceError::DeleteFiles)?;
+
+            for err in output.errors() {
+                tracing::warn!(
+                    key = err.key().unwrap_or_default(),
+                    error = err.message().unwrap_or_default(),
+                    "failed to delete object"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn list_objects(
+        &self,
+        prefix: Option<&str>,
+        continuation_token: Option<String>,
+    ) -> Result<ObjectPage, S3ServiceError> {
+        let output = self
+            .client
+            .list_objects_v2()
+            .bucket(&self.bucket_name)
+            .set_prefix(prefix.map(|prefix| prefix.to_owned()))
+            .set_continuation_token(continuation_token)
+            .send()
+            .await
+            .map_err(S3ServiceError::ListObjects)?;
+
+        Ok(ObjectPage {
+            keys: output
+                .contents()
+                .iter()
+                .filter_map(|object| object.key().map(|key| key.to_owned()))
+                .collect(),
+            continuation_token: output
+                .next_continuation_token()
+                .map(|continuation_token| continuation_token.to_owned()),
+        })
+    }
 }
@@ -0,0 +1,254 @@
+use crate::{interfaces::admins, telemetry::ErrorChain};
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use sqlx::PgPool;
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum ScheduledTaskServiceError {
+    #[error("database error: {0:#?}")]
+    DbError(#[from] sqlx::Error),
+    #[error("invalid cron expression: {0}")]
+    InvalidCronExpression(#[from] cron::error::Error),
+}
+
+#[derive(Clone)]
+pub struct ScheduledTaskService {
+    db_pool: PgPool,
+}
+
+impl ScheduledTaskService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Parses a cron expression. Both the standard 5-field form and the 6/7-field form with
+    /// seconds (and years) are accepted; 5-field expressions fire at second 0.
+    pub fn parse_cron_expression(expression: &str) -> Result<Schedule, ScheduledTaskServiceError> {
+        let expression = expression.trim();
+        let schedule = if expression.split_whitespace().count() == 5 {
+            Schedule::from_str(&format!("0 {expression}"))?
+        } else {
+            Schedule::from_str(expression)?
+        };
+
+        Ok(schedule)
+    }
+
+    pub async fn list_scheduled_tasks(
+        &self,
+    ) -> Result<Vec<admins::ScheduledTask>, ScheduledTaskServiceError> {
+        let scheduled_tasks = sqlx::query_as!(
+            row_types::ScheduledTask,
+            "
+SELECT id, name, cron_expression, metadata, enabled, last_run_at, created_at
+FROM scheduled_tasks
+ORDER BY created_at DESC, id ASC"
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(scheduled_tasks
+            .into_iter()
+            .map(|scheduled_task| scheduled_task.into())
+            .collect())
+    }
+
+    pub async fn get_scheduled_task(
+        &self,
+        scheduled_task_id: Uuid,
+    ) -> Result<Option<admins::ScheduledTask>, ScheduledTaskServiceError> {
+        let scheduled_task = sqlx::query_as!(
+            row_types::ScheduledTask,
+            "
+SELECT id, name, cron_expression, metadata, enabled, last_run_at, created_at
+FROM scheduled_tasks
+WHERE id = $1",
+            scheduled_task_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(scheduled_task.map(|scheduled_task| scheduled_task.into()))
+    }
+
+    pub async fn create_scheduled_task(
+        &self,
+        scheduled_task: admins::CreatingScheduledTask,
+    ) -> Result<admins::ScheduledTask, ScheduledTaskServiceError> {
+        Self::parse_cron_expression(&scheduled_task.cron_expression)?;
+
+        let scheduled_task = sqlx::query_as!(
+            row_types::ScheduledTask,
+            "
+INSERT INTO scheduled_tasks (name, cron_expression, metadata, enabled)
+VALUES ($1, $2, $3, $4)
+RETURNING id, name, cron_expression, metadata, enabled, last_run_at, created_at",
+            scheduled_task.name.as_str(),
+            scheduled_task.cron_expression.trim(),
+            scheduled_task.metadata,
+            scheduled_task.enabled
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(scheduled_task.into())
+    }
+
+    pub async fn update_scheduled_task(
+        &self,
+        scheduled_task_id: Uuid,
+        scheduled_task: admins::UpdatingScheduledTask,
+    ) -> Result<Option<admins::ScheduledTask>, ScheduledTaskServiceError> {
+        if let Some(cron_expression) = &scheduled_task.cron_expression {
+            Self::parse_cron_expression(cron_expression)?;
+        }
+
+        let scheduled_task = sqlx::query_as!(
+            row_types::ScheduledTask,
+            "
+UPDATE scheduled_tasks
+SET
+    cron_expression = COALESCE($2, cron_expression),
+    metadata = COALESCE($3, metadata),
+    enabled = COALESCE($4, enabled)
+WHERE id = $1
+RETURNING id, name, cron_expression, metadata, enabled, last_run_at, created_at",
+            scheduled_task_id,
+            scheduled_task
+                .cron_expression
+                .as_deref()
+                .map(|cron_expression| cron_expression.trim()),
+            scheduled_task.metadata,
+            scheduled_task.enabled
+        )
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(scheduled_task.map(|scheduled_task| scheduled_task.into()))
+    }
+
+    /// Returns `false` if there is no such scheduled task.
+    pub async fn delete_scheduled_task(
+        &self,
+        scheduled_task_id: Uuid,
+    ) -> Result<bool, ScheduledTaskServiceError> {
+        let result = sqlx::query!(
+            "DELETE FROM scheduled_tasks WHERE id = $1",
+            scheduled_task_id
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+
+    /// Claims every enabled scheduled task whose next occurrence is at or before `now`.
+    ///
+    /// `last_run_at` is advanced with a compare-and-set, so each occurrence is claimed at most
+    /// once, even across restarts. Occurrences missed while the server was down collapse into a
+    /// single run.
+    pub async fn claim_due_scheduled_tasks(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<admins::ScheduledTask>, ScheduledTaskServiceError> {
+        let scheduled_tasks = sqlx::query_as!(
+            row_types::ScheduledTask,
+            "
+SELECT id, name, cron_expression, metadata, enabled, last_run_at, created_at
+FROM scheduled_tasks
+WHERE enabled"
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut claimed = Vec::new();
+
+        for scheduled_task in scheduled_tasks {
+            let schedule = match Self::parse_cron_expression(&scheduled_task.cron_expression) {
+                Ok(schedule) => schedule,
+                Err(err) => {
+                    tracing::warn!(
+                        error = %ErrorChain(&err),
+                        scheduled_task_id = %scheduled_task.id,
+                        "skipping scheduled task with invalid cron expression"
+                    );
+                    continue;
+                }
+            };
+
+            let last_run_at = scheduled_task
+                .last_run_at
+                .unwrap_or(scheduled_task.created_at)
+                .and_utc();
+            let is_due = match schedule.after(&last_run_at).next() {
+                Some(next_run_at) => next_run_at <= now,
+                None => false,
+            };
+
+            if !is_due {
+                continue;
+            }
+
+            let result = sqlx::query!(
+                "
+UPDATE scheduled_tasks
+SET last_run_at = $1
+WHERE
+    id = $2
+    AND enabled
+    AND last_run_at IS NOT DISTINCT FROM $3",
+                now.naive_utc(),
+                scheduled_task.id,
+                scheduled_task.last_run_at
+            )
+            .execute(&self.db_pool)
+            .await?;
+
+            if result.rows_affected() == 0 {
+                // claimed by someone else, or disabled in the meantime
+                continue;
+            }
+
+            let mut scheduled_task: admins::ScheduledTask = scheduled_task.into();
+            scheduled_task.last_run_at = Some(now);
+            claimed.push(scheduled_task);
+        }
+
+        Ok(claimed)
+    }
+}
+
+mod row_types {
+    use crate::interfaces::admins::{self, TaskKind};
+    use chrono::NaiveDateTime;
+    use uuid::Uuid;
+
+    pub struct ScheduledTask {
+        pub id: Uuid,
+        pub name: String,
+        pub cron_expression: String,
+        pub metadata: serde_json::Value,
+        pub enabled: bool,
+        pub last_run_at: Option<NaiveDateTime>,
+        pub created_at: NaiveDateTime,
+    }
+
+    impl From<ScheduledTask> for admins::ScheduledTask {
+        fn from(scheduled_task: ScheduledTask) -> Self {
+            Self {
+                id: scheduled_task.id,
+                name: TaskKind::from_name(scheduled_task.name),
+                cron_expression: scheduled_task.cron_expression,
+                metadata: scheduled_task.metadata,
+                enabled: scheduled_task.enabled,
+                last_run_at: scheduled_task
+                    .last_run_at
+                    .map(|last_run_at| last_run_at.and_utc()),
+                created_at: scheduled_task.created_at.and_utc(),
+            }
+        }
+    }
+}
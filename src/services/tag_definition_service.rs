@@ -0,0 +1,184 @@
+use crate::interfaces::tags;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum TagDefinitionServiceError {
+    #[error("database error: {0:#?}")]
+    DbError(#[from] sqlx::Error),
+    #[error("the tag `{0}` is defined already")]
+    AlreadyDefined(String),
+}
+
+impl TagDefinitionServiceError {
+    fn from_sqlx_err(err: sqlx::Error, name: &str) -> Self {
+        match err {
+            sqlx::Error::Database(err) if err.is_unique_violation() => {
+                Self::AlreadyDefined(name.to_owned())
+            }
+            err => err.into(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TagDefinitionService {
+    db_pool: PgPool,
+}
+
+impl TagDefinitionService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    pub async fn list_tag_definitions(
+        &self,
+    ) -> Result<Vec<tags::TagDefinition>, TagDefinitionServiceError> {
+        let tag_definitions = sqlx::query_as!(
+            row_types::TagDefinition,
+            "
+SELECT name, color, description, created_at
+FROM tag_definitions
+ORDER BY name"
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(tag_definitions
+            .into_iter()
+            .map(|tag_definition| tag_definition.into())
+            .collect())
+    }
+
+    pub async fn get_tag_definition(
+        &self,
+        name: &str,
+    ) -> Result<Option<tags::TagDefinition>, TagDefinitionServiceError> {
+        let tag_definition = sqlx::query_as!(
+            row_types::TagDefinition,
+            "
+SELECT name, color, description, created_at
+FROM tag_definitions
+WHERE name = $1",
+            name
+        )
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(tag_definition.map(|tag_definition| tag_definition.into()))
+    }
+
+    /// Returns the definitions of the given tags that have one, by tag, in a single query.
+    #[tracing::instrument(skip_all, fields(count = tags.len()))]
+    pub async fn find_tag_definitions(
+        &self,
+        tags: &[String],
+    ) -> Result<HashMap<String, tags::TagDefinition>, TagDefinitionServiceError> {
+        if tags.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let tag_definitions = sqlx::query_as!(
+            row_types::TagDefinition,
+            "
+SELECT name, color, description, created_at
+FROM tag_definitions
+WHERE name = ANY($1::text[])",
+            tags
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(tag_definitions
+            .into_iter()
+            .map(|tag_definition| (tag_definition.name.clone(), tag_definition.into()))
+            .collect())
+    }
+
+    pub async fn create_tag_definition(
+        &self,
+        tag_definition: tags::CreatingTagDefinition,
+    ) -> Result<tags::TagDefinition, TagDefinitionServiceError> {
+        let name = tag_definition.name.trim();
+        let tag_definition = sqlx::query_as!(
+            row_types::TagDefinition,
+            "
+INSERT INTO tag_definitions (name, color, description)
+VALUES ($1, NULLIF($2, ''), NULLIF($3, ''))
+RETURNING name, color, description, created_at",
+            name,
+            tag_definition.color.map(|color| color.to_ascii_lowercase()),
+            tag_definition.description
+        )
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(|err| TagDefinitionServiceError::from_sqlx_err(err, name))?;
+
+        Ok(tag_definition.into())
+    }
+
+    pub async fn update_tag_definition(
+        &self,
+        name: &str,
+        tag_definition: tags::UpdatingTagDefinition,
+    ) -> Result<Option<tags::TagDefinition>, TagDefinitionServiceError> {
+        let new_name = tag_definition.name.as_deref().map(|name| name.trim());
+        let tag_definition = sqlx::query_as!(
+            row_types::TagDefinition,
+            "
+UPDATE tag_definitions
+SET
+    name = COALESCE($2, name),
+    color = CASE WHEN $3::text IS NULL THEN color ELSE NULLIF($3, '') END,
+    description = CASE WHEN $4::text IS NULL THEN description ELSE NULLIF($4, '') END
+WHERE name = $1
+RETURNING name, color, description, created_at",
+            name,
+            new_name,
+            tag_definition.color.map(|color| color.to_ascii_lowercase()),
+            tag_definition.description
+        )
+        .fetch_optional(&self.db_pool)
+        .await
+        .map_err(|err| TagDefinitionServiceError::from_sqlx_err(err, new_name.unwrap_or(name)))?;
+
+        Ok(tag_definition.map(|tag_definition| tag_definition.into()))
+    }
+
+    /// Returns `false` if the tag has no definition. The tag itself stays on every file and
+    /// collection using it.
+    pub async fn delete_tag_definition(
+        &self,
+        name: &str,
+    ) -> Result<bool, TagDefinitionServiceError> {
+        let result = sqlx::query!("DELETE FROM tag_definitions WHERE name = $1", name)
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(result.rows_affected() != 0)
+    }
+}
+
+mod row_types {
+    use crate::interfaces::tags;
+    use chrono::NaiveDateTime;
+
+    pub struct TagDefinition {
+        pub name: String,
+        pub color: Option<String>,
+        pub description: Option<String>,
+        pub created_at: NaiveDateTime,
+    }
+
+    impl From<TagDefinition> for tags::TagDefinition {
+        fn from(tag_definition: TagDefinition) -> Self {
+            Self {
+                name: tag_definition.name,
+                color: tag_definition.color,
+                description: tag_definition.description,
+                created_at: tag_definition.created_at.and_utc(),
+            }
+        }
+    }
+}
@@ -3,8 +3,9 @@ use argon2::{
     Argon2, PasswordHash,
 };
 use base64::Engine;
-use ring::rand::SecureRandom;
+use ring::{error::Unspecified, rand::SecureRandom};
 
+#[derive(Default)]
 pub struct TokenService;
 
 impl TokenService {
@@ -32,7 +33,7 @@ impl TokenService {
 
     /// Generates a random base64 encoded secure token.
     /// The output length is always `252` bytes (characters).
-    pub fn generate_token(&self) -> Result<String, ()> {
+    pub fn generate_token(&self) -> Result<String, Unspecified> {
         const ENCODER: base64::engine::GeneralPurpose = base64::engine::GeneralPurpose::new(
             &base64::alphabet::URL_SAFE,
             base64::engine::GeneralPurposeConfig::new().with_encode_padding(true),
@@ -41,8 +42,20 @@ impl TokenService {
         // floor(254 / 4) * 3 = 189
         let mut buf = [0u8; 189];
         let rng = ring::rand::SystemRandom::new();
-        rng.fill(&mut buf).map_err(|_| ())?;
+        rng.fill(&mut buf)?;
 
         Ok(ENCODER.encode(buf))
     }
+
+    /// Hashes a token generated by [`TokenService::generate_token`] for storage.
+    /// Unlike passwords, tokens carry enough entropy to be hashed without a salt, which keeps the
+    /// hash usable for lookups.
+    pub fn hash_token(&self, token: &str) -> String {
+        let digest = ring::digest::digest(&ring::digest::SHA256, token.as_bytes());
+        digest
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
 }
@@ -0,0 +1,85 @@
+use crate::config::{LogFormat, TelemetryConfig};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{trace::SdkTracerProvider, Resource};
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+const SERVICE_NAME: &str = "file-indexer";
+
+#[derive(Error, Debug)]
+pub enum TelemetryError {
+    #[error("failed to build otlp exporter: {0:#?}")]
+    BuildExporter(#[from] opentelemetry::trace::TraceError),
+
+    #[error("failed to install tracing subscriber: {0:#?}")]
+    InstallSubscriber(#[from] tracing_subscriber::util::TryInitError),
+}
+
+/// Installs the global tracing subscriber, which also receives the `log` records of the
+/// dependencies. In the JSON format, every event is a single line carrying the fields of the
+/// spans it happened in, such as the request id. Spans are exported over OTLP only if an endpoint is configured; the returned
+/// provider must be shut down to flush them.
+pub fn init(config: &TelemetryConfig) -> Result<Option<SdkTracerProvider>, TelemetryError> {
+    let tracer_provider = match &config.otlp_endpoint {
+        Some(endpoint) => {
+            let exporter = opentelemetry_otlp::SpanExporter::builder()
+                .with_tonic()
+                .with_endpoint(endpoint)
+                .build()?;
+
+            Some(
+                SdkTracerProvider::builder()
+                    .with_batch_exporter(exporter)
+                    .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+                    .build(),
+            )
+        }
+        None => None,
+    };
+    let otel_layer = tracer_provider.as_ref().map(|tracer_provider| {
+        tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer(SERVICE_NAME))
+    });
+
+    let (text_layer, json_layer) = match config.log_format {
+        LogFormat::Text => (Some(tracing_subscriber::fmt::layer()), None),
+        LogFormat::Json => (
+            None,
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_current_span(false)
+                    .with_span_list(true),
+            ),
+        ),
+    };
+
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(text_layer)
+        .with(json_layer)
+        .with(otel_layer)
+        .try_init()?;
+
+    Ok(tracer_provider)
+}
+
+/// Formats an error followed by its sources on a single line, e.g. `failed to list files: database
+/// error: connection refused`. Meant for the `error` field of log events.
+pub struct ErrorChain<'a>(pub &'a (dyn std::error::Error + 'static));
+
+impl Display for ErrorChain<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)?;
+
+        let mut source = self.0.source();
+
+        while let Some(err) = source {
+            write!(f, ": {err}")?;
+            source = err.source();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,7 @@
+//! In-memory stand-ins for the external stores and the mail server, so that routes and services
+//! can be tested without AWS, Meilisearch, SMTP or containers. They are not meant for production
+//! use, and are only built with the `testing` feature, which the integration tests enable.
+
+pub mod mailer;
+pub mod object_storage;
+pub mod search_index;
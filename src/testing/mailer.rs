@@ -0,0 +1,37 @@
+use crate::services::mailer_service::{Mail, MailerService, MailerServiceError};
+use rocket::async_trait;
+use std::sync::Mutex;
+
+/// Keeps the mails instead of delivering them, so that tests can read them with
+/// [`Self::mails`]. Sending never fails.
+pub struct InMemoryMailer {
+    mails: Mutex<Vec<Mail>>,
+}
+
+impl InMemoryMailer {
+    pub fn new() -> Self {
+        Self {
+            mails: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the mails sent so far, in order.
+    pub fn mails(&self) -> Vec<Mail> {
+        self.mails.lock().unwrap().clone()
+    }
+}
+
+impl Default for InMemoryMailer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl MailerService for InMemoryMailer {
+    async fn send(&self, mail: Mail) -> Result<(), MailerServiceError> {
+        self.mails.lock().unwrap().push(mail);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,504 @@
+use crate::{
+    interfaces::files::FileStorage,
+    services::s3_service::{
+        check_presign_duration, to_hex, AbortedUploads, ListedPart, ObjectChecksum, ObjectPage,
+        ObjectStorage, S3ServiceError,
+    },
+};
+use aws_sdk_s3::{error::SdkError, primitives::ByteStream};
+use rocket::async_trait;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+use uuid::Uuid;
+
+/// Presigned URLs point below this host, which never resolves.
+const URL_BASE: &str = "http://object-storage.invalid";
+/// Keys listed per page, as S3 does.
+const LIST_PAGE_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredObject {
+    pub content_type: String,
+    pub body: Vec<u8>,
+}
+
+struct Upload {
+    bucket: String,
+    key: String,
+    content_type: String,
+    /// The ETag and body of each uploaded part, by part number.
+    parts: BTreeMap<u32, (String, Vec<u8>)>,
+}
+
+#[derive(Default)]
+struct Store {
+    /// Objects by bucket and key.
+    objects: BTreeMap<(String, String), StoredObject>,
+    uploads: HashMap<String, Upload>,
+}
+
+/// Keeps objects and multipart uploads in memory, with the semantics the services rely on from
+/// S3: unknown uploads complete and abort as `None`, completing checks every part against its
+/// ETag, and deleting a missing object succeeds.
+///
+/// The presigned URLs lead nowhere, so tests upload the parts through
+/// [`ObjectStorage::upload_part`] instead, and look at the results with [`Self::object`].
+pub struct InMemoryObjectStorage {
+    /// The bucket of the objects without a [`FileStorage`], like the configured bucket of
+    /// [`crate::services::s3_service::S3Service`].
+    bucket_name: String,
+    store: Mutex<Store>,
+    /// Whether starting multipart uploads fails, see [`Self::set_uploads_unavailable`].
+    uploads_unavailable: AtomicBool,
+    /// How long presigning takes, see [`Self::set_presign_delay`].
+    presign_delay: Mutex<Duration>,
+}
+
+impl InMemoryObjectStorage {
+    pub fn new(bucket_name: impl Into<String>) -> Self {
+        Self {
+            bucket_name: bucket_name.into(),
+            store: Mutex::default(),
+            uploads_unavailable: AtomicBool::new(false),
+            presign_delay: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Makes starting multipart uploads fail as if S3 were unreachable, or work again.
+    pub fn set_uploads_unavailable(&self, unavailable: bool) {
+        self.uploads_unavailable
+            .store(unavailable, Ordering::Relaxed);
+    }
+
+    /// Makes every presigned URL take `delay` before it is answered, as if S3 were hanging.
+    pub fn set_presign_delay(&self, delay: Duration) {
+        *self.presign_delay.lock().unwrap() = delay;
+    }
+
+    async fn presign(&self, expires_in: Duration) -> Result<(), S3ServiceError> {
+        check_presign_duration(expires_in)?;
+
+        let presign_delay = *self.presign_delay.lock().unwrap();
+        if !presign_delay.is_zero() {
+            tokio::time::sleep(presign_delay).await;
+        }
+
+        Ok(())
+    }
+
+    fn bucket(&self, storage: Option<&FileStorage>) -> String {
+        match storage {
+            Some(storage) => storage.bucket.clone(),
+            None => self.bucket_name.clone(),
+        }
+    }
+
+    fn e_tag(body: &[u8]) -> String {
+        let digest = ring::digest::digest(&ring::digest::SHA256, body);
+        format!("\"{}\"", to_hex(&digest.as_ref()[..16]))
+    }
+
+    /// Returns the object stored under `key`.
+    pub fn object(&self, key: &str, storage: Option<&FileStorage>) -> Option<StoredObject> {
+        let store = self.store.lock().unwrap();
+        store
+            .objects
+            .get(&(self.bucket(storage), key.to_owned()))
+            .cloned()
+    }
+
+    /// Stores an object directly, e.g. to set up a file whose upload completed elsewhere.
+    pub fn insert_object(
+        &self,
+        key: &str,
+        storage: Option<&FileStorage>,
+        object: StoredObject,
+    ) -> Option<StoredObject> {
+        let mut store = self.store.lock().unwrap();
+        store
+            .objects
+            .insert((self.bucket(storage), key.to_owned()), object)
+    }
+
+    /// Removes an object directly, e.g. to simulate an object that went missing.
+    pub fn remove_object(&self, key: &str, storage: Option<&FileStorage>) -> Option<StoredObject> {
+        let mut store = self.store.lock().unwrap();
+        store
+            .objects
+            .remove(&(self.bucket(storage), key.to_owned()))
+    }
+
+    /// Returns the ids of the multipart uploads that are neither completed nor aborted.
+    pub fn open_upload_ids(&self) -> Vec<String> {
+        let store = self.store.lock().unwrap();
+        Vec::from_iter(store.uploads.keys().cloned())
+    }
+
+    /// Returns the upload if it exists and is for the key, as S3 answers `NoSuchUpload` for an
+    /// upload of another key.
+    fn find_upload<'a>(
+        store: &'a mut Store,
+        upload_id: &str,
+        bucket: &str,
+        key: &str,
+    ) -> Option<&'a mut Upload> {
+        store
+            .uploads
+            .get_mut(upload_id)
+            .filter(|upload| upload.bucket == bucket && upload.key == key)
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for InMemoryObjectStorage {
+    async fn object_exists(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<bool, S3ServiceError> {
+        Ok(self.object(&file_id.to_string(), storage).is_some())
+    }
+
+    async fn object_checksum(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<ObjectChecksum, S3ServiceError> {
+        Ok(match self.object(&file_id.to_string(), storage) {
+            Some(object) => {
+                let digest = ring::digest::digest(&ring::digest::SHA256, &object.body);
+                ObjectChecksum::Hashed(to_hex(digest.as_ref()))
+            }
+            None => ObjectChecksum::Missing,
+        })
+    }
+
+    async fn get_file_body(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<Option<ByteStream>, S3ServiceError> {
+        Ok(self
+            .object(&file_id.to_string(), storage)
+            .map(|object| ByteStream::from(object.body)))
+    }
+
+    async fn create_multipart_upload(
+        &self,
+        file_id: Uuid,
+        mime_type: &str,
+        storage: Option<&FileStorage>,
+    ) -> Result<String, S3ServiceError> {
+        if self.uploads_unavailable.load(Ordering::Relaxed) {
+            return Err(S3ServiceError::Transient {
+                operation: "create multipart upload",
+                error: "the storage is unavailable".into(),
+            });
+        }
+
+        let upload_id = Uuid::new_v4().simple().to_string();
+        let mut store = self.store.lock().unwrap();
+        store.uploads.insert(
+            upload_id.clone(),
+            Upload {
+                bucket: self.bucket(storage),
+                key: file_id.to_string(),
+                content_type: mime_type.to_owned(),
+                parts: BTreeMap::new(),
+            },
+        );
+
+        Ok(upload_id)
+    }
+
+    async fn upload_part(
+        &self,
+        file_id: Uuid,
+        upload_id: &str,
+        part_number: u32,
+        body: Vec<u8>,
+        storage: Option<&FileStorage>,
+    ) -> Result<String, S3ServiceError> {
+        let mut store = self.store.lock().unwrap();
+        let upload = Self::find_upload(
+            &mut store,
+            upload_id,
+            &self.bucket(storage),
+            &file_id.to_string(),
+        )
+        .ok_or_else(|| {
+            S3ServiceError::UploadPart(SdkError::construction_failure(format!(
+                "no upload `{upload_id}` of `{file_id}`"
+            )))
+        })?;
+        let e_tag = Self::e_tag(&body);
+        upload.parts.insert(part_number, (e_tag.clone(), body));
+
+        Ok(e_tag)
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        file_id: Uuid,
+        upload_id: String,
+        parts: &[(u32, String)],
+        storage: Option<&FileStorage>,
+    ) -> Result<Option<()>, S3ServiceError> {
+        let bucket = self.bucket(storage);
+        let key = file_id.to_string();
+        let mut store = self.store.lock().unwrap();
+        let Some(upload) = Self::find_upload(&mut store, &upload_id, &bucket, &key) else {
+            return Ok(None);
+        };
+
+        let invalid_part = |reason: String| {
+            S3ServiceError::CompleteMultipartUpload(SdkError::construction_failure(reason))
+        };
+
+        if parts.is_empty() {
+            return Err(invalid_part("no parts given".to_owned()));
+        }
+
+        let mut body = Vec::new();
+        let mut previous_part_number = 0;
+
+        for (part_number, e_tag) in parts {
+            if *part_number <= previous_part_number {
+                return Err(invalid_part(format!("part {part_number} is out of order")));
+            }
+
+            match upload.parts.get(part_number) {
+                Some((uploaded_e_tag, part)) if uploaded_e_tag == e_tag => {
+                    body.extend_from_slice(part);
+                }
+                Some(_) => {
+                    return Err(invalid_part(format!("part {part_number} has another etag")));
+                }
+                None => {
+                    return Err(invalid_part(format!("part {part_number} was not uploaded")));
+                }
+            }
+
+            previous_part_number = *part_number;
+        }
+
+        let upload = store.uploads.remove(&upload_id).unwrap();
+        store.objects.insert(
+            (bucket, key),
+            StoredObject {
+                content_type: upload.content_type,
+                body,
+            },
+        );
+
+        Ok(Some(()))
+    }
+
+    async fn abort_multipart_upload(
+        &self,
+        file_id: Uuid,
+        upload_id: String,
+        storage: Option<&FileStorage>,
+    ) -> Result<Option<()>, S3ServiceError> {
+        let mut store = self.store.lock().unwrap();
+
+        if Self::find_upload(
+            &mut store,
+            &upload_id,
+            &self.bucket(storage),
+            &file_id.to_string(),
+        )
+        .is_none()
+        {
+            return Ok(None);
+        }
+
+        store.uploads.remove(&upload_id);
+
+        Ok(Some(()))
+    }
+
+    async fn list_parts(
+        &self,
+        file_id: Uuid,
+        upload_id: &str,
+        storage: Option<&FileStorage>,
+    ) -> Result<Option<Vec<ListedPart>>, S3ServiceError> {
+        let mut store = self.store.lock().unwrap();
+        let upload = Self::find_upload(
+            &mut store,
+            upload_id,
+            &self.bucket(storage),
+            &file_id.to_string(),
+        );
+
+        Ok(upload.map(|upload| {
+            Vec::from_iter(
+                upload
+                    .parts
+                    .iter()
+                    .map(|(part_number, (e_tag, body))| ListedPart {
+                        part_number: *part_number,
+                        size: body.len() as u64,
+                        e_tag: e_tag.clone(),
+                    }),
+            )
+        }))
+    }
+
+    async fn abort_all_uploads_for_key(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<AbortedUploads, S3ServiceError> {
+        let bucket = self.bucket(storage);
+        let key = file_id.to_string();
+        let mut store = self.store.lock().unwrap();
+        let mut aborted = AbortedUploads::default();
+
+        store.uploads.retain(|upload_id, upload| {
+            let is_of_key = upload.bucket == bucket && upload.key == key;
+
+            if is_of_key {
+                aborted.upload_ids.push(upload_id.clone());
+            }
+
+            !is_of_key
+        });
+
+        Ok(aborted)
+    }
+
+    async fn generate_presigned_url_for_upload(
+        &self,
+        file_id: Uuid,
+        upload_id: &str,
+        part_number: u32,
+        expires_in: Duration,
+        storage: Option<&FileStorage>,
+    ) -> Result<String, S3ServiceError> {
+        self.presign(expires_in).await?;
+
+        Ok(format!(
+            "{URL_BASE}/{}/{file_id}?uploadId={upload_id}&partNumber={part_number}",
+            self.bucket(storage)
+        ))
+    }
+
+    async fn generate_presigned_url_for_download(
+        &self,
+        file_id: Uuid,
+        expires_in: Duration,
+        storage: Option<&FileStorage>,
+    ) -> Result<Option<String>, S3ServiceError> {
+        self.presign(expires_in).await?;
+
+        if !self.object_exists(file_id, storage).await? {
+            return Ok(None);
+        }
+
+        Ok(Some(format!(
+            "{URL_BASE}/{}/{file_id}",
+            self.bucket(storage)
+        )))
+    }
+
+    async fn generate_presigned_url_for_object_download(
+        &self,
+        key: &str,
+        expires_in: Duration,
+    ) -> Result<Option<String>, S3ServiceError> {
+        self.presign(expires_in).await?;
+
+        if self.object(key, None).is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(format!("{URL_BASE}/{}/{key}", self.bucket_name)))
+    }
+
+    async fn put_object_from_path(
+        &self,
+        key: &str,
+        content_type: &str,
+        path: &Path,
+    ) -> Result<(), S3ServiceError> {
+        let body = tokio::fs::read(path)
+            .await
+            .map_err(|err| S3ServiceError::PutObject(SdkError::construction_failure(err)))?;
+        self.insert_object(
+            key,
+            None,
+            StoredObject {
+                content_type: content_type.to_owned(),
+                body,
+            },
+        );
+
+        Ok(())
+    }
+
+    async fn delete_file(
+        &self,
+        file_id: Uuid,
+        storage: Option<&FileStorage>,
+    ) -> Result<(), S3ServiceError> {
+        self.remove_object(&file_id.to_string(), storage);
+
+        Ok(())
+    }
+
+    async fn delete_files(
+        &self,
+        file_ids: &[Uuid],
+        storage: Option<&FileStorage>,
+    ) -> Result<(), S3ServiceError> {
+        for file_id in file_ids {
+            self.remove_object(&file_id.to_string(), storage);
+        }
+
+        Ok(())
+    }
+
+    async fn list_objects(
+        &self,
+        prefix: Option<&str>,
+        continuation_token: Option<String>,
+    ) -> Result<ObjectPage, S3ServiceError> {
+        let store = self.store.lock().unwrap();
+        let mut keys = Vec::from_iter(
+            store
+                .objects
+                .keys()
+                .filter(|(bucket, _)| *bucket == self.bucket_name)
+                .map(|(_, key)| key)
+                .filter(|key| prefix.is_none_or(|prefix| key.starts_with(prefix)))
+                .filter(|key| {
+                    continuation_token
+                        .as_ref()
+                        .is_none_or(|token| key.as_str() > token.as_str())
+                })
+                .take(LIST_PAGE_SIZE + 1)
+                .cloned(),
+        );
+        let continuation_token = match keys.len() > LIST_PAGE_SIZE {
+            true => {
+                keys.truncate(LIST_PAGE_SIZE);
+                keys.last().cloned()
+            }
+            false => None,
+        };
+
+        Ok(ObjectPage {
+            keys,
+            continuation_token,
+        })
+    }
+}
@@ -0,0 +1,862 @@
+use crate::{
+    db::search_engine::{COLLECTIONS_INDEX_UID, FILES_INDEX_SCHEMA, FILES_INDEX_UID},
+    interfaces::{
+        admins::{MeiliTask, MeiliTaskStatus},
+        collections::{Collection, CollectionSearchHit, CollectionSearchQuery},
+        files::{File, FileCollection, FileSearchDistinct, FileSearchFacets, FileSearchQuery},
+        IndexSettings, RankingRule, SearchIndexSettings, TypoTolerance, UpdatingIndexSettings,
+        SEARCH_MAX_LIMIT, SEARCH_MAX_OFFSET, SEARCH_MIN_LIMIT,
+    },
+    services::index_service::{
+        filters, DocumentIndex, FileIdMatches, FileSearchHits, IndexServiceError,
+        IndexingCollection, IndexingFile, SearchIndex, SearchOptions, SearchedCollection,
+        SearchedFile,
+    },
+};
+use chrono::Utc;
+use meilisearch_sdk::errors::{Error, ErrorCode, ErrorType, MeilisearchError};
+use rocket::async_trait;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet},
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+use uuid::Uuid;
+
+type Document = Map<String, Value>;
+
+struct Store {
+    files: BTreeMap<Uuid, Document>,
+    collections: BTreeMap<Uuid, Document>,
+    settings: IndexSettings,
+    tasks: Vec<MeiliTask>,
+    /// The `(limit, offset)` of every file search, in order.
+    file_search_pages: Vec<(i64, i64)>,
+}
+
+impl Store {
+    /// Records a task that already succeeded, returning its uid.
+    fn succeed(&mut self, index_uid: &str) -> u32 {
+        let now = Utc::now();
+        let uid = self.tasks.len() as u32;
+
+        self.tasks.push(MeiliTask {
+            uid,
+            index_uid: Some(index_uid.to_owned()),
+            status: MeiliTaskStatus::Succeeded,
+            error: None,
+            enqueued_at: now,
+            started_at: Some(now),
+            finished_at: Some(now),
+        });
+
+        uid
+    }
+}
+
+/// Keeps both indexes in memory. Every write is applied before it returns, and its task is
+/// reported as succeeded, unless the index was made unavailable.
+///
+/// The documents are the ones Meilisearch receives, merged into the indexed ones as Meilisearch
+/// does, and file searches evaluate the filter expressions built for Meilisearch, so the two can
+/// be checked against each other. Matching is simpler than in Meilisearch: a hit contains every
+/// query word, case-insensitively, somewhere in its name or one of its tags. There is no typo
+/// tolerance nor ranking; hits come in id order, and highlights repeat the attributes without
+/// marking the matches.
+pub struct InMemorySearchIndex {
+    store: Mutex<Store>,
+    search_delay: Mutex<Duration>,
+    /// Whether every call fails, see [`Self::set_unavailable`].
+    unavailable: AtomicBool,
+}
+
+impl InMemorySearchIndex {
+    pub fn new() -> Self {
+        Self {
+            store: Mutex::new(Store {
+                files: BTreeMap::new(),
+                collections: BTreeMap::new(),
+                settings: IndexSettings {
+                    files: default_settings(),
+                    collections: default_settings(),
+                },
+                tasks: Vec::new(),
+                file_search_pages: Vec::new(),
+            }),
+            search_delay: Mutex::new(Duration::ZERO),
+            unavailable: AtomicBool::new(false),
+        }
+    }
+
+    /// Makes file searches take `delay` before they answer, as if Meilisearch were overloaded.
+    pub fn set_search_delay(&self, delay: Duration) {
+        *self.search_delay.lock().unwrap() = delay;
+    }
+
+    /// Makes every call fail as if Meilisearch could not be reached, until it is made available
+    /// again.
+    pub fn set_unavailable(&self, unavailable: bool) {
+        self.unavailable.store(unavailable, Ordering::SeqCst);
+    }
+
+    /// Returns the `(limit, offset)` of every file search so far, in order.
+    pub fn file_search_pages(&self) -> Vec<(i64, i64)> {
+        let store = self.store.lock().unwrap();
+        store.file_search_pages.clone()
+    }
+
+    /// Returns the indexed document of the file.
+    pub fn file_document(&self, file_id: Uuid) -> Option<Value> {
+        let store = self.store.lock().unwrap();
+        store.files.get(&file_id).cloned().map(Value::Object)
+    }
+
+    /// Returns the indexed document of the collection.
+    pub fn collection_document(&self, collection_id: Uuid) -> Option<Value> {
+        let store = self.store.lock().unwrap();
+        store
+            .collections
+            .get(&collection_id)
+            .cloned()
+            .map(Value::Object)
+    }
+
+    /// Whether the document of the file matches the filter expression, as a search would evaluate
+    /// it, e.g. to check the precedence of an expression joined from several groups.
+    pub fn file_matches(&self, file_id: Uuid, expression: &str) -> Result<bool, IndexServiceError> {
+        let filter = parse_filter(expression, FILES_INDEX_SCHEMA.filterable_attributes)?;
+        let store = self.store.lock().unwrap();
+        Ok(store
+            .files
+            .get(&file_id)
+            .is_some_and(|document| filter.matches(document)))
+    }
+
+    /// Stores a file document as it is, bypassing the shaping of indexed files, e.g. to stand in
+    /// for a document indexed by an earlier version. The document must have a string `id`.
+    pub fn insert_file_document(&self, document: Value) {
+        let Value::Object(document) = document else {
+            panic!("file document must be an object");
+        };
+        let file_id = document
+            .get("id")
+            .and_then(Value::as_str)
+            .and_then(|id| id.parse().ok())
+            .expect("file document must have an id");
+        let mut store = self.store.lock().unwrap();
+        store.files.insert(file_id, document);
+    }
+
+    /// Returns the ids of the indexed files.
+    pub fn file_ids(&self) -> Vec<Uuid> {
+        let store = self.store.lock().unwrap();
+        store.files.keys().copied().collect()
+    }
+
+    fn check_available(&self) -> Result<(), IndexServiceError> {
+        match self.unavailable.load(Ordering::SeqCst) {
+            true => Err(IndexServiceError::MeilisearchError(Error::Other(Box::new(
+                io::Error::new(io::ErrorKind::ConnectionRefused, "connection refused"),
+            )))),
+            false => Ok(()),
+        }
+    }
+
+    /// Returns the files matching the query, before paging.
+    fn matching_files<'a>(
+        store: &'a Store,
+        q: &FileSearchQuery,
+    ) -> Result<Vec<&'a Document>, IndexServiceError> {
+        let filters = filters::build_file_filters(q)
+            .iter()
+            .map(|expression| parse_filter(expression, FILES_INDEX_SCHEMA.filterable_attributes))
+            .collect::<Result<Vec<_>, _>>()?;
+        let words = query_words(&q.q);
+
+        Ok(store
+            .files
+            .values()
+            .filter(|document| contains_words(document, &words))
+            .filter(|document| filters.iter().all(|filter| filter.matches(document)))
+            .collect())
+    }
+}
+
+impl Default for InMemorySearchIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SearchIndex for InMemorySearchIndex {
+    async fn empty_files_index(&self) -> Result<u32, IndexServiceError> {
+        self.check_available()?;
+
+        let mut store = self.store.lock().unwrap();
+        store.files.clear();
+
+        Ok(store.succeed(FILES_INDEX_UID))
+    }
+
+    async fn empty_collections_index(&self) -> Result<u32, IndexServiceError> {
+        self.check_available()?;
+
+        let mut store = self.store.lock().unwrap();
+        store.collections.clear();
+
+        Ok(store.succeed(COLLECTIONS_INDEX_UID))
+    }
+
+    async fn get_index_settings(&self) -> Result<IndexSettings, IndexServiceError> {
+        self.check_available()?;
+
+        Ok(self.store.lock().unwrap().settings.clone())
+    }
+
+    async fn update_index_settings(
+        &self,
+        settings: &UpdatingIndexSettings,
+    ) -> Result<IndexSettings, IndexServiceError> {
+        self.check_available()?;
+
+        let mut store = self.store.lock().unwrap();
+
+        if let Some(files) = &settings.files {
+            store.settings.files = files.clone();
+            store.succeed(FILES_INDEX_UID);
+        }
+
+        if let Some(collections) = &settings.collections {
+            store.settings.collections = collections.clone();
+            store.succeed(COLLECTIONS_INDEX_UID);
+        }
+
+        Ok(store.settings.clone())
+    }
+
+    async fn index_files(
+        &self,
+        files: &[File],
+        collections: &HashMap<Uuid, Vec<FileCollection>>,
+    ) -> Result<u32, IndexServiceError> {
+        self.check_available()?;
+
+        let mut store = self.store.lock().unwrap();
+
+        for file in files {
+            let document = IndexingFile::new(
+                file,
+                collections
+                    .get(&file.id)
+                    .map(|collections| collections.as_slice())
+                    .unwrap_or_default(),
+            );
+            upsert(&mut store.files, file.id, &document)?;
+        }
+
+        Ok(store.succeed(FILES_INDEX_UID))
+    }
+
+    async fn index_collections(
+        &self,
+        collections: &[(Collection, u64)],
+    ) -> Result<u32, IndexServiceError> {
+        self.check_available()?;
+
+        let mut store = self.store.lock().unwrap();
+
+        for (collection, file_count) in collections {
+            let document = IndexingCollection::new(collection, *file_count);
+            upsert(&mut store.collections, collection.id, &document)?;
+        }
+
+        Ok(store.succeed(COLLECTIONS_INDEX_UID))
+    }
+
+    async fn get_raw_document(
+        &self,
+        index: DocumentIndex,
+        id: Uuid,
+    ) -> Result<Option<Document>, IndexServiceError> {
+        self.check_available()?;
+
+        let store = self.store.lock().unwrap();
+        let documents = match index {
+            DocumentIndex::Files => &store.files,
+            DocumentIndex::Collections => &store.collections,
+        };
+
+        Ok(documents.get(&id).cloned())
+    }
+
+    async fn delete_files(&self, file_ids: &[Uuid]) -> Result<Vec<u32>, IndexServiceError> {
+        self.check_available()?;
+
+        if file_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut store = self.store.lock().unwrap();
+
+        for file_id in file_ids {
+            store.files.remove(file_id);
+        }
+
+        Ok(vec![store.succeed(FILES_INDEX_UID)])
+    }
+
+    async fn delete_collections(
+        &self,
+        collection_ids: &[Uuid],
+    ) -> Result<Vec<u32>, IndexServiceError> {
+        self.check_available()?;
+
+        if collection_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut store = self.store.lock().unwrap();
+
+        for collection_id in collection_ids {
+            store.collections.remove(collection_id);
+        }
+
+        Ok(vec![store.succeed(COLLECTIONS_INDEX_UID)])
+    }
+
+    async fn wait_for_task(
+        &self,
+        _task_uid: u32,
+        _timeout: Duration,
+    ) -> Result<(), IndexServiceError> {
+        self.check_available()?;
+
+        // every task succeeds before its write returns
+        Ok(())
+    }
+
+    async fn get_tasks(&self, task_uids: &[u32]) -> Result<Vec<MeiliTask>, IndexServiceError> {
+        self.check_available()?;
+
+        let store = self.store.lock().unwrap();
+
+        Ok(task_uids
+            .iter()
+            .filter_map(|task_uid| store.tasks.get(*task_uid as usize).cloned())
+            .collect())
+    }
+
+    async fn search_files(&self, q: &FileSearchQuery) -> Result<FileSearchHits, IndexServiceError> {
+        self.check_available()?;
+
+        let search_delay = *self.search_delay.lock().unwrap();
+
+        if !search_delay.is_zero() {
+            tokio::time::sleep(search_delay).await;
+        }
+
+        let mut store = self.store.lock().unwrap();
+        store.file_search_pages.push((q.limit, q.offset));
+        let options = SearchOptions::new(
+            q.matching_strategy,
+            q.attributes_to_highlight.as_deref(),
+            q.crop_length,
+        );
+        let mut documents = Self::matching_files(&store, q)?;
+        let mut checksum_counts = None;
+
+        if let Some(distinct) = q.distinct {
+            let attribute = match distinct {
+                FileSearchDistinct::Checksum => "checksum",
+            };
+            let mut counts = HashMap::<String, usize>::new();
+            let mut seen = HashSet::new();
+
+            for document in &documents {
+                if let Some(Value::String(value)) = document.get(attribute) {
+                    *counts.entry(value.clone()).or_default() += 1;
+                }
+            }
+
+            // documents without the attribute are never collapsed, as in Meilisearch
+            documents.retain(|document| match document.get(attribute) {
+                Some(Value::String(value)) => seen.insert(value.clone()),
+                _ => true,
+            });
+            checksum_counts = Some(counts);
+        }
+
+        let mut hits = FileSearchHits::default();
+
+        for document in documents
+            .into_iter()
+            .skip(q.offset.clamp(0, SEARCH_MAX_OFFSET) as usize)
+            .take(q.limit.clamp(SEARCH_MIN_LIMIT, SEARCH_MAX_LIMIT) as usize)
+        {
+            let file = match SearchedFile::from_document(document.clone()) {
+                Ok(file) => file,
+                Err(file_id) => {
+                    hits.skip(file_id);
+                    continue;
+                }
+            };
+            let duplicate_count = match (&checksum_counts, &file.checksum) {
+                (Some(checksum_counts), Some(checksum)) => checksum_counts.get(checksum).copied(),
+                _ => None,
+            };
+
+            hits.hits
+                .push(file.into_hit(options.highlights(Some(document.clone())), duplicate_count));
+        }
+
+        Ok(hits)
+    }
+
+    async fn facet_files(
+        &self,
+        q: &FileSearchQuery,
+    ) -> Result<FileSearchFacets, IndexServiceError> {
+        self.check_available()?;
+
+        let store = self.store.lock().unwrap();
+        let mut counts = HashMap::<String, usize>::new();
+
+        for document in Self::matching_files(&store, q)? {
+            if let Some(Value::String(bucket)) = document.get("size_bucket") {
+                *counts.entry(bucket.clone()).or_default() += 1;
+            }
+        }
+
+        Ok(FileSearchFacets::from_size_bucket_counts(&counts))
+    }
+
+    async fn search_file_ids(
+        &self,
+        q: &FileSearchQuery,
+        max: usize,
+    ) -> Result<FileIdMatches, IndexServiceError> {
+        self.check_available()?;
+
+        let store = self.store.lock().unwrap();
+        let documents = Self::matching_files(&store, q)?;
+        let file_ids = documents
+            .into_iter()
+            .filter_map(|document| document.get("id")?.as_str()?.parse().ok())
+            .take(max + 1)
+            .collect();
+
+        Ok(FileIdMatches::new(file_ids, max))
+    }
+
+    async fn latest_file_ids(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Uuid>, IndexServiceError> {
+        self.check_available()?;
+
+        let store = self.store.lock().unwrap();
+        let mut documents = Vec::from_iter(store.files.iter());
+        documents.sort_by_key(|(_, document)| {
+            Reverse(document.get("uploaded_at").and_then(Value::as_i64))
+        });
+
+        Ok(documents
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(id, _)| *id)
+            .collect())
+    }
+
+    async fn search_collections(
+        &self,
+        q: &CollectionSearchQuery,
+    ) -> Result<Vec<CollectionSearchHit>, IndexServiceError> {
+        self.check_available()?;
+
+        let store = self.store.lock().unwrap();
+        let options = SearchOptions::new(
+            q.matching_strategy,
+            q.attributes_to_highlight.as_deref(),
+            q.crop_length,
+        );
+        let words = query_words(&q.q);
+
+        store
+            .collections
+            .values()
+            .filter(|document| contains_words(document, &words))
+            .skip(q.offset.clamp(0, SEARCH_MAX_OFFSET) as usize)
+            .take(q.limit.clamp(SEARCH_MIN_LIMIT, SEARCH_MAX_LIMIT) as usize)
+            .map(|document| {
+                let collection: SearchedCollection =
+                    serde_json::from_value(Value::Object(document.clone()))
+                        .map_err(Error::ParseError)?;
+
+                Ok(collection.into_hit(options.highlights(Some(document.clone()))))
+            })
+            .collect()
+    }
+}
+
+/// The settings of a fresh Meilisearch index, as far as they are managed.
+fn default_settings() -> SearchIndexSettings {
+    SearchIndexSettings {
+        synonyms: BTreeMap::new(),
+        stop_words: Vec::new(),
+        typo_tolerance: TypoTolerance {
+            enabled: true,
+            min_word_size_for_one_typo: 5,
+            min_word_size_for_two_typos: 9,
+        },
+        ranking_rules: RankingRule::ALL.to_vec(),
+    }
+}
+
+/// Adds the document, or updates the attributes it has on the indexed one, keeping the others.
+fn upsert(
+    documents: &mut BTreeMap<Uuid, Document>,
+    id: Uuid,
+    document: &impl Serialize,
+) -> Result<(), IndexServiceError> {
+    let Value::Object(document) = serde_json::to_value(document).map_err(Error::ParseError)? else {
+        unreachable!("documents serialize as objects");
+    };
+
+    documents.entry(id).or_default().extend(document);
+
+    Ok(())
+}
+
+fn query_words(q: &str) -> Vec<String> {
+    q.split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+/// Whether every word occurs in the name or one of the tags of the document.
+fn contains_words(document: &Document, words: &[String]) -> bool {
+    let name = document
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_lowercase();
+    let tags = document
+        .get("tags")
+        .and_then(Value::as_array)
+        .map(|tags| {
+            tags.iter()
+                .filter_map(Value::as_str)
+                .map(str::to_lowercase)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    words.iter().all(|word| {
+        name.contains(word.as_str()) || tags.iter().any(|tag| tag.contains(word.as_str()))
+    })
+}
+
+/// A filter expression, in the subset of the Meilisearch syntax the filter builder emits.
+#[derive(Debug)]
+enum Filter {
+    Or(Vec<Filter>),
+    And(Vec<Filter>),
+    Not(Box<Filter>),
+    Compare {
+        field: String,
+        operator: Operator,
+        value: String,
+    },
+    IsEmpty {
+        field: String,
+    },
+    Exists {
+        field: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operator {
+    Eq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl Filter {
+    /// Evaluates the filter as Meilisearch does: a comparison on an array matches if any element
+    /// does, one on a missing attribute never matches, and `NOT` matches everything the negated
+    /// filter does not, including documents without the attribute.
+    fn matches(&self, document: &Document) -> bool {
+        match self {
+            Filter::Or(filters) => filters.iter().any(|filter| filter.matches(document)),
+            Filter::And(filters) => filters.iter().all(|filter| filter.matches(document)),
+            Filter::Not(filter) => !filter.matches(document),
+            Filter::Compare {
+                field,
+                operator,
+                value,
+            } => match document.get(field) {
+                Some(Value::Array(elements)) => elements
+                    .iter()
+                    .any(|element| compare(element, *operator, value)),
+                Some(element) => compare(element, *operator, value),
+                None => false,
+            },
+            Filter::IsEmpty { field } => match document.get(field) {
+                Some(Value::Array(elements)) => elements.is_empty(),
+                Some(Value::String(value)) => value.is_empty(),
+                Some(Value::Object(value)) => value.is_empty(),
+                _ => false,
+            },
+            Filter::Exists { field } => document.contains_key(field),
+        }
+    }
+}
+
+/// Compares numerically when both sides are numbers, and otherwise as case-insensitive strings,
+/// which only support equality.
+fn compare(element: &Value, operator: Operator, value: &str) -> bool {
+    if let (Some(element), Ok(value)) = (element.as_f64(), value.parse::<f64>()) {
+        return match operator {
+            Operator::Eq => element == value,
+            Operator::Gt => element > value,
+            Operator::Gte => element >= value,
+            Operator::Lt => element < value,
+            Operator::Lte => element <= value,
+        };
+    }
+
+    let element = match element {
+        Value::String(element) => element.clone(),
+        Value::Bool(element) => element.to_string(),
+        Value::Number(element) => element.to_string(),
+        _ => return false,
+    };
+
+    matches!(operator, Operator::Eq) && element.to_lowercase() == value.to_lowercase()
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    Open,
+    Close,
+    Operator(String),
+    Quoted(String),
+    Word(String),
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '=' | '!' | '<' | '>' => {
+                let mut operator = c.to_string();
+
+                if let Some(c) = chars.next_if_eq(&'=') {
+                    operator.push(c);
+                }
+
+                tokens.push(Token::Operator(operator));
+            }
+            '\'' | '"' => {
+                let mut value = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => value.push(escaped),
+                            None => return Err(format!("unterminated string in `{expression}`")),
+                        },
+                        Some(quote) if quote == c => break,
+                        Some(other) => value.push(other),
+                        None => return Err(format!("unterminated string in `{expression}`")),
+                    }
+                }
+
+                tokens.push(Token::Quoted(value));
+            }
+            c => {
+                let mut word = c.to_string();
+
+                while let Some(c) =
+                    chars.next_if(|c| !c.is_whitespace() && !"()=!<>'\"".contains(*c))
+                {
+                    word.push(c);
+                }
+
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    filterable_attributes: &'a [&'a str],
+}
+
+impl Parser<'_> {
+    fn bump(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    /// Consumes the keyword if it comes next.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let is_next =
+            matches!(self.tokens.get(self.position), Some(Token::Word(word)) if word == keyword);
+
+        if is_next {
+            self.position += 1;
+        }
+
+        is_next
+    }
+
+    fn or(&mut self) -> Result<Filter, String> {
+        let mut filters = vec![self.and()?];
+
+        while self.keyword("OR") {
+            filters.push(self.and()?);
+        }
+
+        Ok(match filters.len() {
+            1 => filters.pop().unwrap(),
+            _ => Filter::Or(filters),
+        })
+    }
+
+    fn and(&mut self) -> Result<Filter, String> {
+        let mut filters = vec![self.not()?];
+
+        while self.keyword("AND") {
+            filters.push(self.not()?);
+        }
+
+        Ok(match filters.len() {
+            1 => filters.pop().unwrap(),
+            _ => Filter::And(filters),
+        })
+    }
+
+    fn not(&mut self) -> Result<Filter, String> {
+        if self.keyword("NOT") {
+            return Ok(Filter::Not(Box::new(self.not()?)));
+        }
+
+        match self.bump() {
+            Some(Token::Open) => {
+                let filter = self.or()?;
+
+                match self.bump() {
+                    Some(Token::Close) => Ok(filter),
+                    token => Err(format!("expected `)`, found {token:?}")),
+                }
+            }
+            Some(Token::Word(field)) => self.condition(field),
+            token => Err(format!("expected an attribute, found {token:?}")),
+        }
+    }
+
+    fn condition(&mut self, field: String) -> Result<Filter, String> {
+        if !self.filterable_attributes.contains(&field.as_str()) {
+            return Err(format!("attribute `{field}` is not filterable"));
+        }
+
+        match self.bump() {
+            Some(Token::Operator(operator)) => {
+                let value = match self.bump() {
+                    Some(Token::Quoted(value) | Token::Word(value)) => value,
+                    token => return Err(format!("expected a value, found {token:?}")),
+                };
+                let compare = |operator| Filter::Compare {
+                    field,
+                    operator,
+                    value,
+                };
+
+                match operator.as_str() {
+                    "=" => Ok(compare(Operator::Eq)),
+                    "!=" => Ok(Filter::Not(Box::new(compare(Operator::Eq)))),
+                    ">" => Ok(compare(Operator::Gt)),
+                    ">=" => Ok(compare(Operator::Gte)),
+                    "<" => Ok(compare(Operator::Lt)),
+                    "<=" => Ok(compare(Operator::Lte)),
+                    operator => Err(format!("unknown operator `{operator}`")),
+                }
+            }
+            Some(Token::Word(word)) if word == "IS" => {
+                let negated = self.keyword("NOT");
+
+                if !self.keyword("EMPTY") {
+                    return Err("expected `EMPTY`".to_owned());
+                }
+
+                let filter = Filter::IsEmpty { field };
+
+                Ok(match negated {
+                    true => Filter::Not(Box::new(filter)),
+                    false => filter,
+                })
+            }
+            Some(Token::Word(word)) if word == "EXISTS" => Ok(Filter::Exists { field }),
+            Some(Token::Word(word)) if word == "NOT" && self.keyword("EXISTS") => {
+                Ok(Filter::Not(Box::new(Filter::Exists { field })))
+            }
+            token => Err(format!(
+                "expected a condition on `{field}`, found {token:?}"
+            )),
+        }
+    }
+}
+
+/// Parses the expression, rejecting it as Meilisearch would if it is malformed or names an
+/// attribute that is not filterable.
+fn parse_filter(
+    expression: &str,
+    filterable_attributes: &[&str],
+) -> Result<Filter, IndexServiceError> {
+    let parse = || {
+        let mut parser = Parser {
+            tokens: tokenize(expression)?,
+            position: 0,
+            filterable_attributes,
+        };
+        let filter = parser.or()?;
+
+        match parser.position < parser.tokens.len() {
+            true => Err(format!("unexpected {:?}", parser.tokens[parser.position])),
+            false => Ok(filter),
+        }
+    };
+
+    parse().map_err(|message| {
+        IndexServiceError::MeilisearchError(Error::Meilisearch(MeilisearchError {
+            error_message: format!("invalid filter `{expression}`: {message}"),
+            error_code: ErrorCode::InvalidSearchFilter,
+            error_type: ErrorType::InvalidRequest,
+            error_link: String::new(),
+        }))
+    })
+}
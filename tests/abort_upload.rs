@@ -0,0 +1,108 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::{files::File, SimpleOk};
+use rocket::http::{RawStr, Status};
+use uuid::Uuid;
+
+fn abort_uri(file_id: Uuid, upload_id: &str, delete_file: bool) -> String {
+    format!(
+        "/files/{file_id}/upload-urls/{}?delete-file={delete_file}",
+        RawStr::new(upload_id).percent_encode()
+    )
+}
+
+/// Answers whether the file has a row and how many tags it has, since unready files are not
+/// answered by `GET /files/<id>`.
+async fn stored_file(app: &TestApp, file_id: Uuid) -> (bool, i64) {
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+
+    sqlx::query_as(
+        "SELECT
+    EXISTS (SELECT 1 FROM files WHERE id = $1),
+    (SELECT COUNT(*) FROM file_tags WHERE file_id = $1)",
+    )
+    .bind(file_id)
+    .fetch_one(&db_pool)
+    .await
+    .unwrap()
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn aborting_an_upload_keeps_the_file_by_default() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app
+        .create_file("draft.pdf", b"never uploaded", &["draft"])
+        .await;
+    let upload = app.start_upload(file.id).await;
+
+    let aborted: SimpleOk = app.delete(&abort_uri(file.id, &upload.id, false)).await;
+    assert!(aborted.ok);
+
+    assert_eq!(stored_file(&app, file.id).await, (true, 1));
+
+    // the upload is gone, and there is no file to delete along with it
+    assert_eq!(
+        app.delete_for_status(&abort_uri(file.id, &upload.id, false))
+            .await,
+        Status::NotFound
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn aborting_an_upload_can_delete_the_file() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app
+        .create_file("draft.pdf", b"never uploaded", &["draft"])
+        .await;
+    let upload = app.start_upload(file.id).await;
+
+    let aborted: SimpleOk = app.delete(&abort_uri(file.id, &upload.id, true)).await;
+    assert!(aborted.ok);
+
+    assert_eq!(stored_file(&app, file.id).await, (false, 0));
+    assert!(app
+        .search_index
+        .as_ref()
+        .unwrap()
+        .file_document(file.id)
+        .is_none());
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_file_is_deleted_even_if_its_upload_is_gone() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app
+        .create_file("draft.pdf", b"never uploaded", &["draft"])
+        .await;
+    let upload = app.start_upload(file.id).await;
+
+    let _: SimpleOk = app.delete(&abort_uri(file.id, &upload.id, false)).await;
+
+    let aborted: SimpleOk = app.delete(&abort_uri(file.id, &upload.id, true)).await;
+    assert!(aborted.ok);
+
+    assert_eq!(stored_file(&app, file.id).await, (false, 0));
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_ready_file_is_not_deleted_by_aborting_an_upload() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app
+        .create_uploaded_file("report.pdf", b"uploaded content", &[])
+        .await;
+    let upload = app.start_upload(file.id).await;
+
+    assert_eq!(
+        app.delete_for_status(&abort_uri(file.id, &upload.id, true))
+            .await,
+        Status::Conflict
+    );
+
+    let kept: File = app.get(&format!("/files/{}", file.id)).await;
+    assert!(kept.is_ready);
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::{
+    interfaces::{
+        admins::{
+            Admin, AdminRole, AdminTask, AdminTaskInitiator, AdminTaskPreview, AdminTaskStatus,
+            TaskKind,
+        },
+        collections::{Collection, CreatingCollection},
+        WithAdminTask,
+    },
+    services::admin_task_service::AdminTaskService,
+};
+use rocket::http::{Header, Status};
+use serde_json::json;
+use uuid::Uuid;
+
+async fn create_collection_as(app: &TestApp, token: &str, name: &str) -> WithAdminTask<Collection> {
+    let response = app
+        .client
+        .post("/collections")
+        .header(Header::new("Authorization", format!("Bearer {token}")))
+        .json(&CreatingCollection {
+            name: name.to_owned(),
+            unique_names: false,
+            tags: Vec::new(),
+        })
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    response.into_json().await.unwrap()
+}
+
+async fn task(app: &TestApp, admin_task_id: Option<Uuid>) -> AdminTask {
+    let admin_task_id = admin_task_id.expect("the mutation has no admin task");
+    app.get(&format!("/admin-tasks/{admin_task_id}")).await
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn tasks_are_attributed_to_the_admin_who_enqueued_them() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let admin: Admin = app.get("/admins/me").await;
+    let editor_token = app.log_in_as(AdminRole::Editor).await;
+
+    let by_admin = create_collection_as(&app, &app.token, "Reports").await;
+    let by_admin = task(&app, by_admin.admin_task_id).await;
+    assert_eq!(by_admin.initiator, AdminTaskInitiator::User);
+    let initiated_by = by_admin.initiated_by.unwrap();
+    assert_eq!(initiated_by.id, admin.id);
+    assert_eq!(initiated_by.username, admin.username);
+
+    let by_editor = create_collection_as(&app, &editor_token, "Drafts").await;
+    let by_editor = task(&app, by_editor.admin_task_id).await;
+    assert_eq!(by_editor.initiator, AdminTaskInitiator::User);
+    let initiated_by = by_editor.initiated_by.unwrap();
+    assert_ne!(initiated_by.id, admin.id);
+    assert_eq!(initiated_by.username, "editor-admin");
+
+    let previews: Vec<AdminTaskPreview> = app.get("/admin-tasks").await;
+    let preview = previews
+        .iter()
+        .find(|preview| preview.id == by_editor.id)
+        .unwrap();
+    assert_eq!(
+        preview.initiated_by.as_ref().map(|by| by.id),
+        Some(initiated_by.id)
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn system_tasks_are_attributed_to_nobody() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+
+    let queued = AdminTaskService::new(db_pool)
+        .enqueue_task(
+            AdminTaskInitiator::System,
+            None,
+            TaskKind::UpdateFile,
+            json!({}),
+            Some(AdminTaskStatus::Completed),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+
+    let task = task(&app, Some(queued.id)).await;
+    assert_eq!(task.initiator, AdminTaskInitiator::System);
+    assert!(task.initiated_by.is_none(), "{task:#?}");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn tasks_are_filtered_by_the_admin_who_enqueued_them() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let admin: Admin = app.get("/admins/me").await;
+    let editor_token = app.log_in_as(AdminRole::Editor).await;
+
+    let by_admin = create_collection_as(&app, &app.token, "Reports").await;
+    let by_editor = create_collection_as(&app, &editor_token, "Drafts").await;
+    let editor_id = task(&app, by_editor.admin_task_id)
+        .await
+        .initiated_by
+        .unwrap()
+        .id;
+
+    let tasks: Vec<AdminTaskPreview> = app
+        .get(&format!("/admin-tasks?initiated-by={editor_id}"))
+        .await;
+    assert_eq!(
+        Vec::from_iter(tasks.iter().map(|task| task.id)),
+        vec![by_editor.admin_task_id.unwrap()]
+    );
+
+    let tasks: Vec<AdminTaskPreview> = app
+        .get(&format!("/admin-tasks?initiated-by={}", admin.id))
+        .await;
+    assert!(tasks
+        .iter()
+        .any(|task| Some(task.id) == by_admin.admin_task_id));
+    assert!(tasks
+        .iter()
+        .all(|task| task.initiated_by.as_ref().map(|by| by.id) == Some(admin.id)));
+
+    let tasks: Vec<AdminTaskPreview> = app
+        .get(&format!("/admin-tasks?initiated-by={}", Uuid::new_v4()))
+        .await;
+    assert!(tasks.is_empty(), "{tasks:#?}");
+}
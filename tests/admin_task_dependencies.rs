@@ -0,0 +1,180 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::{
+    interfaces::admins::{AdminTask, AdminTaskInitiator, AdminTaskStatus, TaskKind},
+    services::admin_task_service::AdminTaskService,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+/// Enqueues a pending task. None of the kinds used here has a handler, so the task runner of the
+/// app leaves the tasks as they are.
+async fn enqueue(
+    admin_task_service: &AdminTaskService,
+    kind: TaskKind,
+    depends_on: Option<Uuid>,
+) -> AdminTask {
+    admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::System,
+            None,
+            kind,
+            json!({}),
+            None,
+            false,
+            depends_on,
+        )
+        .await
+        .unwrap()
+}
+
+async fn task(admin_task_service: &AdminTaskService, task_id: Uuid) -> AdminTask {
+    admin_task_service.get_task(task_id).await.unwrap().unwrap()
+}
+
+async fn active_task_id(admin_task_service: &AdminTaskService, kind: &TaskKind) -> Option<Uuid> {
+    admin_task_service
+        .get_last_active_task(kind)
+        .await
+        .unwrap()
+        .map(|task| task.id)
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_failed_dependency_cancels_its_dependent() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let admin_task_service =
+        AdminTaskService::new(sqlx::PgPool::connect(&app.database_url).await.unwrap());
+
+    let dependency = enqueue(&admin_task_service, TaskKind::UpdateFile, None).await;
+    let dependent = enqueue(
+        &admin_task_service,
+        TaskKind::DeleteFile,
+        Some(dependency.id),
+    )
+    .await;
+    assert_eq!(
+        active_task_id(&admin_task_service, &TaskKind::DeleteFile).await,
+        None
+    );
+
+    admin_task_service
+        .fail_task(dependency.id, "broken")
+        .await
+        .unwrap();
+    admin_task_service
+        .cancel_tasks_with_unsuccessful_dependencies()
+        .await
+        .unwrap();
+
+    let dependent = task(&admin_task_service, dependent.id).await;
+    assert_eq!(dependent.status, AdminTaskStatus::Canceled);
+    assert_eq!(
+        dependent.metadata["canceled_by_dependency"],
+        json!({ "id": dependency.id, "status": "failed" })
+    );
+    assert_eq!(
+        active_task_id(&admin_task_service, &TaskKind::DeleteFile).await,
+        None
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_canceled_dependency_cancels_its_dependent() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let admin_task_service =
+        AdminTaskService::new(sqlx::PgPool::connect(&app.database_url).await.unwrap());
+
+    let dependency = enqueue(&admin_task_service, TaskKind::UpdateFile, None).await;
+    let dependent = enqueue(
+        &admin_task_service,
+        TaskKind::DeleteFile,
+        Some(dependency.id),
+    )
+    .await;
+
+    admin_task_service
+        .cancel_task(dependency.id)
+        .await
+        .unwrap()
+        .unwrap();
+    admin_task_service
+        .cancel_tasks_with_unsuccessful_dependencies()
+        .await
+        .unwrap();
+
+    let dependent = task(&admin_task_service, dependent.id).await;
+    assert_eq!(dependent.status, AdminTaskStatus::Canceled);
+    assert_eq!(
+        dependent.metadata["canceled_by_dependency"],
+        json!({ "id": dependency.id, "status": "canceled" })
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_chain_of_three_tasks_runs_in_order() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let admin_task_service =
+        AdminTaskService::new(sqlx::PgPool::connect(&app.database_url).await.unwrap());
+
+    let first = enqueue(&admin_task_service, TaskKind::UpdateFile, None).await;
+    let second = enqueue(&admin_task_service, TaskKind::DeleteFile, Some(first.id)).await;
+    let third = enqueue(&admin_task_service, TaskKind::UploadFile, Some(second.id)).await;
+    let active = |kind| active_task_id(&admin_task_service, kind);
+
+    assert_eq!(active(&TaskKind::UpdateFile).await, Some(first.id));
+    assert_eq!(active(&TaskKind::DeleteFile).await, None);
+    assert_eq!(active(&TaskKind::UploadFile).await, None);
+
+    admin_task_service
+        .update_task_status(first.id, AdminTaskStatus::Completed)
+        .await
+        .unwrap();
+    assert_eq!(active(&TaskKind::DeleteFile).await, Some(second.id));
+    assert_eq!(active(&TaskKind::UploadFile).await, None);
+
+    admin_task_service
+        .update_task_status(second.id, AdminTaskStatus::Completed)
+        .await
+        .unwrap();
+    assert_eq!(active(&TaskKind::UploadFile).await, Some(third.id));
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_failure_cascades_down_a_chain_of_three_tasks() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let admin_task_service =
+        AdminTaskService::new(sqlx::PgPool::connect(&app.database_url).await.unwrap());
+
+    let first = enqueue(&admin_task_service, TaskKind::UpdateFile, None).await;
+    let second = enqueue(&admin_task_service, TaskKind::DeleteFile, Some(first.id)).await;
+    let third = enqueue(&admin_task_service, TaskKind::UploadFile, Some(second.id)).await;
+
+    admin_task_service
+        .fail_task(first.id, "broken")
+        .await
+        .unwrap();
+    admin_task_service
+        .cancel_tasks_with_unsuccessful_dependencies()
+        .await
+        .unwrap();
+
+    // each task records the dependency it waited on, not the one that failed first
+    let second = task(&admin_task_service, second.id).await;
+    assert_eq!(second.status, AdminTaskStatus::Canceled);
+    assert_eq!(
+        second.metadata["canceled_by_dependency"],
+        json!({ "id": first.id, "status": "failed" })
+    );
+    let third = task(&admin_task_service, third.id).await;
+    assert_eq!(third.status, AdminTaskStatus::Canceled);
+    assert_eq!(
+        third.metadata["canceled_by_dependency"],
+        json!({ "id": second.id, "status": "canceled" })
+    );
+}
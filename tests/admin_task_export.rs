@@ -0,0 +1,126 @@
+mod common;
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use common::TestApp;
+use file_indexer::{
+    interfaces::admins::{AdminTask, AdminTaskInitiator, AdminTaskStatus, TaskKind},
+    services::admin_task_service::AdminTaskService,
+};
+use rocket::http::Status;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+async fn enqueue(admin_task_service: &AdminTaskService, metadata: Value) -> Uuid {
+    admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::System,
+            None,
+            TaskKind::UpdateFile,
+            metadata,
+            Some(AdminTaskStatus::Completed),
+            false,
+            None,
+        )
+        .await
+        .unwrap()
+        .id
+}
+
+fn export_uri(from: DateTime<Utc>, to: DateTime<Utc>) -> String {
+    format!(
+        "/admin-tasks/export?from={}&to={}&format=ndjson",
+        from.to_rfc3339_opts(SecondsFormat::Micros, true),
+        to.to_rfc3339_opts(SecondsFormat::Micros, true)
+    )
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_export_is_one_task_per_line_oldest_first() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let admin_task_service = AdminTaskService::new(db_pool.clone());
+    let from = Utc::now() - Duration::seconds(1);
+
+    let first = enqueue(&admin_task_service, json!({ "note": "line one\nline two" })).await;
+    let second = enqueue(
+        &admin_task_service,
+        json!({ "nested": { "path": "a\r\nb" } }),
+    )
+    .await;
+    sqlx::query("UPDATE admin_tasks SET error = $2 WHERE id = $1")
+        .bind(second)
+        .bind("first failure\nsecond failure")
+        .execute(&db_pool)
+        .await
+        .unwrap();
+    let third = enqueue(&admin_task_service, json!({ "note": "plain" })).await;
+    // enqueued before the range
+    let earlier = enqueue(&admin_task_service, json!({ "note": "earlier" })).await;
+    sqlx::query(
+        "UPDATE admin_tasks SET enqueued_at = enqueued_at - INTERVAL '2 days' WHERE id = $1",
+    )
+    .bind(earlier)
+    .execute(&db_pool)
+    .await
+    .unwrap();
+
+    let (content_type, body) = app
+        .get_text(&export_uri(from, Utc::now() + Duration::seconds(1)))
+        .await;
+    assert_eq!(
+        content_type.map(|content_type| content_type.to_string()),
+        Some("application/x-ndjson".to_owned())
+    );
+
+    let tasks = Vec::from_iter(body.lines().map(|line| {
+        let value: Value = serde_json::from_str(line)
+            .unwrap_or_else(|err| panic!("not a JSON line ({err}): {line}"));
+        assert!(value.is_object(), "{line}");
+        serde_json::from_value::<AdminTask>(value).unwrap()
+    }));
+    assert!(body.ends_with('\n'), "{body}");
+
+    let exported = Vec::from_iter(
+        tasks
+            .iter()
+            .map(|task| task.id)
+            .filter(|id| [first, second, third, earlier].contains(id)),
+    );
+    assert_eq!(exported, [first, second, third]);
+    assert!(tasks
+        .windows(2)
+        .all(|tasks| tasks[0].enqueued_at <= tasks[1].enqueued_at));
+
+    let task = |id: Uuid| tasks.iter().find(|task| task.id == id).unwrap();
+    assert_eq!(task(first).metadata["note"], "line one\nline two");
+    assert_eq!(task(second).metadata["nested"]["path"], "a\r\nb");
+    assert_eq!(
+        task(second).error.as_deref(),
+        Some("first failure\nsecond failure")
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_range_must_be_bounded() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let now = Utc::now();
+
+    for (from, to) in [
+        (now, now),
+        (now, now - Duration::seconds(1)),
+        (now - Duration::days(31) - Duration::seconds(1), now),
+    ] {
+        let (status, error) = app.get_for_error(&export_uri(from, to)).await;
+        assert_eq!(status, Status::UnprocessableEntity, "{from} {to}");
+        assert_eq!(error["fields"][0]["field"], "to", "{from} {to}");
+    }
+
+    let (_, body) = app
+        .get_text(&export_uri(now - Duration::days(31), now))
+        .await;
+    assert!(body
+        .lines()
+        .all(|line| serde_json::from_str::<AdminTask>(line).is_ok()));
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::admins::{AdminTask, AdminTaskStatus};
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_task_failed_by_a_search_outage_keeps_its_error() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let search_index = app.search_index.clone().unwrap();
+
+    search_index.set_unavailable(true);
+
+    let task = app.re_index().await;
+    let file_task = app.wait_for_task(task.file_task.id).await;
+    assert_eq!(file_task.status, AdminTaskStatus::Failed, "{file_task:#?}");
+
+    let file_task: AdminTask = app.get(&format!("/admin-tasks/{}", file_task.id)).await;
+    let error = file_task
+        .error
+        .as_deref()
+        .expect("failed task has no error");
+    assert!(error.contains("connection refused"), "{error}");
+    let failed_at = file_task
+        .failed_at
+        .expect("failed task has no failure time");
+    assert!(failed_at >= file_task.enqueued_at, "{file_task:#?}");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_completed_task_has_no_error() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    let task = app.re_index().await;
+    let file_task = app.wait_for_task(task.file_task.id).await;
+
+    assert_eq!(
+        file_task.status,
+        AdminTaskStatus::Completed,
+        "{file_task:#?}"
+    );
+    assert_eq!(file_task.error, None);
+    assert_eq!(file_task.failed_at, None);
+}
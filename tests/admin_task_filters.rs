@@ -0,0 +1,172 @@
+mod common;
+
+use chrono::SecondsFormat;
+use common::TestApp;
+use file_indexer::{
+    interfaces::admins::{Admin, AdminTaskInitiator, AdminTaskPreview, AdminTaskStatus, TaskKind},
+    services::admin_task_service::AdminTaskService,
+};
+use rocket::http::{RawStr, Status};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+async fn enqueue(
+    admin_task_service: &AdminTaskService,
+    admin_id: Option<Uuid>,
+    metadata: Value,
+) -> Uuid {
+    let initiator = match admin_id {
+        Some(_) => AdminTaskInitiator::User,
+        None => AdminTaskInitiator::System,
+    };
+
+    admin_task_service
+        .enqueue_task(
+            initiator,
+            admin_id,
+            TaskKind::UpdateFile,
+            metadata,
+            Some(AdminTaskStatus::Completed),
+            false,
+            None,
+        )
+        .await
+        .unwrap()
+        .id
+}
+
+fn contains_uri(metadata: &Value, rest: &str) -> String {
+    format!(
+        "/admin-tasks?metadata-contains={}{rest}",
+        RawStr::new(&metadata.to_string()).percent_encode()
+    )
+}
+
+fn ids(tasks: &[AdminTaskPreview]) -> Vec<Uuid> {
+    let mut ids = Vec::from_iter(tasks.iter().map(|task| task.id));
+    ids.sort();
+    ids
+}
+
+fn sorted(mut ids: Vec<Uuid>) -> Vec<Uuid> {
+    ids.sort();
+    ids
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn tasks_are_filtered_by_the_metadata_they_contain() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let admin_task_service = AdminTaskService::new(db_pool);
+    let file_id = Uuid::new_v4();
+    let other_file_id = Uuid::new_v4();
+
+    let renamed = enqueue(
+        &admin_task_service,
+        None,
+        json!({ "file_id": file_id, "delta": { "name": "report.pdf", "size": 10 } }),
+    )
+    .await;
+    let tagged = enqueue(
+        &admin_task_service,
+        None,
+        json!({ "file_id": file_id, "delta": { "tags_for_creation": ["final"] } }),
+    )
+    .await;
+    let other = enqueue(
+        &admin_task_service,
+        None,
+        json!({ "file_id": other_file_id, "delta": { "name": "report.pdf" } }),
+    )
+    .await;
+
+    let tasks: Vec<AdminTaskPreview> = app
+        .get(&contains_uri(&json!({ "file_id": file_id }), ""))
+        .await;
+    assert_eq!(ids(&tasks), sorted(vec![renamed, tagged]));
+
+    let tasks: Vec<AdminTaskPreview> = app
+        .get(&contains_uri(
+            &json!({ "delta": { "name": "report.pdf" } }),
+            "",
+        ))
+        .await;
+    assert_eq!(ids(&tasks), sorted(vec![renamed, other]));
+
+    let tasks: Vec<AdminTaskPreview> = app
+        .get(&contains_uri(
+            &json!({ "file_id": file_id, "delta": { "tags_for_creation": ["final"] } }),
+            "",
+        ))
+        .await;
+    assert_eq!(ids(&tasks), vec![tagged]);
+
+    let tasks: Vec<AdminTaskPreview> = app
+        .get(&contains_uri(&json!({ "file_id": Uuid::new_v4() }), ""))
+        .await;
+    assert!(tasks.is_empty(), "{tasks:#?}");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_metadata_filter_composes_with_the_initiator_and_pagination() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let admin_task_service = AdminTaskService::new(db_pool);
+    let admin: Admin = app.get("/admins/me").await;
+    let metadata = json!({ "file_id": Uuid::new_v4() });
+
+    let mut by_admin = Vec::new();
+    for _ in 0..3 {
+        by_admin.push(enqueue(&admin_task_service, Some(admin.id), metadata.clone()).await);
+    }
+    enqueue(&admin_task_service, None, metadata.clone()).await;
+
+    let initiated_by = format!("&initiated-by={}&limit=2", admin.id);
+    let first_page: Vec<AdminTaskPreview> = app.get(&contains_uri(&metadata, &initiated_by)).await;
+    assert_eq!(first_page.len(), 2);
+
+    let last = first_page.last().unwrap();
+    let cursor = format!(
+        "{initiated_by}&last-admin-task-id={}&last-admin-task-updated-at={}",
+        last.id,
+        last.updated_at.to_rfc3339_opts(SecondsFormat::Micros, true)
+    );
+    let second_page: Vec<AdminTaskPreview> = app.get(&contains_uri(&metadata, &cursor)).await;
+    assert_eq!(second_page.len(), 1);
+
+    let mut listed = ids(&first_page);
+    listed.extend(ids(&second_page));
+    assert_eq!(sorted(listed), sorted(by_admin));
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_metadata_filter_must_be_a_shallow_json_object() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    for filter in [
+        "[1]",
+        "\"file_id\"",
+        "{\"file_id\":",
+        "{\"a\":{\"b\":{\"c\":{\"d\":{}}}}}",
+    ] {
+        let uri = format!(
+            "/admin-tasks?metadata-contains={}",
+            RawStr::new(filter).percent_encode()
+        );
+        assert_eq!(
+            app.get_for_status(&uri).await,
+            Status::UnprocessableEntity,
+            "{filter}"
+        );
+    }
+
+    // nested as deep as allowed
+    let uri = format!(
+        "/admin-tasks?metadata-contains={}",
+        RawStr::new("{\"a\":{\"b\":{\"c\":{}}}}").percent_encode()
+    );
+    assert_eq!(app.get_for_status(&uri).await, Status::Ok);
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::{
+    interfaces::admins::{AdminTaskInitiator, AdminTaskStatus, TaskKind},
+    services::admin_task_service::{AdminTaskCursor, AdminTaskService},
+};
+use serde_json::json;
+use uuid::Uuid;
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn pages_of_tasks_updated_at_the_same_time_neither_skip_nor_repeat_tasks() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let admin_task_service = AdminTaskService::new(db_pool.clone());
+    let marker = json!({ "listing": true });
+
+    let mut task_ids = Vec::new();
+
+    for _ in 0..7 {
+        let task = admin_task_service
+            .enqueue_task(
+                AdminTaskInitiator::System,
+                None,
+                TaskKind::UpdateFile,
+                marker.clone(),
+                Some(AdminTaskStatus::Completed),
+                false,
+                None,
+            )
+            .await
+            .unwrap();
+        task_ids.push(task.id);
+    }
+
+    // the update trigger stamps every row of a statement with the time its transaction started,
+    // so three tasks share one timestamp and four a later one; a task on the next page may then
+    // well have a smaller id than the last one of the page before
+    for task_ids in [&task_ids[4..], &task_ids[..4]] {
+        sqlx::query("UPDATE admin_tasks SET status = status WHERE id = ANY($1)")
+            .bind(task_ids)
+            .execute(&db_pool)
+            .await
+            .unwrap();
+    }
+
+    let expected: Vec<Uuid> = sqlx::query_scalar(
+        "SELECT id FROM admin_tasks WHERE id = ANY($1) ORDER BY updated_at DESC, id ASC",
+    )
+    .bind(&task_ids)
+    .fetch_all(&db_pool)
+    .await
+    .unwrap();
+    let timestamp_count: i64 =
+        sqlx::query_scalar("SELECT COUNT(DISTINCT updated_at) FROM admin_tasks WHERE id = ANY($1)")
+            .bind(&task_ids)
+            .fetch_one(&db_pool)
+            .await
+            .unwrap();
+    assert!(timestamp_count <= 2, "{timestamp_count} timestamps");
+
+    let mut listed = Vec::<Uuid>::new();
+    let mut cursor = None;
+
+    loop {
+        let page = admin_task_service
+            .list_tasks(2, cursor, true, Some(marker.clone()), None)
+            .await
+            .unwrap();
+
+        let Some(last) = page.last() else {
+            break;
+        };
+        cursor = Some(AdminTaskCursor {
+            id: last.id,
+            updated_at: last.updated_at,
+        });
+        listed.extend(page.iter().map(|task| task.id));
+    }
+
+    assert_eq!(listed, expected);
+}
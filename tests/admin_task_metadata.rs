@@ -0,0 +1,168 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::{
+    interfaces::{
+        admins::{AdminTask, AdminTaskInitiator, AdminTaskStatus, TaskKind},
+        files::{File, UploadedParts},
+        WithAdminTask,
+    },
+    services::admin_task_service::{
+        truncate_metadata, AdminTaskService, AdminTaskServiceError, MAX_METADATA_SIZE,
+    },
+};
+use rocket::http::Status;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+#[test]
+fn metadata_within_the_limit_is_kept_as_is() {
+    let metadata = json!({ "file_id": Uuid::nil(), "count": 3 });
+
+    assert_eq!(truncate_metadata(metadata.clone()), metadata);
+}
+
+#[test]
+fn oversized_metadata_drops_its_largest_fields_and_is_marked_truncated() {
+    let metadata = json!({
+        "file_id": Uuid::nil(),
+        "large": "a".repeat(MAX_METADATA_SIZE),
+        "medium": "b".repeat(MAX_METADATA_SIZE / 2),
+        "count": 3,
+    });
+
+    let truncated = truncate_metadata(metadata);
+    assert_eq!(
+        truncated,
+        json!({
+            "file_id": Uuid::nil(),
+            "medium": "b".repeat(MAX_METADATA_SIZE / 2),
+            "count": 3,
+            "truncated": true,
+        })
+    );
+    assert!(truncated.to_string().len() <= MAX_METADATA_SIZE);
+}
+
+#[test]
+fn oversized_metadata_that_is_not_an_object_is_replaced_by_the_marker() {
+    let metadata = json!(["a".repeat(MAX_METADATA_SIZE)]);
+
+    assert_eq!(truncate_metadata(metadata), json!({ "truncated": true }));
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn oversized_metadata_is_refused_for_a_task_still_to_run_and_truncated_for_a_finished_one() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let admin_task_service = AdminTaskService::new(db_pool);
+    let metadata = json!({ "file_ids": vec![Uuid::nil(); 2000], "count": 2000 });
+
+    let result = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::System,
+            None,
+            TaskKind::RetryIndex,
+            metadata.clone(),
+            None,
+            false,
+            None,
+        )
+        .await;
+    assert!(
+        matches!(result, Err(AdminTaskServiceError::MetadataTooLarge { .. })),
+        "{result:?}"
+    );
+
+    let task = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::System,
+            None,
+            TaskKind::BulkTagFiles,
+            metadata,
+            Some(AdminTaskStatus::Completed),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+    let task: AdminTask = app.get(&format!("/admin-tasks/{}", task.id)).await;
+    assert_eq!(task.metadata, json!({ "count": 2000, "truncated": true }));
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn upload_task_records_the_upload_instead_of_its_parts() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let content = b"recorded without its parts";
+    let file = app.create_file("trimmed.txt", content, &[]).await;
+    let upload = app.start_upload(file.id).await;
+    let parts = app.upload_parts(file.id, &upload, content).await;
+
+    let completed: WithAdminTask<File> = app
+        .post(
+            &TestApp::completion_uri(file.id, &upload.id),
+            &UploadedParts { parts },
+        )
+        .await;
+    let task_id = completed.admin_task_id.expect("no admin task recorded");
+    let task: AdminTask = app.get(&format!("/admin-tasks/{task_id}")).await;
+
+    assert_eq!(task.metadata["file_id"], json!(file.id));
+    assert_eq!(task.metadata["upload_id"], json!(upload.id));
+    assert_eq!(task.metadata["size"], json!(content.len()));
+    for key in ["content", "parts"] {
+        assert!(task.metadata.get(key).is_none(), "{:#}", task.metadata);
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn bulk_deletion_counts_protected_files_and_keeps_its_queue_out_of_the_metadata() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let deleted = app
+        .create_uploaded_file("deleted.txt", b"deleted", &[])
+        .await;
+    let protected = app
+        .create_uploaded_file("protected.txt", b"protected", &[])
+        .await;
+    let (status, body) = app
+        .patch_for_error(
+            &format!("/files/{}", protected.id),
+            &json!({ "protected": true }),
+        )
+        .await;
+    assert_eq!(status, Status::Ok, "{body}");
+
+    let task: AdminTask = app
+        .post(
+            "/files/bulk-delete",
+            &json!({ "fileIds": [deleted.id, protected.id] }),
+        )
+        .await;
+    assert!(
+        task.metadata.get("file_ids").is_none(),
+        "{:#}",
+        task.metadata
+    );
+    let task = app.wait_for_task(task.id).await;
+
+    assert_eq!(task.status, AdminTaskStatus::Completed, "{task:#?}");
+    assert_eq!(task.metadata["file_count"], 2);
+    assert_eq!(task.metadata["processed_count"], 2);
+    assert_eq!(task.metadata["deleted_count"], 1);
+    assert_eq!(task.metadata["protected_count"], 1);
+    assert_eq!(task.metadata["protected_file_ids"], json!([protected.id]));
+
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let queued: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM admin_task_queued_files WHERE task_id = $1")
+            .bind(task.id)
+            .fetch_one(&db_pool)
+            .await
+            .unwrap();
+    assert_eq!(queued, 0);
+    let file: Value = app.get(&format!("/files/{}", protected.id)).await;
+    assert_eq!(file["id"], json!(protected.id));
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::TestApp;
+use file_indexer::{
+    db::repositories::PRUNE_CHUNK_SIZE, services::admin_task_service::AdminTaskService,
+};
+use sqlx::PgPool;
+
+/// Tasks of the test finished long before any the app records of its own, so only they are
+/// old enough to be pruned.
+const FINISHED_AT: &str = "2000-01-01T00:00:00Z";
+
+fn finished_before() -> DateTime<Utc> {
+    "2000-01-02T00:00:00Z".parse().unwrap()
+}
+
+async fn insert_finished_tasks(db_pool: &PgPool, count: i64) {
+    sqlx::query(
+        "INSERT INTO admin_tasks (initiator, name, metadata, status, enqueued_at, updated_at)
+SELECT 'system', 'update-file', '{}', 'completed', $2::TIMESTAMPTZ, $2::TIMESTAMPTZ
+FROM generate_series(1, $1)",
+    )
+    .bind(count)
+    .bind(FINISHED_AT)
+    .execute(db_pool)
+    .await
+    .unwrap();
+}
+
+async fn old_task_count(db_pool: &PgPool) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM admin_tasks WHERE updated_at < $1")
+        .bind(finished_before().naive_utc())
+        .fetch_one(db_pool)
+        .await
+        .unwrap()
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn finished_tasks_are_pruned_across_chunk_boundaries() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let db_pool = PgPool::connect(&app.database_url).await.unwrap();
+    let admin_task_service = AdminTaskService::new(db_pool.clone());
+
+    for count in [
+        PRUNE_CHUNK_SIZE - 1,
+        PRUNE_CHUNK_SIZE,
+        PRUNE_CHUNK_SIZE + 1,
+        2 * PRUNE_CHUNK_SIZE + 1,
+    ] {
+        insert_finished_tasks(&db_pool, count).await;
+
+        let deleted_count = admin_task_service
+            .delete_finished_tasks(finished_before())
+            .await
+            .unwrap();
+        assert_eq!(deleted_count, count as u64, "{count} tasks");
+        assert_eq!(old_task_count(&db_pool).await, 0, "{count} tasks");
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn pruning_without_finished_tasks_deletes_nothing() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let db_pool = PgPool::connect(&app.database_url).await.unwrap();
+    let admin_task_service = AdminTaskService::new(db_pool.clone());
+    let task_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM admin_tasks")
+        .fetch_one(&db_pool)
+        .await
+        .unwrap();
+
+    let deleted_count = admin_task_service
+        .delete_finished_tasks(finished_before())
+        .await
+        .unwrap();
+    assert_eq!(deleted_count, 0);
+
+    let remaining_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM admin_tasks")
+        .fetch_one(&db_pool)
+        .await
+        .unwrap();
+    assert!(task_count <= remaining_count);
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use chrono::SecondsFormat;
+use common::TestApp;
+use file_indexer::{
+    interfaces::admins::{AdminTaskInitiator, AdminTaskPreview, AdminTaskStatus, TaskKind},
+    services::admin_task_service::AdminTaskService,
+};
+use rocket::http::{RawStr, Status};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+async fn enqueue(admin_task_service: &AdminTaskService, metadata: Value) -> Uuid {
+    admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::System,
+            None,
+            TaskKind::UpdateFile,
+            metadata,
+            Some(AdminTaskStatus::Completed),
+            false,
+            None,
+        )
+        .await
+        .unwrap()
+        .id
+}
+
+fn search_uri(q: &str, rest: &str) -> String {
+    format!(
+        "/admin-tasks/search?q={}{rest}",
+        RawStr::new(q).percent_encode()
+    )
+}
+
+fn ids(tasks: &[AdminTaskPreview]) -> Vec<Uuid> {
+    let mut ids = Vec::from_iter(tasks.iter().map(|task| task.id));
+    ids.sort();
+    ids
+}
+
+fn sorted(mut ids: Vec<Uuid>) -> Vec<Uuid> {
+    ids.sort();
+    ids
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn tasks_are_found_by_nested_metadata_values_and_errors() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let admin_task_service = AdminTaskService::new(db_pool.clone());
+
+    let renamed = enqueue(
+        &admin_task_service,
+        json!({ "delta": { "name": "Quarterly-Report.pdf" } }),
+    )
+    .await;
+    let listed = enqueue(
+        &admin_task_service,
+        json!({ "batch": [{ "files": [{ "path": "archive/quarterly-report.pdf" }] }] }),
+    )
+    .await;
+    let failed = enqueue(&admin_task_service, json!({ "attempt": 3 })).await;
+    sqlx::query("UPDATE admin_tasks SET error = $2 WHERE id = $1")
+        .bind(failed)
+        .bind("bucket unreachable: connection refused")
+        .execute(&db_pool)
+        .await
+        .unwrap();
+
+    for (q, expected) in [
+        ("quarterly-report", vec![renamed, listed]),
+        ("QUARTERLY-REPORT.PDF", vec![renamed, listed]),
+        ("archive/", vec![listed]),
+        ("connection refused", vec![failed]),
+        // keys are not searched, only values
+        ("attempt", vec![]),
+        ("quarterly-report.docx", vec![]),
+    ] {
+        let tasks: Vec<AdminTaskPreview> = app.get(&search_uri(q, "")).await;
+        assert_eq!(ids(&tasks), sorted(expected), "{q}");
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn like_wildcards_in_the_query_match_literally() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let admin_task_service = AdminTaskService::new(db_pool);
+
+    let literal = enqueue(&admin_task_service, json!({ "name": "50%_off.txt" })).await;
+    enqueue(&admin_task_service, json!({ "name": "50x_off.txt" })).await;
+    enqueue(&admin_task_service, json!({ "name": "500-off.txt" })).await;
+
+    for q in ["50%", "%_off", "0%_"] {
+        let tasks: Vec<AdminTaskPreview> = app.get(&search_uri(q, "")).await;
+        assert_eq!(ids(&tasks), vec![literal], "{q}");
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn search_results_page_with_the_cursor() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let admin_task_service = AdminTaskService::new(db_pool);
+
+    let mut expected = Vec::new();
+    for index in 0..3 {
+        expected.push(
+            enqueue(
+                &admin_task_service,
+                json!({ "name": format!("paged-report-{index}.pdf") }),
+            )
+            .await,
+        );
+    }
+
+    let first_page: Vec<AdminTaskPreview> = app.get(&search_uri("paged-report", "&limit=2")).await;
+    assert_eq!(first_page.len(), 2);
+
+    let last = first_page.last().unwrap();
+    let cursor = format!(
+        "&limit=2&last-admin-task-id={}&last-admin-task-updated-at={}",
+        last.id,
+        last.updated_at.to_rfc3339_opts(SecondsFormat::Micros, true)
+    );
+    let second_page: Vec<AdminTaskPreview> = app.get(&search_uri("paged-report", &cursor)).await;
+    assert_eq!(second_page.len(), 1);
+
+    let mut listed = ids(&first_page);
+    listed.extend(ids(&second_page));
+    assert_eq!(sorted(listed), sorted(expected));
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_query_length_is_limited() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    for q in [String::new(), "a".repeat(257)] {
+        assert_eq!(
+            app.get_for_status(&search_uri(&q, "")).await,
+            Status::UnprocessableEntity,
+            "{} bytes",
+            q.len()
+        );
+    }
+    assert_eq!(
+        app.get_for_status(&search_uri(&"a".repeat(256), "")).await,
+        Status::Ok
+    );
+}
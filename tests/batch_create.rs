@@ -0,0 +1,124 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::files::{CreatingFile, CreatingFiles, File};
+use rocket::http::Status;
+
+fn creating_file(name: &str, tags: &[&str]) -> CreatingFile {
+    CreatingFile {
+        name: name.to_owned(),
+        size: 16,
+        mime_type: "text/plain".to_owned(),
+        checksum: None,
+        tags: Some(Vec::from_iter(tags.iter().map(|tag| (*tag).to_owned()))),
+        allow_duplicate: false,
+    }
+}
+
+async fn file_count(app: &TestApp) -> i64 {
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    sqlx::query_scalar("SELECT COUNT(*) FROM files")
+        .fetch_one(&db_pool)
+        .await
+        .unwrap()
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_full_batch_is_created_in_order() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let creating = Vec::from_iter((0..500).map(|index| {
+        creating_file(
+            &format!("file-{:03}.txt", 499 - index),
+            &["batch", &format!("part-{}", index % 7)],
+        )
+    }));
+
+    let files: Vec<File> = app
+        .post(
+            "/files/batch",
+            &CreatingFiles {
+                files: creating.clone(),
+            },
+        )
+        .await;
+
+    assert_eq!(files.len(), creating.len());
+    for (file, creating) in files.iter().zip(&creating) {
+        assert_eq!(file.name, creating.name);
+        assert!(!file.is_ready);
+
+        let mut tags = creating.tags.clone().unwrap();
+        tags.sort();
+        assert_eq!(file.tags, tags);
+    }
+    assert_eq!(file_count(&app).await, 500);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_batch_out_of_bounds_is_refused() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    for count in [0, 501] {
+        let (status, error) = app
+            .post_for_error(
+                "/files/batch",
+                &CreatingFiles {
+                    files: Vec::from_iter(
+                        (0..count).map(|index| creating_file(&format!("{index}.txt"), &[])),
+                    ),
+                },
+            )
+            .await;
+        assert_eq!(status, Status::UnprocessableEntity, "{count}");
+        assert_eq!(error["fields"][0]["field"], "files", "{count}");
+    }
+
+    assert_eq!(file_count(&app).await, 0);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn invalid_entries_fail_the_whole_batch() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let mut creating =
+        Vec::from_iter((0..5).map(|index| creating_file(&format!("file-{index}.txt"), &["batch"])));
+    creating[1].checksum = Some("not a digest".to_owned());
+    creating[3].checksum = Some("abc".to_owned());
+
+    let (status, error) = app
+        .post_for_error("/files/batch", &CreatingFiles { files: creating })
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity);
+
+    let fields = Vec::from_iter(
+        error["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field["field"].as_str().unwrap()),
+    );
+    assert_eq!(fields, ["files[1].checksum", "files[3].checksum"]);
+    assert_eq!(file_count(&app).await, 0);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_batch_failing_to_insert_creates_nothing() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    let (status, _) = app
+        .post_for_error(
+            "/files/batch",
+            &CreatingFiles {
+                files: vec![
+                    creating_file("a.txt", &["batch"]),
+                    creating_file("b.txt", &["draft", "draft"]),
+                ],
+            },
+        )
+        .await;
+    assert_eq!(status, Status::Conflict);
+    assert_eq!(file_count(&app).await, 0);
+}
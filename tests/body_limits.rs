@@ -0,0 +1,94 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::guards::batch_json::{BATCH_JSON_LIMIT, JSON_LIMIT};
+use rocket::{
+    data::ByteUnit,
+    http::{ContentType, Header, Status},
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+/// A JSON object of exactly `len` bytes.
+fn body_of_len(len: ByteUnit) -> Value {
+    // `{"name":""}` is 11 bytes long
+    let body = json!({ "name": "x".repeat(len.as_u64() as usize - 11) });
+    assert_eq!(
+        serde_json::to_vec(&body).unwrap().len() as u64,
+        len.as_u64()
+    );
+    body
+}
+
+fn batch_routes() -> [String; 3] {
+    [
+        "/files/batch".to_owned(),
+        "/files/bulk-delete".to_owned(),
+        TestApp::completion_uri(Uuid::new_v4(), "upload"),
+    ]
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn json_bodies_past_the_limit_are_refused_with_it() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    // read in full, then refused for lacking fields
+    let (status, _) = app.post_for_error("/files", &body_of_len(JSON_LIMIT)).await;
+    assert_eq!(status, Status::UnprocessableEntity);
+
+    let (status, error) = app
+        .post_for_error("/files", &body_of_len(JSON_LIMIT + 1))
+        .await;
+    assert_eq!(status, Status::PayloadTooLarge);
+    assert_eq!(error["code"], "payload_too_large", "{error}");
+    assert_eq!(error["limitBytes"], JSON_LIMIT.as_u64());
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn batch_bodies_are_read_under_the_batch_limit() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    for uri in batch_routes() {
+        let (status, error) = app.post_for_error(&uri, &body_of_len(JSON_LIMIT + 1)).await;
+        assert_eq!(status, Status::UnprocessableEntity, "{uri}: {error}");
+
+        let (status, _) = app
+            .post_for_error(&uri, &body_of_len(BATCH_JSON_LIMIT))
+            .await;
+        assert_eq!(status, Status::UnprocessableEntity, "{uri}");
+
+        let (status, error) = app
+            .post_for_error(&uri, &body_of_len(BATCH_JSON_LIMIT + 1))
+            .await;
+        assert_eq!(status, Status::PayloadTooLarge, "{uri}");
+        assert_eq!(error["code"], "payload_too_large", "{uri}: {error}");
+        assert_eq!(error["limitBytes"], BATCH_JSON_LIMIT.as_u64(), "{uri}");
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn other_errors_answer_only_the_reason() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    let response = app
+        .client
+        .post("/files")
+        .header(Header::new(
+            "Authorization",
+            format!("Bearer {}", app.token),
+        ))
+        .header(ContentType::JSON)
+        .body(r#"{"name": "report.pdf", "size": "#)
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::BadRequest);
+    let error: Value = response.into_json().await.unwrap();
+    assert_eq!(error, json!({ "status": 400, "message": "Bad Request" }));
+
+    let (status, error) = app.get_for_error("/no-such-route").await;
+    assert_eq!(status, Status::NotFound);
+    assert_eq!(error, json!({ "status": 404, "message": "Not Found" }));
+}
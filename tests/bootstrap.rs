@@ -0,0 +1,80 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::admins::BootstrappingAdmin;
+use futures::future::join_all;
+use rocket::http::Status;
+
+fn bootstrapping(username: &str, bootstrap_token: Option<&str>) -> BootstrappingAdmin {
+    BootstrappingAdmin {
+        username: username.to_owned(),
+        password: format!("{username}-password"),
+        email: format!("{username}@example.com"),
+        bootstrap_token: bootstrap_token.map(str::to_owned),
+    }
+}
+
+async fn bootstrap(app: &TestApp, admin: &BootstrappingAdmin) -> Status {
+    app.client
+        .post("/admins/bootstrap")
+        .json(admin)
+        .dispatch()
+        .await
+        .status()
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn concurrent_bootstraps_of_an_empty_table_create_a_single_admin() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+
+    // the app bootstraps an admin of its own, so the table is emptied again
+    sqlx::query("DELETE FROM admin_sessions")
+        .execute(&db_pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM admins")
+        .execute(&db_pool)
+        .await
+        .unwrap();
+
+    let admins = Vec::from_iter((0..8).map(|index| format!("first-{index}")));
+    let admins = Vec::from_iter(admins.iter().map(|username| bootstrapping(username, None)));
+    let mut statuses = join_all(admins.iter().map(|admin| bootstrap(&app, admin))).await;
+    statuses.sort_by_key(|status| status.code);
+    assert_eq!(
+        statuses,
+        [vec![Status::Ok], vec![Status::Conflict; admins.len() - 1]].concat()
+    );
+
+    let admin_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM admins")
+        .fetch_one(&db_pool)
+        .await
+        .unwrap();
+    assert_eq!(admin_count, 1);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_bootstrap_token_creates_another_admin_only_once() {
+    let app =
+        TestApp::spawn_with_in_memory_stores_and_env(&[("BOOTSTRAP_TOKEN", "let-me-in")]).await;
+
+    assert_eq!(
+        bootstrap(&app, &bootstrapping("without-token", None)).await,
+        Status::Conflict
+    );
+    assert_eq!(
+        bootstrap(&app, &bootstrapping("wrong-token", Some("let-me-out"))).await,
+        Status::Unauthorized
+    );
+    assert_eq!(
+        bootstrap(&app, &bootstrapping("recovery", Some("let-me-in"))).await,
+        Status::Ok
+    );
+    assert_eq!(
+        bootstrap(&app, &bootstrapping("recovery-again", Some("let-me-in"))).await,
+        Status::Conflict
+    );
+}
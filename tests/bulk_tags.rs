@@ -0,0 +1,196 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::{
+    db::repositories::{file::FileRepository, RepositoryError},
+    interfaces::{
+        admins::AdminTask,
+        files::{BulkTagOutcome, BulkTaggedFiles, BulkTaggingFiles, File},
+        WithAdminTask,
+    },
+};
+use rocket::http::Status;
+use serde_json::json;
+use uuid::Uuid;
+
+fn bulk_tagging(file_ids: &[Uuid], add: &[&str], remove: &[&str]) -> BulkTaggingFiles {
+    BulkTaggingFiles {
+        file_ids: file_ids.to_vec(),
+        add: Vec::from_iter(add.iter().map(|tag| (*tag).to_owned())),
+        remove: Vec::from_iter(remove.iter().map(|tag| (*tag).to_owned())),
+    }
+}
+
+fn tags(tags: &[&str]) -> Vec<String> {
+    Vec::from_iter(tags.iter().map(|tag| (*tag).to_owned()))
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_repository_reports_only_the_tags_that_changed() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let draft = app.create_uploaded_file("a.txt", b"a", &["draft"]).await;
+    let reviewed = app.create_uploaded_file("b.txt", b"b", &["reviewed"]).await;
+    let missing = Uuid::new_v4();
+
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let repository = FileRepository::new(db_pool.clone(), db_pool);
+    let mut file_ids = [draft.id, reviewed.id, missing];
+    file_ids.sort_unstable();
+
+    let changes = repository
+        .update_tags_many(&file_ids, &tags(&["reviewed"]), &tags(&["draft"]))
+        .await
+        .unwrap();
+    assert_eq!(changes.len(), 2);
+    let draft_change = changes.iter().find(|c| c.file_id == draft.id).unwrap();
+    assert_eq!(draft_change.added, ["reviewed"]);
+    assert_eq!(draft_change.removed, ["draft"]);
+    let reviewed_change = changes.iter().find(|c| c.file_id == reviewed.id).unwrap();
+    assert!(reviewed_change.added.is_empty());
+    assert!(reviewed_change.removed.is_empty());
+
+    // applying it again changes nothing
+    let changes = repository
+        .update_tags_many(&file_ids, &tags(&["reviewed"]), &tags(&["draft"]))
+        .await
+        .unwrap();
+    assert!(changes
+        .iter()
+        .all(|change| change.added.is_empty() && change.removed.is_empty()));
+
+    // a file going past the limit rolls back the whole change
+    let many = Vec::from_iter((0..64).map(|index| format!("tag-{index}")));
+    let result = repository
+        .update_tags_many(&[draft.id, reviewed.id], &many, &[])
+        .await;
+    assert!(matches!(
+        result,
+        Err(RepositoryError::TooManyTags {
+            limit: 64,
+            attempted: 65
+        })
+    ));
+    let file: File = app.get(&format!("/files/{}", draft.id)).await;
+    assert_eq!(file.tags, ["reviewed"]);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn bulk_tags_update_and_re_index_the_files() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let draft = app.create_uploaded_file("a.txt", b"a", &["draft"]).await;
+    let reviewed = app
+        .create_uploaded_file("b.txt", b"b", &["reviewed", "photos"])
+        .await;
+    let unready = app.create_file("c.txt", b"c", &["draft"]).await;
+    let missing = Uuid::new_v4();
+
+    let answer: WithAdminTask<BulkTaggedFiles> = app
+        .post(
+            "/files/bulk-tags",
+            &bulk_tagging(
+                &[draft.id, reviewed.id, unready.id, missing, draft.id],
+                &["reviewed", "reviewed"],
+                &["draft"],
+            ),
+        )
+        .await;
+    let outcomes = Vec::from_iter(
+        answer
+            .result
+            .files
+            .iter()
+            .map(|file| (file.file_id, file.outcome)),
+    );
+    assert_eq!(
+        outcomes,
+        [
+            (draft.id, BulkTagOutcome::Updated),
+            (reviewed.id, BulkTagOutcome::Unchanged),
+            (unready.id, BulkTagOutcome::Updated),
+            (missing, BulkTagOutcome::NotFound),
+        ]
+    );
+    assert_eq!(answer.result.files[0].added, ["reviewed"]);
+    assert_eq!(answer.result.files[0].removed, ["draft"]);
+    assert_eq!(answer.result.updated_count, 2);
+    assert_eq!(answer.result.not_found_count, 1);
+
+    let file: File = app.get(&format!("/files/{}", draft.id)).await;
+    assert_eq!(file.tags, ["reviewed"]);
+
+    let search_index = app.search_index.as_ref().unwrap();
+    let document = search_index.file_document(draft.id).unwrap();
+    assert_eq!(document["tags"], json!(["reviewed"]));
+    assert!(search_index.file_document(unready.id).is_none());
+
+    let task: AdminTask = app
+        .get(&format!(
+            "/admin-tasks/{}",
+            answer.admin_task_id.expect("the change is recorded")
+        ))
+        .await;
+    assert_eq!(task.metadata["updated_count"], json!(2));
+    assert_eq!(task.metadata["not_found_count"], json!(1));
+    assert_eq!(task.metadata["added_count"], json!(2));
+    assert_eq!(task.metadata["removed_count"], json!(2));
+    assert_eq!(task.metadata["indexing"], json!("done"));
+
+    // nothing changes the second time, so nothing is recorded
+    let answer: WithAdminTask<BulkTaggedFiles> = app
+        .post(
+            "/files/bulk-tags",
+            &bulk_tagging(&[draft.id], &["reviewed"], &["draft"]),
+        )
+        .await;
+    assert_eq!(answer.result.files[0].outcome, BulkTagOutcome::Unchanged);
+    assert_eq!(answer.admin_task_id, None);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn invalid_bulk_tag_changes_are_refused() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app.create_uploaded_file("a.txt", b"a", &[]).await;
+
+    let (status, body) = app
+        .post_for_error("/files/bulk-tags", &bulk_tagging(&[], &["reviewed"], &[]))
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity, "{body}");
+    assert_eq!(body["fields"][0]["field"], json!("fileIds"), "{body}");
+
+    let too_many = Vec::from_iter((0..1001).map(|_| Uuid::new_v4()));
+    let (status, body) = app
+        .post_for_error(
+            "/files/bulk-tags",
+            &bulk_tagging(&too_many, &["reviewed"], &[]),
+        )
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity, "{body}");
+
+    let (status, body) = app
+        .post_for_error("/files/bulk-tags", &bulk_tagging(&[file.id], &[], &[]))
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity, "{body}");
+    assert_eq!(body["code"], json!("no_fields"), "{body}");
+
+    let (status, body) = app
+        .post_for_error(
+            "/files/bulk-tags",
+            &bulk_tagging(&[file.id], &["reviewed"], &["reviewed"]),
+        )
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity, "{body}");
+    assert_eq!(body["fields"][0]["field"], json!("remove"), "{body}");
+
+    let many = Vec::from_iter((0..65).map(|index| format!("tag-{index}")));
+    let many = Vec::from_iter(many.iter().map(String::as_str));
+    let (status, body) = app
+        .post_for_error("/files/bulk-tags", &bulk_tagging(&[file.id], &many, &[]))
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity, "{body}");
+    assert_eq!(body["fields"][0]["field"], json!("add"), "{body}");
+    let file: File = app.get(&format!("/files/{}", file.id)).await;
+    assert!(file.tags.is_empty());
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::{
+    admins::{AdminTask, AdminTaskPreview, AdminTaskStatus, TaskKind},
+    files::{CreatingFile, File},
+};
+use rocket::http::Status;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+fn sha256_hex(content: &[u8]) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, content);
+    String::from_iter(digest.as_ref().iter().map(|byte| format!("{byte:02x}")))
+}
+
+async fn backfill(app: &TestApp, files_per_second: u32) -> AdminTask {
+    let task: AdminTask = app
+        .post(
+            "/admin-tasks/backfill-checksums",
+            &json!({ "filesPerSecond": files_per_second }),
+        )
+        .await;
+    let task = app.wait_for_task(task.id).await;
+    assert_eq!(task.status, AdminTaskStatus::Completed, "{task:#?}");
+    task
+}
+
+async fn checksum(app: &TestApp, file: &File) -> Option<String> {
+    app.get::<File>(&format!("/files/{}", file.id))
+        .await
+        .checksum
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn checksums_are_filled_in_from_the_objects() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let storage = app.storage.as_ref().unwrap();
+    let contents: [&[u8]; 3] = [b"first", b"second", b"third"];
+    let mut files = Vec::new();
+
+    for (index, content) in contents.iter().enumerate() {
+        files.push(
+            app.create_uploaded_file(&format!("file-{index}.pdf"), content, &[])
+                .await,
+        );
+    }
+
+    let missing = app
+        .create_uploaded_file("missing.pdf", b"missing", &[])
+        .await;
+    storage.remove_object(&missing.id.to_string(), None);
+    let given: File = app
+        .post(
+            "/files",
+            &CreatingFile {
+                name: "given.pdf".to_owned(),
+                size: 5,
+                mime_type: "application/pdf".to_owned(),
+                checksum: Some("ab".repeat(32)),
+                tags: None,
+                allow_duplicate: false,
+            },
+        )
+        .await;
+    let given = app.upload_file(given.id, b"given").await;
+
+    let task = backfill(&app, 1000).await;
+    assert_eq!(task.metadata["hashed_count"], 3, "{task:#?}");
+    assert_eq!(task.metadata["stored_count"], 0);
+    assert_eq!(task.metadata["unavailable_count"], 1);
+
+    let search_index = app.search_index.as_ref().unwrap();
+
+    for (file, content) in files.iter().zip(contents) {
+        assert_eq!(checksum(&app, file).await, Some(sha256_hex(content)));
+        assert_eq!(
+            search_index.file_document(file.id).unwrap()["checksum"],
+            sha256_hex(content)
+        );
+    }
+
+    assert_eq!(checksum(&app, &missing).await, None);
+    assert_eq!(checksum(&app, &given).await, Some("ab".repeat(32)));
+
+    let tasks: Vec<AdminTaskPreview> = app.get("/admin-tasks").await;
+    let preview = tasks
+        .iter()
+        .find(|preview| preview.name == TaskKind::BackfillChecksums)
+        .unwrap();
+    assert_eq!(
+        preview.summary.as_deref(),
+        Some("Backfilled checksums of 3 files, 3 hashed, 1 objects unavailable")
+    );
+
+    // files checked before are left alone, apart from those still lacking an object
+    let task = backfill(&app, 1000).await;
+    assert_eq!(task.metadata["hashed_count"], 0, "{task:#?}");
+    assert_eq!(task.metadata["unavailable_count"], 1);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_backfill_is_throttled() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    for index in 0..4 {
+        app.create_uploaded_file(&format!("file-{index}.pdf"), b"content", &[])
+            .await;
+    }
+
+    // two batches of two files, each taking a second
+    let started_at = Instant::now();
+    let task = backfill(&app, 2).await;
+    assert!(
+        Duration::from_secs(2) <= started_at.elapsed(),
+        "{:?}",
+        started_at.elapsed()
+    );
+    assert_eq!(task.metadata["hashed_count"], 4);
+    assert_eq!(task.metadata["last_batch"]["hashed_count"], 2, "{task:#?}");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_throttle_is_bounded_and_defaulted() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    for files_per_second in [0, 1001] {
+        let (status, error) = app
+            .post_for_error(
+                "/admin-tasks/backfill-checksums",
+                &json!({ "filesPerSecond": files_per_second }),
+            )
+            .await;
+        assert_eq!(status, Status::UnprocessableEntity, "{files_per_second}");
+        assert_eq!(error["fields"][0]["field"], "filesPerSecond", "{error}");
+    }
+
+    let task: AdminTask = app.post("/admin-tasks/backfill-checksums", &()).await;
+    assert_eq!(task.metadata["files_per_second"], 20);
+}
@@ -0,0 +1,120 @@
+use file_indexer::{
+    interfaces::CircuitState,
+    services::index_service::{
+        Circuit, CIRCUIT_FAILURE_THRESHOLD, CIRCUIT_OPEN_DURATION, CIRCUIT_PROBE_RETRY_AFTER,
+    },
+};
+use std::time::{Duration, Instant};
+
+/// A circuit opened at `opened_at` by as many outages in a row as it takes.
+fn opened_circuit(opened_at: Instant) -> Circuit {
+    let mut circuit = Circuit::default();
+
+    for _ in 0..CIRCUIT_FAILURE_THRESHOLD {
+        assert_eq!(circuit.admit(opened_at), Ok(false));
+        circuit.record(true, opened_at);
+    }
+
+    circuit
+}
+
+#[test]
+fn outages_below_the_threshold_keep_the_circuit_closed() {
+    let now = Instant::now();
+    let mut circuit = Circuit::default();
+
+    for _ in 1..CIRCUIT_FAILURE_THRESHOLD {
+        circuit.record(true, now);
+    }
+    assert_eq!(circuit.state(now), CircuitState::Closed);
+    assert_eq!(circuit.retry_after(now), None);
+
+    // a success in between starts the count over
+    circuit.record(false, now);
+    circuit.record(true, now);
+    assert_eq!(circuit.state(now), CircuitState::Closed);
+}
+
+#[test]
+fn an_open_circuit_skips_calls_until_its_cooldown_ends() {
+    let opened_at = Instant::now();
+    let mut circuit = opened_circuit(opened_at);
+    assert_eq!(circuit.state(opened_at), CircuitState::Open);
+    assert_eq!(circuit.retry_after(opened_at), Some(CIRCUIT_OPEN_DURATION));
+
+    let later = opened_at + Duration::from_secs(10);
+    assert_eq!(
+        circuit.retry_after(later),
+        Some(CIRCUIT_OPEN_DURATION - Duration::from_secs(10))
+    );
+    assert_eq!(
+        circuit.admit(later),
+        Err(CIRCUIT_OPEN_DURATION - Duration::from_secs(10))
+    );
+
+    let cooled_down = opened_at + CIRCUIT_OPEN_DURATION;
+    assert_eq!(circuit.state(cooled_down), CircuitState::HalfOpen);
+    assert_eq!(circuit.retry_after(cooled_down), None);
+}
+
+#[test]
+fn a_half_open_circuit_lets_a_single_probe_through() {
+    let opened_at = Instant::now();
+    let mut circuit = opened_circuit(opened_at);
+    let cooled_down = opened_at + CIRCUIT_OPEN_DURATION;
+
+    assert_eq!(circuit.admit(cooled_down), Ok(true));
+    assert_eq!(circuit.state(cooled_down), CircuitState::HalfOpen);
+
+    for _ in 0..3 {
+        assert_eq!(circuit.admit(cooled_down), Err(CIRCUIT_PROBE_RETRY_AFTER));
+    }
+    assert_eq!(
+        circuit.retry_after(cooled_down),
+        Some(CIRCUIT_PROBE_RETRY_AFTER)
+    );
+}
+
+#[test]
+fn a_successful_probe_closes_the_circuit() {
+    let opened_at = Instant::now();
+    let mut circuit = opened_circuit(opened_at);
+    let cooled_down = opened_at + CIRCUIT_OPEN_DURATION;
+
+    assert_eq!(circuit.admit(cooled_down), Ok(true));
+    circuit.record(false, cooled_down);
+
+    assert_eq!(circuit.state(cooled_down), CircuitState::Closed);
+    assert_eq!(circuit.retry_after(cooled_down), None);
+    assert_eq!(circuit.admit(cooled_down), Ok(false));
+    assert_eq!(circuit.admit(cooled_down), Ok(false));
+}
+
+#[test]
+fn a_failed_probe_reopens_the_circuit() {
+    let opened_at = Instant::now();
+    let mut circuit = opened_circuit(opened_at);
+    let probed_at = opened_at + CIRCUIT_OPEN_DURATION + Duration::from_secs(5);
+
+    assert_eq!(circuit.admit(probed_at), Ok(true));
+    circuit.record(true, probed_at);
+
+    assert_eq!(circuit.state(probed_at), CircuitState::Open);
+    assert_eq!(circuit.retry_after(probed_at), Some(CIRCUIT_OPEN_DURATION));
+    assert_eq!(circuit.admit(probed_at), Err(CIRCUIT_OPEN_DURATION));
+
+    // the next cooldown counts from the failed probe
+    assert_eq!(circuit.admit(probed_at + CIRCUIT_OPEN_DURATION), Ok(true));
+}
+
+#[test]
+fn a_probe_given_up_lets_the_next_call_probe() {
+    let opened_at = Instant::now();
+    let mut circuit = opened_circuit(opened_at);
+    let cooled_down = opened_at + CIRCUIT_OPEN_DURATION;
+
+    assert_eq!(circuit.admit(cooled_down), Ok(true));
+    circuit.end_probe();
+
+    assert_eq!(circuit.admit(cooled_down), Ok(true));
+}
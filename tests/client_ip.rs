@@ -0,0 +1,139 @@
+use file_indexer::{config::ProxyConfig, guards::client_ip::resolve_client_ip};
+use std::net::IpAddr;
+
+fn proxy(trusted_proxies: &[&str]) -> ProxyConfig {
+    ProxyConfig {
+        trusted_proxies: Vec::from_iter(
+            trusted_proxies
+                .iter()
+                .map(|network| network.parse().unwrap()),
+        ),
+    }
+}
+
+fn ip(address: &str) -> IpAddr {
+    address.parse().unwrap()
+}
+
+fn resolve(
+    proxy: &ProxyConfig,
+    peer: &str,
+    forwarded: &[&str],
+    x_forwarded_for: &[&str],
+) -> IpAddr {
+    resolve_client_ip(
+        proxy,
+        ip(peer),
+        forwarded.iter().copied(),
+        x_forwarded_for.iter().copied(),
+    )
+}
+
+#[test]
+fn untrusted_peers_cannot_claim_another_address() {
+    let proxy = proxy(&["10.0.0.0/8"]);
+
+    assert_eq!(
+        resolve(&proxy, "203.0.113.9", &[], &["10.1.2.3"]),
+        ip("203.0.113.9")
+    );
+    assert_eq!(
+        resolve(&proxy, "203.0.113.9", &["for=198.51.100.1"], &[]),
+        ip("203.0.113.9")
+    );
+    assert_eq!(
+        resolve(&ProxyConfig::default(), "127.0.0.1", &[], &["198.51.100.1"]),
+        ip("127.0.0.1")
+    );
+}
+
+#[test]
+fn trusted_peers_are_followed_to_the_first_untrusted_hop() {
+    let proxy = proxy(&["10.0.0.0/8", "192.0.2.10"]);
+
+    assert_eq!(
+        resolve(&proxy, "10.0.0.2", &[], &["198.51.100.1"]),
+        ip("198.51.100.1")
+    );
+    // the client made up the first hop, the proxies appended the others
+    assert_eq!(
+        resolve(
+            &proxy,
+            "10.0.0.2",
+            &[],
+            &["1.1.1.1, 198.51.100.1, 192.0.2.10", "10.20.0.1"]
+        ),
+        ip("198.51.100.1")
+    );
+    // every hop is a proxy, so the farthest one is the client
+    assert_eq!(
+        resolve(&proxy, "10.0.0.2", &[], &["10.9.9.9, 10.20.0.1"]),
+        ip("10.9.9.9")
+    );
+    assert_eq!(resolve(&proxy, "10.0.0.2", &[], &[]), ip("10.0.0.2"));
+}
+
+#[test]
+fn forwarded_elements_are_parsed() {
+    let proxy = proxy(&["10.0.0.0/8", "2001:db8:ffff::/48"]);
+
+    assert_eq!(
+        resolve(
+            &proxy,
+            "10.0.0.2",
+            &["for=192.0.2.60;proto=http;by=203.0.113.43, For=\"10.0.0.7:4711\""],
+            &[]
+        ),
+        ip("192.0.2.60")
+    );
+    assert_eq!(
+        resolve(
+            &proxy,
+            "2001:db8:ffff::1",
+            &["proto=https;for=\"[2001:db8:cafe::17]:4711\""],
+            &[]
+        ),
+        ip("2001:db8:cafe::17")
+    );
+    assert_eq!(
+        resolve(&proxy, "10.0.0.2", &["for=\"[2001:db8:cafe::17]\""], &[]),
+        ip("2001:db8:cafe::17")
+    );
+    // `Forwarded` wins over `X-Forwarded-For`
+    assert_eq!(
+        resolve(&proxy, "10.0.0.2", &["for=192.0.2.60"], &["198.51.100.1"]),
+        ip("192.0.2.60")
+    );
+}
+
+#[test]
+fn hops_that_cannot_be_followed_stop_at_the_proxy_appending_them() {
+    let proxy = proxy(&["10.0.0.0/8"]);
+
+    assert_eq!(
+        resolve(&proxy, "10.0.0.2", &["for=unknown, for=10.0.0.7"], &[]),
+        ip("10.0.0.7")
+    );
+    assert_eq!(
+        resolve(&proxy, "10.0.0.2", &["for=_hidden"], &[]),
+        ip("10.0.0.2")
+    );
+    assert_eq!(
+        resolve(&proxy, "10.0.0.2", &["proto=https"], &[]),
+        ip("10.0.0.2")
+    );
+    assert_eq!(
+        resolve(&proxy, "10.0.0.2", &[], &["198.51.100.1, not-an-ip"]),
+        ip("10.0.0.2")
+    );
+}
+
+#[test]
+fn mapped_ipv4_peers_match_ipv4_networks() {
+    let proxy = proxy(&["10.0.0.0/8"]);
+
+    assert_eq!(
+        resolve(&proxy, "::ffff:10.0.0.2", &[], &["198.51.100.1"]),
+        ip("198.51.100.1")
+    );
+}
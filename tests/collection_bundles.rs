@@ -0,0 +1,167 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::{
+    admins::{AdminTask, AdminTaskStatus},
+    collections::Collection,
+    files::{File, FileDownloadUrl},
+};
+use rocket::http::Status;
+use std::io::{Cursor, Read};
+use uuid::Uuid;
+use zip::{CompressionMethod, ZipArchive};
+
+const CONTENTS: [(&str, &[u8]); 3] = [
+    ("march.pdf", b"the invoices of march"),
+    ("april.pdf", b"the invoices of april, a little longer"),
+    ("empty.txt", b""),
+];
+
+async fn create_collection_of_three(app: &TestApp) -> Collection {
+    let collection = app.create_collection("Invoices", &["invoices"]).await;
+
+    for (name, content) in CONTENTS {
+        app.create_uploaded_file(name, content, &["invoices"]).await;
+    }
+
+    // neither is a member
+    app.create_file("unready.pdf", b"unready", &["invoices"])
+        .await;
+    app.create_uploaded_file("other.pdf", b"other", &["receipts"])
+        .await;
+
+    collection
+}
+
+/// Bundles the collection and waits for the bundle file.
+async fn bundle(app: &TestApp, collection: &Collection) -> File {
+    let task: AdminTask = app
+        .post(&format!("/collections/{}/bundles", collection.id), &())
+        .await;
+    let task = app.wait_for_task(task.id).await;
+    assert_eq!(task.status, AdminTaskStatus::Completed, "{task:#?}");
+    assert_eq!(task.metadata["file_ids"].as_array().unwrap().len(), 3);
+
+    let bundle_file_id = task.metadata["bundle_file_id"].as_str().unwrap();
+    let bundle: File = app.get(&format!("/files/{bundle_file_id}")).await;
+    assert!(bundle.is_ready);
+    assert_eq!(bundle.name, "Invoices.zip");
+    assert_eq!(bundle.mime_type, "application/zip");
+    assert_eq!(bundle.tags, ["bundle"]);
+    bundle
+}
+
+/// Checks that the zip holds the files of [`CONTENTS`] as they are, in the order of their ids.
+fn check_archive(bundle: &File, archive: Vec<u8>, mut files: Vec<File>) {
+    assert_eq!(archive.len(), bundle.size);
+    files.sort_by_key(|file| file.id);
+
+    let mut archive = ZipArchive::new(Cursor::new(archive)).unwrap();
+    assert_eq!(archive.len(), files.len());
+
+    for (index, file) in files.iter().enumerate() {
+        let mut entry = archive.by_index(index).unwrap();
+        assert_eq!(entry.name(), file.name);
+        assert_eq!(entry.compression(), CompressionMethod::Stored);
+
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content).unwrap();
+        let (_, expected) = CONTENTS
+            .iter()
+            .find(|(name, _)| *name == file.name)
+            .unwrap();
+        assert_eq!(&content, expected, "{}", file.name);
+    }
+}
+
+async fn members(app: &TestApp, collection: &Collection) -> Vec<File> {
+    app.get(&format!("/collections/{}/files", collection.id))
+        .await
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn three_files_are_bundled_into_a_downloadable_zip() {
+    let app = TestApp::spawn().await;
+    let collection = create_collection_of_three(&app).await;
+    let bundle = bundle(&app, &collection).await;
+
+    let download_url: FileDownloadUrl = app
+        .post(&format!("/files/{}/download-urls", bundle.id), &())
+        .await;
+    let response = reqwest::get(&download_url.url).await.unwrap();
+    assert!(response.status().is_success(), "{response:?}");
+    let archive = response.bytes().await.unwrap().to_vec();
+
+    check_archive(&bundle, archive, members(&app, &collection).await);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn bundles_are_written_to_the_object_storage() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let collection = create_collection_of_three(&app).await;
+    let bundle = bundle(&app, &collection).await;
+
+    let archive = app
+        .storage
+        .as_ref()
+        .unwrap()
+        .object(&bundle.id.to_string(), None)
+        .unwrap()
+        .body;
+    check_archive(&bundle, archive, members(&app, &collection).await);
+
+    // the bundle is indexed like any other file
+    let document = app
+        .search_index
+        .as_ref()
+        .unwrap()
+        .file_document(bundle.id)
+        .unwrap();
+    assert_eq!(document["name"], "Invoices.zip");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn collections_too_large_are_not_bundled() {
+    let app =
+        TestApp::spawn_with_in_memory_stores_and_env(&[("UPLOAD_MAX_FILE_SIZE_BYTES", "1024")])
+            .await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+
+    // one more file than a bundle may hold
+    let collection = app.create_collection("Ledgers", &["ledger"]).await;
+    sqlx::query(
+        "
+WITH inserted AS (
+    INSERT INTO files (name, name_sort_key, size, mime_type, is_ready)
+    SELECT 'ledger-' || number || '.csv', 'ledger-' || number || '.csv', 1, 'text/csv', TRUE
+    FROM generate_series(1, 1001) AS number
+    RETURNING id
+)
+INSERT INTO file_tags (file_id, tag) SELECT id, 'ledger' FROM inserted",
+    )
+    .execute(&db_pool)
+    .await
+    .unwrap();
+
+    // few files, but a zip over the maximum file size
+    let large = app.create_collection("Scans", &["scan"]).await;
+    app.create_uploaded_file("scan.png", &[0; 1024], &["scan"])
+        .await;
+
+    for collection in [collection, large] {
+        let (status, error) = app
+            .post_for_error(&format!("/collections/{}/bundles", collection.id), &())
+            .await;
+        assert_eq!(status, Status::UnprocessableEntity, "{}", collection.name);
+        assert_eq!(error["code"], "collection_too_large", "{error}");
+    }
+
+    assert_eq!(
+        app.post_for_status(&format!("/collections/{}/bundles", Uuid::new_v4()), &())
+            .await,
+        Status::NotFound
+    );
+}
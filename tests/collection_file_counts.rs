@@ -0,0 +1,90 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::{admins::AdminTaskStatus, files::File, WithAdminTask};
+use serde_json::json;
+use uuid::Uuid;
+
+/// Re-indexes everything, waiting for both tasks to complete.
+async fn re_index(app: &TestApp) {
+    let task = app.re_index().await;
+
+    for task_id in [task.file_task.id, task.collection_task.id] {
+        let task = app.wait_for_task(task_id).await;
+        assert_eq!(task.status, AdminTaskStatus::Completed, "{task:#?}");
+    }
+}
+
+fn indexed_file_count(app: &TestApp, collection_id: Uuid) -> u64 {
+    let document = app
+        .search_index
+        .as_ref()
+        .unwrap()
+        .collection_document(collection_id)
+        .expect("the collection is not indexed");
+    document["file_count"].as_u64().unwrap()
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_re_index_counts_the_ready_files_of_each_collection() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let reports = app
+        .create_collection("Reports", &["reports", "final"])
+        .await;
+    let drafts = app.create_collection("Drafts", &["draft"]).await;
+    let empty = app.create_collection("Empty", &["archived"]).await;
+
+    app.create_uploaded_file("q1.pdf", b"first quarter", &["reports", "final"])
+        .await;
+    app.create_uploaded_file("q2.pdf", b"second quarter", &["reports", "final", "draft"])
+        .await;
+    // misses one of the tags of the collection
+    app.create_uploaded_file("q3.pdf", b"third quarter", &["reports"])
+        .await;
+    // never uploaded, so not counted
+    app.create_file("q4.pdf", b"fourth quarter", &["reports", "final", "draft"])
+        .await;
+
+    re_index(&app).await;
+
+    assert_eq!(indexed_file_count(&app, reports.id), 2);
+    assert_eq!(indexed_file_count(&app, drafts.id), 1);
+    assert_eq!(indexed_file_count(&app, empty.id), 0);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_re_index_follows_files_added_to_and_removed_from_a_collection() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let collection = app.create_collection("Reports", &["reports"]).await;
+    let kept = app
+        .create_uploaded_file("kept.pdf", b"kept content", &["reports"])
+        .await;
+    let moved = app
+        .create_uploaded_file("moved.pdf", b"moved content", &[])
+        .await;
+
+    re_index(&app).await;
+    assert_eq!(indexed_file_count(&app, collection.id), 1);
+
+    let _: WithAdminTask<File> = app
+        .patch(
+            &format!("/files/{}", moved.id),
+            &json!({ "tagsForCreation": ["reports"] }),
+        )
+        .await;
+    re_index(&app).await;
+    assert_eq!(indexed_file_count(&app, collection.id), 2);
+
+    for file in [&kept, &moved] {
+        let _: WithAdminTask<File> = app
+            .patch(
+                &format!("/files/{}", file.id),
+                &json!({ "tagsForDeletion": ["reports"] }),
+            )
+            .await;
+    }
+    re_index(&app).await;
+    assert_eq!(indexed_file_count(&app, collection.id), 0);
+}
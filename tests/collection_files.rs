@@ -0,0 +1,77 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::files::File;
+use rocket::http::RawStr;
+use uuid::Uuid;
+
+/// Lists every file of the collection a page of `limit` at a time, following the cursor.
+async fn list_all(app: &TestApp, collection_id: Uuid, limit: usize) -> Vec<File> {
+    let mut files: Vec<File> = Vec::new();
+
+    loop {
+        let cursor = match files.last() {
+            Some(last) => format!(
+                "&last-file-id={}&last-file-name={}",
+                last.id,
+                RawStr::new(&last.name).percent_encode()
+            ),
+            None => String::new(),
+        };
+        let page: Vec<File> = app
+            .get(&format!(
+                "/collections/{collection_id}/files?limit={limit}{cursor}"
+            ))
+            .await;
+        assert!(page.len() <= limit, "{page:#?}");
+
+        let done = page.len() < limit;
+        files.extend(page);
+
+        if done {
+            return files;
+        }
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn files_sharing_a_name_are_each_listed_once() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let collection = app.create_collection("Reports", &["reports"]).await;
+
+    let mut expected = Vec::new();
+    for (index, name) in ["report.pdf", "a.pdf", "report.pdf", "z.pdf", "report.pdf"]
+        .into_iter()
+        .enumerate()
+    {
+        let content = format!("content {index}");
+        let file = app
+            .create_uploaded_file(name, content.as_bytes(), &["reports", "final"])
+            .await;
+        expected.push((file.name, file.id));
+    }
+    // neither listed, one being unready and the other out of the collection
+    app.create_file("b.pdf", b"unready content", &["reports"])
+        .await;
+    app.create_uploaded_file("c.pdf", b"untagged content", &[])
+        .await;
+    expected.sort();
+
+    for limit in [1, 2, 3, 100] {
+        let files = list_all(&app, collection.id, limit).await;
+
+        assert_eq!(
+            Vec::from_iter(files.iter().map(|file| (file.name.clone(), file.id))),
+            expected,
+            "limit {limit}"
+        );
+        assert!(
+            files
+                .iter()
+                .all(|file| file.is_ready
+                    && file.tags == vec!["final".to_owned(), "reports".to_owned()]),
+            "{files:#?}"
+        );
+    }
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::collections::Collection;
+use rocket::http::RawStr;
+
+fn names(collections: &[Collection]) -> Vec<&str> {
+    Vec::from_iter(
+        collections
+            .iter()
+            .map(|collection| collection.name.as_str()),
+    )
+}
+
+fn tags_uri(tags: &[&str], rest: &str) -> String {
+    let tags = Vec::from_iter(
+        tags.iter()
+            .map(|tag| format!("tag={}", RawStr::new(tag).percent_encode())),
+    );
+    format!("/collections?{}{rest}", tags.join("&"))
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn collections_are_filtered_by_every_tag_given() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    app.create_collection("Design reports", &["team:design", "reports"])
+        .await;
+    app.create_collection("Design drafts", &["team:design", "drafts"])
+        .await;
+    app.create_collection("Sales reports", &["team:sales", "reports"])
+        .await;
+    app.create_collection("Untagged", &[]).await;
+
+    for (tags, expected) in [
+        (vec!["team:design"], vec!["Design drafts", "Design reports"]),
+        (vec!["reports"], vec!["Design reports", "Sales reports"]),
+        (vec!["team:design", "reports"], vec!["Design reports"]),
+        (
+            vec!["reports", "reports"],
+            vec!["Design reports", "Sales reports"],
+        ),
+        (vec!["team:design", "team:sales"], vec![]),
+        (vec!["team:marketing"], vec![]),
+    ] {
+        let collections: Vec<Collection> = app.get(&tags_uri(&tags, "")).await;
+        assert_eq!(names(&collections), expected, "{tags:?}");
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_tag_filter_composes_with_the_cursor() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    for name in ["Alpha", "Beta", "Delta", "Gamma"] {
+        app.create_collection(name, &["team:design", "reports"])
+            .await;
+    }
+    app.create_collection("Charlie", &["team:design"]).await;
+    app.create_collection("Epsilon", &["reports"]).await;
+
+    let tags = ["team:design", "reports"];
+    let first_page: Vec<Collection> = app.get(&tags_uri(&tags, "&limit=3")).await;
+    assert_eq!(names(&first_page), vec!["Alpha", "Beta", "Delta"]);
+
+    let last = first_page.last().unwrap();
+    let cursor = format!(
+        "&limit=3&last-collection-id={}&last-collection-name={}",
+        last.id, last.name
+    );
+    let second_page: Vec<Collection> = app.get(&tags_uri(&tags, &cursor)).await;
+    assert_eq!(names(&second_page), vec!["Gamma"]);
+}
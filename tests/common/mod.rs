@@ -0,0 +1,766 @@
+//! Runs the server against containerized Postgres, Meilisearch and MinIO, wired by the same
+//! `Services::init` and `build_rocket` as production. Every `TestApp` starts its own containers, so tests never see
+//! each other's data. Docker must be available.
+
+#![allow(dead_code)]
+
+use aws_sdk_s3::config::{BehaviorVersion, Credentials, Region};
+use chrono::{DateTime, Utc};
+use file_indexer::{
+    build_rocket,
+    config::Config,
+    db::repositories::admin::AdminRepository,
+    interfaces::{
+        admins::{
+            Admin, AdminLogin, AdminRole, AdminSession, AdminTask, AdminTaskStatus,
+            BootstrappingAdmin, CreatingAdmin, ReIndexAdminTask,
+        },
+        collections::{Collection, CreatingCollection},
+        files::{
+            CreatingFile, File, FileSearchHit, FileSearchQuery, FileSearchResult, FileUploadUrl,
+            FileUploadUrlPart, UploadedPart, UploadedParts,
+        },
+        WithAdminTask,
+    },
+    services::{
+        admin_service::AdminService,
+        index_service::{MeilisearchIndex, SearchIndex},
+        mailer_service::Mail,
+        s3_service::{ObjectStorage, S3Service},
+        Services,
+    },
+    testing::{
+        mailer::InMemoryMailer, object_storage::InMemoryObjectStorage,
+        search_index::InMemorySearchIndex,
+    },
+};
+use rocket::{
+    http::{ContentType, Header, RawStr, Status},
+    local::asynchronous::{Client, LocalRequest},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use testcontainers_modules::{
+    meilisearch::Meilisearch,
+    minio::MinIO,
+    postgres::Postgres,
+    testcontainers::{runners::AsyncRunner, ContainerAsync, ImageExt},
+};
+use uuid::Uuid;
+
+const POSTGRES_TAG: &str = "16-alpine";
+const MEILISEARCH_TAG: &str = "v1.15.2";
+const MINIO_ACCESS_KEY: &str = "minioadmin";
+const MINIO_SECRET_KEY: &str = "minioadmin";
+const BUCKET_NAME: &str = "file-indexer";
+const REGION: &str = "us-east-1";
+const ADMIN_USERNAME: &str = "admin";
+const ADMIN_PASSWORD: &str = "admin-password";
+/// The `application_name` of the read replica sessions, to tell them apart in `pg_stat_activity`.
+pub const READ_REPLICA_APPLICATION_NAME: &str = "file-indexer-replica";
+
+/// How long `wait_for_*` helpers poll before failing the test.
+const WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+const WAIT_INTERVAL: Duration = Duration::from_millis(200);
+
+pub struct TestApp {
+    pub client: Client,
+    /// The database of the server, for tests that change it behind the server's back.
+    pub database_url: String,
+    /// The read replica of the server, if it has one.
+    pub read_database_url: Option<String>,
+    /// The session token of the bootstrapped admin, who has the `admin` role.
+    pub token: String,
+    http: reqwest::Client,
+    _postgres: ContainerAsync<Postgres>,
+    _meilisearch: Option<ContainerAsync<Meilisearch>>,
+    _minio: Option<ContainerAsync<MinIO>>,
+    /// The object storage of the server, unless it runs against MinIO.
+    pub storage: Option<Arc<InMemoryObjectStorage>>,
+    /// The search index of the server, unless it runs against Meilisearch.
+    pub search_index: Option<Arc<InMemorySearchIndex>>,
+    /// The mails the server sent.
+    pub mailer: Arc<InMemoryMailer>,
+}
+
+impl TestApp {
+    /// Starts the containers, runs the migrations through the server startup and logs in as a
+    /// freshly bootstrapped admin.
+    pub async fn spawn() -> Self {
+        Self::start(false, false, false, &[]).await
+    }
+
+    /// Like [`TestApp::spawn`], but keeps the objects in an [`InMemoryObjectStorage`] instead of
+    /// MinIO. Parts are uploaded to it directly, as its presigned URLs lead nowhere.
+    pub async fn spawn_with_in_memory_storage() -> Self {
+        Self::start(true, false, false, &[]).await
+    }
+
+    /// Like [`TestApp::spawn`], but also searches an [`InMemorySearchIndex`] instead of
+    /// Meilisearch, so that only Postgres runs in a container. Writes reach the index before they
+    /// return, so searches need no waiting.
+    pub async fn spawn_with_in_memory_stores() -> Self {
+        Self::start(true, true, false, &[]).await
+    }
+
+    /// Like [`TestApp::spawn_with_in_memory_stores`], with more configuration variables.
+    pub async fn spawn_with_in_memory_stores_and_env(env: &[(&'static str, &str)]) -> Self {
+        Self::start(true, true, false, env).await
+    }
+
+    /// Like [`TestApp::spawn_with_in_memory_stores`], but with a read replica, stood in for by
+    /// read-only sessions on the same database. Any write sent to the replica fails.
+    pub async fn spawn_with_read_replica() -> Self {
+        Self::start(true, true, true, &[]).await
+    }
+
+    async fn start(
+        in_memory_storage: bool,
+        in_memory_search: bool,
+        read_replica: bool,
+        extra_env: &[(&'static str, &str)],
+    ) -> Self {
+        let postgres = Postgres::default()
+            .with_tag(POSTGRES_TAG)
+            .start()
+            .await
+            .expect("failed to start postgres");
+
+        let database_url = format!(
+            "postgres://postgres:postgres@{}:{}/postgres",
+            postgres.get_host().await.unwrap(),
+            postgres.get_host_port_ipv4(5432).await.unwrap(),
+        );
+        let mut env = HashMap::from([
+            ("DATABASE_URL", database_url.clone()),
+            ("AWS_REGION", REGION.to_owned()),
+            ("AWS_S3_BUCKET_NAME", BUCKET_NAME.to_owned()),
+            // sweep often, so that tests of the sweep need not wait long
+            ("INDEX_SWEEP_INTERVAL_SECS", "1".to_owned()),
+        ]);
+        let read_database_url = read_replica.then(|| {
+            format!(
+                "{database_url}?application_name={READ_REPLICA_APPLICATION_NAME}\
+                 &options=-c%20default_transaction_read_only%3Don"
+            )
+        });
+
+        if let Some(read_database_url) = &read_database_url {
+            env.insert("DATABASE_READ_URL", read_database_url.clone());
+        }
+
+        env.extend(
+            extra_env
+                .iter()
+                .map(|(name, value)| (*name, (*value).to_owned())),
+        );
+        let meilisearch = match in_memory_search {
+            true => {
+                // required by the configuration, but never connected to
+                env.insert("MEILISEARCH_URL", "http://search-engine.invalid".to_owned());
+                None
+            }
+            false => {
+                let (meilisearch, meilisearch_url) = start_meilisearch().await;
+                env.insert("MEILISEARCH_URL", meilisearch_url);
+                Some(meilisearch)
+            }
+        };
+        let minio = match in_memory_storage {
+            true => None,
+            false => {
+                let minio = MinIO::default()
+                    .start()
+                    .await
+                    .expect("failed to start minio");
+                let s3_endpoint_url = format!(
+                    "http://{}:{}",
+                    minio.get_host().await.unwrap(),
+                    minio.get_host_port_ipv4(9000).await.unwrap(),
+                );
+
+                create_bucket(&s3_endpoint_url).await;
+                env.insert("AWS_S3_ENDPOINT_URL", s3_endpoint_url);
+
+                Some(minio)
+            }
+        };
+
+        // the s3 service reads its credentials from the environment, as in production; every
+        // test sets the same values
+        std::env::set_var("AWS_ACCESS_KEY_ID", MINIO_ACCESS_KEY);
+        std::env::set_var("AWS_SECRET_ACCESS_KEY", MINIO_SECRET_KEY);
+
+        let config = Config::from_lookup(|name| env.get(name).map(OsString::from))
+            .expect("invalid test configuration");
+
+        let storage = in_memory_storage.then(|| Arc::new(InMemoryObjectStorage::new(BUCKET_NAME)));
+        let search_index = in_memory_search.then(|| Arc::new(InMemorySearchIndex::new()));
+        let mut services = match (&storage, &search_index) {
+            (None, None) => Services::init(&config).await,
+            (storage, search_index) => {
+                let object_storage: Arc<dyn ObjectStorage> = match storage {
+                    Some(storage) => storage.clone(),
+                    None => Arc::new(
+                        S3Service::init(&config.s3)
+                            .await
+                            .expect("failed to initialize s3 service"),
+                    ),
+                };
+                let search_index: Arc<dyn SearchIndex> = match search_index {
+                    Some(search_index) => search_index.clone(),
+                    None => Arc::new(
+                        MeilisearchIndex::init(&config.search_engine)
+                            .await
+                            .expect("failed to initialize search engine module"),
+                    ),
+                };
+
+                Services::init_with(&config, object_storage, search_index).await
+            }
+        };
+        let mailer = Arc::new(InMemoryMailer::new());
+        services.admin_service = AdminService::new(
+            AdminRepository::new(
+                sqlx::PgPool::connect(&database_url)
+                    .await
+                    .expect("failed to connect to postgres"),
+            ),
+            mailer.clone(),
+            config.mailer.password_reset_url.clone(),
+            config.admins.bootstrap_token.clone(),
+        );
+        let client = Client::tracked(build_rocket(config, services, None))
+            .await
+            .expect("failed to launch rocket");
+        let mut app = Self {
+            client,
+            database_url,
+            read_database_url,
+            token: String::new(),
+            http: reqwest::Client::new(),
+            _postgres: postgres,
+            _meilisearch: meilisearch,
+            _minio: minio,
+            storage,
+            search_index,
+            mailer,
+        };
+
+        app.token = app.bootstrap_admin().await;
+        app
+    }
+
+    async fn bootstrap_admin(&self) -> String {
+        let response = self
+            .client
+            .post("/admins/bootstrap")
+            .json(&BootstrappingAdmin {
+                username: ADMIN_USERNAME.to_owned(),
+                password: ADMIN_PASSWORD.to_owned(),
+                email: "admin@example.com".to_owned(),
+                bootstrap_token: None,
+            })
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok, "failed to bootstrap admin");
+
+        let session: AdminSession = self
+            .send(self.client.post("/admins/sessions").json(&AdminLogin {
+                username: ADMIN_USERNAME.to_owned(),
+                password: ADMIN_PASSWORD.to_owned(),
+            }))
+            .await;
+        session.token
+    }
+
+    fn authorization(&self) -> Header<'static> {
+        Header::new("Authorization", format!("Bearer {}", self.token))
+    }
+
+    /// Creates an admin with the role, named after it, and logs in as them, answering their
+    /// session token.
+    pub async fn log_in_as(&self, role: AdminRole) -> String {
+        let username = format!("{role:?}-admin").to_lowercase();
+        let password = format!("{username}-password");
+        let _: Admin = self
+            .post(
+                "/admins",
+                &CreatingAdmin {
+                    username: username.clone(),
+                    password: password.clone(),
+                    email: format!("{username}@example.com"),
+                    role,
+                },
+            )
+            .await;
+
+        let session: AdminSession = self
+            .send(
+                self.client
+                    .post("/admins/sessions")
+                    .json(&AdminLogin { username, password }),
+            )
+            .await;
+        session.token
+    }
+
+    /// Gets with the session token, or without any, answering only the status.
+    pub async fn get_for_status_as(&self, token: Option<&str>, uri: &str) -> Status {
+        let mut request = self.client.get(uri.to_owned());
+
+        if let Some(token) = token {
+            request = request.header(Header::new("Authorization", format!("Bearer {token}")));
+        }
+
+        request.dispatch().await.status()
+    }
+
+    /// Posts with the session token, or without any, answering only the status.
+    pub async fn post_for_status_as(
+        &self,
+        token: Option<&str>,
+        uri: &str,
+        body: &impl Serialize,
+    ) -> Status {
+        let mut request = self
+            .client
+            .post(uri.to_owned())
+            .header(ContentType::JSON)
+            .body(serde_json::to_string(body).unwrap());
+
+        if let Some(token) = token {
+            request = request.header(Header::new("Authorization", format!("Bearer {token}")));
+        }
+
+        request.dispatch().await.status()
+    }
+
+    /// Dispatches the request and deserializes its body, failing the test on anything but `200`.
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: rocket::local::asynchronous::LocalRequest<'_>,
+    ) -> T {
+        let uri = request.uri().to_string();
+        let response = request.dispatch().await;
+        let status = response.status();
+        let body = response.into_string().await.unwrap_or_default();
+        assert_eq!(status, Status::Ok, "{uri} answered {status}: {body}");
+
+        serde_json::from_str(&body)
+            .unwrap_or_else(|err| panic!("{uri} answered an unexpected body ({err}): {body}"))
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, uri: &str) -> T {
+        self.send(self.client.get(uri.to_owned()).header(self.authorization()))
+            .await
+    }
+
+    pub async fn post<T: DeserializeOwned>(&self, uri: &str, body: &impl Serialize) -> T {
+        self.send(
+            self.client
+                .post(uri.to_owned())
+                .header(self.authorization())
+                .header(ContentType::JSON)
+                .body(serde_json::to_string(body).unwrap()),
+        )
+        .await
+    }
+
+    pub async fn put<T: DeserializeOwned>(&self, uri: &str, body: &impl Serialize) -> T {
+        self.send(
+            self.client
+                .put(uri.to_owned())
+                .header(self.authorization())
+                .header(ContentType::JSON)
+                .body(serde_json::to_string(body).unwrap()),
+        )
+        .await
+    }
+
+    pub async fn patch<T: DeserializeOwned>(&self, uri: &str, body: &impl Serialize) -> T {
+        self.send(
+            self.client
+                .patch(uri.to_owned())
+                .header(self.authorization())
+                .header(ContentType::JSON)
+                .body(serde_json::to_string(body).unwrap()),
+        )
+        .await
+    }
+
+    pub async fn delete<T: DeserializeOwned>(&self, uri: &str) -> T {
+        self.send(
+            self.client
+                .delete(uri.to_owned())
+                .header(self.authorization()),
+        )
+        .await
+    }
+
+    /// Gets without failing the test on an error, for tests of the error answers.
+    pub async fn get_for_status(&self, uri: &str) -> Status {
+        self.client
+            .get(uri.to_owned())
+            .header(self.authorization())
+            .dispatch()
+            .await
+            .status()
+    }
+
+    /// Deletes without failing the test on an error, for tests of the error answers.
+    pub async fn delete_for_status(&self, uri: &str) -> Status {
+        self.client
+            .delete(uri.to_owned())
+            .header(self.authorization())
+            .dispatch()
+            .await
+            .status()
+    }
+
+    /// Posts without failing the test on an error, for tests of the error answers.
+    pub async fn post_for_status(&self, uri: &str, body: &impl Serialize) -> Status {
+        self.client
+            .post(uri.to_owned())
+            .header(self.authorization())
+            .header(ContentType::JSON)
+            .body(serde_json::to_string(body).unwrap())
+            .dispatch()
+            .await
+            .status()
+    }
+
+    /// Gets a body that is not JSON, e.g. an export, answering its content type and text.
+    pub async fn get_text(&self, uri: &str) -> (Option<ContentType>, String) {
+        let response = self
+            .client
+            .get(uri.to_owned())
+            .header(self.authorization())
+            .dispatch()
+            .await;
+        let status = response.status();
+        let content_type = response.content_type();
+        let body = response.into_string().await.unwrap_or_default();
+        assert_eq!(status, Status::Ok, "{uri}: {body}");
+
+        (content_type, body)
+    }
+
+    /// Gets a request expected to fail, answering its status and error body.
+    pub async fn get_for_error(&self, uri: &str) -> (Status, Value) {
+        let response = self
+            .client
+            .get(uri.to_owned())
+            .header(self.authorization())
+            .dispatch()
+            .await;
+        let status = response.status();
+        let body = response.into_string().await.unwrap_or_default();
+        let body = serde_json::from_str(&body)
+            .unwrap_or_else(|err| panic!("unexpected error body ({err}): {body}"));
+
+        (status, body)
+    }
+
+    /// Posts a request expected to fail, answering its status and error body.
+    pub async fn post_for_error(&self, uri: &str, body: &impl Serialize) -> (Status, Value) {
+        self.send_for_error(self.client.post(uri.to_owned()), body)
+            .await
+    }
+
+    /// Patches with a request expected to fail, answering its status and error body.
+    pub async fn patch_for_error(&self, uri: &str, body: &impl Serialize) -> (Status, Value) {
+        self.send_for_error(self.client.patch(uri.to_owned()), body)
+            .await
+    }
+
+    /// Puts a request expected to fail, answering its status and error body.
+    pub async fn put_for_error(&self, uri: &str, body: &impl Serialize) -> (Status, Value) {
+        self.send_for_error(self.client.put(uri.to_owned()), body)
+            .await
+    }
+
+    async fn send_for_error(
+        &self,
+        request: LocalRequest<'_>,
+        body: &impl Serialize,
+    ) -> (Status, Value) {
+        let response = request
+            .header(self.authorization())
+            .header(ContentType::JSON)
+            .body(serde_json::to_string(body).unwrap())
+            .dispatch()
+            .await;
+        let status = response.status();
+        let body = response.into_string().await.unwrap_or_default();
+        let body = serde_json::from_str(&body)
+            .unwrap_or_else(|err| panic!("unexpected error body ({err}): {body}"));
+
+        (status, body)
+    }
+
+    pub async fn create_collection(&self, name: &str, tags: &[&str]) -> Collection {
+        let created: WithAdminTask<Collection> = self
+            .post(
+                "/collections",
+                &CreatingCollection {
+                    name: name.to_owned(),
+                    unique_names: false,
+                    tags: Vec::from_iter(tags.iter().map(|tag| (*tag).to_owned())),
+                },
+            )
+            .await;
+        created.result
+    }
+
+    /// Creates an unready file sized for `content`, without uploading it.
+    pub async fn create_file(&self, name: &str, content: &[u8], tags: &[&str]) -> File {
+        self.post(
+            "/files",
+            &CreatingFile {
+                name: name.to_owned(),
+                size: content.len(),
+                mime_type: "application/octet-stream".to_owned(),
+                checksum: None,
+                tags: Some(Vec::from_iter(tags.iter().map(|tag| (*tag).to_owned()))),
+                allow_duplicate: false,
+            },
+        )
+        .await
+    }
+
+    /// Uploads `content` to the presigned URLs of a new multipart upload and completes it.
+    pub async fn upload_file(&self, file_id: Uuid, content: &[u8]) -> File {
+        let upload = self.start_upload(file_id).await;
+        assert_eq!(
+            upload.parts.len(),
+            upload.part_count as usize,
+            "the upload has more parts than listed"
+        );
+
+        let parts = self.upload_parts(file_id, &upload, content).await;
+        self.complete_upload(file_id, &upload.id, parts).await
+    }
+
+    pub async fn start_upload(&self, file_id: Uuid) -> FileUploadUrl {
+        self.post(&format!("/files/{file_id}/upload-urls"), &())
+            .await
+    }
+
+    /// Uploads the slices of `content` of the listed parts, returning their ETags.
+    pub async fn upload_parts(
+        &self,
+        file_id: Uuid,
+        upload: &FileUploadUrl,
+        content: &[u8],
+    ) -> Vec<UploadedPart> {
+        let mut parts = Vec::with_capacity(upload.parts.len());
+
+        for part in &upload.parts {
+            let start = part.offset as usize;
+            let end = start + part.size as usize;
+            let body = content[start..end].to_vec();
+            let e_tag = match &self.storage {
+                Some(storage) => storage
+                    .upload_part(file_id, &upload.id, part.part_number, body, None)
+                    .await
+                    .expect("failed to upload part"),
+                None => self.put_part(part, body).await,
+            };
+
+            parts.push(UploadedPart {
+                part_number: part.part_number,
+                e_tag,
+            });
+        }
+
+        parts
+    }
+
+    async fn put_part(&self, part: &FileUploadUrlPart, body: Vec<u8>) -> String {
+        let response = self
+            .http
+            .put(&part.url)
+            .body(body)
+            .send()
+            .await
+            .expect("failed to upload part");
+        assert!(
+            response.status().is_success(),
+            "uploading part {} answered {}",
+            part.part_number,
+            response.status()
+        );
+
+        response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .expect("uploaded part has no etag")
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    pub fn completion_uri(file_id: Uuid, upload_id: &str) -> String {
+        format!(
+            "/files/{file_id}/upload-urls/{}/completes",
+            RawStr::new(upload_id).percent_encode()
+        )
+    }
+
+    pub async fn complete_upload(
+        &self,
+        file_id: Uuid,
+        upload_id: &str,
+        parts: Vec<UploadedPart>,
+    ) -> File {
+        let completed: WithAdminTask<File> = self
+            .post(
+                &Self::completion_uri(file_id, upload_id),
+                &UploadedParts { parts },
+            )
+            .await;
+        completed.result
+    }
+
+    /// Creates a file and uploads `content` to it.
+    pub async fn create_uploaded_file(&self, name: &str, content: &[u8], tags: &[&str]) -> File {
+        let file = self.create_file(name, content, tags).await;
+        self.upload_file(file.id, content).await
+    }
+
+    pub async fn search_files(&self, q: &str) -> Vec<FileSearchHit> {
+        self.search_files_with_skipped(q).await.hits
+    }
+
+    /// Searches files, keeping the count of hits skipped for their malformed documents.
+    pub async fn search_files_with_skipped(&self, q: &str) -> FileSearchResult {
+        let query: FileSearchQuery = serde_json::from_value(serde_json::json!({ "q": q })).unwrap();
+        self.post("/searches/files", &query).await
+    }
+
+    /// Searches until the hits satisfy `predicate`, since the index is updated asynchronously.
+    pub async fn wait_for_search(
+        &self,
+        q: &str,
+        predicate: impl Fn(&[FileSearchHit]) -> bool,
+    ) -> Vec<FileSearchHit> {
+        let deadline = Instant::now() + WAIT_TIMEOUT;
+
+        loop {
+            let hits = self.search_files(q).await;
+
+            if predicate(&hits) {
+                return hits;
+            }
+
+            assert!(
+                Instant::now() < deadline,
+                "search for {q:?} never matched, last hits: {hits:#?}"
+            );
+            tokio::time::sleep(WAIT_INTERVAL).await;
+        }
+    }
+
+    /// Polls the mails until one is sent to `to`, since mails are sent in the background,
+    /// answering the latest.
+    pub async fn wait_for_mail(&self, to: &str) -> Mail {
+        let deadline = Instant::now() + WAIT_TIMEOUT;
+
+        loop {
+            if let Some(mail) = self.mailer.mails().into_iter().rfind(|mail| mail.to == to) {
+                return mail;
+            }
+
+            assert!(Instant::now() < deadline, "no mail was ever sent to {to}");
+            tokio::time::sleep(WAIT_INTERVAL).await;
+        }
+    }
+
+    pub async fn re_index(&self) -> ReIndexAdminTask {
+        self.post("/admin-tasks/re-index", &()).await
+    }
+
+    /// Polls the admin task until the task runner finishes it, whether it completed or not.
+    pub async fn wait_for_task(&self, task_id: Uuid) -> AdminTask {
+        let deadline = Instant::now() + WAIT_TIMEOUT;
+
+        loop {
+            let task: AdminTask = self.get(&format!("/admin-tasks/{task_id}")).await;
+
+            match task.status {
+                AdminTaskStatus::Pending | AdminTaskStatus::InProgress => {}
+                _ => return task,
+            }
+
+            assert!(
+                Instant::now() < deadline,
+                "admin task {task_id} never finished: {task:#?}"
+            );
+            tokio::time::sleep(WAIT_INTERVAL).await;
+        }
+    }
+}
+
+/// Starts a Meilisearch container, returning it with its URL.
+pub async fn start_meilisearch() -> (ContainerAsync<Meilisearch>, String) {
+    let meilisearch = Meilisearch::default()
+        .with_tag(MEILISEARCH_TAG)
+        .start()
+        .await
+        .expect("failed to start meilisearch");
+    let url = format!(
+        "http://{}:{}",
+        meilisearch.get_host().await.unwrap(),
+        meilisearch.get_host_port_ipv4(7700).await.unwrap(),
+    );
+
+    (meilisearch, url)
+}
+
+async fn create_bucket(endpoint_url: &str) {
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new(REGION))
+        .endpoint_url(endpoint_url)
+        .credentials_provider(Credentials::new(
+            MINIO_ACCESS_KEY,
+            MINIO_SECRET_KEY,
+            None,
+            None,
+            "test",
+        ))
+        .force_path_style(true)
+        .build();
+
+    aws_sdk_s3::Client::from_conf(config)
+        .create_bucket()
+        .bucket(BUCKET_NAME)
+        .send()
+        .await
+        .expect("failed to create bucket");
+}
+
+/// A ready file, as it would be handed to a search index, without storage or tag definitions.
+pub fn file(name: &str, size: usize, mime_type: &str, tags: &[&str], uploaded_at: &str) -> File {
+    File {
+        id: Uuid::new_v4(),
+        name: name.to_owned(),
+        size,
+        mime_type: mime_type.to_owned(),
+        checksum: None,
+        is_ready: true,
+        protected: false,
+        superseded_by: None,
+        uploaded_at: uploaded_at.parse::<DateTime<Utc>>().unwrap(),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        storage: None,
+        tag_definitions: Vec::new(),
+    }
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::{
+    collections::{CreatingCollection, UpdatingCollection},
+    files::{CreatingFile, CreatingFiles, UpdatingFile},
+};
+use rocket::http::Status;
+use serde_json::{json, Value};
+
+fn creating_file(name: &str, tags: &[&str]) -> CreatingFile {
+    CreatingFile {
+        name: name.to_owned(),
+        size: 16,
+        mime_type: "text/plain".to_owned(),
+        checksum: None,
+        tags: Some(Vec::from_iter(tags.iter().map(|tag| (*tag).to_owned()))),
+        allow_duplicate: false,
+    }
+}
+
+fn assert_conflict((status, body): (Status, Value), key: &str, value: &str) {
+    assert_eq!(status, Status::Conflict, "{body}");
+    assert_eq!(body["code"], json!("conflict"), "{body}");
+    assert_eq!(body["key"], json!(key), "{body}");
+    assert_eq!(body["value"], json!(value), "{body}");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn creating_a_file_with_a_repeated_tag_conflicts() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    let answer = app
+        .post_for_error("/files", &creating_file("notes.txt", &["work", "work"]))
+        .await;
+    assert_conflict(answer, "file_tags_pkey", "work");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn creating_files_with_a_repeated_tag_conflicts() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    let answer = app
+        .post_for_error(
+            "/files/batch",
+            &CreatingFiles {
+                files: vec![
+                    creating_file("a.txt", &["work"]),
+                    creating_file("b.txt", &["draft", "draft"]),
+                ],
+            },
+        )
+        .await;
+    assert_conflict(answer, "file_tags_pkey", "draft");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn adding_a_tag_a_file_has_conflicts() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app.create_file("notes.txt", b"some notes", &["work"]).await;
+
+    let answer = app
+        .patch_for_error(
+            &format!("/files/{}", file.id),
+            &UpdatingFile {
+                name: None,
+                size: None,
+                mime_type: None,
+                tags_for_creation: Some(vec!["work".to_owned()]),
+                tags_for_deletion: None,
+                protected: None,
+                superseded_by: None,
+            },
+        )
+        .await;
+    assert_conflict(answer, "file_tags_pkey", "work");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn creating_a_collection_with_a_repeated_tag_conflicts() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    let answer = app
+        .post_for_error(
+            "/collections",
+            &CreatingCollection {
+                name: "Invoices".to_owned(),
+                unique_names: false,
+                tags: vec!["invoices".to_owned(), "invoices".to_owned()],
+            },
+        )
+        .await;
+    assert_conflict(answer, "collection_tags_pkey", "invoices");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn adding_a_tag_a_collection_has_conflicts() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let collection = app.create_collection("Invoices", &["invoices"]).await;
+
+    let answer = app
+        .patch_for_error(
+            &format!("/collections/{}", collection.id),
+            &UpdatingCollection {
+                name: None,
+                unique_names: None,
+                tags_for_creation: Some(vec!["invoices".to_owned()]),
+                tags_for_deletion: None,
+            },
+        )
+        .await;
+    assert_conflict(answer, "collection_tags_pkey", "invoices");
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use common::TestApp;
+use file_indexer::interfaces::{collections::Collection, files::File, WithAdminTask};
+use rocket::http::Status;
+use serde_json::json;
+use uuid::Uuid;
+
+fn file_cursor_uri(file_id: Uuid, uploaded_at: DateTime<Utc>, verify: bool) -> String {
+    format!(
+        "/files?limit=10&last-file-id={file_id}&last-file-uploaded-at={}&verify-cursor={verify}",
+        uploaded_at.to_rfc3339_opts(SecondsFormat::Micros, true)
+    )
+}
+
+/// Uploads the files one after another, answering them oldest first.
+async fn create_files(app: &TestApp, names: &[&str]) -> Vec<File> {
+    let mut files = Vec::new();
+
+    for name in names {
+        files.push(app.create_uploaded_file(name, b"content", &[]).await);
+    }
+
+    files
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_cursor_of_an_existing_file_is_accepted() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let files = create_files(&app, &["a.txt", "b.txt"]).await;
+    let newest = &files[1];
+
+    let page: Vec<File> = app
+        .get(&file_cursor_uri(newest.id, newest.uploaded_at, true))
+        .await;
+
+    assert!(!page.iter().any(|file| file.id == newest.id), "{page:#?}");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_cursor_of_a_deleted_file_is_refused() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let files = create_files(&app, &["a.txt", "b.txt"]).await;
+    let deleted = &files[1];
+
+    assert_eq!(
+        app.delete_for_status(&format!("/files/{}", deleted.id))
+            .await,
+        Status::Ok
+    );
+
+    let (status, error) = app
+        .get_for_error(&file_cursor_uri(deleted.id, deleted.uploaded_at, true))
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity);
+    assert_eq!(error["code"], "invalid_cursor");
+
+    // without verification, the cursor applies as it is
+    assert_eq!(
+        app.get_for_status(&file_cursor_uri(deleted.id, deleted.uploaded_at, false))
+            .await,
+        Status::Ok
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_cursor_with_another_timestamp_is_refused() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let files = create_files(&app, &["a.txt", "b.txt"]).await;
+    let newest = &files[1];
+
+    for uploaded_at in [
+        newest.uploaded_at - Duration::hours(1),
+        newest.uploaded_at + Duration::microseconds(1),
+    ] {
+        let (status, error) = app
+            .get_for_error(&file_cursor_uri(newest.id, uploaded_at, true))
+            .await;
+        assert_eq!(status, Status::UnprocessableEntity, "{uploaded_at}");
+        assert_eq!(error["code"], "invalid_cursor", "{uploaded_at}");
+
+        assert_eq!(
+            app.get_for_status(&file_cursor_uri(newest.id, uploaded_at, false))
+                .await,
+            Status::Ok,
+            "{uploaded_at}"
+        );
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_cursor_of_a_renamed_collection_is_refused() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let first = app.create_collection("Archive", &[]).await;
+    let second = app.create_collection("Reports", &[]).await;
+    let cursor_uri = |verify: bool| {
+        format!(
+            "/collections?last-collection-id={}&last-collection-name=Archive&verify-cursor={verify}",
+            first.id
+        )
+    };
+
+    let page: Vec<Collection> = app.get(&cursor_uri(true)).await;
+    assert_eq!(
+        Vec::from_iter(page.iter().map(|collection| collection.id)),
+        vec![second.id]
+    );
+
+    let _: WithAdminTask<Collection> = app
+        .patch(
+            &format!("/collections/{}", first.id),
+            &json!({ "name": "Zettelkasten" }),
+        )
+        .await;
+
+    let (status, error) = app.get_for_error(&cursor_uri(true)).await;
+    assert_eq!(status, Status::UnprocessableEntity);
+    assert_eq!(error["code"], "invalid_cursor");
+
+    let page: Vec<Collection> = app.get(&cursor_uri(false)).await;
+    assert_eq!(page.len(), 2, "{page:#?}");
+}
@@ -0,0 +1,180 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::TestApp;
+use file_indexer::{
+    interfaces::{
+        admins::AdminTaskStatus,
+        collections::{CollectionDownloadManifest, CreatedCollectionDownloadManifest},
+        files::FileDownloadUrl,
+    },
+    testing::object_storage::StoredObject,
+};
+use rocket::http::Status;
+use uuid::Uuid;
+
+/// Above the number of files a manifest is generated for right away.
+const ASYNC_FILE_COUNT: usize = 5001;
+
+fn manifest_uri(collection_id: Uuid) -> String {
+    format!("/collections/{collection_id}/download-manifests")
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn small_collections_get_their_manifest_right_away() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let collection = app.create_collection("Invoices", &["invoices"]).await;
+    let mut files = Vec::new();
+
+    for (name, content) in [("march.pdf", "march"), ("april.pdf", "april content")] {
+        files.push(
+            app.create_uploaded_file(name, content.as_bytes(), &["invoices", "2024"])
+                .await,
+        );
+    }
+
+    let missing = app
+        .create_uploaded_file("missing.pdf", b"missing", &["invoices"])
+        .await;
+    app.storage
+        .as_ref()
+        .unwrap()
+        .remove_object(&missing.id.to_string(), None);
+    app.create_file("unready.pdf", b"unready", &["invoices"])
+        .await;
+    app.create_uploaded_file("other.pdf", b"other", &["receipts"])
+        .await;
+
+    let requested_at = Utc::now();
+    let created: CreatedCollectionDownloadManifest =
+        app.post(&manifest_uri(collection.id), &()).await;
+    assert_eq!(created.admin_task_id, None);
+    let manifest = created.manifest.unwrap();
+    assert_eq!(manifest.collection_id, collection.id);
+    assert_eq!(manifest.missing_count, 1);
+
+    // the default download URL duration, taken before the first URL was presigned
+    let expiry = Duration::hours(1);
+    assert!(requested_at + expiry <= manifest.expires_at);
+    assert!(manifest.expires_at <= Utc::now() + expiry);
+
+    files.sort_by_key(|file| file.id);
+    assert_eq!(
+        Vec::from_iter(
+            manifest
+                .files
+                .iter()
+                .map(|file| (file.id, file.name.as_str(), file.size))
+        ),
+        Vec::from_iter(
+            files
+                .iter()
+                .map(|file| (file.id, file.name.as_str(), file.size))
+        ),
+    );
+    assert!(manifest
+        .files
+        .iter()
+        .all(|file| file.url.contains(&file.id.to_string())));
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn empty_and_unknown_collections() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    app.create_uploaded_file("report.pdf", b"report", &[]).await;
+
+    let collection = app.create_collection("Everything", &[]).await;
+    let created: CreatedCollectionDownloadManifest =
+        app.post(&manifest_uri(collection.id), &()).await;
+    assert!(created.manifest.unwrap().files.is_empty());
+
+    assert_eq!(
+        app.post_for_status(&manifest_uri(Uuid::new_v4()), &())
+            .await,
+        Status::NotFound
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn large_collections_get_their_manifest_from_a_task() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let storage = app.storage.as_ref().unwrap();
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let collection = app.create_collection("Ledgers", &["ledger"]).await;
+
+    // far quicker than uploading them one by one
+    let mut file_ids: Vec<Uuid> = sqlx::query_scalar(
+        "
+INSERT INTO files (name, name_sort_key, size, mime_type, is_ready)
+SELECT 'ledger-' || number || '.csv', 'ledger-' || number || '.csv', 6, 'text/csv', TRUE
+FROM generate_series(1, $1) AS number
+RETURNING id",
+    )
+    .bind(ASYNC_FILE_COUNT as i32)
+    .fetch_all(&db_pool)
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO file_tags (file_id, tag) SELECT UNNEST($1::uuid[]), 'ledger'")
+        .bind(&file_ids)
+        .execute(&db_pool)
+        .await
+        .unwrap();
+
+    for file_id in &file_ids[1..] {
+        storage.insert_object(
+            &file_id.to_string(),
+            None,
+            StoredObject {
+                content_type: "text/csv".to_owned(),
+                body: b"ledger".to_vec(),
+            },
+        );
+    }
+
+    let created: CreatedCollectionDownloadManifest =
+        app.post(&manifest_uri(collection.id), &()).await;
+    assert!(created.manifest.is_none());
+    let task_id = created.admin_task_id.unwrap();
+
+    let task = app.wait_for_task(task_id).await;
+    assert_eq!(task.status, AdminTaskStatus::Completed, "{task:#?}");
+    assert_eq!(task.metadata["file_count"], ASYNC_FILE_COUNT - 1);
+    assert_eq!(task.metadata["missing_count"], 1);
+
+    let download_url: FileDownloadUrl = app
+        .post(
+            &format!("{}/{task_id}/download-urls", manifest_uri(collection.id)),
+            &(),
+        )
+        .await;
+    assert!(download_url
+        .url
+        .ends_with(&format!("manifests/{task_id}.json")));
+
+    let object = storage
+        .object(&format!("manifests/{task_id}.json"), None)
+        .unwrap();
+    let manifest: CollectionDownloadManifest = serde_json::from_slice(&object.body).unwrap();
+    assert_eq!(manifest.collection_id, collection.id);
+    assert_eq!(manifest.missing_count, 1);
+    file_ids.remove(0);
+    file_ids.sort();
+    assert_eq!(
+        Vec::from_iter(manifest.files.iter().map(|file| file.id)),
+        file_ids
+    );
+
+    // the manifest of a task is only served for its collection
+    let other = app.create_collection("Others", &["other"]).await;
+    assert_eq!(
+        app.post_for_status(
+            &format!("{}/{task_id}/download-urls", manifest_uri(other.id)),
+            &(),
+        )
+        .await,
+        Status::NotFound
+    );
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::{
+    interfaces::{admins::TaskKind, files::FileDownloadUrl},
+    testing::object_storage::StoredObject,
+};
+use rocket::http::Status;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+fn download_uri(file_id: Uuid) -> String {
+    format!("/files/{file_id}/download-urls")
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_ready_file_with_its_object_is_downloadable() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app
+        .create_uploaded_file("report.pdf", b"uploaded content", &[])
+        .await;
+
+    let download: FileDownloadUrl = app.post(&download_uri(file.id), &json!({})).await;
+    assert!(!download.url.is_empty());
+    assert_eq!(download.file_id, None);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn an_unknown_file_is_not_found() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app
+        .create_uploaded_file("report.pdf", b"uploaded content", &[])
+        .await;
+    assert_eq!(
+        app.delete_for_status(&format!("/files/{}", file.id)).await,
+        Status::Ok
+    );
+
+    for file_id in [Uuid::new_v4(), file.id] {
+        let (status, error) = app.post_for_error(&download_uri(file_id), &json!({})).await;
+        assert_eq!(status, Status::NotFound, "{file_id}");
+        assert_eq!(error["code"], "file_not_found", "{file_id}");
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn an_unready_file_is_not_downloadable_even_with_its_object() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let content = b"uploaded content";
+    let file = app.create_file("report.pdf", content, &[]).await;
+
+    // the object is there, the file was just never marked ready
+    app.storage.as_ref().unwrap().insert_object(
+        &file.id.to_string(),
+        None,
+        StoredObject {
+            content_type: file.mime_type.clone(),
+            body: content.to_vec(),
+        },
+    );
+
+    let (status, error) = app.post_for_error(&download_uri(file.id), &json!({})).await;
+    assert_eq!(status, Status::Conflict);
+    assert_eq!(error["code"], "file_not_ready");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_ready_file_without_its_object_enqueues_a_reconciliation() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app
+        .create_uploaded_file("report.pdf", b"uploaded content", &[])
+        .await;
+    app.storage
+        .as_ref()
+        .unwrap()
+        .remove_object(&file.id.to_string(), None)
+        .unwrap();
+
+    let (status, error) = app.post_for_error(&download_uri(file.id), &json!({})).await;
+    assert_eq!(status, Status::BadGateway);
+    assert_eq!(error["code"], "object_missing");
+
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let metadata: Vec<Value> =
+        sqlx::query_scalar("SELECT metadata FROM admin_tasks WHERE name = $1")
+            .bind(TaskKind::ReconcileStorage.as_str())
+            .fetch_all(&db_pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        metadata,
+        [json!({ "delete_orphans": false, "mark_missing_unready": false })]
+    );
+}
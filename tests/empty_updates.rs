@@ -0,0 +1,108 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::{collections::Collection, files::File, WithAdminTask};
+use rocket::http::Status;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+const WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+const WAIT_INTERVAL: Duration = Duration::from_millis(200);
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn an_update_without_fields_is_refused() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app
+        .create_uploaded_file("report.pdf", b"uploaded content", &[])
+        .await;
+    let collection = app.create_collection("Reports", &[]).await;
+    let empty_bodies = [
+        json!({}),
+        json!({ "tagsForCreation": [], "tagsForDeletion": [] }),
+    ];
+
+    for uri in [
+        format!("/files/{}", file.id),
+        format!("/collections/{}", collection.id),
+    ] {
+        for body in &empty_bodies {
+            let (status, error) = app.patch_for_error(&uri, body).await;
+            assert_eq!(status, Status::UnprocessableEntity, "{uri} {body}");
+            assert_eq!(error["code"], "no_fields", "{uri} {body}");
+        }
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_file_update_that_changes_nothing_is_not_indexed() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let search_index = app.search_index.clone().unwrap();
+    let unchanged = app
+        .create_uploaded_file("report.pdf", b"uploaded content", &[])
+        .await;
+    let changed = app
+        .create_uploaded_file("draft.pdf", b"other content", &[])
+        .await;
+
+    // marks the indexed document, which indexing the file again would overwrite
+    let mut document = search_index.file_document(unchanged.id).unwrap();
+    document["name"] = Value::from("indexed-report.pdf");
+    search_index.insert_file_document(document);
+
+    let updated: WithAdminTask<File> = app
+        .patch(
+            &format!("/files/{}", unchanged.id),
+            &json!({ "name": "report.pdf" }),
+        )
+        .await;
+    assert_eq!(updated.admin_task_id, None);
+
+    let updated: WithAdminTask<File> = app
+        .patch(
+            &format!("/files/{}", changed.id),
+            &json!({ "name": "final-draft.pdf" }),
+        )
+        .await;
+    assert!(updated.admin_task_id.is_some());
+
+    // queued files are flushed once their window passes, so one queued earlier would be by now
+    let deadline = Instant::now() + WAIT_TIMEOUT;
+
+    while search_index.file_document(changed.id).unwrap()["name"] != "final-draft.pdf" {
+        assert!(
+            Instant::now() < deadline,
+            "the changed file was not indexed in time"
+        );
+        tokio::time::sleep(WAIT_INTERVAL).await;
+    }
+
+    assert_eq!(
+        search_index.file_document(unchanged.id).unwrap()["name"],
+        "indexed-report.pdf"
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_collection_update_that_changes_nothing_is_not_recorded() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let collection = app.create_collection("Reports", &["team:design"]).await;
+    let uri = format!("/collections/{}", collection.id);
+
+    let updated: WithAdminTask<Collection> = app
+        .patch(
+            &uri,
+            &json!({ "name": "Reports", "tagsForDeletion": ["team:other"] }),
+        )
+        .await;
+    assert_eq!(updated.admin_task_id, None);
+    assert_eq!(updated.result.tags, vec!["team:design".to_owned()]);
+
+    let updated: WithAdminTask<Collection> = app
+        .patch(&uri, &json!({ "tagsForDeletion": ["team:design"] }))
+        .await;
+    assert!(updated.admin_task_id.is_some());
+    assert!(updated.result.tags.is_empty());
+}
@@ -0,0 +1,40 @@
+use file_indexer::telemetry::ErrorChain;
+use std::io;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+#[error("failed to export files")]
+struct ExportError(#[source] ListFilesError);
+
+#[derive(Error, Debug)]
+#[error("failed to list files")]
+struct ListFilesError(#[source] RepositoryError);
+
+#[derive(Error, Debug)]
+#[error("database error")]
+struct RepositoryError(#[source] io::Error);
+
+#[test]
+fn an_error_is_followed_by_each_of_its_sources() {
+    let err = ExportError(ListFilesError(RepositoryError(io::Error::new(
+        io::ErrorKind::ConnectionRefused,
+        "connection refused",
+    ))));
+
+    assert_eq!(
+        ErrorChain(&err).to_string(),
+        "failed to export files: failed to list files: database error: connection refused"
+    );
+    // the chain starts wherever it is given
+    assert_eq!(
+        ErrorChain(&err.0 .0).to_string(),
+        "database error: connection refused"
+    );
+}
+
+#[test]
+fn an_error_without_sources_is_formatted_alone() {
+    let err = io::Error::new(io::ErrorKind::TimedOut, "timed out");
+
+    assert_eq!(ErrorChain(&err).to_string(), "timed out");
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::{
+    interfaces::files::{
+        CreatingFile, File, FileSearchQuery, FileSearchQueryFilter, FileSearchResult,
+    },
+    services::index_service::filters::build_file_filter,
+};
+use rocket::http::Status;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+const WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+const WAIT_INTERVAL: Duration = Duration::from_millis(200);
+
+fn exists(field: &str) -> FileSearchQueryFilter {
+    FileSearchQueryFilter::Exists {
+        field: field.to_owned(),
+    }
+}
+
+fn not_exists(field: &str) -> FileSearchQueryFilter {
+    FileSearchQueryFilter::NotExists {
+        field: field.to_owned(),
+    }
+}
+
+fn filtered(filters: Vec<Vec<FileSearchQueryFilter>>) -> FileSearchQuery {
+    FileSearchQuery {
+        filters,
+        ..serde_json::from_value(json!({ "q": "" })).unwrap()
+    }
+}
+
+async fn uploaded_file_with_checksum(app: &TestApp, name: &str, content: &[u8]) -> File {
+    let file: File = app
+        .post(
+            "/files",
+            &CreatingFile {
+                name: name.to_owned(),
+                size: content.len(),
+                mime_type: "application/pdf".to_owned(),
+                checksum: Some("ab".repeat(32)),
+                tags: None,
+                allow_duplicate: false,
+            },
+        )
+        .await;
+    app.upload_file(file.id, content).await
+}
+
+/// Searches until the names of the hits are `expected`, since the index is updated asynchronously.
+async fn wait_for_names(app: &TestApp, q: &FileSearchQuery, expected: &[&str]) {
+    let deadline = Instant::now() + WAIT_TIMEOUT;
+
+    loop {
+        let result: FileSearchResult = app.post("/searches/files", q).await;
+        let mut names = Vec::from_iter(result.hits.iter().map(|hit| hit.file.name.clone()));
+        names.sort();
+
+        if names == expected {
+            return;
+        }
+
+        assert!(
+            Instant::now() < deadline,
+            "search with {:?} never matched, last hits: {names:?}",
+            q.filters
+        );
+        tokio::time::sleep(WAIT_INTERVAL).await;
+    }
+}
+
+#[test]
+fn existence_filters_become_exists_expressions() {
+    assert_eq!(
+        build_file_filter(&[exists("checksum")]).unwrap(),
+        "(checksum EXISTS)"
+    );
+    assert_eq!(
+        build_file_filter(&[not_exists("checksum")]).unwrap(),
+        "(checksum NOT EXISTS)"
+    );
+}
+
+#[test]
+fn only_optional_fields_may_be_tested_for_existence() {
+    assert!(filtered(vec![vec![exists("checksum")]])
+        .validate()
+        .is_empty());
+
+    let errors = filtered(vec![
+        vec![not_exists("checksum")],
+        vec![exists("checksum"), not_exists("name")],
+    ])
+    .validate();
+    assert_eq!(
+        Vec::from_iter(errors.iter().map(|error| error.field.as_str())),
+        ["filters[1][1].field"]
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn files_missing_a_checksum_are_found_for_a_backfill() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let hashed = uploaded_file_with_checksum(&app, "hashed.pdf", b"hashed").await;
+    let unhashed = app
+        .create_uploaded_file("unhashed.pdf", b"unhashed", &[])
+        .await;
+
+    // unset optional fields are left out of the document rather than indexed as null
+    let search_index = app.search_index.as_ref().unwrap();
+    let document = search_index.file_document(unhashed.id).unwrap();
+    assert!(document.get("checksum").is_none(), "{document}");
+    let document = search_index.file_document(hashed.id).unwrap();
+    assert_eq!(document["checksum"], "ab".repeat(32));
+
+    wait_for_names(
+        &app,
+        &filtered(vec![vec![not_exists("checksum")]]),
+        &["unhashed.pdf"],
+    )
+    .await;
+    wait_for_names(
+        &app,
+        &filtered(vec![vec![exists("checksum")]]),
+        &["hashed.pdf"],
+    )
+    .await;
+
+    let (status, error) = app
+        .post_for_error("/searches/files", &filtered(vec![vec![not_exists("name")]]))
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity);
+    assert_eq!(
+        error["fields"][0]["field"], "filters[0][0].field",
+        "{error}"
+    );
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::files::File;
+use rocket::http::RawStr;
+
+fn prefix_uri(prefix: &str, rest: &str) -> String {
+    format!(
+        "/files?name-prefix={}{rest}",
+        RawStr::new(prefix).percent_encode()
+    )
+}
+
+fn names(files: &[File]) -> Vec<&str> {
+    let mut names = Vec::from_iter(files.iter().map(|file| file.name.as_str()));
+    names.sort();
+    names
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn wildcards_in_the_prefix_match_literally() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    for name in [
+        "50%_off.txt",
+        "50x_off.txt",
+        "5000.txt",
+        "a_b.txt",
+        "axb.txt",
+        "back\\slash.txt",
+        "backslash.txt",
+    ] {
+        app.create_uploaded_file(name, b"content", &[]).await;
+    }
+
+    for (prefix, expected) in [
+        ("50%", vec!["50%_off.txt"]),
+        ("50", vec!["50%_off.txt", "5000.txt", "50x_off.txt"]),
+        ("a_", vec!["a_b.txt"]),
+        ("back\\", vec!["back\\slash.txt"]),
+        ("%", vec![]),
+        ("_", vec![]),
+    ] {
+        let files: Vec<File> = app.get(&prefix_uri(prefix, "")).await;
+        assert_eq!(names(&files), expected, "{prefix}");
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_prefix_composes_with_the_sort_and_the_cursor() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    for name in [
+        "report-10.pdf",
+        "report-2.pdf",
+        "summary.pdf",
+        "report-1.pdf",
+    ] {
+        app.create_uploaded_file(name, b"content", &[]).await;
+    }
+    // not ready, so never listed
+    app.create_file("report-3.pdf", b"content", &[]).await;
+
+    let files: Vec<File> = app.get(&prefix_uri("report-", "")).await;
+    assert_eq!(
+        names(&files),
+        vec!["report-1.pdf", "report-10.pdf", "report-2.pdf"]
+    );
+
+    let first_page: Vec<File> = app
+        .get(&prefix_uri("report-", "&sort=name-natural&limit=2"))
+        .await;
+    assert_eq!(
+        Vec::from_iter(first_page.iter().map(|file| file.name.as_str())),
+        vec!["report-1.pdf", "report-2.pdf"]
+    );
+
+    let last = first_page.last().unwrap();
+    let cursor = format!(
+        "&sort=name-natural&limit=2&last-file-id={}&last-file-name={}",
+        last.id,
+        RawStr::new(&last.name).percent_encode()
+    );
+    let second_page: Vec<File> = app.get(&prefix_uri("report-", &cursor)).await;
+    assert_eq!(
+        Vec::from_iter(second_page.iter().map(|file| file.name.as_str())),
+        vec!["report-10.pdf"]
+    );
+}
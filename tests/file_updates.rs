@@ -0,0 +1,99 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::{files::File, WithAdminTask};
+use rocket::http::Status;
+use serde_json::json;
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_size_and_mime_type_of_a_ready_file_cannot_change() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app
+        .create_uploaded_file("report.pdf", b"uploaded content", &[])
+        .await;
+    let uri = format!("/files/{}", file.id);
+
+    for body in [json!({ "size": 4 }), json!({ "mimeType": "text/plain" })] {
+        let (status, _) = app.patch_for_error(&uri, &body).await;
+        assert_eq!(status, Status::Conflict, "{body}");
+    }
+
+    let unchanged: File = app.get(&uri).await;
+    assert_eq!(unchanged.size, file.size);
+    assert_eq!(unchanged.mime_type, file.mime_type);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_name_and_tags_of_a_ready_file_can_change() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app
+        .create_uploaded_file("report.pdf", b"uploaded content", &["draft"])
+        .await;
+
+    let updated: WithAdminTask<File> = app
+        .patch(
+            &format!("/files/{}", file.id),
+            &json!({
+                "name": "final-report.pdf",
+                "tagsForCreation": ["final"],
+                "tagsForDeletion": ["draft"],
+            }),
+        )
+        .await;
+
+    assert_eq!(updated.result.name, "final-report.pdf");
+    assert_eq!(updated.result.tags, vec!["final".to_owned()]);
+    assert_eq!(updated.result.size, file.size);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn an_update_of_a_ready_file_mixing_refused_fields_changes_nothing() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app
+        .create_uploaded_file("report.pdf", b"uploaded content", &["draft"])
+        .await;
+    let uri = format!("/files/{}", file.id);
+
+    let (status, _) = app
+        .patch_for_error(
+            &uri,
+            &json!({
+                "name": "final-report.pdf",
+                "tagsForCreation": ["final"],
+                "mimeType": "text/plain",
+            }),
+        )
+        .await;
+    assert_eq!(status, Status::Conflict);
+
+    let unchanged: File = app.get(&uri).await;
+    assert_eq!(unchanged.name, "report.pdf");
+    assert_eq!(unchanged.tags, vec!["draft".to_owned()]);
+    assert_eq!(unchanged.mime_type, file.mime_type);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn every_field_of_an_unready_file_can_change() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app.create_file("report.pdf", b"declared", &[]).await;
+
+    let updated: WithAdminTask<File> = app
+        .patch(
+            &format!("/files/{}", file.id),
+            &json!({
+                "name": "report.txt",
+                "size": 1234,
+                "mimeType": "text/plain",
+            }),
+        )
+        .await;
+
+    assert!(!updated.result.is_ready);
+    assert_eq!(updated.result.name, "report.txt");
+    assert_eq!(updated.result.size, 1234);
+    assert_eq!(updated.result.mime_type, "text/plain");
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::files::{BulkDeletingFiles, CreatingFile, CreatingFileVersion};
+use rocket::http::Status;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+fn fields(body: &Value) -> Vec<&str> {
+    Vec::from_iter(
+        body["fields"]
+            .as_array()
+            .unwrap_or_else(|| panic!("no field errors: {body}"))
+            .iter()
+            .map(|field| field["field"].as_str().unwrap()),
+    )
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn malformed_checksums_are_refused_with_a_field_error() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    let (status, body) = app
+        .post_for_error(
+            "/files",
+            &CreatingFile {
+                name: "a.txt".to_owned(),
+                size: 1,
+                mime_type: "text/plain".to_owned(),
+                checksum: Some("not-a-digest".to_owned()),
+                tags: None,
+                allow_duplicate: false,
+            },
+        )
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity, "{body}");
+    assert_eq!(fields(&body), ["checksum"], "{body}");
+
+    let file = app.create_uploaded_file("b.txt", b"b", &[]).await;
+    let (status, body) = app
+        .post_for_error(
+            &format!("/files/{}/versions", file.id),
+            &CreatingFileVersion {
+                name: None,
+                size: 1,
+                mime_type: "text/plain".to_owned(),
+                checksum: Some("a".repeat(63)),
+            },
+        )
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity, "{body}");
+    assert_eq!(fields(&body), ["checksum"], "{body}");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn bulk_deletions_name_the_fields_they_are_refused_for() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    let (status, body) = app
+        .post_for_error(
+            "/files/bulk-delete",
+            &BulkDeletingFiles {
+                file_ids: None,
+                query: None,
+            },
+        )
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity, "{body}");
+    assert_eq!(fields(&body), ["fileIds", "query"], "{body}");
+
+    let (status, body) = app
+        .post_for_error(
+            "/files/bulk-delete",
+            &json!({ "fileIds": [Uuid::new_v4()], "query": { "q": "a" } }),
+        )
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity, "{body}");
+    assert_eq!(fields(&body), ["fileIds", "query"], "{body}");
+
+    let too_many = Vec::from_iter((0..10_001).map(|_| Uuid::new_v4()));
+    let (status, body) = app
+        .post_for_error(
+            "/files/bulk-delete",
+            &BulkDeletingFiles {
+                file_ids: Some(too_many),
+                query: None,
+            },
+        )
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity, "{body}");
+    assert_eq!(fields(&body), ["fileIds"], "{body}");
+}
@@ -0,0 +1,150 @@
+//! Generates filter groups and checks that the expressions built from them select exactly the
+//! files conjunctive normal form selects: a file matches when every group has a filter it
+//! matches. The in-memory search index parses expressions with the precedence Meilisearch gives
+//! them (`NOT` over `AND` over `OR`), so evaluating the groups joined into one expression fails
+//! when a group is not parenthesized.
+
+mod common;
+
+use common::file;
+use file_indexer::{
+    interfaces::files::{
+        File, FileSearchQuery, FileSearchQueryFilter, FileSearchQueryFilterOperator,
+    },
+    services::index_service::{filters::build_file_filters, SearchIndex},
+    testing::search_index::InMemorySearchIndex,
+};
+use proptest::{prelude::*, test_runner::TestRunner};
+use std::collections::{BTreeSet, HashMap};
+use uuid::Uuid;
+
+const TAGS: [&str; 3] = ["red", "green", "blue"];
+const MIME_TYPES: [&str; 2] = ["text/plain", "image/png"];
+const OPERATORS: [FileSearchQueryFilterOperator; 6] = [
+    FileSearchQueryFilterOperator::Eq,
+    FileSearchQueryFilterOperator::Neq,
+    FileSearchQueryFilterOperator::Gt,
+    FileSearchQueryFilterOperator::Gte,
+    FileSearchQueryFilterOperator::Lt,
+    FileSearchQueryFilterOperator::Lte,
+];
+
+/// A file for every combination of the tags, alternating mime types, sizes and protection.
+fn files() -> Vec<File> {
+    Vec::from_iter((0..1 << TAGS.len()).map(|bits: usize| {
+        let tags = Vec::from_iter(
+            TAGS.iter()
+                .enumerate()
+                .filter(|(index, _)| bits & (1 << index) != 0)
+                .map(|(_, tag)| *tag),
+        );
+        let mut file = file(
+            &format!("file-{bits}.txt"),
+            bits * 10,
+            MIME_TYPES[bits % MIME_TYPES.len()],
+            &tags,
+            "2024-01-10T00:00:00Z",
+        );
+        file.protected = bits.is_multiple_of(3);
+        file
+    }))
+}
+
+fn filter() -> impl Strategy<Value = FileSearchQueryFilter> {
+    let tag = proptest::sample::select(&TAGS[..]).prop_map(str::to_owned);
+    let mime_type = proptest::sample::select(&MIME_TYPES[..]).prop_map(str::to_owned);
+
+    prop_oneof![
+        tag.clone()
+            .prop_map(|value| FileSearchQueryFilter::Tag { value }),
+        tag.prop_map(|value| FileSearchQueryFilter::NotTag { value }),
+        mime_type
+            .clone()
+            .prop_map(|value| FileSearchQueryFilter::MimeType { value }),
+        mime_type.prop_map(|value| FileSearchQueryFilter::NotMimeType { value }),
+        (proptest::sample::select(&OPERATORS[..]), 0..80usize)
+            .prop_map(|(operator, value)| FileSearchQueryFilter::Size { operator, value }),
+        Just(FileSearchQueryFilter::TagIsEmpty),
+        Just(FileSearchQueryFilter::TagIsNotEmpty),
+        any::<bool>().prop_map(|value| FileSearchQueryFilter::Protected { value }),
+    ]
+}
+
+fn matches(file: &File, filter: &FileSearchQueryFilter) -> bool {
+    use FileSearchQueryFilterOperator::{Eq, Gt, Gte, Lt, Lte, Neq};
+
+    match filter {
+        FileSearchQueryFilter::Tag { value } => file.tags.contains(value),
+        FileSearchQueryFilter::NotTag { value } => !file.tags.contains(value),
+        FileSearchQueryFilter::MimeType { value } => file.mime_type == *value,
+        FileSearchQueryFilter::NotMimeType { value } => file.mime_type != *value,
+        FileSearchQueryFilter::Size { operator, value } => match operator {
+            Eq => file.size == *value,
+            Neq => file.size != *value,
+            Gt => file.size > *value,
+            Gte => file.size >= *value,
+            Lt => file.size < *value,
+            Lte => file.size <= *value,
+        },
+        FileSearchQueryFilter::TagIsEmpty => file.tags.is_empty(),
+        FileSearchQueryFilter::TagIsNotEmpty => !file.tags.is_empty(),
+        FileSearchQueryFilter::Protected { value } => file.protected == *value,
+        _ => unreachable!("not generated"),
+    }
+}
+
+#[test]
+fn groups_are_joined_with_and_and_their_filters_with_or() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let index = InMemorySearchIndex::new();
+    let files = files();
+    runtime
+        .block_on(index.index_files(&files, &HashMap::new()))
+        .unwrap();
+
+    let groups = proptest::collection::vec(proptest::collection::vec(filter(), 0..4), 0..4);
+    TestRunner::default()
+        .run(&groups, |groups| {
+            let expected = BTreeSet::from_iter(
+                files
+                    .iter()
+                    .filter(|file| {
+                        groups.iter().all(|group| {
+                            group.is_empty() || group.iter().any(|filter| matches(file, filter))
+                        })
+                    })
+                    .map(|file| file.id),
+            );
+
+            let q = FileSearchQuery {
+                q: String::new(),
+                limit: 100,
+                offset: 0,
+                filters: groups.clone(),
+                matching_strategy: None,
+                attributes_to_highlight: None,
+                crop_length: None,
+                distinct: None,
+                include_unready: false,
+                include_superseded: false,
+            };
+
+            let hits = runtime.block_on(index.search_files(&q)).unwrap();
+            let found = BTreeSet::<Uuid>::from_iter(hits.hits.iter().map(|hit| hit.file.id));
+            prop_assert_eq!(&found, &expected);
+
+            let expression = build_file_filters(&q).join(" AND ");
+            let found = BTreeSet::from_iter(
+                files
+                    .iter()
+                    .filter(|file| index.file_matches(file.id, &expression).unwrap())
+                    .map(|file| file.id),
+            );
+            prop_assert_eq!(&found, &expected, "expression: {}", expression);
+            Ok(())
+        })
+        .unwrap();
+}
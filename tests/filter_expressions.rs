@@ -0,0 +1,82 @@
+//! The Meilisearch filter expressions built from search filters, checked as strings. What the
+//! expressions match is checked against both indexes in `search_filters.rs`.
+
+use file_indexer::{
+    interfaces::files::{FileSearchQueryFilter, FileSearchQueryFilterOperator},
+    services::index_service::filters::{build_checksum_filter, build_file_filter},
+};
+
+fn tag(value: &str) -> FileSearchQueryFilter {
+    FileSearchQueryFilter::Tag {
+        value: value.to_owned(),
+    }
+}
+
+fn not_tag(value: &str) -> FileSearchQueryFilter {
+    FileSearchQueryFilter::NotTag {
+        value: value.to_owned(),
+    }
+}
+
+fn expression(filters: &[FileSearchQueryFilter]) -> String {
+    build_file_filter(filters).expect("no expression for a non-empty group")
+}
+
+#[test]
+fn negated_filters_negate_the_whole_comparison() {
+    assert_eq!(
+        expression(&[not_tag("archived")]),
+        "(NOT tags = 'archived')"
+    );
+    assert_eq!(
+        expression(&[FileSearchQueryFilter::NotMimeType {
+            value: "image/png".to_owned(),
+        }]),
+        "(NOT mime_type = 'image/png')"
+    );
+}
+
+#[test]
+fn negated_filters_bind_tighter_than_the_or_of_their_group() {
+    assert_eq!(
+        expression(&[not_tag("archived"), tag("invoice")]),
+        "(NOT tags = 'archived' OR tags = 'invoice')"
+    );
+    assert_eq!(
+        expression(&[
+            FileSearchQueryFilter::Size {
+                operator: FileSearchQueryFilterOperator::Lt,
+                value: 100,
+            },
+            FileSearchQueryFilter::NotMimeType {
+                value: "text/plain".to_owned(),
+            },
+        ]),
+        "(size < 100 OR NOT mime_type = 'text/plain')"
+    );
+}
+
+#[test]
+fn quotes_and_backslashes_are_escaped() {
+    assert_eq!(expression(&[tag("it's")]), r"(tags = 'it\'s')");
+    assert_eq!(expression(&[not_tag(r"a\b")]), r"(NOT tags = 'a\\b')");
+    // a trailing backslash must not escape the closing quote
+    assert_eq!(
+        expression(&[tag(r"x\' OR tags = 'y")]),
+        r"(tags = 'x\\\' OR tags = \'y')"
+    );
+}
+
+#[test]
+fn empty_groups_build_no_expression() {
+    assert_eq!(build_file_filter(&[]), None);
+}
+
+#[test]
+fn duplicates_are_counted_among_the_checksums_of_the_page() {
+    assert_eq!(
+        build_checksum_filter(&["9f86d0", "2c26b4"]),
+        "checksum IN ['9f86d0', '2c26b4']"
+    );
+    assert_eq!(build_checksum_filter(&["it's"]), r"checksum IN ['it\'s']");
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use chrono::{TimeDelta, Utc};
+use common::{file, TestApp};
+use file_indexer::{
+    interfaces::{
+        admins::{DeletedIndexDocument, IndexDocument},
+        files::FileWithIndexStatus,
+    },
+    services::index_service::{document_indexed_at, SearchIndex},
+    testing::search_index::InMemorySearchIndex,
+};
+use rocket::http::Status;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[rocket::async_test]
+async fn file_documents_record_when_they_were_indexed() {
+    let search_index = InMemorySearchIndex::new();
+    let file = file("dated.txt", 10, "text/plain", &[], "2024-01-10T00:00:00Z");
+    let indexed_at = |search_index: &InMemorySearchIndex| {
+        let document = search_index.file_document(file.id).unwrap();
+        document_indexed_at(document.as_object().unwrap()).unwrap()
+    };
+
+    let before = Utc::now() - TimeDelta::milliseconds(1);
+    search_index
+        .index_files(std::slice::from_ref(&file), &HashMap::new())
+        .await
+        .unwrap();
+    let first = indexed_at(&search_index);
+    assert!(before <= first && first <= Utc::now(), "{first}");
+
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    search_index
+        .index_files(std::slice::from_ref(&file), &HashMap::new())
+        .await
+        .unwrap();
+    assert!(indexed_at(&search_index) > first);
+
+    // documents indexed before they recorded it
+    assert_eq!(
+        document_indexed_at(serde_json::json!({ "id": file.id }).as_object().unwrap()),
+        None
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn file_documents_are_shown_and_deleted() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let search_index = app.search_index.as_ref().unwrap();
+    let file = app
+        .create_uploaded_file("ghost.txt", b"only in the index soon", &["ghost"])
+        .await;
+    let uri = format!("/admin-tasks/index-documents/files/{}", file.id);
+
+    let document: IndexDocument = app.get(&uri).await;
+    assert_eq!(document.index_uid, "file-indexer-files");
+    assert!(document.indexed_at.is_some());
+    assert_eq!(
+        serde_json::Value::Object(document.document),
+        search_index.file_document(file.id).unwrap()
+    );
+
+    let missing_uri = format!("/admin-tasks/index-documents/files/{}", Uuid::new_v4());
+    assert_eq!(app.get_for_status(&missing_uri).await, Status::NotFound);
+    assert_eq!(app.delete_for_status(&missing_uri).await, Status::NotFound);
+
+    let _: DeletedIndexDocument = app.delete(&uri).await;
+    assert!(search_index.file_document(file.id).is_none());
+    assert_eq!(app.get_for_status(&uri).await, Status::NotFound);
+    assert_eq!(app.delete_for_status(&uri).await, Status::NotFound);
+    // the file itself stays
+    assert_eq!(
+        app.get_for_status(&format!("/files/{}", file.id)).await,
+        Status::Ok
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn collection_documents_are_shown_and_deleted() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let collection = app.create_collection("Ghosts", &["ghost"]).await;
+    let uri = format!("/admin-tasks/index-documents/collections/{}", collection.id);
+
+    let document: IndexDocument = app.get(&uri).await;
+    assert_eq!(document.index_uid, "file-indexer-collections");
+    assert_eq!(document.document["name"], "Ghosts");
+
+    let missing_uri = format!(
+        "/admin-tasks/index-documents/collections/{}",
+        Uuid::new_v4()
+    );
+    assert_eq!(app.get_for_status(&missing_uri).await, Status::NotFound);
+
+    let _: DeletedIndexDocument = app.delete(&uri).await;
+    assert_eq!(app.get_for_status(&uri).await, Status::NotFound);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn files_report_their_index_status_on_request() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let search_index = app.search_index.as_ref().unwrap();
+    let file = app
+        .create_uploaded_file("status.txt", b"indexed on upload", &[])
+        .await;
+    let uri = format!("/files/{}?include-index-status=true", file.id);
+
+    let fetched: FileWithIndexStatus = app.get(&uri).await;
+    assert_eq!(fetched.file.id, file.id);
+    let index_status = fetched.index_status.unwrap();
+    assert!(index_status.indexed);
+    let indexed_at = index_status.indexed_at.unwrap();
+
+    // a document written long ago, as if later updates had not been indexed
+    let mut document = search_index.file_document(file.id).unwrap();
+    let stale_indexed_at = indexed_at - TimeDelta::days(1);
+    document["indexed_at"] = stale_indexed_at.timestamp_millis().into();
+    search_index.insert_file_document(document);
+    let fetched: FileWithIndexStatus = app.get(&uri).await;
+    assert_eq!(
+        fetched.index_status.unwrap().indexed_at,
+        Some(stale_indexed_at)
+    );
+
+    let _: DeletedIndexDocument = app
+        .delete(&format!("/admin-tasks/index-documents/files/{}", file.id))
+        .await;
+    let fetched: FileWithIndexStatus = app.get(&uri).await;
+    let index_status = fetched.index_status.unwrap();
+    assert!(!index_status.indexed);
+    assert_eq!(index_status.indexed_at, None);
+
+    // without the flag the file is answered as usual
+    let fetched: serde_json::Value = app.get(&format!("/files/{}", file.id)).await;
+    assert!(fetched.get("indexStatus").is_none());
+}
@@ -0,0 +1,194 @@
+//! The expected index schema in `db::search_engine` is the one source of truth for creating the
+//! indexes, verifying them on startup, and building the filters searches send.
+
+mod common;
+
+use file_indexer::{
+    config::SearchEngineConfig,
+    db::search_engine::{
+        IndexSchema, SearchEngine, SearchEngineError, COLLECTIONS_INDEX_SCHEMA, FILES_INDEX_SCHEMA,
+    },
+    interfaces::{
+        files::{FileSearchQuery, FileSearchQueryFilter, FILE_SEARCH_OPTIONAL_FIELDS},
+        fixtures, SearchAttribute,
+    },
+    services::index_service::filters::{build_file_filter, build_file_filters},
+    testing::search_index::InMemorySearchIndex,
+};
+use meilisearch_sdk::{client::Client, settings::PaginationSetting};
+use serde_json::json;
+use uuid::Uuid;
+
+fn config(url: &str, check_only: bool) -> SearchEngineConfig {
+    SearchEngineConfig {
+        url: url.to_owned(),
+        api_key: None,
+        check_only,
+    }
+}
+
+#[test]
+fn searchable_attributes_of_the_api_are_searchable_in_both_indexes() {
+    for attribute in [SearchAttribute::Name, SearchAttribute::Tags] {
+        for schema in [&FILES_INDEX_SCHEMA, &COLLECTIONS_INDEX_SCHEMA] {
+            assert!(
+                schema.searchable_attributes.contains(&attribute.to_str()),
+                "{} cannot search {}",
+                schema.uid,
+                attribute.to_str()
+            );
+        }
+    }
+
+    for field in FILE_SEARCH_OPTIONAL_FIELDS {
+        assert!(FILES_INDEX_SCHEMA.filterable_attributes.contains(field));
+    }
+}
+
+/// The in-memory index refuses expressions naming attributes the schema does not make
+/// filterable, as Meilisearch does.
+#[test]
+fn every_filter_names_a_filterable_attribute() {
+    let index = InMemorySearchIndex::new();
+    let (_, files) = fixtures::groups()
+        .into_iter()
+        .find(|(group, _)| *group == "files")
+        .unwrap();
+
+    for fixture in files
+        .iter()
+        .filter(|fixture| fixture.name.starts_with("FileSearchQueryFilter::"))
+    {
+        let filter: FileSearchQueryFilter = serde_json::from_value(fixture.json.clone()).unwrap();
+        let expression = build_file_filter(&[filter]).unwrap();
+
+        assert!(
+            index.file_matches(Uuid::nil(), &expression).is_ok(),
+            "{} builds `{expression}`",
+            fixture.name
+        );
+    }
+
+    let q: FileSearchQuery = serde_json::from_value(json!({ "q": "" })).unwrap();
+    for expression in build_file_filters(&q) {
+        assert!(
+            index.file_matches(Uuid::nil(), &expression).is_ok(),
+            "`{expression}`"
+        );
+    }
+}
+
+async fn assert_matches_schema(client: &Client, schema: &IndexSchema) {
+    let index = client.index(schema.uid);
+    let mut filterable_attributes = index.get_filterable_attributes().await.unwrap();
+    filterable_attributes.sort();
+    let mut expected_filterable_attributes = schema.filterable_attributes.to_vec();
+    expected_filterable_attributes.sort();
+    let mut sortable_attributes = index.get_sortable_attributes().await.unwrap();
+    sortable_attributes.sort();
+    let mut expected_sortable_attributes = schema.sortable_attributes.to_vec();
+    expected_sortable_attributes.sort();
+
+    assert_eq!(
+        index.get_searchable_attributes().await.unwrap(),
+        schema.searchable_attributes,
+        "{}",
+        schema.uid
+    );
+    assert_eq!(
+        filterable_attributes, expected_filterable_attributes,
+        "{}",
+        schema.uid
+    );
+    assert_eq!(
+        sortable_attributes, expected_sortable_attributes,
+        "{}",
+        schema.uid
+    );
+    assert_eq!(
+        index.get_pagination().await.unwrap().max_total_hits,
+        schema.max_total_hits,
+        "{}",
+        schema.uid
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn created_indexes_pass_their_verification() {
+    let (_meilisearch, url) = common::start_meilisearch().await;
+
+    let client = SearchEngine::init(&config(&url, false))
+        .await
+        .expect("failed to create the indexes")
+        .into_client();
+    for schema in [&FILES_INDEX_SCHEMA, &COLLECTIONS_INDEX_SCHEMA] {
+        assert_matches_schema(&client, schema).await;
+    }
+
+    SearchEngine::init(&config(&url, true))
+        .await
+        .expect("created indexes failed their verification");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn drifted_indexes_are_refused_when_checking_and_repaired_otherwise() {
+    let (_meilisearch, url) = common::start_meilisearch().await;
+    let client = SearchEngine::init(&config(&url, false))
+        .await
+        .unwrap()
+        .into_client();
+    let index = client.index(FILES_INDEX_SCHEMA.uid);
+    for task in [
+        index
+            .set_filterable_attributes(["tags", "added_by_hand"])
+            .await
+            .unwrap(),
+        index
+            .set_searchable_attributes(["tags", "name"])
+            .await
+            .unwrap(),
+        index
+            .set_pagination(PaginationSetting {
+                max_total_hits: 1000,
+            })
+            .await
+            .unwrap(),
+    ] {
+        task.wait_for_completion(&client, None, None).await.unwrap();
+    }
+
+    let err = SearchEngine::init(&config(&url, true))
+        .await
+        .err()
+        .expect("a drifted index passed its verification");
+    let SearchEngineError::IndexSchemaMismatch {
+        index_uid,
+        mismatches,
+    } = err
+    else {
+        panic!("unexpected error: {err:?}");
+    };
+    assert_eq!(index_uid, FILES_INDEX_SCHEMA.uid);
+    for mismatch in ["searchable attributes", "uploaded_at", "max total hits"] {
+        assert!(mismatches.contains(mismatch), "{mismatches}");
+    }
+
+    SearchEngine::init(&config(&url, false))
+        .await
+        .expect("failed to repair the index");
+    SearchEngine::init(&config(&url, true))
+        .await
+        .expect("the repaired index failed its verification");
+
+    let filterable_attributes = index.get_filterable_attributes().await.unwrap();
+    assert!(filterable_attributes.contains(&"added_by_hand".to_owned()));
+    for attribute in FILES_INDEX_SCHEMA.filterable_attributes {
+        assert!(filterable_attributes.contains(&(*attribute).to_owned()));
+    }
+    assert_eq!(
+        index.get_searchable_attributes().await.unwrap(),
+        FILES_INDEX_SCHEMA.searchable_attributes
+    );
+}
@@ -0,0 +1,226 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::{
+    config::SearchEngineConfig,
+    interfaces::{
+        IndexSettings, RankingRule, SearchIndexSettings, TypoTolerance, UpdatingIndexSettings,
+    },
+    services::index_service::{MeilisearchIndex, SearchIndex},
+    testing::search_index::InMemorySearchIndex,
+};
+use rocket::http::Status;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+fn settings() -> SearchIndexSettings {
+    SearchIndexSettings {
+        synonyms: BTreeMap::new(),
+        stop_words: Vec::new(),
+        typo_tolerance: TypoTolerance {
+            enabled: true,
+            min_word_size_for_one_typo: 5,
+            min_word_size_for_two_typos: 9,
+        },
+        ranking_rules: RankingRule::ALL.to_vec(),
+    }
+}
+
+/// Settings differing from `base` in one setting each.
+fn changes(base: &SearchIndexSettings) -> Vec<(&'static str, SearchIndexSettings)> {
+    let mut ranking_rules = base.ranking_rules.clone();
+    ranking_rules.reverse();
+
+    vec![
+        (
+            "synonyms",
+            SearchIndexSettings {
+                synonyms: BTreeMap::from([
+                    ("invoice".to_owned(), vec!["bill".to_owned()]),
+                    ("bill".to_owned(), vec!["invoice".to_owned()]),
+                ]),
+                ..base.clone()
+            },
+        ),
+        (
+            "stop words",
+            SearchIndexSettings {
+                stop_words: vec!["a".to_owned(), "the".to_owned()],
+                ..base.clone()
+            },
+        ),
+        (
+            "typo tolerance",
+            SearchIndexSettings {
+                typo_tolerance: TypoTolerance {
+                    enabled: !base.typo_tolerance.enabled,
+                    ..base.typo_tolerance
+                },
+                ..base.clone()
+            },
+        ),
+        (
+            "typo word sizes",
+            SearchIndexSettings {
+                typo_tolerance: TypoTolerance {
+                    min_word_size_for_one_typo: 3,
+                    min_word_size_for_two_typos: 7,
+                    ..base.typo_tolerance
+                },
+                ..base.clone()
+            },
+        ),
+        (
+            "ranking rules",
+            SearchIndexSettings {
+                ranking_rules,
+                ..base.clone()
+            },
+        ),
+    ]
+}
+
+fn value(settings: &impl serde::Serialize) -> Value {
+    serde_json::to_value(settings).unwrap()
+}
+
+#[test]
+fn ranking_rules_must_list_every_rule_once() {
+    assert!(settings().is_valid());
+
+    let mut missing = settings();
+    missing.ranking_rules.pop();
+    assert!(!missing.is_valid());
+
+    let mut repeated = settings();
+    repeated.ranking_rules[0] = repeated.ranking_rules[1];
+    assert!(!repeated.is_valid());
+
+    let mut extra = settings();
+    extra.ranking_rules.push(RankingRule::Words);
+    assert!(!extra.is_valid());
+}
+
+#[test]
+fn words_must_not_be_blank() {
+    let mut stop_words = settings();
+    stop_words.stop_words = vec![" ".to_owned()];
+    assert!(!stop_words.is_valid());
+
+    let mut synonym = settings();
+    synonym.synonyms = BTreeMap::from([("".to_owned(), vec!["bill".to_owned()])]);
+    assert!(!synonym.is_valid());
+
+    let mut equivalent = settings();
+    equivalent.synonyms = BTreeMap::from([("invoice".to_owned(), vec!["\t".to_owned()])]);
+    assert!(!equivalent.is_valid());
+}
+
+#[test]
+fn two_typos_must_not_need_shorter_words_than_one() {
+    let mut typo_tolerance = settings();
+    typo_tolerance.typo_tolerance.min_word_size_for_one_typo = 8;
+    typo_tolerance.typo_tolerance.min_word_size_for_two_typos = 4;
+    assert!(!typo_tolerance.is_valid());
+
+    let updating = UpdatingIndexSettings {
+        files: None,
+        collections: Some(typo_tolerance),
+    };
+    assert!(!updating.is_valid());
+}
+
+#[rocket::async_test]
+async fn in_memory_index_round_trips_each_setting() {
+    check_round_trips(&InMemorySearchIndex::new()).await;
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn meilisearch_round_trips_each_setting() {
+    let (_meilisearch, url) = common::start_meilisearch().await;
+    let index = MeilisearchIndex::init(&SearchEngineConfig {
+        url,
+        api_key: None,
+        check_only: false,
+    })
+    .await
+    .expect("failed to initialize search engine module");
+
+    check_round_trips(&index).await;
+}
+
+/// Changes each setting of each index on its own, checking that it reads back as written and
+/// leaves the other index alone.
+async fn check_round_trips(index: &dyn SearchIndex) {
+    let initial = index.get_index_settings().await.unwrap();
+    assert!(initial.files.is_valid(), "{initial:?}");
+    assert!(initial.collections.is_valid(), "{initial:?}");
+
+    for (name, files) in changes(&initial.files) {
+        let updated = index
+            .update_index_settings(&UpdatingIndexSettings {
+                files: Some(files.clone()),
+                collections: None,
+            })
+            .await
+            .unwrap();
+        let read = index.get_index_settings().await.unwrap();
+
+        assert_eq!(value(&updated), value(&read), "files {name}");
+        assert_eq!(value(&read.files), value(&files), "files {name}");
+        assert_eq!(
+            value(&read.collections),
+            value(&initial.collections),
+            "files {name}"
+        );
+    }
+
+    let files = index.get_index_settings().await.unwrap().files;
+    for (name, collections) in changes(&initial.collections) {
+        index
+            .update_index_settings(&UpdatingIndexSettings {
+                files: None,
+                collections: Some(collections.clone()),
+            })
+            .await
+            .unwrap();
+        let read = index.get_index_settings().await.unwrap();
+
+        assert_eq!(
+            value(&read.collections),
+            value(&collections),
+            "collections {name}"
+        );
+        assert_eq!(value(&read.files), value(&files), "collections {name}");
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn index_settings_are_updated_through_the_api_only_when_valid() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let initial: IndexSettings = app.get("/admin-tasks/index-settings").await;
+
+    let mut invalid = value(&initial.files);
+    invalid["rankingRules"] = json!(["words", "words"]);
+    let (status, _) = app
+        .put_for_error("/admin-tasks/index-settings", &json!({ "files": invalid }))
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity);
+
+    let (_, files) = changes(&initial.files).remove(0);
+    let updated: IndexSettings = app
+        .put(
+            "/admin-tasks/index-settings",
+            &UpdatingIndexSettings {
+                files: Some(files.clone()),
+                collections: None,
+            },
+        )
+        .await;
+    assert_eq!(value(&updated.files), value(&files));
+
+    let read: IndexSettings = app.get("/admin-tasks/index-settings").await;
+    assert_eq!(value(&read), value(&updated));
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::TestApp;
+use std::time::{Duration, Instant};
+
+const WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+const WAIT_INTERVAL: Duration = Duration::from_millis(200);
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn sweep_deletes_documents_of_files_deleted_in_the_database() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let search_index = app.search_index.as_ref().unwrap();
+    let kept = app
+        .create_uploaded_file("kept.txt", b"still in the database", &[])
+        .await;
+    let deleted = app
+        .create_uploaded_file("deleted.txt", b"deleted by hand", &["incident"])
+        .await;
+    assert!(search_index.file_document(deleted.id).is_some());
+
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    sqlx::query("DELETE FROM file_tags WHERE file_id = $1")
+        .bind(deleted.id)
+        .execute(&db_pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM files WHERE id = $1")
+        .bind(deleted.id)
+        .execute(&db_pool)
+        .await
+        .unwrap();
+
+    let deadline = Instant::now() + WAIT_TIMEOUT;
+
+    while search_index.file_document(deleted.id).is_some() {
+        assert!(
+            Instant::now() < deadline,
+            "the sweep did not delete the document in time"
+        );
+        tokio::time::sleep(WAIT_INTERVAL).await;
+    }
+
+    assert!(search_index.file_document(kept.id).is_some());
+}
@@ -0,0 +1,191 @@
+//! Indexes file documents over the size cap against Meilisearch and the in-memory search index,
+//! which both must accept them truncated rather than fail on them.
+
+mod common;
+
+use common::{file, TestApp};
+use file_indexer::{
+    config::SearchEngineConfig,
+    interfaces::{
+        admins::{AdminTaskStatus, MeiliTaskStatus},
+        files::{File, FileCollection, FileSearchQuery, FileSearchQueryFilter},
+    },
+    services::index_service::{MeilisearchIndex, SearchIndex},
+    testing::search_index::InMemorySearchIndex,
+};
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use uuid::Uuid;
+
+const WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+const WAIT_INTERVAL: Duration = Duration::from_millis(100);
+/// The size cap of file documents.
+const FILE_DOCUMENT_MAX_BYTES: usize = 128 * 1024;
+
+/// Tags long and many enough for the document to be several times over the cap.
+fn huge_tags() -> Vec<String> {
+    Vec::from_iter((0..2000).map(|index| format!("tag-{index:04}-{}", "x".repeat(200))))
+}
+
+fn truncated_filter(value: bool) -> FileSearchQuery {
+    FileSearchQuery {
+        filters: vec![vec![FileSearchQueryFilter::TruncatedForIndex { value }]],
+        ..serde_json::from_value(json!({ "q": "" })).unwrap()
+    }
+}
+
+async fn wait_for_task(index: &dyn SearchIndex, task_uid: u32) {
+    let deadline = Instant::now() + WAIT_TIMEOUT;
+
+    loop {
+        let tasks = index.get_tasks(&[task_uid]).await.unwrap();
+
+        match tasks.first().map(|task| (task.status, &task.error)) {
+            Some((MeiliTaskStatus::Succeeded, _)) => return,
+            Some((MeiliTaskStatus::Failed, error)) => panic!("task {task_uid} failed: {error:?}"),
+            _ => {}
+        }
+
+        assert!(
+            Instant::now() < deadline,
+            "task {task_uid} did not finish in time"
+        );
+        tokio::time::sleep(WAIT_INTERVAL).await;
+    }
+}
+
+async fn search_files(index: &dyn SearchIndex, truncated: bool) -> Vec<File> {
+    let hits = index
+        .search_files(&truncated_filter(truncated))
+        .await
+        .unwrap();
+    assert_eq!(hits.skipped_hits, 0);
+    Vec::from_iter(hits.hits.into_iter().map(|hit| {
+        assert_eq!(hit.truncated_for_index, truncated, "{}", hit.file.name);
+        hit.file
+    }))
+}
+
+#[rocket::async_test]
+async fn in_memory_index_truncates_oversized_documents() {
+    let index = InMemorySearchIndex::new();
+    let huge = check_truncation(&index).await;
+
+    let document = index.file_document(huge).unwrap();
+    let document_len = serde_json::to_vec(&document).unwrap().len();
+    assert!(
+        document_len <= FILE_DOCUMENT_MAX_BYTES,
+        "{document_len} bytes"
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn meilisearch_truncates_oversized_documents() {
+    let (_meilisearch, url) = common::start_meilisearch().await;
+    let index = MeilisearchIndex::init(&SearchEngineConfig {
+        url,
+        api_key: None,
+        check_only: false,
+    })
+    .await
+    .expect("failed to initialize search engine module");
+
+    check_truncation(&index).await;
+}
+
+/// Indexes an oversized file along with a small one, returning the id of the oversized one.
+async fn check_truncation(index: &dyn SearchIndex) -> Uuid {
+    let tags = huge_tags();
+    let mut huge = file(
+        "huge.pdf",
+        100,
+        "application/pdf",
+        &[],
+        "2024-01-10T00:00:00Z",
+    );
+    huge.tags = tags.clone();
+    let small = file(
+        "small.pdf",
+        100,
+        "application/pdf",
+        &["work"],
+        "2024-01-10T00:00:00Z",
+    );
+    let collections = HashMap::from([(
+        huge.id,
+        Vec::from_iter((0..100).map(|index| FileCollection {
+            id: Uuid::new_v4(),
+            name: format!("Collection {index}"),
+        })),
+    )]);
+
+    let task_uid = index
+        .index_files(&[huge.clone(), small], &collections)
+        .await
+        .unwrap();
+    wait_for_task(index, task_uid).await;
+
+    let truncated = search_files(index, true).await;
+    assert_eq!(truncated.len(), 1);
+    let indexed_tags = truncated[0].tags.clone();
+    assert!(!indexed_tags.is_empty() && indexed_tags.len() < tags.len());
+    assert_eq!(
+        indexed_tags,
+        tags[..indexed_tags.len()],
+        "a prefix of the tags"
+    );
+
+    let untruncated = search_files(index, false).await;
+    assert_eq!(
+        Vec::from_iter(untruncated.iter().map(|file| file.name.as_str())),
+        ["small.pdf"]
+    );
+
+    // truncating the same file again keeps the same tags
+    let task_uid = index
+        .index_files(&[huge.clone()], &collections)
+        .await
+        .unwrap();
+    wait_for_task(index, task_uid).await;
+    assert_eq!(search_files(index, true).await[0].tags, indexed_tags);
+
+    huge.id
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn truncated_files_keep_every_tag_outside_the_index() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let huge = app.create_uploaded_file("huge.pdf", b"huge", &[]).await;
+    let mut tags = huge_tags();
+
+    // far more tags than a request may give a file
+    sqlx::query("INSERT INTO file_tags (file_id, tag) SELECT $1, UNNEST($2::text[])")
+        .bind(huge.id)
+        .bind(&tags)
+        .execute(&db_pool)
+        .await
+        .unwrap();
+
+    let re_index = app.re_index().await;
+    let task = app.wait_for_task(re_index.file_task.id).await;
+    assert_eq!(task.status, AdminTaskStatus::Completed, "{task:#?}");
+
+    let document = app
+        .search_index
+        .as_ref()
+        .unwrap()
+        .file_document(huge.id)
+        .unwrap();
+    assert_eq!(document["truncated_for_index"], true);
+    assert!(document["tags"].as_array().unwrap().len() < tags.len());
+
+    let file: File = app.get(&format!("/files/{}", huge.id)).await;
+    tags.sort();
+    assert_eq!(file.tags, tags);
+}
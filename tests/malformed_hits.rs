@@ -0,0 +1,112 @@
+mod common;
+
+use common::{file, TestApp};
+use file_indexer::{
+    interfaces::files::FileSearchQuery,
+    services::index_service::{IndexService, IndexTimeouts, SearchIndex},
+    testing::search_index::InMemorySearchIndex,
+};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use uuid::Uuid;
+
+fn query(q: &str) -> FileSearchQuery {
+    serde_json::from_value(serde_json::json!({ "q": q })).unwrap()
+}
+
+#[rocket::async_test]
+async fn malformed_documents_are_skipped_and_repaired_once() {
+    let search_index = Arc::new(InMemorySearchIndex::new());
+    let index_service = IndexService::new(
+        search_index.clone(),
+        IndexTimeouts {
+            search: Duration::from_secs(5),
+            index: Duration::from_secs(10),
+        },
+    );
+    let intact = file(
+        "notes-intact.txt",
+        10,
+        "text/plain",
+        &["notes"],
+        "2024-01-10T00:00:00Z",
+    );
+    search_index
+        .index_files(std::slice::from_ref(&intact), &HashMap::new())
+        .await
+        .unwrap();
+
+    // documents of an older shape, lacking `tags` and `uploaded_at`, one without a readable id
+    let legacy_id = Uuid::new_v4();
+    search_index.insert_file_document(serde_json::json!({
+        "id": legacy_id.to_string(),
+        "name": "notes-legacy.txt",
+        "size": 10,
+        "mime_type": "text/plain",
+        "is_ready": true,
+    }));
+    search_index.insert_file_document(serde_json::json!({
+        "id": Uuid::new_v4().to_string(),
+        "name": "notes-garbled.txt",
+        "size": "ten",
+        "mime_type": "text/plain",
+        "tags": [],
+        "uploaded_at": 0,
+        "is_ready": true,
+    }));
+
+    let hits = index_service.search_files(&query("notes")).await.unwrap();
+    assert_eq!(
+        Vec::from_iter(hits.hits.iter().map(|hit| hit.file.id)),
+        vec![intact.id]
+    );
+    assert_eq!(hits.skipped_hits, 2);
+    assert_eq!(hits.malformed_file_ids.len(), 2);
+    assert!(hits.malformed_file_ids.contains(&legacy_id));
+
+    let mut file_ids_to_repair = index_service.file_ids_to_repair(&hits);
+    file_ids_to_repair.sort();
+    let mut malformed_file_ids = hits.malformed_file_ids.clone();
+    malformed_file_ids.sort();
+    assert_eq!(file_ids_to_repair, malformed_file_ids);
+
+    // a second search finding them does not ask for another repair
+    let hits = index_service.search_files(&query("notes")).await.unwrap();
+    assert_eq!(hits.skipped_hits, 2);
+    assert!(index_service.file_ids_to_repair(&hits).is_empty());
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn searches_skip_malformed_documents_until_they_are_re_indexed() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let search_index = app.search_index.as_ref().unwrap();
+    let kept = app
+        .create_uploaded_file("ledger-kept.txt", b"kept as indexed", &["ledger"])
+        .await;
+    let legacy = app
+        .create_uploaded_file("ledger-legacy.txt", b"indexed long ago", &["ledger"])
+        .await;
+
+    let mut document = search_index.file_document(legacy.id).unwrap();
+    let fields = document.as_object_mut().unwrap();
+    fields.remove("tags");
+    fields.remove("uploaded_at");
+    search_index.insert_file_document(document);
+
+    let result = app.search_files_with_skipped("ledger").await;
+    assert_eq!(
+        Vec::from_iter(result.hits.iter().map(|hit| hit.file.id)),
+        vec![kept.id]
+    );
+    assert_eq!(result.skipped_hits, 1);
+
+    // the search hands the document to a retry-index task, which writes it anew
+    app.wait_for_search("ledger", |hits| {
+        hits.iter().any(|hit| hit.file.id == legacy.id)
+    })
+    .await;
+    assert_eq!(
+        app.search_files_with_skipped("ledger").await.skipped_hits,
+        0
+    );
+}
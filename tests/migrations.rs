@@ -0,0 +1,75 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::{
+    config::DatabaseConfig,
+    db::database::{Database, DatabaseError},
+    interfaces::admins::{Migration, MigrationStatus},
+};
+
+/// Records another checksum for the latest migration, as if its file had been edited since it
+/// was applied, answering its version.
+async fn edit_latest_migration(app: &TestApp) -> i64 {
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+
+    sqlx::query_scalar(
+        "UPDATE _sqlx_migrations
+SET checksum = '\\x00'
+WHERE version = (SELECT MAX(version) FROM _sqlx_migrations)
+RETURNING version",
+    )
+    .fetch_one(&db_pool)
+    .await
+    .unwrap()
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn an_edited_migration_is_listed_as_drifted() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    let migrations: Vec<Migration> = app.get("/admin-tasks/migrations").await;
+    assert!(!migrations.is_empty());
+    assert!(migrations
+        .iter()
+        .all(|migration| migration.status == MigrationStatus::Applied
+            && migration.checksum == migration.applied_checksum));
+
+    let version = edit_latest_migration(&app).await;
+
+    let migrations: Vec<Migration> = app.get("/admin-tasks/migrations").await;
+    for migration in &migrations {
+        match migration.version == version {
+            true => {
+                assert_eq!(migration.status, MigrationStatus::ChecksumMismatch);
+                assert_eq!(migration.applied_checksum.as_deref(), Some("00"));
+                assert_ne!(migration.checksum, migration.applied_checksum);
+            }
+            false => assert_eq!(migration.status, MigrationStatus::Applied),
+        }
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn an_edited_migration_stops_a_startup_that_only_checks_migrations() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let config = DatabaseConfig {
+        url: app.database_url.clone(),
+        read_url: None,
+        check_migrations_only: true,
+    };
+
+    Database::init(&config).await.unwrap();
+
+    let version = edit_latest_migration(&app).await;
+
+    match Database::init(&config).await {
+        Err(DatabaseError::MigrationsNotUpToDate(drifted)) => {
+            assert!(drifted.starts_with(&format!("{version} (")), "{drifted}");
+            assert!(drifted.ends_with("ChecksumMismatch"), "{drifted}");
+        }
+        Err(err) => panic!("unexpected error: {err:#?}"),
+        Ok(_) => panic!("started with an edited migration"),
+    }
+}
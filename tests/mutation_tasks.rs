@@ -0,0 +1,135 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::{
+    admins::{AdminTask, AdminTaskPreview, TaskKind},
+    collections::{Collection, CreatingCollection},
+    files::{File, UploadedParts},
+    SimpleOk, WithAdminTask,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+/// Gets the task of a mutation, checking that it records the mutation of the entity.
+async fn recorded_task(
+    app: &TestApp,
+    admin_task_id: Option<Uuid>,
+    name: TaskKind,
+    key: &str,
+    id: Uuid,
+) -> AdminTask {
+    let admin_task_id = admin_task_id.expect("the mutation has no admin task");
+    let task: AdminTask = app.get(&format!("/admin-tasks/{admin_task_id}")).await;
+
+    assert_eq!(task.name, name, "{task:#?}");
+    assert_eq!(task.metadata[key], json!(id), "{task:#?}");
+    task
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn file_mutations_answer_their_admin_tasks() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let content = b"uploaded content";
+    let file = app.create_file("report.pdf", content, &[]).await;
+    let other = app
+        .create_uploaded_file("other.pdf", b"other content", &[])
+        .await;
+
+    let upload = app.start_upload(file.id).await;
+    let parts = app.upload_parts(file.id, &upload, content).await;
+    let completed: WithAdminTask<File> = app
+        .post(
+            &TestApp::completion_uri(file.id, &upload.id),
+            &UploadedParts { parts },
+        )
+        .await;
+    let upload_task = recorded_task(
+        &app,
+        completed.admin_task_id,
+        TaskKind::UploadFile,
+        "file_id",
+        file.id,
+    )
+    .await;
+
+    let updated: WithAdminTask<File> = app
+        .patch(
+            &format!("/files/{}", file.id),
+            &json!({ "name": "final-report.pdf" }),
+        )
+        .await;
+    let update_task = recorded_task(
+        &app,
+        updated.admin_task_id,
+        TaskKind::UpdateFile,
+        "file_id",
+        file.id,
+    )
+    .await;
+
+    let tasks: Vec<AdminTaskPreview> = app.get(&format!("/files/{}/admin-tasks", file.id)).await;
+    let mut task_ids = Vec::from_iter(tasks.iter().map(|task| task.id));
+    task_ids.sort();
+    let mut expected = vec![upload_task.id, update_task.id];
+    expected.sort();
+    assert_eq!(task_ids, expected);
+
+    let tasks: Vec<AdminTaskPreview> = app.get(&format!("/files/{}/admin-tasks", other.id)).await;
+    assert!(tasks
+        .iter()
+        .all(|task| task.id != upload_task.id && task.id != update_task.id));
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn collection_mutations_answer_their_admin_tasks() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    let created: WithAdminTask<Collection> = app
+        .post(
+            "/collections",
+            &CreatingCollection {
+                name: "Reports".to_owned(),
+                unique_names: false,
+                tags: vec!["reports".to_owned()],
+            },
+        )
+        .await;
+    let collection_id = created.result.id;
+    recorded_task(
+        &app,
+        created.admin_task_id,
+        TaskKind::CreateCollection,
+        "collection_id",
+        collection_id,
+    )
+    .await;
+
+    let updated: WithAdminTask<Collection> = app
+        .patch(
+            &format!("/collections/{collection_id}"),
+            &json!({ "name": "Final reports" }),
+        )
+        .await;
+    recorded_task(
+        &app,
+        updated.admin_task_id,
+        TaskKind::UpdateCollection,
+        "collection_id",
+        collection_id,
+    )
+    .await;
+
+    let deleted: WithAdminTask<SimpleOk> =
+        app.delete(&format!("/collections/{collection_id}")).await;
+    assert!(deleted.result.ok);
+    recorded_task(
+        &app,
+        deleted.admin_task_id,
+        TaskKind::DeleteCollection,
+        "collection_id",
+        collection_id,
+    )
+    .await;
+}
@@ -0,0 +1,72 @@
+use file_indexer::db::repositories::name_sort_key;
+use uuid::Uuid;
+
+fn sorted(names: &[&str]) -> Vec<String> {
+    let mut names = Vec::from_iter(names.iter().map(|name| (*name).to_owned()));
+    names.sort_by_key(|name| name_sort_key(name));
+    names
+}
+
+#[test]
+fn digit_runs_sort_by_their_value() {
+    assert_eq!(
+        sorted(&["img10.png", "img2.png", "img1.png", "img100.png"]),
+        ["img1.png", "img2.png", "img10.png", "img100.png"]
+    );
+    assert_eq!(
+        sorted(&["v1.10.0", "v1.9.2", "v1.9.10"]),
+        ["v1.9.2", "v1.9.10", "v1.10.0"]
+    );
+    assert_eq!(name_sort_key("img007"), name_sort_key("img7"));
+    assert_eq!(name_sort_key("0"), name_sort_key("000"));
+    assert_eq!(name_sort_key("a1"), format!("a{}1", "0".repeat(19)));
+}
+
+#[test]
+fn digit_runs_sort_before_letters() {
+    assert_eq!(sorted(&["b", "10", "a", "9"]), ["9", "10", "a", "b"]);
+}
+
+#[test]
+fn runs_too_long_to_pad_are_kept_as_they_are() {
+    let digits = "123456789012345678901234";
+
+    assert_eq!(name_sort_key(digits), digits);
+    assert_eq!(name_sort_key(&format!("00{digits}")), digits);
+}
+
+#[test]
+fn letters_are_compared_regardless_of_case() {
+    assert_eq!(name_sort_key("README.md"), "readme.md");
+    assert_eq!(name_sort_key("Äpfel"), name_sort_key("äpfel"));
+    assert_eq!(name_sort_key("ÉTÉ"), "été");
+    assert_eq!(name_sort_key("ΣΟΦΙΑ"), name_sort_key("σοφια"));
+    assert_eq!(
+        sorted(&["Zebra", "apple", "Mango"]),
+        ["apple", "Mango", "Zebra"]
+    );
+}
+
+#[test]
+fn equal_keys_are_told_apart_by_id() {
+    let first = Uuid::from_u128(1);
+    let second = Uuid::from_u128(2);
+    let third = Uuid::from_u128(3);
+    let mut files = vec![
+        (second, "IMG02.png"),
+        (third, "img10.png"),
+        (first, "img2.png"),
+    ];
+
+    files.sort_by_key(|(id, name)| (name_sort_key(name), *id));
+
+    assert_eq!(name_sort_key("IMG02.png"), name_sort_key("img2.png"));
+    assert_eq!(
+        files,
+        [
+            (first, "img2.png"),
+            (second, "IMG02.png"),
+            (third, "img10.png")
+        ]
+    );
+}
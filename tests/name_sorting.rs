@@ -0,0 +1,77 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::files::File;
+use rocket::http::Status;
+
+fn names(files: &[File]) -> Vec<&str> {
+    Vec::from_iter(files.iter().map(|file| file.name.as_str()))
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn files_are_listed_in_natural_name_order() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    let mut shared_key = Vec::new();
+    for name in ["img10.png", "img2.png", "IMG02.png", "img1.png"] {
+        let file = app.create_uploaded_file(name, b"pixels", &["photos"]).await;
+
+        if name.ends_with("2.png") {
+            shared_key.push(file);
+        }
+    }
+    shared_key.sort_by_key(|file| file.id);
+    let expected = [
+        "img1.png",
+        shared_key[0].name.as_str(),
+        shared_key[1].name.as_str(),
+        "img10.png",
+    ];
+
+    let files: Vec<File> = app.get("/files?sort=name-natural").await;
+    assert_eq!(names(&files), expected);
+
+    let first_page: Vec<File> = app.get("/files?sort=name-natural&limit=2").await;
+    let last = first_page.last().unwrap();
+    let second_page: Vec<File> = app
+        .get(&format!(
+            "/files?sort=name-natural&limit=2&last-file-id={}&last-file-name={}",
+            last.id, last.name
+        ))
+        .await;
+    assert_eq!(names(&first_page), expected[..2]);
+    assert_eq!(names(&second_page), expected[2..]);
+
+    let collection = app.create_collection("photos", &["photos"]).await;
+    let files: Vec<File> = app
+        .get(&format!(
+            "/collections/{}/files?sort=name-natural",
+            collection.id
+        ))
+        .await;
+    assert_eq!(names(&files), expected);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn name_cursors_are_only_allowed_with_name_sorting() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app.create_uploaded_file("img1.png", b"pixels", &[]).await;
+
+    let status = app
+        .get_for_status(&format!(
+            "/files?last-file-id={}&last-file-name={}",
+            file.id, file.name
+        ))
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity);
+
+    let status = app
+        .get_for_status(&format!(
+            "/files?sort=name-natural&last-file-id={}",
+            file.id
+        ))
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity);
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use common::TestApp;
+use serde_json::Value;
+use std::path::Path;
+
+/// The committed copy of the document, for client teams to generate clients from.
+const SPEC_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+
+/// Fails when the served document differs from the committed one. Run with
+/// `UPDATE_OPENAPI_SPEC=1` to rewrite the committed copy instead.
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn served_spec_matches_the_committed_copy() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let served: Value = app.get("/openapi.json").await;
+
+    if std::env::var_os("UPDATE_OPENAPI_SPEC").is_some() {
+        let spec = serde_json::to_string_pretty(&served).unwrap() + "\n";
+        std::fs::write(SPEC_PATH, spec).unwrap();
+        return;
+    }
+
+    let committed = std::fs::read_to_string(Path::new(SPEC_PATH))
+        .expect("openapi.json is missing; run with UPDATE_OPENAPI_SPEC=1 to write it");
+    let committed: Value = serde_json::from_str(&committed).unwrap();
+    assert!(
+        served == committed,
+        "the served spec differs from openapi.json; run with UPDATE_OPENAPI_SPEC=1 to update it"
+    );
+}
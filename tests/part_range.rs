@@ -0,0 +1,36 @@
+use file_indexer::forms::part_range::PartRangeFormField;
+use rocket::form::{FromFormField, ValueField};
+
+fn parse(value: &str) -> Option<PartRangeFormField> {
+    PartRangeFormField::from_value(ValueField::from_value(value)).ok()
+}
+
+#[test]
+fn ranges_are_parsed_inclusively() {
+    assert_eq!(
+        parse("1-1000"),
+        Some(PartRangeFormField {
+            first: 1,
+            last: 1000
+        })
+    );
+    assert_eq!(parse("7-7"), Some(PartRangeFormField { first: 7, last: 7 }));
+    assert_eq!(
+        parse(" 2 - 5 "),
+        Some(PartRangeFormField { first: 2, last: 5 })
+    );
+}
+
+#[test]
+fn part_numbers_start_at_one_and_must_not_be_reversed() {
+    for value in ["0-5", "0-0", "5-4"] {
+        assert_eq!(parse(value), None, "{value}");
+    }
+}
+
+#[test]
+fn malformed_ranges_are_refused() {
+    for value in ["", "5", "-5", "5-", "a-b", "1-2-3", "-1-5", "1-4294967296"] {
+        assert_eq!(parse(value), None, "{value}");
+    }
+}
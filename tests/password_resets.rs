@@ -0,0 +1,160 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::admins::{AdminLogin, RequestingPasswordReset, ResettingPassword};
+use rocket::http::{ContentType, Status};
+
+const RESET_URL: &str = "https://files.example.com/password-resets/";
+
+async fn spawn() -> TestApp {
+    TestApp::spawn_with_in_memory_stores_and_env(&[(
+        "PASSWORD_RESET_URL",
+        &format!("{RESET_URL}{{token}}"),
+    )])
+    .await
+}
+
+/// Requests a password reset without a session, answering the status and body.
+async fn request_reset(app: &TestApp, email: &str) -> (Status, String) {
+    let response = app
+        .client
+        .post("/admins/password-resets")
+        .header(ContentType::JSON)
+        .json(&RequestingPasswordReset {
+            email: email.to_owned(),
+        })
+        .dispatch()
+        .await;
+    let status = response.status();
+
+    (status, response.into_string().await.unwrap_or_default())
+}
+
+/// Requests a password reset for the email and answers the token of the mailed link.
+async fn mailed_token(app: &TestApp, email: &str) -> String {
+    let before = app.mailer.mails().len();
+    let (status, _) = request_reset(app, email).await;
+    assert_eq!(status, Status::Ok);
+
+    let mail = app.wait_for_mail(email).await;
+    assert!(app.mailer.mails().len() > before, "no new mail was sent");
+
+    mail.body
+        .lines()
+        .find_map(|line| line.strip_prefix(RESET_URL))
+        .unwrap_or_else(|| panic!("the mail has no reset link: {}", mail.body))
+        .to_owned()
+}
+
+async fn reset_password(app: &TestApp, token: &str, password: &str) -> Status {
+    app.post_for_status_as(
+        None,
+        &format!("/admins/password-resets/{token}"),
+        &ResettingPassword {
+            password: password.to_owned(),
+        },
+    )
+    .await
+}
+
+async fn log_in(app: &TestApp, password: &str) -> Status {
+    app.post_for_status_as(
+        None,
+        "/admins/sessions",
+        &AdminLogin {
+            username: "admin".to_owned(),
+            password: password.to_owned(),
+        },
+    )
+    .await
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn an_unknown_email_is_answered_like_a_known_one() {
+    let app = spawn().await;
+
+    let known = request_reset(&app, "admin@example.com").await;
+    let unknown = request_reset(&app, "nobody@example.com").await;
+
+    assert_eq!(known.0, Status::Ok);
+    assert_eq!(known, unknown);
+
+    app.wait_for_mail("admin@example.com").await;
+    assert!(app
+        .mailer
+        .mails()
+        .iter()
+        .all(|mail| mail.to == "admin@example.com"));
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_reset_sets_the_password_and_revokes_the_sessions() {
+    let app = spawn().await;
+    let token = mailed_token(&app, "admin@example.com").await;
+
+    assert_eq!(
+        reset_password(&app, &token, "new-admin-password").await,
+        Status::Ok
+    );
+
+    assert_eq!(
+        app.get_for_status_as(Some(&app.token), "/admins/me").await,
+        Status::Unauthorized
+    );
+    assert_eq!(log_in(&app, "admin-password").await, Status::Unauthorized);
+    assert_eq!(log_in(&app, "new-admin-password").await, Status::Ok);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_reset_token_is_used_only_once() {
+    let app = spawn().await;
+    let token = mailed_token(&app, "admin@example.com").await;
+
+    assert_eq!(
+        reset_password(&app, &token, "new-admin-password").await,
+        Status::Ok
+    );
+    assert_eq!(
+        reset_password(&app, &token, "another-password").await,
+        Status::NotFound
+    );
+
+    assert_eq!(log_in(&app, "new-admin-password").await, Status::Ok);
+    assert_eq!(log_in(&app, "another-password").await, Status::Unauthorized);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn an_expired_reset_token_is_refused() {
+    let app = spawn().await;
+    let token = mailed_token(&app, "admin@example.com").await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+
+    sqlx::query(
+        "UPDATE admin_password_resets
+SET expired_at = CURRENT_TIMESTAMP - INTERVAL '1 second'",
+    )
+    .execute(&db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!(
+        reset_password(&app, &token, "new-admin-password").await,
+        Status::NotFound
+    );
+    assert_eq!(log_in(&app, "admin-password").await, Status::Ok);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn an_unknown_reset_token_is_refused() {
+    let app = spawn().await;
+
+    assert_eq!(
+        reset_password(&app, "not-a-token", "new-admin-password").await,
+        Status::NotFound
+    );
+}
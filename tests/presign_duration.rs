@@ -0,0 +1,34 @@
+use aws_sdk_s3::presigning::PresigningConfig;
+use file_indexer::{config::MAX_PRESIGN_DURATION, services::s3_service::check_presign_duration};
+use std::time::Duration;
+
+#[test]
+fn presign_durations_are_checked_against_the_bounds_of_s3() {
+    let cases = [
+        (Duration::ZERO, false),
+        (Duration::from_millis(999), false),
+        (Duration::from_secs(1), true),
+        (Duration::from_secs(60 * 60 * 24 * 7), true),
+        (Duration::from_secs(60 * 60 * 24 * 7 + 1), false),
+    ];
+
+    for (expires_in, valid) in cases {
+        let checked = check_presign_duration(expires_in);
+        assert_eq!(checked.is_ok(), valid, "{expires_in:?}");
+
+        if let Err(err) = checked {
+            assert_eq!(err.0, expires_in);
+        }
+    }
+}
+
+#[test]
+fn presign_durations_that_pass_the_check_are_accepted_by_the_sdk() {
+    for expires_in in [Duration::from_secs(1), MAX_PRESIGN_DURATION] {
+        check_presign_duration(expires_in).unwrap();
+        PresigningConfig::expires_in(expires_in)
+            .unwrap_or_else(|err| panic!("{expires_in:?} refused: {err}"));
+    }
+
+    assert!(PresigningConfig::expires_in(MAX_PRESIGN_DURATION + Duration::from_secs(1)).is_err());
+}
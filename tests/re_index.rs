@@ -0,0 +1,185 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::{
+    db::repositories::file::FileRepository,
+    fairings::task_runner::{
+        re_index_files::{ReIndexFilesHandler, ReIndexFilesMetadata},
+        TaskHandler, TaskStep,
+    },
+    interfaces::{
+        admins::{AdminTaskInitiator, AdminTaskStatus, TaskKind},
+        files::FileStorage,
+    },
+    services::{
+        admin_task_service::AdminTaskService,
+        file_service::FileService,
+        index_service::{IndexService, IndexTimeouts},
+    },
+};
+use rocket::http::Status;
+use serde_json::json;
+use std::time::Duration;
+use uuid::Uuid;
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn re_index_restores_files_and_collections() {
+    let app = TestApp::spawn().await;
+    let content = b"indexed again from the database";
+
+    let collection = app.create_collection("Invoices", &["invoices"]).await;
+    let file = app
+        .create_uploaded_file("invoice-2024.pdf", content, &["invoices"])
+        .await;
+    app.wait_for_search("invoice", |hits| {
+        hits.iter().any(|hit| hit.file.id == file.id)
+    })
+    .await;
+
+    let task = app.re_index().await;
+    let file_task = app.wait_for_task(task.file_task.id).await;
+    assert_eq!(
+        file_task.status,
+        AdminTaskStatus::Completed,
+        "{file_task:#?}"
+    );
+    let collection_task = app.wait_for_task(task.collection_task.id).await;
+    assert_eq!(
+        collection_task.status,
+        AdminTaskStatus::Completed,
+        "{collection_task:#?}"
+    );
+
+    // the tasks clear the indexes first, so the file is only found if it was indexed again
+    let hits = app
+        .wait_for_search("invoice", |hits| {
+            hits.iter().any(|hit| hit.file.id == file.id)
+        })
+        .await;
+    let hit = hits.iter().find(|hit| hit.file.id == file.id).unwrap();
+    assert!(hit
+        .collections
+        .iter()
+        .any(|hit_collection| hit_collection.id == collection.id));
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn an_upload_completed_during_a_re_index_is_indexed_exactly_once() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let search_index = app.search_index.clone().unwrap();
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let handler = ReIndexFilesHandler::new(
+        FileService::new(
+            FileRepository::new(db_pool.clone(), db_pool),
+            FileStorage {
+                bucket: "file-indexer".to_owned(),
+                region: "us-east-1".to_owned(),
+            },
+        ),
+        IndexService::new(
+            search_index.clone(),
+            IndexTimeouts {
+                search: Duration::from_secs(5),
+                index: Duration::from_secs(10),
+            },
+        ),
+    );
+
+    let first = app.create_uploaded_file("report-1.txt", b"1", &[]).await;
+    let second = app.create_uploaded_file("report-2.txt", b"2", &[]).await;
+    let content = b"completed while the files are re-indexed";
+    let late = app.create_file("late-report.txt", content, &[]).await;
+    let upload = app.start_upload(late.id).await;
+    let parts = app.upload_parts(late.id, &upload, content).await;
+
+    // the steps are run by hand, so that the upload completes between two of them
+    let task_id = Uuid::new_v4();
+    let mut metadata: ReIndexFilesMetadata =
+        serde_json::from_value(json!({ "index_cleared": false })).unwrap();
+    assert_eq!(
+        handler.step(task_id, &mut metadata).await.unwrap(),
+        TaskStep::NotCompleted
+    );
+    assert!(search_index.file_ids().is_empty());
+    assert_eq!(
+        handler.step(task_id, &mut metadata).await.unwrap(),
+        TaskStep::NotCompleted
+    );
+
+    app.complete_upload(late.id, &upload.id, parts).await;
+    assert_eq!(
+        handler.step(task_id, &mut metadata).await.unwrap(),
+        TaskStep::Completed
+    );
+
+    let mut expected = vec![first.id, second.id, late.id];
+    expected.sort_unstable();
+    assert_eq!(search_index.file_ids(), expected);
+
+    let hits = app.search_files("late-report").await;
+    assert_eq!(
+        Vec::from_iter(hits.iter().map(|hit| hit.file.id)),
+        [late.id]
+    );
+
+    // the re-index passed the file while it was unready, so only its completion indexed it
+    let metadata = serde_json::to_value(&metadata).unwrap();
+    assert_eq!(metadata["indexed_count"], json!(2), "{metadata}");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_re_index_that_fails_to_enqueue_leaves_the_index_alone() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let search_index = app.search_index.clone().unwrap();
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let admin_task_service = AdminTaskService::new(db_pool.clone());
+    let file = app
+        .create_uploaded_file("report.txt", b"indexed", &[])
+        .await;
+
+    // a collections task that is queued already fails the second enqueue; it waits on a task no
+    // handler runs, so the task runner of the app leaves it queued
+    let blocker = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::System,
+            None,
+            TaskKind::UpdateFile,
+            json!({}),
+            None,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+    let queued = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::System,
+            None,
+            TaskKind::ReIndexCollections,
+            json!({}),
+            None,
+            false,
+            Some(blocker.id),
+        )
+        .await
+        .unwrap();
+
+    let (status, error) = app.post_for_error("/admin-tasks/re-index", &()).await;
+    assert_eq!(status, Status::Conflict);
+    assert_eq!(error["taskId"], json!(queued.id));
+
+    let file_task_statuses: Vec<AdminTaskStatus> =
+        sqlx::query_scalar("SELECT status FROM admin_tasks WHERE name = $1")
+            .bind(TaskKind::ReIndexFiles.as_str())
+            .fetch_all(&db_pool)
+            .await
+            .unwrap();
+    assert_eq!(file_task_statuses, [AdminTaskStatus::Canceled]);
+
+    // the task runner had the time to pick the canceled task up, had it been left queued
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(search_index.file_ids(), [file.id]);
+}
@@ -0,0 +1,118 @@
+//! Runs the same reads and writes with and without a read replica. The replica is stood in for
+//! by read-only sessions on the same database, so any write routed to it fails the test.
+
+mod common;
+
+use common::{TestApp, READ_REPLICA_APPLICATION_NAME};
+use file_indexer::interfaces::{
+    admins::AdminTaskStatus,
+    collections::Collection,
+    files::{File, FileUploadRecord},
+    tags::{RenamingTag, TagRenamePreview},
+    WithAdminTask,
+};
+use serde_json::json;
+
+fn names(files: &[File]) -> Vec<&str> {
+    Vec::from_iter(files.iter().map(|file| file.name.as_str()))
+}
+
+async fn check_reads_and_writes(app: &TestApp) {
+    let collection = app.create_collection("Invoices", &["invoices"]).await;
+    let mut files = Vec::new();
+
+    for name in ["march.pdf", "april.pdf", "may.pdf"] {
+        files.push(
+            app.create_uploaded_file(name, name.as_bytes(), &["invoices"])
+                .await,
+        );
+    }
+
+    let listed: Vec<File> = app.get("/files?sort=name-natural").await;
+    assert_eq!(names(&listed), ["april.pdf", "march.pdf", "may.pdf"]);
+    let page: Vec<File> = app
+        .get(&format!(
+            "/files?sort=name-natural&limit=1&last-file-id={}&last-file-name=april.pdf",
+            listed[0].id
+        ))
+        .await;
+    assert_eq!(names(&page), ["march.pdf"]);
+
+    let collections: Vec<Collection> = app.get("/collections").await;
+    assert_eq!(collections.len(), 1);
+    let members: Vec<File> = app
+        .get(&format!("/collections/{}/files", collection.id))
+        .await;
+    assert_eq!(members.len(), 3);
+
+    let uploads: Vec<FileUploadRecord> = app.get(&format!("/files/{}/uploads", files[0].id)).await;
+    assert_eq!(uploads.len(), 1);
+
+    let preview: WithAdminTask<TagRenamePreview> = app
+        .post(
+            "/tags/invoices/rename?dry-run=true",
+            &RenamingTag {
+                new_name: "bills".to_owned(),
+            },
+        )
+        .await;
+    assert_eq!(preview.result.files.renamed_count, 3);
+
+    // a write is read back at once
+    app.patch::<File>(
+        &format!("/files/{}", files[0].id),
+        &json!({ "name": "june.pdf" }),
+    )
+    .await;
+    let file: File = app.get(&format!("/files/{}", files[0].id)).await;
+    assert_eq!(file.name, "june.pdf");
+    let listed: Vec<File> = app.get("/files?sort=name-natural").await;
+    assert_eq!(names(&listed), ["april.pdf", "june.pdf", "may.pdf"]);
+
+    // the scans of background tasks read from the replica and write their progress to the primary
+    let re_index = app.re_index().await;
+
+    for task_id in [re_index.file_task.id, re_index.collection_task.id] {
+        let task = app.wait_for_task(task_id).await;
+        assert_eq!(task.status, AdminTaskStatus::Completed, "{task:#?}");
+    }
+
+    let search_index = app.search_index.as_ref().unwrap();
+    assert_eq!(search_index.file_ids().len(), 3);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_single_pool_serves_reads_and_writes() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    assert_eq!(app.read_database_url, None);
+
+    check_reads_and_writes(&app).await;
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn reads_are_served_by_the_replica() {
+    let app = TestApp::spawn_with_read_replica().await;
+
+    check_reads_and_writes(&app).await;
+
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let replica_sessions: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM pg_stat_activity WHERE application_name = $1")
+            .bind(READ_REPLICA_APPLICATION_NAME)
+            .fetch_one(&db_pool)
+            .await
+            .unwrap();
+    assert!(0 < replica_sessions, "no read went to the replica");
+
+    // the stand-in refuses writes, as a replica would
+    let replica_pool = sqlx::PgPool::connect(app.read_database_url.as_deref().unwrap())
+        .await
+        .unwrap();
+    let err = sqlx::query("UPDATE files SET name = name")
+        .execute(&replica_pool)
+        .await
+        .expect_err("the replica took a write");
+    assert!(err.to_string().contains("read-only"), "{err}");
+}
@@ -0,0 +1,130 @@
+use file_indexer::{
+    interfaces::{files::CreatingFile, tags::validate_tags},
+    services::index_service::{bisect_rejected, BatchOutcome, Bisection},
+};
+use std::{cell::Cell, convert::Infallible};
+
+/// Bisects `documents`, rejecting every batch holding a document `is_bad` says is bad. Returns the
+/// bisection, with the accepted batches as they were handed over, and the number of calls made.
+async fn bisect<'a>(
+    documents: &'a [u32],
+    is_bad: impl Fn(u32) -> bool,
+) -> (Bisection<'a, u32, Vec<u32>, String>, usize) {
+    let calls = Cell::new(0);
+    let bisection = bisect_rejected(documents, |batch: &[u32]| {
+        calls.set(calls.get() + 1);
+        let outcome = match batch.iter().find(|document| is_bad(**document)) {
+            Some(document) => BatchOutcome::Rejected(format!("document {document} is invalid")),
+            None => BatchOutcome::Accepted(batch.to_vec()),
+        };
+
+        async move { Ok::<_, Infallible>(outcome) }
+    })
+    .await
+    .unwrap();
+
+    (bisection, calls.get())
+}
+
+fn rejected(bisection: &Bisection<'_, u32, Vec<u32>, String>) -> Vec<u32> {
+    Vec::from_iter(bisection.rejected.iter().map(|(document, _)| **document))
+}
+
+fn accepted(bisection: &Bisection<'_, u32, Vec<u32>, String>) -> Vec<u32> {
+    Vec::from_iter(bisection.accepted.iter().flatten().copied())
+}
+
+#[rocket::async_test]
+async fn batches_without_bad_documents_are_handed_over_once() {
+    let documents = Vec::from_iter(0..100);
+    let (bisection, calls) = bisect(&documents, |_| false).await;
+
+    assert_eq!(calls, 1);
+    assert_eq!(bisection.accepted, vec![documents.clone()]);
+    assert!(bisection.rejected.is_empty());
+
+    let (bisection, calls) = bisect(&[], |_| true).await;
+
+    assert_eq!(calls, 0);
+    assert!(bisection.accepted.is_empty());
+    assert!(bisection.rejected.is_empty());
+}
+
+#[rocket::async_test]
+async fn bad_documents_are_isolated_and_the_others_accepted() {
+    let documents = Vec::from_iter(0..1000);
+    let (bisection, calls) = bisect(&documents, |document| document == 617).await;
+
+    assert_eq!(rejected(&bisection), vec![617]);
+    assert_eq!(bisection.rejected[0].1, "document 617 is invalid");
+    assert_eq!(
+        accepted(&bisection),
+        Vec::from_iter((0..1000).filter(|document| *document != 617))
+    );
+    // two calls per halving of the batch
+    assert!(calls <= 2 * 10 + 1, "{calls} calls");
+
+    let (bisection, _) = bisect(&documents, |document| [3, 4, 500, 999].contains(&document)).await;
+
+    assert_eq!(rejected(&bisection), vec![3, 4, 500, 999]);
+    assert_eq!(
+        accepted(&bisection),
+        Vec::from_iter((0..1000).filter(|document| ![3, 4, 500, 999].contains(document)))
+    );
+}
+
+#[rocket::async_test]
+async fn every_document_may_be_rejected() {
+    let documents = Vec::from_iter(0..7);
+    let (bisection, _) = bisect(&documents, |_| true).await;
+
+    assert_eq!(rejected(&bisection), documents);
+    assert!(bisection.accepted.is_empty());
+}
+
+#[rocket::async_test]
+async fn errors_stop_the_bisection() {
+    let documents = Vec::from_iter(0..10);
+    let calls = Cell::new(0);
+    let result = bisect_rejected(&documents, |batch: &[u32]| {
+        calls.set(calls.get() + 1);
+        // the first half is rejected, then the search index goes away
+        let result: Result<BatchOutcome<(), ()>, _> = match batch.len() {
+            10 => Ok(BatchOutcome::Rejected(())),
+            _ => Err("meilisearch is unavailable"),
+        };
+
+        async move { result }
+    })
+    .await;
+
+    assert_eq!(result.err(), Some("meilisearch is unavailable"));
+    assert_eq!(calls.get(), 2);
+}
+
+#[test]
+fn tags_that_cannot_be_indexed_are_refused() {
+    let tags = vec![
+        "invoices".to_owned(),
+        "line\nbreak".to_owned(),
+        "a".repeat(10 * 1024),
+        "  ".to_owned(),
+        "tab\there".to_owned(),
+    ];
+    let fields = Vec::from_iter(
+        validate_tags("tags", &tags)
+            .into_iter()
+            .map(|error| error.field),
+    );
+
+    assert_eq!(fields, vec!["tags[1]", "tags[2]", "tags[3]", "tags[4]"]);
+    assert!(validate_tags("tags", &["2024 taxes".to_owned(), "요약".to_owned()]).is_empty());
+}
+
+#[test]
+fn lone_surrogates_are_refused_when_parsing() {
+    let body =
+        r#"{"name": "report.pdf", "size": 1, "mimeType": "application/pdf", "tags": ["\ud800"]}"#;
+
+    assert!(serde_json::from_str::<CreatingFile>(body).is_err());
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::admins::AdminRole;
+use rocket::http::Status;
+use serde_json::json;
+use uuid::Uuid;
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn exports_and_download_urls_need_a_session_of_any_role() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let viewer = app.log_in_as(AdminRole::Viewer).await;
+    let file = app
+        .create_uploaded_file("downloaded.txt", b"downloaded", &["guarded"])
+        .await;
+    let collection = app.create_collection("downloaded", &["guarded"]).await;
+    let unknown_task_id = Uuid::new_v4();
+
+    assert_eq!(
+        app.get_for_status_as(None, "/files/export?format=ndjson")
+            .await,
+        Status::Unauthorized
+    );
+    assert_eq!(
+        app.get_for_status_as(Some(&viewer), "/files/export?format=ndjson")
+            .await,
+        Status::Ok
+    );
+
+    let posts = [
+        (
+            "/files/exports".to_owned(),
+            json!({ "format": "csv" }),
+            Status::Ok,
+        ),
+        (
+            format!("/files/exports/{unknown_task_id}/download-urls"),
+            json!({}),
+            Status::NotFound,
+        ),
+        (
+            format!("/files/{}/download-urls", file.id),
+            json!({}),
+            Status::Ok,
+        ),
+        (
+            format!("/collections/{}/download-manifests", collection.id),
+            json!({}),
+            Status::Ok,
+        ),
+        (
+            format!(
+                "/collections/{}/download-manifests/{unknown_task_id}/download-urls",
+                collection.id
+            ),
+            json!({}),
+            Status::NotFound,
+        ),
+    ];
+
+    for (uri, body, status) in posts {
+        assert_eq!(
+            app.post_for_status_as(None, &uri, &body).await,
+            Status::Unauthorized,
+            "{uri}"
+        );
+        assert_eq!(
+            app.post_for_status_as(Some(&viewer), &uri, &body).await,
+            status,
+            "{uri}"
+        );
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn file_changes_need_an_editor() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let creating = json!({
+        "name": "created.txt",
+        "size": 7,
+        "mimeType": "text/plain",
+        "tags": [],
+    });
+
+    for (role, status) in [
+        (AdminRole::Viewer, Status::Forbidden),
+        (AdminRole::Editor, Status::Ok),
+        (AdminRole::Admin, Status::Ok),
+    ] {
+        let token = app.log_in_as(role).await;
+
+        assert_eq!(
+            app.post_for_status_as(Some(&token), "/files", &creating)
+                .await,
+            status,
+            "{role:?}"
+        );
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn admin_tasks_need_an_admin() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    for (role, status) in [
+        (AdminRole::Viewer, Status::Forbidden),
+        (AdminRole::Editor, Status::Forbidden),
+        (AdminRole::Admin, Status::Ok),
+    ] {
+        let token = app.log_in_as(role).await;
+
+        assert_eq!(
+            app.get_for_status_as(Some(&token), "/admin-tasks").await,
+            status,
+            "{role:?}"
+        );
+    }
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::routes::{with_timeout, Dependency, RouteError};
+use rocket::http::Status;
+use serde_json::json;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Sets its flag when dropped, to tell whether a timed-out call was cancelled.
+struct DropFlag(Arc<AtomicBool>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[rocket::async_test]
+async fn calls_answering_in_time_pass_through() {
+    let result = with_timeout(Dependency::Storage, Duration::from_secs(5), async { 42 }).await;
+
+    assert!(matches!(result, Ok(42)));
+}
+
+#[rocket::async_test]
+async fn calls_past_the_deadline_are_cancelled_and_answer_a_timeout() {
+    let dropped = Arc::new(AtomicBool::new(false));
+    let flag = DropFlag(dropped.clone());
+
+    let result = with_timeout(Dependency::Storage, Duration::from_millis(50), async move {
+        let _flag = flag;
+        tokio::time::sleep(Duration::from_secs(60)).await;
+    })
+    .await;
+
+    let Err(err) = result else {
+        panic!("the call did not time out");
+    };
+    assert!(matches!(err, RouteError::Timeout(Dependency::Storage)));
+    assert_eq!(err.code(), Some("storage_timeout"));
+    assert!(dropped.load(Ordering::SeqCst), "the call was left running");
+}
+
+#[rocket::async_test]
+async fn timeouts_name_their_dependency() {
+    let err = with_timeout(
+        Dependency::SearchEngine,
+        Duration::from_millis(10),
+        tokio::time::sleep(Duration::from_secs(60)),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(err.code(), Some("search_engine_timeout"));
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn hanging_dependencies_answer_gateway_timeout() {
+    let app = TestApp::spawn_with_in_memory_stores_and_env(&[
+        ("SEARCH_TIMEOUT_SECS", "1"),
+        ("STORAGE_TIMEOUT_SECS", "1"),
+    ])
+    .await;
+    let file = app
+        .create_uploaded_file("hanging.txt", b"hanging", &[])
+        .await;
+    let pending = app.create_file("pending.txt", b"pending", &[]).await;
+
+    app.search_index
+        .as_ref()
+        .unwrap()
+        .set_search_delay(Duration::from_secs(3));
+    let (status, body) = app
+        .post_for_error("/searches/files", &json!({ "q": "hanging" }))
+        .await;
+    assert_eq!(status, Status::GatewayTimeout, "{body}");
+    assert_eq!(body["code"], "search_engine_timeout", "{body}");
+
+    app.storage
+        .as_ref()
+        .unwrap()
+        .set_presign_delay(Duration::from_secs(3));
+    for uri in [
+        format!("/files/{}/download-urls", file.id),
+        format!("/files/{}/upload-urls", pending.id),
+    ] {
+        let (status, body) = app.post_for_error(&uri, &json!({})).await;
+
+        assert_eq!(status, Status::GatewayTimeout, "{uri}: {body}");
+        assert_eq!(body["code"], "storage_timeout", "{uri}: {body}");
+    }
+}
@@ -0,0 +1,125 @@
+use aws_sdk_s3::{
+    config::http::HttpResponse,
+    error::{ConnectorError, ErrorMetadata, SdkError},
+    operation::{head_object::HeadObjectError, list_parts::ListPartsError},
+    primitives::SdkBody,
+};
+use file_indexer::services::s3_service::{existence_error, S3ServiceError};
+use std::io;
+
+#[derive(Debug, PartialEq, Eq)]
+enum Class {
+    NotFound,
+    PermissionDenied,
+    Transient,
+}
+
+fn class(error: Option<S3ServiceError>) -> Class {
+    match error {
+        None => Class::NotFound,
+        Some(S3ServiceError::PermissionDenied { .. }) => Class::PermissionDenied,
+        Some(S3ServiceError::Transient { .. }) => Class::Transient,
+        Some(err) => panic!("unexpected error: {err:#?}"),
+    }
+}
+
+fn response(status: u16) -> HttpResponse {
+    HttpResponse::new(status.try_into().unwrap(), SdkBody::empty())
+}
+
+fn metadata(code: Option<&str>) -> ErrorMetadata {
+    let mut metadata = ErrorMetadata::builder();
+
+    if let Some(code) = code {
+        metadata = metadata.code(code);
+    }
+
+    metadata.build()
+}
+
+fn head_object_error(status: u16, code: Option<&str>) -> SdkError<HeadObjectError, HttpResponse> {
+    SdkError::service_error(HeadObjectError::generic(metadata(code)), response(status))
+}
+
+fn list_parts_error(status: u16, code: Option<&str>) -> SdkError<ListPartsError, HttpResponse> {
+    SdkError::service_error(ListPartsError::generic(metadata(code)), response(status))
+}
+
+fn classify_head_object(err: SdkError<HeadObjectError, HttpResponse>) -> Class {
+    class(existence_error(
+        "head object",
+        err,
+        &["NotFound", "NoSuchKey"],
+    ))
+}
+
+#[test]
+fn service_errors_of_an_object_are_classified_by_status_and_code() {
+    let cases = [
+        (404, None, Class::NotFound),
+        (404, Some("NotFound"), Class::NotFound),
+        (400, Some("NoSuchKey"), Class::NotFound),
+        (403, None, Class::PermissionDenied),
+        (403, Some("AccessDenied"), Class::PermissionDenied),
+        (400, Some("ExpiredToken"), Class::PermissionDenied),
+        (400, Some("InvalidAccessKeyId"), Class::PermissionDenied),
+        (400, Some("InvalidToken"), Class::PermissionDenied),
+        (400, Some("SignatureDoesNotMatch"), Class::PermissionDenied),
+        (400, Some("AllAccessDisabled"), Class::PermissionDenied),
+        (400, Some("InvalidRequest"), Class::Transient),
+        (500, Some("InternalError"), Class::Transient),
+        (503, Some("SlowDown"), Class::Transient),
+        (503, None, Class::Transient),
+    ];
+
+    for (status, code, expected) in cases {
+        assert_eq!(
+            classify_head_object(head_object_error(status, code)),
+            expected,
+            "{status} {code:?}"
+        );
+    }
+}
+
+#[test]
+fn failures_without_an_answer_of_s3_are_transient() {
+    let cases: [(&str, SdkError<HeadObjectError, HttpResponse>); 4] = [
+        ("timeout", SdkError::timeout_error("timed out")),
+        (
+            "dispatch failure",
+            SdkError::dispatch_failure(ConnectorError::io(Box::new(io::Error::new(
+                io::ErrorKind::ConnectionReset,
+                "connection reset",
+            )))),
+        ),
+        (
+            "construction failure",
+            SdkError::construction_failure("invalid request"),
+        ),
+        (
+            "unreadable response",
+            SdkError::response_error("truncated body", response(200)),
+        ),
+    ];
+
+    for (name, err) in cases {
+        assert_eq!(classify_head_object(err), Class::Transient, "{name}");
+    }
+}
+
+#[test]
+fn only_the_codes_of_the_operation_mean_not_found() {
+    let classify = |status, code| {
+        class(existence_error(
+            "list parts",
+            list_parts_error(status, code),
+            &["NoSuchUpload"],
+        ))
+    };
+
+    assert_eq!(classify(404, Some("NoSuchUpload")), Class::NotFound);
+    assert_eq!(classify(400, Some("NoSuchUpload")), Class::NotFound);
+    // a missing object says nothing about whether the upload exists
+    assert_eq!(classify(400, Some("NoSuchKey")), Class::Transient);
+    assert_eq!(classify(403, Some("NoSuchBucket")), Class::PermissionDenied);
+}
@@ -0,0 +1,466 @@
+//! Runs the same filtered searches against Meilisearch and the in-memory search index, so that
+//! the filter expressions mean the same to both.
+
+mod common;
+
+use common::file;
+use file_indexer::{
+    config::SearchEngineConfig,
+    interfaces::{
+        admins::MeiliTaskStatus,
+        files::{
+            FileSearchDistinct, FileSearchQuery, FileSearchQueryFilter,
+            FileSearchQueryFilterOperator,
+        },
+    },
+    services::index_service::{IndexServiceError, MeilisearchIndex, SearchIndex},
+    testing::search_index::InMemorySearchIndex,
+};
+use meilisearch_sdk::errors::{Error, ErrorCode};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+const WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+const WAIT_INTERVAL: Duration = Duration::from_millis(100);
+
+#[rocket::async_test]
+async fn in_memory_index_applies_filters() {
+    check_filters(&InMemorySearchIndex::new()).await;
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn meilisearch_applies_filters() {
+    let (_meilisearch, url) = common::start_meilisearch().await;
+    let index = MeilisearchIndex::init(&SearchEngineConfig {
+        url,
+        api_key: None,
+        check_only: false,
+    })
+    .await
+    .expect("failed to initialize search engine module");
+
+    check_filters(&index).await;
+}
+
+fn query(q: &str, filters: Vec<Vec<FileSearchQueryFilter>>) -> FileSearchQuery {
+    FileSearchQuery {
+        q: q.to_owned(),
+        limit: 100,
+        offset: 0,
+        filters,
+        matching_strategy: None,
+        attributes_to_highlight: None,
+        crop_length: None,
+        distinct: None,
+        include_unready: false,
+        include_superseded: false,
+    }
+}
+
+fn filtered(filters: Vec<Vec<FileSearchQueryFilter>>) -> FileSearchQuery {
+    query("", filters)
+}
+
+fn tag(value: &str) -> FileSearchQueryFilter {
+    FileSearchQueryFilter::Tag {
+        value: value.to_owned(),
+    }
+}
+
+fn size(operator: FileSearchQueryFilterOperator, value: usize) -> FileSearchQueryFilter {
+    FileSearchQueryFilter::Size { operator, value }
+}
+
+#[rocket::async_test]
+async fn in_memory_index_resolves_file_ids_past_a_thousand() {
+    check_file_id_matches(&InMemorySearchIndex::new()).await;
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn meilisearch_resolves_file_ids_past_a_thousand() {
+    let (_meilisearch, url) = common::start_meilisearch().await;
+    let index = MeilisearchIndex::init(&SearchEngineConfig {
+        url,
+        api_key: None,
+        check_only: false,
+    })
+    .await
+    .expect("failed to initialize search engine module");
+
+    check_file_id_matches(&index).await;
+}
+
+#[rocket::async_test]
+async fn in_memory_index_collapses_equal_checksums() {
+    check_distinct_checksums(&InMemorySearchIndex::new()).await;
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn meilisearch_collapses_equal_checksums() {
+    let (_meilisearch, url) = common::start_meilisearch().await;
+    let index = MeilisearchIndex::init(&SearchEngineConfig {
+        url,
+        api_key: None,
+        check_only: false,
+    })
+    .await
+    .expect("failed to initialize search engine module");
+
+    check_distinct_checksums(&index).await;
+}
+
+/// Meilisearch stops at 1000 hits unless the index allows more, so bulk deletions by query must
+/// reach past it and tell when the matches were cut short.
+async fn check_file_id_matches(index: &dyn SearchIndex) {
+    let files = Vec::from_iter((0..1200).map(|number| {
+        file(
+            &format!("ledger-{number:04}.csv"),
+            10,
+            "text/csv",
+            &["ledger"],
+            "2024-01-10T00:00:00Z",
+        )
+    }));
+
+    for batch in files.chunks(500) {
+        let task_uid = index.index_files(batch, &HashMap::new()).await.unwrap();
+        wait_for_task(index, task_uid).await;
+    }
+
+    let q = query("ledger", vec![]);
+
+    let matches = index.search_file_ids(&q, 10_000).await.unwrap();
+    assert_eq!(matches.file_ids.len(), 1200);
+    assert!(!matches.truncated);
+
+    let matches = index.search_file_ids(&q, 1100).await.unwrap();
+    assert_eq!(matches.file_ids.len(), 1100);
+    assert!(matches.truncated);
+
+    let matches = index.search_file_ids(&q, 1200).await.unwrap();
+    assert_eq!(matches.file_ids.len(), 1200);
+    assert!(!matches.truncated);
+}
+
+async fn check_distinct_checksums(index: &dyn SearchIndex) {
+    let files = Vec::from_iter(["copy-1.txt", "copy-2.txt", "copy-3.txt"].map(|name| {
+        let mut file = file(name, 10, "text/plain", &["copy"], "2024-01-10T00:00:00Z");
+        file.checksum = Some("bb".to_owned());
+        file
+    }));
+    let task_uid = index.index_files(&files, &HashMap::new()).await.unwrap();
+    wait_for_task(index, task_uid).await;
+
+    let q = query("copy", vec![]);
+    assert_eq!(index.search_files(&q).await.unwrap().hits.len(), 3);
+
+    let hits = index
+        .search_files(&FileSearchQuery {
+            distinct: Some(FileSearchDistinct::Checksum),
+            ..q
+        })
+        .await
+        .unwrap()
+        .hits;
+    assert_eq!(hits.len(), 1, "{hits:?}");
+    assert_eq!(hits[0].file.checksum.as_deref(), Some("bb"));
+    assert_eq!(hits[0].duplicate_count, Some(3));
+}
+
+async fn wait_for_task(index: &dyn SearchIndex, task_uid: u32) {
+    let deadline = Instant::now() + WAIT_TIMEOUT;
+
+    loop {
+        let tasks = index.get_tasks(&[task_uid]).await.unwrap();
+
+        match tasks.first().map(|task| (task.status, &task.error)) {
+            Some((MeiliTaskStatus::Succeeded, _)) => return,
+            Some((MeiliTaskStatus::Failed, error)) => panic!("task {task_uid} failed: {error:?}"),
+            _ => {}
+        }
+
+        assert!(
+            Instant::now() < deadline,
+            "task {task_uid} did not finish in time"
+        );
+        tokio::time::sleep(WAIT_INTERVAL).await;
+    }
+}
+
+async fn search_names(index: &dyn SearchIndex, q: &FileSearchQuery) -> Vec<String> {
+    let hits = index
+        .search_files(q)
+        .await
+        .unwrap_or_else(|err| panic!("search failed for {:?}: {err:?}", q.filters));
+    assert_eq!(hits.skipped_hits, 0);
+    let mut names = Vec::from_iter(hits.hits.into_iter().map(|hit| hit.file.name));
+    names.sort();
+    names
+}
+
+async fn check_filters(index: &dyn SearchIndex) {
+    use FileSearchQueryFilterOperator::{Gt, Gte, Lt, Neq};
+
+    let mut report = file(
+        "report.pdf",
+        100,
+        "application/pdf",
+        &["work", "2024"],
+        "2024-01-10T00:00:00Z",
+    );
+    report.checksum = Some("aa".to_owned());
+    let mut photo = file(
+        "photo.png",
+        2_000_000,
+        "image/png",
+        &["holiday", "it's \\ odd"],
+        "2024-05-10T00:00:00Z",
+    );
+    photo.checksum = Some("aa".to_owned());
+    photo.protected = true;
+    let notes = file("notes.txt", 50, "text/plain", &[], "2024-06-10T00:00:00Z");
+    let mut draft = file(
+        "draft.txt",
+        10,
+        "text/plain",
+        &["work"],
+        "2024-07-10T00:00:00Z",
+    );
+    draft.is_ready = false;
+    let mut old_notes = file(
+        "old-notes.txt",
+        40,
+        "text/plain",
+        &[],
+        "2023-06-10T00:00:00Z",
+    );
+    old_notes.superseded_by = Some(notes.id);
+
+    let task_uid = index
+        .index_files(&[report, photo, notes, draft, old_notes], &HashMap::new())
+        .await
+        .unwrap();
+    wait_for_task(index, task_uid).await;
+
+    let cases = [
+        (
+            filtered(vec![]),
+            vec!["notes.txt", "photo.png", "report.pdf"],
+        ),
+        (
+            FileSearchQuery {
+                include_unready: true,
+                include_superseded: true,
+                ..filtered(vec![])
+            },
+            vec![
+                "draft.txt",
+                "notes.txt",
+                "old-notes.txt",
+                "photo.png",
+                "report.pdf",
+            ],
+        ),
+        (query("repo", vec![]), vec!["report.pdf"]),
+        (query("PHOTO", vec![]), vec!["photo.png"]),
+        (query("holiday", vec![]), vec!["photo.png"]),
+        (filtered(vec![vec![size(Gt, 100)]]), vec!["photo.png"]),
+        (
+            filtered(vec![vec![size(Gte, 100)]]),
+            vec!["photo.png", "report.pdf"],
+        ),
+        (
+            filtered(vec![vec![size(Neq, 100)]]),
+            vec!["notes.txt", "photo.png"],
+        ),
+        (
+            filtered(vec![vec![FileSearchQueryFilter::MimeType {
+                value: "text/plain".to_owned(),
+            }]]),
+            vec!["notes.txt"],
+        ),
+        (
+            filtered(vec![vec![FileSearchQueryFilter::NotMimeType {
+                value: "text/plain".to_owned(),
+            }]]),
+            vec!["photo.png", "report.pdf"],
+        ),
+        (filtered(vec![vec![tag("work")]]), vec!["report.pdf"]),
+        (filtered(vec![vec![tag("it's \\ odd")]]), vec!["photo.png"]),
+        (
+            filtered(vec![vec![FileSearchQueryFilter::NotTag {
+                value: "work".to_owned(),
+            }]]),
+            vec!["notes.txt", "photo.png"],
+        ),
+        (
+            filtered(vec![vec![
+                FileSearchQueryFilter::NotTag {
+                    value: "work".to_owned(),
+                },
+                tag("work"),
+            ]]),
+            vec!["notes.txt", "photo.png", "report.pdf"],
+        ),
+        (
+            filtered(vec![vec![
+                FileSearchQueryFilter::NotTag {
+                    value: "holiday".to_owned(),
+                },
+                size(Gt, 1000),
+            ]]),
+            vec!["notes.txt", "photo.png", "report.pdf"],
+        ),
+        (
+            filtered(vec![
+                vec![FileSearchQueryFilter::NotTag {
+                    value: "work".to_owned(),
+                }],
+                vec![FileSearchQueryFilter::NotMimeType {
+                    value: "image/png".to_owned(),
+                }],
+            ]),
+            vec!["notes.txt"],
+        ),
+        (
+            filtered(vec![vec![FileSearchQueryFilter::TagIsEmpty]]),
+            vec!["notes.txt"],
+        ),
+        (
+            filtered(vec![vec![FileSearchQueryFilter::TagIsNotEmpty]]),
+            vec!["photo.png", "report.pdf"],
+        ),
+        (
+            filtered(vec![vec![FileSearchQueryFilter::Exists {
+                field: "checksum".to_owned(),
+            }]]),
+            vec!["photo.png", "report.pdf"],
+        ),
+        (
+            filtered(vec![vec![FileSearchQueryFilter::NotExists {
+                field: "checksum".to_owned(),
+            }]]),
+            vec!["notes.txt"],
+        ),
+        (
+            filtered(vec![vec![FileSearchQueryFilter::Protected { value: true }]]),
+            vec!["photo.png"],
+        ),
+        (
+            filtered(vec![vec![FileSearchQueryFilter::Protected {
+                value: false,
+            }]]),
+            vec!["notes.txt", "report.pdf"],
+        ),
+        (
+            filtered(vec![vec![FileSearchQueryFilter::SizeBucket {
+                value: "<1MB".to_owned(),
+            }]]),
+            vec!["notes.txt", "report.pdf"],
+        ),
+        (
+            filtered(vec![vec![FileSearchQueryFilter::SizeBucket {
+                value: "1-10MB".to_owned(),
+            }]]),
+            vec!["photo.png"],
+        ),
+        (
+            filtered(vec![vec![FileSearchQueryFilter::TruncatedForIndex {
+                value: true,
+            }]]),
+            vec![],
+        ),
+        (
+            filtered(vec![vec![FileSearchQueryFilter::TruncatedForIndex {
+                value: false,
+            }]]),
+            vec!["notes.txt", "photo.png", "report.pdf"],
+        ),
+        (
+            filtered(vec![vec![FileSearchQueryFilter::UploadedAt {
+                operator: Lt,
+                value: "2024-05-10T00:00:00Z".parse().unwrap(),
+            }]]),
+            vec!["report.pdf"],
+        ),
+        (
+            filtered(vec![vec![tag("work"), tag("holiday")]]),
+            vec!["photo.png", "report.pdf"],
+        ),
+        (
+            filtered(vec![
+                vec![tag("work"), tag("holiday")],
+                vec![size(Gt, 1000)],
+            ]),
+            vec!["photo.png"],
+        ),
+    ];
+
+    for (q, expected) in &cases {
+        assert_eq!(
+            search_names(index, q).await,
+            *expected,
+            "unexpected hits for {:?} with filters {:?}",
+            q.q,
+            q.filters
+        );
+    }
+
+    let hits = index
+        .search_files(&FileSearchQuery {
+            distinct: Some(FileSearchDistinct::Checksum),
+            ..filtered(vec![])
+        })
+        .await
+        .unwrap();
+    let mut duplicate_counts = Vec::from_iter(
+        hits.hits
+            .iter()
+            .map(|hit| (hit.file.checksum.as_deref(), hit.duplicate_count)),
+    );
+    duplicate_counts.sort();
+    assert_eq!(duplicate_counts, [(None, None), (Some("aa"), Some(2))]);
+
+    let facets = index.facet_files(&filtered(vec![])).await.unwrap();
+    assert_eq!(
+        Vec::from_iter(
+            facets
+                .size_bucket
+                .iter()
+                .map(|facet| (facet.value.as_str(), facet.count))
+        ),
+        [
+            ("<1MB", 2),
+            ("1-10MB", 1),
+            ("10-100MB", 0),
+            ("100MB-1GB", 0),
+            (">1GB", 0)
+        ]
+    );
+    let facets = index
+        .facet_files(&filtered(vec![vec![tag("work")]]))
+        .await
+        .unwrap();
+    assert_eq!(facets.size_bucket[0].count, 1);
+    assert_eq!(facets.size_bucket[1].count, 0);
+
+    let err = index
+        .search_files(&filtered(vec![vec![FileSearchQueryFilter::Exists {
+            field: "name".to_owned(),
+        }]]))
+        .await
+        .expect_err("filtering on an attribute that is not filterable must fail");
+    assert!(
+        matches!(
+            &err,
+            IndexServiceError::MeilisearchError(Error::Meilisearch(err))
+                if err.error_code == ErrorCode::InvalidSearchFilter
+        ),
+        "unexpected error: {err:?}"
+    );
+}
@@ -0,0 +1,188 @@
+//! The matching strategy and highlighting options of searches. Only Meilisearch marks up the
+//! matches; the in-memory index repeats the highlighted attributes as they are.
+
+mod common;
+
+use common::file;
+use file_indexer::{
+    config::SearchEngineConfig,
+    interfaces::{
+        collections::CollectionSearchQuery, files::FileSearchQuery, SearchAttribute,
+        SearchMatchingStrategy,
+    },
+    services::index_service::{MeilisearchIndex, SearchIndex},
+    testing::search_index::InMemorySearchIndex,
+};
+use serde_json::json;
+use std::{collections::HashMap, time::Duration};
+
+fn query(q: &str) -> FileSearchQuery {
+    serde_json::from_value(json!({ "q": q })).unwrap()
+}
+
+async fn meilisearch() -> (impl Drop, MeilisearchIndex) {
+    let (meilisearch, url) = common::start_meilisearch().await;
+    let index = MeilisearchIndex::init(&SearchEngineConfig {
+        url,
+        api_key: None,
+        check_only: false,
+    })
+    .await
+    .expect("failed to initialize search engine module");
+
+    (meilisearch, index)
+}
+
+async fn index_names(index: &dyn SearchIndex, names: &[&str]) {
+    let files = Vec::from_iter(names.iter().map(|name| {
+        file(
+            name,
+            10,
+            "application/pdf",
+            &["finance"],
+            "2025-01-01T00:00:00Z",
+        )
+    }));
+    let task_uid = index.index_files(&files, &HashMap::new()).await.unwrap();
+    index
+        .wait_for_task(task_uid, Duration::from_secs(30))
+        .await
+        .unwrap();
+}
+
+async fn search_names(index: &dyn SearchIndex, q: &FileSearchQuery) -> Vec<String> {
+    let mut names = Vec::from_iter(
+        index
+            .search_files(q)
+            .await
+            .unwrap()
+            .hits
+            .into_iter()
+            .map(|hit| hit.file.name),
+    );
+    names.sort();
+    names
+}
+
+#[test]
+fn only_searchable_attributes_may_be_highlighted() {
+    let q: FileSearchQuery = serde_json::from_value(json!({
+        "q": "report",
+        "attributesToHighlight": ["name", "tags"],
+    }))
+    .unwrap();
+    assert_eq!(
+        q.attributes_to_highlight,
+        Some(vec![SearchAttribute::Name, SearchAttribute::Tags])
+    );
+
+    for attribute in ["checksum", "mimeType", "Name", ""] {
+        let body = json!({ "q": "report", "attributesToHighlight": [attribute] });
+
+        assert!(
+            serde_json::from_value::<FileSearchQuery>(body.clone()).is_err(),
+            "{attribute:?} was accepted for files"
+        );
+        assert!(
+            serde_json::from_value::<CollectionSearchQuery>(body).is_err(),
+            "{attribute:?} was accepted for collections"
+        );
+    }
+}
+
+#[rocket::async_test]
+async fn hits_have_highlights_only_when_requested() {
+    let index = InMemorySearchIndex::new();
+    index_names(&index, &["report.pdf"]).await;
+
+    let hits = index.search_files(&query("report")).await.unwrap().hits;
+    assert!(hits[0].highlights.is_none());
+
+    let hits = index
+        .search_files(&FileSearchQuery {
+            attributes_to_highlight: Some(vec![SearchAttribute::Name]),
+            ..query("report")
+        })
+        .await
+        .unwrap()
+        .hits;
+    assert!(hits[0].highlights.as_ref().unwrap().name.is_some());
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn meilisearch_marks_up_the_matches_of_highlighted_attributes() {
+    let (_meilisearch, index) = meilisearch().await;
+    index_names(&index, &["quarterly report.pdf"]).await;
+
+    let hits = index
+        .search_files(&FileSearchQuery {
+            attributes_to_highlight: Some(vec![SearchAttribute::Name, SearchAttribute::Tags]),
+            ..query("report finance")
+        })
+        .await
+        .unwrap()
+        .hits;
+    let highlights = hits[0].highlights.as_ref().expect("no highlights");
+
+    assert_eq!(
+        highlights.name.as_deref(),
+        Some("quarterly <em>report</em>.pdf")
+    );
+    assert_eq!(
+        highlights.tags.as_deref(),
+        Some(&["<em>finance</em>".to_owned()][..])
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn meilisearch_crops_highlighted_attributes_around_the_matches() {
+    let (_meilisearch, index) = meilisearch().await;
+    index_names(
+        &index,
+        &["one two three four five six seven eight report nine ten.pdf"],
+    )
+    .await;
+
+    let hits = index
+        .search_files(&FileSearchQuery {
+            attributes_to_highlight: Some(vec![SearchAttribute::Name]),
+            crop_length: Some(3),
+            ..query("report")
+        })
+        .await
+        .unwrap()
+        .hits;
+    let name = hits[0]
+        .highlights
+        .as_ref()
+        .and_then(|highlights| highlights.name.clone())
+        .expect("no highlighted name");
+
+    assert!(name.contains("<em>report</em>"), "{name}");
+    assert!(!name.contains("one"), "{name}");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn meilisearch_matches_all_words_on_request() {
+    let (_meilisearch, index) = meilisearch().await;
+    index_names(&index, &["annual report.pdf", "annual summary.pdf"]).await;
+
+    assert_eq!(
+        search_names(&index, &query("annual report")).await,
+        ["annual report.pdf", "annual summary.pdf"]
+    );
+    assert_eq!(
+        search_names(
+            &index,
+            &FileSearchQuery {
+                matching_strategy: Some(SearchMatchingStrategy::All),
+                ..query("annual report")
+            }
+        )
+        .await,
+        ["annual report.pdf"]
+    );
+}
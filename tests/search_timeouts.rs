@@ -0,0 +1,48 @@
+use file_indexer::{
+    interfaces::{files::FileSearchQuery, CircuitState},
+    services::index_service::{IndexService, IndexServiceError, IndexTimeouts},
+    testing::search_index::InMemorySearchIndex,
+};
+use std::{sync::Arc, time::Duration};
+
+const SEARCH_TIMEOUT: Duration = Duration::from_millis(50);
+
+fn query(q: &str) -> FileSearchQuery {
+    serde_json::from_value(serde_json::json!({ "q": q })).unwrap()
+}
+
+#[rocket::async_test]
+async fn searches_that_time_out_count_as_outages() {
+    let search_index = Arc::new(InMemorySearchIndex::new());
+    let index_service = IndexService::new(
+        search_index.clone(),
+        IndexTimeouts {
+            search: SEARCH_TIMEOUT,
+            index: Duration::from_secs(10),
+        },
+    );
+
+    assert!(index_service.search_files(&query("notes")).await.is_ok());
+
+    search_index.set_search_delay(SEARCH_TIMEOUT * 4);
+
+    for consecutive_failures in 1..=5 {
+        let result = index_service.search_files(&query("notes")).await;
+        assert!(
+            matches!(result, Err(IndexServiceError::Timeout { after }) if after == SEARCH_TIMEOUT),
+            "{result:?}"
+        );
+        assert_eq!(
+            index_service.circuit_status().consecutive_failures,
+            consecutive_failures
+        );
+    }
+
+    // once the circuit opens, searches fail without waiting
+    assert_eq!(index_service.circuit_status().state, CircuitState::Open);
+    let result = index_service.search_files(&query("notes")).await;
+    assert!(
+        matches!(result, Err(IndexServiceError::CircuitOpen { .. })),
+        "{result:?}"
+    );
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::{
+    collections::CollectionSearchQuery, files::FileSearchQuery, FieldError, SEARCH_MAX_LIMIT,
+    SEARCH_MAX_OFFSET, SEARCH_MAX_QUERY_LENGTH,
+};
+use rocket::http::Status;
+use serde_json::{json, Value};
+
+fn file_query(body: Value) -> FileSearchQuery {
+    serde_json::from_value(body).unwrap()
+}
+
+fn collection_query(body: Value) -> CollectionSearchQuery {
+    serde_json::from_value(body).unwrap()
+}
+
+fn fields(errors: Vec<FieldError>) -> Vec<String> {
+    Vec::from_iter(errors.into_iter().map(|error| error.field))
+}
+
+/// Checks a rule on both search queries, which share it.
+fn assert_fields(body: Value, expected: &[&str]) {
+    assert_eq!(
+        fields(file_query(body.clone()).validate()),
+        expected,
+        "files: {body}"
+    );
+    assert_eq!(
+        fields(collection_query(body.clone()).validate()),
+        expected,
+        "collections: {body}"
+    );
+}
+
+#[test]
+fn limit_must_be_between_one_and_the_maximum() {
+    for limit in [1, SEARCH_MAX_LIMIT] {
+        assert_fields(json!({ "q": "x", "limit": limit }), &[]);
+    }
+
+    for limit in [-1, 0, SEARCH_MAX_LIMIT + 1, 100_000] {
+        assert_fields(json!({ "q": "x", "limit": limit }), &["limit"]);
+    }
+}
+
+#[test]
+fn offset_must_be_between_zero_and_the_maximum() {
+    for offset in [0, SEARCH_MAX_OFFSET] {
+        assert_fields(json!({ "q": "x", "offset": offset }), &[]);
+    }
+
+    for offset in [-1, SEARCH_MAX_OFFSET + 1] {
+        assert_fields(json!({ "q": "x", "offset": offset }), &["offset"]);
+    }
+}
+
+#[test]
+fn query_length_is_counted_in_characters() {
+    assert_fields(json!({ "q": "é".repeat(SEARCH_MAX_QUERY_LENGTH) }), &[]);
+    assert_fields(
+        json!({ "q": "x".repeat(SEARCH_MAX_QUERY_LENGTH + 1) }),
+        &["q"],
+    );
+}
+
+#[test]
+fn every_rule_broken_is_reported() {
+    assert_fields(
+        json!({
+            "q": "x".repeat(SEARCH_MAX_QUERY_LENGTH + 1),
+            "limit": 0,
+            "offset": -1,
+        }),
+        &["q", "limit", "offset"],
+    );
+}
+
+#[test]
+fn defaults_are_within_the_bounds() {
+    assert_fields(json!({ "q": "" }), &[]);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn out_of_range_searches_never_reach_the_index() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let search_index = app.search_index.clone().unwrap();
+
+    for (body, field) in [
+        (json!({ "q": "x", "limit": SEARCH_MAX_LIMIT + 1 }), "limit"),
+        (json!({ "q": "x", "limit": 0 }), "limit"),
+        (json!({ "q": "x", "offset": -1 }), "offset"),
+    ] {
+        for uri in ["/searches/files", "/searches/collections"] {
+            let (status, error) = app.post_for_error(uri, &body).await;
+
+            assert_eq!(status, Status::UnprocessableEntity, "{uri}: {error}");
+            assert_eq!(error["fields"][0]["field"], json!(field), "{uri}: {error}");
+        }
+    }
+    assert_eq!(search_index.file_search_pages(), []);
+
+    let _: Value = app
+        .post(
+            "/searches/files",
+            &json!({ "q": "x", "limit": SEARCH_MAX_LIMIT }),
+        )
+        .await;
+    assert_eq!(search_index.file_search_pages(), [(SEARCH_MAX_LIMIT, 0)]);
+}
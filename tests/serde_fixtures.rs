@@ -0,0 +1,183 @@
+use file_indexer::interfaces::{
+    admins::TaskKind,
+    files::{FileSearchQueryFilter, FileSearchQueryFilterOperator},
+    fixtures::{self, Fixture},
+};
+use schemars::schema_for;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// Set to rewrite the committed fixtures from the samples, after an intended change to a DTO.
+const UPDATE_VAR: &str = "UPDATE_FIXTURES";
+
+fn fixture_path(group: &str) -> String {
+    format!("{}/tests/fixtures/{group}.json", env!("CARGO_MANIFEST_DIR"))
+}
+
+fn committed(group: &str) -> Map<String, Value> {
+    let path = fixture_path(group);
+    let content = std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("failed to read {path}: {err}; run with {UPDATE_VAR}=1"));
+    serde_json::from_str(&content).unwrap_or_else(|err| panic!("invalid JSON in {path}: {err}"))
+}
+
+fn samples(group: &[Fixture]) -> Map<String, Value> {
+    group
+        .iter()
+        .map(|fixture| (fixture.name.clone(), fixture.json.clone()))
+        .collect()
+}
+
+/// The variant tags listed by the schema of an enum, whether unit variants or internally tagged.
+fn schema_tags(schema: Value, tag: Option<&str>) -> BTreeSet<String> {
+    let mut tags = BTreeSet::new();
+    let mut pending = vec![schema];
+
+    while let Some(schema) = pending.pop() {
+        match schema {
+            Value::Object(mut object) => {
+                if let (None, Some(Value::Array(values))) = (tag, object.get("enum")) {
+                    tags.extend(values.iter().filter_map(|v| v.as_str().map(str::to_owned)));
+                }
+                if let Some(tag) = tag {
+                    if let Some(values) = object
+                        .get("properties")
+                        .and_then(|properties| properties.get(tag))
+                        .and_then(|property| property.get("enum"))
+                        .and_then(Value::as_array)
+                    {
+                        tags.extend(values.iter().filter_map(|v| v.as_str().map(str::to_owned)));
+                    }
+                }
+                for key in ["oneOf", "anyOf"] {
+                    if let Some(Value::Array(variants)) = object.remove(key) {
+                        pending.extend(variants);
+                    }
+                }
+            }
+            _ => continue,
+        }
+    }
+
+    tags
+}
+
+fn sample_tags(group: &str, prefix: &str, tag: Option<&str>) -> BTreeSet<String> {
+    let (_, fixtures) = fixtures::groups()
+        .into_iter()
+        .find(|(name, _)| *name == group)
+        .expect("unknown fixture group");
+
+    fixtures
+        .into_iter()
+        .filter(|fixture| fixture.name.starts_with(prefix))
+        .filter_map(|fixture| match tag {
+            Some(tag) => fixture.json.get(tag).cloned(),
+            None => Some(fixture.json),
+        })
+        .filter_map(|value| value.as_str().map(str::to_owned))
+        .collect()
+}
+
+#[test]
+fn every_sample_serializes_to_its_committed_fixture() {
+    let update = std::env::var_os(UPDATE_VAR).is_some();
+    let mut mismatches = Vec::new();
+
+    for (group, fixtures) in fixtures::groups() {
+        let samples = samples(&fixtures);
+
+        if update {
+            let content = serde_json::to_string_pretty(&samples).unwrap() + "\n";
+            std::fs::write(fixture_path(group), content).unwrap();
+            continue;
+        }
+
+        let committed = committed(group);
+        for (name, json) in &samples {
+            match committed.get(name) {
+                Some(expected) if expected == json => {}
+                Some(expected) => mismatches.push(format!(
+                    "{group}/{name}: serialized to\n{json:#}\nbut the fixture is\n{expected:#}"
+                )),
+                None => mismatches.push(format!("{group}/{name}: no committed fixture")),
+            }
+        }
+        for name in committed.keys().filter(|name| !samples.contains_key(*name)) {
+            mismatches.push(format!(
+                "{group}/{name}: no sample for the committed fixture"
+            ));
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "{}\n\nIf the change is intended, run with {UPDATE_VAR}=1 and commit the fixtures.",
+        mismatches.join("\n\n")
+    );
+}
+
+#[test]
+fn every_committed_fixture_reads_back_unchanged() {
+    for (group, fixtures) in fixtures::groups() {
+        let committed = committed(group);
+
+        for fixture in fixtures {
+            let Some(json) = committed.get(&fixture.name) else {
+                continue;
+            };
+            let read = fixture
+                .round_trip(json.clone())
+                .unwrap_or_else(|err| panic!("{group}/{}: {err}", fixture.name));
+            assert_eq!(&read, json, "{group}/{}", fixture.name);
+        }
+    }
+}
+
+#[test]
+fn fixture_names_are_unique_within_a_group() {
+    for (group, fixtures) in fixtures::groups() {
+        let mut names = BTreeSet::new();
+
+        for fixture in &fixtures {
+            assert!(
+                names.insert(&fixture.name),
+                "{group}/{} is defined twice",
+                fixture.name
+            );
+        }
+    }
+}
+
+#[test]
+fn every_search_filter_and_operator_has_a_fixture() {
+    let filters = schema_tags(
+        serde_json::to_value(schema_for!(FileSearchQueryFilter)).unwrap(),
+        Some("type"),
+    );
+    let operators = schema_tags(
+        serde_json::to_value(schema_for!(FileSearchQueryFilterOperator)).unwrap(),
+        None,
+    );
+
+    assert!(!filters.is_empty());
+    assert_eq!(
+        sample_tags("files", "FileSearchQueryFilter::", Some("type")),
+        filters
+    );
+    assert!(!operators.is_empty());
+    assert_eq!(
+        sample_tags("files", "FileSearchQueryFilterOperator::", None),
+        operators
+    );
+}
+
+#[test]
+fn every_task_kind_has_a_fixture() {
+    let kinds = TaskKind::ALL
+        .iter()
+        .map(|kind| kind.as_str().to_owned())
+        .collect::<BTreeSet<_>>();
+
+    assert_eq!(sample_tags("admins", "TaskKind::", None), kinds);
+}
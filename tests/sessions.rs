@@ -0,0 +1,94 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::admins::{AdminRole, AdminSession};
+use rocket::http::{Header, Status};
+
+/// Moves the timestamps of the session back by the given intervals, as Postgres reads them.
+async fn age_session(app: &TestApp, token: &str, last_used_ago: &str, expires_in: &str) {
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    sqlx::query(
+        "UPDATE admin_sessions
+SET
+    last_used_at = CURRENT_TIMESTAMP - $2::INTERVAL,
+    expired_at = CURRENT_TIMESTAMP + $3::INTERVAL
+WHERE token = $1",
+    )
+    .bind(token)
+    .bind(last_used_ago)
+    .bind(expires_in)
+    .execute(&db_pool)
+    .await
+    .unwrap();
+}
+
+async fn refresh(app: &TestApp, token: &str) -> (Status, Option<AdminSession>) {
+    let response = app
+        .client
+        .post("/admins/sessions/refresh")
+        .header(Header::new("Authorization", format!("Bearer {token}")))
+        .dispatch()
+        .await;
+    let status = response.status();
+    let session = response.into_json().await;
+
+    (status, session.filter(|_| status == Status::Ok))
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_session_idle_for_too_long_is_refused() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let token = app.log_in_as(AdminRole::Viewer).await;
+
+    // used just within the idle timeout of 12 hours
+    age_session(&app, &token, "11 hours 59 minutes", "1 day").await;
+    assert_eq!(
+        app.get_for_status_as(Some(&token), "/admins/me").await,
+        Status::Ok
+    );
+
+    age_session(&app, &token, "12 hours 1 minute", "1 day").await;
+    assert_eq!(
+        app.get_for_status_as(Some(&token), "/admins/me").await,
+        Status::Unauthorized
+    );
+    assert_eq!(refresh(&app, &token).await.0, Status::Unauthorized);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_session_past_its_expiry_is_refused_however_recently_used() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let token = app.log_in_as(AdminRole::Viewer).await;
+
+    age_session(&app, &token, "0 seconds", "-1 second").await;
+    assert_eq!(
+        app.get_for_status_as(Some(&token), "/admins/me").await,
+        Status::Unauthorized
+    );
+    assert_eq!(refresh(&app, &token).await.0, Status::Unauthorized);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_rotated_token_stops_working_at_once() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let token = app.log_in_as(AdminRole::Viewer).await;
+
+    let (status, session) = refresh(&app, &token).await;
+    assert_eq!(status, Status::Ok);
+    let session = session.unwrap();
+    assert_ne!(session.token, token);
+
+    assert_eq!(
+        app.get_for_status_as(Some(&token), "/admins/me").await,
+        Status::Unauthorized
+    );
+    assert_eq!(refresh(&app, &token).await.0, Status::Unauthorized);
+    assert_eq!(
+        app.get_for_status_as(Some(&session.token), "/admins/me")
+            .await,
+        Status::Ok
+    );
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::{
+    interfaces::admins::{AdminTaskInitiator, TaskKind},
+    services::admin_task_service::{AdminTaskService, AdminTaskServiceError},
+};
+use futures::future::join_all;
+use serde_json::json;
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn concurrent_enqueues_of_a_singleton_kind_create_a_single_task() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let admin_task_service = AdminTaskService::new(db_pool.clone());
+
+    // the tasks wait on a task no handler runs, so the task runner of the app leaves them queued
+    let blocker = admin_task_service
+        .enqueue_task(
+            AdminTaskInitiator::System,
+            None,
+            TaskKind::UpdateFile,
+            json!({}),
+            None,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+
+    let results = join_all((0..8).map(|_| {
+        admin_task_service.enqueue_task(
+            AdminTaskInitiator::System,
+            None,
+            TaskKind::ReconcileStorage,
+            json!({}),
+            None,
+            false,
+            Some(blocker.id),
+        )
+    }))
+    .await;
+
+    let created = Vec::from_iter(results.iter().filter_map(|result| result.as_ref().ok()));
+    assert_eq!(created.len(), 1, "{results:#?}");
+    for result in &results {
+        match result {
+            Ok(_) => {}
+            Err(AdminTaskServiceError::AlreadyQueued { task_id }) => {
+                assert_eq!(*task_id, created[0].id);
+            }
+            Err(err) => panic!("unexpected error: {err:#?}"),
+        }
+    }
+
+    let queued_count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM admin_tasks WHERE name = $1 AND status = 'pending'",
+    )
+    .bind(TaskKind::ReconcileStorage.as_str())
+    .fetch_one(&db_pool)
+    .await
+    .unwrap();
+    assert_eq!(queued_count, 1);
+}
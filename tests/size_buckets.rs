@@ -0,0 +1,54 @@
+use file_indexer::interfaces::files::{
+    file_size_bucket, FileSearchQuery, FileSearchQueryFilter, FILE_SIZE_BUCKETS,
+};
+
+#[test]
+fn sizes_on_a_boundary_fall_in_the_larger_bucket() {
+    let cases = [
+        (0, "<1MB"),
+        (999_999, "<1MB"),
+        (1_000_000, "1-10MB"),
+        (9_999_999, "1-10MB"),
+        (10_000_000, "10-100MB"),
+        (99_999_999, "10-100MB"),
+        (100_000_000, "100MB-1GB"),
+        (999_999_999, "100MB-1GB"),
+        (1_000_000_000, ">1GB"),
+        (usize::MAX, ">1GB"),
+    ];
+
+    for (size, bucket) in cases {
+        assert_eq!(file_size_bucket(size), bucket, "bucket of {size} bytes");
+    }
+}
+
+#[test]
+fn buckets_are_in_increasing_order() {
+    assert_eq!(FILE_SIZE_BUCKETS[0].min_size, 0);
+    assert!(FILE_SIZE_BUCKETS
+        .windows(2)
+        .all(|buckets| buckets[0].min_size < buckets[1].min_size));
+}
+
+#[test]
+fn size_bucket_filters_must_name_a_known_bucket() {
+    let query = |value: &str| -> FileSearchQuery {
+        serde_json::from_value(serde_json::json!({
+            "q": "",
+            "filters": [[{ "type": "sizeBucket", "value": value }]],
+        }))
+        .unwrap()
+    };
+
+    for bucket in FILE_SIZE_BUCKETS {
+        assert!(query(bucket.name).validate().is_empty(), "{}", bucket.name);
+    }
+
+    let errors = query("huge").validate();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].field, "filters[0][0].value");
+    assert!(matches!(
+        &query("<1MB").filters[0][0],
+        FileSearchQueryFilter::SizeBucket { value } if value == "<1MB"
+    ));
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::{
+    admins::{AdminTask, AdminTaskStatus},
+    files::{File, FileStorage},
+};
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn files_without_storage_are_assigned_the_configured_one_by_the_backfill() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let recorded = app
+        .create_uploaded_file("recorded.txt", b"recorded", &[])
+        .await;
+    let legacy = app.create_uploaded_file("legacy.txt", b"legacy", &[]).await;
+    let storage = recorded.storage.clone().unwrap();
+
+    // as if created before files recorded their storage
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    sqlx::query("UPDATE files SET bucket = NULL, region = NULL WHERE id = $1")
+        .bind(legacy.id)
+        .execute(&db_pool)
+        .await
+        .unwrap();
+    let file: File = app.get(&format!("/files/{}", legacy.id)).await;
+    assert_eq!(file.storage, None);
+
+    let task: AdminTask = app.post("/admin-tasks/backfill-storage", &()).await;
+    let task = app.wait_for_task(task.id).await;
+    assert_eq!(task.status, AdminTaskStatus::Completed, "{task:#?}");
+    assert_eq!(task.metadata["backfilled_count"], 1);
+
+    let file: File = app.get(&format!("/files/{}", legacy.id)).await;
+    assert_eq!(file.storage, Some(storage));
+    let file: File = app.get(&format!("/files/{}", recorded.id)).await;
+    assert_eq!(
+        file.storage,
+        Some(FileStorage {
+            bucket: "file-indexer".to_owned(),
+            region: "us-east-1".to_owned(),
+        })
+    );
+}
@@ -0,0 +1,44 @@
+use file_indexer::{config::StorageCostConfig, interfaces::files::StorageClass};
+
+const MIB: u64 = 1024 * 1024;
+const GIB: u64 = 1024 * MIB;
+
+fn monthly_cost(standard_per_gb_month: f64, bytes: u64) -> f64 {
+    StorageCostConfig {
+        standard_per_gb_month,
+    }
+    .monthly_cost(StorageClass::Standard, bytes)
+}
+
+#[test]
+fn costs_round_half_a_cent_up() {
+    // at 5.12 per GiB, a MiB costs half a cent
+    let cases = [
+        (0, 0.0),
+        (1, 0.0),
+        (MIB - 1, 0.0),
+        (MIB, 0.01),
+        (MIB + 1, 0.01),
+        (2 * MIB - 1, 0.01),
+        (2 * MIB, 0.01),
+        (2 * MIB + 1, 0.01),
+        (3 * MIB - 1, 0.01),
+        (3 * MIB, 0.02),
+        (3 * MIB + 1, 0.02),
+    ];
+
+    for (bytes, expected) in cases {
+        assert_eq!(monthly_cost(5.12, bytes), expected, "{bytes} bytes");
+    }
+}
+
+#[test]
+fn costs_are_billed_per_gib() {
+    let cases = [(GIB, 0.02), (1024 * GIB, 23.55), (10 * 1024 * GIB, 235.52)];
+
+    for (bytes, expected) in cases {
+        assert_eq!(monthly_cost(0.023, bytes), expected, "{bytes} bytes");
+    }
+
+    assert_eq!(monthly_cost(0.0, 1024 * GIB), 0.0);
+}
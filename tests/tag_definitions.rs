@@ -0,0 +1,245 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::{
+    collections::Collection,
+    files::File,
+    tags::{CreatingTagDefinition, TagDefinition},
+};
+use rocket::http::Status;
+use serde_json::json;
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use tracing::{
+    field::{Field, Visit},
+    Event, Subscriber,
+};
+use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+/// Counts the statements sqlx runs against `tag_definitions`, from its `sqlx::query` events.
+#[derive(Clone, Default)]
+struct TagDefinitionQueries(Arc<AtomicUsize>);
+
+impl TagDefinitionQueries {
+    fn take(&self) -> usize {
+        self.0.swap(0, Ordering::SeqCst)
+    }
+}
+
+impl<S: Subscriber> Layer<S> for TagDefinitionQueries {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if event.metadata().target() != "sqlx::query" {
+            return;
+        }
+
+        let mut visitor = MentionsTagDefinitions(false);
+        event.record(&mut visitor);
+
+        if visitor.0 {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+struct MentionsTagDefinitions(bool);
+
+impl Visit for MentionsTagDefinitions {
+    fn record_str(&mut self, _field: &Field, value: &str) {
+        self.0 |= value.contains("tag_definitions");
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn Debug) {}
+}
+
+async fn define(app: &TestApp, name: &str, color: &str) -> TagDefinition {
+    app.post(
+        "/tags/definitions",
+        &CreatingTagDefinition {
+            name: name.to_owned(),
+            color: Some(color.to_owned()),
+            description: None,
+        },
+    )
+    .await
+}
+
+fn defined_tags(tag_definitions: &[TagDefinition]) -> Vec<&str> {
+    Vec::from_iter(
+        tag_definitions
+            .iter()
+            .map(|tag_definition| tag_definition.name.as_str()),
+    )
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn tag_definitions_are_created_updated_and_deleted() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    let created: TagDefinition = app
+        .post(
+            "/tags/definitions",
+            &json!({ "name": "  urgent ", "color": "#FF8800", "description": "Due this week" }),
+        )
+        .await;
+    assert_eq!(created.name, "urgent");
+    assert_eq!(created.color.as_deref(), Some("#ff8800"));
+    assert_eq!(created.description.as_deref(), Some("Due this week"));
+
+    let gotten: TagDefinition = app.get("/tags/definitions/urgent").await;
+    assert_eq!(gotten, created);
+    assert_eq!(
+        app.post_for_status("/tags/definitions", &json!({ "name": "urgent" }))
+            .await,
+        Status::Conflict
+    );
+    let (status, error) = app
+        .post_for_error(
+            "/tags/definitions",
+            &json!({ "name": "late", "color": "orange" }),
+        )
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity);
+    assert_eq!(error["fields"][0]["field"], "color", "{error}");
+
+    define(&app, "archived", "#000000").await;
+    let listed: Vec<TagDefinition> = app.get("/tags/definitions").await;
+    assert_eq!(defined_tags(&listed), ["archived", "urgent"]);
+
+    // an empty field clears it, and a left out one is kept
+    let updated: TagDefinition = app
+        .patch(
+            "/tags/definitions/urgent",
+            &json!({ "name": "overdue", "description": "" }),
+        )
+        .await;
+    assert_eq!(updated.name, "overdue");
+    assert_eq!(updated.color.as_deref(), Some("#ff8800"));
+    assert_eq!(updated.description, None);
+    assert_eq!(
+        app.get_for_status("/tags/definitions/urgent").await,
+        Status::NotFound
+    );
+    let (status, _) = app
+        .patch_for_error("/tags/definitions/overdue", &json!({ "name": "archived" }))
+        .await;
+    assert_eq!(status, Status::Conflict);
+    let (status, _) = app
+        .patch_for_error("/tags/definitions/overdue", &json!({}))
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity);
+
+    assert_eq!(
+        app.delete_for_status("/tags/definitions/overdue").await,
+        Status::Ok
+    );
+    assert_eq!(
+        app.delete_for_status("/tags/definitions/overdue").await,
+        Status::NotFound
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn files_and_collections_carry_the_definitions_of_their_tags() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    define(&app, "urgent", "#ff0000").await;
+    define(&app, "invoices", "#00ff00").await;
+    define(&app, "unused", "#0000ff").await;
+
+    let file = app
+        .create_uploaded_file("march.pdf", b"march", &["invoices", "draft", "urgent"])
+        .await;
+    let file: File = app.get(&format!("/files/{}", file.id)).await;
+    assert_eq!(
+        defined_tags(&file.tag_definitions),
+        ["invoices", "urgent"],
+        "in the order of the tags, leaving out undefined ones"
+    );
+
+    let collection = app.create_collection("Invoices", &["invoices"]).await;
+    assert_eq!(defined_tags(&collection.tag_definitions), ["invoices"]);
+    let collection: Collection = app.get(&format!("/collections/{}", collection.id)).await;
+    assert_eq!(defined_tags(&collection.tag_definitions), ["invoices"]);
+    let files: Vec<File> = app
+        .get(&format!("/collections/{}/files", collection.id))
+        .await;
+    assert_eq!(
+        defined_tags(&files[0].tag_definitions),
+        ["invoices", "urgent"]
+    );
+
+    // the tags stay in use without a definition
+    assert_eq!(
+        app.delete_for_status("/tags/definitions/urgent").await,
+        Status::Ok
+    );
+    let file: File = app.get(&format!("/files/{}", file.id)).await;
+    assert_eq!(file.tags, ["draft", "invoices", "urgent"]);
+    assert_eq!(defined_tags(&file.tag_definitions), ["invoices"]);
+
+    // renaming a definition leaves the tags alone
+    app.patch::<TagDefinition>("/tags/definitions/invoices", &json!({ "name": "bills" }))
+        .await;
+    let file: File = app.get(&format!("/files/{}", file.id)).await;
+    assert_eq!(file.tags, ["draft", "invoices", "urgent"]);
+    assert!(file.tag_definitions.is_empty(), "{file:#?}");
+    let body = app.get_text(&format!("/files/{}", file.id)).await.1;
+    assert!(!body.contains("tagDefinitions"), "{body}");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn definitions_are_looked_up_once_per_page() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    for index in 0..20 {
+        define(&app, &format!("tag-{index}"), "#336699").await;
+    }
+
+    for index in 0..20 {
+        let tags = [
+            format!("tag-{index}"),
+            format!("tag-{}", (index + 1) % 20),
+            format!("undefined-{index}"),
+        ];
+        let tags = Vec::from_iter(tags.iter().map(String::as_str));
+        app.create_uploaded_file(&format!("file-{index}.pdf"), b"content", &tags)
+            .await;
+        app.create_collection(&format!("Collection {index}"), &tags[..1])
+            .await;
+    }
+
+    let queries = TagDefinitionQueries::default();
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(queries.clone()));
+
+    let files: Vec<File> = app.get("/files?limit=20").await;
+    assert_eq!(files.len(), 20);
+    assert!(files.iter().all(|file| file.tag_definitions.len() == 2));
+    assert_eq!(
+        queries.take(),
+        1,
+        "looking up the definitions of a file page"
+    );
+
+    let collections: Vec<Collection> = app.get("/collections?limit=20").await;
+    assert_eq!(collections.len(), 20);
+    assert!(collections
+        .iter()
+        .all(|collection| collection.tag_definitions.len() == 1));
+    assert_eq!(
+        queries.take(),
+        1,
+        "looking up the definitions of a collection page"
+    );
+
+    let file: File = app.get(&format!("/files/{}", files[0].id)).await;
+    assert_eq!(file.tag_definitions.len(), 2);
+    assert_eq!(queries.take(), 1, "looking up the definitions of a file");
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::{collections::Collection, files::File, WithAdminTask};
+use rocket::http::Status;
+use serde_json::json;
+
+fn tags(range: std::ops::Range<usize>) -> Vec<String> {
+    Vec::from_iter(range.map(|index| format!("tag-{index:02}")))
+}
+
+fn tag_refs(tags: &[String]) -> Vec<&str> {
+    Vec::from_iter(tags.iter().map(String::as_str))
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn existing_and_new_file_tags_together_are_limited() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let existing = tags(0..60);
+    let file = app
+        .create_uploaded_file("report.pdf", b"uploaded content", &tag_refs(&existing))
+        .await;
+    let uri = format!("/files/{}", file.id);
+
+    // within the limit alone, over it with the existing ones
+    let (status, error) = app
+        .patch_for_error(&uri, &json!({ "tagsForCreation": tags(60..65) }))
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity, "{error}");
+    assert_eq!(error["fields"][0]["field"], "tagsForCreation", "{error}");
+    let kept: File = app.get(&uri).await;
+    assert_eq!(kept.tags, existing);
+
+    let updated: WithAdminTask<File> = app
+        .patch(&uri, &json!({ "tagsForCreation": tags(60..64) }))
+        .await;
+    assert_eq!(updated.result.tags.len(), 64);
+
+    // deleted tags make room for the new ones
+    let updated: WithAdminTask<File> = app
+        .patch(
+            &uri,
+            &json!({ "tagsForCreation": tags(64..66), "tagsForDeletion": tags(0..2) }),
+        )
+        .await;
+    assert_eq!(updated.result.tags, tags(2..66));
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_file_is_not_created_with_too_many_tags() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    let (status, error) = app
+        .post_for_error(
+            "/files",
+            &json!({
+                "name": "report.pdf",
+                "size": 16,
+                "mimeType": "application/pdf",
+                "tags": tags(0..65),
+            }),
+        )
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity, "{error}");
+    assert_eq!(error["fields"][0]["field"], "tags", "{error}");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn existing_and_new_collection_tags_together_are_limited() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let existing = tags(0..60);
+    let collection = app.create_collection("Reports", &tag_refs(&existing)).await;
+    let uri = format!("/collections/{}", collection.id);
+
+    let (status, error) = app
+        .patch_for_error(&uri, &json!({ "tagsForCreation": tags(60..65) }))
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity, "{error}");
+    assert_eq!(error["fields"][0]["field"], "tagsForCreation", "{error}");
+    let kept: Collection = app.get(&uri).await;
+    assert_eq!(kept.tags, existing);
+
+    let updated: WithAdminTask<Collection> = app
+        .patch(&uri, &json!({ "tagsForCreation": tags(60..64) }))
+        .await;
+    assert_eq!(updated.result.tags.len(), 64);
+
+    let (status, error) = app
+        .post_for_error(
+            "/collections",
+            &json!({ "name": "Drafts", "uniqueNames": false, "tags": tags(0..65) }),
+        )
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity, "{error}");
+    assert_eq!(error["fields"][0]["field"], "tags", "{error}");
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::{
+    admins::{AdminTaskPreview, AdminTaskStatus, TaskKind},
+    collections::Collection,
+    files::File,
+    tags::{RenamingTag, TagRenamePreview},
+    WithAdminTask,
+};
+use rocket::http::Status;
+use uuid::Uuid;
+
+fn renaming(new_name: &str) -> RenamingTag {
+    RenamingTag {
+        new_name: new_name.to_owned(),
+    }
+}
+
+/// Tags `invoices` on three files and two collections, one of each having `bills` already.
+async fn seed(app: &TestApp) -> (Vec<File>, Vec<Collection>) {
+    let files = vec![
+        app.create_uploaded_file("march.pdf", b"march", &["invoices"])
+            .await,
+        app.create_uploaded_file("april.pdf", b"april", &["invoices", "bills"])
+            .await,
+        app.create_uploaded_file("january.pdf", b"january", &["invoices", "draft"])
+            .await,
+        app.create_uploaded_file("receipt.pdf", b"receipt", &["receipts"])
+            .await,
+    ];
+    let collections = vec![
+        app.create_collection("Invoices", &["invoices"]).await,
+        app.create_collection("Accounting", &["invoices", "bills"])
+            .await,
+    ];
+
+    (files, collections)
+}
+
+async fn rename_tasks(app: &TestApp) -> Vec<AdminTaskPreview> {
+    let tasks: Vec<AdminTaskPreview> = app.get("/admin-tasks").await;
+    Vec::from_iter(
+        tasks
+            .into_iter()
+            .filter(|task| task.name == TaskKind::RenameTag),
+    )
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_dry_run_counts_the_rename_without_renaming() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let (files, _) = seed(&app).await;
+
+    let preview: WithAdminTask<TagRenamePreview> = app
+        .post("/tags/invoices/rename?dry-run=true", &renaming(" bills "))
+        .await;
+    assert_eq!(preview.admin_task_id, None);
+    let preview = preview.result;
+    assert_eq!(preview.tag, "invoices");
+    assert_eq!(preview.new_name, "bills");
+    assert!(preview.target_exists);
+    assert_eq!(preview.files.renamed_count, 2);
+    assert_eq!(preview.files.merged_count, 1);
+    assert_eq!(
+        preview.files.sample_names,
+        ["april.pdf", "january.pdf", "march.pdf"]
+    );
+    assert_eq!(preview.collections.renamed_count, 1);
+    assert_eq!(preview.collections.merged_count, 1);
+    assert_eq!(preview.collections.sample_names, ["Accounting", "Invoices"]);
+
+    assert!(rename_tasks(&app).await.is_empty());
+    let file: File = app.get(&format!("/files/{}", files[0].id)).await;
+    assert_eq!(file.tags, ["invoices"]);
+
+    let preview: WithAdminTask<TagRenamePreview> = app
+        .post("/tags/invoices/rename?dry-run=true", &renaming("paid"))
+        .await;
+    assert!(!preview.result.target_exists);
+    assert_eq!(preview.result.files.renamed_count, 3);
+    assert_eq!(preview.result.files.merged_count, 0);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_dry_run_samples_a_few_names() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    for index in 0..12 {
+        app.create_uploaded_file(&format!("file-{index:02}.pdf"), b"content", &["invoices"])
+            .await;
+    }
+
+    let preview: WithAdminTask<TagRenamePreview> = app
+        .post("/tags/invoices/rename?dry-run=true", &renaming("bills"))
+        .await;
+    assert_eq!(preview.result.files.renamed_count, 12);
+    assert_eq!(
+        preview.result.files.sample_names,
+        Vec::from_iter((0..10).map(|index| format!("file-{index:02}.pdf")))
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_rename_records_what_it_previewed() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let (files, collections) = seed(&app).await;
+
+    let renamed: WithAdminTask<TagRenamePreview> =
+        app.post("/tags/invoices/rename", &renaming("bills")).await;
+    assert_eq!(renamed.result.files.renamed_count, 2);
+    let task_id = renamed.admin_task_id.expect("the rename has no admin task");
+
+    let task = app.wait_for_task(task_id).await;
+    assert_eq!(task.status, AdminTaskStatus::Completed, "{task:#?}");
+    assert_eq!(task.metadata["tag"], "invoices");
+    assert_eq!(task.metadata["new_name"], "bills");
+    assert_eq!(task.metadata["target_exists"], true);
+    assert_eq!(task.metadata["file_count"], 2);
+    assert_eq!(task.metadata["merged_file_count"], 1);
+    assert_eq!(task.metadata["collection_count"], 1);
+    assert_eq!(task.metadata["merged_collection_count"], 1);
+
+    let summaries = Vec::from_iter(
+        rename_tasks(&app)
+            .await
+            .into_iter()
+            .filter_map(|task| task.summary),
+    );
+    assert_eq!(
+        summaries,
+        ["Renamed tag 'invoices' to 'bills' on 2 files, merged with existing tag on 1"]
+    );
+
+    let tags = |file_id: Uuid| {
+        let app = &app;
+        async move { app.get::<File>(&format!("/files/{file_id}")).await.tags }
+    };
+    assert_eq!(tags(files[0].id).await, ["bills"]);
+    assert_eq!(tags(files[1].id).await, ["bills"]);
+    assert_eq!(tags(files[2].id).await, ["bills", "draft"]);
+    assert_eq!(tags(files[3].id).await, ["receipts"]);
+
+    for collection in collections {
+        let collection: Collection = app.get(&format!("/collections/{}", collection.id)).await;
+        assert_eq!(collection.tags, ["bills"]);
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_new_name_must_be_a_different_tag() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    for new_name in [" invoices ", "", "line\nbreak"] {
+        let (status, error) = app
+            .post_for_error("/tags/invoices/rename?dry-run=true", &renaming(new_name))
+            .await;
+        assert_eq!(status, Status::UnprocessableEntity, "{new_name:?}");
+        assert_eq!(error["fields"][0]["field"], "newName", "{error}");
+    }
+
+    assert!(rename_tasks(&app).await.is_empty());
+}
@@ -0,0 +1,247 @@
+mod common;
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use common::{file, TestApp};
+use file_indexer::{
+    config::SearchEngineConfig,
+    interfaces::{
+        collections::{Collection, CollectionSearchQuery},
+        files::{FileSearchQuery, FileSearchQueryFilter, FileSearchQueryFilterOperator},
+    },
+    services::index_service::{MeilisearchIndex, SearchIndex},
+    testing::search_index::InMemorySearchIndex,
+};
+use serde_json::Value;
+use std::{collections::HashMap, time::Duration};
+use uuid::Uuid;
+
+fn collection(created_at: DateTime<Utc>) -> Collection {
+    Collection {
+        id: Uuid::new_v4(),
+        name: "invoices".to_owned(),
+        unique_names: false,
+        created_at,
+        tags: vec!["invoice".to_owned()],
+        tag_definitions: Vec::new(),
+    }
+}
+
+/// 2025-03-01T12:34:56.789123Z, finer than the millisecond precision of responses.
+fn precise_instant() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 3, 1, 12, 34, 56).unwrap() + chrono::Duration::microseconds(789_123)
+}
+
+#[test]
+fn timestamps_are_written_with_milliseconds_and_a_z_suffix() {
+    let collection = collection(precise_instant());
+    let json = serde_json::to_value(&collection).unwrap();
+
+    assert_eq!(json["createdAt"], "2025-03-01T12:34:56.789Z");
+
+    let read: Collection = serde_json::from_value(json).unwrap();
+    assert_eq!(read.created_at.timestamp_millis(), 1740832496789);
+    assert_eq!(
+        serde_json::to_value(&read).unwrap()["createdAt"],
+        "2025-03-01T12:34:56.789Z"
+    );
+}
+
+#[test]
+fn timestamps_are_read_with_any_offset_and_precision() {
+    for created_at in [
+        "2025-03-01T12:34:56.789Z",
+        "2025-03-01T12:34:56.789+00:00",
+        "2025-03-01T21:34:56.789+09:00",
+        "2025-03-01T12:34:56.789000Z",
+    ] {
+        let mut json = serde_json::to_value(collection(Utc::now())).unwrap();
+        json["createdAt"] = Value::from(created_at);
+        let read: Collection = serde_json::from_value(json).unwrap();
+
+        assert_eq!(
+            read.created_at.timestamp_millis(),
+            1740832496789,
+            "{created_at}"
+        );
+    }
+
+    let mut json = serde_json::to_value(collection(Utc::now())).unwrap();
+    json["createdAt"] = Value::from("2025-03-01 12:34:56");
+    assert!(serde_json::from_value::<Collection>(json).is_err());
+}
+
+#[rocket::async_test]
+async fn collection_search_hits_keep_created_at_to_the_millisecond() {
+    let search_index = InMemorySearchIndex::new();
+    let collection = collection(precise_instant());
+    search_index
+        .index_collections(&[(collection.clone(), 0)])
+        .await
+        .unwrap();
+
+    let query: CollectionSearchQuery =
+        serde_json::from_value(serde_json::json!({ "q": "invoices" })).unwrap();
+    let hits = search_index.search_collections(&query).await.unwrap();
+
+    assert_eq!(hits.len(), 1);
+    assert_eq!(
+        hits[0].collection.created_at.timestamp_millis(),
+        collection.created_at.timestamp_millis()
+    );
+}
+
+#[rocket::async_test]
+async fn in_memory_index_keeps_uploaded_at_to_the_millisecond() {
+    check_uploaded_at(&InMemorySearchIndex::new()).await;
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn meilisearch_keeps_uploaded_at_to_the_millisecond() {
+    let (_meilisearch, url) = common::start_meilisearch().await;
+    let index = MeilisearchIndex::init(&SearchEngineConfig {
+        url,
+        api_key: None,
+        check_only: false,
+    })
+    .await
+    .expect("failed to initialize search engine module");
+
+    check_uploaded_at(&index).await;
+}
+
+/// Indexes two files a millisecond apart, one on its own and one in a batch, and checks that
+/// hits and `uploadedAt` filters tell them apart.
+async fn check_uploaded_at(index: &dyn SearchIndex) {
+    let earlier_at = precise_instant();
+    let later_at = earlier_at + chrono::Duration::milliseconds(1);
+    let mut earlier = file(
+        "earlier.txt",
+        10,
+        "text/plain",
+        &["stamped"],
+        "2025-01-01T00:00:00Z",
+    );
+    earlier.uploaded_at = earlier_at;
+    let mut later = file(
+        "later.txt",
+        10,
+        "text/plain",
+        &["stamped"],
+        "2025-01-01T00:00:00Z",
+    );
+    later.uploaded_at = later_at;
+
+    for files in [
+        vec![earlier.clone()],
+        vec![
+            later.clone(),
+            file("other.txt", 10, "text/plain", &[], "2025-01-01T00:00:00Z"),
+        ],
+    ] {
+        let task_uid = index.index_files(&files, &HashMap::new()).await.unwrap();
+        index
+            .wait_for_task(task_uid, Duration::from_secs(30))
+            .await
+            .unwrap();
+    }
+
+    let search = |operator, value: Option<DateTime<Utc>>| FileSearchQuery {
+        q: "stamped".to_owned(),
+        limit: 10,
+        offset: 0,
+        filters: Vec::from_iter(
+            value.map(|value| vec![FileSearchQueryFilter::UploadedAt { operator, value }]),
+        ),
+        matching_strategy: None,
+        attributes_to_highlight: None,
+        crop_length: None,
+        distinct: None,
+        include_unready: false,
+        include_superseded: false,
+    };
+    let uploaded_at = |q: FileSearchQuery| async move {
+        let mut hits = Vec::from_iter(
+            index
+                .search_files(&q)
+                .await
+                .unwrap()
+                .hits
+                .into_iter()
+                .map(|hit| (hit.file.name, hit.file.uploaded_at.timestamp_millis())),
+        );
+        hits.sort();
+        hits
+    };
+
+    use FileSearchQueryFilterOperator::{Eq, Gte, Lt};
+
+    assert_eq!(
+        uploaded_at(search(Eq, None)).await,
+        [
+            ("earlier.txt".to_owned(), earlier_at.timestamp_millis()),
+            ("later.txt".to_owned(), later_at.timestamp_millis()),
+        ]
+    );
+    assert_eq!(
+        uploaded_at(search(Lt, Some(later_at))).await,
+        [("earlier.txt".to_owned(), earlier_at.timestamp_millis())]
+    );
+    assert_eq!(
+        uploaded_at(search(Gte, Some(later_at))).await,
+        [("later.txt".to_owned(), later_at.timestamp_millis())]
+    );
+    assert_eq!(
+        uploaded_at(search(Eq, Some(earlier_at))).await,
+        [("earlier.txt".to_owned(), earlier_at.timestamp_millis())]
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn search_hits_and_gets_write_the_same_timestamps() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let file = app
+        .create_uploaded_file("stamped.txt", b"stamped", &["stamped"])
+        .await;
+    let collection = app.create_collection("stamped", &["stamped"]).await;
+
+    let fetched: Value = app.get(&format!("/files/{}", file.id)).await;
+    let found: Value = app
+        .post("/searches/files", &serde_json::json!({ "q": "stamped" }))
+        .await;
+    let hit = found["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|hit| hit["id"] == file.id.to_string())
+        .unwrap();
+    assert_eq!(hit["uploadedAt"], fetched["uploadedAt"]);
+
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let stored: NaiveDateTime = sqlx::query_scalar("SELECT uploaded_at FROM files WHERE id = $1")
+        .bind(file.id)
+        .fetch_one(&db_pool)
+        .await
+        .unwrap();
+    let found: DateTime<Utc> = serde_json::from_value(hit["uploadedAt"].clone()).unwrap();
+    assert_eq!(
+        found.timestamp_millis(),
+        stored.and_utc().timestamp_millis()
+    );
+
+    let fetched: Value = app.get(&format!("/collections/{}", collection.id)).await;
+    let found: Value = app
+        .post(
+            "/searches/collections",
+            &serde_json::json!({ "q": "stamped" }),
+        )
+        .await;
+    let hit = found
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|hit| hit["id"] == collection.id.to_string())
+        .unwrap();
+    assert_eq!(hit["createdAt"], fetched["createdAt"]);
+}
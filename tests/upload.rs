@@ -0,0 +1,147 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::files::{File, FileSearchResult, UploadedParts};
+use rocket::http::Status;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn uploaded_file_becomes_searchable() {
+    let app = TestApp::spawn().await;
+    let content = b"a file uploaded through presigned urls";
+
+    let file = app
+        .create_file("quarterly-report.txt", content, &["reports"])
+        .await;
+    assert!(!file.is_ready);
+
+    let file = app.upload_file(file.id, content).await;
+    assert!(file.is_ready);
+    assert_eq!(file.size, content.len());
+
+    let hits = app
+        .wait_for_search("quarterly", |hits| {
+            hits.iter().any(|hit| hit.file.id == file.id)
+        })
+        .await;
+    let hit = hits.iter().find(|hit| hit.file.id == file.id).unwrap();
+    assert_eq!(hit.file.name, "quarterly-report.txt");
+    assert_eq!(hit.file.tags, ["reports"]);
+
+    let fetched: File = app.get(&format!("/files/{}", file.id)).await;
+    assert!(fetched.is_ready);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn unready_file_is_not_searchable() {
+    let app = TestApp::spawn().await;
+    let content = b"never uploaded";
+
+    let unready = app.create_file("pending-draft.txt", content, &[]).await;
+    let ready = app
+        .create_uploaded_file("pending-final.txt", content, &[])
+        .await;
+
+    // once the uploaded file shows up, the unready one would have been indexed as well
+    let hits = app
+        .wait_for_search("pending", |hits| {
+            hits.iter().any(|hit| hit.file.id == ready.id)
+        })
+        .await;
+    assert!(hits.iter().all(|hit| hit.file.id != unready.id));
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn unready_file_stays_unsearchable_after_a_metadata_update() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let search_index = app.search_index.clone().unwrap();
+    let unready = app.create_file("draft.txt", b"never uploaded", &[]).await;
+    let ready = app
+        .create_uploaded_file("final.txt", b"uploaded", &[])
+        .await;
+
+    for (file, name) in [
+        (&unready, "renamed-draft.txt"),
+        (&ready, "renamed-final.txt"),
+    ] {
+        let (status, body) = app
+            .patch_for_error(&format!("/files/{}", file.id), &json!({ "name": name }))
+            .await;
+        assert_eq!(status, Status::Ok, "{body}");
+    }
+
+    // both updates are flushed by the index flusher shortly after, the unready file's first
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while search_index
+        .file_document(ready.id)
+        .is_none_or(|document| document["name"] != "renamed-final.txt")
+    {
+        assert!(Instant::now() < deadline, "the update was never indexed");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(search_index.file_document(unready.id), None);
+
+    let ready_only = [ready.id];
+    let found = app.search_files("renamed").await;
+    assert_eq!(
+        Vec::from_iter(found.iter().map(|hit| hit.file.id)),
+        ready_only
+    );
+
+    let including_unready = json!({ "q": "renamed", "includeUnready": true });
+    let found: FileSearchResult = app.post("/searches/files", &including_unready).await;
+    assert_eq!(
+        Vec::from_iter(found.hits.iter().map(|hit| hit.file.id)),
+        ready_only
+    );
+    assert_eq!(
+        app.post_for_status_as(None, "/searches/files", &including_unready)
+            .await,
+        Status::Unauthorized
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_failed_completion_leaves_the_file_unready_until_a_retry() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let content = b"completed on the second attempt";
+    let file = app.create_file("retried.txt", content, &[]).await;
+    let upload = app.start_upload(file.id).await;
+    let parts = app.upload_parts(file.id, &upload, content).await;
+    let completion_uri = TestApp::completion_uri(file.id, &upload.id);
+
+    let mut wrong_parts = parts.clone();
+    wrong_parts[0].e_tag = "\"not-the-etag\"".to_owned();
+    let status = app
+        .post_for_status(&completion_uri, &UploadedParts { parts: wrong_parts })
+        .await;
+    assert_eq!(status, Status::InternalServerError);
+    assert_eq!(
+        app.get_for_status(&format!("/files/{}", file.id)).await,
+        Status::NotFound
+    );
+
+    let completed = app
+        .complete_upload(file.id, &upload.id, parts.clone())
+        .await;
+    assert!(completed.is_ready);
+
+    // a retry of the completion answers the same, while any other upload conflicts
+    let retried = app
+        .complete_upload(file.id, &upload.id, parts.clone())
+        .await;
+    assert_eq!(retried.id, file.id);
+    assert_eq!(
+        app.post_for_status(
+            &TestApp::completion_uri(file.id, "another-upload"),
+            &UploadedParts { parts }
+        )
+        .await,
+        Status::Conflict
+    );
+}
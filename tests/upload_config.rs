@@ -0,0 +1,176 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::files::{CreatingFile, CreatingFiles, FileUploadUrl, UploadedParts};
+use rocket::http::{RawStr, Status};
+use serde_json::json;
+
+const MIB: usize = 1024 * 1024;
+/// The smallest part size S3 allows, rather than the default of 64 MiB.
+const PART_SIZE: usize = 5 * MIB;
+const MAX_FILE_SIZE: usize = 20 * MIB;
+
+async fn spawn() -> TestApp {
+    TestApp::spawn_with_in_memory_stores_and_env(&[
+        ("UPLOAD_PART_SIZE_BYTES", &PART_SIZE.to_string()),
+        ("UPLOAD_MAX_FILE_SIZE_BYTES", &MAX_FILE_SIZE.to_string()),
+    ])
+    .await
+}
+
+fn content(size: usize) -> Vec<u8> {
+    Vec::from_iter((0..size).map(|index| (index % 251) as u8))
+}
+
+fn part_plan(upload: &FileUploadUrl) -> Vec<(u32, u64, u64)> {
+    Vec::from_iter(
+        upload
+            .parts
+            .iter()
+            .map(|part| (part.part_number, part.offset, part.size)),
+    )
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn uploads_are_planned_in_the_configured_part_size() {
+    let app = spawn().await;
+
+    for (size, plan) in [
+        (1, vec![(1, 0, 1)]),
+        (PART_SIZE, vec![(1, 0, PART_SIZE)]),
+        (PART_SIZE + 1, vec![(1, 0, PART_SIZE + 1)]),
+        (
+            2 * PART_SIZE,
+            vec![(1, 0, PART_SIZE), (2, PART_SIZE, PART_SIZE)],
+        ),
+        // the last part takes the remainder
+        (
+            3 * PART_SIZE - 1,
+            vec![(1, 0, PART_SIZE), (2, PART_SIZE, 2 * PART_SIZE - 1)],
+        ),
+    ] {
+        let file = app.create_file("report.pdf", &content(size), &[]).await;
+        let upload = app.start_upload(file.id).await;
+
+        let plan = Vec::from_iter(
+            plan.into_iter()
+                .map(|(number, offset, size)| (number, offset as u64, size as u64)),
+        );
+        assert_eq!(upload.part_count as usize, plan.len(), "{size} bytes");
+        assert_eq!(part_plan(&upload), plan, "{size} bytes");
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_file_uploads_in_the_configured_part_size() {
+    let app = spawn().await;
+    let content = content(2 * PART_SIZE + PART_SIZE / 2 + 3);
+    let file = app.create_file("report.pdf", &content, &[]).await;
+    let upload = app.start_upload(file.id).await;
+
+    // reissued URLs of a part cover the same bytes as the first ones
+    let reissued: FileUploadUrl = app
+        .post(
+            &format!(
+                "/files/{}/upload-urls/{}/parts?part-range=2-2",
+                file.id,
+                RawStr::new(&upload.id).percent_encode()
+            ),
+            &(),
+        )
+        .await;
+    assert_eq!(part_plan(&reissued), part_plan(&upload)[1..]);
+
+    let parts = app.upload_parts(file.id, &upload, &content).await;
+    let file = app.complete_upload(file.id, &upload.id, parts).await;
+    assert!(file.is_ready);
+
+    let object = app
+        .storage
+        .as_ref()
+        .unwrap()
+        .object(&file.id.to_string(), None)
+        .unwrap();
+    assert!(
+        object.body == content,
+        "the object differs from the content"
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn parts_must_be_completed_once_and_in_order() {
+    let app = spawn().await;
+    let content = content(3 * PART_SIZE);
+    let file = app.create_file("report.pdf", &content, &[]).await;
+    let upload = app.start_upload(file.id).await;
+    let parts = app.upload_parts(file.id, &upload, &content).await;
+
+    let mut reordered = parts.clone();
+    reordered.swap(0, 1);
+    let mut repeated = parts.clone();
+    repeated[2] = repeated[1].clone();
+
+    for parts in [reordered, repeated, parts[..2].to_vec()] {
+        assert_eq!(
+            app.post_for_status(
+                &TestApp::completion_uri(file.id, &upload.id),
+                &UploadedParts { parts },
+            )
+            .await,
+            Status::UnprocessableEntity
+        );
+    }
+
+    let file = app.complete_upload(file.id, &upload.id, parts).await;
+    assert!(file.is_ready);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn files_above_the_configured_size_are_refused_on_creation() {
+    let app = spawn().await;
+    let creating_file = |size: usize| CreatingFile {
+        name: "report.pdf".to_owned(),
+        size,
+        mime_type: "application/pdf".to_owned(),
+        checksum: None,
+        tags: None,
+        allow_duplicate: false,
+    };
+
+    let (status, error) = app
+        .post_for_error("/files", &creating_file(MAX_FILE_SIZE + 1))
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity);
+    assert_eq!(error["fields"][0]["field"], "size", "{error}");
+
+    let (status, error) = app
+        .post_for_error(
+            "/files/batch",
+            &CreatingFiles {
+                files: vec![creating_file(1), creating_file(MAX_FILE_SIZE + 1)],
+            },
+        )
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity);
+    assert_eq!(error["fields"][0]["field"], "files[1].size", "{error}");
+
+    let file = app.create_file("report.pdf", b"draft", &[]).await;
+    let (status, error) = app
+        .patch_for_error(
+            &format!("/files/{}", file.id),
+            &json!({ "size": MAX_FILE_SIZE + 1 }),
+        )
+        .await;
+    assert_eq!(status, Status::UnprocessableEntity);
+    assert_eq!(error["fields"][0]["field"], "size", "{error}");
+
+    assert_eq!(
+        app.post_for_status("/files", &creating_file(MAX_FILE_SIZE))
+            .await,
+        Status::Ok
+    );
+}
@@ -0,0 +1,165 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::files::{FileUploadRecord, UploadOutcome};
+use rocket::http::{RawStr, Status};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+const WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+const WAIT_INTERVAL: Duration = Duration::from_millis(200);
+
+async fn uploads(app: &TestApp, file_id: Uuid) -> Vec<FileUploadRecord> {
+    app.get(&format!("/files/{file_id}/uploads")).await
+}
+
+/// Polls the uploads of the file until `predicate` holds, as the file gc runs in the background.
+async fn wait_for_uploads(
+    app: &TestApp,
+    file_id: Uuid,
+    predicate: impl Fn(&[FileUploadRecord]) -> bool,
+) -> Vec<FileUploadRecord> {
+    let deadline = Instant::now() + WAIT_TIMEOUT;
+
+    loop {
+        let uploads = uploads(app, file_id).await;
+
+        if predicate(&uploads) {
+            return uploads;
+        }
+
+        assert!(
+            Instant::now() < deadline,
+            "the uploads never matched: {uploads:#?}"
+        );
+        tokio::time::sleep(WAIT_INTERVAL).await;
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn every_attempt_of_an_upload_is_recorded() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let content = b"uploaded content";
+    let file = app.create_file("report.pdf", content, &[]).await;
+
+    let aborted = app.start_upload(file.id).await;
+    let pending = uploads(&app, file.id).await;
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].upload_id, aborted.id);
+    assert_eq!(pending[0].outcome, UploadOutcome::Pending);
+    assert_eq!(pending[0].part_count, 1);
+    assert_eq!(pending[0].total_bytes, content.len());
+    assert_eq!(pending[0].completed_at, None);
+
+    assert_eq!(
+        app.delete_for_status(&format!(
+            "/files/{}/upload-urls/{}",
+            file.id,
+            RawStr::new(&aborted.id).percent_encode()
+        ))
+        .await,
+        Status::Ok
+    );
+    app.upload_file(file.id, content).await;
+
+    let records = uploads(&app, file.id).await;
+    assert_eq!(
+        Vec::from_iter(
+            records
+                .iter()
+                .map(|record| (record.upload_id == aborted.id, record.outcome))
+        ),
+        [
+            (false, UploadOutcome::Completed),
+            (true, UploadOutcome::Aborted)
+        ]
+    );
+    assert!(records.iter().all(|record| record
+        .completed_at
+        .is_some_and(|completed_at| record.started_at <= completed_at)));
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_uploads_of_an_unknown_file_are_not_found() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+
+    assert_eq!(
+        app.get_for_status(&format!("/files/{}/uploads", Uuid::new_v4()))
+            .await,
+        Status::NotFound
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn the_file_gc_expires_uploads_left_pending() {
+    let app = TestApp::spawn_with_in_memory_stores_and_env(&[("FILE_GC_INTERVAL_SECS", "1")]).await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let stale = app.create_file("stale.pdf", b"stale content", &[]).await;
+    let fresh = app.create_file("fresh.pdf", b"fresh content", &[]).await;
+    let stale_upload = app.start_upload(stale.id).await;
+    let fresh_upload = app.start_upload(fresh.id).await;
+
+    // started before the unready-file TTL, while the file itself is recent enough to be kept
+    sqlx::query(
+        "UPDATE upload_records SET started_at = started_at - INTERVAL '3 hours' WHERE upload_id = $1",
+    )
+    .bind(&stale_upload.id)
+    .execute(&db_pool)
+    .await
+    .unwrap();
+
+    let records = wait_for_uploads(&app, stale.id, |records| {
+        records[0].outcome == UploadOutcome::Expired
+    })
+    .await;
+    assert!(records[0].completed_at.is_some());
+
+    let open_upload_ids = app.storage.as_ref().unwrap().open_upload_ids();
+    assert!(!open_upload_ids.contains(&stale_upload.id));
+    assert!(open_upload_ids.contains(&fresh_upload.id));
+    assert_eq!(
+        uploads(&app, fresh.id).await[0].outcome,
+        UploadOutcome::Pending
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn housekeeping_prunes_old_finished_uploads() {
+    let app = TestApp::spawn_with_in_memory_stores_and_env(&[
+        ("HOUSEKEEPING_INTERVAL_SECS", "1"),
+        ("UPLOAD_RECORD_RETENTION_SECS", "3600"),
+    ])
+    .await;
+    let db_pool = sqlx::PgPool::connect(&app.database_url).await.unwrap();
+    let old = app
+        .create_uploaded_file("old.pdf", b"old content", &[])
+        .await;
+    let recent = app
+        .create_uploaded_file("recent.pdf", b"recent content", &[])
+        .await;
+    let pending = app
+        .create_file("pending.pdf", b"pending content", &[])
+        .await;
+    app.start_upload(pending.id).await;
+
+    sqlx::query(
+        "UPDATE upload_records
+SET started_at = started_at - INTERVAL '2 hours', completed_at = completed_at - INTERVAL '2 hours'
+WHERE file_id = ANY($1)",
+    )
+    .bind(vec![old.id, pending.id])
+    .execute(&db_pool)
+    .await
+    .unwrap();
+
+    wait_for_uploads(&app, old.id, |records| records.is_empty()).await;
+    assert_eq!(uploads(&app, recent.id).await.len(), 1);
+    assert_eq!(
+        uploads(&app, pending.id).await[0].outcome,
+        UploadOutcome::Pending
+    );
+}
@@ -0,0 +1,536 @@
+mod common;
+
+use common::TestApp;
+use file_indexer::interfaces::{
+    admins::{AdminTask, AdminTaskStatus},
+    files::{
+        CreatedFile, CreatingFile, File, FileUploadProgress, FileUploadRecord, FileUploadUrl,
+        UploadOutcome, UploadedParts,
+    },
+    SimpleOk, WithAdminTask,
+};
+use rocket::http::Status;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+/// The smallest part size the upload configuration allows.
+const PART_SIZE: usize = 5 * 1024 * 1024;
+/// How long the mocked S3 takes to presign each URL.
+const PRESIGN_DELAY: Duration = Duration::from_millis(20);
+/// Any hex SHA-256 digest will do, as uploads do not verify it.
+const CHECKSUM: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+fn creating_file(name: &str, content: &[u8]) -> CreatingFile {
+    CreatingFile {
+        name: name.to_owned(),
+        size: content.len(),
+        mime_type: "application/octet-stream".to_owned(),
+        checksum: None,
+        tags: None,
+        allow_duplicate: false,
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn upload_urls_split_the_file_into_parts() {
+    let app = TestApp::spawn_with_in_memory_storage().await;
+    // the last part takes the remainder rather than making a part of its own
+    let content = vec![7u8; PART_SIZE * 2 + 1024];
+    let file = app.create_file("large.bin", &content, &[]).await;
+
+    let upload = app.start_upload(file.id).await;
+    assert_eq!(upload.part_count, 2);
+    assert_eq!(
+        Vec::from_iter(
+            upload
+                .parts
+                .iter()
+                .map(|part| (part.part_number, part.offset, part.size))
+        ),
+        [
+            (1, 0, PART_SIZE as u64),
+            (2, PART_SIZE as u64, (PART_SIZE + 1024) as u64)
+        ]
+    );
+
+    let more: FileUploadUrl = app
+        .post(
+            &format!(
+                "/files/{}/upload-urls/{}/parts?part-range=2-5",
+                file.id, upload.id
+            ),
+            &(),
+        )
+        .await;
+    assert_eq!(more.id, upload.id);
+    assert_eq!(
+        Vec::from_iter(more.parts.iter().map(|part| part.part_number)),
+        [2]
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn completion_assembles_the_object() {
+    let app = TestApp::spawn_with_in_memory_storage().await;
+    let storage = app.storage.as_ref().unwrap();
+    let content = Vec::from_iter((0..PART_SIZE * 2).map(|index| index as u8));
+    let file = app.create_file("assembled.bin", &content, &[]).await;
+
+    let file = app.upload_file(file.id, &content).await;
+    assert!(file.is_ready);
+
+    let object = storage.object(&file.id.to_string(), None).unwrap();
+    assert_eq!(object.body, content);
+    assert_eq!(object.content_type, "application/octet-stream");
+    assert!(storage.open_upload_ids().is_empty());
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn completion_requires_every_part_in_order() {
+    let app = TestApp::spawn_with_in_memory_storage().await;
+    let content = vec![1u8; PART_SIZE * 2];
+    let file = app.create_file("parts.bin", &content, &[]).await;
+    let upload = app.start_upload(file.id).await;
+    let mut parts = app.upload_parts(file.id, &upload, &content).await;
+    let uri = TestApp::completion_uri(file.id, &upload.id);
+
+    let missing = UploadedParts {
+        parts: parts[..1].to_vec(),
+    };
+    assert_eq!(
+        app.post_for_status(&uri, &missing).await,
+        Status::UnprocessableEntity
+    );
+
+    parts.reverse();
+    let reversed = UploadedParts {
+        parts: parts.clone(),
+    };
+    assert_eq!(
+        app.post_for_status(&uri, &reversed).await,
+        Status::UnprocessableEntity
+    );
+
+    let fetched: File = app.get(&format!("/files/{}", file.id)).await;
+    assert!(!fetched.is_ready);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn completion_retries_answer_the_same_file() {
+    let app = TestApp::spawn_with_in_memory_storage().await;
+    let content = b"completed twice";
+    let file = app.create_file("retried.txt", content, &[]).await;
+    let upload = app.start_upload(file.id).await;
+    let parts = app.upload_parts(file.id, &upload, content).await;
+
+    let completed = app
+        .complete_upload(file.id, &upload.id, parts.clone())
+        .await;
+    let retried: WithAdminTask<File> = app
+        .post(
+            &TestApp::completion_uri(file.id, &upload.id),
+            &UploadedParts {
+                parts: parts.clone(),
+            },
+        )
+        .await;
+    assert_eq!(retried.result.id, completed.id);
+    assert!(retried.result.is_ready);
+    assert_eq!(retried.admin_task_id, None);
+
+    // a ready file only completes again with the upload that made it ready
+    assert_eq!(
+        app.post_for_status(
+            &TestApp::completion_uri(file.id, "another-upload"),
+            &UploadedParts { parts },
+        )
+        .await,
+        Status::Conflict
+    );
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn upload_progress_sums_the_received_parts() {
+    let app = TestApp::spawn_with_in_memory_storage().await;
+    let content = vec![3u8; PART_SIZE * 2 + 1024];
+    let file = app.create_file("progress.bin", &content, &[]).await;
+    let uri = format!("/files/{}/upload-progress", file.id);
+
+    assert_eq!(app.get_for_status(&uri).await, Status::NotFound);
+
+    let upload = app.start_upload(file.id).await;
+    let progress: FileUploadProgress = app.get(&uri).await;
+    assert_eq!(progress.bytes_received, 0);
+    assert_eq!(progress.total_bytes, content.len() as u64);
+    assert_eq!((progress.parts_received, progress.parts_total), (0, 2));
+    assert_eq!(progress.percent, 0.0);
+
+    let first = FileUploadUrl {
+        parts: upload.parts[..1].to_vec(),
+        ..upload.clone()
+    };
+    let mut parts = app.upload_parts(file.id, &first, &content).await;
+    let progress: FileUploadProgress = app.get(&uri).await;
+    assert_eq!(progress.bytes_received, PART_SIZE as u64);
+    assert_eq!((progress.parts_received, progress.parts_total), (1, 2));
+    assert_eq!(
+        progress.percent,
+        PART_SIZE as f64 * 100.0 / content.len() as f64
+    );
+
+    let second = FileUploadUrl {
+        parts: upload.parts[1..].to_vec(),
+        ..upload.clone()
+    };
+    parts.extend(app.upload_parts(file.id, &second, &content).await);
+    let progress: FileUploadProgress = app.get(&uri).await;
+    assert_eq!(progress.bytes_received, content.len() as u64);
+    assert_eq!((progress.parts_received, progress.parts_total), (2, 2));
+    assert_eq!(progress.percent, 100.0);
+
+    app.complete_upload(file.id, &upload.id, parts).await;
+    let progress: FileUploadProgress = app.get(&uri).await;
+    assert_eq!(progress.bytes_received, content.len() as u64);
+    assert_eq!((progress.parts_received, progress.parts_total), (2, 2));
+    assert_eq!(progress.percent, 100.0);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn created_file_includes_the_upload_urls() {
+    let app = TestApp::spawn_with_in_memory_storage().await;
+    let content = vec![5u8; PART_SIZE + 1024];
+
+    let created: CreatedFile = app
+        .post(
+            "/files?include-upload-urls=true",
+            &creating_file("inline.bin", &content),
+        )
+        .await;
+    assert!(!created.file.is_ready);
+    assert_eq!(created.upload_error, None);
+    let upload = created.upload.expect("the upload was not started");
+    assert_eq!(upload.part_count, 1);
+    assert_eq!(upload.parts.len(), 1);
+
+    let parts = app.upload_parts(created.file.id, &upload, &content).await;
+    let file = app
+        .complete_upload(created.file.id, &upload.id, parts)
+        .await;
+    assert!(file.is_ready);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn created_file_reports_an_upload_that_failed_to_start() {
+    let app = TestApp::spawn_with_in_memory_storage().await;
+    let storage = app.storage.as_ref().unwrap();
+    let content = b"created while the storage is down";
+
+    storage.set_uploads_unavailable(true);
+    let created: CreatedFile = app
+        .post(
+            "/files?include-upload-urls=true",
+            &creating_file("retried.txt", content),
+        )
+        .await;
+    assert!(created.upload.is_none());
+    assert_eq!(created.upload_error.as_deref(), Some("storage_unavailable"));
+    assert!(storage.open_upload_ids().is_empty());
+
+    // only the upload urls need to be requested again
+    storage.set_uploads_unavailable(false);
+    let file = app.upload_file(created.file.id, content).await;
+    assert!(file.is_ready);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn upload_urls_hand_out_the_active_upload_again() {
+    let app = TestApp::spawn_with_in_memory_storage().await;
+    let storage = app.storage.as_ref().unwrap();
+    let content = vec![3u8; PART_SIZE * 2];
+    let file = app.create_file("repeated.bin", &content, &[]).await;
+
+    let upload = app.start_upload(file.id).await;
+    let again = app.start_upload(file.id).await;
+    assert_eq!(again.id, upload.id);
+    assert_eq!(again.part_count, upload.part_count);
+    assert_eq!(storage.open_upload_ids(), [upload.id.as_str()]);
+
+    let records: Vec<FileUploadRecord> = app.get(&format!("/files/{}/uploads", file.id)).await;
+    assert_eq!(records.len(), 1);
+
+    let parts = app.upload_parts(file.id, &again, &content).await;
+    let file = app.complete_upload(file.id, &upload.id, parts).await;
+    assert!(file.is_ready);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn upload_urls_replace_the_active_upload() {
+    let app = TestApp::spawn_with_in_memory_storage().await;
+    let storage = app.storage.as_ref().unwrap();
+    let content = b"replaced before any part was uploaded";
+    let file = app.create_file("replaced.txt", content, &[]).await;
+
+    let upload = app.start_upload(file.id).await;
+    let replacement: FileUploadUrl = app
+        .post(&format!("/files/{}/upload-urls?replace=true", file.id), &())
+        .await;
+    assert_ne!(replacement.id, upload.id);
+    assert_eq!(storage.open_upload_ids(), [replacement.id.as_str()]);
+
+    let records: Vec<FileUploadRecord> = app.get(&format!("/files/{}/uploads", file.id)).await;
+    let outcomes = Vec::from_iter(
+        records
+            .iter()
+            .map(|record| (record.upload_id.as_str(), record.outcome)),
+    );
+    assert!(outcomes.contains(&(upload.id.as_str(), UploadOutcome::Aborted)));
+    assert!(outcomes.contains(&(replacement.id.as_str(), UploadOutcome::Pending)));
+
+    let parts = app.upload_parts(file.id, &replacement, content).await;
+    let file = app.complete_upload(file.id, &replacement.id, parts).await;
+    assert!(file.is_ready);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn upload_urls_start_another_upload_after_an_abort() {
+    let app = TestApp::spawn_with_in_memory_storage().await;
+    let storage = app.storage.as_ref().unwrap();
+    let content = b"aborted and started again";
+    let file = app.create_file("restarted.txt", content, &[]).await;
+
+    let upload = app.start_upload(file.id).await;
+    let aborted: SimpleOk = app
+        .delete(&format!("/files/{}/upload-urls/{}", file.id, upload.id))
+        .await;
+    assert!(aborted.ok);
+
+    let restarted = app.start_upload(file.id).await;
+    assert_ne!(restarted.id, upload.id);
+    assert_eq!(storage.open_upload_ids(), [restarted.id.as_str()]);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn bulk_deletion_aborts_the_uploads_of_the_deleted_files() {
+    let app = TestApp::spawn_with_in_memory_storage().await;
+    let storage = app.storage.as_ref().unwrap();
+    let content = b"deleted while being uploaded";
+    let uploading = app.create_file("uploading.txt", content, &[]).await;
+    let idle = app.create_file("idle.txt", content, &[]).await;
+    app.start_upload(uploading.id).await;
+
+    let task: AdminTask = app
+        .post(
+            "/files/bulk-delete",
+            &json!({ "fileIds": [uploading.id, idle.id] }),
+        )
+        .await;
+    let task = app.wait_for_task(task.id).await;
+    assert_eq!(task.status, AdminTaskStatus::Completed, "{task:#?}");
+    assert_eq!(task.metadata["aborted_upload_count"], 1);
+    assert!(storage.open_upload_ids().is_empty());
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn duplicate_of_a_file_being_uploaded_is_refused_with_its_progress() {
+    let app = TestApp::spawn_with_in_memory_storage().await;
+    let content = vec![9u8; PART_SIZE * 2];
+    let creating = CreatingFile {
+        checksum: Some(CHECKSUM.to_owned()),
+        ..creating_file("restarted.bin", &content)
+    };
+    let file: File = app.post("/files", &creating).await;
+    let upload = app.start_upload(file.id).await;
+    let first = FileUploadUrl {
+        parts: upload.parts[..1].to_vec(),
+        ..upload.clone()
+    };
+    let mut parts = app.upload_parts(file.id, &first, &content).await;
+
+    let (status, body) = app.post_for_error("/files", &creating).await;
+    assert_eq!(status, Status::Conflict, "{body}");
+    assert_eq!(body["code"], json!("duplicate_upload"), "{body}");
+    assert_eq!(body["fileId"], json!(file.id), "{body}");
+    assert_eq!(body["progress"]["partsReceived"], json!(1), "{body}");
+    assert_eq!(body["progress"]["partsTotal"], json!(2), "{body}");
+
+    // resuming hands out the same upload, so only the missing part is uploaded
+    let resumed = app.start_upload(file.id).await;
+    assert_eq!(resumed.id, upload.id);
+    let second = FileUploadUrl {
+        parts: resumed.parts[1..].to_vec(),
+        ..resumed
+    };
+    parts.extend(app.upload_parts(file.id, &second, &content).await);
+    let file = app.complete_upload(file.id, &upload.id, parts).await;
+    assert!(file.is_ready);
+
+    // a ready file is not being uploaded anymore
+    let again: File = app.post("/files", &creating).await;
+    assert_ne!(again.id, file.id);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn duplicate_of_a_file_being_uploaded_may_be_allowed() {
+    let app = TestApp::spawn_with_in_memory_storage().await;
+    let content = b"registered twice on purpose";
+    let creating = CreatingFile {
+        checksum: Some(CHECKSUM.to_owned()),
+        ..creating_file("twice.txt", content)
+    };
+    let file: File = app.post("/files", &creating).await;
+    app.start_upload(file.id).await;
+
+    let duplicate: File = app
+        .post(
+            "/files",
+            &CreatingFile {
+                allow_duplicate: true,
+                ..creating
+            },
+        )
+        .await;
+    assert_ne!(duplicate.id, file.id);
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn files_without_a_checksum_have_no_duplicates() {
+    let app = TestApp::spawn_with_in_memory_storage().await;
+    let content = b"no checksum to tell them apart";
+    let file: File = app
+        .post("/files", &creating_file("unchecked.txt", content))
+        .await;
+    app.start_upload(file.id).await;
+
+    let other: File = app
+        .post("/files", &creating_file("unchecked.txt", content))
+        .await;
+    assert_ne!(other.id, file.id);
+}
+
+/// A file of `part_count` parts of [`PART_SIZE`], whose upload `upload-urls` hands out again.
+async fn create_file_of_parts(app: &TestApp, part_count: usize) -> File {
+    let created: CreatedFile = app
+        .post(
+            "/files?include-upload-urls=true",
+            &CreatingFile {
+                size: PART_SIZE * part_count,
+                ..creating_file("huge.bin", &[])
+            },
+        )
+        .await;
+    assert!(created.upload.is_some(), "{:?}", created.upload_error);
+
+    created.file
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn a_thousand_upload_urls_are_presigned_concurrently() {
+    let app = TestApp::spawn_with_in_memory_stores_and_env(&[(
+        "UPLOAD_PART_SIZE_BYTES",
+        &PART_SIZE.to_string(),
+    )])
+    .await;
+    let file = create_file_of_parts(&app, 1000).await;
+    app.storage
+        .as_ref()
+        .unwrap()
+        .set_presign_delay(PRESIGN_DELAY);
+
+    let started = Instant::now();
+    let upload: FileUploadUrl = app
+        .post(&format!("/files/{}/upload-urls", file.id), &())
+        .await;
+    let elapsed = started.elapsed();
+
+    assert_eq!(upload.part_count, 1000);
+    assert!(upload
+        .parts
+        .iter()
+        .map(|part| part.part_number)
+        .eq(1..=1000));
+    // one at a time, the presigns would take 20 seconds
+    assert!(elapsed < PRESIGN_DELAY * 100, "took {elapsed:?}");
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn upload_urls_are_handed_out_in_ranges_of_at_most_a_thousand_parts() {
+    let app = TestApp::spawn_with_in_memory_stores_and_env(&[(
+        "UPLOAD_PART_SIZE_BYTES",
+        &PART_SIZE.to_string(),
+    )])
+    .await;
+    let file = create_file_of_parts(&app, 1001).await;
+    let uri = |part_range: &str| format!("/files/{}/upload-urls?part-range={part_range}", file.id);
+
+    let first: FileUploadUrl = app.post(&uri("1-1000"), &()).await;
+    assert_eq!(first.part_count, 1001);
+    assert!(first.parts.iter().map(|part| part.part_number).eq(1..=1000));
+
+    let rest: FileUploadUrl = app.post(&uri("1001-2000"), &()).await;
+    assert_eq!(rest.id, first.id);
+    assert_eq!(
+        Vec::from_iter(
+            rest.parts
+                .iter()
+                .map(|part| (part.part_number, part.offset))
+        ),
+        [(1001, (PART_SIZE * 1000) as u64)]
+    );
+
+    for part_range in ["1-1001", "1002-1002", "0-5", "5-1"] {
+        assert_eq!(
+            app.post_for_status(&uri(part_range), &()).await,
+            Status::UnprocessableEntity,
+            "{part_range}"
+        );
+    }
+}
+
+#[rocket::async_test]
+#[ignore = "requires docker"]
+async fn part_urls_are_refused_for_an_upload_that_is_not_active() {
+    let app = TestApp::spawn_with_in_memory_stores().await;
+    let content = b"only the active upload is presigned";
+    let file = app.create_file("active.txt", content, &[]).await;
+    let uri = |upload_id: &str| format!("/files/{}/upload-urls/{upload_id}/parts", file.id);
+
+    let upload = app.start_upload(file.id).await;
+    let replacement: FileUploadUrl = app
+        .post(&format!("/files/{}/upload-urls?replace=true", file.id), &())
+        .await;
+    assert_eq!(
+        app.post_for_status(&uri(&upload.id), &()).await,
+        Status::Conflict
+    );
+    assert_eq!(
+        app.post_for_status(&uri("unknown"), &()).await,
+        Status::Conflict
+    );
+
+    let parts: FileUploadUrl = app.post(&uri(&replacement.id), &()).await;
+    assert_eq!(parts.id, replacement.id);
+
+    let uploaded = app.upload_parts(file.id, &replacement, content).await;
+    app.complete_upload(file.id, &replacement.id, uploaded)
+        .await;
+    assert_eq!(
+        app.post_for_status(&uri(&replacement.id), &()).await,
+        Status::Conflict
+    );
+}